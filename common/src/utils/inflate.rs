@@ -0,0 +1,466 @@
+use std::fmt::{Display, Formatter};
+
+const MAX_BITS: usize = 15;
+const MAX_LIT_LEN_CODES: usize = 288;
+const MAX_DIST_CODES: usize = 30;
+const END_OF_BLOCK: u16 = 256;
+
+const LEN_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LEN_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+/// Order in which code length code lengths are stored in a dynamic block header.
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InflateError {
+    UnexpectedEof,
+    InvalidBlockType(u8),
+    StoredLengthMismatch,
+    OversubscribedCode,
+    InvalidCode,
+    InvalidRepeat,
+    MissingEndOfBlock,
+    DistanceTooFar(usize),
+    InvalidZlibHeader,
+    ChecksumMismatch,
+}
+
+impl Display for InflateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InflateError::UnexpectedEof => write!(f, "unexpected end of compressed data"),
+            InflateError::InvalidBlockType(ty) => write!(f, "invalid block type {ty}"),
+            InflateError::StoredLengthMismatch => {
+                write!(f, "stored block length does not match its complement")
+            }
+            InflateError::OversubscribedCode => write!(f, "over-subscribed Huffman code"),
+            InflateError::InvalidCode => write!(f, "invalid Huffman code"),
+            InflateError::InvalidRepeat => write!(f, "invalid code length repeat"),
+            InflateError::MissingEndOfBlock => write!(f, "missing end-of-block code"),
+            InflateError::DistanceTooFar(dist) => {
+                write!(f, "distance {dist} is beyond the start of the output")
+            }
+            InflateError::InvalidZlibHeader => write!(f, "invalid zlib header"),
+            InflateError::ChecksumMismatch => write!(f, "Adler-32 checksum mismatch"),
+        }
+    }
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    bit_buf: u32,
+    bit_count: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            pos: 0,
+            bit_buf: 0,
+            bit_count: 0,
+        }
+    }
+
+    /// Reads `n` (at most 16) bits, least significant bit first.
+    fn bits(&mut self, n: u32) -> Result<u32, InflateError> {
+        while self.bit_count < n {
+            let byte = *self.data.get(self.pos).ok_or(InflateError::UnexpectedEof)?;
+            self.pos += 1;
+            self.bit_buf |= (byte as u32) << self.bit_count;
+            self.bit_count += 8;
+        }
+        let value = self.bit_buf & ((1u32 << n) - 1);
+        self.bit_buf >>= n;
+        self.bit_count -= n;
+        Ok(value)
+    }
+
+    /// Drops the remaining bits of the current byte.
+    fn align_to_byte(&mut self) {
+        self.bit_buf = 0;
+        self.bit_count = 0;
+    }
+
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8], InflateError> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .filter(|end| *end <= self.data.len())
+            .ok_or(InflateError::UnexpectedEof)?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+}
+
+/// Canonical Huffman code, decoded one bit at a time like zlib's `puff`.
+struct Huffman {
+    /// Number of codes of each length.
+    counts: [u16; MAX_BITS + 1],
+    /// Symbols ordered by code.
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    /// Builds the code from per-symbol code lengths. Incomplete codes are allowed since
+    /// a single distance code is legal.
+    fn new(lengths: &[u8]) -> Result<Self, InflateError> {
+        let mut counts = [0u16; MAX_BITS + 1];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+
+        let mut left: i32 = 1;
+        for count in &counts[1..] {
+            left <<= 1;
+            left -= *count as i32;
+            if left < 0 {
+                return Err(InflateError::OversubscribedCode);
+            }
+        }
+
+        let mut offsets = [0u16; MAX_BITS + 1];
+        for len in 1..MAX_BITS {
+            offsets[len + 1] = offsets[len] + counts[len];
+        }
+        let mut symbols = vec![0u16; lengths.len()];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbols[offsets[len as usize] as usize] = symbol as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+
+        Ok(Self { counts, symbols })
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Result<u16, InflateError> {
+        let mut code: i32 = 0;
+        let mut first: i32 = 0;
+        let mut index: i32 = 0;
+        for len in 1..=MAX_BITS {
+            code |= reader.bits(1)? as i32;
+            let count = self.counts[len] as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first += count;
+            first <<= 1;
+            code <<= 1;
+        }
+        Err(InflateError::InvalidCode)
+    }
+}
+
+/// Decompresses a raw DEFLATE stream (no zlib or gzip header). `size_hint` is used to
+/// preallocate the output, e.g. the uncompressed size stored in a ZIP entry.
+///
+/// https://www.rfc-editor.org/rfc/rfc1951
+pub fn inflate(data: &[u8], size_hint: usize) -> Result<Vec<u8>, InflateError> {
+    inflate_blocks(&mut BitReader::new(data), size_hint)
+}
+
+/// Decompresses a zlib stream, i.e. what `java.util.zip.Deflater` produces with default
+/// settings, and checks its Adler-32 trailer.
+///
+/// https://www.rfc-editor.org/rfc/rfc1950
+pub fn inflate_zlib(data: &[u8], size_hint: usize) -> Result<Vec<u8>, InflateError> {
+    let [cmf, flg, ..] = *data else {
+        return Err(InflateError::UnexpectedEof);
+    };
+    let is_deflate = cmf & 0x0F == 8;
+    let has_preset_dictionary = flg & 0x20 != 0;
+    if !is_deflate || has_preset_dictionary || (u16::from(cmf) << 8 | u16::from(flg)) % 31 != 0 {
+        return Err(InflateError::InvalidZlibHeader);
+    }
+    let mut reader = BitReader::new(&data[2..]);
+    let out = inflate_blocks(&mut reader, size_hint)?;
+    reader.align_to_byte();
+    let trailer = reader.bytes(4)?;
+    if u32::from_be_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]) != adler32(&out) {
+        return Err(InflateError::ChecksumMismatch);
+    }
+    Ok(out)
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MOD_ADLER: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    // 5552 is the largest n with 255n(n+1)/2 + (n+1)(MOD_ADLER-1) < 2^32
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= MOD_ADLER;
+        b %= MOD_ADLER;
+    }
+    (b << 16) | a
+}
+
+fn inflate_blocks(reader: &mut BitReader, size_hint: usize) -> Result<Vec<u8>, InflateError> {
+    let mut out = Vec::with_capacity(size_hint);
+    loop {
+        let is_last = reader.bits(1)? == 1;
+        match reader.bits(2)? {
+            0 => inflate_stored(reader, &mut out)?,
+            1 => {
+                let (lit_len, dist) = fixed_codes()?;
+                inflate_codes(reader, &mut out, &lit_len, &dist)?
+            }
+            2 => {
+                let (lit_len, dist) = dynamic_codes(reader)?;
+                inflate_codes(reader, &mut out, &lit_len, &dist)?
+            }
+            ty => return Err(InflateError::InvalidBlockType(ty as u8)),
+        }
+        if is_last {
+            return Ok(out);
+        }
+    }
+}
+
+fn inflate_stored(reader: &mut BitReader, out: &mut Vec<u8>) -> Result<(), InflateError> {
+    reader.align_to_byte();
+    let header = reader.bytes(4)?;
+    let len = u16::from_le_bytes([header[0], header[1]]);
+    let nlen = u16::from_le_bytes([header[2], header[3]]);
+    if len != !nlen {
+        return Err(InflateError::StoredLengthMismatch);
+    }
+    out.extend_from_slice(reader.bytes(len as usize)?);
+    Ok(())
+}
+
+fn fixed_codes() -> Result<(Huffman, Huffman), InflateError> {
+    let mut lengths = [0u8; MAX_LIT_LEN_CODES];
+    lengths[..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..].fill(8);
+    let lit_len = Huffman::new(&lengths)?;
+    let dist = Huffman::new(&[5u8; MAX_DIST_CODES])?;
+    Ok((lit_len, dist))
+}
+
+fn dynamic_codes(reader: &mut BitReader) -> Result<(Huffman, Huffman), InflateError> {
+    let lit_len_count = reader.bits(5)? as usize + 257;
+    let dist_count = reader.bits(5)? as usize + 1;
+    let code_length_count = reader.bits(4)? as usize + 4;
+    if lit_len_count > MAX_LIT_LEN_CODES || dist_count > MAX_DIST_CODES {
+        return Err(InflateError::InvalidCode);
+    }
+
+    let mut code_length_lengths = [0u8; 19];
+    for &symbol in &CODE_LENGTH_ORDER[..code_length_count] {
+        code_length_lengths[symbol] = reader.bits(3)? as u8;
+    }
+    let code_length_code = Huffman::new(&code_length_lengths)?;
+
+    let mut lengths = vec![0u8; lit_len_count + dist_count];
+    let mut i = 0;
+    while i < lengths.len() {
+        let symbol = code_length_code.decode(reader)?;
+        let (value, repeat) = match symbol {
+            0..=15 => {
+                lengths[i] = symbol as u8;
+                i += 1;
+                continue;
+            }
+            16 => {
+                let prev = *i
+                    .checked_sub(1)
+                    .and_then(|prev| lengths.get(prev))
+                    .ok_or(InflateError::InvalidRepeat)?;
+                (prev, 3 + reader.bits(2)? as usize)
+            }
+            17 => (0, 3 + reader.bits(3)? as usize),
+            _ => (0, 11 + reader.bits(7)? as usize),
+        };
+        if i + repeat > lengths.len() {
+            return Err(InflateError::InvalidRepeat);
+        }
+        lengths[i..i + repeat].fill(value);
+        i += repeat;
+    }
+    if lengths[END_OF_BLOCK as usize] == 0 {
+        return Err(InflateError::MissingEndOfBlock);
+    }
+
+    let lit_len = Huffman::new(&lengths[..lit_len_count])?;
+    let dist = Huffman::new(&lengths[lit_len_count..])?;
+    Ok((lit_len, dist))
+}
+
+fn inflate_codes(
+    reader: &mut BitReader,
+    out: &mut Vec<u8>,
+    lit_len: &Huffman,
+    dist: &Huffman,
+) -> Result<(), InflateError> {
+    loop {
+        let symbol = lit_len.decode(reader)?;
+        match symbol {
+            0..=255 => out.push(symbol as u8),
+            END_OF_BLOCK => return Ok(()),
+            _ => {
+                let len_index = (symbol - 257) as usize;
+                if len_index >= LEN_BASE.len() {
+                    return Err(InflateError::InvalidCode);
+                }
+                let len = LEN_BASE[len_index] as usize
+                    + reader.bits(LEN_EXTRA[len_index] as u32)? as usize;
+
+                let dist_index = dist.decode(reader)? as usize;
+                if dist_index >= DIST_BASE.len() {
+                    return Err(InflateError::InvalidCode);
+                }
+                let distance = DIST_BASE[dist_index] as usize
+                    + reader.bits(DIST_EXTRA[dist_index] as u32)? as usize;
+                if distance > out.len() {
+                    return Err(InflateError::DistanceTooFar(distance));
+                }
+
+                // the copy may overlap the bytes it produces, e.g. distance 1 repeats a byte
+                let start = out.len() - distance;
+                for k in 0..len {
+                    out.push(out[start + k]);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inflate_stored_block() {
+        // given
+        let data = [0x01, 0x05, 0x00, 0xfa, 0xff, b'h', b'e', b'l', b'l', b'o'];
+
+        // when
+        let out = inflate(&data, 5).unwrap();
+
+        // then
+        assert_eq!(out, b"hello");
+    }
+
+    #[test]
+    fn inflate_fixed_huffman_block() {
+        // given: raw deflate of "hello hello hello hello"
+        let data = [0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x57, 0xc8, 0x40, 0x27, 0x01];
+
+        // when
+        let out = inflate(&data, 0).unwrap();
+
+        // then
+        assert_eq!(out, b"hello hello hello hello");
+    }
+
+    #[test]
+    fn inflate_fixed_huffman_block_with_long_matches() {
+        // given: raw deflate of "abcdefghijklmnopqrstuvwxyz" * 4 + "0123456789" * 3
+        let data = [
+            0x4b, 0x4c, 0x4a, 0x4e, 0x49, 0x4d, 0x4b, 0xcf, 0xc8, 0xcc, 0xca, 0xce, 0xc9, 0xcd,
+            0xcb, 0x2f, 0x28, 0x2c, 0x2a, 0x2e, 0x29, 0x2d, 0x2b, 0xaf, 0xa8, 0xac, 0x4a, 0xa4,
+            0xaa, 0x8c, 0x81, 0xa1, 0x91, 0xb1, 0x89, 0xa9, 0x99, 0xb9, 0x85, 0x25, 0x36, 0x16,
+            0x00,
+        ];
+        let expected = "abcdefghijklmnopqrstuvwxyz".repeat(4) + &"0123456789".repeat(3);
+
+        // when
+        let out = inflate(&data, expected.len()).unwrap();
+
+        // then
+        assert_eq!(out, expected.as_bytes());
+    }
+
+    #[test]
+    fn inflate_dynamic_huffman_block() {
+        // given
+        let data = [
+            0x15, 0xc4, 0xb1, 0x0d, 0x00, 0x00, 0x0c, 0xc2, 0xb0, 0x9d, 0x2f, 0x13, 0xf8, 0xff,
+            0x86, 0xaa, 0x1e, 0x2c, 0xe1, 0x29, 0xed, 0xac, 0x0c, 0xf3, 0x49, 0x7a,
+        ];
+
+        // when
+        let out = inflate(&data, 0).unwrap();
+
+        // then
+        assert_eq!(out, b"ba\naaaaabbaccdbcbadab\nadabba\nc");
+    }
+
+    #[test]
+    fn inflate_rejects_truncated_input() {
+        // given
+        let data = [0xcb, 0x48, 0xcd];
+
+        // when
+        let res = inflate(&data, 0);
+
+        // then
+        assert_eq!(res, Err(InflateError::UnexpectedEof));
+    }
+
+    #[test]
+    fn inflate_rejects_invalid_block_type() {
+        // given: BFINAL=1, BTYPE=11
+        let data = [0x07];
+
+        // when
+        let res = inflate(&data, 0);
+
+        // then
+        assert_eq!(res, Err(InflateError::InvalidBlockType(3)));
+    }
+
+    // zlib.compress(b"jimage jimage jimage resource")
+    const ZLIB_DATA: [u8; 25] = [
+        0x78, 0x9c, 0xcb, 0xca, 0xcc, 0x4d, 0x4c, 0x4f, 0x55, 0xc8, 0x42, 0xa1, 0x8a, 0x52, 0x8b,
+        0xf3, 0x4b, 0x8b, 0x92, 0x53, 0x01, 0xa3, 0xcb, 0x0b, 0x10,
+    ];
+
+    #[test]
+    fn inflate_zlib_stream() {
+        // given
+        let data = ZLIB_DATA;
+
+        // when
+        let out = inflate_zlib(&data, 0).unwrap();
+
+        // then
+        assert_eq!(out, b"jimage jimage jimage resource");
+    }
+
+    #[test]
+    fn inflate_zlib_detects_checksum_mismatch() {
+        // given
+        let mut data = ZLIB_DATA;
+        data[24] ^= 0x01;
+
+        // when
+        let result = inflate_zlib(&data, 0);
+
+        // then
+        assert_eq!(result, Err(InflateError::ChecksumMismatch));
+    }
+}
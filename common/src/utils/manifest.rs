@@ -0,0 +1,142 @@
+use crate::utils::zip::{ZipArchive, ZipError};
+
+pub const MANIFEST_NAME: &str = "META-INF/MANIFEST.MF";
+pub const VERSIONS_DIR: &str = "META-INF/versions/";
+
+/// Main section of a JAR manifest. Per-entry sections are not needed by the VM and are skipped.
+///
+/// https://docs.oracle.com/en/java/javase/25/docs/specs/jar/jar.html#jar-manifest
+#[derive(Debug, Default, Clone)]
+pub struct Manifest {
+    main_attributes: Vec<(String, String)>,
+}
+
+impl Manifest {
+    /// Lines end with CR, LF or CRLF. Writers wrap lines at 72 bytes, a line starting with a single
+    /// space continues the previous one. The main section ends at the first empty line.
+    pub fn parse(data: &[u8]) -> Self {
+        let text = String::from_utf8_lossy(data);
+        let mut main_attributes: Vec<(String, String)> = Vec::new();
+
+        for line in text.split("\r\n").flat_map(|l| l.split(['\r', '\n'])) {
+            if line.is_empty() {
+                if main_attributes.is_empty() {
+                    continue;
+                }
+                break;
+            }
+            if let Some(continuation) = line.strip_prefix(' ') {
+                if let Some((_, value)) = main_attributes.last_mut() {
+                    value.push_str(continuation);
+                }
+                continue;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                let value = value.strip_prefix(' ').unwrap_or(value);
+                main_attributes.push((name.to_string(), value.to_string()));
+            }
+        }
+
+        Self { main_attributes }
+    }
+
+    /// Reads the manifest of the archive, `None` if it has none.
+    pub fn read_from(archive: &ZipArchive) -> Result<Option<Self>, ZipError> {
+        archive
+            .read_by_name(MANIFEST_NAME)
+            .transpose()
+            .map(|data| data.map(|data| Self::parse(&data)))
+    }
+
+    /// Attribute names are case-insensitive.
+    pub fn main_attribute(&self, name: &str) -> Option<&str> {
+        self.main_attributes
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn main_class(&self) -> Option<&str> {
+        self.main_attribute("Main-Class")
+    }
+
+    /// Space-separated relative URLs of other archives or directories.
+    pub fn class_path(&self) -> Vec<&str> {
+        self.main_attribute("Class-Path")
+            .map(|value| value.split_ascii_whitespace().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn is_multi_release(&self) -> bool {
+        self.main_attribute("Multi-Release")
+            .map(|value| value.trim().eq_ignore_ascii_case("true"))
+            .unwrap_or(false)
+    }
+}
+
+/// Splits `META-INF/versions/N/path` into `(N, path)`.
+pub fn versioned_entry(name: &str) -> Option<(u32, &str)> {
+    let (version, path) = name.strip_prefix(VERSIONS_DIR)?.split_once('/')?;
+    Some((version.parse().ok()?, path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_main_attributes_with_continuations() {
+        // given
+        let data = b"Manifest-Version: 1.0\r\n\
+            Main-Class: com.example.app.VeryLongPackageNameThatDoesNotFitIntoSeventyTw\r\n \
+            oBytes\r\n\
+            Class-Path: lib/a.jar lib/b.jar \r\n \
+            lib/c.jar\r\n\
+            \r\n\
+            Name: com/example/app/\r\n\
+            Sealed: true\r\n";
+
+        // when
+        let manifest = Manifest::parse(data);
+
+        // then
+        assert_eq!(
+            manifest.main_class(),
+            Some("com.example.app.VeryLongPackageNameThatDoesNotFitIntoSeventyTwoBytes")
+        );
+        assert_eq!(
+            manifest.class_path(),
+            ["lib/a.jar", "lib/b.jar", "lib/c.jar"]
+        );
+        assert_eq!(manifest.main_attribute("Sealed"), None);
+    }
+
+    #[test]
+    fn attribute_names_are_case_insensitive() {
+        // given
+        let data = b"multi-release: TRUE\nmain-class: Main\n";
+
+        // when
+        let manifest = Manifest::parse(data);
+
+        // then
+        assert!(manifest.is_multi_release());
+        assert_eq!(manifest.main_attribute("MAIN-CLASS"), Some("Main"));
+    }
+
+    #[test]
+    fn splits_versioned_entries() {
+        // given
+        let names = [
+            "META-INF/versions/11/com/example/Util.class",
+            "META-INF/versions/x/com/example/Util.class",
+            "com/example/Util.class",
+        ];
+
+        // when
+        let parsed: Vec<_> = names.iter().map(|name| versioned_entry(name)).collect();
+
+        // then
+        assert_eq!(parsed, [Some((11, "com/example/Util.class")), None, None]);
+    }
+}
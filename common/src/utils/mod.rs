@@ -1,3 +1,6 @@
 pub mod cursor;
 pub mod indent_write;
+pub mod inflate;
+pub mod manifest;
 pub mod telemetry;
+pub mod zip;
@@ -0,0 +1,420 @@
+use crate::utils::cursor::{ByteCursor, ByteOrder, CursorError};
+use crate::utils::inflate::{InflateError, inflate};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const LOCAL_HEADER_SIGNATURE: u32 = 0x04034b50;
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x02014b50;
+const END_OF_CENTRAL_DIR_SIGNATURE: u32 = 0x06054b50;
+const END_OF_CENTRAL_DIR_SIZE: usize = 22;
+const LOCAL_HEADER_SIZE: usize = 30;
+/// The end of central directory record is followed by a comment of at most 64KiB.
+const MAX_END_OF_CENTRAL_DIR_SEARCH: u64 = END_OF_CENTRAL_DIR_SIZE as u64 + u16::MAX as u64;
+const FLAG_ENCRYPTED: u16 = 0x0001;
+/// Set by the value `0xFFFFFFFF`/`0xFFFF` in fields that are moved to the ZIP64 extra field.
+const ZIP64_MARKER: u32 = 0xFFFFFFFF;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressionMethod {
+    Stored,
+    Deflated,
+    Other(u16),
+}
+
+impl From<u16> for CompressionMethod {
+    fn from(value: u16) -> Self {
+        match value {
+            0 => CompressionMethod::Stored,
+            8 => CompressionMethod::Deflated,
+            other => CompressionMethod::Other(other),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ZipError {
+    Io(std::io::Error),
+    NotAZipFile,
+    Corrupted(String),
+    Unsupported(String),
+    Inflate(String, InflateError),
+    CrcMismatch(String),
+}
+
+impl Display for ZipError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ZipError::Io(e) => write!(f, "{e}"),
+            ZipError::NotAZipFile => write!(f, "zip END header not found"),
+            ZipError::Corrupted(msg) => write!(f, "invalid zip file: {msg}"),
+            ZipError::Unsupported(msg) => write!(f, "unsupported zip feature: {msg}"),
+            ZipError::Inflate(name, e) => write!(f, "invalid deflate data in {name}: {e}"),
+            ZipError::CrcMismatch(name) => write!(f, "invalid entry CRC for {name}"),
+        }
+    }
+}
+
+impl From<std::io::Error> for ZipError {
+    fn from(value: std::io::Error) -> Self {
+        ZipError::Io(value)
+    }
+}
+
+impl From<CursorError> for ZipError {
+    fn from(_: CursorError) -> Self {
+        ZipError::Corrupted("truncated header".to_string())
+    }
+}
+
+/// An entry of the central directory.
+#[derive(Debug, Clone)]
+pub struct ZipEntry {
+    pub name: String,
+    pub method: CompressionMethod,
+    pub crc32: u32,
+    pub compressed_size: u64,
+    pub size: u64,
+    flags: u16,
+    local_header_offset: u64,
+}
+
+impl ZipEntry {
+    pub fn is_dir(&self) -> bool {
+        self.name.ends_with('/')
+    }
+}
+
+/// Read-only ZIP/JAR archive. Only the central directory is read up front, entry data is
+/// read from the file on demand.
+///
+/// https://pkware.cachefly.net/webdocs/casestudies/APPNOTE.TXT
+#[derive(Debug)]
+pub struct ZipArchive {
+    path: PathBuf,
+    file: Mutex<File>,
+    entries: Vec<ZipEntry>,
+    index: HashMap<String, usize>,
+}
+
+impl ZipArchive {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ZipError> {
+        let path = path.as_ref().to_path_buf();
+        let mut file = File::open(&path)?;
+        let file_len = file.metadata()?.len();
+
+        let tail_len = file_len.min(MAX_END_OF_CENTRAL_DIR_SEARCH);
+        let mut tail = vec![0u8; tail_len as usize];
+        file.seek(SeekFrom::Start(file_len - tail_len))?;
+        file.read_exact(&mut tail)?;
+        let eocd_pos = Self::find_end_of_central_dir(&tail).ok_or(ZipError::NotAZipFile)?;
+
+        let mut eocd = ByteCursor::with_order(&tail[eocd_pos..], ByteOrder::LittleEndian);
+        eocd.skip(4)?; // signature
+        let disk = eocd.u16()?;
+        let central_dir_disk = eocd.u16()?;
+        eocd.skip(2)?; // entries on this disk
+        let entry_count = eocd.u16()?;
+        let central_dir_size = eocd.u32()?;
+        let central_dir_offset = eocd.u32()?;
+        if disk != 0 || central_dir_disk != 0 {
+            return Err(ZipError::Unsupported("multi-disk archive".to_string()));
+        }
+        if entry_count == u16::MAX
+            || central_dir_size == ZIP64_MARKER
+            || central_dir_offset == ZIP64_MARKER
+        {
+            return Err(ZipError::Unsupported("ZIP64 archive".to_string()));
+        }
+        // data prepended to the archive (e.g. a launcher script) shifts all offsets
+        let eocd_offset = file_len - tail_len + eocd_pos as u64;
+        let central_dir_start = eocd_offset
+            .checked_sub(central_dir_size as u64)
+            .ok_or_else(|| ZipError::Corrupted("central directory size".to_string()))?;
+        let prefix_len = central_dir_start
+            .checked_sub(central_dir_offset as u64)
+            .ok_or_else(|| ZipError::Corrupted("central directory offset".to_string()))?;
+
+        let mut central_dir = vec![0u8; central_dir_size as usize];
+        file.seek(SeekFrom::Start(central_dir_start))?;
+        file.read_exact(&mut central_dir)?;
+        let entries = Self::read_central_dir(&central_dir, entry_count as usize, prefix_len)?;
+
+        let index = entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| (entry.name.clone(), i))
+            .collect();
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+            entries,
+            index,
+        })
+    }
+
+    fn find_end_of_central_dir(tail: &[u8]) -> Option<usize> {
+        let signature = END_OF_CENTRAL_DIR_SIGNATURE.to_le_bytes();
+        (0..=tail.len().checked_sub(END_OF_CENTRAL_DIR_SIZE)?)
+            .rev()
+            .find(|&pos| tail[pos..pos + 4] == signature)
+    }
+
+    fn read_central_dir(
+        data: &[u8],
+        entry_count: usize,
+        prefix_len: u64,
+    ) -> Result<Vec<ZipEntry>, ZipError> {
+        let mut cursor = ByteCursor::with_order(data, ByteOrder::LittleEndian);
+        let mut entries = Vec::with_capacity(entry_count);
+        for _ in 0..entry_count {
+            if cursor.u32()? != CENTRAL_HEADER_SIGNATURE {
+                return Err(ZipError::Corrupted(
+                    "bad central directory header".to_string(),
+                ));
+            }
+            cursor.skip(4)?; // version made by, version needed
+            let flags = cursor.u16()?;
+            let method = cursor.u16()?;
+            cursor.skip(4)?; // modification time and date
+            let crc32 = cursor.u32()?;
+            let compressed_size = cursor.u32()?;
+            let size = cursor.u32()?;
+            let name_len = cursor.u16()? as usize;
+            let extra_len = cursor.u16()? as usize;
+            let comment_len = cursor.u16()? as usize;
+            cursor.skip(8)?; // disk number, internal and external attributes
+            let local_header_offset = cursor.u32()?;
+            let name = String::from_utf8_lossy(cursor.slice(name_len)?).into_owned();
+            cursor.skip(extra_len + comment_len)?;
+
+            if compressed_size == ZIP64_MARKER
+                || size == ZIP64_MARKER
+                || local_header_offset == ZIP64_MARKER
+            {
+                return Err(ZipError::Unsupported(format!("ZIP64 entry {name}")));
+            }
+            entries.push(ZipEntry {
+                name,
+                method: method.into(),
+                crc32,
+                compressed_size: compressed_size as u64,
+                size: size as u64,
+                flags,
+                local_header_offset: local_header_offset as u64 + prefix_len,
+            });
+        }
+        Ok(entries)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Entries in central directory order.
+    pub fn entries(&self) -> &[ZipEntry] {
+        &self.entries
+    }
+
+    pub fn by_name(&self, name: &str) -> Option<&ZipEntry> {
+        self.index.get(name).map(|&i| &self.entries[i])
+    }
+
+    /// Reads and decompresses the entry, checking its CRC.
+    pub fn read(&self, entry: &ZipEntry) -> Result<Vec<u8>, ZipError> {
+        if entry.flags & FLAG_ENCRYPTED != 0 {
+            return Err(ZipError::Unsupported(format!(
+                "encrypted entry {}",
+                entry.name
+            )));
+        }
+        let compressed = self.read_raw(entry)?;
+        let data = match entry.method {
+            CompressionMethod::Stored => compressed,
+            CompressionMethod::Deflated => inflate(&compressed, entry.size as usize)
+                .map_err(|e| ZipError::Inflate(entry.name.clone(), e))?,
+            CompressionMethod::Other(method) => {
+                return Err(ZipError::Unsupported(format!(
+                    "compression method {method} of {}",
+                    entry.name
+                )));
+            }
+        };
+        if data.len() as u64 != entry.size || crc32(&data) != entry.crc32 {
+            return Err(ZipError::CrcMismatch(entry.name.clone()));
+        }
+        Ok(data)
+    }
+
+    pub fn read_by_name(&self, name: &str) -> Option<Result<Vec<u8>, ZipError>> {
+        self.by_name(name).map(|entry| self.read(entry))
+    }
+
+    fn read_raw(&self, entry: &ZipEntry) -> Result<Vec<u8>, ZipError> {
+        let mut file = self.file.lock().unwrap();
+        let mut header = [0u8; LOCAL_HEADER_SIZE];
+        file.seek(SeekFrom::Start(entry.local_header_offset))?;
+        file.read_exact(&mut header)?;
+        let mut cursor = ByteCursor::with_order(&header, ByteOrder::LittleEndian);
+        if cursor.u32()? != LOCAL_HEADER_SIGNATURE {
+            return Err(ZipError::Corrupted(format!(
+                "bad local header of {}",
+                entry.name
+            )));
+        }
+        // sizes in the local header may be zero when a data descriptor follows the data,
+        // the central directory is authoritative
+        cursor.skip(22)?;
+        let name_len = cursor.u16()? as u64;
+        let extra_len = cursor.u16()? as u64;
+
+        let mut data = vec![0u8; entry.compressed_size as usize];
+        file.seek(SeekFrom::Current((name_len + extra_len) as i64))?;
+        file.read_exact(&mut data)?;
+        Ok(data)
+    }
+}
+
+/// CRC-32 as used by ZIP (reflected, polynomial 0xEDB88320).
+pub fn crc32(data: &[u8]) -> u32 {
+    const TABLE: [u32; 256] = {
+        let mut table = [0u32; 256];
+        let mut i = 0;
+        while i < 256 {
+            let mut crc = i as u32;
+            let mut bit = 0;
+            while bit < 8 {
+                crc = if crc & 1 != 0 {
+                    (crc >> 1) ^ 0xEDB88320
+                } else {
+                    crc >> 1
+                };
+                bit += 1;
+            }
+            table[i] = crc;
+            i += 1;
+        }
+        table
+    };
+
+    !data.iter().fold(!0u32, |crc, &byte| {
+        TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc32_of_check_string() {
+        // given
+        let data = b"123456789";
+
+        // when
+        let crc = crc32(data);
+
+        // then
+        assert_eq!(crc, 0xCBF43926);
+    }
+
+    // two entries written by Python's zipfile: a/Stored.txt (stored), Deflated.txt (deflated)
+    const TWO_ENTRIES_ZIP: [u8; 248] = [
+        0x50, 0x4b, 0x03, 0x04, 0x14, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x21, 0x00, 0xc6,
+        0xa9, 0x1e, 0x95, 0x0c, 0x00, 0x00, 0x00, 0x0c, 0x00, 0x00, 0x00, 0x0c, 0x00, 0x00, 0x00,
+        0x61, 0x2f, 0x53, 0x74, 0x6f, 0x72, 0x65, 0x64, 0x2e, 0x74, 0x78, 0x74, 0x73, 0x74, 0x6f,
+        0x72, 0x65, 0x64, 0x20, 0x65, 0x6e, 0x74, 0x72, 0x79, 0x50, 0x4b, 0x03, 0x04, 0x14, 0x00,
+        0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x21, 0x00, 0xfd, 0x2e, 0x67, 0xc7, 0x0e, 0x00, 0x00,
+        0x00, 0x1a, 0x00, 0x00, 0x00, 0x0c, 0x00, 0x00, 0x00, 0x44, 0x65, 0x66, 0x6c, 0x61, 0x74,
+        0x65, 0x64, 0x2e, 0x74, 0x78, 0x74, 0x4b, 0x49, 0x4d, 0xcb, 0x49, 0x2c, 0x49, 0x4d, 0x51,
+        0x48, 0x41, 0x67, 0x00, 0x00, 0x50, 0x4b, 0x01, 0x02, 0x14, 0x03, 0x14, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x21, 0x00, 0xc6, 0xa9, 0x1e, 0x95, 0x0c, 0x00, 0x00, 0x00, 0x0c,
+        0x00, 0x00, 0x00, 0x0c, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x80, 0x01, 0x00, 0x00, 0x00, 0x00, 0x61, 0x2f, 0x53, 0x74, 0x6f, 0x72, 0x65, 0x64, 0x2e,
+        0x74, 0x78, 0x74, 0x50, 0x4b, 0x01, 0x02, 0x14, 0x03, 0x14, 0x00, 0x00, 0x00, 0x08, 0x00,
+        0x00, 0x00, 0x21, 0x00, 0xfd, 0x2e, 0x67, 0xc7, 0x0e, 0x00, 0x00, 0x00, 0x1a, 0x00, 0x00,
+        0x00, 0x0c, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80, 0x01,
+        0x36, 0x00, 0x00, 0x00, 0x44, 0x65, 0x66, 0x6c, 0x61, 0x74, 0x65, 0x64, 0x2e, 0x74, 0x78,
+        0x74, 0x50, 0x4b, 0x05, 0x06, 0x00, 0x00, 0x00, 0x00, 0x02, 0x00, 0x02, 0x00, 0x74, 0x00,
+        0x00, 0x00, 0x6e, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ];
+
+    fn write_temp(name: &str, data: &[u8]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("{}-{name}", std::process::id()));
+        std::fs::write(&path, data).unwrap();
+        path
+    }
+
+    #[test]
+    fn reads_stored_and_deflated_entries() {
+        // given
+        let path = write_temp("two_entries.zip", &TWO_ENTRIES_ZIP);
+
+        // when
+        let archive = ZipArchive::open(&path).unwrap();
+
+        // then
+        let names: Vec<_> = archive.entries().iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["a/Stored.txt", "Deflated.txt"]);
+        assert_eq!(
+            archive.read_by_name("a/Stored.txt").unwrap().unwrap(),
+            b"stored entry"
+        );
+        assert_eq!(
+            archive.read_by_name("Deflated.txt").unwrap().unwrap(),
+            b"deflated deflated deflated"
+        );
+        assert!(archive.read_by_name("Missing.txt").is_none());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn reads_archive_with_prepended_data() {
+        // given
+        let mut data = b"#!/bin/sh\nexec java -jar \"$0\"\n".to_vec();
+        data.extend_from_slice(&TWO_ENTRIES_ZIP);
+        let path = write_temp("prefixed.zip", &data);
+
+        // when
+        let archive = ZipArchive::open(&path).unwrap();
+
+        // then
+        assert_eq!(
+            archive.read_by_name("Deflated.txt").unwrap().unwrap(),
+            b"deflated deflated deflated"
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn detects_crc_mismatch() {
+        // given
+        let mut data = TWO_ENTRIES_ZIP;
+        data[42] ^= 0xFF; // first byte of "stored entry"
+        let path = write_temp("bad_crc.zip", &data);
+
+        // when
+        let archive = ZipArchive::open(&path).unwrap();
+        let result = archive.read_by_name("a/Stored.txt").unwrap();
+
+        // then
+        assert!(matches!(result, Err(ZipError::CrcMismatch(name)) if name == "a/Stored.txt"));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn rejects_non_zip_file() {
+        // given
+        let path = write_temp("not_a.zip", b"definitely not a zip archive");
+
+        // when
+        let result = ZipArchive::open(&path);
+
+        // then
+        assert!(matches!(result, Err(ZipError::NotAZipFile)));
+        std::fs::remove_file(path).unwrap();
+    }
+}
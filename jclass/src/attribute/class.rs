@@ -1,8 +1,13 @@
 use crate::ClassFormatErr;
 use crate::attribute::{AttributeType, SharedAttribute};
 use crate::constant::pool::ConstantPool;
+use crate::flags::{ModuleFlags, RequiresFlags};
 use common::utils::cursor::ByteCursor;
 
+/// `ModuleResolution` flag: the module is not a default root, it is only resolved when it is a
+/// root given on the command line or required by another module.
+pub const DO_NOT_RESOLVE_BY_DEFAULT: u16 = 0x0001;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ClassAttr {
     Shared(SharedAttribute),
@@ -11,9 +16,16 @@ pub enum ClassAttr {
     EnclosingMethod(u16, u16),
     SourceDebugExtension,
     BootstrapMethods(Vec<BootstrapMethodEntry>),
-    Module,
-    ModulePackages,
-    ModuleMainClass,
+    Module(ModuleAttribute),
+    /// Package constant indexes
+    ModulePackages(Vec<u16>),
+    /// Class constant index of the main class
+    ModuleMainClass(u16),
+    /// Utf8 constant index of the target platform, e.g. `linux-amd64`
+    ModuleTarget(u16),
+    ModuleHashes(ModuleHashes),
+    /// `DO_NOT_RESOLVE_BY_DEFAULT` and `WARN_*` flags
+    ModuleResolution(u16),
     NestHost(u16),
     NestMembers(Vec<u16>),
     Record,
@@ -35,6 +47,139 @@ impl BootstrapMethodEntry {
     }
 }
 
+/// https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.7.25
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleAttribute {
+    /// Module constant index
+    pub module_name_idx: u16,
+    pub module_flags: ModuleFlags,
+    /// Utf8 constant index, 0 if the version is unknown
+    pub module_version_idx: u16,
+    pub requires: Vec<ModuleRequires>,
+    pub exports: Vec<ModuleExports>,
+    pub opens: Vec<ModuleExports>,
+    /// Class constant indexes of the service interfaces
+    pub uses: Vec<u16>,
+    pub provides: Vec<ModuleProvides>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleRequires {
+    /// Module constant index
+    pub requires_idx: u16,
+    pub requires_flags: RequiresFlags,
+    /// Utf8 constant index, 0 if the version is unknown
+    pub requires_version_idx: u16,
+}
+
+/// Shared by `exports` and `opens`, which have the same layout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleExports {
+    /// Package constant index
+    pub package_idx: u16,
+    pub flags: u16,
+    /// Module constant indexes, empty for an unqualified export
+    pub to_idx: Vec<u16>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleProvides {
+    /// Class constant index of the service interface
+    pub provides_idx: u16,
+    /// Class constant indexes of the implementations
+    pub provides_with_idx: Vec<u16>,
+}
+
+impl ModuleAttribute {
+    fn read(cursor: &mut ByteCursor<'_>) -> Result<Self, ClassFormatErr> {
+        let module_name_idx = cursor.u16()?;
+        let module_flags = ModuleFlags::new(cursor.u16()?);
+        let module_version_idx = cursor.u16()?;
+
+        let requires_count = cursor.u16()? as usize;
+        let mut requires = Vec::with_capacity(requires_count);
+        for _ in 0..requires_count {
+            requires.push(ModuleRequires {
+                requires_idx: cursor.u16()?,
+                requires_flags: RequiresFlags::new(cursor.u16()?),
+                requires_version_idx: cursor.u16()?,
+            });
+        }
+        let exports = Self::read_exports(cursor)?;
+        let opens = Self::read_exports(cursor)?;
+        let uses = Self::read_indexes(cursor)?;
+
+        let provides_count = cursor.u16()? as usize;
+        let mut provides = Vec::with_capacity(provides_count);
+        for _ in 0..provides_count {
+            provides.push(ModuleProvides {
+                provides_idx: cursor.u16()?,
+                provides_with_idx: Self::read_indexes(cursor)?,
+            });
+        }
+
+        Ok(Self {
+            module_name_idx,
+            module_flags,
+            module_version_idx,
+            requires,
+            exports,
+            opens,
+            uses,
+            provides,
+        })
+    }
+
+    fn read_exports(cursor: &mut ByteCursor<'_>) -> Result<Vec<ModuleExports>, ClassFormatErr> {
+        let count = cursor.u16()? as usize;
+        let mut exports = Vec::with_capacity(count);
+        for _ in 0..count {
+            exports.push(ModuleExports {
+                package_idx: cursor.u16()?,
+                flags: cursor.u16()?,
+                to_idx: Self::read_indexes(cursor)?,
+            });
+        }
+        Ok(exports)
+    }
+
+    fn read_indexes(cursor: &mut ByteCursor<'_>) -> Result<Vec<u16>, ClassFormatErr> {
+        let count = cursor.u16()? as usize;
+        let mut indexes = Vec::with_capacity(count);
+        for _ in 0..count {
+            indexes.push(cursor.u16()?);
+        }
+        Ok(indexes)
+    }
+}
+
+/// Hashes of the modules tied to this one at link time, so that they can only be upgraded
+/// together.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleHashes {
+    /// Utf8 constant index of the digest algorithm, e.g. `SHA-256`
+    pub algorithm_idx: u16,
+    /// (Utf8 constant index of the module name, hash)
+    pub hashes: Vec<(u16, Vec<u8>)>,
+}
+
+impl ModuleHashes {
+    fn read(cursor: &mut ByteCursor<'_>) -> Result<Self, ClassFormatErr> {
+        let algorithm_idx = cursor.u16()?;
+        let count = cursor.u16()? as usize;
+        let mut hashes = Vec::with_capacity(count);
+        for _ in 0..count {
+            let module_name_idx = cursor.u16()?;
+            let len = cursor.u16()? as usize;
+            hashes.push((module_name_idx, cursor.bytes(len)?.to_vec()));
+        }
+        Ok(Self {
+            algorithm_idx,
+            hashes,
+        })
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct InnerClassEntry {
     pub inner_class_info_index: u16,
@@ -65,7 +210,7 @@ impl<'a> ClassAttr {
         cursor: &mut ByteCursor<'a>,
     ) -> Result<Self, ClassFormatErr> {
         let attribute_name_index = cursor.u16()?;
-        let _attribute_length = cursor.u32()? as usize;
+        let attribute_length = cursor.u32()? as usize;
 
         let attribute_type = AttributeType::try_from(pool.get_utf8(&attribute_name_index)?)?;
         match attribute_type {
@@ -125,6 +270,14 @@ impl<'a> ClassAttr {
                 }
                 Ok(ClassAttr::PermittedSubclasses(classes))
             }
+            AttributeType::Module => Ok(ClassAttr::Module(ModuleAttribute::read(cursor)?)),
+            AttributeType::ModulePackages => Ok(ClassAttr::ModulePackages(
+                ModuleAttribute::read_indexes(cursor)?,
+            )),
+            AttributeType::ModuleMainClass => Ok(ClassAttr::ModuleMainClass(cursor.u16()?)),
+            AttributeType::ModuleTarget => Ok(ClassAttr::ModuleTarget(cursor.u16()?)),
+            AttributeType::ModuleHashes => Ok(ClassAttr::ModuleHashes(ModuleHashes::read(cursor)?)),
+            AttributeType::ModuleResolution => Ok(ClassAttr::ModuleResolution(cursor.u16()?)),
             AttributeType::RuntimeVisibleAnnotations
             | AttributeType::RuntimeVisibleTypeAnnotations
             | AttributeType::RuntimeInvisibleTypeAnnotations
@@ -133,6 +286,7 @@ impl<'a> ClassAttr {
             | AttributeType::RuntimeInvisibleAnnotations
             | AttributeType::Signature => Ok(ClassAttr::Shared(SharedAttribute::read(
                 attribute_type,
+                attribute_length,
                 cursor,
             )?)),
             other => unimplemented!("Class attribute {:?} not implemented", other),
@@ -261,9 +415,58 @@ impl<'a> ClassAttr {
                     Ok(())
                 })?;
             }
-            ClassAttr::Module => unimplemented!(),
-            ClassAttr::ModulePackages => unimplemented!(),
-            ClassAttr::ModuleMainClass => unimplemented!(),
+            ClassAttr::Module(module) => module.fmt_pretty(ind, cp)?,
+            ClassAttr::ModulePackages(packages) => {
+                writeln!(ind, "ModulePackages:")?;
+                ind.with_indent(|ind| {
+                    for package in packages {
+                        writeln!(
+                            ind,
+                            "{:<40} // {}",
+                            format!("#{package}"),
+                            pretty_try!(ind, cp.get_package_name(package))
+                        )?;
+                    }
+                    Ok(())
+                })?;
+            }
+            ClassAttr::ModuleMainClass(idx) => {
+                writeln!(
+                    ind,
+                    "{:<40} // {}",
+                    format!("ModuleMainClass: #{idx}"),
+                    pretty_try!(ind, cp.get_class_name(idx))
+                )?;
+            }
+            ClassAttr::ModuleTarget(idx) => {
+                writeln!(ind, "ModuleTarget:")?;
+                ind.with_indent(|ind| {
+                    writeln!(
+                        ind,
+                        "{:<40} // {}",
+                        format!("target_platform: #{idx}"),
+                        pretty_try!(ind, cp.get_utf8(idx))
+                    )
+                })?;
+            }
+            ClassAttr::ModuleHashes(hashes) => {
+                writeln!(ind, "ModuleHashes:")?;
+                ind.with_indent(|ind| {
+                    writeln!(
+                        ind,
+                        "algorithm: {}",
+                        pretty_try!(ind, cp.get_utf8(&hashes.algorithm_idx))
+                    )?;
+                    for (module, hash) in &hashes.hashes {
+                        let hex: String = hash.iter().map(|b| format!("{b:02x}")).collect();
+                        writeln!(ind, "{} {hex}", pretty_try!(ind, cp.get_utf8(module)))?;
+                    }
+                    Ok(())
+                })?;
+            }
+            ClassAttr::ModuleResolution(flags) => {
+                writeln!(ind, "ModuleResolution: 0x{flags:04x}")?;
+            }
             ClassAttr::NestHost(idx) => {
                 let constant = pretty_try!(ind, cp.get_raw(idx));
                 writeln!(
@@ -296,3 +499,146 @@ impl<'a> ClassAttr {
         Ok(())
     }
 }
+
+#[cfg(feature = "pretty_print")]
+impl ModuleAttribute {
+    /// Same layout as `javap -v`: every table is preceded by its length, each entry is printed as
+    /// its raw indexes and flags with the resolved names as a comment.
+    fn fmt_pretty(
+        &self,
+        ind: &mut common::utils::indent_write::Indented<'_>,
+        cp: &ConstantPool,
+    ) -> std::fmt::Result {
+        use common::pretty_try;
+        use std::fmt::Write as _;
+
+        let version = |idx: u16| -> Result<&str, ClassFormatErr> {
+            if idx == 0 { Ok("") } else { cp.get_utf8(&idx) }
+        };
+
+        writeln!(ind, "Module:")?;
+        ind.with_indent(|ind| {
+            writeln!(
+                ind,
+                "{:<40} // \"{}\"{}",
+                format!(
+                    "#{},{:x}",
+                    self.module_name_idx,
+                    self.module_flags.get_raw()
+                ),
+                pretty_try!(ind, cp.get_module_name(&self.module_name_idx)),
+                if self.module_flags.is_open() {
+                    " ACC_OPEN"
+                } else {
+                    ""
+                }
+            )?;
+            writeln!(
+                ind,
+                "{:<40} // {}",
+                format!("#{}", self.module_version_idx),
+                pretty_try!(ind, version(self.module_version_idx))
+            )?;
+
+            writeln!(ind, "{:<40} // requires", self.requires.len())?;
+            ind.with_indent(|ind| {
+                for req in &self.requires {
+                    let flags = req.requires_flags;
+                    let mut names = String::new();
+                    for (set, name) in [
+                        (flags.is_transitive(), " ACC_TRANSITIVE"),
+                        (flags.is_static_phase(), " ACC_STATIC_PHASE"),
+                        (flags.is_synthetic(), " ACC_SYNTHETIC"),
+                        (flags.is_mandated(), " ACC_MANDATED"),
+                    ] {
+                        if set {
+                            names.push_str(name);
+                        }
+                    }
+                    writeln!(
+                        ind,
+                        "{:<36} // \"{}\"{}",
+                        format!("#{},{:x}", req.requires_idx, flags.get_raw()),
+                        pretty_try!(ind, cp.get_module_name(&req.requires_idx)),
+                        names
+                    )?;
+                    writeln!(
+                        ind,
+                        "{:<36} // {}",
+                        format!("#{}", req.requires_version_idx),
+                        pretty_try!(ind, version(req.requires_version_idx))
+                    )?;
+                }
+                Ok(())
+            })?;
+
+            for (kind, entries) in [("exports", &self.exports), ("opens", &self.opens)] {
+                writeln!(ind, "{:<40} // {}", entries.len(), kind)?;
+                ind.with_indent(|ind| {
+                    for entry in entries {
+                        writeln!(
+                            ind,
+                            "{:<36} // {}",
+                            format!("#{},{:x}", entry.package_idx, entry.flags),
+                            pretty_try!(ind, cp.get_package_name(&entry.package_idx))
+                        )?;
+                        if entry.to_idx.is_empty() {
+                            continue;
+                        }
+                        writeln!(ind, "{:<36} // to", entry.to_idx.len())?;
+                        ind.with_indent(|ind| {
+                            for to in &entry.to_idx {
+                                writeln!(
+                                    ind,
+                                    "{:<32} // \"{}\"",
+                                    format!("#{to}"),
+                                    pretty_try!(ind, cp.get_module_name(to))
+                                )?;
+                            }
+                            Ok(())
+                        })?;
+                    }
+                    Ok(())
+                })?;
+            }
+
+            writeln!(ind, "{:<40} // uses", self.uses.len())?;
+            ind.with_indent(|ind| {
+                for service in &self.uses {
+                    writeln!(
+                        ind,
+                        "{:<36} // {}",
+                        format!("#{service}"),
+                        pretty_try!(ind, cp.get_class_name(service))
+                    )?;
+                }
+                Ok(())
+            })?;
+
+            writeln!(ind, "{:<40} // provides", self.provides.len())?;
+            ind.with_indent(|ind| {
+                for provides in &self.provides {
+                    writeln!(
+                        ind,
+                        "{:<36} // {}",
+                        format!("#{}", provides.provides_idx),
+                        pretty_try!(ind, cp.get_class_name(&provides.provides_idx))
+                    )?;
+                    writeln!(ind, "{:<36} // with", provides.provides_with_idx.len())?;
+                    ind.with_indent(|ind| {
+                        for with in &provides.provides_with_idx {
+                            writeln!(
+                                ind,
+                                "{:<32} // {}",
+                                format!("#{with}"),
+                                pretty_try!(ind, cp.get_class_name(with))
+                            )?;
+                        }
+                        Ok(())
+                    })?;
+                }
+                Ok(())
+            })
+        })
+    }
+}
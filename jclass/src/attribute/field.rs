@@ -15,16 +15,20 @@ impl<'a> FieldAttribute {
         cursor: &mut ByteCursor<'a>,
     ) -> Result<Self, ClassFormatErr> {
         let attribute_name_index = cursor.u16()?;
-        let _attribute_length = cursor.u32()? as usize;
+        let attribute_length = cursor.u32()? as usize;
 
         let attribute_type = AttributeType::try_from(pool.get_utf8(&attribute_name_index)?)?;
         match attribute_type {
             AttributeType::ConstantValue => Ok(FieldAttribute::ConstantValue(cursor.u16()?)),
             AttributeType::RuntimeVisibleAnnotations
+            | AttributeType::RuntimeInvisibleAnnotations
+            | AttributeType::RuntimeVisibleTypeAnnotations
+            | AttributeType::RuntimeInvisibleTypeAnnotations
             | AttributeType::Synthetic
             | AttributeType::Deprecated
             | AttributeType::Signature => Ok(FieldAttribute::Shared(SharedAttribute::read(
                 attribute_type,
+                attribute_length,
                 cursor,
             )?)),
             _ => unimplemented!(),
@@ -1,5 +1,5 @@
 use crate::ClassFormatErr;
-use crate::attribute::AttributeType;
+use crate::attribute::{AttributeType, TypeAnnotation};
 use crate::constant::pool::ConstantPool;
 use common::utils::cursor::ByteCursor;
 use num_enum::TryFromPrimitive;
@@ -11,8 +11,8 @@ pub enum CodeAttributeInfo {
     LocalVariableTable(Vec<LocalVariableEntry>),
     StackMapTable(Vec<StackMapFrame>),
     LocalVariableTypeTable(Vec<LocalVariableTypeEntry>),
-    RuntimeVisibleTypeAnnotations,
-    RuntimeInvisibleTypeAnnotations,
+    RuntimeVisibleTypeAnnotations(Vec<TypeAnnotation>),
+    RuntimeInvisibleTypeAnnotations(Vec<TypeAnnotation>),
 }
 
 /// https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.7.12
@@ -365,6 +365,14 @@ impl<'a> CodeAttributeInfo {
                 }
                 Ok(CodeAttributeInfo::StackMapTable(frames))
             }
+            AttributeType::RuntimeVisibleTypeAnnotations => Ok(
+                CodeAttributeInfo::RuntimeVisibleTypeAnnotations(TypeAnnotation::read_all(cursor)?),
+            ),
+            AttributeType::RuntimeInvisibleTypeAnnotations => {
+                Ok(CodeAttributeInfo::RuntimeInvisibleTypeAnnotations(
+                    TypeAnnotation::read_all(cursor)?,
+                ))
+            }
             other => unimplemented!("{other:?}"),
         }
     }
@@ -443,8 +451,8 @@ impl<'a> CodeAttributeInfo {
                     Ok(())
                 })?;
             }
-            CodeAttributeInfo::RuntimeVisibleTypeAnnotations => unimplemented!(),
-            CodeAttributeInfo::RuntimeInvisibleTypeAnnotations => unimplemented!(),
+            CodeAttributeInfo::RuntimeVisibleTypeAnnotations(_) => unimplemented!(),
+            CodeAttributeInfo::RuntimeInvisibleTypeAnnotations(_) => unimplemented!(),
         }
         Ok(())
     }
@@ -1,6 +1,6 @@
 use crate::ClassFormatErr;
 use crate::attribute::method::code::CodeAttributeInfo;
-use crate::attribute::{Annotation, AttributeType, SharedAttribute};
+use crate::attribute::{Annotation, AttributeType, ElementValue, RawAttribute, SharedAttribute};
 use crate::constant::pool::ConstantPool;
 use common::utils::cursor::ByteCursor;
 
@@ -37,9 +37,11 @@ pub enum MethodAttribute {
     Shared(SharedAttribute),
     Code(CodeAttribute),
     Exceptions(Vec<u16>),
-    RuntimeVisibleParameterAnnotations,
-    RuntimeInvisibleParameterAnnotations,
-    AnnotationsDefault,
+    /// Annotations of each formal parameter.
+    RuntimeVisibleParameterAnnotations(RawAttribute<Vec<Vec<Annotation>>>),
+    RuntimeInvisibleParameterAnnotations(RawAttribute<Vec<Vec<Annotation>>>),
+    /// Default value of an annotation interface element.
+    AnnotationsDefault(RawAttribute<ElementValue>),
     MethodParameters(Vec<MethodParameterEntry>),
 }
 
@@ -58,17 +60,20 @@ impl<'a> MethodAttribute {
         cursor: &mut ByteCursor<'a>,
     ) -> Result<Self, ClassFormatErr> {
         let attribute_name_index = cursor.u16()?;
-        let _attribute_length = cursor.u32()? as usize;
+        let attribute_length = cursor.u32()? as usize;
 
         let attribute_type = AttributeType::try_from(pool.get_utf8(&attribute_name_index)?)?;
         match attribute_type {
             AttributeType::Code => Ok(MethodAttribute::Code(CodeAttribute::read(pool, cursor)?)),
             AttributeType::RuntimeVisibleAnnotations
+            | AttributeType::RuntimeVisibleTypeAnnotations
+            | AttributeType::RuntimeInvisibleTypeAnnotations
             | AttributeType::Synthetic
             | AttributeType::Deprecated
             | AttributeType::RuntimeInvisibleAnnotations
             | AttributeType::Signature => Ok(MethodAttribute::Shared(SharedAttribute::read(
                 attribute_type,
+                attribute_length,
                 cursor,
             )?)),
             AttributeType::MethodParameters => {
@@ -88,19 +93,33 @@ impl<'a> MethodAttribute {
                 Ok(MethodAttribute::Exceptions(exception_index_table))
             }
             AttributeType::RuntimeVisibleParameterAnnotations => {
-                let number_of_parameters = cursor.u8()?;
-                for _ in 0..number_of_parameters {
-                    let num_annotations = cursor.u16()?;
-                    for _ in 0..num_annotations {
-                        let _annotation = Annotation::read(cursor)?;
-                    }
-                }
-                Ok(MethodAttribute::RuntimeVisibleParameterAnnotations)
+                Ok(MethodAttribute::RuntimeVisibleParameterAnnotations(
+                    RawAttribute::read(cursor, attribute_length, Self::read_parameter_annotations)?,
+                ))
             }
+            AttributeType::RuntimeInvisibleParameterAnnotations => {
+                Ok(MethodAttribute::RuntimeInvisibleParameterAnnotations(
+                    RawAttribute::read(cursor, attribute_length, Self::read_parameter_annotations)?,
+                ))
+            }
+            AttributeType::AnnotationDefault => Ok(MethodAttribute::AnnotationsDefault(
+                RawAttribute::read(cursor, attribute_length, ElementValue::read)?,
+            )),
             other => unimplemented!("Method attribute {:?} not implemented", other),
         }
     }
 
+    fn read_parameter_annotations(
+        cursor: &mut ByteCursor<'a>,
+    ) -> Result<Vec<Vec<Annotation>>, ClassFormatErr> {
+        let num_parameters = cursor.u8()?;
+        let mut parameter_annotations = Vec::with_capacity(num_parameters as usize);
+        for _ in 0..num_parameters {
+            parameter_annotations.push(Annotation::read_all(cursor)?);
+        }
+        Ok(parameter_annotations)
+    }
+
     #[cfg(feature = "pretty_print")]
     pub(crate) fn fmt_pretty(
         &self,
@@ -134,9 +153,9 @@ impl<'a> MethodAttribute {
                     Ok(())
                 })?
             }
-            MethodAttribute::RuntimeVisibleParameterAnnotations => unimplemented!(),
-            MethodAttribute::RuntimeInvisibleParameterAnnotations => unimplemented!(),
-            MethodAttribute::AnnotationsDefault => unimplemented!(),
+            MethodAttribute::RuntimeVisibleParameterAnnotations(_) => unimplemented!(),
+            MethodAttribute::RuntimeInvisibleParameterAnnotations(_) => unimplemented!(),
+            MethodAttribute::AnnotationsDefault(_) => unimplemented!(),
             MethodAttribute::MethodParameters(params) => {
                 const W_NAME: usize = 32;
                 writeln!(ind, "MethodParameters:")?;
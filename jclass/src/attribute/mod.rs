@@ -8,15 +8,13 @@ pub mod class;
 pub mod field;
 pub mod method;
 
-// TODO: this block is AI, I want to sleep and don't want to write it. need to rewrite
-///////////////////////////////////////////////////////////////////////////////////////////////////
-///////////////////////////////////////////////////////////////////////////////////////////////////
-///////////////////////////////////////////////////////////////////////////////////////////////////
+/// https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.7.20.1
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TargetInfo {
     TypeParameter {
         type_parameter_index: u8,
     },
+    /// `65535` for the superclass, otherwise an index into `interfaces`.
     Supertype {
         supertype_index: u16,
     },
@@ -46,55 +44,27 @@ pub enum TargetInfo {
     },
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct LocalVarEntry {
-    pub start_pc: u16,
-    pub length: u16,
-    pub index: u16,
-}
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct TypePath {
-    pub path: Vec<TypePathEntry>,
-}
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct TypePathEntry {
-    pub type_path_kind: u8,
-    pub type_argument_index: u8,
-}
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct TypeAnnotation {
-    pub target_info: TargetInfo,
-    pub target_path: TypePath,
-    pub type_index: u16,
-    pub element_value_pairs: Vec<ElementValuePair>,
-}
-
-impl<'a> TypeAnnotation {
-    pub(crate) fn read(cursor: &mut ByteCursor<'a>) -> Result<Self, ClassFormatErr> {
-        let target_type = cursor.u8()?;
-
-        let target_info = match target_type {
-            0x00 => TargetInfo::TypeParameter {
+impl<'a> TargetInfo {
+    fn read(target_type: u8, cursor: &mut ByteCursor<'a>) -> Result<Self, ClassFormatErr> {
+        Ok(match target_type {
+            0x00 | 0x01 => TargetInfo::TypeParameter {
                 type_parameter_index: cursor.u8()?,
             },
-            0x01 => TargetInfo::Supertype {
+            0x10 => TargetInfo::Supertype {
                 supertype_index: cursor.u16()?,
             },
-            0x10 => TargetInfo::TypeParameterBound {
+            0x11 | 0x12 => TargetInfo::TypeParameterBound {
                 type_parameter_index: cursor.u8()?,
                 bound_index: cursor.u8()?,
             },
-            0x11 => TargetInfo::Empty,
-            0x12 => TargetInfo::MethodFormalParameter {
+            0x13..=0x15 => TargetInfo::Empty,
+            0x16 => TargetInfo::MethodFormalParameter {
                 formal_parameter_index: cursor.u8()?,
             },
-            0x13 => TargetInfo::Throws {
+            0x17 => TargetInfo::Throws {
                 throws_type_index: cursor.u16()?,
             },
-            0x14 => {
+            0x40 | 0x41 => {
                 let table_length = cursor.u16()?;
                 let mut localvar_table = Vec::with_capacity(table_length as usize);
                 for _ in 0..table_length {
@@ -106,20 +76,37 @@ impl<'a> TypeAnnotation {
                 }
                 TargetInfo::LocalVar { localvar_table }
             }
-            0x15 => TargetInfo::Catch {
+            0x42 => TargetInfo::Catch {
                 exception_table_index: cursor.u16()?,
             },
-            0x16 => TargetInfo::Offset {
+            0x43..=0x46 => TargetInfo::Offset {
                 offset: cursor.u16()?,
             },
-            0x17 => TargetInfo::TypeArgument {
+            0x47..=0x4B => TargetInfo::TypeArgument {
                 offset: cursor.u16()?,
                 type_argument_index: cursor.u8()?,
             },
-            _ => unimplemented!(),
-        };
+            other => return Err(ClassFormatErr::UnknownTag(other)),
+        })
+    }
+}
 
-        // Read type_path
+/// https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.7.20.1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalVarEntry {
+    pub start_pc: u16,
+    pub length: u16,
+    pub index: u16,
+}
+
+/// https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.7.20.2
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypePath {
+    pub path: Vec<TypePathEntry>,
+}
+
+impl<'a> TypePath {
+    fn read(cursor: &mut ByteCursor<'a>) -> Result<Self, ClassFormatErr> {
         let path_length = cursor.u8()?;
         let mut path = Vec::with_capacity(path_length as usize);
         for _ in 0..path_length {
@@ -128,27 +115,81 @@ impl<'a> TypeAnnotation {
                 type_argument_index: cursor.u8()?,
             });
         }
-        let target_path = TypePath { path };
+        Ok(Self { path })
+    }
+}
 
-        // Read annotation data
-        let type_index = cursor.u16()?;
-        let num_element_value_pairs = cursor.u16()?;
-        let mut element_value_pairs = Vec::with_capacity(num_element_value_pairs as usize);
-        for _ in 0..num_element_value_pairs {
-            element_value_pairs.push(ElementValuePair::read(cursor)?);
-        }
+/// https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.7.20.2
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypePathEntry {
+    pub type_path_kind: u8,
+    pub type_argument_index: u8,
+}
+
+/// https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.7.20
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeAnnotation {
+    /// Kind of the annotated type, several kinds share the shape of their `target_info`.
+    pub target_type: u8,
+    pub target_info: TargetInfo,
+    pub target_path: TypePath,
+    pub type_index: u16,
+    pub element_value_pairs: Vec<ElementValuePair>,
+}
+
+impl<'a> TypeAnnotation {
+    pub(crate) fn read(cursor: &mut ByteCursor<'a>) -> Result<Self, ClassFormatErr> {
+        let target_type = cursor.u8()?;
+        let target_info = TargetInfo::read(target_type, cursor)?;
+        let target_path = TypePath::read(cursor)?;
+        let annotation = Annotation::read(cursor)?;
 
         Ok(Self {
+            target_type,
             target_info,
             target_path,
-            type_index,
-            element_value_pairs,
+            type_index: annotation.type_index,
+            element_value_pairs: annotation.element_value_pairs,
+        })
+    }
+
+    pub(crate) fn read_all(cursor: &mut ByteCursor<'a>) -> Result<Vec<Self>, ClassFormatErr> {
+        let num_annotations = cursor.u16()?;
+        let mut annotations = Vec::with_capacity(num_annotations as usize);
+        for _ in 0..num_annotations {
+            annotations.push(Self::read(cursor)?);
+        }
+        Ok(annotations)
+    }
+}
+
+/// An attribute parsed along with the bytes it was read from. Core reflection parses annotations
+/// on its own, from these bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawAttribute<T> {
+    pub value: T,
+    /// The attribute without its name index and length.
+    pub bytes: Vec<u8>,
+}
+
+impl<'a, T> RawAttribute<T> {
+    pub(crate) fn read(
+        cursor: &mut ByteCursor<'a>,
+        attribute_length: usize,
+        read_value: impl FnOnce(&mut ByteCursor<'a>) -> Result<T, ClassFormatErr>,
+    ) -> Result<Self, ClassFormatErr> {
+        let bytes = cursor.slice(attribute_length)?;
+        let mut attribute_cursor = ByteCursor::new(bytes);
+        let value = read_value(&mut attribute_cursor)?;
+        if !attribute_cursor.is_eof() {
+            return Err(ClassFormatErr::TrailingBytes);
+        }
+        Ok(Self {
+            value,
+            bytes: bytes.to_vec(),
         })
     }
 }
-///////////////////////////////////////////////////////////////////////////////////////////////////
-///////////////////////////////////////////////////////////////////////////////////////////////////
-///////////////////////////////////////////////////////////////////////////////////////////////////
 
 /// https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.7
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -179,6 +220,9 @@ pub enum AttributeType {
     Module,
     ModulePackages,
     ModuleMainClass,
+    ModuleTarget,
+    ModuleHashes,
+    ModuleResolution,
     NestHost,
     NestMembers,
     Record,
@@ -191,15 +235,16 @@ pub enum SharedAttribute {
     Synthetic,
     Deprecated,
     Signature(u16),
-    RuntimeVisibleAnnotations(Vec<Annotation>),
-    RuntimeInvisibleAnnotations(Vec<Annotation>),
-    RuntimeVisibleTypeAnnotations,
-    RuntimeInvisibleTypeAnnotations,
+    RuntimeVisibleAnnotations(RawAttribute<Vec<Annotation>>),
+    RuntimeInvisibleAnnotations(RawAttribute<Vec<Annotation>>),
+    RuntimeVisibleTypeAnnotations(RawAttribute<Vec<TypeAnnotation>>),
+    RuntimeInvisibleTypeAnnotations(RawAttribute<Vec<TypeAnnotation>>),
 }
 
 impl<'a> SharedAttribute {
     pub(crate) fn read(
         attr_type: AttributeType,
+        attribute_length: usize,
         cursor: &mut ByteCursor<'a>,
     ) -> Result<Self, ClassFormatErr> {
         match attr_type {
@@ -210,34 +255,24 @@ impl<'a> SharedAttribute {
                 Ok(SharedAttribute::Signature(signature_index))
             }
             AttributeType::RuntimeVisibleAnnotations => {
-                let num_annotations = cursor.u16()?;
-                let mut annotations = Vec::with_capacity(num_annotations as usize);
-                for _ in 0..num_annotations {
-                    annotations.push(Annotation::read(cursor)?);
-                }
-                Ok(SharedAttribute::RuntimeVisibleAnnotations(annotations))
+                Ok(SharedAttribute::RuntimeVisibleAnnotations(
+                    RawAttribute::read(cursor, attribute_length, Annotation::read_all)?,
+                ))
             }
             AttributeType::RuntimeInvisibleAnnotations => {
-                let num_annotations = cursor.u16()?;
-                let mut annotations = Vec::with_capacity(num_annotations as usize);
-                for _ in 0..num_annotations {
-                    annotations.push(Annotation::read(cursor)?);
-                }
-                Ok(SharedAttribute::RuntimeInvisibleAnnotations(annotations))
-            }
-            AttributeType::RuntimeInvisibleTypeAnnotations => {
-                let num_annotations = cursor.u16()?;
-                for _ in 0..num_annotations {
-                    TypeAnnotation::read(cursor)?;
-                }
-                Ok(SharedAttribute::RuntimeInvisibleTypeAnnotations)
+                Ok(SharedAttribute::RuntimeInvisibleAnnotations(
+                    RawAttribute::read(cursor, attribute_length, Annotation::read_all)?,
+                ))
             }
             AttributeType::RuntimeVisibleTypeAnnotations => {
-                let num_annotations = cursor.u16()?;
-                for _ in 0..num_annotations {
-                    TypeAnnotation::read(cursor)?;
-                }
-                Ok(SharedAttribute::RuntimeVisibleTypeAnnotations)
+                Ok(SharedAttribute::RuntimeVisibleTypeAnnotations(
+                    RawAttribute::read(cursor, attribute_length, TypeAnnotation::read_all)?,
+                ))
+            }
+            AttributeType::RuntimeInvisibleTypeAnnotations => {
+                Ok(SharedAttribute::RuntimeInvisibleTypeAnnotations(
+                    RawAttribute::read(cursor, attribute_length, TypeAnnotation::read_all)?,
+                ))
             }
             _ => Err(ClassFormatErr::AttributeIsNotShared(attr_type.to_string())),
         }
@@ -318,14 +353,14 @@ impl<'a> SharedAttribute {
             )?,
             SharedAttribute::RuntimeVisibleAnnotations(annotations) => {
                 writeln!(ind, "RuntimeVisibleAnnotations:")?;
-                ind.with_indent(|ind| Self::fmt_annotations(ind, cp, annotations))?
+                ind.with_indent(|ind| Self::fmt_annotations(ind, cp, &annotations.value))?
             }
             SharedAttribute::RuntimeInvisibleAnnotations(annotations) => {
                 writeln!(ind, "RuntimeInvisibleAnnotations:")?;
-                ind.with_indent(|ind| Self::fmt_annotations(ind, cp, annotations))?
+                ind.with_indent(|ind| Self::fmt_annotations(ind, cp, &annotations.value))?
             }
-            SharedAttribute::RuntimeVisibleTypeAnnotations => unimplemented!(),
-            SharedAttribute::RuntimeInvisibleTypeAnnotations => unimplemented!(),
+            SharedAttribute::RuntimeVisibleTypeAnnotations(_) => unimplemented!(),
+            SharedAttribute::RuntimeInvisibleTypeAnnotations(_) => unimplemented!(),
         }
 
         Ok(())
@@ -354,6 +389,15 @@ impl<'a> Annotation {
             element_value_pairs,
         })
     }
+
+    pub(crate) fn read_all(cursor: &mut ByteCursor<'a>) -> Result<Vec<Self>, ClassFormatErr> {
+        let num_annotations = cursor.u16()?;
+        let mut annotations = Vec::with_capacity(num_annotations as usize);
+        for _ in 0..num_annotations {
+            annotations.push(Self::read(cursor)?);
+        }
+        Ok(annotations)
+    }
 }
 
 /// https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.7.16
@@ -502,6 +546,10 @@ impl AttributeType {
     const ATTR_MODULE: &'static str = "Module";
     const ATTR_MODULE_PACKAGES: &'static str = "ModulePackages";
     const ATTR_MODULE_MAIN_CLASS: &'static str = "ModuleMainClass";
+    // JDK-specific, written by jlink and the JDK build into module-info classes
+    const ATTR_MODULE_TARGET: &'static str = "ModuleTarget";
+    const ATTR_MODULE_HASHES: &'static str = "ModuleHashes";
+    const ATTR_MODULE_RESOLUTION: &'static str = "ModuleResolution";
     const ATTR_NEST_HOST: &'static str = "NestHost";
     const ATTR_NEST_MEMBERS: &'static str = "NestMembers";
     const ATTR_RECORD: &'static str = "Record";
@@ -540,6 +588,9 @@ impl AttributeType {
             Self::Module => Self::ATTR_MODULE,
             Self::ModulePackages => Self::ATTR_MODULE_PACKAGES,
             Self::ModuleMainClass => Self::ATTR_MODULE_MAIN_CLASS,
+            Self::ModuleTarget => Self::ATTR_MODULE_TARGET,
+            Self::ModuleHashes => Self::ATTR_MODULE_HASHES,
+            Self::ModuleResolution => Self::ATTR_MODULE_RESOLUTION,
             Self::NestHost => Self::ATTR_NEST_HOST,
             Self::NestMembers => Self::ATTR_NEST_MEMBERS,
             Self::Record => Self::ATTR_RECORD,
@@ -583,6 +634,9 @@ impl TryFrom<&str> for AttributeType {
             Self::ATTR_MODULE => Self::Module,
             Self::ATTR_MODULE_PACKAGES => Self::ModulePackages,
             Self::ATTR_MODULE_MAIN_CLASS => Self::ModuleMainClass,
+            Self::ATTR_MODULE_TARGET => Self::ModuleTarget,
+            Self::ATTR_MODULE_HASHES => Self::ModuleHashes,
+            Self::ATTR_MODULE_RESOLUTION => Self::ModuleResolution,
             Self::ATTR_NEST_HOST => Self::NestHost,
             Self::ATTR_NEST_MEMBERS => Self::NestMembers,
             Self::ATTR_RECORD => Self::Record,
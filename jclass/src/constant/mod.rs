@@ -50,6 +50,8 @@ pub enum ConstantInfo {
     InvokeDynamic(DynamicInfo),
     MethodHandle(MethodHandleInfo),
     MethodType(u16),
+    Module(u16),
+    Package(u16),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -181,8 +183,8 @@ impl<'a> ConstantInfo {
             ConstantTag::InvokeDynamic => {
                 Self::InvokeDynamic(DynamicInfo::new(cursor.u16()?, cursor.u16()?))
             }
-            ConstantTag::Module => todo!(),
-            ConstantTag::Package => todo!(),
+            ConstantTag::Module => Self::Module(cursor.u16()?),
+            ConstantTag::Package => Self::Package(cursor.u16()?),
             ConstantTag::MethodHandle => {
                 Self::MethodHandle(MethodHandleInfo::new(cursor.u8()?, cursor.u16()?))
             }
@@ -209,6 +211,8 @@ impl<'a> ConstantInfo {
             ConstantInfo::InvokeDynamic(_) => ConstantTag::InvokeDynamic,
             ConstantInfo::MethodHandle(_) => ConstantTag::MethodHandle,
             ConstantInfo::MethodType(_) => ConstantTag::MethodType,
+            ConstantInfo::Module(_) => ConstantTag::Module,
+            ConstantInfo::Package(_) => ConstantTag::Package,
         }
     }
 
@@ -430,6 +434,13 @@ impl<'a> ConstantInfo {
                 pretty_try!(ind, cp.get_utf8(idx)),
                 op_w = op_w
             ),
+            ConstantInfo::Module(idx) | ConstantInfo::Package(idx) => writeln!(
+                ind,
+                "{:<op_w$} // {}",
+                format!("#{}", idx),
+                pretty_try!(ind, cp.get_utf8(idx)),
+                op_w = op_w
+            ),
             ConstantInfo::MethodHandle(handle_info) => {
                 let handle_kind = pretty_try!(ind, handle_info.get_kind());
                 let method_ref = pretty_try!(ind, cp.get_methodref(&handle_info.reference_index));
@@ -556,6 +567,8 @@ impl<'a> ConstantInfo {
             }
             ConstantInfo::MethodType(idx) => cp.get_utf8(idx)?.to_string(),
             ConstantInfo::Dynamic(_) => "Dynamic (details omitted)".to_owned(),
+            ConstantInfo::Module(idx) => format!("module {}", cp.get_utf8(idx)?),
+            ConstantInfo::Package(idx) => format!("package {}", cp.get_utf8(idx)?),
             e => todo!("Pretty print not implemented for {e:?}"),
         })
     }
@@ -40,6 +40,35 @@ impl ConstantPool {
                 )),
             })
     }
+
+    pub fn get_module_name(&self, idx: &u16) -> Result<&str, ClassFormatErr> {
+        self.inner
+            .get(*idx as usize)
+            .ok_or(ClassFormatErr::ConstantNotFound(*idx))
+            .and_then(|entry| match entry {
+                ConstantInfo::Module(name_index) => self.get_utf8(name_index),
+                e => Err(ClassFormatErr::TypeError(
+                    *idx,
+                    ConstantTag::Module.to_string(),
+                    e.get_tag().to_string(),
+                )),
+            })
+    }
+
+    /// Package names are in internal form, e.g. `java/lang`.
+    pub fn get_package_name(&self, idx: &u16) -> Result<&str, ClassFormatErr> {
+        self.inner
+            .get(*idx as usize)
+            .ok_or(ClassFormatErr::ConstantNotFound(*idx))
+            .and_then(|entry| match entry {
+                ConstantInfo::Package(name_index) => self.get_utf8(name_index),
+                e => Err(ClassFormatErr::TypeError(
+                    *idx,
+                    ConstantTag::Package.to_string(),
+                    e.get_tag().to_string(),
+                )),
+            })
+    }
 }
 
 #[cfg(feature = "pretty_print")]
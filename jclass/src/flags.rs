@@ -24,6 +24,16 @@ pub struct MethodParamFlags(u16);
 #[derive(Debug, Clone, Copy)]
 pub struct FieldFlags(u16);
 
+/// https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.7.25
+/// `module_flags` of the Module attribute
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModuleFlags(u16);
+
+/// https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.7.25
+/// `requires_flags` of the Module attribute
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequiresFlags(u16);
+
 impl ClassFlags {
     pub fn new(value: u16) -> Self {
         Self(value)
@@ -182,6 +192,54 @@ impl MethodFlags {
     }
 }
 
+impl ModuleFlags {
+    pub fn new(value: u16) -> Self {
+        Self(value)
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.0 & 0x0020 != 0
+    }
+
+    pub fn is_synthetic(&self) -> bool {
+        self.0 & 0x1000 != 0
+    }
+
+    pub fn is_mandated(&self) -> bool {
+        self.0 & 0x8000 != 0
+    }
+
+    pub fn get_raw(&self) -> &u16 {
+        &self.0
+    }
+}
+
+impl RequiresFlags {
+    pub fn new(value: u16) -> Self {
+        Self(value)
+    }
+
+    pub fn is_transitive(&self) -> bool {
+        self.0 & 0x0020 != 0
+    }
+
+    pub fn is_static_phase(&self) -> bool {
+        self.0 & 0x0040 != 0
+    }
+
+    pub fn is_synthetic(&self) -> bool {
+        self.0 & 0x1000 != 0
+    }
+
+    pub fn is_mandated(&self) -> bool {
+        self.0 & 0x8000 != 0
+    }
+
+    pub fn get_raw(&self) -> &u16 {
+        &self.0
+    }
+}
+
 impl MethodParamFlags {
     pub fn new(value: u16) -> Self {
         Self(value)
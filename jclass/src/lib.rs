@@ -45,6 +45,10 @@ impl ClassFile {
             .ok_or(ClassFormatErr::WrongMagic(val))
     }
 
+    pub fn get_class_name(&self) -> Result<&str, ClassFormatErr> {
+        self.cp.get_class_name(&self.this_class)
+    }
+
     pub fn get_super_class_name(&self) -> Option<Result<&str, ClassFormatErr>> {
         if self.super_class == 0 {
             None
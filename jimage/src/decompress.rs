@@ -0,0 +1,365 @@
+use common::utils::cursor::{ByteCursor, ByteOrder, CursorError};
+use common::utils::inflate::{InflateError, inflate_zlib};
+use std::fmt::{Display, Formatter};
+
+const COMPRESSED_HEADER_MAGIC: u32 = 0xCAFEFAFA;
+/// magic (4), compressed size (8), uncompressed size (8), decompressor name offset (4),
+/// decompressor config offset (4), is terminal (1)
+const COMPRESSED_HEADER_SIZE: usize = 29;
+
+const CONSTANT_UTF8: u8 = 1;
+const CONSTANT_LONG: u8 = 5;
+const CONSTANT_DOUBLE: u8 = 6;
+/// Utf8 constant replaced by an index into the image strings table.
+const EXTERNALIZED_STRING: u8 = 23;
+/// Descriptor Utf8 constant stored as a skeleton (class names removed after each `L`) plus
+/// package and simple class name indexes into the image strings table.
+const EXTERNALIZED_STRING_DESCRIPTOR: u8 = 25;
+
+#[derive(Debug)]
+pub enum DecompressError {
+    Truncated,
+    UnknownDecompressor(String),
+    Inflate(InflateError),
+    InvalidConstantTag(u8),
+    InvalidStringOffset(u32),
+    SizeMismatch { expected: u64, actual: usize },
+}
+
+impl Display for DecompressError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecompressError::Truncated => write!(f, "truncated compressed resource"),
+            DecompressError::UnknownDecompressor(name) => {
+                write!(f, "unknown decompressor \"{name}\"")
+            }
+            DecompressError::Inflate(e) => write!(f, "zip decompressor: {e}"),
+            DecompressError::InvalidConstantTag(tag) => {
+                write!(
+                    f,
+                    "compact-cp decompressor: invalid constant pool tag {tag}"
+                )
+            }
+            DecompressError::InvalidStringOffset(off) => {
+                write!(f, "string offset {off} is outside of the strings table")
+            }
+            DecompressError::SizeMismatch { expected, actual } => write!(
+                f,
+                "decompressed size {actual} does not match the expected {expected}"
+            ),
+        }
+    }
+}
+
+impl From<CursorError> for DecompressError {
+    fn from(_: CursorError) -> Self {
+        DecompressError::Truncated
+    }
+}
+
+struct CompressedHeader {
+    uncompressed_size: u64,
+    decompressor_name_off: u32,
+}
+
+impl CompressedHeader {
+    fn read(resource: &[u8], order: ByteOrder) -> Result<Option<Self>, DecompressError> {
+        if resource.len() < COMPRESSED_HEADER_SIZE {
+            return Ok(None);
+        }
+        let mut cur = ByteCursor::with_order(resource, order);
+        if cur.u32()? != COMPRESSED_HEADER_MAGIC {
+            return Ok(None);
+        }
+        let _compressed_size = cur.u64()?;
+        let uncompressed_size = cur.u64()?;
+        let decompressor_name_off = cur.u32()?;
+        let _decompressor_config_off = cur.u32()?;
+        let _is_terminal = cur.u8()?;
+        Ok(Some(Self {
+            uncompressed_size,
+            decompressor_name_off,
+        }))
+    }
+}
+
+/// Undoes `jlink --compress`. Every layer is prefixed by a compressed resource header naming its
+/// decompressor, and the output of one layer may itself start with another header when plugins
+/// were stacked, so layers are peeled off until no header is left.
+///
+/// `strings` is the image strings table, which holds the decompressor names and the strings
+/// shared by `compact-cp`.
+pub(crate) fn decompress(
+    mut resource: Vec<u8>,
+    order: ByteOrder,
+    strings: &[u8],
+) -> Result<Vec<u8>, DecompressError> {
+    while let Some(header) = CompressedHeader::read(&resource, order)? {
+        let name = string_at(strings, header.decompressor_name_off)?;
+        let body = &resource[COMPRESSED_HEADER_SIZE..];
+        let out = match name {
+            b"zip" => inflate_zlib(body, header.uncompressed_size as usize)
+                .map_err(DecompressError::Inflate)?,
+            b"compact-cp" => expand_shared_strings(body, strings)?,
+            other => {
+                return Err(DecompressError::UnknownDecompressor(
+                    String::from_utf8_lossy(other).into_owned(),
+                ));
+            }
+        };
+        if out.len() as u64 != header.uncompressed_size {
+            return Err(DecompressError::SizeMismatch {
+                expected: header.uncompressed_size,
+                actual: out.len(),
+            });
+        }
+        resource = out;
+    }
+    Ok(resource)
+}
+
+/// Strings in the table are NUL terminated modified UTF-8, the same encoding as Utf8 constants,
+/// so they are copied into the class file as is.
+fn string_at(strings: &[u8], off: u32) -> Result<&[u8], DecompressError> {
+    let tail = strings
+        .get(off as usize..)
+        .ok_or(DecompressError::InvalidStringOffset(off))?;
+    let end = tail
+        .iter()
+        .position(|&b| b == 0)
+        .ok_or(DecompressError::InvalidStringOffset(off))?;
+    Ok(&tail[..end])
+}
+
+/// Rebuilds the constant pool of a class file whose Utf8 constants were moved to the strings
+/// table by the `compact-cp` (string sharing) plugin. Everything after the constant pool is
+/// copied unchanged.
+fn expand_shared_strings(class: &[u8], strings: &[u8]) -> Result<Vec<u8>, DecompressError> {
+    let mut cur = ByteCursor::new(class);
+    let mut out = Vec::with_capacity(class.len() * 2);
+    out.extend_from_slice(cur.slice(8)?); // magic, minor, major
+    let count = cur.u16()?;
+    out.extend_from_slice(&count.to_be_bytes());
+
+    let mut index = 1;
+    while index < count {
+        let tag = cur.u8()?;
+        match tag {
+            CONSTANT_UTF8 => {
+                let len = cur.u16()?;
+                push_utf8(&mut out, cur.slice(len as usize)?)?;
+            }
+            EXTERNALIZED_STRING => {
+                let off = read_compressed_int(&mut cur)?;
+                push_utf8(&mut out, string_at(strings, off)?)?;
+            }
+            EXTERNALIZED_STRING_DESCRIPTOR => {
+                let descriptor = reconstruct_descriptor(&mut cur, strings)?;
+                push_utf8(&mut out, &descriptor)?;
+            }
+            _ => {
+                let size = constant_size(tag).ok_or(DecompressError::InvalidConstantTag(tag))?;
+                out.push(tag);
+                out.extend_from_slice(cur.slice(size)?);
+            }
+        }
+        // 8-byte constants take two entries
+        index += if matches!(tag, CONSTANT_LONG | CONSTANT_DOUBLE) {
+            2
+        } else {
+            1
+        };
+    }
+
+    out.extend_from_slice(cur.slice(cur.remaining())?);
+    Ok(out)
+}
+
+fn push_utf8(out: &mut Vec<u8>, bytes: &[u8]) -> Result<(), DecompressError> {
+    let len = u16::try_from(bytes.len()).map_err(|_| DecompressError::Truncated)?;
+    out.push(CONSTANT_UTF8);
+    out.extend_from_slice(&len.to_be_bytes());
+    out.extend_from_slice(bytes);
+    Ok(())
+}
+
+/// Every `L` of the skeleton is followed by a package (without the trailing `/`, empty for the
+/// unnamed package) and a simple class name, each given as a strings table offset.
+fn reconstruct_descriptor(
+    cur: &mut ByteCursor,
+    strings: &[u8],
+) -> Result<Vec<u8>, DecompressError> {
+    let skeleton = string_at(strings, read_compressed_int(cur)?)?;
+    let indexes_len = read_compressed_int(cur)?;
+    let mut indexes = ByteCursor::new(cur.slice(indexes_len as usize)?);
+
+    let mut descriptor = Vec::with_capacity(skeleton.len() * 4);
+    for &b in skeleton {
+        descriptor.push(b);
+        if b == b'L' {
+            let package = string_at(strings, read_compressed_int(&mut indexes)?)?;
+            if !package.is_empty() {
+                descriptor.extend_from_slice(package);
+                descriptor.push(b'/');
+            }
+            let class = string_at(strings, read_compressed_int(&mut indexes)?)?;
+            descriptor.extend_from_slice(class);
+        }
+    }
+    Ok(descriptor)
+}
+
+/// Variable length int of `jdk.internal.jimage.decompressor.CompressIndexes`: if the high bit of
+/// the first byte is set, bits 5-6 hold the total length (1-3 bytes) and bits 0-4 the most
+/// significant value bits, otherwise the value is a plain 4-byte big-endian int.
+fn read_compressed_int(cur: &mut ByteCursor) -> Result<u32, DecompressError> {
+    let header = cur.u8()?;
+    let (len, mut value) = if header & 0x80 != 0 {
+        (((header & 0x60) >> 5) as usize, (header & 0x1F) as u32)
+    } else {
+        (4, header as u32)
+    };
+    for _ in 1..len {
+        value = (value << 8) | cur.u8()? as u32;
+    }
+    Ok(value)
+}
+
+/// Size of the constant pool entry after its tag, for tags `compact-cp` leaves untouched.
+fn constant_size(tag: u8) -> Option<usize> {
+    match tag {
+        3 | 4 => Some(4), // Integer, Float
+        CONSTANT_LONG | CONSTANT_DOUBLE => Some(8),
+        7 | 8 | 16 | 19 | 20 => Some(2), // Class, String, MethodType, Module, Package
+        9..=12 | 17 | 18 => Some(4), // Fieldref, Methodref, InterfaceMethodref, NameAndType, Dynamic, InvokeDynamic
+        15 => Some(3),               // MethodHandle
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Offsets: "" 0, "zip" 1, "compact-cp" 5, "java/lang" 16, "Object" 26, "(L;)V" 33
+    const STRINGS: &[u8] = b"\0zip\0compact-cp\0java/lang\0Object\0(L;)V\0";
+    const ZIP: u32 = 1;
+    const COMPACT_CP: u32 = 5;
+
+    fn with_header(decompressor: u32, uncompressed_size: usize, body: &[u8]) -> Vec<u8> {
+        let mut resource = Vec::new();
+        resource.extend_from_slice(&COMPRESSED_HEADER_MAGIC.to_le_bytes());
+        resource.extend_from_slice(&(body.len() as u64).to_le_bytes());
+        resource.extend_from_slice(&(uncompressed_size as u64).to_le_bytes());
+        resource.extend_from_slice(&decompressor.to_le_bytes());
+        resource.extend_from_slice(&0u32.to_le_bytes());
+        resource.push(1);
+        resource.extend_from_slice(body);
+        resource
+    }
+
+    /// zlib stream holding `data` in a single stored block.
+    fn zlib_stored(data: &[u8]) -> Vec<u8> {
+        let len = data.len() as u16;
+        let mut out = vec![0x78, 0x01, 0x01];
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(data);
+        let (mut a, mut b) = (1u32, 0u32);
+        for &byte in data {
+            a = (a + byte as u32) % 65521;
+            b = (b + a) % 65521;
+        }
+        out.extend_from_slice(&((b << 16) | a).to_be_bytes());
+        out
+    }
+
+    fn shared_strings_class() -> Vec<u8> {
+        let mut class = vec![0xCA, 0xFE, 0xBA, 0xBE, 0, 0, 0, 61, 0, 4];
+        class.extend_from_slice(&[EXTERNALIZED_STRING, 0xB0]); // "java/lang"
+        class.extend_from_slice(&[EXTERNALIZED_STRING_DESCRIPTOR, 0xC0, 33, 0xA2, 0xB0, 0xBA]);
+        class.extend_from_slice(&[CONSTANT_UTF8, 0, 4, b'C', b'o', b'd', b'e']);
+        class.extend_from_slice(&[0xAA, 0xBB]);
+        class
+    }
+
+    fn expanded_class() -> Vec<u8> {
+        let mut class = vec![0xCA, 0xFE, 0xBA, 0xBE, 0, 0, 0, 61, 0, 4];
+        class.extend_from_slice(&[CONSTANT_UTF8, 0, 9]);
+        class.extend_from_slice(b"java/lang");
+        class.extend_from_slice(&[CONSTANT_UTF8, 0, 21]);
+        class.extend_from_slice(b"(Ljava/lang/Object;)V");
+        class.extend_from_slice(&[CONSTANT_UTF8, 0, 4, b'C', b'o', b'd', b'e']);
+        class.extend_from_slice(&[0xAA, 0xBB]);
+        class
+    }
+
+    #[test]
+    fn decompress_zip() {
+        // given
+        let resource = with_header(ZIP, 13, &zlib_stored(b"hello, jimage"));
+
+        // when
+        let out = decompress(resource, ByteOrder::LittleEndian, STRINGS).unwrap();
+
+        // then
+        assert_eq!(out, b"hello, jimage");
+    }
+
+    #[test]
+    fn decompress_compact_cp() {
+        // given
+        let expected = expanded_class();
+        let resource = with_header(COMPACT_CP, expected.len(), &shared_strings_class());
+
+        // when
+        let out = decompress(resource, ByteOrder::LittleEndian, STRINGS).unwrap();
+
+        // then
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn decompress_stacked_headers() {
+        // given: compact-cp applied first, then zip
+        let expected = expanded_class();
+        let inner = with_header(COMPACT_CP, expected.len(), &shared_strings_class());
+        let resource = with_header(ZIP, inner.len(), &zlib_stored(&inner));
+
+        // when
+        let out = decompress(resource, ByteOrder::LittleEndian, STRINGS).unwrap();
+
+        // then
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn decompress_unknown_decompressor() {
+        // given: "java/lang" is not a decompressor
+        let resource = with_header(16, 5, b"hello");
+
+        // when
+        let err = decompress(resource, ByteOrder::LittleEndian, STRINGS).unwrap_err();
+
+        // then
+        assert!(matches!(err, DecompressError::UnknownDecompressor(name) if name == "java/lang"));
+    }
+
+    #[test]
+    fn decompress_size_mismatch() {
+        // given
+        let resource = with_header(ZIP, 12, &zlib_stored(b"hello, jimage"));
+
+        // when
+        let err = decompress(resource, ByteOrder::LittleEndian, STRINGS).unwrap_err();
+
+        // then
+        assert!(matches!(
+            err,
+            DecompressError::SizeMismatch {
+                expected: 12,
+                actual: 13
+            }
+        ));
+    }
+}
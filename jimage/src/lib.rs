@@ -1,9 +1,15 @@
 use common::utils::cursor::{ByteCursor, ByteOrder};
 use memmap2::Mmap;
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::fs::File;
 use std::path::Path;
 
+mod decompress;
+
 const HASH_MUL: u32 = 0x01_00_01_93;
+const MODULES_DIR: &str = "/modules";
+const PACKAGES_DIR: &str = "/packages";
 
 #[derive(Debug)]
 pub struct Header {
@@ -28,6 +34,23 @@ struct Entry {
     uncompressed_size: u64,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Resource {
+    pub module: String,
+    /// Path inside the module, e.g. `java/lang/Object.class`.
+    pub path: String,
+    pub size: u64,
+    /// 0 if the resource is stored uncompressed.
+    pub compressed_size: u64,
+}
+
+impl Resource {
+    /// `/<module>/<path>`
+    pub fn full_name(&self) -> String {
+        format!("/{}/{}", self.module, self.path)
+    }
+}
+
 pub struct JImage {
     mmap: Mmap,
     pub header: Header,
@@ -36,18 +59,33 @@ pub struct JImage {
     locations_off: usize,
     strings_off: usize,
     data_base: usize,
+    /// Dotted package name -> name of the module that contains its classes.
+    package_to_module: HashMap<String, String>,
 }
 
 impl JImage {
     pub fn new<P: AsRef<Path>>(p: P) -> Self {
-        let file = File::open(p).unwrap();
-        let mmap = unsafe { Mmap::map(&file).unwrap() };
+        Self::open(p).unwrap()
+    }
+
+    /// Like [`JImage::new`], but reports a missing file or a file that is not a jimage as an
+    /// error instead of panicking.
+    pub fn open<P: AsRef<Path>>(p: P) -> std::io::Result<Self> {
+        let invalid = |msg: String| std::io::Error::new(std::io::ErrorKind::InvalidData, msg);
+        let file = File::open(p)?;
+        let mmap = unsafe { Mmap::map(&file)? };
         let mut cur = ByteCursor::with_order(&mmap, ByteOrder::LittleEndian);
+        let mut next_u32 = || {
+            cur.u32()
+                .map_err(|_| invalid("truncated jimage header".to_string()))
+        };
 
-        let magic = cur.u32().unwrap();
-        assert_eq!(magic, 0xCAFEDADA, "bad magic: 0x{magic:08X}");
+        let magic = next_u32()?;
+        if magic != 0xCAFEDADA {
+            return Err(invalid(format!("bad magic: 0x{magic:08X}")));
+        }
 
-        let ver = cur.u32().unwrap();
+        let ver = next_u32()?;
         let major = (ver >> 16) as u16;
         let minor = (ver & 0xFFFF) as u16;
 
@@ -55,11 +93,11 @@ impl JImage {
             magic,
             major,
             minor,
-            flags: cur.u32().unwrap(),
-            resource_count: cur.u32().unwrap(),
-            table_length: cur.u32().unwrap(),
-            locations_size: cur.u32().unwrap(),
-            strings_size: cur.u32().unwrap(),
+            flags: next_u32()?,
+            resource_count: next_u32()?,
+            table_length: next_u32()?,
+            locations_size: next_u32()?,
+            strings_size: next_u32()?,
         };
 
         let header_size = cur.position();
@@ -68,8 +106,11 @@ impl JImage {
         let locations_off = offsets_off + (header.table_length as usize) * 4;
         let strings_off = locations_off + (header.locations_size as usize);
         let data_base = strings_off + (header.strings_size as usize);
+        if data_base > mmap.len() {
+            return Err(invalid("jimage index is larger than the file".to_string()));
+        }
 
-        Self {
+        let mut jimage = Self {
             mmap,
             header,
             redirect_off,
@@ -77,7 +118,31 @@ impl JImage {
             locations_off,
             strings_off,
             data_base,
+            package_to_module: HashMap::new(),
+        };
+        jimage.package_to_module = jimage.read_package_to_module()?;
+        Ok(jimage)
+    }
+
+    /// Every `/packages/<package>` directory lists `(is_empty, module name offset)` pairs, a package
+    /// split across modules is empty in all but one of them.
+    fn read_package_to_module(&self) -> std::io::Result<HashMap<String, String>> {
+        let mut map = HashMap::new();
+        for package_loc in self.directory_children(PACKAGES_DIR)? {
+            let package = self.decode_location(package_loc);
+            let content = self.content(&package)?;
+            let mut cur = ByteCursor::with_order(&content, ByteOrder::LittleEndian);
+            while let (Ok(is_empty), Ok(module_off)) = (cur.u32(), cur.u32()) {
+                if is_empty == 0 {
+                    map.insert(
+                        self.string_at(package.base_off as usize),
+                        self.string_at(module_off as usize),
+                    );
+                    break;
+                }
+            }
         }
+        Ok(map)
     }
 
     fn hash_seeded(seed: u32, name: &str) -> u32 {
@@ -183,20 +248,134 @@ impl JImage {
         name
     }
 
-    pub fn open_java_base_class(&self, path: &str) -> Option<&[u8]> {
-        let full_name = format!("/java.base/{path}.class");
-        let (_, loc_off) = self.lookup_slot(&full_name)?;
+    fn find_entry(&self, full_name: &str) -> Option<Entry> {
+        let (_, loc_off) = self.lookup_slot(full_name)?;
         let e = self.decode_location(loc_off);
         // verify name, just in case
         if self.make_name(&e) != full_name {
             return None;
         }
+        Some(e)
+    }
 
+    /// Resources stored by `jlink --compress` are decompressed into an owned buffer, others are
+    /// borrowed from the mapped image. A resource that can't be decompressed is `InvalidData`.
+    fn content(&self, e: &Entry) -> std::io::Result<Cow<'_, [u8]>> {
         let start = self.data_base + (e.content_off as usize);
-        if e.compressed_size != 0 {
-            unimplemented!("compressed resources not supported yet");
+        if e.compressed_size == 0 {
+            let end = start + (e.uncompressed_size as usize);
+            return Ok(Cow::Borrowed(&self.mmap[start..end]));
         }
-        let end = start + (e.uncompressed_size as usize);
-        Some(&self.mmap[start..end])
+        let end = start + (e.compressed_size as usize);
+        let strings =
+            &self.mmap[self.strings_off..self.strings_off + self.header.strings_size as usize];
+        let content = decompress::decompress(
+            self.mmap[start..end].to_vec(),
+            ByteOrder::LittleEndian,
+            strings,
+        )
+        .map_err(|err| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("cannot decompress {}: {err}", self.make_name(e)),
+            )
+        })?;
+        Ok(Cow::Owned(content))
+    }
+
+    /// Directory content is the location offsets of its children.
+    fn directory_children(&self, dir: &str) -> std::io::Result<Vec<usize>> {
+        let Some(e) = self.find_entry(dir) else {
+            return Ok(Vec::new());
+        };
+        let content = self.content(&e)?;
+        let mut cur = ByteCursor::with_order(&content, ByteOrder::LittleEndian);
+        Ok(std::iter::from_fn(|| cur.u32().ok())
+            .map(|off| off as usize)
+            .collect())
+    }
+
+    /// Names of all modules in the image, sorted.
+    pub fn modules(&self) -> std::io::Result<Vec<String>> {
+        let mut modules: Vec<_> = self
+            .directory_children(MODULES_DIR)?
+            .into_iter()
+            .map(|loc_off| {
+                let e = self.decode_location(loc_off);
+                self.string_at(e.base_off as usize)
+            })
+            .collect();
+        modules.sort();
+        Ok(modules)
+    }
+
+    /// Dotted package name -> module name, for every non-empty package of the image.
+    pub fn packages(&self) -> &HashMap<String, String> {
+        &self.package_to_module
+    }
+
+    /// Accepts both `java.lang` and `java/lang`.
+    pub fn module_of_package(&self, package: &str) -> Option<&str> {
+        self.package_to_module
+            .get(&package.replace('/', "."))
+            .map(String::as_str)
+    }
+
+    /// All resources sorted by name, excluding the `/modules` and `/packages` directory trees.
+    /// Pass a module name to only list its resources.
+    pub fn resources(&self, module: Option<&str>) -> Vec<Resource> {
+        let mut resources: Vec<_> = (0..self.header.table_length)
+            .map(|slot| self.offset_at(slot) as usize)
+            .filter(|&loc_off| loc_off != 0 && loc_off < self.header.locations_size as usize)
+            .map(|loc_off| self.decode_location(loc_off))
+            .filter(|e| e.module_off != 0)
+            .filter_map(|e| {
+                let entry_module = self.string_at(e.module_off as usize);
+                let is_directory_tree =
+                    entry_module == MODULES_DIR[1..] || entry_module == PACKAGES_DIR[1..];
+                if is_directory_tree || module.is_some_and(|m| m != entry_module) {
+                    return None;
+                }
+                let full_name = self.make_name(&e);
+                Some(Resource {
+                    path: full_name[entry_module.len() + 2..].to_string(),
+                    module: entry_module,
+                    size: e.uncompressed_size,
+                    compressed_size: e.compressed_size,
+                })
+            })
+            .collect();
+        resources.sort_by(|a, b| (&a.module, &a.path).cmp(&(&b.module, &b.path)));
+        resources
+    }
+
+    /// Resource `path` (e.g. `java/util/logging/Logger.class`) of `module`, `None` if there is
+    /// no such resource.
+    pub fn open_resource(
+        &self,
+        module: &str,
+        path: &str,
+    ) -> std::io::Result<Option<Cow<'_, [u8]>>> {
+        match self.find_entry(&format!("/{module}/{path}")) {
+            Some(e) => self.content(&e).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Whether `module` has resource `path`, without reading it.
+    pub fn contains(&self, module: &str, path: &str) -> bool {
+        self.find_entry(&format!("/{module}/{path}")).is_some()
+    }
+
+    /// Finds the class by its binary name (e.g. `java/lang/String`) in whichever module contains
+    /// its package.
+    pub fn open_class(&self, binary_name: &str) -> std::io::Result<Option<Cow<'_, [u8]>>> {
+        let Some(module) = binary_name
+            .rsplit_once('/')
+            .and_then(|(package, _)| self.module_of_package(package))
+        else {
+            return Ok(None);
+        };
+        self.open_resource(module, &format!("{binary_name}.class"))
     }
 }
@@ -0,0 +1,357 @@
+use clap::{Parser, Subcommand};
+use jclass::ClassFile;
+use jimage::{JImage, Resource};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+#[derive(Parser, Debug)]
+#[command(version, about = "Inspects JDK runtime images (lib/modules)")]
+pub struct Args {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Lists the resources of the image with their sizes, or its modules or packages
+    List {
+        image: PathBuf,
+        #[arg(long, conflicts_with = "packages", help = "Only list module names")]
+        modules: bool,
+        #[arg(long, help = "List packages and the module that contains them")]
+        packages: bool,
+        #[arg(
+            short,
+            long,
+            help = "Only list resources of these modules (repeatable)"
+        )]
+        module: Vec<String>,
+    },
+    /// Extracts resources into <dir>/<module>/<path>
+    Extract {
+        image: PathBuf,
+        #[arg(short, long, default_value = ".", help = "Target directory")]
+        dir: PathBuf,
+        #[arg(
+            short,
+            long,
+            help = "Only extract resources of these modules (repeatable)"
+        )]
+        module: Vec<String>,
+        #[arg(
+            short,
+            long,
+            help = "Only extract resources whose path inside the module matches the glob \
+            ('*' and '?' don't cross '/', '**' does), e.g. 'java/util/**/*.class'"
+        )]
+        include: Option<String>,
+    },
+    /// Prints the header of the image
+    Info { image: PathBuf },
+    /// Parses every class of the image and reports the ones that fail
+    Verify {
+        image: PathBuf,
+        #[arg(
+            short,
+            long,
+            help = "Only verify classes of these modules (repeatable)"
+        )]
+        module: Vec<String>,
+    },
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let image_path = match &args.command {
+        Command::List { image, .. }
+        | Command::Extract { image, .. }
+        | Command::Info { image }
+        | Command::Verify { image, .. } => image.clone(),
+    };
+    let image_path = image_path.as_path();
+    let image = match JImage::open(image_path) {
+        Ok(image) => image,
+        Err(e) => {
+            eprintln!("Error: cannot open {}: {e}", image_path.display());
+            return ExitCode::from(2);
+        }
+    };
+
+    let result = match args.command {
+        Command::List {
+            modules,
+            packages,
+            module,
+            ..
+        } => list(&image, image_path, modules, packages, &module),
+        Command::Extract {
+            dir,
+            module,
+            include,
+            ..
+        } => extract(&image, &dir, &module, include.as_deref()),
+        Command::Info { .. } => info(&image, image_path),
+        Command::Verify { module, .. } => {
+            return verify(&image, image_path, &module);
+        }
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn selected_resources(image: &JImage, modules: &[String]) -> Vec<Resource> {
+    if modules.is_empty() {
+        image.resources(None)
+    } else {
+        modules
+            .iter()
+            .flat_map(|module| image.resources(Some(module)))
+            .collect()
+    }
+}
+
+fn list(
+    image: &JImage,
+    image_path: &Path,
+    modules: bool,
+    packages: bool,
+    module: &[String],
+) -> Result<(), String> {
+    if modules {
+        let modules = image
+            .modules()
+            .map_err(|e| format!("cannot list modules: {e}"))?;
+        for module in modules {
+            println!("{module}");
+        }
+        return Ok(());
+    }
+    if packages {
+        let mut packages: Vec<_> = image.packages().iter().collect();
+        packages.sort();
+        for (package, module) in packages {
+            println!("{package} {module}");
+        }
+        return Ok(());
+    }
+
+    println!("jimage: {}", image_path.display());
+    let mut current_module = None;
+    for resource in selected_resources(image, module) {
+        if current_module.as_ref() != Some(&resource.module) {
+            println!();
+            println!("Module: {}", resource.module);
+            current_module = Some(resource.module.clone());
+        }
+        if resource.compressed_size == 0 {
+            println!("    {:>10}  {}", resource.size, resource.path);
+        } else {
+            println!(
+                "    {:>10}  {} (compressed {})",
+                resource.size, resource.path, resource.compressed_size
+            );
+        }
+    }
+    Ok(())
+}
+
+fn extract(
+    image: &JImage,
+    dir: &Path,
+    module: &[String],
+    include: Option<&str>,
+) -> Result<(), String> {
+    let mut count = 0;
+    for resource in selected_resources(image, module) {
+        if include.is_some_and(|glob| !glob_matches(glob.as_bytes(), resource.path.as_bytes())) {
+            continue;
+        }
+        let target = dir.join(&resource.module).join(&resource.path);
+        let content = image
+            .open_resource(&resource.module, &resource.path)
+            .map_err(|e| format!("cannot read {}: {e}", resource.full_name()))?
+            .ok_or_else(|| format!("cannot read {}", resource.full_name()))?;
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("cannot create {}: {e}", parent.display()))?;
+        }
+        std::fs::write(&target, &content)
+            .map_err(|e| format!("cannot write {}: {e}", target.display()))?;
+        count += 1;
+    }
+    println!("Extracted {count} resources to {}", dir.display());
+    Ok(())
+}
+
+fn info(image: &JImage, image_path: &Path) -> Result<(), String> {
+    let modules = image
+        .modules()
+        .map_err(|e| format!("cannot list modules: {e}"))?;
+    let header = &image.header;
+    println!("jimage: {}", image_path.display());
+    println!("  Magic: 0x{:08X}", header.magic);
+    println!("  Version: {}.{}", header.major, header.minor);
+    println!("  Flags: 0x{:08X}", header.flags);
+    println!("  Resource count: {}", header.resource_count);
+    println!("  Table length: {}", header.table_length);
+    println!("  Offsets size: {}", header.table_length * 4);
+    println!("  Redirects size: {}", header.table_length * 4);
+    println!("  Locations size: {}", header.locations_size);
+    println!("  Strings size: {}", header.strings_size);
+    println!("  Modules: {}", modules.len());
+    println!("  Packages: {}", image.packages().len());
+    Ok(())
+}
+
+fn verify(image: &JImage, image_path: &Path, module: &[String]) -> ExitCode {
+    // panics of the class parser are reported as failures below, keep them off stderr
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(|_| {}));
+    let mut checked = 0;
+    let mut failed = 0;
+    for resource in selected_resources(image, module) {
+        if !resource.path.ends_with(".class") {
+            continue;
+        }
+        checked += 1;
+        let content = match image.open_resource(&resource.module, &resource.path) {
+            Ok(Some(content)) => content,
+            Ok(None) => {
+                failed += 1;
+                println!("FAILED {}: cannot read resource", resource.full_name());
+                continue;
+            }
+            Err(e) => {
+                failed += 1;
+                println!("FAILED {}: {e}", resource.full_name());
+                continue;
+            }
+        };
+        let bytes = content.into_owned();
+        // parts of jclass still panic on input they don't support, report those as failures too
+        let error = match std::panic::catch_unwind(move || ClassFile::try_from(bytes)) {
+            Ok(Ok(_)) => None,
+            Ok(Err(e)) => Some(format!("{e:?}")),
+            Err(payload) => Some(panic_message(payload.as_ref())),
+        };
+        if let Some(error) = error {
+            failed += 1;
+            println!("FAILED {}: {error}", resource.full_name());
+        }
+    }
+    std::panic::set_hook(default_hook);
+    println!(
+        "{}: {checked} classes checked, {failed} failed",
+        image_path.display()
+    );
+    if failed == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    let message = payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic");
+    format!("panicked: {message}")
+}
+
+/// `*` and `?` match within a path segment, `**` matches across segments.
+fn glob_matches(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        [b'*', b'*', rest @ ..] => {
+            // `**/` also matches no directory at all
+            rest.strip_prefix(b"/")
+                .is_some_and(|rest| glob_matches(rest, text))
+                || (0..=text.len()).any(|i| glob_matches(rest, &text[i..]))
+        }
+        [b'*', rest @ ..] => (0..=text.len())
+            .take_while(|&i| i == 0 || text[i - 1] != b'/')
+            .any(|i| glob_matches(rest, &text[i..])),
+        [b'?', rest @ ..] => {
+            matches!(text, [c, tail @ ..] if *c != b'/' && glob_matches(rest, tail))
+        }
+        [p, rest @ ..] => matches!(text, [c, tail @ ..] if c == p && glob_matches(rest, tail)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_matches_literal() {
+        assert!(glob_matches(
+            b"java/lang/Object.class",
+            b"java/lang/Object.class"
+        ));
+        assert!(!glob_matches(
+            b"java/lang/Object.class",
+            b"java/lang/Object.clas"
+        ));
+        assert!(!glob_matches(
+            b"java/lang/Object",
+            b"java/lang/Object.class"
+        ));
+    }
+
+    #[test]
+    fn glob_matches_star_stays_in_segment() {
+        assert!(glob_matches(
+            b"java/lang/*.class",
+            b"java/lang/Object.class"
+        ));
+        assert!(glob_matches(b"java/lang/*", b"java/lang/"));
+        assert!(!glob_matches(
+            b"java/lang/*.class",
+            b"java/lang/invoke/MethodHandle.class"
+        ));
+        assert!(!glob_matches(b"*", b"java/lang"));
+    }
+
+    #[test]
+    fn glob_matches_question_mark_is_one_char_of_a_segment() {
+        assert!(glob_matches(
+            b"java/?ang/Object.class",
+            b"java/lang/Object.class"
+        ));
+        assert!(!glob_matches(
+            b"java/??ang/Object.class",
+            b"java/lang/Object.class"
+        ));
+        assert!(!glob_matches(b"java?lang", b"java/lang"));
+    }
+
+    #[test]
+    fn glob_matches_double_star_crosses_segments() {
+        assert!(glob_matches(
+            b"java/**/*.class",
+            b"java/lang/invoke/MethodHandle.class"
+        ));
+        assert!(glob_matches(b"**", b"java/lang/Object.class"));
+        assert!(glob_matches(
+            b"java/lang/**",
+            b"java/lang/ref/Reference.class"
+        ));
+        assert!(!glob_matches(
+            b"java/**/*.class",
+            b"javax/lang/Object.class"
+        ));
+    }
+
+    #[test]
+    fn glob_matches_double_star_slash_matches_no_directory() {
+        assert!(glob_matches(b"java/**/Object.class", b"java/Object.class"));
+        assert!(glob_matches(b"**/module-info.class", b"module-info.class"));
+    }
+}
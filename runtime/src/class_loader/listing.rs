@@ -0,0 +1,252 @@
+use crate::debug_error_log;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::UNIX_EPOCH;
+
+/// Files of classpath directories. With an index file the listings outlive the VM: a directory
+/// whose modification time is the one recorded in the index is not read again. Adding or
+/// removing a file changes the modification time of its directory, editing one doesn't need to.
+#[derive(Debug)]
+pub(super) struct DirectoryListings {
+    index_file: Option<PathBuf>,
+    listings: Mutex<HashMap<PathBuf, Listing>>,
+    changed: AtomicBool,
+}
+
+#[derive(Debug)]
+struct Listing {
+    modified: u128,
+    files: Arc<[String]>,
+}
+
+impl DirectoryListings {
+    /// Starts from the listings of `index_file`, an index that can't be read is rebuilt.
+    pub fn new(index_file: Option<&Path>) -> Self {
+        let listings = index_file
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .map(|content| content.lines().filter_map(Self::parse_line).collect())
+            .unwrap_or_default();
+        Self {
+            index_file: index_file.map(Path::to_path_buf),
+            listings: Mutex::new(listings),
+            changed: AtomicBool::new(false),
+        }
+    }
+
+    /// Names of the regular files of `dir`, none if it is not a directory.
+    pub fn files(&self, dir: &Path) -> Arc<[String]> {
+        let Some(modified) = Self::modified(dir) else {
+            return Arc::from([]);
+        };
+        if self.index_file.is_none() {
+            return Self::read_dir(dir);
+        }
+        let dir = std::path::absolute(dir).unwrap_or_else(|_| dir.to_path_buf());
+        let mut listings = self.listings.lock().unwrap();
+        if let Some(listing) = listings.get(&dir)
+            && listing.modified == modified
+        {
+            return listing.files.clone();
+        }
+        let files = Self::read_dir(&dir);
+        listings.insert(
+            dir,
+            Listing {
+                modified,
+                files: files.clone(),
+            },
+        );
+        self.changed.store(true, Ordering::Relaxed);
+        files
+    }
+
+    /// Writes the index file if a directory was read since it was loaded. The file is replaced
+    /// as a whole, so VMs sharing it never see half of it.
+    pub fn save(&self) {
+        let Some(index_file) = &self.index_file else {
+            return;
+        };
+        if !self.changed.swap(false, Ordering::Relaxed) {
+            return;
+        }
+        let mut content = String::new();
+        for (dir, listing) in self.listings.lock().unwrap().iter() {
+            let Some(dir) = dir.to_str().filter(|dir| !dir.contains(['\t', '\n'])) else {
+                continue;
+            };
+            content.push_str(&format!(
+                "{}\t{}\t{}\n",
+                listing.modified,
+                dir,
+                listing.files.join("/")
+            ));
+        }
+        let tmp = index_file.with_extension(format!("tmp{}", std::process::id()));
+        if let Err(_e) =
+            std::fs::write(&tmp, content).and_then(|_| std::fs::rename(&tmp, index_file))
+        {
+            debug_error_log!("Failed to write classpath index {index_file:?}: {_e}");
+            let _ = std::fs::remove_file(&tmp);
+        }
+    }
+
+    /// `<modified>\t<directory>\t<file>/<file>/...`
+    fn parse_line(line: &str) -> Option<(PathBuf, Listing)> {
+        let mut parts = line.splitn(3, '\t');
+        let modified = parts.next()?.parse().ok()?;
+        let dir = PathBuf::from(parts.next()?);
+        let files = parts
+            .next()?
+            .split('/')
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect();
+        Some((dir, Listing { modified, files }))
+    }
+
+    fn modified(dir: &Path) -> Option<u128> {
+        let metadata = std::fs::metadata(dir).ok().filter(|m| m.is_dir())?;
+        let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+        Some(modified.as_nanos())
+    }
+
+    /// File names that can't be written in the index (with a tab or a newline) are left out,
+    /// no class or resource name has them.
+    fn read_dir(dir: &Path) -> Arc<[String]> {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Arc::from([]);
+        };
+        entries
+            .filter_map(Result::ok)
+            .filter(|entry| std::fs::metadata(entry.path()).is_ok_and(|m| m.is_file()))
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter(|name| !name.contains(['\t', '\n']))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A directory `<temp>/<pid>-<name>/dir` with the files, and the index file next to it.
+    fn dir_with_index(name: &str, files: &[&str]) -> (PathBuf, PathBuf) {
+        let base = std::env::temp_dir().join(format!("{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base);
+        let dir = base.join("dir");
+        std::fs::create_dir_all(&dir).unwrap();
+        for file in files {
+            std::fs::write(dir.join(file), b"").unwrap();
+        }
+        (dir, base.join("index"))
+    }
+
+    fn remove(index: &Path) {
+        std::fs::remove_dir_all(index.parent().unwrap()).unwrap();
+    }
+
+    fn sorted(files: Arc<[String]>) -> Vec<String> {
+        let mut files = files.to_vec();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn up_to_date_directory_is_served_from_the_index() {
+        // given
+        let (dir, index) = dir_with_index("listing_indexed", &["A.class"]);
+        let modified = DirectoryListings::modified(&dir).unwrap();
+        std::fs::write(
+            &index,
+            format!("{modified}\t{}\tB.class/c.txt\n", dir.display()),
+        )
+        .unwrap();
+        let listings = DirectoryListings::new(Some(&index));
+
+        // when
+        let files = listings.files(&dir);
+
+        // then
+        assert_eq!(sorted(files), ["B.class", "c.txt"]);
+        assert!(!listings.changed.load(Ordering::Relaxed));
+        remove(&index);
+    }
+
+    #[test]
+    fn stale_directory_is_read_again() {
+        // given
+        let (dir, index) = dir_with_index("listing_stale", &["A.class"]);
+        let modified = DirectoryListings::modified(&dir).unwrap();
+        std::fs::write(
+            &index,
+            format!("{}\t{}\tB.class\n", modified + 1, dir.display()),
+        )
+        .unwrap();
+        let listings = DirectoryListings::new(Some(&index));
+
+        // when
+        let files = listings.files(&dir);
+
+        // then
+        assert_eq!(sorted(files), ["A.class"]);
+        assert!(listings.changed.load(Ordering::Relaxed));
+        remove(&index);
+    }
+
+    #[test]
+    fn saved_index_is_read_back() {
+        // given
+        let (dir, index) = dir_with_index("listing_saved", &["A.class", "b.txt"]);
+        let listings = DirectoryListings::new(Some(&index));
+        listings.files(&dir);
+
+        // when
+        listings.save();
+
+        // then
+        let content = std::fs::read_to_string(&index).unwrap();
+        let (indexed_dir, listing) = DirectoryListings::parse_line(content.trim_end()).unwrap();
+        assert_eq!(indexed_dir, std::path::absolute(&dir).unwrap());
+        assert_eq!(listing.modified, DirectoryListings::modified(&dir).unwrap());
+        assert_eq!(sorted(listing.files), ["A.class", "b.txt"]);
+        let reloaded = DirectoryListings::new(Some(&index));
+        assert_eq!(sorted(reloaded.files(&dir)), ["A.class", "b.txt"]);
+        assert!(!reloaded.changed.load(Ordering::Relaxed));
+        remove(&index);
+    }
+
+    #[test]
+    fn unchanged_listings_are_not_saved() {
+        // given
+        let (dir, index) = dir_with_index("listing_unchanged", &["A.class"]);
+        let listings = DirectoryListings::new(Some(&index));
+        listings.files(&dir);
+        listings.save();
+        std::fs::remove_file(&index).unwrap();
+
+        // when
+        listings.files(&dir);
+        listings.save();
+
+        // then
+        assert!(!index.exists());
+        remove(&index);
+    }
+
+    #[test]
+    fn malformed_index_lines_are_skipped() {
+        // given
+        let lines = ["", "12", "12\t/dir", "modified\t/dir\tA.class"];
+
+        // when
+        let parsed = lines.map(DirectoryListings::parse_line);
+
+        // then
+        assert!(parsed.iter().all(Option::is_none));
+        let (dir, listing) = DirectoryListings::parse_line("12\t/dir\t").unwrap();
+        assert_eq!(dir, Path::new("/dir"));
+        assert!(listing.files.is_empty());
+    }
+}
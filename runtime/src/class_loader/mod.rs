@@ -1,26 +1,108 @@
 use crate::class_loader::system::SystemClassLoader;
 use crate::error::JvmError;
-use crate::{VmConfig, debug_log};
+use crate::heap::HeapRef;
+use crate::module::ModuleGraph;
+use crate::{VmConfig, build_exception, debug_log};
+use common::utils::zip::{ZipArchive, ZipError};
 use jimage::JImage;
+use std::collections::HashMap;
+use std::io::ErrorKind;
 use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 //use toml::Value;
 //use toml_edit::Document;
 
+mod listing;
+mod resource;
 mod system;
 
 // TODO: It is more like a stub for now, need to respect the doc
 
 #[derive(Debug, Clone)]
-struct ClassSource {
-    jmod_path: PathBuf,
-    entry_name: String,
+enum ResourceSource {
+    Directory {
+        root: PathBuf,
+        entry_name: String,
+    },
+    Archive {
+        archive: Arc<ZipArchive>,
+        entry_name: String,
+    },
 }
 
+impl ResourceSource {
+    fn url(&self) -> String {
+        match self {
+            ResourceSource::Directory { root, entry_name } => {
+                resource::file_url(&root.join(entry_name))
+            }
+            ResourceSource::Archive {
+                archive,
+                entry_name,
+            } => resource::jar_url(archive.path(), entry_name),
+        }
+    }
+
+    /// URL of the classpath entry the resource is in.
+    fn location(&self) -> String {
+        match self {
+            ResourceSource::Directory { root, .. } => resource::file_url(root),
+            ResourceSource::Archive { archive, .. } => resource::file_url(archive.path()),
+        }
+    }
+
+    fn read(&self) -> Result<Vec<u8>, ZipError> {
+        match self {
+            ResourceSource::Directory { root, entry_name } => {
+                std::fs::read(root.join(entry_name)).map_err(ZipError::Io)
+            }
+            ResourceSource::Archive {
+                archive,
+                entry_name,
+            } => archive
+                .read_by_name(entry_name)
+                .unwrap_or_else(|| Err(ZipError::Io(ErrorKind::NotFound.into()))),
+        }
+    }
+}
+
+/// Which builtin loader defines a class found by [`ClassLoader::load`]: classes of the runtime
+/// image belong to the bootstrap loader, classes of the module path and the classpath to the
+/// application class loader.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuiltinLoader {
+    Boot,
+    App,
+}
+
+/// The `jdk.internal.loader.ClassLoaders` platform and application loader objects. Classes
+/// they define are still read by the VM itself, the objects give them a Java identity.
+#[derive(Debug, Clone, Copy)]
+pub struct BuiltinLoaders {
+    pub platform: HeapRef,
+    pub app: HeapRef,
+}
+
+/// A class file found by [`ClassLoader::load`].
+pub struct FoundClass {
+    pub bytes: Vec<u8>,
+    pub loader: BuiltinLoader,
+    /// Where the class file was read from, as `--verbose-class` prints it.
+    pub source: String,
+}
+
+/// What `--verbose-class` prints as the source of a class of a startup archive.
+const ARCHIVE_SOURCE: &str = "startup archive";
+
 /// https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-5.html#jvms-5.3.1
 
 pub struct ClassLoader {
     jimage: JImage,
+    modules: ModuleGraph,
     system: SystemClassLoader,
+    /// Class files of the runtime image kept in a startup archive, read instead of the image
+    /// while the archive is loaded.
+    archived: Mutex<HashMap<String, Vec<u8>>>,
     //fixtures_path: PathBuf,
 }
 
@@ -30,32 +112,76 @@ impl ClassLoader {
         let modules_path = &vm_config.home.join("lib").join("modules");
         debug_log!("Loading JImage from path: {:?}", modules_path);
         let jimage = JImage::new(modules_path);
+        let modules = ModuleGraph::resolve(vm_config, &jimage)?;
         debug_log!(
             "Loading SystemClassLoader from classpath: {:?}",
             vm_config.class_path
         );
-        let system_loader = SystemClassLoader::new(&vm_config.class_path)?;
+        let system_loader = SystemClassLoader::new(
+            &vm_config.class_path,
+            vm_config.feature_version(),
+            vm_config.classpath_index.as_deref(),
+        )?;
 
         //let fixtures_path = PathBuf::from("javap/tests/testdata/fixtures.toml");
 
         Ok(Self {
             jimage,
+            modules,
             system: system_loader,
+            archived: Mutex::new(HashMap::new()),
             //fixtures_path,
         })
     }
 
+    pub fn modules(&self) -> &ModuleGraph {
+        &self.modules
+    }
+
+    /// Writes the classpath index of `--classpath-index` with the directories read so far.
+    pub fn save_classpath_index(&self) {
+        self.system.save_index();
+    }
+
+    /// Replaces the class files of the runtime image by the ones of a startup archive, by
+    /// internal name. An empty map goes back to the image.
+    pub fn set_archived_classes(&self, classes: HashMap<String, Vec<u8>>) {
+        *self.archived.lock().unwrap() = classes;
+    }
+
+    /// A class whose package belongs to a module of the boot layer is only looked up in that
+    /// module, every other class comes from the classpath.
     #[hotpath::measure]
-    pub fn load(&self, name: &str) -> Result<Vec<u8>, JvmError> {
-        if let Some(bytes) = self.jimage.open_java_base_class(name) {
-            debug_log!("Bytecode of \"{name}\" found using JImage.");
-            //self.add_tested_class(name)?;
-            Ok(bytes.to_vec())
-        } else {
-            let bytes = self.system.find_class(name)?;
-            debug_log!("Bytecode of \"{name}\" found using SystemClassLoader.");
-            Ok(bytes)
+    pub fn load(&self, name: &str) -> Result<FoundClass, JvmError> {
+        if let Some(bytes) = self.archived.lock().unwrap().get(name) {
+            debug_log!("Bytecode of \"{name}\" found in the startup archive.");
+            return Ok(FoundClass {
+                bytes: bytes.clone(),
+                loader: BuiltinLoader::Boot,
+                source: ARCHIVE_SOURCE.to_string(),
+            });
         }
+        let module_id = self.modules.module_of_class(name);
+        let Some(module) = self.modules.get(module_id) else {
+            let (bytes, location) = self.system.find_class(name)?;
+            debug_log!("Bytecode of \"{name}\" found using SystemClassLoader.");
+            return Ok(FoundClass {
+                bytes,
+                loader: BuiltinLoader::App,
+                source: location,
+            });
+        };
+
+        let entry_name = format!("{name}.class");
+        let bytes = self
+            .read_module_resource(module, &entry_name)?
+            .ok_or_else(|| build_exception!(ClassNotFoundException, name.replace('/', ".")))?;
+        debug_log!("Bytecode of \"{name}\" found in module {}.", module.name());
+        Ok(FoundClass {
+            bytes,
+            loader: Self::defining_loader(&module.source),
+            source: Self::module_location(module),
+        })
     }
 
     /*
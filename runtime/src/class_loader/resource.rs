@@ -0,0 +1,236 @@
+use crate::build_exception;
+use crate::class_loader::{BuiltinLoader, ClassLoader};
+use crate::error::JvmError;
+use crate::module::finder::ModuleSource;
+use crate::module::{ModuleId, ResolvedModule};
+use common::utils::zip::ZipArchive;
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+impl ClassLoader {
+    /// https://docs.oracle.com/en/java/javase/25/docs/api/java.base/java/lang/ClassLoader.html#getResources(java.lang.String)
+    ///
+    /// URLs of resource `name` in the modules `loader` defines and, for the application loader,
+    /// on the classpath. Like `BuiltinClassLoader`, a resource in a package of a named module is
+    /// only looked up in that module, and only found there if it is a class or the package is
+    /// opened to every module.
+    pub fn find_resources(&self, loader: BuiltinLoader, name: &str) -> Vec<String> {
+        let package = name.rsplit_once('/').map(|(package, _)| package);
+        let package_module = package
+            .map(|package| self.modules.module_of_package(package))
+            .filter(ModuleId::is_named);
+        if let (Some(module), Some(package)) = (package_module, package)
+            && !name.ends_with(".class")
+            && !self.modules.is_open_to_all(module, package)
+        {
+            return Vec::new();
+        }
+
+        let mut urls: Vec<_> = self
+            .modules
+            .iter()
+            .filter(|module| Self::defining_loader(&module.source) == loader)
+            .filter(|module| package_module.is_none_or(|id| id == module.id()))
+            .filter_map(|module| self.module_resource_url(module, name))
+            .collect();
+        if loader == BuiltinLoader::App && package_module.is_none() {
+            urls.extend(self.system.find_resources(name));
+        }
+        urls
+    }
+
+    /// URL of resource `name` of module `module_name`, if `loader` defines that module.
+    pub fn find_module_resource(
+        &self,
+        loader: BuiltinLoader,
+        module_name: &str,
+        name: &str,
+    ) -> Option<String> {
+        let module = self.defined_module(loader, module_name)?;
+        self.module_resource_url(module, name)
+    }
+
+    /// Content of resource `name` of module `module_name`, if `loader` defines that module.
+    pub fn open_module_resource(
+        &self,
+        loader: BuiltinLoader,
+        module_name: &str,
+        name: &str,
+    ) -> Result<Option<Vec<u8>>, JvmError> {
+        match self.defined_module(loader, module_name) {
+            Some(module) => self.read_module_resource(module, name),
+            None => Ok(None),
+        }
+    }
+
+    /// Reads what a `jrt:`, `file:` or `jar:file:` URL points to. A directory reads as the
+    /// sorted names of its entries, one per line, like `FileURLConnection` lists it.
+    pub fn read_url(&self, url: &str) -> Result<Vec<u8>, JvmError> {
+        let not_found = || build_exception!(FileNotFoundException, url.to_string());
+        if let Some(path) = url.strip_prefix("jrt:/") {
+            let path = decode(path);
+            let (module, name) = path.split_once('/').ok_or_else(not_found)?;
+            return self
+                .jimage
+                .open_resource(module, name)
+                .map_err(|e| build_exception!(IOException, e.to_string()))?
+                .map(Cow::into_owned)
+                .ok_or_else(not_found);
+        }
+        if let Some(spec) = url.strip_prefix("jar:file:") {
+            let (archive_path, entry_name) = spec.split_once("!/").ok_or_else(not_found)?;
+            let archive = self.archive(&PathBuf::from(decode(archive_path)))?;
+            return archive
+                .read_by_name(&decode(entry_name))
+                .ok_or_else(not_found)?
+                .map_err(|e| build_exception!(IOException, e.to_string()));
+        }
+        if let Some(path) = url.strip_prefix("file:") {
+            let path = PathBuf::from(decode(path));
+            if path.is_dir() {
+                let mut names: Vec<_> = std::fs::read_dir(&path)
+                    .map_err(|_| not_found())?
+                    .filter_map(Result::ok)
+                    .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                    .collect();
+                names.sort();
+                return Ok(names
+                    .iter()
+                    .flat_map(|n| [n.as_bytes(), b"\n"].concat())
+                    .collect());
+            }
+            return std::fs::read(&path).map_err(|_| not_found());
+        }
+        Err(not_found())
+    }
+
+    pub(super) fn defining_loader(source: &ModuleSource) -> BuiltinLoader {
+        match source {
+            ModuleSource::System => BuiltinLoader::Boot,
+            ModuleSource::Exploded(_) | ModuleSource::Archive(_) => BuiltinLoader::App,
+        }
+    }
+
+    /// `jrt:/<module>` for the modules of the runtime image, the URL of the directory or JAR
+    /// of the module path otherwise.
+    pub(super) fn module_location(module: &ResolvedModule) -> String {
+        match &module.source {
+            ModuleSource::System => format!("jrt:/{}", module.name()),
+            ModuleSource::Exploded(root) => file_url(root),
+            ModuleSource::Archive(archive) => file_url(archive.path()),
+        }
+    }
+
+    fn defined_module(&self, loader: BuiltinLoader, module_name: &str) -> Option<&ResolvedModule> {
+        self.modules
+            .by_name(module_name)
+            .and_then(|id| self.modules.get(id))
+            .filter(|module| Self::defining_loader(&module.source) == loader)
+    }
+
+    fn module_resource_url(&self, module: &ResolvedModule, name: &str) -> Option<String> {
+        match &module.source {
+            ModuleSource::System => self
+                .jimage
+                .contains(module.name(), name)
+                .then(|| jrt_url(module.name(), name)),
+            ModuleSource::Exploded(root) => {
+                let path = root.join(name);
+                path.is_file().then(|| file_url(&path))
+            }
+            ModuleSource::Archive(archive) => {
+                archive.by_name(name).map(|_| jar_url(archive.path(), name))
+            }
+        }
+    }
+
+    pub(super) fn read_module_resource(
+        &self,
+        module: &ResolvedModule,
+        name: &str,
+    ) -> Result<Option<Vec<u8>>, JvmError> {
+        Ok(match &module.source {
+            ModuleSource::System => self
+                .jimage
+                .open_resource(module.name(), name)
+                .map_err(|e| build_exception!(IOException, e.to_string()))?
+                .map(Cow::into_owned),
+            ModuleSource::Exploded(root) => std::fs::read(root.join(name)).ok(),
+            ModuleSource::Archive(archive) => archive.read_by_name(name).and_then(Result::ok),
+        })
+    }
+
+    /// Archives of the classpath and the module path are already open, any other JAR a
+    /// `jar:` URL names is opened for the read.
+    fn archive(&self, path: &Path) -> Result<Arc<ZipArchive>, JvmError> {
+        let module_archive = self.modules.iter().find_map(|module| match &module.source {
+            ModuleSource::Archive(archive)
+                if std::path::absolute(archive.path()).is_ok_and(|p| p == path) =>
+            {
+                Some(archive)
+            }
+            _ => None,
+        });
+        if let Some(archive) = self.system.archive(path).or(module_archive) {
+            return Ok(archive.clone());
+        }
+        ZipArchive::open(path)
+            .map(Arc::new)
+            .map_err(|e| build_exception!(IOException, format!("{}: {e}", path.display())))
+    }
+}
+
+fn jrt_url(module: &str, name: &str) -> String {
+    format!("jrt:/{module}/{}", encode(name))
+}
+
+/// Directories end with `/`, as `ParseUtil.fileToEncodedURL` makes them.
+pub(super) fn file_url(path: &Path) -> String {
+    let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut url = format!("file:{}", encode(&path.to_string_lossy()));
+    if path.is_dir() && !url.ends_with('/') {
+        url.push('/');
+    }
+    url
+}
+
+pub(super) fn jar_url(archive: &Path, entry_name: &str) -> String {
+    format!("jar:{}!/{}", file_url(archive), encode(entry_name))
+}
+
+/// Percent-encodes every byte that may not appear in the path of a URL.
+fn encode(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for b in path.bytes() {
+        if b.is_ascii_alphanumeric() || b"/-_.!~*'()$&+,;=:@".contains(&b) {
+            encoded.push(b as char);
+        } else {
+            encoded.push_str(&format!("%{b:02X}"));
+        }
+    }
+    encoded
+}
+
+fn decode(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| path.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(b) => {
+                decoded.push(b);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
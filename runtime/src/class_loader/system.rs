@@ -1,78 +1,241 @@
-use crate::class_loader::ClassSource;
+use crate::class_loader::ResourceSource;
+use crate::class_loader::listing::DirectoryListings;
 use crate::error::JvmError;
-use crate::{build_exception, debug_log};
+use crate::{build_exception, debug_error_log, debug_log};
+use common::utils::manifest::{Manifest, versioned_entry};
+use common::utils::zip::ZipArchive;
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::Read;
 use std::path::{Path, PathBuf};
-use walkdir::WalkDir;
+use std::sync::{Arc, RwLock};
+use tracing_log::log::warn;
+
+/// Resource name -> where it is found, in classpath order. Classes are indexed by their entry
+/// name, e.g. `com/example/Main.class`.
+type PackageIndex = HashMap<String, Vec<ResourceSource>>;
 
 #[derive(Debug)]
 pub(super) struct SystemClassLoader {
-    index: HashMap<String, ClassSource>,
+    entries: Vec<ClassPathEntry>,
+    /// Resources of each package looked up so far. A package is indexed the first time
+    /// something in it is looked up, by listing its directory in every classpath entry.
+    packages: RwLock<HashMap<String, Arc<PackageIndex>>>,
+    listings: DirectoryListings,
+}
+
+#[derive(Debug)]
+enum ClassPathEntry {
+    Directory(PathBuf),
+    /// The archive's resources by package, as resource name and entry name.
+    Archive {
+        archive: Arc<ZipArchive>,
+        packages: HashMap<String, Vec<(String, String)>>,
+    },
+}
+
+impl ClassPathEntry {
+    fn path(&self) -> &Path {
+        match self {
+            ClassPathEntry::Directory(root) => root,
+            ClassPathEntry::Archive { archive, .. } => archive.path(),
+        }
+    }
 }
 
 impl SystemClassLoader {
-    pub fn new(path: &Vec<String>) -> Result<Self, JvmError> {
+    pub fn new(
+        path: &[String],
+        feature_version: u32,
+        index_file: Option<&Path>,
+    ) -> Result<Self, JvmError> {
         debug_log!("Creating SystemClassLoader from classpath entries: {path:?}");
-        let mut index = HashMap::new();
-
-        for entry in path {
-            let files_and_folders = WalkDir::new(entry);
-            let files_and_folders = files_and_folders.into_iter().collect::<Vec<_>>();
-            let java_classes: Vec<_> = files_and_folders
-                .into_iter()
-                .filter_map(Result::ok)
-                .map(|e| e.into_path())
-                .filter(|path| {
-                    path.is_file() && path.extension().map(|ext| ext == "class").unwrap_or(false)
-                })
-                .collect();
-            for class in java_classes {
-                let rel = class.strip_prefix(entry).unwrap_or(&class);
-                let rel_str = Self::path_to_forward_slash(rel);
-                if let Some(key) = Self::binary_name_from_rel(&rel_str) {
-                    index.entry(key).or_insert_with(|| ClassSource {
-                        jmod_path: PathBuf::from(entry),
-                        entry_name: rel.to_string_lossy().into_owned(),
-                    });
+        // earlier classpath entries come first, so `find_class` takes the first source
+        let entries = path
+            .iter()
+            .filter_map(|entry| {
+                if Self::is_archive(Path::new(entry)) {
+                    Self::index_archive(entry, feature_version)
+                } else {
+                    Some(ClassPathEntry::Directory(PathBuf::from(entry)))
+                }
+            })
+            .collect();
+        Ok(Self {
+            entries,
+            packages: RwLock::new(HashMap::new()),
+            listings: DirectoryListings::new(index_file),
+        })
+    }
+
+    fn is_archive(path: &Path) -> bool {
+        path.is_file()
+            && path
+                .extension()
+                .map(|ext| ext.eq_ignore_ascii_case("jar") || ext.eq_ignore_ascii_case("zip"))
+                .unwrap_or(false)
+    }
+
+    /// Like the reference implementation, an archive that can't be opened is skipped
+    /// rather than failing the whole classpath.
+    ///
+    /// In a multi-release archive the entry from the highest `META-INF/versions/N/` with
+    /// N not above the running feature version replaces the base entry.
+    fn index_archive(entry: &str, feature_version: u32) -> Option<ClassPathEntry> {
+        let archive = match ZipArchive::open(entry) {
+            Ok(archive) => Arc::new(archive),
+            Err(_e) => {
+                debug_error_log!("Skipping classpath entry \"{entry}\": {_e}");
+                return None;
+            }
+        };
+        let multi_release = match Manifest::read_from(&archive) {
+            Ok(manifest) => manifest.is_some_and(|m| m.is_multi_release()),
+            Err(_e) => {
+                debug_error_log!("Ignoring unreadable manifest of \"{entry}\": {_e}");
+                false
+            }
+        };
+
+        // resource name -> (version, entry name), version 0 being the base entry
+        let mut resources: HashMap<&str, (u32, &str)> = HashMap::new();
+        for zip_entry in archive.entries() {
+            if zip_entry.is_dir() {
+                continue;
+            }
+            let (version, name) = match versioned_entry(&zip_entry.name) {
+                Some((version, name)) if multi_release && version <= feature_version => {
+                    (version, name)
                 }
+                // entries for later releases are not visible at all
+                Some(_) if multi_release => continue,
+                _ => (0, zip_entry.name.as_str()),
+            };
+            let best = resources.entry(name).or_insert((version, &zip_entry.name));
+            if version > best.0 {
+                *best = (version, &zip_entry.name);
             }
         }
 
-        debug_log!(
-            "System classpath index prepared. Found {} classes.",
-            index.len()
-        );
-        Ok(Self { index })
+        let mut packages: HashMap<String, Vec<(String, String)>> = HashMap::new();
+        for (name, (_, entry_name)) in resources {
+            packages
+                .entry(package_of(name).to_string())
+                .or_default()
+                .push((name.to_string(), entry_name.to_string()));
+        }
+        Some(ClassPathEntry::Archive { archive, packages })
+    }
+
+    /// Resources of `package` in all classpath entries. Classes of one package in more than
+    /// one entry are reported, the first entry having a class still wins.
+    fn package(&self, package: &str) -> Arc<PackageIndex> {
+        if let Some(index) = self.packages.read().unwrap().get(package) {
+            return index.clone();
+        }
+        let (index, class_entries) = self.index_package(package);
+        if class_entries.len() > 1 {
+            warn!(
+                "Package {} is split between classpath entries {}, classes are taken from the first entry that has them",
+                package.replace('/', "."),
+                class_entries.join(", ")
+            );
+        }
+        self.packages
+            .write()
+            .unwrap()
+            .entry(package.to_string())
+            .or_insert_with(|| Arc::new(index))
+            .clone()
+    }
+
+    /// Lists `package` in every classpath entry, with the entries that have classes of it.
+    fn index_package(&self, package: &str) -> (PackageIndex, Vec<String>) {
+        let mut index = PackageIndex::new();
+        // a name reaching outside of the classpath directories is in no package
+        let valid = package.is_empty()
+            || package
+                .split('/')
+                .all(|part| !part.is_empty() && part != "." && part != "..");
+        let mut class_entries = Vec::new();
+        for entry in self.entries.iter().filter(|_| valid) {
+            let mut has_classes = false;
+            match entry {
+                ClassPathEntry::Directory(root) => {
+                    for file in self.listings.files(&root.join(package)).iter() {
+                        has_classes |= file.ends_with(".class");
+                        let name = match package {
+                            "" => file.clone(),
+                            _ => format!("{package}/{file}"),
+                        };
+                        index
+                            .entry(name.clone())
+                            .or_default()
+                            .push(ResourceSource::Directory {
+                                root: root.clone(),
+                                entry_name: name,
+                            });
+                    }
+                }
+                ClassPathEntry::Archive { archive, packages } => {
+                    for (name, entry_name) in packages.get(package).into_iter().flatten() {
+                        has_classes |= name.ends_with(".class");
+                        index
+                            .entry(name.clone())
+                            .or_default()
+                            .push(ResourceSource::Archive {
+                                archive: archive.clone(),
+                                entry_name: entry_name.clone(),
+                            });
+                    }
+                }
+            }
+            if has_classes {
+                class_entries.push(entry.path().display().to_string());
+            }
+        }
+        (index, class_entries)
     }
 
     #[hotpath::measure]
-    pub(crate) fn find_class(&self, name: &str) -> Result<Vec<u8>, JvmError> {
-        let key = Self::normalize_key(name);
-        let src = self
-            .index
-            .get(&key)
-            .ok_or_else(|| build_exception!(ClassNotFoundException, name.replace('/', ".")))?;
+    /// The class file and the URL of the classpath entry it was found in.
+    pub(crate) fn find_class(&self, name: &str) -> Result<(Vec<u8>, String), JvmError> {
+        let not_found = || build_exception!(ClassNotFoundException, name.replace('/', "."));
+        let resource = format!("{}.class", Self::normalize_key(name));
+        let index = self.package(package_of(&resource));
+        let src = index
+            .get(&resource)
+            .and_then(|sources| sources.first())
+            .ok_or_else(not_found)?;
+
+        let bytes = src.read().map_err(|_e| {
+            debug_error_log!("Failed to read {}: {_e}", src.url());
+            not_found()
+        })?;
+        Ok((bytes, src.location()))
+    }
 
-        let abs_path = src.jmod_path.join(&src.entry_name);
-        let mut file = File::open(&abs_path)
-            .map_err(|_| build_exception!(ClassNotFoundException, name.replace('/', ".")))?;
-        let mut buf = Vec::new();
-        file.read_to_end(&mut buf)
-            .map_err(|_| build_exception!(ClassNotFoundException, name.replace('/', ".")))?;
-        Ok(buf)
+    /// URLs of every classpath entry that has resource `name`.
+    pub(crate) fn find_resources(&self, name: &str) -> Vec<String> {
+        self.package(package_of(name))
+            .get(name)
+            .map(|sources| sources.iter().map(ResourceSource::url).collect())
+            .unwrap_or_default()
     }
 
-    fn path_to_forward_slash(p: &Path) -> String {
-        p.components()
-            .map(|c| c.as_os_str().to_string_lossy())
-            .collect::<Vec<_>>()
-            .join("/")
+    /// An archive of the classpath, so that `jar:` URLs pointing into it don't reopen it.
+    pub(crate) fn archive(&self, path: &Path) -> Option<&Arc<ZipArchive>> {
+        self.entries.iter().find_map(|entry| match entry {
+            ClassPathEntry::Archive { archive, .. }
+                if std::path::absolute(archive.path()).is_ok_and(|p| p == path) =>
+            {
+                Some(archive)
+            }
+            _ => None,
+        })
     }
 
-    fn binary_name_from_rel(rel: &str) -> Option<String> {
-        rel.strip_suffix(".class").map(|s| s.to_string())
+    /// Writes the listings of the classpath directories to the index file, if there is one.
+    pub(crate) fn save_index(&self) {
+        self.listings.save();
     }
 
     fn normalize_key(name: &str) -> String {
@@ -84,3 +247,136 @@ impl SystemClassLoader {
         }
     }
 }
+
+/// Directory part of a resource name, empty for the unnamed package.
+fn package_of(name: &str) -> &str {
+    name.rsplit_once('/').map_or("", |(package, _)| package)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Classpath directories `<temp>/<pid>-<name>/<i>` with the given files, relative paths and
+    /// contents, one slice per directory.
+    fn classpath(name: &str, dirs: &[&[(&str, &[u8])]]) -> Vec<String> {
+        let base = std::env::temp_dir().join(format!("{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base);
+        dirs.iter()
+            .enumerate()
+            .map(|(i, files)| {
+                let root = base.join(i.to_string());
+                for (path, content) in *files {
+                    let path = root.join(path);
+                    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+                    std::fs::write(path, content).unwrap();
+                }
+                std::fs::create_dir_all(&root).unwrap();
+                root.display().to_string()
+            })
+            .collect()
+    }
+
+    fn remove(path: &[String]) {
+        let base = Path::new(&path[0]).parent().unwrap();
+        std::fs::remove_dir_all(base).unwrap();
+    }
+
+    #[test]
+    fn first_entry_with_the_class_wins() {
+        // given
+        let path = classpath(
+            "first_wins",
+            &[
+                &[("p/Other.class", b"other")],
+                &[("p/A.class", b"second")],
+                &[("p/A.class", b"third")],
+            ],
+        );
+        let loader = SystemClassLoader::new(&path, 25, None).unwrap();
+
+        // when
+        let (bytes, location) = loader.find_class("p.A").unwrap();
+        let urls = loader.find_resources("p/A.class");
+
+        // then
+        assert_eq!(bytes, b"second");
+        assert!(location.ends_with(&format!("{}/", path[1])));
+        assert_eq!(urls.len(), 2);
+        assert!(urls[0].contains(&format!("{}/p/A.class", path[1])));
+        assert!(urls[1].contains(&format!("{}/p/A.class", path[2])));
+        remove(&path);
+    }
+
+    #[test]
+    fn missing_class_is_not_found() {
+        // given
+        let path = classpath("missing", &[&[("p/A.class", b"a")]]);
+        let loader = SystemClassLoader::new(&path, 25, None).unwrap();
+
+        // when
+        let result = loader.find_class("p/Missing");
+
+        // then
+        assert!(result.is_err());
+        remove(&path);
+    }
+
+    #[test]
+    fn package_with_classes_in_two_entries_is_split() {
+        // given
+        let path = classpath(
+            "split",
+            &[
+                &[("p/A.class", b"a")],
+                &[("p/readme.txt", b"resource only")],
+                &[("p/B.class", b"b")],
+            ],
+        );
+        let loader = SystemClassLoader::new(&path, 25, None).unwrap();
+
+        // when
+        let (index, class_entries) = loader.index_package("p");
+
+        // then
+        assert_eq!(class_entries, [path[0].clone(), path[2].clone()]);
+        assert!(index.contains_key("p/A.class"));
+        assert!(index.contains_key("p/B.class"));
+        assert!(index.contains_key("p/readme.txt"));
+        remove(&path);
+    }
+
+    #[test]
+    fn package_with_resources_besides_its_classes_is_not_split() {
+        // given
+        let path = classpath(
+            "not_split",
+            &[
+                &[("p/A.class", b"a")],
+                &[("p/readme.txt", b"resource only")],
+            ],
+        );
+        let loader = SystemClassLoader::new(&path, 25, None).unwrap();
+
+        // when
+        let (_, class_entries) = loader.index_package("p");
+
+        // then
+        assert_eq!(class_entries, [path[0].clone()]);
+        remove(&path);
+    }
+
+    #[test]
+    fn names_leaving_the_classpath_are_in_no_package() {
+        // given
+        let path = classpath("outside", &[&[("p/A.class", b"a")]]);
+        let loader = SystemClassLoader::new(&path, 25, None).unwrap();
+
+        // when
+        let (index, _) = loader.index_package("p/..");
+
+        // then
+        assert!(index.is_empty());
+        remove(&path);
+    }
+}
@@ -155,11 +155,26 @@ pub enum JavaExceptionKind {
     ArrayStoreException,
     InternalError,
     NoSuchMethodError,
+    NoSuchFieldError,
     ClassNotFoundException,
     UnsatisfiedLinkError,
     IncompatibleClassChangeError,
     ClassFormatError,
+    VerifyError,
+    ClassCircularityError,
+    NoClassDefFoundError,
+    IllegalAccessError,
+    LinkageError,
+    IllegalMonitorStateException,
+    IllegalArgumentException,
+    IllegalAccessException,
+    InstantiationException,
+    OutOfMemoryError,
+    StackOverflowError,
     IOException,
+    FileNotFoundException,
+    FindException,
+    ResolutionException,
 }
 
 impl JavaExceptionKind {
@@ -173,11 +188,26 @@ impl JavaExceptionKind {
             Self::ArrayStoreException => "java/lang/ArrayStoreException",
             Self::InternalError => "java/lang/InternalError",
             Self::NoSuchMethodError => "java/lang/NoSuchMethodError",
+            Self::NoSuchFieldError => "java/lang/NoSuchFieldError",
             Self::ClassNotFoundException => "java/lang/ClassNotFoundException",
             Self::UnsatisfiedLinkError => "java/lang/UnsatisfiedLinkError",
             Self::IncompatibleClassChangeError => "java/lang/IncompatibleClassChangeError",
             Self::ClassFormatError => "java/lang/ClassFormatError",
+            Self::VerifyError => "java/lang/VerifyError",
+            Self::ClassCircularityError => "java/lang/ClassCircularityError",
+            Self::NoClassDefFoundError => "java/lang/NoClassDefFoundError",
+            Self::IllegalAccessError => "java/lang/IllegalAccessError",
+            Self::LinkageError => "java/lang/LinkageError",
+            Self::IllegalMonitorStateException => "java/lang/IllegalMonitorStateException",
+            Self::IllegalArgumentException => "java/lang/IllegalArgumentException",
+            Self::IllegalAccessException => "java/lang/IllegalAccessException",
+            Self::InstantiationException => "java/lang/InstantiationException",
+            Self::OutOfMemoryError => "java/lang/OutOfMemoryError",
+            Self::StackOverflowError => "java/lang/StackOverflowError",
             Self::IOException => "java/io/IOException",
+            Self::FileNotFoundException => "java/io/FileNotFoundException",
+            Self::FindException => "java/lang/module/FindException",
+            Self::ResolutionException => "java/lang/module/ResolutionException",
         }
     }
 
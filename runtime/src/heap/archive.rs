@@ -0,0 +1,444 @@
+use crate::class_loader::BuiltinLoaders;
+use crate::error::JvmError;
+use crate::heap::HeapRef;
+use crate::keys::{ClassId, FieldKey, FullyQualifiedMethodKey, ThreadId};
+use crate::rt::ClassState;
+use crate::thread::JavaThreadState;
+use crate::vm::Value;
+use crate::vm::signal;
+use crate::vm::stack::FrameStack;
+use crate::{VirtualMachine, VmConfig, debug_log};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+const MAGIC: &[u8; 8] = b"JVMSTART";
+const FORMAT_VERSION: u32 = 2;
+
+/// State of a VM at the end of startup: the bootstrap loader classes in the order they were
+/// loaded with their class files, states and static fields, the heap and whatever the VM keeps
+/// outside of it. The class files are parsed and linked again, but without looking them up and
+/// decompressing them in the runtime image; what the archive mostly saves is running the JDK
+/// initialization. Class and method ids are handed out in loading order, so loading the same
+/// classes again gives them the ids the objects of the heap refer to.
+pub struct StartupArchive {
+    classes: Vec<ArchivedClass>,
+    heap: Vec<u8>,
+    string_pool: Vec<(String, HeapRef)>,
+    thread_obj: HeapRef,
+    thread_name: HeapRef,
+    thread_group: HeapRef,
+    builtin_loaders: Option<(HeapRef, HeapRef)>,
+    java_handled_signals: u64,
+}
+
+struct ArchivedClass {
+    name: String,
+    /// `None` for array classes, which are created again rather than read.
+    class_file: Option<Vec<u8>>,
+    state: ClassState,
+    mirror: Option<HeapRef>,
+    /// Name, descriptor and value of each static field.
+    static_fields: Vec<(String, String, Value)>,
+}
+
+/// Writes the state of `vm`, booted but not running any application code yet, to `path`.
+pub fn dump(vm: &VirtualMachine, thread: &JavaThreadState, path: &Path) -> Result<(), String> {
+    if vm.threads.all().len() != 1 {
+        return Err("other Java threads are alive".to_string());
+    }
+    let ma = vm.method_area();
+    let interner = vm.interner();
+    let mut classes = Vec::new();
+    for (index, class) in ma.classes().iter() {
+        let class_id = ClassId::from_usize(index + 1);
+        let name = interner.resolve(&class.get_name());
+        if index != classes.len()
+            || ma.get_class_loader(class_id).is_some()
+            || ma.find_loaded_class(None, class.get_name()) != Some(class_id)
+        {
+            return Err(format!(
+                "class {} is not a class of the bootstrap loader",
+                name.replace('/', ".")
+            ));
+        }
+        let class_file = match class.as_class_like() {
+            Ok(_) => Some(
+                ma.class_loader()
+                    .load(name)
+                    .map_err(|e| e.into_pretty_string(interner))?
+                    .bytes,
+            ),
+            Err(_) => None,
+        };
+        let (state, static_fields) = match class.as_class_like() {
+            Ok(class) => (
+                class.state(),
+                class
+                    .static_field_entries()
+                    .into_iter()
+                    .map(|(key, value)| {
+                        (
+                            interner.resolve(&key.name).to_string(),
+                            interner.resolve(&key.desc).to_string(),
+                            value,
+                        )
+                    })
+                    .collect(),
+            ),
+            Err(_) => (ClassState::Linked, Vec::new()),
+        };
+        if state == ClassState::Initializing {
+            return Err(format!(
+                "class {} is being initialized",
+                name.replace('/', ".")
+            ));
+        }
+        classes.push(ArchivedClass {
+            name: name.to_string(),
+            class_file,
+            state,
+            mirror: class.get_mirror_ref(),
+            static_fields,
+        });
+    }
+    let (heap, string_pool) = {
+        let heap = vm.heap_read();
+        let string_pool = heap
+            .string_pool()
+            .map(|(sym, heap_ref)| (interner.resolve(&sym).to_string(), heap_ref))
+            .collect();
+        (heap.allocated_memory().to_vec(), string_pool)
+    };
+    let archive = StartupArchive {
+        classes,
+        heap,
+        string_pool,
+        thread_obj: thread.thread_obj,
+        thread_name: thread.name,
+        thread_group: thread.group_obj,
+        builtin_loaders: ma
+            .builtin_loaders()
+            .map(|loaders| (loaders.platform, loaders.app)),
+        java_handled_signals: signal::java_handled_signals(),
+    };
+    let mut out = Vec::with_capacity(archive.heap.len() * 2);
+    archive
+        .write(&mut out, &fingerprint(&vm.config))
+        .map_err(|e| e.to_string())?;
+    std::fs::write(path, out).map_err(|e| e.to_string())?;
+    debug_log!(
+        "Dumped {} classes and {} bytes of heap to {}",
+        archive.classes.len(),
+        archive.heap.len(),
+        path.display()
+    );
+    Ok(())
+}
+
+/// Reads the archive at `path` and loads its classes again from the class files it keeps,
+/// checking that each one gets the id it had when the archive was dumped. The VM is left as it
+/// was booted up to then, only with more classes loaded, if the archive can't be used.
+pub fn load(
+    vm: &VirtualMachine,
+    path: &Path,
+    thread_id: ThreadId,
+) -> Result<StartupArchive, String> {
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    let mut archive = StartupArchive::read(&mut bytes.as_slice(), &fingerprint(&vm.config))?;
+    let class_loader = vm.method_area().class_loader();
+    class_loader.set_archived_classes(
+        archive
+            .classes
+            .iter_mut()
+            .filter_map(|class| Some((class.name.clone(), class.class_file.take()?)))
+            .collect(),
+    );
+    let loaded = load_classes(vm, &archive, thread_id);
+    // classes loaded later are read from the runtime image
+    class_loader.set_archived_classes(HashMap::new());
+    loaded.map(|()| archive)
+}
+
+fn load_classes(
+    vm: &VirtualMachine,
+    archive: &StartupArchive,
+    thread_id: ThreadId,
+) -> Result<(), String> {
+    let ma = vm.method_area();
+    for (index, class) in archive.classes.iter().enumerate() {
+        let name_sym = vm.interner().get_or_intern(&class.name);
+        let class_id = ma
+            .get_class_id_or_load(name_sym, thread_id)
+            .map_err(|e| e.into_pretty_string(vm.interner()))?;
+        if class_id.to_index() != index {
+            return Err(format!(
+                "class {} was loaded in a different order",
+                class.name.replace('/', ".")
+            ));
+        }
+    }
+    if ma.classes().count() != archive.classes.len() {
+        return Err("more classes were loaded than when the archive was dumped".to_string());
+    }
+    Ok(())
+}
+
+/// Puts back the heap, the class mirrors, states and static fields and the VM state of a
+/// [`load`]ed archive, and returns the main thread.
+pub fn restore(
+    vm: &VirtualMachine,
+    archive: StartupArchive,
+    thread_id: ThreadId,
+) -> Result<JavaThreadState, JvmError> {
+    let ma = vm.method_area();
+    let interner = vm.interner();
+    let string_pool = archive
+        .string_pool
+        .iter()
+        .map(|(value, heap_ref)| (interner.get_or_intern(value), *heap_ref))
+        .collect();
+    vm.heap_write().restore(&archive.heap, string_pool)?;
+
+    let mut thread = JavaThreadState {
+        id: thread_id,
+        thread_obj: archive.thread_obj,
+        group_obj: archive.thread_group,
+        name: archive.thread_name,
+        stack: FrameStack::new(&vm.config),
+    };
+    let register_natives_sym = interner.get_or_intern("registerNatives");
+    for (index, archived) in archive.classes.iter().enumerate() {
+        let class_id = ClassId::from_usize(index + 1);
+        if let Some(mirror) = archived.mirror {
+            ma.restore_mirror(class_id, mirror)?;
+        }
+        let Ok(class) = ma.get_class(&class_id).as_class_like() else {
+            continue;
+        };
+        for (name, desc, value) in &archived.static_fields {
+            let key = FieldKey::new(interner.get_or_intern(name), interner.get_or_intern(desc));
+            class.set_static_field_value(&key, *value)?;
+        }
+        class.transition(class.state(), archived.state);
+        // natives are registered by `registerNatives` in the static initializer
+        let key =
+            FullyQualifiedMethodKey::new(class.name(), register_natives_sym, vm.br().void_desc);
+        if archived.state == ClassState::Initialized
+            && let Some(register) = vm.native_registry.get(&key)
+        {
+            register(vm, &mut thread, &[])?;
+        }
+    }
+    if let Some((platform, app)) = archive.builtin_loaders {
+        ma.set_builtin_loaders(BuiltinLoaders { platform, app })?;
+    }
+    signal::install_java_handlers(archive.java_handled_signals);
+    Ok(thread)
+}
+
+/// What the archive depends on besides the classes it names: the runtime image, the verify
+/// mode, the classpath and boot layer options the JDK initialization saw, the heap sizes and
+/// the VM binary, whose object layouts and natives shaped the heap.
+fn fingerprint(config: &VmConfig) -> String {
+    let binary = std::env::current_exe()
+        .and_then(std::fs::metadata)
+        .map(|meta| {
+            let modified = meta
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |time| time.as_nanos());
+            format!("{}:{}", meta.len(), modified)
+        })
+        .unwrap_or_default();
+    format!(
+        "{}|{}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{}|{}|{}",
+        config.home.display(),
+        config.version,
+        config.verify,
+        config.class_path,
+        config.module_path,
+        config.main_module,
+        config.add_exports,
+        config.add_opens,
+        config.initial_heap_size,
+        config.max_heap_size,
+        binary
+    )
+}
+
+impl StartupArchive {
+    fn write(&self, out: &mut impl Write, fingerprint: &str) -> std::io::Result<()> {
+        out.write_all(MAGIC)?;
+        out.write_u32::<LittleEndian>(FORMAT_VERSION)?;
+        write_str(out, fingerprint)?;
+        out.write_u32::<LittleEndian>(self.classes.len() as u32)?;
+        for class in &self.classes {
+            write_str(out, &class.name)?;
+            match &class.class_file {
+                Some(bytes) => {
+                    out.write_u8(1)?;
+                    out.write_u32::<LittleEndian>(bytes.len() as u32)?;
+                    out.write_all(bytes)?;
+                }
+                None => out.write_u8(0)?,
+            }
+            out.write_u8(class.state as u8)?;
+            out.write_u64::<LittleEndian>(class.mirror.unwrap_or(0) as u64)?;
+            out.write_u32::<LittleEndian>(class.static_fields.len() as u32)?;
+            for (name, desc, value) in &class.static_fields {
+                write_str(out, name)?;
+                write_str(out, desc)?;
+                write_value(out, *value)?;
+            }
+        }
+        out.write_u64::<LittleEndian>(self.heap.len() as u64)?;
+        out.write_all(&self.heap)?;
+        out.write_u32::<LittleEndian>(self.string_pool.len() as u32)?;
+        for (value, heap_ref) in &self.string_pool {
+            write_str(out, value)?;
+            out.write_u64::<LittleEndian>(*heap_ref as u64)?;
+        }
+        for heap_ref in [self.thread_obj, self.thread_name, self.thread_group] {
+            out.write_u64::<LittleEndian>(heap_ref as u64)?;
+        }
+        let (platform, app) = self.builtin_loaders.unwrap_or((0, 0));
+        out.write_u64::<LittleEndian>(platform as u64)?;
+        out.write_u64::<LittleEndian>(app as u64)?;
+        out.write_u64::<LittleEndian>(self.java_handled_signals)
+    }
+
+    fn read(input: &mut impl Read, fingerprint: &str) -> Result<Self, String> {
+        let mut magic = [0; 8];
+        input.read_exact(&mut magic).map_err(|e| e.to_string())?;
+        if &magic != MAGIC {
+            return Err("not a startup archive".to_string());
+        }
+        let version = input
+            .read_u32::<LittleEndian>()
+            .map_err(|e| e.to_string())?;
+        if version != FORMAT_VERSION || read_str(input).map_err(|e| e.to_string())? != fingerprint {
+            return Err(
+                "the archive was dumped by a different VM build, runtime image or options"
+                    .to_string(),
+            );
+        }
+        Self::read_contents(input).map_err(|e| format!("corrupt archive: {e}"))
+    }
+
+    fn read_contents(input: &mut impl Read) -> std::io::Result<Self> {
+        let class_count = input.read_u32::<LittleEndian>()?;
+        let mut classes = Vec::with_capacity(class_count as usize);
+        for _ in 0..class_count {
+            let name = read_str(input)?;
+            let class_file = match input.read_u8()? {
+                0 => None,
+                1 => {
+                    let mut bytes = vec![0; input.read_u32::<LittleEndian>()? as usize];
+                    input.read_exact(&mut bytes)?;
+                    Some(bytes)
+                }
+                other => return Err(invalid_data(format!("class file tag {other}"))),
+            };
+            let state = match input.read_u8()? {
+                state @ 0..=4 => ClassState::from(state),
+                other => return Err(invalid_data(format!("class state {other}"))),
+            };
+            let mirror = Some(input.read_u64::<LittleEndian>()? as HeapRef).filter(|m| *m != 0);
+            let field_count = input.read_u32::<LittleEndian>()?;
+            let mut static_fields = Vec::with_capacity(field_count as usize);
+            for _ in 0..field_count {
+                static_fields.push((read_str(input)?, read_str(input)?, read_value(input)?));
+            }
+            classes.push(ArchivedClass {
+                name,
+                class_file,
+                state,
+                mirror,
+                static_fields,
+            });
+        }
+        let mut heap = vec![0; input.read_u64::<LittleEndian>()? as usize];
+        input.read_exact(&mut heap)?;
+        let pool_size = input.read_u32::<LittleEndian>()?;
+        let mut string_pool = Vec::with_capacity(pool_size as usize);
+        for _ in 0..pool_size {
+            string_pool.push((
+                read_str(input)?,
+                input.read_u64::<LittleEndian>()? as HeapRef,
+            ));
+        }
+        let mut read_ref = || input.read_u64::<LittleEndian>().map(|r| r as HeapRef);
+        let thread_obj = read_ref()?;
+        let thread_name = read_ref()?;
+        let thread_group = read_ref()?;
+        let platform = read_ref()?;
+        let app = read_ref()?;
+        Ok(Self {
+            classes,
+            heap,
+            string_pool,
+            thread_obj,
+            thread_name,
+            thread_group,
+            builtin_loaders: Some((platform, app)).filter(|(platform, _)| *platform != 0),
+            java_handled_signals: input.read_u64::<LittleEndian>()?,
+        })
+    }
+}
+
+fn invalid_data(message: String) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message)
+}
+
+fn write_str(out: &mut impl Write, s: &str) -> std::io::Result<()> {
+    out.write_u32::<LittleEndian>(s.len() as u32)?;
+    out.write_all(s.as_bytes())
+}
+
+fn read_str(input: &mut impl Read) -> std::io::Result<String> {
+    let mut bytes = vec![0; input.read_u32::<LittleEndian>()? as usize];
+    input.read_exact(&mut bytes)?;
+    String::from_utf8(bytes).map_err(|e| invalid_data(e.to_string()))
+}
+
+fn write_value(out: &mut impl Write, value: Value) -> std::io::Result<()> {
+    match value {
+        Value::Integer(i) => {
+            out.write_u8(0)?;
+            out.write_i32::<LittleEndian>(i)
+        }
+        Value::Long(l) => {
+            out.write_u8(1)?;
+            out.write_i64::<LittleEndian>(l)
+        }
+        Value::Float(f) => {
+            out.write_u8(2)?;
+            out.write_f32::<LittleEndian>(f)
+        }
+        Value::Double(d) => {
+            out.write_u8(3)?;
+            out.write_f64::<LittleEndian>(d)
+        }
+        Value::Ref(heap_ref) => {
+            out.write_u8(4)?;
+            out.write_u64::<LittleEndian>(heap_ref as u64)
+        }
+        Value::Null => out.write_u8(5),
+    }
+}
+
+fn read_value(input: &mut impl Read) -> std::io::Result<Value> {
+    Ok(match input.read_u8()? {
+        0 => Value::Integer(input.read_i32::<LittleEndian>()?),
+        1 => Value::Long(input.read_i64::<LittleEndian>()?),
+        2 => Value::Float(input.read_f32::<LittleEndian>()?),
+        3 => Value::Double(input.read_f64::<LittleEndian>()?),
+        4 => Value::Ref(input.read_u64::<LittleEndian>()? as HeapRef),
+        5 => Value::Null,
+        other => return Err(invalid_data(format!("value tag {other}"))),
+    })
+}
@@ -0,0 +1,337 @@
+use crate::error::JvmError;
+use crate::heap::method_area::MethodArea;
+use crate::heap::{Heap, HeapRef};
+use crate::keys::ClassId;
+use crate::rt::JvmClass;
+use crate::thread::JavaThreadState;
+use crate::vm::Value;
+use crate::{VirtualMachine, debug_log};
+use common::jtype::AllocationType;
+use std::collections::{HashMap, HashSet};
+
+/// What the collector has to know about the instances of a class beyond their fields.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum InstanceKind {
+    Plain,
+    /// Weak and phantom references, the referent doesn't keep its object alive. Soft and
+    /// final references are kept strong: there is no memory pressure policy and no finalizer.
+    WeakReference,
+    /// The backtrace refers to the classes of the stack frames by id.
+    Throwable,
+}
+
+/// Runs a collection for `Runtime.gc`: finds the objects reachable from the roots, clears the
+/// weak references to the others and unloads the classes of user-defined loaders that are not
+/// reachable any more. Memory of dead objects is not reclaimed, the heap only grows.
+///
+/// Only the stack of the current thread is known to the VM, so nothing is collected while other
+/// Java threads are alive, not even the weak references and classes only the current thread
+/// could reach. A thread stops counting once `join()` on it returns.
+pub fn collect(vm: &VirtualMachine, thread: &JavaThreadState) -> Result<(), JvmError> {
+    if vm.threads.all().iter().any(|other| other.id != thread.id) {
+        debug_log!("Skipping collection, other Java threads are alive");
+        return Ok(());
+    }
+    let ma = vm.method_area();
+    let namespaces = ma.loader_namespaces();
+    let (cleared, dead_loaders) = {
+        let heap = vm.heap_read();
+        let mut marker = Marker::new(vm, &heap, &namespaces)?;
+        marker.mark_roots(vm, thread);
+        marker.drain()?;
+        let cleared = marker.unreachable_referents()?;
+        let dead_loaders: HashSet<HeapRef> = namespaces
+            .keys()
+            .filter(|loader| !marker.marked.contains(loader))
+            .copied()
+            .collect();
+        (cleared, dead_loaders)
+    };
+    if let Some(referent_offset) = Marker::referent_offset(ma, vm)? {
+        let mut heap = vm.heap_write();
+        for reference in cleared {
+            heap.write_field(
+                reference,
+                referent_offset,
+                Value::Null,
+                AllocationType::Reference,
+            )?;
+        }
+    }
+    if !dead_loaders.is_empty() {
+        // Safety: the current thread is the only Java thread, and everything it can reach
+        // keeps the loader of its class reachable
+        unsafe { ma.unload_classes(&dead_loaders) };
+    }
+    Ok(())
+}
+
+/// Marks the objects reachable from the roots, and the classes they keep alive: the class of
+/// an object, the class of the method a `ResolvedMethodName` stands for, the classes defined or
+/// initiated by a reachable loader and the supertypes, mirrors, loaders, static field values and
+/// resolved constants of reachable classes, those of the class files they were redefined with
+/// included.
+struct Marker<'a> {
+    ma: &'a MethodArea,
+    heap: &'a Heap,
+    namespaces: &'a HashMap<HeapRef, Vec<ClassId>>,
+    marked: HashSet<HeapRef>,
+    live_classes: HashSet<ClassId>,
+    pending_objects: Vec<HeapRef>,
+    pending_classes: Vec<ClassId>,
+    instance_kinds: HashMap<ClassId, InstanceKind>,
+    weak_references: Vec<HeapRef>,
+    referent_offset: Option<usize>,
+    backtrace_offset: usize,
+}
+
+impl<'a> Marker<'a> {
+    fn new(
+        vm: &'a VirtualMachine,
+        heap: &'a Heap,
+        namespaces: &'a HashMap<HeapRef, Vec<ClassId>>,
+    ) -> Result<Self, JvmError> {
+        let ma = vm.method_area();
+        let backtrace_offset = ma
+            .get_instance_class(&vm.br().get_java_lang_throwable_id()?)?
+            .get_instance_field(&vm.br().throwable_backtrace_fk)?
+            .offset;
+        Ok(Self {
+            ma,
+            heap,
+            namespaces,
+            marked: HashSet::new(),
+            live_classes: HashSet::new(),
+            pending_objects: Vec::new(),
+            pending_classes: Vec::new(),
+            instance_kinds: HashMap::new(),
+            weak_references: Vec::new(),
+            referent_offset: Self::referent_offset(ma, vm)?,
+            backtrace_offset,
+        })
+    }
+
+    /// Offset of `Reference.referent`, `None` while no reference class is loaded.
+    fn referent_offset(ma: &MethodArea, vm: &VirtualMachine) -> Result<Option<usize>, JvmError> {
+        let Some(reference_id) = ma.find_loaded_class(None, vm.br().java_lang_ref_reference_sym)
+        else {
+            return Ok(None);
+        };
+        Ok(Some(
+            ma.get_instance_class(&reference_id)?
+                .get_instance_field(&vm.br().reference_referent_fk)?
+                .offset,
+        ))
+    }
+
+    /// The stack of the current thread, the live threads, interned strings, the builtin
+    /// loaders with all their classes and the loaders in the middle of loading a class.
+    fn mark_roots(&mut self, vm: &VirtualMachine, thread: &JavaThreadState) {
+        for frame in thread.stack.frames() {
+            let class_id = self.ma.get_method(&frame.method_id()).class_id();
+            self.mark_class(class_id);
+            for heap_ref in frame.references() {
+                self.mark(heap_ref);
+            }
+        }
+        self.mark(thread.group_obj);
+        for registered in vm.threads.all() {
+            self.mark(registered.thread_obj);
+            self.mark(registered.name);
+        }
+        for string in self.heap.interned_strings() {
+            self.mark(string);
+        }
+        if let Some(loaders) = self.ma.builtin_loaders() {
+            self.mark(loaders.platform);
+            self.mark(loaders.app);
+        }
+        for loader in self.ma.loading_loaders() {
+            self.mark(loader);
+        }
+        for (index, _) in self.ma.classes().iter() {
+            let class_id = ClassId::from_usize(index + 1);
+            let loader = self.ma.get_class_loader(class_id);
+            if self.ma.is_builtin_loader(loader) {
+                self.mark_class(class_id);
+            }
+        }
+    }
+
+    fn mark(&mut self, heap_ref: HeapRef) {
+        if heap_ref != 0 && self.marked.insert(heap_ref) {
+            self.pending_objects.push(heap_ref);
+        }
+    }
+
+    fn mark_value(&mut self, value: Value) {
+        if let Value::Ref(heap_ref) = value {
+            self.mark(heap_ref);
+        }
+    }
+
+    fn mark_class(&mut self, class_id: ClassId) {
+        if self.live_classes.insert(class_id) {
+            self.pending_classes.push(class_id);
+        }
+    }
+
+    fn drain(&mut self) -> Result<(), JvmError> {
+        loop {
+            if let Some(class_id) = self.pending_classes.pop() {
+                self.trace_class(class_id);
+            } else if let Some(heap_ref) = self.pending_objects.pop() {
+                self.trace_object(heap_ref)?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn trace_class(&mut self, class_id: ClassId) {
+        let ma = self.ma;
+        let class = ma.get_class(&class_id);
+        if let Some(mirror) = class.get_mirror_ref() {
+            self.mark(mirror);
+        }
+        if let Some(loader) = ma.get_class_loader(class_id) {
+            self.mark(loader);
+        }
+        if let Some(super_id) = class.get_super_id() {
+            self.mark_class(super_id);
+        }
+        match class {
+            JvmClass::Instance(_) | JvmClass::Interface(_) => {
+                let Ok(class) = class.as_class_like() else {
+                    return;
+                };
+                // a class that failed linking may have no interfaces yet
+                if let Ok(interfaces) = class.get_interfaces() {
+                    for interface_id in interfaces {
+                        self.mark_class(*interface_id);
+                    }
+                }
+                for value in class.static_field_values() {
+                    self.mark_value(value);
+                }
+                if let Ok(cp) = ma.get_cp(&class_id) {
+                    for heap_ref in cp.references() {
+                        self.mark(heap_ref);
+                    }
+                }
+                for cp in ma.redefined_cps(class_id) {
+                    for heap_ref in cp.references() {
+                        self.mark(heap_ref);
+                    }
+                }
+            }
+            JvmClass::InstanceArray(arr) => self.mark_class(arr.element_class_id),
+            JvmClass::PrimitiveArray(_) | JvmClass::Primitive(_) => {}
+        }
+    }
+
+    fn trace_object(&mut self, heap_ref: HeapRef) -> Result<(), JvmError> {
+        let ma = self.ma;
+        let heap = self.heap;
+        let class_id = heap.get_class_id(heap_ref)?;
+        self.mark_class(class_id);
+        if let Ok(mirrored_id) = ma.get_class_id_by_mirror(&heap_ref) {
+            self.mark_class(mirrored_id);
+        }
+        if let Some(method_id) = ma.get_resolved_method(&heap_ref) {
+            self.mark_class(ma.get_method(&method_id).class_id());
+        }
+        if let Some(classes) = self.namespaces.get(&heap_ref) {
+            for class_id in classes {
+                self.mark_class(*class_id);
+            }
+        }
+        if heap.is_array(heap_ref)? {
+            if heap.is_reference_array(heap_ref)? {
+                for index in 0..heap.get_array_length(heap_ref)? {
+                    self.mark_value(heap.read_array_element(heap_ref, index)?);
+                }
+            }
+            return Ok(());
+        }
+        let kind = self.instance_kind(class_id);
+        match kind {
+            InstanceKind::WeakReference => self.weak_references.push(heap_ref),
+            InstanceKind::Throwable => self.mark_backtrace_classes(heap_ref)?,
+            InstanceKind::Plain => {}
+        }
+        for field in ma.get_class(&class_id).get_instance_fields() {
+            if kind == InstanceKind::WeakReference && Some(field.offset) == self.referent_offset {
+                continue;
+            }
+            let field_type = ma
+                .get_field_descriptor(&field.descriptor_id)
+                .as_allocation_type();
+            if field_type == AllocationType::Reference {
+                self.mark_value(heap.read_field(heap_ref, field.offset, field_type)?);
+            }
+        }
+        Ok(())
+    }
+
+    fn instance_kind(&mut self, class_id: ClassId) -> InstanceKind {
+        if let Some(kind) = self.instance_kinds.get(&class_id) {
+            return *kind;
+        }
+        let br = self.ma.br();
+        let is = |sym| self.ma.instance_of(class_id, None, sym);
+        let kind = if is(br.java_lang_throwable_sym) {
+            InstanceKind::Throwable
+        } else if is(br.java_lang_ref_reference_sym)
+            && !is(br.java_lang_ref_soft_reference_sym)
+            && !is(br.java_lang_ref_final_reference_sym)
+        {
+            InstanceKind::WeakReference
+        } else {
+            InstanceKind::Plain
+        };
+        self.instance_kinds.insert(class_id, kind);
+        kind
+    }
+
+    /// The first array of the backtrace holds the class ids of the frames, see
+    /// `Throwable.fillInStackTrace`.
+    fn mark_backtrace_classes(&mut self, throwable: HeapRef) -> Result<(), JvmError> {
+        let heap = self.heap;
+        let Value::Ref(backtrace) =
+            heap.read_field(throwable, self.backtrace_offset, AllocationType::Reference)?
+        else {
+            return Ok(());
+        };
+        if !heap.is_array(backtrace)? || heap.get_array_length(backtrace)? == 0 {
+            return Ok(());
+        }
+        let Value::Ref(class_ids) = heap.read_array_element(backtrace, 0)? else {
+            return Ok(());
+        };
+        for index in 0..heap.get_array_length(class_ids)? {
+            let class_id = heap.read_array_element(class_ids, index)?.as_int()?;
+            self.mark_class(ClassId::from_i32(class_id));
+        }
+        Ok(())
+    }
+
+    /// Weak references whose referent was not marked.
+    fn unreachable_referents(&self) -> Result<Vec<HeapRef>, JvmError> {
+        let Some(referent_offset) = self.referent_offset else {
+            return Ok(Vec::new());
+        };
+        let mut cleared = Vec::new();
+        for reference in &self.weak_references {
+            let referent =
+                self.heap
+                    .read_field(*reference, referent_offset, AllocationType::Reference)?;
+            if let Value::Ref(referent) = referent
+                && !self.marked.contains(&referent)
+            {
+                cleared.push(*reference);
+            }
+        }
+        Ok(cleared)
+    }
+}
@@ -1,43 +1,93 @@
-use crate::class_loader::ClassLoader;
-use crate::error::JvmError;
+use crate::class_loader::{BuiltinLoader, BuiltinLoaders, ClassLoader};
+use crate::error::{JavaExceptionKind, JvmError};
+use crate::heap::slots::SlotVec;
 use crate::heap::{Heap, HeapRef};
-use crate::jdwp::{ClassPrepareInfo, ClassStatus, DebugEvent, DebugState, TypeTag};
+use crate::jdwp::{
+    ClassPrepareInfo, ClassStatus, ClassUnloadInfo, DebugEvent, DebugState, TypeTag,
+};
 use crate::keys::{
     ClassId, FieldDescriptorId, FieldKey, FullyQualifiedMethodKey, MethodDescriptorId, MethodKey,
     ThreadId,
 };
+use crate::module::descriptor::{JAVA_BASE, package_of};
+use crate::module::finder::ModuleSource;
+use crate::module::{ModuleGraph, ModuleId};
 use crate::rt::array::{ObjectArrayClass, PrimitiveArrayClass};
 use crate::rt::class::InstanceClass;
 use crate::rt::constant_pool::RuntimeConstantPool;
 use crate::rt::field::InstanceField;
+use crate::rt::format;
 use crate::rt::interface::InterfaceClass;
 use crate::rt::method::Method;
 use crate::rt::{ClassLike, JvmClass, PrimitiveClass};
 use crate::vm::Value;
 use crate::vm::bootstrap_registry::BootstrapRegistry;
-use crate::{MethodId, Symbol, VmConfig, debug_log, throw_exception};
+use crate::{MethodId, Symbol, VerifyMode, VmConfig, build_exception, debug_log, throw_exception};
 use common::descriptor::MethodDescriptor;
 use common::error::{LinkageError, MethodDescriptorErr};
-use common::jtype::{AllocationType, JavaType, PrimitiveType};
+use common::jtype::{AllocationType, JavaType, PrimitiveType, ReturnType};
+use dashmap::{DashMap, DashSet};
 use jclass::ClassFile;
+use jclass::constant::ConstantInfo;
 use lasso::{Spur, ThreadedRodeo};
 use once_cell::sync::OnceCell;
-use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, RwLock, RwLockReadGuard};
+
+/// Two loaders, `None` standing for the builtin ones, that must agree on a class.
+type LoaderPair = (Option<HeapRef>, Option<HeapRef>);
 
+/// Classes, methods and descriptors are shared between threads without a global lock, entries
+/// never move once added. Field descriptor ids stay valid for good; class, method and method
+/// descriptor ids stay valid until the GC unloads the classes of a dead loader, after which their
+/// slots go to later definitions. Only class loading itself is serialized, per class name and loader.
 pub struct MethodArea {
     debug_state: Arc<DebugState>,
     bootstrap_class_loader: ClassLoader,
-    class_name_to_index: HashMap<Spur, ClassId>,
-    mirror_to_class_index: HashMap<HeapRef, ClassId>,
-    classes: Vec<JvmClass>,
-    methods: Vec<Method>,
-
-    field_descriptors: Vec<JavaType>,
-    field_descriptors_index: HashMap<Symbol, FieldDescriptorId>,
-
-    method_descriptors: Vec<MethodDescriptor>,
-    method_descriptors_index: HashMap<Symbol, MethodDescriptorId>,
+    builtin_loaders: OnceCell<BuiltinLoaders>,
+    /// Namespace of the builtin loaders: the VM reads the classes of the bootstrap, platform
+    /// and application loaders itself, so a name means the same class to all of them.
+    class_name_to_index: DashMap<Spur, ClassId>,
+    /// Namespaces of user-defined loaders: the classes each loader defined or initiated.
+    loader_classes: DashMap<(HeapRef, Symbol), ClassId>,
+    mirror_to_class_index: DashMap<HeapRef, ClassId>,
+    /// Classes defined by `Lookup.defineHiddenClass`, they are in no namespace.
+    hidden_classes: DashSet<ClassId>,
+    hidden_class_suffix: AtomicU64,
+    /// Methods of the `ResolvedMethodName`s of member names, the `vmtarget` hotspot injects.
+    resolved_methods: DashMap<HeapRef, MethodId>,
+    /// Slots of unloaded classes and their methods are reused, see [`MethodArea::unload_classes`].
+    classes: SlotVec<JvmClass>,
+    methods: SlotVec<Method>,
+
+    /// Classes currently being loaded or defined and the thread doing it, like the
+    /// placeholder table in hotspot. Keyed by loader (`None` for the builtin ones) and name.
+    loading: Mutex<HashMap<(Option<HeapRef>, Symbol), ThreadId>>,
+    load_finished: Condvar,
+    /// Loader constraints of §5.3.4 by class name: each pair of loaders must agree on the class
+    /// of that name, constraints sharing a loader chain together.
+    loader_constraints: Mutex<HashMap<Symbol, Vec<LoaderPair>>>,
+    /// Held for writing while classes are unloaded, by threads outside the Java world (the
+    /// JDWP agent) for reading while they look at classes.
+    class_unloading: RwLock<()>,
+    /// Held while classes are redefined, so that a method is replaced by one version only.
+    redefinition: Mutex<()>,
+    /// Constant pools of the class files each redefined class was redefined with, the ones of
+    /// replaced versions included as frames may still run them.
+    redefined_cps: DashMap<ClassId, Vec<Arc<RuntimeConstantPool>>>,
+
+    field_descriptors: boxcar::Vec<JavaType>,
+    field_descriptors_index: DashMap<Symbol, FieldDescriptorId>,
+
+    /// Descriptors no method uses any more are freed with the unloaded classes.
+    method_descriptors: SlotVec<MethodDescriptor>,
+    method_descriptors_index: DashMap<Symbol, MethodDescriptorId>,
+
+    substituted_methods: HashSet<FullyQualifiedMethodKey>,
+    verify_mode: VerifyMode,
+    /// `--verbose-class`: print the classes read for the builtin loaders as they are loaded.
+    verbose_class: bool,
 
     interner: Arc<ThreadedRodeo>,
     bootstrap_registry: Arc<BootstrapRegistry>,
@@ -48,22 +98,37 @@ impl MethodArea {
         vm_config: &VmConfig,
         string_interner: Arc<ThreadedRodeo>,
         debug_state: Arc<DebugState>,
+        substituted_methods: HashSet<FullyQualifiedMethodKey>,
     ) -> Result<(Self, Arc<BootstrapRegistry>), JvmError> {
         debug_log!("Creating Method Area...");
         let bootstrap_class_loader = ClassLoader::new(vm_config)?;
 
-        //TODO: preallocate better. check why method_descriptors needs so much space
-        let mut method_area = Self {
+        //TODO: preallocate better
+        let method_area = Self {
             debug_state,
             bootstrap_class_loader,
-            class_name_to_index: HashMap::new(),
-            mirror_to_class_index: HashMap::new(),
-            classes: Vec::with_capacity(1024),
-            methods: Vec::with_capacity(16384),
-            field_descriptors: Vec::with_capacity(2048),
-            field_descriptors_index: HashMap::new(),
-            method_descriptors: Vec::with_capacity(65536),
-            method_descriptors_index: HashMap::new(),
+            builtin_loaders: OnceCell::new(),
+            class_name_to_index: DashMap::new(),
+            loader_classes: DashMap::new(),
+            mirror_to_class_index: DashMap::new(),
+            hidden_classes: DashSet::new(),
+            hidden_class_suffix: AtomicU64::new(0x0800_0000),
+            resolved_methods: DashMap::new(),
+            classes: SlotVec::with_capacity(1024),
+            methods: SlotVec::with_capacity(16384),
+            loading: Mutex::new(HashMap::new()),
+            load_finished: Condvar::new(),
+            loader_constraints: Mutex::new(HashMap::new()),
+            class_unloading: RwLock::new(()),
+            redefinition: Mutex::new(()),
+            redefined_cps: DashMap::new(),
+            field_descriptors: boxcar::Vec::with_capacity(2048),
+            field_descriptors_index: DashMap::new(),
+            method_descriptors: SlotVec::with_capacity(8192),
+            method_descriptors_index: DashMap::new(),
+            substituted_methods,
+            verify_mode: vm_config.verify,
+            verbose_class: vm_config.verbose_class,
             bootstrap_registry: Arc::new(BootstrapRegistry::new(&string_interner)),
             interner: string_interner,
         };
@@ -74,7 +139,7 @@ impl MethodArea {
         Ok((method_area, br))
     }
 
-    fn preload_basic_classes(&mut self) -> Result<(), JvmError> {
+    fn preload_basic_classes(&self) -> Result<(), JvmError> {
         let todo = ThreadId::from_usize(666);
         let java_lang_object_id =
             self.get_class_id_or_load(self.br().java_lang_object_sym, todo)?;
@@ -125,7 +190,7 @@ impl MethodArea {
         Ok(())
     }
 
-    pub fn classes(&self) -> &Vec<JvmClass> {
+    pub fn classes(&self) -> &SlotVec<JvmClass> {
         &self.classes
     }
 
@@ -133,6 +198,111 @@ impl MethodArea {
         &self.bootstrap_registry
     }
 
+    pub fn modules(&self) -> &ModuleGraph {
+        self.bootstrap_class_loader.modules()
+    }
+
+    pub fn class_loader(&self) -> &ClassLoader {
+        &self.bootstrap_class_loader
+    }
+
+    pub fn is_substituted(&self, class_id: ClassId, key: &MethodKey) -> bool {
+        self.substituted_methods
+            .contains(&FullyQualifiedMethodKey::new(
+                self.get_class(&class_id).get_name(),
+                key.name,
+                key.desc,
+            ))
+    }
+
+    pub fn set_builtin_loaders(&self, loaders: BuiltinLoaders) -> Result<(), JvmError> {
+        self.builtin_loaders
+            .set(loaders)
+            .map_err(|_| JvmError::Todo("Builtin class loaders already set".to_string()))
+    }
+
+    pub fn builtin_loaders(&self) -> Option<&BuiltinLoaders> {
+        self.builtin_loaders.get()
+    }
+
+    /// The bootstrap loader (`None`) and the platform and application loaders share the
+    /// namespace of the VM.
+    pub fn is_builtin_loader(&self, loader: Option<HeapRef>) -> bool {
+        match (loader, self.builtin_loaders.get()) {
+            (None, _) => true,
+            (Some(loader), Some(builtin)) => loader == builtin.platform || loader == builtin.app,
+            (Some(_), None) => false,
+        }
+    }
+
+    /// Whether a class of the namespace of the VM is visible to the builtin loader `loader`, i.e.
+    /// was defined by it or one of its parents.
+    pub fn is_visible_to_builtin_loader(&self, loader: Option<HeapRef>, class_id: ClassId) -> bool {
+        match (self.get_class_loader(class_id), self.builtin_loaders.get()) {
+            (None, _) => true,
+            (Some(defining), Some(builtin)) if defining == builtin.platform => loader.is_some(),
+            (Some(defining), _) => loader == Some(defining),
+        }
+    }
+
+    /// Whether classes defined by `loader` are verified when they are linked.
+    pub fn should_verify(&self, loader: Option<HeapRef>) -> bool {
+        match self.verify_mode {
+            VerifyMode::None => false,
+            VerifyMode::Remote => loader.is_some(),
+            VerifyMode::All => true,
+        }
+    }
+
+    /// Defining loader of a class, arrays have the loader of their element class.
+    pub fn get_class_loader(&self, class_id: ClassId) -> Option<HeapRef> {
+        match self.get_class(&class_id) {
+            JvmClass::Instance(ic) => ic.loader(),
+            JvmClass::Interface(i) => i.loader(),
+            JvmClass::InstanceArray(oac) => self.get_class_loader(oac.element_class_id),
+            JvmClass::PrimitiveArray(_) | JvmClass::Primitive(_) => None,
+        }
+    }
+
+    /// Module of a class being defined: classes of user-defined loaders are in the unnamed
+    /// module, whatever their package.
+    pub fn class_module(&self, loader: Option<HeapRef>, name: Symbol) -> ModuleId {
+        if self.is_builtin_loader(loader) {
+            self.modules().module_of_class(self.interner.resolve(&name))
+        } else {
+            ModuleId::UNNAMED
+        }
+    }
+
+    /// The class `loader` defined or initiated under `name_sym`, if any.
+    pub fn find_loaded_class(&self, loader: Option<HeapRef>, name_sym: Symbol) -> Option<ClassId> {
+        match loader {
+            Some(loader) if !self.is_builtin_loader(Some(loader)) => {
+                self.loader_classes.get(&(loader, name_sym)).map(|id| *id)
+            }
+            _ => self.class_name_to_index.get(&name_sym).map(|id| *id),
+        }
+    }
+
+    /// Records `loader` as an initiating loader of the class, so later lookups of the name
+    /// through `loader` don't ask it again.
+    pub fn record_initiating_loader(
+        &self,
+        loader: HeapRef,
+        name_sym: Symbol,
+        class_id: ClassId,
+    ) -> Result<(), JvmError> {
+        if !self.is_builtin_loader(Some(loader)) {
+            self.check_loader_constraints(Some(loader), name_sym, class_id)?;
+            self.loader_classes.insert((loader, name_sym), class_id);
+        }
+        Ok(())
+    }
+
+    fn dictionary_key(&self, loader: Option<HeapRef>) -> Option<HeapRef> {
+        loader.filter(|loader| !self.is_builtin_loader(Some(*loader)))
+    }
+
     pub fn interner(&self) -> &ThreadedRodeo {
         &self.interner
     }
@@ -149,18 +319,16 @@ impl MethodArea {
         FullyQualifiedMethodKey::new(name, method.name, method.desc)
     }
 
-    fn push_field_descriptor(&mut self, ty: JavaType) -> FieldDescriptorId {
-        self.field_descriptors.push(ty);
-        FieldDescriptorId::from_usize(self.field_descriptors.len())
+    fn push_field_descriptor(&self, ty: JavaType) -> FieldDescriptorId {
+        FieldDescriptorId::from_usize(self.field_descriptors.push(ty) + 1)
     }
 
     pub fn get_field_descriptor(&self, id: &FieldDescriptorId) -> &JavaType {
         &self.field_descriptors[id.to_index()]
     }
 
-    fn push_method_descriptor(&mut self, descriptor: MethodDescriptor) -> MethodDescriptorId {
-        self.method_descriptors.push(descriptor);
-        MethodDescriptorId::from_usize(self.method_descriptors.len())
+    fn push_method_descriptor(&self, descriptor: MethodDescriptor) -> MethodDescriptorId {
+        MethodDescriptorId::from_usize(self.method_descriptors.push(descriptor) + 1)
     }
 
     pub fn get_method_descriptor(&self, id: &MethodDescriptorId) -> &MethodDescriptor {
@@ -173,41 +341,98 @@ impl MethodArea {
     }
 
     pub fn get_or_new_method_descriptor_id(
-        &mut self,
+        &self,
         descriptor: &Symbol,
     ) -> Result<MethodDescriptorId, MethodDescriptorErr> {
-        if let Some(method_desc) = self.method_descriptors_index.get(descriptor) {
-            return Ok(*method_desc);
-        }
-        let descriptor_str = self.interner.resolve(descriptor);
-        let method_descriptor = MethodDescriptor::try_from(descriptor_str)?;
-        Ok(self.push_method_descriptor(method_descriptor))
+        self.method_descriptors_index
+            .entry(*descriptor)
+            .or_try_insert_with(|| {
+                let descriptor_str = self.interner.resolve(descriptor);
+                let method_descriptor = MethodDescriptor::try_from(descriptor_str)?;
+                Ok(self.push_method_descriptor(method_descriptor))
+            })
+            .map(|id| *id)
     }
 
     pub fn get_or_new_field_descriptor_id(
-        &mut self,
+        &self,
         descriptor: Symbol,
     ) -> Result<FieldDescriptorId, JvmError> {
-        if let Some(type_desc) = self.field_descriptors_index.get(&descriptor) {
-            return Ok(*type_desc);
-        }
-        let descriptor_str = self.interner.resolve(&descriptor);
-        let ty = JavaType::try_from(descriptor_str)?;
-        Ok(self.push_field_descriptor(ty))
+        self.field_descriptors_index
+            .entry(descriptor)
+            .or_try_insert_with(|| {
+                let descriptor_str = self.interner.resolve(&descriptor);
+                let ty = JavaType::try_from(descriptor_str)?;
+                Ok(self.push_field_descriptor(ty))
+            })
+            .map(|id| *id)
     }
 
-    pub fn push_method(&mut self, method: Method) -> MethodId {
-        self.methods.push(method);
-        MethodId::from_usize(self.methods.len())
+    pub fn push_method(&self, method: Method) -> MethodId {
+        MethodId::from_usize(self.methods.push(method) + 1)
     }
 
     pub fn get_method(&self, method_id: &MethodId) -> &Method {
         &self.methods[method_id.to_index()]
     }
 
-    pub fn push_class(&mut self, class: JvmClass) -> ClassId {
-        self.classes.push(class);
-        ClassId::from_usize(self.classes.len())
+    /// The latest version of a method, the method itself unless its class was redefined.
+    pub fn current_method_id(&self, method_id: MethodId) -> MethodId {
+        let mut current = method_id;
+        while let Some(next) = self.get_method(&current).redefined_as() {
+            current = next;
+        }
+        current
+    }
+
+    /// Latest versions of the methods declared by a class.
+    pub(crate) fn declared_methods(&self, class_id: ClassId) -> HashMap<MethodKey, MethodId> {
+        self.methods
+            .iter()
+            .filter(|(_, method)| method.class_id() == class_id && method.redefined_as().is_none())
+            .map(|(index, method)| {
+                let key = MethodKey {
+                    name: method.name,
+                    desc: method.desc,
+                };
+                (key, MethodId::from_usize(index + 1))
+            })
+            .collect()
+    }
+
+    /// Constant pools of the class files `class_id` was redefined with.
+    pub(crate) fn redefined_cps(&self, class_id: ClassId) -> Vec<Arc<RuntimeConstantPool>> {
+        self.redefined_cps
+            .get(&class_id)
+            .map(|cps| cps.clone())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn lock_redefinition(&self) -> MutexGuard<'_, ()> {
+        self.redefinition.lock().unwrap()
+    }
+
+    /// Installs the new versions of the methods of a redefined class, which resolve their
+    /// references in `cp`. Breakpoints set in the class are cleared, as their locations are in
+    /// the old code.
+    pub(crate) fn replace_methods(
+        &self,
+        class_id: ClassId,
+        cp: Arc<RuntimeConstantPool>,
+        methods: Vec<(MethodId, Method)>,
+    ) {
+        self.redefined_cps.entry(class_id).or_default().push(cp);
+        for (old_id, method) in methods {
+            let new_id = self.push_method(method);
+            self.get_method(&old_id).set_redefined_as(new_id);
+        }
+        self.debug_state
+            .breakpoints
+            .retain(|location, _| location.class_id != class_id);
+    }
+
+    pub fn push_class(&self, class: JvmClass) -> ClassId {
+        ClassId::from_usize(self.classes.push(class) + 1)
     }
 
     pub fn get_class(&self, class_id: &ClassId) -> &JvmClass {
@@ -278,6 +503,70 @@ impl MethodArea {
         throw_exception!(NoSuchMethodError, method_key: key, class_sym: class_sym)
     }
 
+    /// Method resolution of §5.4.3.3 and §5.4.3.4 for method handles: the class and its
+    /// superclasses, then the superinterfaces and for interfaces the methods of `Object`.
+    pub fn resolve_method(&self, class_id: &ClassId, key: &MethodKey) -> Option<MethodId> {
+        if let Ok(Some(method_id)) = self.get_static_method_id_rec(class_id, key) {
+            return Some(method_id);
+        }
+        match self.get_class(class_id) {
+            JvmClass::Instance(class) => class.get_interface_method_id(key).ok(),
+            JvmClass::Interface(interface) => interface
+                .get_interfaces()
+                .ok()?
+                .iter()
+                .find_map(|id| self.get_interface_class(id).ok()?.get_methods().get(key))
+                .copied()
+                .or_else(|| {
+                    let object_id = self.br().get_java_lang_object_id().ok()?;
+                    self.get_class(&object_id).get_vtable_method_id(key).ok()
+                }),
+            class => class.get_vtable_method_id(key).ok(),
+        }
+    }
+
+    /// §2.9.3: a native varargs method of `MethodHandle` or `VarHandle` taking an `Object[]`.
+    pub fn is_signature_polymorphic(&self, method_id: &MethodId) -> bool {
+        let method = self.get_method(method_id);
+        let class_name = self.get_class(&method.class_id()).get_name();
+        (class_name == self.br().java_lang_invoke_method_handle_sym
+            || class_name == self.br().java_lang_invoke_var_handle_sym)
+            && method.is_native()
+            && method.flags().is_varargs()
+            && self.get_method_descriptor(&method.descriptor_id()).params
+                == [JavaType::Array(Box::new(JavaType::Instance(
+                    "java/lang/Object".to_string(),
+                )))]
+    }
+
+    /// The signature polymorphic method `name` declared by `class_id`, any call site
+    /// descriptor links to it.
+    pub fn find_signature_polymorphic_method(
+        &self,
+        class_id: &ClassId,
+        name: Symbol,
+    ) -> Option<MethodId> {
+        self.get_class_like(class_id)
+            .ok()?
+            .declared_method_ids()
+            .ok()?
+            .iter()
+            .copied()
+            .find(|id| self.get_method(id).name == name && self.is_signature_polymorphic(id))
+    }
+
+    pub fn set_resolved_method(&self, resolved_method_name: HeapRef, method_id: MethodId) {
+        self.resolved_methods
+            .insert(resolved_method_name, method_id);
+    }
+
+    /// The method of a `ResolvedMethodName`.
+    pub fn get_resolved_method(&self, resolved_method_name: &HeapRef) -> Option<MethodId> {
+        self.resolved_methods
+            .get(resolved_method_name)
+            .map(|entry| *entry)
+    }
+
     pub fn get_interface_class(&self, class_id: &ClassId) -> Result<&InterfaceClass, JvmError> {
         match self.get_class(class_id) {
             JvmClass::Interface(ic) => Ok(ic),
@@ -352,18 +641,77 @@ impl MethodArea {
         &self,
         method_id: &MethodId,
     ) -> Result<&RuntimeConstantPool, JvmError> {
-        let class_id = self.get_method(method_id).class_id();
-        self.get_cp(&class_id)
+        let method = self.get_method(method_id);
+        match method.constant_pool() {
+            Some(cp) => Ok(cp),
+            None => self.get_cp(&method.class_id()),
+        }
+    }
+
+    /// Whether the method is annotated `@CallerSensitive`. Like in the reference implementation,
+    /// the annotation only counts in classes of the bootstrap and platform loaders.
+    pub fn is_caller_sensitive(&self, method_id: &MethodId) -> bool {
+        self.has_privileged_annotation(method_id, self.br().caller_sensitive_desc)
+    }
+
+    /// Whether the method is the code of a lambda form, annotated `@LambdaForm.Compiled`.
+    pub fn is_compiled_lambda_form(&self, method_id: &MethodId) -> bool {
+        self.has_privileged_annotation(method_id, self.br().lambda_form_compiled_desc)
+    }
+
+    fn has_privileged_annotation(&self, method_id: &MethodId, annotation_desc: Symbol) -> bool {
+        let method = self.get_method(method_id);
+        let privileged = match self.get_class_loader(method.class_id()) {
+            None => true,
+            Some(loader) => self
+                .builtin_loaders()
+                .is_some_and(|builtin| builtin.platform == loader),
+        };
+        let Ok(cp) = self.get_cp_by_method_id(method_id) else {
+            return false;
+        };
+        privileged
+            && method.annotation_type_idxs().iter().any(|idx| {
+                cp.get_utf8_sym(idx, &self.interner)
+                    .is_ok_and(|sym| sym == annotation_desc)
+            })
     }
 
     pub(crate) fn load_array_class(
-        &mut self,
+        &self,
         name_sym: Symbol,
         thread_id: ThreadId,
     ) -> Result<ClassId, JvmError> {
         if let Some(class_id) = self.class_name_to_index.get(&name_sym) {
             return Ok(*class_id);
         }
+        self.with_load_placeholder(None, name_sym, thread_id, || {
+            self.create_array_class(name_sym, None, thread_id)
+        })
+    }
+
+    /// Array class of an element class defined by a user-defined loader, which is also the
+    /// defining loader of the array class.
+    pub fn load_array_class_in(
+        &self,
+        loader: HeapRef,
+        name_sym: Symbol,
+        element_class_id: ClassId,
+        thread_id: ThreadId,
+    ) -> Result<ClassId, JvmError> {
+        self.with_load_placeholder(Some(loader), name_sym, thread_id, || {
+            self.create_array_class(name_sym, Some(element_class_id), thread_id)
+        })
+    }
+
+    /// `element_class_id` is the already resolved element class of an object array, otherwise
+    /// the element class is loaded by the builtin loaders.
+    fn create_array_class(
+        &self,
+        name_sym: Symbol,
+        element_class_id: Option<ClassId>,
+        thread_id: ThreadId,
+    ) -> Result<ClassId, JvmError> {
         let type_descriptor_id = self.get_or_new_field_descriptor_id(name_sym)?;
         let type_descriptor = self.get_field_descriptor(&type_descriptor_id);
         let obj_class_id = self.br().get_java_lang_object_id()?;
@@ -390,8 +738,13 @@ impl MethodArea {
             JvmClass::InstanceArray(ObjectArrayClass {
                 name: name_sym,
                 super_id: self.br().get_java_lang_object_id()?,
-                element_class_id: self
-                    .get_class_id_or_load(self.interner.get_or_intern(instance_type), thread_id)?,
+                element_class_id: match element_class_id {
+                    Some(element_class_id) => element_class_id,
+                    None => self.get_class_id_or_load(
+                        self.interner.get_or_intern(instance_type),
+                        thread_id,
+                    )?,
+                },
                 vtable,
                 vtable_index,
                 mirror_ref: OnceCell::new(),
@@ -401,9 +754,7 @@ impl MethodArea {
                 "Array class with non-array or non-primitive type descriptor".to_string(),
             ))?
         };
-        let class_id = self.push_class(class);
-        self.class_name_to_index.insert(name_sym, class_id);
-        Ok(class_id)
+        Ok(self.push_class(class))
     }
 
     pub fn is_assignable_from(&self, this_class: ClassId, cls: ClassId) -> bool {
@@ -455,29 +806,90 @@ impl MethodArea {
     }
 
     //TODO: probably need try to load?
-    pub fn instance_of(&self, this_class_id: ClassId, other_sym: Symbol) -> bool {
-        if let Some(&other_class_id) = self.class_name_to_index.get(&other_sym) {
+    /// `other_sym` is looked up in the namespace of `loader`, the loader of the class that
+    /// names it.
+    pub fn instance_of(
+        &self,
+        this_class_id: ClassId,
+        loader: Option<HeapRef>,
+        other_sym: Symbol,
+    ) -> bool {
+        if let Some(other_class_id) = self.find_loaded_class(loader, other_sym) {
             self.is_subclass_of(this_class_id, other_class_id)
         } else {
             false
         }
     }
 
+    /// Runs `load` as the only loader of `name_sym` in the namespace of `loader` and publishes
+    /// the class under that name. Other threads asking for the same name wait for the result
+    /// instead of loading it twice; the same thread asking again means the class
+    /// (transitively) extends itself.
+    fn with_load_placeholder(
+        &self,
+        loader: Option<HeapRef>,
+        name_sym: Symbol,
+        thread_id: ThreadId,
+        load: impl FnOnce() -> Result<ClassId, JvmError>,
+    ) -> Result<ClassId, JvmError> {
+        let key = (self.dictionary_key(loader), name_sym);
+        {
+            let mut loading = self.loading.lock().unwrap();
+            loop {
+                if let Some(class_id) = self.find_loaded_class(key.0, name_sym) {
+                    return Ok(class_id);
+                }
+                match loading.get(&key) {
+                    Some(loader) if *loader == thread_id => {
+                        return Err(build_exception!(
+                            ClassCircularityError,
+                            self.interner.resolve(&name_sym).replace('/', ".")
+                        ));
+                    }
+                    Some(_) => loading = self.load_finished.wait(loading).unwrap(),
+                    None => {
+                        loading.insert(key, thread_id);
+                        break;
+                    }
+                }
+            }
+        }
+        let res = load().and_then(|class_id| {
+            self.check_loader_constraints(key.0, name_sym, class_id)?;
+            Ok(class_id)
+        });
+        let mut loading = self.loading.lock().unwrap();
+        if let Ok(class_id) = res {
+            match key.0 {
+                Some(loader) => self.loader_classes.insert((loader, name_sym), class_id),
+                None => self.class_name_to_index.insert(name_sym, class_id),
+            };
+        }
+        loading.remove(&key);
+        self.load_finished.notify_all();
+        res
+    }
+
     #[hotpath::measure]
-    fn load_class(&mut self, name_sym: Symbol, thread_id: ThreadId) -> Result<ClassId, JvmError> {
-        let data = {
+    fn load_class(&self, name_sym: Symbol, thread_id: ThreadId) -> Result<ClassId, JvmError> {
+        let found = {
             hotpath::measure_block!("load_class::read_raw_class", {
                 let name_str = self.interner.resolve(&name_sym);
                 if name_str.starts_with("[") {
-                    return self.load_array_class(name_sym, thread_id);
+                    return self.create_array_class(name_sym, None, thread_id);
                 }
                 self.bootstrap_class_loader.load(name_str)?
             })
         };
+        let loader = match found.loader {
+            BuiltinLoader::Boot => None,
+            BuiltinLoader::App => self.builtin_loaders.get().map(|loaders| loaders.app),
+        };
         let cf = hotpath::measure_block!(
             "load_class::parse_class_file",
-            ClassFile::try_from(data).map_err(LinkageError::from)?
+            format::parse_class_file(found.bytes)?
         );
+        format::check_format(&cf)?;
         let super_id = match cf.get_super_class_name() {
             Some(super_name) => {
                 let super_name = super_name.unwrap();
@@ -487,16 +899,155 @@ impl MethodArea {
             None => None,
         };
         let class_id = hotpath::measure_block!("load_class::load_and_link_class", {
-            if cf.access_flags.is_interface() {
-                InterfaceClass::load_and_link(cf, self, super_id, thread_id)?
-            } else {
-                InstanceClass::load_and_link(cf, self, super_id, thread_id)?
-            }
+            Self::load_and_link(cf, self, super_id, loader, thread_id)?
         });
-        self.class_name_to_index.insert(name_sym, class_id);
+        if self.verbose_class {
+            println!(
+                "[class,load] {} source: {}",
+                self.interner.resolve(&name_sym).replace('/', "."),
+                found.source
+            );
+        }
         Ok(class_id)
     }
 
+    fn load_and_link(
+        cf: ClassFile,
+        method_area: &MethodArea,
+        super_id: Option<ClassId>,
+        loader: Option<HeapRef>,
+        thread_id: ThreadId,
+    ) -> Result<ClassId, JvmError> {
+        if cf.access_flags.is_interface() {
+            InterfaceClass::load_and_link(cf, method_area, super_id, loader, thread_id)
+        } else {
+            InstanceClass::load_and_link(cf, method_area, super_id, loader, thread_id)
+        }
+    }
+
+    /// Defines a class from `ClassLoader.defineClass`. `resolve_supertypes` loads the
+    /// superinterfaces and returns the superclass, both resolved through `loader`; it runs under
+    /// the placeholder of the class, so a class that reaches itself that way is circular. A
+    /// second definition of the same name by the same loader is a `LinkageError`.
+    pub fn define_class(
+        &self,
+        loader: Option<HeapRef>,
+        cf: ClassFile,
+        thread_id: ThreadId,
+        resolve_supertypes: impl FnOnce(&ClassFile) -> Result<Option<ClassId>, JvmError>,
+    ) -> Result<ClassId, JvmError> {
+        let name_sym = self
+            .interner
+            .get_or_intern(cf.get_class_name().map_err(LinkageError::from)?);
+        let duplicate = || {
+            build_exception!(
+                LinkageError,
+                "loader {} attempted duplicate class definition for {}.",
+                self.describe_loader(loader),
+                self.interner.resolve(&name_sym).replace('/', ".")
+            )
+        };
+        if self.find_loaded_class(loader, name_sym).is_some() {
+            return Err(duplicate());
+        }
+        let mut defined = false;
+        let class_id = self.with_load_placeholder(loader, name_sym, thread_id, || {
+            defined = true;
+            let super_id = resolve_supertypes(&cf)?;
+            Self::load_and_link(cf, self, super_id, loader, thread_id)
+        })?;
+        if !defined {
+            return Err(duplicate());
+        }
+        Ok(class_id)
+    }
+
+    /// Defines a hidden class for `Lookup.defineHiddenClass`. Like in hotspot its name gets a
+    /// unique `+0x...` suffix, shown as `/0x...` by `Class.getName`. Nothing is recorded under
+    /// the name, so the class can't be found through `loader`.
+    pub fn define_hidden_class(
+        &self,
+        loader: Option<HeapRef>,
+        mut cf: ClassFile,
+        super_id: Option<ClassId>,
+        thread_id: ThreadId,
+    ) -> Result<ClassId, JvmError> {
+        let hidden_name = format!(
+            "{}+0x{:016x}",
+            cf.get_class_name().map_err(LinkageError::from)?,
+            self.hidden_class_suffix.fetch_add(1, Ordering::Relaxed)
+        );
+        cf.cp.inner.push(ConstantInfo::Utf8(hidden_name));
+        cf.cp.inner[cf.this_class as usize] = ConstantInfo::Class((cf.cp.inner.len() - 1) as u16);
+        let class_id = Self::load_and_link(cf, self, super_id, loader, thread_id)?;
+        self.hidden_classes.insert(class_id);
+        Ok(class_id)
+    }
+
+    pub fn is_hidden(&self, class_id: &ClassId) -> bool {
+        self.hidden_classes.contains(class_id)
+    }
+
+    /// Resolves a class while linking a class defined by `loader`. User-defined loaders
+    /// resolve supertypes before the definition, so only builtin loaders load here.
+    pub fn get_class_id_or_load_in(
+        &self,
+        loader: Option<HeapRef>,
+        name_sym: Symbol,
+        thread_id: ThreadId,
+    ) -> Result<ClassId, JvmError> {
+        if self.is_builtin_loader(loader) {
+            return self.get_class_id_or_load(name_sym, thread_id);
+        }
+        self.find_loaded_class(loader, name_sym).ok_or_else(|| {
+            build_exception!(
+                NoClassDefFoundError,
+                self.interner.resolve(&name_sym).to_string()
+            )
+        })
+    }
+
+    /// A class of the runtime image, for `ClassLoader.findBootstrapClass`. Classes of the
+    /// module path and the classpath are not visible to the bootstrap loader.
+    pub fn find_bootstrap_class(
+        &self,
+        name_sym: Symbol,
+        thread_id: ThreadId,
+    ) -> Result<Option<ClassId>, JvmError> {
+        let name = self.interner.resolve(&name_sym);
+        let module = self.modules().module_of_class(name);
+        let in_image = self
+            .modules()
+            .get(module)
+            .is_some_and(|m| matches!(m.source, ModuleSource::System));
+        if !in_image {
+            return Ok(None);
+        }
+        match self.get_class_id_or_load(name_sym, thread_id) {
+            Ok(class_id) if self.get_class_loader(class_id).is_none() => Ok(Some(class_id)),
+            Ok(_) => Ok(None),
+            Err(JvmError::JavaException(e))
+                if e.kind == JavaExceptionKind::ClassNotFoundException =>
+            {
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// `'bootstrap'`, `'app'` or the class and identity of a user-defined loader, as in
+    /// hotspot's linkage error messages.
+    pub fn describe_loader(&self, loader: Option<HeapRef>) -> String {
+        let Some(loader) = loader else {
+            return "'bootstrap'".to_string();
+        };
+        match self.builtin_loaders.get() {
+            Some(builtin) if loader == builtin.app => "'app'".to_string(),
+            Some(builtin) if loader == builtin.platform => "'platform'".to_string(),
+            _ => format!("@{loader:x}"),
+        }
+    }
+
     fn get_class_type_tag(&self, class_id: &ClassId) -> TypeTag {
         let class = self.get_class(class_id);
         if class.is_array() {
@@ -513,7 +1064,7 @@ impl MethodArea {
 
     #[hotpath::measure]
     pub fn get_class_id_or_load(
-        &mut self,
+        &self,
         name_sym: Symbol,
         thread_id: ThreadId,
     ) -> Result<ClassId, JvmError> {
@@ -522,7 +1073,9 @@ impl MethodArea {
                 return Ok(*class_id);
             }
         });
-        let class_id = self.load_class(name_sym, thread_id)?;
+        let class_id = self.with_load_placeholder(None, name_sym, thread_id, || {
+            self.load_class(name_sym, thread_id)
+        })?;
         if self.debug_state.should_check() {
             let name_str = self.interner.resolve(&name_sym);
             if let Some(matched) = self.debug_state.matches_class_prepare(name_str) {
@@ -543,17 +1096,291 @@ impl MethodArea {
         Ok(class_id)
     }
 
+    /// Module of a class, arrays are in the module of their element class and primitive types
+    /// in `java.base`.
+    pub fn get_class_module(&self, class_id: ClassId) -> ModuleId {
+        match self.get_class(&class_id) {
+            JvmClass::Instance(ic) => ic.module(),
+            JvmClass::Interface(i) => i.module(),
+            JvmClass::InstanceArray(oac) => self.get_class_module(oac.element_class_id),
+            JvmClass::PrimitiveArray(_) | JvmClass::Primitive(_) => self
+                .modules()
+                .by_name(JAVA_BASE)
+                .unwrap_or(ModuleId::UNNAMED),
+        }
+    }
+
+    /// https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-5.html#jvms-5.3.4
+    ///
+    /// Records that `name_sym` must denote the same class for `loader1` and `loader2`. Returns
+    /// false, and records nothing, when the loaders already know different classes by that name.
+    pub fn add_loader_constraint(
+        &self,
+        name_sym: Symbol,
+        loader1: Option<HeapRef>,
+        loader2: Option<HeapRef>,
+    ) -> bool {
+        let (loader1, loader2) = (self.dictionary_key(loader1), self.dictionary_key(loader2));
+        if loader1 == loader2 {
+            return true;
+        }
+        let mut constraints = self.loader_constraints.lock().unwrap();
+        let pairs = constraints.entry(name_sym).or_default();
+        if pairs.contains(&(loader1, loader2)) {
+            return true;
+        }
+        pairs.push((loader1, loader2));
+        let mut known = constrained_loaders(pairs, loader1)
+            .into_iter()
+            .filter_map(|loader| self.find_loaded_class(loader, name_sym));
+        let consistent = match known.next() {
+            Some(first) => known.all(|class_id| class_id == first),
+            None => true,
+        };
+        if !consistent {
+            pairs.pop();
+        }
+        consistent
+    }
+
+    /// A loader constrained to agree with others on `name_sym` can only get the class they
+    /// already have.
+    fn check_loader_constraints(
+        &self,
+        loader: Option<HeapRef>,
+        name_sym: Symbol,
+        class_id: ClassId,
+    ) -> Result<(), JvmError> {
+        let loader = self.dictionary_key(loader);
+        let constraints = self.loader_constraints.lock().unwrap();
+        let Some(pairs) = constraints.get(&name_sym) else {
+            return Ok(());
+        };
+        for other in constrained_loaders(pairs, loader) {
+            if let Some(existing) = self.find_loaded_class(other, name_sym)
+                && existing != class_id
+            {
+                return Err(build_exception!(
+                    LinkageError,
+                    "loader constraint violation: loader {} wants to load class {}. A different class with the same name was previously loaded by {}.",
+                    self.describe_loader(loader),
+                    self.interner.resolve(&name_sym).replace('/', "."),
+                    self.describe_loader(other)
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// A method overriding or implementing `overridden_id` of a class of another loader
+    /// constrains both loaders to agree on the classes named in its descriptor.
+    pub fn add_override_loader_constraints(
+        &self,
+        method_id: MethodId,
+        overridden_id: MethodId,
+    ) -> Result<(), JvmError> {
+        let method = self.get_method(&method_id);
+        let overridden = self.get_method(&overridden_id);
+        if overridden.is_private() {
+            return Ok(());
+        }
+        let loader = self.get_class_loader(method.class_id());
+        let other = self.get_class_loader(overridden.class_id());
+        if self.dictionary_key(loader) == self.dictionary_key(other) {
+            return Ok(());
+        }
+        let descriptor = self.get_method_descriptor(&method.descriptor_id());
+        let ret = match &descriptor.ret {
+            ReturnType::Type(ret) => Some(ret),
+            ReturnType::Void => None,
+        };
+        for class_name in descriptor.params.iter().chain(ret).filter_map(class_name) {
+            let name_sym = self.interner.get_or_intern(class_name);
+            if !self.add_loader_constraint(name_sym, loader, other) {
+                let class_name_of = |method: &Method| {
+                    let class = self.get_class(&method.class_id()).get_name();
+                    self.interner.resolve(&class).replace('/', ".")
+                };
+                let this_name = class_name_of(method);
+                return Err(build_exception!(
+                    LinkageError,
+                    "loader constraint violation for class {this_name}: when selecting overriding method '{}' the class loader {} of the selected method's type {this_name}, and the class loader {} for its super type {} have different Class objects for the type {} used in the signature",
+                    descriptor.to_java_signature(&this_name, self.interner.resolve(&method.name)),
+                    self.describe_loader(loader),
+                    self.describe_loader(other),
+                    class_name_of(overridden),
+                    class_name.replace('/', ".")
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether both classes are in the same package and namespace.
+    pub(crate) fn is_same_runtime_package(&self, class_id: ClassId, other_id: ClassId) -> bool {
+        let (name, other_name) = (
+            self.interner.resolve(&self.get_class(&class_id).get_name()),
+            self.interner.resolve(&self.get_class(&other_id).get_name()),
+        );
+        package_of(name) == package_of(other_name)
+            && self.dictionary_key(self.get_class_loader(class_id))
+                == self.dictionary_key(self.get_class_loader(other_id))
+    }
+
+    /// A method can't override a final method it can access, a package-private one is only
+    /// overridden from its runtime package.
+    pub fn check_final_override(
+        &self,
+        method_id: MethodId,
+        overridden_id: MethodId,
+    ) -> Result<(), JvmError> {
+        let overridden = self.get_method(&overridden_id);
+        if !overridden.is_final() || overridden.is_private() {
+            return Ok(());
+        }
+        let method = self.get_method(&method_id);
+        let class = self.get_class(&method.class_id());
+        let super_class = self.get_class(&overridden.class_id());
+        let (class_name, super_name) = (
+            self.interner.resolve(&class.get_name()),
+            self.interner.resolve(&super_class.get_name()),
+        );
+        if overridden.is_package_private()
+            && !self.is_same_runtime_package(method.class_id(), overridden.class_id())
+        {
+            return Ok(());
+        }
+        Err(build_exception!(
+            IncompatibleClassChangeError,
+            "class {} overrides final method {}.{}{}",
+            class_name.replace('/', "."),
+            super_name.replace('/', "."),
+            self.interner.resolve(&overridden.name),
+            self.interner.resolve(&overridden.desc)
+        ))
+    }
+
+    /// https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-5.html#jvms-5.4.1
+    ///
+    /// The superclass of a class can be neither an interface nor final.
+    pub fn check_superclass(&self, this_name: Symbol, super_id: ClassId) -> Result<(), JvmError> {
+        let super_class = self.get_class(&super_id);
+        let this_name = self.interner.resolve(&this_name).replace('/', ".");
+        let super_name = self
+            .interner
+            .resolve(&super_class.get_name())
+            .replace('/', ".");
+        if super_class.is_interface() {
+            return Err(build_exception!(
+                IncompatibleClassChangeError,
+                "class {this_name} has interface {super_name} as super class"
+            ));
+        }
+        if super_class.is_final() {
+            return Err(build_exception!(
+                IncompatibleClassChangeError,
+                "class {this_name} cannot inherit from final class {super_name}"
+            ));
+        }
+        Ok(())
+    }
+
+    /// A class or interface can only implement or extend interfaces.
+    pub fn check_superinterface(
+        &self,
+        this_name: Symbol,
+        interface_id: ClassId,
+    ) -> Result<(), JvmError> {
+        let interface = self.get_class(&interface_id);
+        if interface.is_interface() {
+            return Ok(());
+        }
+        Err(build_exception!(
+            IncompatibleClassChangeError,
+            "class {} can not implement {}, because it is not an interface",
+            self.interner.resolve(&this_name).replace('/', "."),
+            self.interner
+                .resolve(&interface.get_name())
+                .replace('/', ".")
+        ))
+    }
+
+    /// https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-5.html#jvms-5.4.4
+    ///
+    /// A class is accessible when it is public and its package is exported by a module that
+    /// the accessor's module reads, or when both are in the same package.
+    pub fn check_class_access(&self, accessor: ClassId, target: ClassId) -> Result<(), JvmError> {
+        if accessor == target {
+            return Ok(());
+        }
+        let target = match self.get_class(&target) {
+            JvmClass::InstanceArray(oac) => oac.element_class_id,
+            JvmClass::PrimitiveArray(_) | JvmClass::Primitive(_) => return Ok(()),
+            _ => target,
+        };
+        let class = self.get_class(&target);
+        let from = self.get_class_module(accessor);
+        let to = self.get_class_module(target);
+        let is_public = class.as_class_like()?.flags().is_public();
+        if is_public && from == to {
+            return Ok(());
+        }
+        let accessor_name = self.interner.resolve(&self.get_class(&accessor).get_name());
+        let target_name = self.interner.resolve(&class.get_name());
+
+        if is_public {
+            let modules = self.modules();
+            let reason = if !modules.can_read(from, to) {
+                format!(
+                    "{} does not read {}",
+                    modules.describe(from),
+                    modules.describe(to)
+                )
+            } else {
+                let package = package_of(target_name).unwrap_or_default();
+                if modules.is_exported(to, &package, from) {
+                    return Ok(());
+                }
+                format!(
+                    "{} does not export {} to {}",
+                    modules.describe(to),
+                    package.replace('/', "."),
+                    modules.describe(from)
+                )
+            };
+            return Err(build_exception!(
+                IllegalAccessError,
+                "class {} ({}) cannot access class {} ({}) because {}",
+                accessor_name.replace('/', "."),
+                modules.describe_location(from),
+                target_name.replace('/', "."),
+                modules.describe_location(to),
+                reason
+            ));
+        }
+
+        if from == to && package_of(accessor_name) == package_of(target_name) {
+            return Ok(());
+        }
+        Err(build_exception!(
+            IllegalAccessError,
+            "failed to access class {} from class {}",
+            target_name.replace('/', "."),
+            accessor_name.replace('/', ".")
+        ))
+    }
+
     pub fn get_class_id_by_mirror(&self, mirror: &HeapRef) -> Result<ClassId, JvmError> {
         self.mirror_to_class_index
             .get(mirror)
-            .copied()
+            .map(|class_id| *class_id)
             .ok_or(JvmError::Todo(
                 "Class ID not found for given mirror reference".to_string(),
             ))
     }
 
     pub fn get_mirror_ref_or_create(
-        &mut self,
+        &self,
         class_id: ClassId,
         heap: &RwLock<Heap>,
     ) -> Result<HeapRef, JvmError> {
@@ -579,9 +1406,203 @@ impl MethodArea {
                 AllocationType::Boolean,
             )?;
         }
+        if let Some(loader) = self.get_class_loader(class_id) {
+            let class_loader_field = self
+                .get_instance_class(&class_class_id)?
+                .get_instance_field(&self.br().class_class_loader_fk)?;
+            heap.write().unwrap().write_field(
+                mirror_ref,
+                class_loader_field.offset,
+                Value::Ref(loader),
+                AllocationType::Reference,
+            )?;
+        }
+        // the mirror has to be resolvable before other threads can see it
         self.mirror_to_class_index.insert(mirror_ref, class_id);
         let target_class = self.get_class(&class_id);
-        target_class.set_mirror_ref(mirror_ref)?;
+        if target_class.set_mirror_ref(mirror_ref).is_err() {
+            // another thread created the mirror first, ours stays unreachable
+            self.mirror_to_class_index.remove(&mirror_ref);
+            return target_class
+                .get_mirror_ref()
+                .ok_or(JvmError::Todo("Class mirror not set".to_string()));
+        }
         Ok(mirror_ref)
     }
+
+    /// Gives a class the mirror it had when the startup archive was dumped.
+    pub fn restore_mirror(&self, class_id: ClassId, mirror_ref: HeapRef) -> Result<(), JvmError> {
+        self.get_class(&class_id).set_mirror_ref(mirror_ref)?;
+        self.mirror_to_class_index.insert(mirror_ref, class_id);
+        Ok(())
+    }
+
+    /// User-defined loaders that are loading or defining a class right now.
+    pub fn loading_loaders(&self) -> Vec<HeapRef> {
+        self.loading
+            .lock()
+            .unwrap()
+            .keys()
+            .filter_map(|(loader, _)| *loader)
+            .collect()
+    }
+
+    /// Keeps classes from being unloaded while the guard is alive.
+    pub fn block_class_unloading(&self) -> RwLockReadGuard<'_, ()> {
+        self.class_unloading.read().unwrap()
+    }
+
+    /// Classes each user-defined loader defined or initiated.
+    pub fn loader_namespaces(&self) -> HashMap<HeapRef, Vec<ClassId>> {
+        let mut namespaces: HashMap<HeapRef, Vec<ClassId>> = HashMap::new();
+        for (index, _) in self.classes.iter() {
+            let class_id = ClassId::from_usize(index + 1);
+            if let Some(loader) = self.dictionary_key(self.get_class_loader(class_id)) {
+                namespaces.entry(loader).or_default().push(class_id);
+            }
+        }
+        for entry in self.loader_classes.iter() {
+            namespaces
+                .entry(entry.key().0)
+                .or_default()
+                .push(*entry.value());
+        }
+        namespaces
+    }
+
+    /// Unloads the classes defined by `dead_loaders`, user-defined loaders nothing reaches any
+    /// more, with the array classes of them. The slots of the classes, their methods and the
+    /// method descriptors only they used are reused by later definitions.
+    ///
+    /// # Safety
+    /// No other thread may run Java code or hold references into the method area, and nothing
+    /// reachable may refer to the classes of `dead_loaders`.
+    pub unsafe fn unload_classes(&self, dead_loaders: &HashSet<HeapRef>) {
+        let _unloading = self.class_unloading.write().unwrap();
+        let unloaded: HashSet<ClassId> = self
+            .classes
+            .iter()
+            .map(|(index, _)| ClassId::from_usize(index + 1))
+            .filter(|class_id| {
+                self.get_class_loader(*class_id)
+                    .is_some_and(|loader| dead_loaders.contains(&loader))
+            })
+            .collect();
+        if unloaded.is_empty() {
+            return;
+        }
+
+        self.loader_classes.retain(|(loader, _), class_id| {
+            !dead_loaders.contains(loader) && !unloaded.contains(class_id)
+        });
+        self.class_name_to_index
+            .retain(|_, class_id| !unloaded.contains(class_id));
+        self.mirror_to_class_index
+            .retain(|_, class_id| !unloaded.contains(class_id));
+        self.hidden_classes
+            .retain(|class_id| !unloaded.contains(class_id));
+        self.redefined_cps
+            .retain(|class_id, _| !unloaded.contains(class_id));
+        self.resolved_methods
+            .retain(|_, method_id| !unloaded.contains(&self.get_method(method_id).class_id()));
+        self.debug_state
+            .breakpoints
+            .retain(|location, _| !unloaded.contains(&location.class_id));
+        self.loader_constraints
+            .lock()
+            .unwrap()
+            .values_mut()
+            .for_each(|pairs| {
+                pairs.retain(|(l1, l2)| {
+                    ![l1, l2]
+                        .into_iter()
+                        .any(|loader| loader.is_some_and(|l| dead_loaders.contains(&l)))
+                })
+            });
+
+        let methods: Vec<usize> = self
+            .methods
+            .iter()
+            .filter(|(_, method)| unloaded.contains(&method.class_id()))
+            .map(|(index, _)| index)
+            .collect();
+        let mut descriptors: HashSet<MethodDescriptorId> = methods
+            .iter()
+            .map(|index| self.methods[*index].descriptor_id())
+            .collect();
+        for class_id in &unloaded {
+            let class = self.get_class(class_id);
+            let name = self.interner.resolve(&class.get_name());
+            debug_log!("Unloading class {}", name);
+            if self.debug_state.should_check()
+                && let Some(matched) = self
+                    .debug_state
+                    .matches_class_unload(&name.replace('/', "."))
+            {
+                let signature = if class.is_array() {
+                    name.to_string()
+                } else {
+                    format!("L{name};")
+                };
+                for request_id in matched {
+                    self.debug_state
+                        .send_event(DebugEvent::ClassUnload(ClassUnloadInfo {
+                            request_id,
+                            signature: signature.clone(),
+                        }));
+                }
+            }
+        }
+        // Safety: the caller guarantees the unloaded classes and their methods are unreachable
+        unsafe {
+            for index in methods {
+                self.methods.free(index);
+            }
+            for class_id in &unloaded {
+                self.classes.free(class_id.to_index());
+            }
+        }
+        for (_, method) in self.methods.iter() {
+            descriptors.remove(&method.descriptor_id());
+        }
+        self.method_descriptors_index
+            .retain(|_, descriptor_id| !descriptors.contains(descriptor_id));
+        // Safety: the methods using these descriptors are gone, other users only look at a
+        // descriptor while executing Java code, which no other thread does now
+        unsafe {
+            for descriptor_id in descriptors {
+                self.method_descriptors.free(descriptor_id.to_index());
+            }
+        }
+    }
+}
+
+/// Loaders that must agree with `loader` on a class name, `loader` included.
+fn constrained_loaders(pairs: &[LoaderPair], loader: Option<HeapRef>) -> Vec<Option<HeapRef>> {
+    let mut loaders = vec![loader];
+    let mut i = 0;
+    while i < loaders.len() {
+        let current = loaders[i];
+        for (loader1, loader2) in pairs {
+            let other = match current {
+                _ if *loader1 == current => *loader2,
+                _ if *loader2 == current => *loader1,
+                _ => continue,
+            };
+            if !loaders.contains(&other) {
+                loaders.push(other);
+            }
+        }
+        i += 1;
+    }
+    loaders
+}
+
+/// Class named by a type of a descriptor, the element class for arrays.
+fn class_name(java_type: &JavaType) -> Option<&str> {
+    match java_type {
+        JavaType::Instance(name) => Some(name),
+        JavaType::Array(element) => class_name(element),
+        _ => None,
+    }
 }
@@ -9,7 +9,10 @@ use std::collections::HashMap;
 use std::num::NonZeroU32;
 use std::sync::Arc;
 
+pub mod archive;
+pub mod gc;
 pub mod method_area;
+pub mod slots;
 
 // TODO: use u32 or usize for HeapRef?
 // TODO: add specific struct for heap reference, and allow only heap create instance
@@ -215,6 +218,10 @@ impl Heap {
         self.alloc_array_internal(class_id, length, AllocationType::Reference)
     }
 
+    pub fn is_reference_array(&self, heap_ref: HeapRef) -> Result<bool, JvmError> {
+        Ok(self.get_allocation_type(heap_ref)? == AllocationType::Reference)
+    }
+
     pub fn get_class_id(&self, heap_ref: HeapRef) -> Result<ClassId, JvmError> {
         let header = self.get_header(heap_ref);
         Ok(ClassId::new(header.class_id))
@@ -546,6 +553,44 @@ impl Heap {
         self.alloc_string_from_interned_with_char_mapping(val_sym, None)
     }
 
+    /// The interned strings, they live as long as the VM.
+    pub fn interned_strings(&self) -> impl Iterator<Item = HeapRef> + '_ {
+        self.string_pool.values().copied()
+    }
+
+    /// The interned strings with their values.
+    pub fn string_pool(&self) -> impl Iterator<Item = (Symbol, HeapRef)> + '_ {
+        self.string_pool.iter().map(|(sym, h)| (*sym, *h))
+    }
+
+    /// The allocated part of the heap, object headers included. References are offsets into
+    /// it, so it can be copied back as a whole by [`Heap::restore`].
+    pub fn allocated_memory(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.memory, self.allocated) }
+    }
+
+    /// Replaces the objects of the heap with `memory` taken from [`Heap::allocated_memory`]
+    /// and the interned strings with `string_pool`.
+    pub fn restore(
+        &mut self,
+        memory: &[u8],
+        string_pool: HashMap<Symbol, HeapRef>,
+    ) -> Result<(), JvmError> {
+        if memory.len() < ObjectHeader::SIZE || memory.len() > self.capacity {
+            return Err(JvmError::Todo(format!(
+                "Heap snapshot of {} bytes doesn't fit the heap of {} bytes",
+                memory.len(),
+                self.capacity
+            )));
+        }
+        unsafe {
+            std::ptr::copy_nonoverlapping(memory.as_ptr(), self.memory, memory.len());
+        }
+        self.allocated = memory.len();
+        self.string_pool = string_pool;
+        Ok(())
+    }
+
     pub fn get_str_from_pool_or_new(&mut self, val_sym: Symbol) -> Result<HeapRef, JvmError> {
         if let Some(h) = self.string_pool.get(&val_sym) {
             Ok(*h)
@@ -0,0 +1,207 @@
+use std::cell::UnsafeCell;
+use std::ops::Index;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Append-only table like `boxcar::Vec`, except that entries can be freed and their slots
+/// handed out again by later pushes. Entries never move, so references to them stay valid
+/// until they are freed.
+pub struct SlotVec<T> {
+    slots: boxcar::Vec<Slot<T>>,
+    free: Mutex<Vec<usize>>,
+}
+
+/// Readers only look at the value of a ready slot, so `push` can fill a freed slot while
+/// other threads iterate over the table.
+struct Slot<T> {
+    ready: AtomicBool,
+    value: UnsafeCell<Option<T>>,
+}
+
+impl<T> Slot<T> {
+    fn value(&self) -> Option<&T> {
+        if !self.ready.load(Ordering::Acquire) {
+            return None;
+        }
+        // Safety: a ready slot is only written by `free`, once nothing refers to the entry
+        unsafe { (*self.value.get()).as_ref() }
+    }
+}
+
+// Safety: a slot is written by `push` while it is not ready, and by `free`, whose caller
+// guarantees that nothing refers to the entry any more.
+unsafe impl<T: Send> Send for Slot<T> {}
+unsafe impl<T: Send + Sync> Sync for Slot<T> {}
+
+impl<T> SlotVec<T> {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            slots: boxcar::Vec::with_capacity(capacity),
+            free: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn push(&self, value: T) -> usize {
+        if let Some(index) = self.free.lock().unwrap().pop() {
+            let slot = &self.slots[index];
+            // Safety: readers skip the slot until it is ready again, and the free list hands
+            // the index to this push only
+            unsafe { *slot.value.get() = Some(value) };
+            slot.ready.store(true, Ordering::Release);
+            return index;
+        }
+        self.slots.push(Slot {
+            ready: AtomicBool::new(true),
+            value: UnsafeCell::new(Some(value)),
+        })
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.slots.get(index).and_then(Slot::value)
+    }
+
+    /// Drops the entry at `index` and makes its slot available to `push`.
+    ///
+    /// # Safety
+    /// No reference to the entry may be alive, and the index must not be used again until
+    /// `push` returns it.
+    pub unsafe fn free(&self, index: usize) -> Option<T> {
+        let slot = &self.slots[index];
+        if !slot.ready.swap(false, Ordering::AcqRel) {
+            return None;
+        }
+        let value = unsafe { (*slot.value.get()).take() };
+        self.free.lock().unwrap().push(index);
+        value
+    }
+
+    /// Number of entries that are not freed.
+    pub fn count(&self) -> usize {
+        self.slots.count() - self.free.lock().unwrap().len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &T)> {
+        self.slots
+            .iter()
+            .filter_map(|(index, slot)| slot.value().map(|v| (index, v)))
+    }
+}
+
+impl<T> Index<usize> for SlotVec<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        self.get(index)
+            .unwrap_or_else(|| panic!("slot {index} is freed"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn pushed_entries_get_consecutive_indices() {
+        // given
+        let slots = SlotVec::with_capacity(2);
+
+        // when
+        let first = slots.push("first");
+        let second = slots.push("second");
+
+        // then
+        assert_eq!((first, second), (0, 1));
+        assert_eq!(slots.get(first), Some(&"first"));
+        assert_eq!(slots[second], "second");
+        assert_eq!(slots.count(), 2);
+    }
+
+    #[test]
+    fn freed_entry_is_gone() {
+        // given
+        let slots = SlotVec::with_capacity(2);
+        let first = slots.push("first");
+        let second = slots.push("second");
+
+        // when
+        let freed = unsafe { slots.free(first) };
+
+        // then
+        assert_eq!(freed, Some("first"));
+        assert_eq!(slots.get(first), None);
+        assert_eq!(unsafe { slots.free(first) }, None);
+        assert_eq!(slots.count(), 1);
+        assert_eq!(slots.iter().collect::<Vec<_>>(), vec![(second, &"second")]);
+    }
+
+    #[test]
+    fn push_reuses_freed_slot() {
+        // given
+        let slots = SlotVec::with_capacity(3);
+        slots.push("first");
+        let second = slots.push("second");
+        slots.push("third");
+        unsafe { slots.free(second) };
+
+        // when
+        let reused = slots.push("fourth");
+        let appended = slots.push("fifth");
+
+        // then
+        assert_eq!(reused, second);
+        assert_eq!(slots[reused], "fourth");
+        assert_eq!(appended, 3);
+        assert_eq!(slots.count(), 4);
+    }
+
+    #[test]
+    fn reader_racing_a_push_sees_no_entry_or_the_whole_entry() {
+        // given
+        const ENTRIES: usize = 256;
+        const LEN: usize = 64;
+        let slots = Arc::new(SlotVec::with_capacity(ENTRIES));
+        for i in 0..ENTRIES {
+            slots.push(vec![i; LEN]);
+        }
+        for i in 0..ENTRIES {
+            unsafe { slots.free(i) };
+        }
+        let done = Arc::new(AtomicBool::new(false));
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let slots = slots.clone();
+                let done = done.clone();
+                std::thread::spawn(move || {
+                    while !done.load(Ordering::Acquire) {
+                        for (_, entry) in slots.iter() {
+                            assert!(entry.len() == LEN && entry.iter().all(|v| *v == entry[0]));
+                        }
+                        for index in 0..ENTRIES {
+                            if let Some(entry) = slots.get(index) {
+                                assert_eq!(entry.len(), LEN);
+                            }
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        // when
+        let indices: Vec<_> = (0..ENTRIES)
+            .map(|i| slots.push(vec![ENTRIES + i; LEN]))
+            .collect();
+        done.store(true, Ordering::Release);
+
+        // then
+        for reader in readers {
+            reader.join().unwrap();
+        }
+        let mut sorted = indices.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (0..ENTRIES).collect::<Vec<_>>());
+        for (i, index) in indices.iter().enumerate() {
+            assert_eq!(slots[*index], vec![ENTRIES + i; LEN]);
+        }
+    }
+}
@@ -1,10 +1,10 @@
 use crate::error::JvmError;
 use crate::interpreter::Interpreter;
 use crate::keys::{FieldKey, MethodKey};
-use crate::rt::constant_pool::RuntimeConstant;
+use crate::rt::constant_pool::entry::MethodEntryView;
 use crate::thread::JavaThreadState;
 use crate::vm::Value;
-use crate::{VirtualMachine, throw_exception};
+use crate::{MethodId, VirtualMachine, throw_exception};
 use common::instruction::{ArrayType, LookupSwitchData, TableSwitchData};
 use std::cmp::Ordering;
 use tracing_log::log::warn;
@@ -148,13 +148,8 @@ pub(super) fn handle_anewarray(
         throw_exception!(NegativeArraySizeException, size.to_string())?
     }
     let cur_frame_method_id = thread.stack.cur_java_frame()?.method_id();
-    let target_array_sym = vm
-        .method_area_read()
-        .get_cp_by_method_id(&cur_frame_method_id)?
-        .get_class_sym(&idx, vm.interner())?;
-    let target_array_class_id = vm
-        .method_area_write()
-        .get_class_id_or_load(target_array_sym, thread.id)?;
+    let target_array_class_id =
+        Interpreter::resolve_class_constant(thread, cur_frame_method_id, idx, vm)?;
     let array_ref = vm
         .heap_write()
         .alloc_object_array(target_array_class_id, size)?;
@@ -443,14 +438,13 @@ pub(super) fn handle_getfield(
     let target_obj_ref = thread.stack.pop_obj_val()?;
     let cur_frame_method_id = thread.stack.cur_java_frame()?.method_id();
     let field_view = vm
-        .method_area_read()
+        .method_area()
         .get_cp_by_method_id(&cur_frame_method_id)?
         .get_field_view(&idx, vm.interner())?;
-    let target_class_id = vm
-        .method_area_write()
-        .get_class_id_or_load(field_view.class_sym, thread.id)?;
+    let target_class_id =
+        Interpreter::resolve_class_constant(thread, cur_frame_method_id, idx, vm)?;
     let (target_field_offset, target_field_descriptor_id) = {
-        let ma = vm.method_area_read();
+        let ma = vm.method_area();
         let target_field =
             ma.get_instance_field(&target_class_id, &field_view.name_and_type.into())?;
         (target_field.offset, target_field.descriptor_id)
@@ -458,7 +452,7 @@ pub(super) fn handle_getfield(
     let value = vm.heap_read().read_field(
         target_obj_ref,
         target_field_offset,
-        vm.method_area_read()
+        vm.method_area()
             .get_field_descriptor(&target_field_descriptor_id)
             .as_allocation_type(),
     )?;
@@ -473,19 +467,18 @@ pub(super) fn handle_getstatic(
 ) -> Result<(), JvmError> {
     let cur_frame_method_id = thread.stack.cur_java_frame()?.method_id();
     let target_field_view = vm
-        .method_area_read()
+        .method_area()
         .get_cp_by_method_id(&cur_frame_method_id)?
         .get_field_view(&idx, vm.interner())?;
-    let target_class_id = vm
-        .method_area_write()
-        .get_class_id_or_load(target_field_view.class_sym, thread.id)?;
+    let target_class_id =
+        Interpreter::resolve_class_constant(thread, cur_frame_method_id, idx, vm)?;
     Interpreter::ensure_initialized(thread, Some(target_class_id), vm)?;
     let field_key: FieldKey = target_field_view.name_and_type.into();
     let actual_static_field_class_id = vm
-        .method_area_read()
+        .method_area()
         .resolve_static_field_actual_class_id(target_class_id, &field_key)?;
     let value = vm
-        .method_area_read()
+        .method_area()
         .get_static_field_value(&actual_static_field_class_id, &field_key)?;
     thread.stack.push_operand(value)
 }
@@ -906,17 +899,28 @@ pub(super) fn handle_invokevirtual(
 ) -> Result<(), JvmError> {
     let cur_frame_method_id = thread.stack.cur_java_frame()?.method_id();
     let target_method_view = vm
-        .method_area_read()
+        .method_area()
         .get_cp_by_method_id(&cur_frame_method_id)?
         .get_method_view(&idx, vm.interner())?;
+    if let Some(method_id) = signature_polymorphic_method(thread, vm, idx, &target_method_view)? {
+        return Interpreter::invoke_signature_polymorphic(
+            thread,
+            idx,
+            method_id,
+            target_method_view.name_and_type.descriptor_sym,
+            vm,
+        );
+    }
+    // only the accessibility of the class matters, the method is selected by the receiver
+    Interpreter::resolve_class_constant(thread, cur_frame_method_id, idx, vm)?;
     let method_key: MethodKey = target_method_view.name_and_type.into();
 
     let target_method_desc_id = vm
-        .method_area_write()
+        .method_area()
         .get_or_new_method_descriptor_id(&method_key.desc)
         .unwrap();
     let arg_count = vm
-        .method_area_read()
+        .method_area()
         .get_method_descriptor(&target_method_desc_id)
         .params
         .len()
@@ -926,7 +930,7 @@ pub(super) fn handle_invokevirtual(
     let actual_class_id = vm.heap_read().get_class_id(object_ref)?;
 
     let target_method_id = vm
-        .method_area_read()
+        .method_area()
         .get_class(&actual_class_id)
         .get_vtable_method_id(&method_key)?;
     let args = Interpreter::prepare_method_args(thread, target_method_id, vm)?;
@@ -941,16 +945,19 @@ pub(super) fn handle_instanceof(
 ) -> Result<(), JvmError> {
     let cur_frame_method_id = thread.stack.cur_java_frame()?.method_id();
     let class_name_sym = vm
-        .method_area_read()
+        .method_area()
         .get_cp_by_method_id(&cur_frame_method_id)?
         .get_class_sym(&idx, vm.interner())?;
 
     let obj_ref = thread.stack.pop_nullable_ref_val()?;
     if let Some(obj_ref) = obj_ref {
         let target_class = vm.heap_read().get_class_id(obj_ref)?;
+        let loader = vm
+            .method_area()
+            .get_class_loader(vm.method_area().get_method(&cur_frame_method_id).class_id());
         let res = vm
-            .method_area_read()
-            .instance_of(target_class, class_name_sym);
+            .method_area()
+            .instance_of(target_class, loader, class_name_sym);
         thread
             .stack
             .push_operand(Value::Integer(if res { 1 } else { 0 }))
@@ -1201,33 +1208,7 @@ pub(super) fn handle_ldc_ldcw_ldc2w(
     idx: u16,
 ) -> Result<(), JvmError> {
     let cur_method_id = thread.stack.cur_java_frame()?.method_id();
-    let ldc_operand = {
-        let ma = vm.method_area_read();
-        let cp = ma.get_cp_by_method_id(&cur_method_id)?;
-        match cp.get_constant(&idx, vm.interner())? {
-            RuntimeConstant::Integer(val) => Value::Integer(*val),
-            RuntimeConstant::Float(val) => Value::Float(*val),
-            RuntimeConstant::Long(val) => Value::Long(*val),
-            RuntimeConstant::Double(val) => Value::Double(*val),
-            RuntimeConstant::Class(class_entry) => {
-                let class_name_sym = class_entry.get_name_sym()?;
-                drop(ma);
-                let class_id = vm
-                    .method_area_write()
-                    .get_class_id_or_load(class_name_sym, thread.id)?;
-                Value::Ref(
-                    vm.method_area_write()
-                        .get_mirror_ref_or_create(class_id, &vm.heap)?,
-                )
-            }
-            RuntimeConstant::String(str_entry) => {
-                let string_sym = str_entry.get_string_sym()?;
-                let string_ref = vm.heap_write().get_str_from_pool_or_new(string_sym)?;
-                Value::Ref(string_ref)
-            }
-            _ => unimplemented!(),
-        }
-    };
+    let ldc_operand = Interpreter::loadable_constant(thread, cur_method_id, idx, vm)?;
     thread.stack.push_operand(ldc_operand)
 }
 
@@ -1238,16 +1219,11 @@ pub(super) fn handle_new(
     idx: u16,
 ) -> Result<(), JvmError> {
     let cur_frame_method_id = thread.stack.cur_java_frame()?.method_id();
-    let target_class_name = vm
-        .method_area_read()
-        .get_cp_by_method_id(&cur_frame_method_id)?
-        .get_class_sym(&idx, vm.interner())?;
-    let target_class_id = vm
-        .method_area_write()
-        .get_class_id_or_load(target_class_name, thread.id)?;
+    let target_class_id =
+        Interpreter::resolve_class_constant(thread, cur_frame_method_id, idx, vm)?;
     Interpreter::ensure_initialized(thread, Some(target_class_id), vm)?;
     let instance_ref = vm.heap_write().alloc_instance(
-        vm.method_area_read()
+        vm.method_area()
             .get_instance_class(&target_class_id)?
             .get_instance_size()?,
         target_class_id,
@@ -1265,7 +1241,7 @@ pub(super) fn handle_newarray(
     if size < 0 {
         throw_exception!(NegativeArraySizeException, size.to_string())?
     }
-    let class_id = vm.method_area_write().load_array_class(
+    let class_id = vm.method_area().load_array_class(
         vm.interner().get_or_intern(array_type.descriptor()),
         thread.id,
     )?;
@@ -1291,14 +1267,13 @@ pub(super) fn handle_putfield(
     let target_obj_ref = thread.stack.pop_obj_val()?;
     let cur_frame_method_id = thread.stack.cur_java_frame()?.method_id();
     let field_view = vm
-        .method_area_read()
+        .method_area()
         .get_cp_by_method_id(&cur_frame_method_id)?
         .get_field_view(&idx, vm.interner())?;
-    let target_class_id = vm
-        .method_area_write()
-        .get_class_id_or_load(field_view.class_sym, thread.id)?;
+    let target_class_id =
+        Interpreter::resolve_class_constant(thread, cur_frame_method_id, idx, vm)?;
     let (target_field_offset, target_field_descriptor_id) = {
-        let ma = vm.method_area_read();
+        let ma = vm.method_area();
         let target_field =
             ma.get_instance_field(&target_class_id, &field_view.name_and_type.into())?;
         (target_field.offset, target_field.descriptor_id)
@@ -1307,7 +1282,7 @@ pub(super) fn handle_putfield(
         target_obj_ref,
         target_field_offset,
         value,
-        vm.method_area_read()
+        vm.method_area()
             .get_field_descriptor(&target_field_descriptor_id)
             .as_allocation_type(),
     )
@@ -1322,18 +1297,17 @@ pub(super) fn handle_putstatic(
     let value = thread.stack.pop_operand()?;
     let cur_frame_method_id = thread.stack.cur_java_frame()?.method_id();
     let target_field_view = vm
-        .method_area_read()
+        .method_area()
         .get_cp_by_method_id(&cur_frame_method_id)?
         .get_field_view(&idx, vm.interner())?;
-    let target_class_id = vm
-        .method_area_write()
-        .get_class_id_or_load(target_field_view.class_sym, thread.id)?;
+    let target_class_id =
+        Interpreter::resolve_class_constant(thread, cur_frame_method_id, idx, vm)?;
     Interpreter::ensure_initialized(thread, Some(target_class_id), vm)?;
     let field_key: FieldKey = target_field_view.name_and_type.into();
     let actual_static_field_class_id = vm
-        .method_area_read()
+        .method_area()
         .resolve_static_field_actual_class_id(target_class_id, &field_key)?;
-    vm.method_area_read()
+    vm.method_area()
         .get_class_like(&actual_static_field_class_id)?
         .set_static_field_value(&field_key, value)
 }
@@ -1347,7 +1321,7 @@ pub(super) fn handle_invokeinterface(
 ) -> Result<(), JvmError> {
     let cur_frame_method_id = thread.stack.cur_java_frame()?.method_id();
     let target_method_view = vm
-        .method_area_read()
+        .method_area()
         .get_cp_by_method_id(&cur_frame_method_id)?
         .get_interface_method_view(&idx, vm.interner())?;
     let object_ref = thread
@@ -1365,9 +1339,10 @@ pub(super) fn handle_invokeinterface(
             let _ = thread.stack.pop_operand()?;
         }
     } else {
+        Interpreter::resolve_class_constant(thread, cur_frame_method_id, idx, vm)?;
         let target_class_id = vm.heap_read().get_class_id(object_ref)?;
         let target_method_id = vm
-            .method_area_read()
+            .method_area()
             .get_instance_class(&target_class_id)?
             .get_interface_method_id(&target_method_view.name_and_type.into())?;
         let args = Interpreter::prepare_method_args(thread, target_method_id, vm)?;
@@ -1384,14 +1359,13 @@ pub(super) fn handle_invokespecial(
 ) -> Result<(), JvmError> {
     let cur_frame_method_id = thread.stack.cur_java_frame()?.method_id();
     let target_method_view = vm
-        .method_area_read()
+        .method_area()
         .get_cp_by_method_id(&cur_frame_method_id)?
         .get_method_view(&idx, vm.interner())?;
-    let target_class_id = vm
-        .method_area_write()
-        .get_class_id_or_load(target_method_view.class_sym, thread.id)?;
+    let target_class_id =
+        Interpreter::resolve_class_constant(thread, cur_frame_method_id, idx, vm)?;
     let target_method_id = vm
-        .method_area_read()
+        .method_area()
         .get_instance_class(&target_class_id)?
         .get_special_method_id(&target_method_view.name_and_type.into())?;
     let args = Interpreter::prepare_method_args(thread, target_method_id, vm)?;
@@ -1406,20 +1380,48 @@ pub(super) fn handle_invokestatic(
 ) -> Result<(), JvmError> {
     let cur_frame_method_id = thread.stack.cur_java_frame()?.method_id();
     let target_method_view = vm
-        .method_area_read()
+        .method_area()
         .get_cp_by_method_id(&cur_frame_method_id)?
         .get_method_or_interface_method_view(&idx, vm.interner())?;
-    let target_class_id = vm
-        .method_area_write()
-        .get_class_id_or_load(target_method_view.class_sym, thread.id)?;
+    if let Some(method_id) = signature_polymorphic_method(thread, vm, idx, &target_method_view)? {
+        return Interpreter::invoke_signature_polymorphic(
+            thread,
+            idx,
+            method_id,
+            target_method_view.name_and_type.descriptor_sym,
+            vm,
+        );
+    }
+    let target_class_id =
+        Interpreter::resolve_class_constant(thread, cur_frame_method_id, idx, vm)?;
     Interpreter::ensure_initialized(thread, Some(target_class_id), vm)?;
     let target_method_id = vm
-        .method_area_read()
+        .method_area()
         .get_static_method_id(&target_class_id, target_method_view.name_and_type.into())?;
     let args = Interpreter::prepare_method_args(thread, target_method_id, vm)?;
     Interpreter::invoke_static_method(thread, target_method_id, vm, args)
 }
 
+/// The signature polymorphic method of `MethodHandle` or `VarHandle` the method constant at `idx`
+/// names, whatever its descriptor.
+fn signature_polymorphic_method(
+    thread: &mut JavaThreadState,
+    vm: &VirtualMachine,
+    idx: u16,
+    method_view: &MethodEntryView,
+) -> Result<Option<MethodId>, JvmError> {
+    if method_view.class_sym != vm.br().java_lang_invoke_method_handle_sym
+        && method_view.class_sym != vm.br().java_lang_invoke_var_handle_sym
+    {
+        return Ok(None);
+    }
+    let cur_frame_method_id = thread.stack.cur_java_frame()?.method_id();
+    let class_id = Interpreter::resolve_class_constant(thread, cur_frame_method_id, idx, vm)?;
+    Ok(vm
+        .method_area()
+        .find_signature_polymorphic_method(&class_id, method_view.name_and_type.name_sym))
+}
+
 #[inline]
 pub(super) fn handle_invokedynamic(
     thread: &mut JavaThreadState,
@@ -1428,7 +1430,7 @@ pub(super) fn handle_invokedynamic(
 ) -> Result<(), JvmError> {
     let cur_frame_method_id = thread.stack.cur_java_frame()?.method_id();
     let bootstrap_method = vm
-        .method_area_read()
+        .method_area()
         .get_cp_by_method_id(&cur_frame_method_id)?
         .get_invoke_dynamic_view(&idx, vm.interner())?;
     todo!()
@@ -1614,13 +1616,19 @@ pub(super) fn handle_tableswitch(
 }
 
 #[inline]
-pub(super) fn handle_monitorenter(thread: &mut JavaThreadState) -> Result<(), JvmError> {
-    let _obj = thread.stack.pop_obj_val()?;
-    Ok(())
+pub(super) fn handle_monitorenter(
+    thread: &mut JavaThreadState,
+    vm: &VirtualMachine,
+) -> Result<(), JvmError> {
+    let obj = thread.stack.pop_obj_val()?;
+    Interpreter::monitor_enter(thread, obj, vm)
 }
 
 #[inline]
-pub(super) fn handle_monitorexit(thread: &mut JavaThreadState) -> Result<(), JvmError> {
-    let _obj = thread.stack.pop_obj_val()?;
-    Ok(())
+pub(super) fn handle_monitorexit(
+    thread: &mut JavaThreadState,
+    vm: &VirtualMachine,
+) -> Result<(), JvmError> {
+    let obj = thread.stack.pop_obj_val()?;
+    vm.monitors.exit(thread.id, obj)
 }
@@ -0,0 +1,298 @@
+use crate::error::JvmError;
+use crate::heap::HeapRef;
+use crate::interpreter::Interpreter;
+use crate::keys::ClassId;
+use crate::rt::{ClassState, format};
+use crate::thread::JavaThreadState;
+use crate::vm::Value;
+use crate::{MethodId, Symbol, VirtualMachine, build_exception};
+use common::error::LinkageError;
+use jclass::ClassFile;
+
+impl Interpreter {
+    /// https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-5.html#jvms-5.3
+    ///
+    /// Loads `name_sym` through `loader`. The builtin loaders are implemented by the VM,
+    /// user-defined loaders are asked with `loadClass(String)` and recorded as an initiating
+    /// loader of the class they return.
+    pub fn load_class(
+        thread: &mut JavaThreadState,
+        loader: Option<HeapRef>,
+        name_sym: Symbol,
+        vm: &VirtualMachine,
+    ) -> Result<ClassId, JvmError> {
+        let ma = vm.method_area();
+        if let Some(class_id) = ma.find_loaded_class(loader, name_sym) {
+            return Ok(class_id);
+        }
+        let Some(loader_ref) = loader.filter(|l| !ma.is_builtin_loader(Some(*l))) else {
+            return ma.get_class_id_or_load(name_sym, thread.id);
+        };
+
+        let name = vm.interner().resolve(&name_sym);
+        let class_id = match name.strip_prefix('[') {
+            Some(element) => Self::load_array_class(thread, loader_ref, name_sym, element, vm)?,
+            None => Self::invoke_load_class(thread, loader_ref, name_sym, vm)?,
+        };
+        ma.record_initiating_loader(loader_ref, name_sym, class_id)?;
+        Ok(class_id)
+    }
+
+    /// §5.3.3: the element class is loaded through `loader`, the array class belongs to the
+    /// defining loader of the element class.
+    fn load_array_class(
+        thread: &mut JavaThreadState,
+        loader: HeapRef,
+        name_sym: Symbol,
+        element: &str,
+        vm: &VirtualMachine,
+    ) -> Result<ClassId, JvmError> {
+        let element_name = if element.starts_with('[') {
+            element
+        } else if let Some(class_name) = element.strip_prefix('L') {
+            class_name.trim_end_matches(';')
+        } else {
+            // arrays of primitives are defined by the bootstrap loader
+            return vm.method_area().get_class_id_or_load(name_sym, thread.id);
+        };
+        let element_sym = vm.interner().get_or_intern(element_name);
+        let element_class_id = Self::load_class(thread, Some(loader), element_sym, vm)?;
+
+        let ma = vm.method_area();
+        match ma.get_class_loader(element_class_id) {
+            Some(element_loader) if !ma.is_builtin_loader(Some(element_loader)) => {
+                ma.load_array_class_in(element_loader, name_sym, element_class_id, thread.id)
+            }
+            _ => ma.get_class_id_or_load(name_sym, thread.id),
+        }
+    }
+
+    fn invoke_load_class(
+        thread: &mut JavaThreadState,
+        loader: HeapRef,
+        name_sym: Symbol,
+        vm: &VirtualMachine,
+    ) -> Result<ClassId, JvmError> {
+        let dotted_name = vm.symbol_to_pretty_string(name_sym);
+        let name_ref = vm.heap_write().alloc_string(&dotted_name)?;
+        let loader_class_id = vm.heap_read().get_class_id(loader)?;
+        let load_class_method_id = vm
+            .method_area()
+            .get_class(&loader_class_id)
+            .get_vtable_method_id(&vm.br().class_loader_load_class_mk)?;
+        let mirror = Self::invoke_instance_method(
+            thread,
+            load_class_method_id,
+            vm,
+            vec![Value::Ref(loader), Value::Ref(name_ref)],
+        )?
+        .map(|value| value.as_nullable_obj_ref())
+        .transpose()?
+        .flatten()
+        .ok_or_else(|| build_exception!(NoClassDefFoundError, dotted_name.clone()))?;
+
+        let class_id = vm.method_area().get_class_id_by_mirror(&mirror)?;
+        let actual_name = vm.method_area().get_class(&class_id).get_name();
+        if actual_name != name_sym {
+            return Err(build_exception!(
+                NoClassDefFoundError,
+                "{} (wrong name: {})",
+                vm.interner().resolve(&name_sym),
+                vm.interner().resolve(&actual_name)
+            ));
+        }
+        Ok(class_id)
+    }
+
+    /// Loads the class of a field descriptor, or of `V`, through `loader`. Primitive types give
+    /// their primitive class.
+    pub fn load_descriptor_class(
+        thread: &mut JavaThreadState,
+        loader: Option<HeapRef>,
+        desc: &str,
+        vm: &VirtualMachine,
+    ) -> Result<ClassId, JvmError> {
+        let name = match desc {
+            "Z" => "boolean",
+            "B" => "byte",
+            "C" => "char",
+            "S" => "short",
+            "I" => "int",
+            "J" => "long",
+            "F" => "float",
+            "D" => "double",
+            "V" => "void",
+            _ => {
+                let name = desc
+                    .strip_prefix('L')
+                    .and_then(|name| name.strip_suffix(';'))
+                    .unwrap_or(desc);
+                return Self::load_class(thread, loader, vm.interner().get_or_intern(name), vm);
+            }
+        };
+        vm.method_area()
+            .get_class_id_or_load(vm.interner().get_or_intern(name), thread.id)
+    }
+
+    /// Loads the classes of the parameter types and the return type of a method descriptor
+    /// through `loader`, the return type last.
+    pub fn load_method_descriptor_classes(
+        thread: &mut JavaThreadState,
+        loader: Option<HeapRef>,
+        desc: &str,
+        vm: &VirtualMachine,
+    ) -> Result<Vec<ClassId>, JvmError> {
+        let mut class_ids = Vec::new();
+        let mut rest = desc.strip_prefix('(').unwrap_or(desc);
+        while !rest.is_empty() {
+            if let Some(return_type) = rest.strip_prefix(')') {
+                rest = return_type;
+                continue;
+            }
+            let dims = rest.bytes().take_while(|b| *b == b'[').count();
+            let len = match rest.as_bytes().get(dims) {
+                Some(b'L') => rest.find(';').map_or(rest.len(), |end| end + 1),
+                Some(_) => dims + 1,
+                None => rest.len(),
+            };
+            class_ids.push(Self::load_descriptor_class(
+                thread,
+                loader,
+                &rest[..len],
+                vm,
+            )?);
+            rest = &rest[len..];
+        }
+        Ok(class_ids)
+    }
+
+    /// Loads a class named by `accessor` through the defining loader of `accessor` and checks
+    /// that `accessor` may access it. A class naming itself gets itself, the only way a hidden
+    /// class can be resolved.
+    pub fn resolve_class(
+        thread: &mut JavaThreadState,
+        accessor: ClassId,
+        name_sym: Symbol,
+        vm: &VirtualMachine,
+    ) -> Result<ClassId, JvmError> {
+        if vm.method_area().get_class(&accessor).get_name() == name_sym {
+            return Ok(accessor);
+        }
+        let loader = vm.method_area().get_class_loader(accessor);
+        let class_id = Self::load_class(thread, loader, name_sym, vm)?;
+        vm.method_area().check_class_access(accessor, class_id)?;
+        Ok(class_id)
+    }
+
+    /// Resolves the class named by the class, field or method constant at `idx` in the pool of
+    /// `method_id`, see [`Self::resolve_class`]. The class is loaded and access checked once per
+    /// class constant, a failed resolution is retried.
+    pub fn resolve_class_constant(
+        thread: &mut JavaThreadState,
+        method_id: MethodId,
+        idx: u16,
+        vm: &VirtualMachine,
+    ) -> Result<ClassId, JvmError> {
+        let ma = vm.method_area();
+        let cp = ma.get_cp_by_method_id(&method_id)?;
+        let class_idx = cp.get_class_idx(&idx)?;
+        if let Some(class_id) = cp.get_resolved_class(&class_idx) {
+            return Ok(class_id);
+        }
+        let name_sym = cp.get_class_sym(&class_idx, vm.interner())?;
+        let accessor = ma.get_method(&method_id).class_id();
+        let class_id = Self::resolve_class(thread, accessor, name_sym, vm)?;
+        Ok(cp.set_resolved_class(class_idx, class_id))
+    }
+
+    /// Finishes linking a class whose verification constraints name classes its defining loader
+    /// hadn't loaded, by loading them through that loader now that the class is published. A
+    /// class that fails stays `Loaded`, and throws the `VerifyError` again on the next attempt.
+    pub(super) fn link_class(
+        thread: &mut JavaThreadState,
+        class_id: ClassId,
+        vm: &VirtualMachine,
+    ) -> Result<(), JvmError> {
+        let ma = vm.method_area();
+        let class = ma.get_class_like(&class_id)?;
+        if class.state() != ClassState::Loaded {
+            return Ok(());
+        }
+        for constraint in class.verification_constraints() {
+            let from = Self::load_class(thread, class.loader(), constraint.from, vm)?;
+            let to = Self::load_class(thread, class.loader(), constraint.to, vm)?;
+            constraint.check(from, to, class.name(), ma)?;
+        }
+        class.set_linked();
+        Ok(())
+    }
+
+    /// `ClassLoader.defineClass`: parses `bytes` and defines the class with `loader` as its
+    /// defining loader, once its superclass and superinterfaces are loaded through `loader`.
+    /// `expected_name` is the binary name the caller asked for, if any.
+    pub fn define_class(
+        thread: &mut JavaThreadState,
+        loader: Option<HeapRef>,
+        expected_name: Option<&str>,
+        bytes: Vec<u8>,
+        vm: &VirtualMachine,
+    ) -> Result<ClassId, JvmError> {
+        let cf = format::parse_class_file(bytes)?;
+        format::check_format(&cf)?;
+        let actual_name = cf.get_class_name().map_err(LinkageError::from)?;
+        if let Some(expected_name) = expected_name.map(|name| name.replace('.', "/"))
+            && expected_name != actual_name
+        {
+            return Err(build_exception!(
+                NoClassDefFoundError,
+                "{expected_name} (wrong name: {actual_name})"
+            ));
+        }
+
+        let class_id = vm
+            .method_area()
+            .define_class(loader, cf, thread.id, |cf: &ClassFile| {
+                Self::load_supertypes(thread, loader, cf, vm)
+            })?;
+        Self::link_class(thread, class_id, vm)?;
+        Ok(class_id)
+    }
+
+    /// `Lookup.defineHiddenClass`: like [`Self::define_class`], but the class gets a name of its
+    /// own and isn't entered in the namespace of `loader`.
+    pub fn define_hidden_class(
+        thread: &mut JavaThreadState,
+        loader: Option<HeapRef>,
+        bytes: Vec<u8>,
+        vm: &VirtualMachine,
+    ) -> Result<ClassId, JvmError> {
+        let cf = format::parse_class_file(bytes)?;
+        format::check_format(&cf)?;
+        let super_id = Self::load_supertypes(thread, loader, &cf, vm)?;
+        vm.method_area()
+            .define_hidden_class(loader, cf, super_id, thread.id)
+    }
+
+    /// Loads the superinterfaces and the superclass of a class to define through `loader`,
+    /// returns the superclass.
+    fn load_supertypes(
+        thread: &mut JavaThreadState,
+        loader: Option<HeapRef>,
+        cf: &ClassFile,
+        vm: &VirtualMachine,
+    ) -> Result<Option<ClassId>, JvmError> {
+        for interface in &cf.interfaces {
+            let name = cf
+                .cp
+                .get_class_name(interface)
+                .map_err(LinkageError::from)?;
+            Self::load_class(thread, loader, vm.interner().get_or_intern(name), vm)?;
+        }
+        cf.get_super_class_name()
+            .map(|name| {
+                let name = name.map_err(LinkageError::from)?;
+                Self::load_class(thread, loader, vm.interner().get_or_intern(name), vm)
+            })
+            .transpose()
+    }
+}
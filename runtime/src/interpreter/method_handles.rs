@@ -0,0 +1,609 @@
+use crate::error::JvmError;
+use crate::heap::HeapRef;
+use crate::interpreter::Interpreter;
+use crate::keys::{ClassId, MethodKey, Symbol};
+use crate::native::reflection::{box_value, unbox};
+use crate::rt::constant_pool::entry::MethodHandleEntryView;
+use crate::rt::constant_pool::{LinkedCallSite, RuntimeConstant};
+use crate::thread::JavaThreadState;
+use crate::vm::Value;
+use crate::{MethodId, VirtualMachine, build_exception, throw_exception};
+use common::jtype::{AllocationType, JavaType, PrimitiveType, ReturnType};
+
+/// `java.lang.invoke.MethodHandleNatives.Constants.REF_invokeVirtual`
+const REF_INVOKE_VIRTUAL: i32 = 5;
+
+impl Interpreter {
+    /// The `MethodType` of a method descriptor, its classes loaded through the defining loader
+    /// of `accessor`.
+    pub fn method_type(
+        thread: &mut JavaThreadState,
+        accessor: ClassId,
+        desc: Symbol,
+        vm: &VirtualMachine,
+    ) -> Result<HeapRef, JvmError> {
+        let loader = vm.method_area().get_class_loader(accessor);
+        let mut class_ids =
+            Self::load_method_descriptor_classes(thread, loader, vm.interner().resolve(&desc), vm)?;
+        let return_type = class_ids.pop().ok_or_else(|| {
+            JvmError::Todo(format!(
+                "Invalid method descriptor {}",
+                vm.interner().resolve(&desc)
+            ))
+        })?;
+        let mirrors = class_ids
+            .into_iter()
+            .map(|class_id| mirror(vm, class_id))
+            .collect::<Result<Vec<_>, _>>()?;
+        let param_types = object_array(vm, thread, "[Ljava/lang/Class;", mirrors)?;
+        let method_type = Self::invoke_method_handle_natives(
+            thread,
+            "findMethodHandleType",
+            "(Ljava/lang/Class;[Ljava/lang/Class;)Ljava/lang/invoke/MethodType;",
+            vec![mirror(vm, return_type)?, Value::Ref(param_types)],
+            vm,
+        )?;
+        method_type.as_obj_ref()
+    }
+
+    /// The `MethodHandle` of a method handle constant of `accessor`, made by
+    /// `MethodHandleNatives.linkMethodHandleConstant`.
+    pub fn method_handle(
+        thread: &mut JavaThreadState,
+        accessor: ClassId,
+        view: MethodHandleEntryView,
+        vm: &VirtualMachine,
+    ) -> Result<HeapRef, JvmError> {
+        let (ref_kind, class_sym, name_and_type, is_field) = match view {
+            MethodHandleEntryView::GetField(field) => {
+                (1, field.class_sym, field.name_and_type, true)
+            }
+            MethodHandleEntryView::GetStatic(field) => {
+                (2, field.class_sym, field.name_and_type, true)
+            }
+            MethodHandleEntryView::PutField(field) => {
+                (3, field.class_sym, field.name_and_type, true)
+            }
+            MethodHandleEntryView::PutStatic(field) => {
+                (4, field.class_sym, field.name_and_type, true)
+            }
+            MethodHandleEntryView::InvokeVirtual(method) => {
+                (5, method.class_sym, method.name_and_type, false)
+            }
+            MethodHandleEntryView::InvokeStatic(method) => {
+                (6, method.class_sym, method.name_and_type, false)
+            }
+            MethodHandleEntryView::InvokeSpecial(method) => {
+                (7, method.class_sym, method.name_and_type, false)
+            }
+            MethodHandleEntryView::NewInvokeSpecial(method) => {
+                (8, method.class_sym, method.name_and_type, false)
+            }
+            MethodHandleEntryView::InvokeInterface(method) => {
+                (9, method.class_sym, method.name_and_type, false)
+            }
+        };
+        let defc = Self::resolve_class(thread, accessor, class_sym, vm)?;
+        let member_type = if is_field {
+            let loader = vm.method_area().get_class_loader(accessor);
+            let desc = vm.interner().resolve(&name_and_type.descriptor_sym);
+            mirror(vm, Self::load_descriptor_class(thread, loader, desc, vm)?)?
+        } else {
+            Value::Ref(Self::method_type(
+                thread,
+                accessor,
+                name_and_type.descriptor_sym,
+                vm,
+            )?)
+        };
+        let method_handle = Self::invoke_method_handle_natives(
+            thread,
+            "linkMethodHandleConstant",
+            "(Ljava/lang/Class;ILjava/lang/Class;Ljava/lang/String;Ljava/lang/Object;)Ljava/lang/invoke/MethodHandle;",
+            vec![
+                mirror(vm, accessor)?,
+                Value::Integer(ref_kind),
+                mirror(vm, defc)?,
+                Value::Ref(
+                    vm.heap_write()
+                        .get_str_from_pool_or_new(name_and_type.name_sym)?,
+                ),
+                member_type,
+            ],
+            vm,
+        )?;
+        method_handle.as_obj_ref()
+    }
+
+    /// The value of the loadable constant `idx` in the constant pool of `method_id` (§5.1), as
+    /// `ldc` pushes it.
+    pub(super) fn loadable_constant(
+        thread: &mut JavaThreadState,
+        method_id: MethodId,
+        idx: u16,
+        vm: &VirtualMachine,
+    ) -> Result<Value, JvmError> {
+        let ma = vm.method_area();
+        let cp = ma.get_cp_by_method_id(&method_id)?;
+        let accessor_id = ma.get_method(&method_id).class_id();
+        let value = match cp.get_constant(&idx, vm.interner())? {
+            RuntimeConstant::Integer(val) => Value::Integer(*val),
+            RuntimeConstant::Float(val) => Value::Float(*val),
+            RuntimeConstant::Long(val) => Value::Long(*val),
+            RuntimeConstant::Double(val) => Value::Double(*val),
+            RuntimeConstant::Class(_) => mirror(
+                vm,
+                Self::resolve_class_constant(thread, method_id, idx, vm)?,
+            )?,
+            RuntimeConstant::String(str_entry) => {
+                let string_sym = str_entry.get_string_sym()?;
+                Value::Ref(vm.heap_write().get_str_from_pool_or_new(string_sym)?)
+            }
+            RuntimeConstant::MethodType(_) => match cp.get_resolved_reference(&idx) {
+                Some(method_type_ref) => Value::Ref(method_type_ref),
+                None => {
+                    let desc_sym = cp.get_method_type_sym(&idx, vm.interner())?;
+                    let method_type_ref = Self::method_type(thread, accessor_id, desc_sym, vm)?;
+                    Value::Ref(cp.set_resolved_reference(idx, method_type_ref))
+                }
+            },
+            RuntimeConstant::MethodHandle(_) => match cp.get_resolved_reference(&idx) {
+                Some(method_handle_ref) => Value::Ref(method_handle_ref),
+                None => {
+                    let view = cp.get_method_handle_view(&idx, vm.interner())?;
+                    let method_handle_ref = Self::method_handle(thread, accessor_id, view, vm)?;
+                    Value::Ref(cp.set_resolved_reference(idx, method_handle_ref))
+                }
+            },
+            RuntimeConstant::Dynamic(_) => {
+                let desc_sym = cp
+                    .get_dynamic_view(&idx, vm.interner())?
+                    .nat_view
+                    .descriptor_sym;
+                let value = Self::dynamic_constant(thread, method_id, idx, vm)?;
+                match JavaType::try_from(vm.interner().resolve(&desc_sym))? {
+                    JavaType::Primitive(_) => match value.as_nullable_obj_ref()? {
+                        Some(boxed) => match unbox(vm, boxed)? {
+                            Some((_, primitive)) => primitive,
+                            None => throw_exception!(
+                                InternalError,
+                                "Dynamic constant of primitive type is not a box"
+                            )?,
+                        },
+                        None => throw_exception!(NullPointerException)?,
+                    },
+                    _ => value,
+                }
+            }
+            other => throw_exception!(
+                IncompatibleClassChangeError,
+                "{} constant at {} is not loadable",
+                other.get_type(),
+                idx
+            )?,
+        };
+        Ok(value)
+    }
+
+    /// The object the dynamically-computed constant `idx` in the constant pool of `method_id`
+    /// resolved to (§5.4.3.6), a box for a primitive type. It is resolved once by calling its
+    /// bootstrap method through `MethodHandleNatives.linkDynamicConstant`, a constant whose
+    /// resolution needs its own value fails with `StackOverflowError`. A resolution failing with
+    /// a `LinkageError` is not retried, later attempts throw the same error.
+    fn dynamic_constant(
+        thread: &mut JavaThreadState,
+        method_id: MethodId,
+        idx: u16,
+        vm: &VirtualMachine,
+    ) -> Result<Value, JvmError> {
+        let cp = vm.method_area().get_cp_by_method_id(&method_id)?;
+        if let Some(value) = cp.get_resolved_dynamic_constant(&idx) {
+            return Ok(value);
+        }
+        if let Some(error) = cp.get_dynamic_constant_error(&idx) {
+            return Err(JvmError::JavaExceptionThrown(error));
+        }
+        if !cp.begin_dynamic_constant_resolution(idx, thread.id) {
+            throw_exception!(StackOverflowError)?
+        }
+        let value = Self::link_dynamic_constant(thread, method_id, idx, vm);
+        cp.end_dynamic_constant_resolution(idx, thread.id);
+        match value {
+            Ok(value) => Ok(cp.set_resolved_dynamic_constant(idx, value)),
+            Err(e) => Err(Self::record_dynamic_constant_error(
+                thread, method_id, idx, e, vm,
+            )),
+        }
+    }
+
+    /// Like hotspot, only `LinkageError`s are recorded: a resolution failing for lack of
+    /// memory or stack may succeed later.
+    fn record_dynamic_constant_error(
+        thread: &mut JavaThreadState,
+        method_id: MethodId,
+        idx: u16,
+        e: JvmError,
+        vm: &VirtualMachine,
+    ) -> JvmError {
+        let error = match e {
+            JvmError::JavaExceptionThrown(error) => error,
+            JvmError::JavaException(exception) => {
+                match vm.map_rust_error_to_java_exception(thread, exception) {
+                    Ok(error) => error,
+                    Err(e) => return e,
+                }
+            }
+            e => return e,
+        };
+        let is_linkage_error = vm.heap_read().get_class_id(error).is_ok_and(|class_id| {
+            vm.method_area()
+                .instance_of(class_id, None, vm.br().java_lang_linkage_error_sym)
+        });
+        if !is_linkage_error {
+            return JvmError::JavaExceptionThrown(error);
+        }
+        match vm.method_area().get_cp_by_method_id(&method_id) {
+            Ok(cp) => JvmError::JavaExceptionThrown(cp.set_dynamic_constant_error(idx, error)),
+            Err(e) => e,
+        }
+    }
+
+    fn link_dynamic_constant(
+        thread: &mut JavaThreadState,
+        method_id: MethodId,
+        idx: u16,
+        vm: &VirtualMachine,
+    ) -> Result<Value, JvmError> {
+        let (accessor, view) = {
+            let ma = vm.method_area();
+            (
+                ma.get_method(&method_id).class_id(),
+                ma.get_cp_by_method_id(&method_id)?
+                    .get_dynamic_view(&idx, vm.interner())?,
+            )
+        };
+        let bootstrap_method = Self::method_handle(thread, accessor, view.method_handle, vm)?;
+        let loader = vm.method_area().get_class_loader(accessor);
+        let desc = vm.interner().resolve(&view.nat_view.descriptor_sym);
+        let type_id = Self::load_descriptor_class(thread, loader, desc, vm)?;
+        let mut static_args = Vec::with_capacity(view.bootstrap_arguments.len());
+        for arg_idx in view.bootstrap_arguments {
+            static_args.push(Self::bootstrap_argument(thread, method_id, arg_idx, vm)?);
+        }
+        let static_args = match static_args.is_empty() {
+            true => Value::Null,
+            false => Value::Ref(object_array(
+                vm,
+                thread,
+                "[Ljava/lang/Object;",
+                static_args,
+            )?),
+        };
+        Self::invoke_method_handle_natives(
+            thread,
+            "linkDynamicConstant",
+            "(Ljava/lang/Object;Ljava/lang/Object;Ljava/lang/Object;Ljava/lang/Object;Ljava/lang/Object;)Ljava/lang/Object;",
+            vec![
+                mirror(vm, accessor)?,
+                Value::Ref(bootstrap_method),
+                Value::Ref(
+                    vm.heap_write()
+                        .get_str_from_pool_or_new(view.nat_view.name_sym)?,
+                ),
+                mirror(vm, type_id)?,
+                static_args,
+            ],
+            vm,
+        )
+    }
+
+    /// A static argument of a bootstrap method, primitive constants boxed.
+    fn bootstrap_argument(
+        thread: &mut JavaThreadState,
+        method_id: MethodId,
+        idx: u16,
+        vm: &VirtualMachine,
+    ) -> Result<Value, JvmError> {
+        let is_dynamic = matches!(
+            vm.method_area()
+                .get_cp_by_method_id(&method_id)?
+                .get_constant(&idx, vm.interner())?,
+            RuntimeConstant::Dynamic(_)
+        );
+        if is_dynamic {
+            return Self::dynamic_constant(thread, method_id, idx, vm);
+        }
+        let (ty, value) = match Self::loadable_constant(thread, method_id, idx, vm)? {
+            value @ Value::Integer(_) => (PrimitiveType::Int, value),
+            value @ Value::Long(_) => (PrimitiveType::Long, value),
+            value @ Value::Float(_) => (PrimitiveType::Float, value),
+            value @ Value::Double(_) => (PrimitiveType::Double, value),
+            reference => return Ok(reference),
+        };
+        Ok(Value::Ref(box_value(vm, thread, ty, value)?))
+    }
+
+    /// The method a resolved `MemberName` stands for.
+    pub(crate) fn member_name_method_id(
+        vm: &VirtualMachine,
+        member_name: HeapRef,
+    ) -> Result<MethodId, JvmError> {
+        let Some(resolved_method_name) = read_reference_field(vm, member_name, "method")? else {
+            throw_exception!(InternalError, "MemberName is not resolved to a method")?
+        };
+        vm.method_area()
+            .get_resolved_method(&resolved_method_name)
+            .ok_or(build_exception!(
+                InternalError,
+                "ResolvedMethodName of unknown method"
+            ))
+    }
+
+    /// Invokes the signature polymorphic `method_id` at the call site `idx` of the current
+    /// frame with the operands its descriptor `call_site_desc` takes (§2.9.3). The intrinsics
+    /// dispatch right here, `invokeExact`, `invoke` and the access modes of `VarHandle` are
+    /// linked once per call site by `MethodHandleNatives.linkMethod` to an adapter taking an
+    /// appendix.
+    pub(super) fn invoke_signature_polymorphic(
+        thread: &mut JavaThreadState,
+        idx: u16,
+        method_id: MethodId,
+        call_site_desc: Symbol,
+        vm: &VirtualMachine,
+    ) -> Result<(), JvmError> {
+        let (name, is_static, arg_count, returns_value) = {
+            let ma = vm.method_area();
+            let method = ma.get_method(&method_id);
+            let desc = ma.get_method_descriptor(
+                &ma.get_or_new_method_descriptor_id(&call_site_desc).unwrap(),
+            );
+            (
+                vm.interner().resolve(&method.name).to_string(),
+                method.is_static(),
+                desc.params.len() + !method.is_static() as usize,
+                !matches!(desc.ret, ReturnType::Void),
+            )
+        };
+        let mut args = Vec::with_capacity(arg_count);
+        for _ in 0..arg_count {
+            args.push(thread.stack.pop_operand()?);
+        }
+        args.reverse();
+
+        let ret = match name.as_str() {
+            "invokeBasic" => Self::invoke_basic(thread, args, vm)?,
+            "linkToStatic" | "linkToSpecial" if is_static => {
+                let target = Self::trailing_member_name(&mut args, vm)?;
+                Self::invoke_linked(thread, target, args, vm)?
+            }
+            "linkToVirtual" | "linkToInterface" if is_static => {
+                let target = Self::trailing_member_name(&mut args, vm)?;
+                let target = Self::select_receiver_method(target, &args, vm)?;
+                Self::invoke_linked(thread, target, args, vm)?
+            }
+            _ => {
+                let call_site = match Self::current_cp_linked_call_site(thread, idx, vm)? {
+                    Some(call_site) => call_site,
+                    None => Self::link_call_site(thread, idx, method_id, call_site_desc, vm)?,
+                };
+                if let Some(appendix) = call_site.appendix {
+                    args.push(Value::Ref(appendix));
+                }
+                Self::invoke_linked(thread, call_site.adapter, args, vm)?
+            }
+        };
+        if returns_value && let Some(ret) = ret {
+            thread.stack.push_operand(ret)?;
+        }
+        Ok(())
+    }
+
+    fn current_cp_linked_call_site(
+        thread: &JavaThreadState,
+        idx: u16,
+        vm: &VirtualMachine,
+    ) -> Result<Option<LinkedCallSite>, JvmError> {
+        let cur_method_id = thread.stack.cur_java_frame()?.method_id();
+        Ok(vm
+            .method_area()
+            .get_cp_by_method_id(&cur_method_id)?
+            .get_linked_call_site(&idx))
+    }
+
+    fn link_call_site(
+        thread: &mut JavaThreadState,
+        idx: u16,
+        method_id: MethodId,
+        call_site_desc: Symbol,
+        vm: &VirtualMachine,
+    ) -> Result<LinkedCallSite, JvmError> {
+        let cur_method_id = thread.stack.cur_java_frame()?.method_id();
+        let (caller, defc, name) = {
+            let ma = vm.method_area();
+            let method = ma.get_method(&method_id);
+            (
+                ma.get_method(&cur_method_id).class_id(),
+                method.class_id(),
+                method.name,
+            )
+        };
+        let method_type = Self::method_type(thread, caller, call_site_desc, vm)?;
+        let appendix_result = object_array(vm, thread, "[Ljava/lang/Object;", vec![Value::Null])?;
+        let member_name = Self::invoke_method_handle_natives(
+            thread,
+            "linkMethod",
+            "(Ljava/lang/Class;ILjava/lang/Class;Ljava/lang/String;Ljava/lang/Object;[Ljava/lang/Object;)Ljava/lang/invoke/MemberName;",
+            vec![
+                mirror(vm, caller)?,
+                Value::Integer(REF_INVOKE_VIRTUAL),
+                mirror(vm, defc)?,
+                Value::Ref(vm.heap_write().get_str_from_pool_or_new(name)?),
+                Value::Ref(method_type),
+                Value::Ref(appendix_result),
+            ],
+            vm,
+        )?
+        .as_obj_ref()?;
+        let call_site = LinkedCallSite {
+            adapter: Self::member_name_method_id(vm, member_name)?,
+            appendix: vm
+                .heap_read()
+                .read_array_element(appendix_result, 0)?
+                .as_nullable_obj_ref()?,
+        };
+        Ok(vm
+            .method_area()
+            .get_cp_by_method_id(&cur_method_id)?
+            .set_linked_call_site(idx, call_site))
+    }
+
+    /// `MethodHandle.invokeBasic`: enters the compiled lambda form of the receiver, which takes
+    /// the receiver and the other arguments as they are.
+    fn invoke_basic(
+        thread: &mut JavaThreadState,
+        args: Vec<Value>,
+        vm: &VirtualMachine,
+    ) -> Result<Option<Value>, JvmError> {
+        let Some(method_handle) = args[0].as_nullable_obj_ref()? else {
+            throw_exception!(NullPointerException)?
+        };
+        let Some(form) = read_reference_field(vm, method_handle, "form")? else {
+            throw_exception!(InternalError, "MethodHandle without a LambdaForm")?
+        };
+        let Some(vmentry) = read_reference_field(vm, form, "vmentry")? else {
+            throw_exception!(InternalError, "LambdaForm is not compiled")?
+        };
+        let target = Self::member_name_method_id(vm, vmentry)?;
+        Self::invoke_linked(thread, target, args, vm)
+    }
+
+    /// Pops the `MemberName` the `linkTo*` intrinsics take last and gives its method.
+    fn trailing_member_name(
+        args: &mut Vec<Value>,
+        vm: &VirtualMachine,
+    ) -> Result<MethodId, JvmError> {
+        let Some(member_name) = args
+            .pop()
+            .map(|arg| arg.as_nullable_obj_ref())
+            .transpose()?
+            .flatten()
+        else {
+            throw_exception!(NullPointerException)?
+        };
+        Self::member_name_method_id(vm, member_name)
+    }
+
+    /// The method `linkToVirtual` and `linkToInterface` select for the class of the receiver.
+    fn select_receiver_method(
+        method_id: MethodId,
+        args: &[Value],
+        vm: &VirtualMachine,
+    ) -> Result<MethodId, JvmError> {
+        let Some(receiver) = args
+            .first()
+            .map(|arg| arg.as_nullable_obj_ref())
+            .transpose()?
+            .flatten()
+        else {
+            throw_exception!(NullPointerException)?
+        };
+        let receiver_class_id = vm.heap_read().get_class_id(receiver)?;
+        let ma = vm.method_area();
+        let method = ma.get_method(&method_id);
+        let key = MethodKey {
+            name: method.name,
+            desc: method.desc,
+        };
+        let receiver_class = ma.get_class(&receiver_class_id);
+        receiver_class.get_vtable_method_id(&key).or_else(|_| {
+            ma.get_instance_class(&receiver_class_id)?
+                .get_interface_method_id(&key)
+        })
+    }
+
+    /// Invokes a method a call site was linked to, initializing its class first if it's static.
+    fn invoke_linked(
+        thread: &mut JavaThreadState,
+        method_id: MethodId,
+        args: Vec<Value>,
+        vm: &VirtualMachine,
+    ) -> Result<Option<Value>, JvmError> {
+        let (is_static, class_id) = {
+            let ma = vm.method_area();
+            let method = ma.get_method(&method_id);
+            (method.is_static(), method.class_id())
+        };
+        if is_static {
+            Self::ensure_initialized(thread, Some(class_id), vm)?;
+        }
+        Self::invoke_method_core(thread, method_id, args, vm)
+    }
+
+    /// Calls the static method `name` of `java.lang.invoke.MethodHandleNatives`, the entry
+    /// points of the JDK for linking method handles and their call sites.
+    fn invoke_method_handle_natives(
+        thread: &mut JavaThreadState,
+        name: &str,
+        desc: &str,
+        args: Vec<Value>,
+        vm: &VirtualMachine,
+    ) -> Result<Value, JvmError> {
+        let class_id = vm.method_area().get_class_id_or_load(
+            vm.br().java_lang_invoke_method_handle_natives_sym,
+            thread.id,
+        )?;
+        let method_id = vm.method_area().get_static_method_id(
+            &class_id,
+            MethodKey {
+                name: vm.interner().get_or_intern(name),
+                desc: vm.interner().get_or_intern(desc),
+            },
+        )?;
+        Self::invoke_static_method_for_value(thread, method_id, vm, args)?.ok_or(build_exception!(
+            InternalError,
+            "MethodHandleNatives.{name} returned nothing"
+        ))
+    }
+}
+
+fn mirror(vm: &VirtualMachine, class_id: ClassId) -> Result<Value, JvmError> {
+    Ok(Value::Ref(
+        vm.method_area()
+            .get_mirror_ref_or_create(class_id, &vm.heap)?,
+    ))
+}
+
+fn object_array(
+    vm: &VirtualMachine,
+    thread: &JavaThreadState,
+    array_class_name: &str,
+    elements: Vec<Value>,
+) -> Result<HeapRef, JvmError> {
+    let array_class_id = vm
+        .method_area()
+        .get_class_id_or_load(vm.interner().get_or_intern(array_class_name), thread.id)?;
+    let mut heap = vm.heap_write();
+    let array_ref = heap.alloc_object_array(array_class_id, elements.len() as i32)?;
+    for (i, element) in elements.into_iter().enumerate() {
+        heap.write_array_element(array_ref, i as i32, element)?;
+    }
+    Ok(array_ref)
+}
+
+/// The reference field `name` of `obj`, looked up in the class of `obj`.
+fn read_reference_field(
+    vm: &VirtualMachine,
+    obj: HeapRef,
+    name: &str,
+) -> Result<Option<HeapRef>, JvmError> {
+    let offset = {
+        let ma = vm.method_area();
+        let class_id = vm.heap_read().get_class_id(obj)?;
+        ma.get_instance_class(&class_id)?
+            .get_instance_field_by_name(&vm.interner().get_or_intern(name))?
+            .offset
+    };
+    vm.heap_read()
+        .read_field(obj, offset, AllocationType::Reference)?
+        .as_nullable_obj_ref()
+}
@@ -2,18 +2,23 @@ use crate::error::JvmError;
 use crate::heap::HeapRef;
 use crate::interpreter::handlers::*;
 use crate::interpreter::return_handlers::*;
-use crate::keys::{ClassId, FieldKey};
-use crate::rt::{ClassLike, JvmClass};
+use crate::keys::ClassId;
+use crate::rt::ClassLike;
+use crate::rt::init::InitAction;
 use crate::thread::JavaThreadState;
 use crate::vm::Value;
+use crate::vm::signal;
 use crate::vm::stack::{FrameType, JavaFrame, NativeFrame};
 use crate::{MethodId, VirtualMachine, build_exception, debug_log_instruction, error_log_method};
 use common::instruction::Instruction;
 use jclass::attribute::method::ExceptionTableEntry;
+use std::collections::HashSet;
 use std::ops::ControlFlow;
 use tracing_log::log::warn;
 
 mod handlers;
+mod loading;
+mod method_handles;
 mod return_handlers;
 
 pub struct Interpreter;
@@ -193,8 +198,8 @@ impl Interpreter {
             Instruction::Sastore => handle_sastore(thread, vm)?,
             Instruction::Sipush(value) => handle_sipush(thread, value)?,
             Instruction::TableSwitch(switch) => handle_tableswitch(thread, switch)?,
-            Instruction::Monitorenter => handle_monitorenter(thread)?,
-            Instruction::Monitorexit => handle_monitorexit(thread)?,
+            Instruction::Monitorenter => handle_monitorenter(thread, vm)?,
+            Instruction::Monitorexit => handle_monitorexit(thread, vm)?,
             Instruction::Return => {
                 return Ok(ControlFlow::Break(None));
             }
@@ -235,11 +240,11 @@ impl Interpreter {
         vm: &VirtualMachine,
     ) -> Result<Vec<Value>, JvmError> {
         let mut args_count = vm
-            .method_area_read()
+            .method_area()
             .get_method_descriptor_by_method_id(&method_id)
             .params
             .len();
-        if !vm.method_area_read().get_method(&method_id).is_static() {
+        if !vm.method_area().get_method(&method_id).is_static() {
             args_count += 1;
         }
         // TODO: I saw somewhere a data structure with fixed capacity, that can avoid heap allocation
@@ -269,13 +274,16 @@ impl Interpreter {
 
         let exception_class_id = vm.heap_read().get_class_id(java_exception)?;
         let catch_type_sym = vm
-            .method_area_read()
+            .method_area()
             .get_cp_by_method_id(method_id)?
             .get_class_sym(&catch_type, vm.interner())?;
 
+        let loader = vm
+            .method_area()
+            .get_class_loader(vm.method_area().get_method(method_id).class_id());
         Ok(vm
-            .method_area_read()
-            .instance_of(exception_class_id, catch_type_sym))
+            .method_area()
+            .instance_of(exception_class_id, loader, catch_type_sym))
     }
 
     fn find_exception_handler(
@@ -285,7 +293,7 @@ impl Interpreter {
         thread: &mut JavaThreadState,
     ) -> Result<bool, JvmError> {
         let pc = thread.stack.pc()?;
-        let ma = vm.method_area_read();
+        let ma = vm.method_area();
         let exception_table = ma.get_method(method_id).get_exception_table()?;
 
         for entry in exception_table.iter() {
@@ -310,8 +318,11 @@ impl Interpreter {
         method_id: MethodId,
         vm: &VirtualMachine,
     ) -> Result<Option<Value>, JvmError> {
-        let code_ptr = vm.method_area_read().get_method(&method_id).get_code()? as *const [u8];
+        let code_ptr = vm.method_area().get_method(&method_id).get_code()? as *const [u8];
         loop {
+            if signal::poll_requested() {
+                vm.safepoint_poll(thread)?;
+            }
             // SAFETY: code_ptr is valid as long as method exists in method area (always)
             // need to use pointer to avoid borrow checker issues
             let code = unsafe { &*code_ptr };
@@ -352,13 +363,13 @@ impl Interpreter {
         vm: &VirtualMachine,
     ) -> Result<Option<Value>, JvmError> {
         let is_static = {
-            let ma = vm.method_area_read();
+            let ma = vm.method_area();
             ma.get_method(&method_id).is_static()
         };
         let clone_desc = vm.br.clone_desc;
         let object_class_sym = vm.br.java_lang_object_sym;
         let mut method_key = vm
-            .method_area_read()
+            .method_area()
             .build_fully_qualified_native_method_key(&method_id);
         // native instance method of array special handling (for now, only Object.clone)
         if !is_static
@@ -369,7 +380,8 @@ impl Interpreter {
         {
             method_key.class = None;
         }
-        let frame = NativeFrame::new(method_id);
+        let frame = NativeFrame::new(method_id, args);
+        let args = frame.args();
         thread.stack.push_frame(FrameType::NativeFrame(frame))?;
         let native = vm.native_registry.get(&method_key).ok_or(build_exception!(
             UnsatisfiedLinkError,
@@ -397,12 +409,19 @@ impl Interpreter {
         vm: &VirtualMachine,
     ) -> Result<Option<Value>, JvmError> {
         let (max_stack, max_locals) = vm
-            .method_area_read()
+            .method_area()
             .get_method(&method_id)
             .get_frame_attributes()?;
+        let sync_obj = Self::synchronized_method_monitor(method_id, &args, vm)?;
         let frame = JavaFrame::new(method_id, max_stack, max_locals, args);
         thread.stack.push_frame(FrameType::JavaFrame(frame))?;
+        if let Some(obj) = sync_obj {
+            Self::monitor_enter(thread, obj, vm)?;
+        }
         let method_ret = Self::interpret_method(thread, method_id, vm);
+        if let Some(obj) = sync_obj {
+            vm.monitors.exit(thread.id, obj)?;
+        }
         if let Err(e) = &method_ret {
             error_log_method!(
                 &method_id,
@@ -415,15 +434,62 @@ impl Interpreter {
         Ok(method_ret)
     }
 
+    /// Returns the object whose monitor a synchronized method locks: the receiver, or the
+    /// class mirror for static methods.
+    fn synchronized_method_monitor(
+        method_id: MethodId,
+        args: &[Value],
+        vm: &VirtualMachine,
+    ) -> Result<Option<HeapRef>, JvmError> {
+        let (is_synchronized, is_static, class_id) = {
+            let ma = vm.method_area();
+            let method = ma.get_method(&method_id);
+            (
+                method.is_synchronized(),
+                method.is_static(),
+                method.class_id(),
+            )
+        };
+        if !is_synchronized {
+            return Ok(None);
+        }
+        if is_static {
+            Ok(Some(
+                vm.method_area()
+                    .get_mirror_ref_or_create(class_id, &vm.heap)?,
+            ))
+        } else {
+            Ok(Some(args[0].as_obj_ref()?))
+        }
+    }
+
+    /// Acquires the monitor of `obj` on behalf of the current frame. While blocked the thread
+    /// keeps answering safepoint requests, so it still shows up in thread dumps.
+    pub(crate) fn monitor_enter(
+        thread: &mut JavaThreadState,
+        obj: HeapRef,
+        vm: &VirtualMachine,
+    ) -> Result<(), JvmError> {
+        let thread_id = thread.id;
+        let depth = thread.stack.frames().len();
+        vm.monitors.enter(thread_id, obj, depth, || {
+            if signal::poll_requested() {
+                vm.safepoint_poll(thread)?;
+            }
+            Ok(())
+        })
+    }
+
     fn invoke_method_core(
         thread: &mut JavaThreadState,
         method_id: MethodId,
         args: Vec<Value>,
         vm: &VirtualMachine,
     ) -> Result<Option<Value>, JvmError> {
-        let is_native = {
-            let ma = vm.method_area_read();
-            ma.get_method(&method_id).is_native()
+        let (method_id, is_native) = {
+            let ma = vm.method_area();
+            let method_id = ma.current_method_id(method_id);
+            (method_id, ma.get_method(&method_id).is_native())
         };
         if is_native {
             Self::invoke_native_method(thread, method_id, args, vm)
@@ -445,17 +511,44 @@ impl Interpreter {
         Ok(())
     }
 
-    fn interface_needs_initialization(
-        interface_id: ClassId,
+    /// Returns the superinterfaces of a class that have to be initialized with it (JVMS §5.5
+    /// step 7): those declaring a non-abstract, non-static method, in the order of a
+    /// recursive enumeration over the direct superinterfaces.
+    fn superinterfaces_to_initialize(
+        class_id: ClassId,
         vm: &VirtualMachine,
-    ) -> Result<bool, JvmError> {
-        let has_clinit = {
-            let ma = vm.method_area_read();
-            let interface = ma.get_interface_class(&interface_id)?;
-            interface.has_clinit()
-        };
+    ) -> Result<Vec<ClassId>, JvmError> {
+        fn collect(
+            interface_ids: &[ClassId],
+            vm: &VirtualMachine,
+            visited: &mut HashSet<ClassId>,
+            out: &mut Vec<ClassId>,
+        ) -> Result<(), JvmError> {
+            let ma = vm.method_area();
+            for interface_id in interface_ids {
+                if !visited.insert(*interface_id) {
+                    continue;
+                }
+                let interface = ma.get_interface_class(interface_id)?;
+                collect(interface.get_direct_interfaces()?, vm, visited, out)?;
+                let declares_default_method = interface.get_methods().values().any(|id| {
+                    let method = ma.get_method(id);
+                    !method.is_abstract() && !method.is_static()
+                });
+                if declares_default_method {
+                    out.push(*interface_id);
+                }
+            }
+            Ok(())
+        }
 
-        Ok(has_clinit) //TODO: || interface.has_non_constant_static_fields()?
+        let mut out = Vec::new();
+        let direct_interfaces = vm
+            .method_area()
+            .get_class_like(&class_id)?
+            .get_direct_interfaces()?;
+        collect(direct_interfaces, vm, &mut HashSet::new(), &mut out)?;
+        Ok(out)
     }
 
     fn run_clinit_if_exists(
@@ -463,15 +556,20 @@ impl Interpreter {
         class_id: ClassId,
         vm: &VirtualMachine,
     ) -> Result<(), JvmError> {
-        let ma = vm.method_area_read();
-        if let Some(&clinit_method_id) = ma.get_class_like(&class_id)?.get_clinit_method_id() {
-            drop(ma);
+        let clinit_method_id = vm
+            .method_area()
+            .get_class_like(&class_id)?
+            .get_clinit_method_id()
+            .copied();
+        if let Some(clinit_method_id) = clinit_method_id {
             Self::invoke_method_internal(thread, clinit_method_id, vec![], vm)?;
         }
 
         Ok(())
     }
 
+    /// Initializes the class as described in JVMS §5.5. If another thread is running the
+    /// `<clinit>`, waits until it is done.
     pub fn ensure_initialized(
         thread: &mut JavaThreadState,
         class_id: Option<ClassId>,
@@ -481,85 +579,110 @@ impl Interpreter {
             return Ok(());
         };
 
-        {
-            let ma = vm.method_area_read();
-            let class = ma.get_class_like(&class_id)?;
-
-            if class.is_initialized_or_initializing() {
-                return Ok(());
+        let class = vm.method_area().get_class_like(&class_id)?;
+        if class.is_initialized() {
+            return Ok(());
+        }
+        // a class is linked before it is initialized, a failed linking doesn't make it erroneous
+        Self::link_class(thread, class_id, vm)?;
+        let thread_id = thread.id;
+        let action = vm.class_init.begin(class, class_id, thread_id, || {
+            if signal::poll_requested() {
+                vm.safepoint_poll(thread)?;
+            }
+            Ok(())
+        })?;
+        match action {
+            InitAction::Initialize => {}
+            InitAction::Done => return Ok(()),
+            InitAction::Erroneous => {
+                return Err(build_exception!(
+                    NoClassDefFoundError,
+                    "Could not initialize class {}",
+                    vm.symbol_to_pretty_string(class.name())
+                ));
             }
-
-            class.set_initializing();
         }
 
-        let (is_instance, is_interface) = {
-            let ma = vm.method_area_read();
-            let jvm_class = ma.get_class(&class_id);
-            match jvm_class {
-                JvmClass::Instance(_) => (true, false),
-                JvmClass::Interface(_) => (false, true),
-                _ => (false, false),
-            }
+        let res = Self::initialize_class(thread, class_id, vm);
+        vm.class_init.finish(class, class_id, res.is_ok());
+        res
+    }
+
+    /// Steps 7-11 of §5.5: initializes the superclass and superinterfaces, then runs `<clinit>`.
+    fn initialize_class(
+        thread: &mut JavaThreadState,
+        class_id: ClassId,
+        vm: &VirtualMachine,
+    ) -> Result<(), JvmError> {
+        let (super_id, is_interface) = {
+            let ma = vm.method_area();
+            let class = ma.get_class(&class_id);
+            (class.get_super_id(), class.is_interface())
         };
 
-        if is_instance {
-            let super_id = {
-                let ma = vm.method_area_read();
-                let inst_class = ma.get_instance_class(&class_id)?;
-                inst_class.get_super()
-            };
-            if let Some(super_id) = super_id {
-                Self::ensure_initialized(thread, Some(super_id), vm)?;
+        // interfaces don't initialize their superinterfaces
+        if !is_interface {
+            Self::ensure_initialized(thread, super_id, vm)?;
+            for interface_id in Self::superinterfaces_to_initialize(class_id, vm)? {
+                Self::ensure_initialized(thread, Some(interface_id), vm)?;
             }
-            let interfaces = vm
-                .method_area_read()
-                .get_instance_class(&class_id)?
-                .get_interfaces()?
-                .clone(); // TODO: avoid clone?
-            for interface_id in interfaces {
-                if Self::interface_needs_initialization(interface_id, vm)? {
-                    Self::ensure_initialized(thread, Some(interface_id), vm)?;
-                }
-            }
-
-            Self::run_clinit_if_exists(thread, class_id, vm)?;
+        }
 
-            let cur_class_name = vm.method_area_read().get_instance_class(&class_id)?.name();
+        Self::run_clinit_if_exists(thread, class_id, vm)
+            .map_err(|e| Self::wrap_initializer_exception(thread, e, vm))
+    }
 
-            //TODO: stub
-            if vm.interner().resolve(&cur_class_name) == "jdk/internal/access/SharedSecrets" {
-                warn!(
-                    "TODO: Stub: Setting jdk/internal/access/SharedSecrets javaLangRefAccess to non-null value, to avoid NPEs"
-                );
-                let ref_access_fk = FieldKey {
-                    name: vm.interner().get_or_intern("javaLangRefAccess"),
-                    desc: vm
-                        .interner()
-                        .get_or_intern("Ljdk/internal/access/JavaLangRefAccess;"),
-                };
-                vm.method_area_read()
-                    .get_instance_class(&class_id)?
-                    .set_static_field_value(&ref_access_fk, Value::Ref(0))?;
-            }
-        } else if is_interface {
-            let interfaces = vm
-                .method_area_read()
-                .get_interface_class(&class_id)?
-                .get_interfaces()?
-                .clone(); // TODO: avoid clone?
-            for super_interface_id in interfaces {
-                if Self::interface_needs_initialization(super_interface_id, vm)? {
-                    Self::ensure_initialized(thread, Some(super_interface_id), vm)?;
+    /// Step 11 of §5.5: exceptions other than `Error`s thrown by `<clinit>` are wrapped in
+    /// `ExceptionInInitializerError`.
+    fn wrap_initializer_exception(
+        thread: &mut JavaThreadState,
+        e: JvmError,
+        vm: &VirtualMachine,
+    ) -> JvmError {
+        let res = (|| {
+            let exception_ref = match e {
+                JvmError::JavaExceptionThrown(exception_ref) => exception_ref,
+                JvmError::JavaException(exception) => {
+                    vm.map_rust_error_to_java_exception(thread, exception)?
                 }
+                e => return Err(e),
+            };
+            let exception_class_id = vm.heap_read().get_class_id(exception_ref)?;
+            if vm
+                .method_area()
+                .instance_of(exception_class_id, None, vm.br().java_lang_error_sym)
+            {
+                return Ok(exception_ref);
             }
 
-            Self::run_clinit_if_exists(thread, class_id, vm)?;
+            let wrapper_class_id = vm.method_area().get_class_id_or_load(
+                vm.br().java_lang_exception_in_initializer_error_sym,
+                thread.id,
+            )?;
+            let (constructor_id, instance_size) = {
+                let ma = vm.method_area();
+                let class = ma.get_instance_class(&wrapper_class_id)?;
+                (
+                    class.get_special_method_id(&vm.br().throwable_cause_constructor_mk)?,
+                    class.get_instance_size()?,
+                )
+            };
+            let wrapper_ref = vm
+                .heap_write()
+                .alloc_instance(instance_size, wrapper_class_id)?;
+            Self::invoke_instance_method(
+                thread,
+                constructor_id,
+                vm,
+                vec![Value::Ref(wrapper_ref), Value::Ref(exception_ref)],
+            )?;
+            Ok(wrapper_ref)
+        })();
+        match res {
+            Ok(exception_ref) => JvmError::JavaExceptionThrown(exception_ref),
+            Err(e) => e,
         }
-
-        vm.method_area_read()
-            .get_class_like(&class_id)?
-            .set_initialized();
-        Ok(())
     }
 
     pub fn invoke_instance_method(
@@ -578,9 +701,22 @@ impl Interpreter {
         vm: &VirtualMachine,
         args: Vec<Value>,
     ) -> Result<(), JvmError> {
-        let class_id = vm.method_area_read().get_method(&method_id).class_id();
+        let class_id = vm.method_area().get_method(&method_id).class_id();
         Self::ensure_initialized(thread, Some(class_id), vm)?;
         Self::invoke_method_internal(thread, method_id, args, vm)?;
         Ok(())
     }
+
+    /// Like [`Self::invoke_static_method`], but the result goes back to the Rust caller instead
+    /// of the operand stack of the current frame.
+    pub fn invoke_static_method_for_value(
+        thread: &mut JavaThreadState,
+        method_id: MethodId,
+        vm: &VirtualMachine,
+        args: Vec<Value>,
+    ) -> Result<Option<Value>, JvmError> {
+        let class_id = vm.method_area().get_method(&method_id).class_id();
+        Self::ensure_initialized(thread, Some(class_id), vm)?;
+        Self::invoke_method_core(thread, method_id, args, vm)
+    }
 }
@@ -19,6 +19,22 @@ pub enum EventModifier {
     ClassMatch { class_pattern: String },
 }
 
+/// A class file of `VirtualMachine.RedefineClasses`.
+#[derive(Clone)]
+pub struct ClassDefinition {
+    pub ref_type: u32,
+    pub class_file: Vec<u8>,
+}
+
+impl std::fmt::Debug for ClassDefinition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClassDefinition")
+            .field("ref_type", &self.ref_type)
+            .field("class_file_len", &self.class_file.len())
+            .finish()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum JdwpCommand {
     // VirtualMachine (1)
@@ -39,7 +55,7 @@ pub enum JdwpCommand {
     VmHoldEvents,
     VmReleaseEvents,
     VmCapabilitiesNew,
-    VmRedefineClasses,
+    VmRedefineClasses { classes: Vec<ClassDefinition> },
     VmSetDefaultStratum { stratum: String },
     VmAllClassesWithGeneric,
     VmInstanceCounts { ref_types: Vec<u64> },
@@ -87,7 +103,21 @@ impl JdwpCommand {
             (1, 15) => Ok(JdwpCommand::VmHoldEvents),
             (1, 16) => Ok(JdwpCommand::VmReleaseEvents),
             (1, 17) => Ok(JdwpCommand::VmCapabilitiesNew),
-            (1, 18) => todo!(),
+            (1, 18) => {
+                let count = cursor.read_i32::<BigEndian>()?;
+                let mut classes = Vec::with_capacity(count.max(0) as usize);
+                for _ in 0..count {
+                    let ref_type = cursor.read_u32::<BigEndian>()?;
+                    let len = cursor.read_i32::<BigEndian>()?;
+                    let mut class_file = vec![0u8; len.max(0) as usize];
+                    cursor.read_exact(&mut class_file)?;
+                    classes.push(ClassDefinition {
+                        ref_type,
+                        class_file,
+                    });
+                }
+                Ok(JdwpCommand::VmRedefineClasses { classes })
+            }
             (1, 19) => todo!(),
             (1, 20) => Ok(JdwpCommand::VmAllClassesWithGeneric),
             (1, 21) => todo!(),
@@ -1,3 +1,4 @@
+use crate::rt::redefine::RedefineError;
 use std::io;
 
 pub const NONE: u16 = 0;
@@ -59,6 +60,22 @@ pub const TRANSPORT_INIT: u16 = 510;
 pub const NATIVE_METHOD: u16 = 511;
 pub const INVALID_COUNT: u16 = 512;
 
+pub fn redefine_error_code(error: &RedefineError) -> u16 {
+    match error {
+        RedefineError::InvalidClass => INVALID_CLASS,
+        RedefineError::NamesDontMatch => NAMES_DONT_MATCH,
+        RedefineError::InvalidClassFormat(_) => INVALID_CLASS_FORMAT,
+        RedefineError::FailsVerification(_) => FAILS_VERIFICATION,
+        RedefineError::HierarchyChange => HIERARCHY_CHANGE_NOT_IMPLEMENTED,
+        RedefineError::ClassModifiersChange => CLASS_MODIFIERS_CHANGE_NOT_IMPLEMENTED,
+        RedefineError::SchemaChange => SCHEMA_CHANGE_NOT_IMPLEMENTED,
+        RedefineError::MethodAddition => ADD_METHOD_NOT_IMPLEMENTED,
+        RedefineError::MethodDeletion => DELETE_METHOD_NOT_IMPLEMENTED,
+        RedefineError::MethodModifiersChange => METHOD_MODIFIERS_CHANGE_NOT_IMPLEMENTED,
+        RedefineError::Internal(_) => INTERNAL,
+    }
+}
+
 #[derive(Debug)]
 pub enum JdwpError {
     Io(io::Error),
@@ -1,9 +1,10 @@
 use crate::VirtualMachine;
-use crate::jdwp::agent::command::JdwpCommand;
+use crate::jdwp::agent::command::{ClassDefinition, JdwpCommand};
 use crate::jdwp::agent::error_code::JdwpError;
 use crate::jdwp::agent::packet::{CommandPacket, Packet, ReplyPacket};
 use crate::jdwp::{DebugEvent, DebugState};
 use crate::keys::ClassId;
+use crate::rt::redefine;
 use std::io;
 use std::num::NonZeroU32;
 use std::sync::Arc;
@@ -128,6 +129,13 @@ async fn send_events(stream: &mut TcpStream, events: &[DebugEvent]) -> Result<()
                 buffer.extend(signature_bytes);
                 buffer.extend(&(info.status as i32).to_be_bytes()); // status
             }
+            DebugEvent::ClassUnload(info) => {
+                buffer.extend(9u8.to_be_bytes()); // event kind: CLASS_UNLOAD
+                buffer.extend(&info.request_id.0.to_be_bytes()); // request id
+                let signature_bytes = info.signature.as_bytes();
+                buffer.extend(&(signature_bytes.len() as u32).to_be_bytes());
+                buffer.extend(signature_bytes);
+            }
         }
     }
 
@@ -170,6 +178,9 @@ async fn handle_connection(
                         eprintln!("Error sending reply: {}", e);
                         break;
                     }
+                    if let Some(exit_code) = debug.take_exit_request() {
+                        vm.halt(exit_code);
+                    }
                 }
                 Err(e) => {
                     eprintln!("Error handling command: {}", e);
@@ -201,6 +212,8 @@ fn handle_command(
 
     println!("Received command: {:?}", cmd);
 
+    let _no_unloading = vm.method_area().block_class_unloading();
+
     let data = match cmd {
         JdwpCommand::VmIdSizes => Ok(handle_id_size()),
         JdwpCommand::EventRequestSet(event_request) => {
@@ -212,6 +225,17 @@ fn handle_command(
             debug.resume_all();
             return Ok(None);
         }
+        JdwpCommand::VmExit { exit_code } => {
+            debug.request_exit(exit_code);
+            Ok(Vec::new())
+        }
+        JdwpCommand::VmRedefineClasses { classes } => {
+            return Ok(Some(ReplyPacket {
+                id: cmd_packet.id,
+                error_code: handle_vm_redefine_classes(vm, classes),
+                data: Vec::new(),
+            }));
+        }
         JdwpCommand::VmCapabilities => Ok(handle_vm_capabilities()),
         JdwpCommand::VmCapabilitiesNew => Ok(handle_vm_capabilities_new()),
         JdwpCommand::VmAllClasses => Ok(handle_vm_all_classes(vm)),
@@ -236,8 +260,32 @@ fn handle_command(
     }))
 }
 
+/// Replies with an error code only, nothing is redefined unless all classes are.
+fn handle_vm_redefine_classes(vm: &VirtualMachine, classes: Vec<ClassDefinition>) -> u16 {
+    let ma = vm.method_area();
+    let classes = classes
+        .into_iter()
+        .map(|def| {
+            NonZeroU32::new(def.ref_type)
+                .map(ClassId::new)
+                .filter(|class_id| ma.classes().get(class_id.to_index()).is_some())
+                .map(|class_id| (class_id, def.class_file))
+        })
+        .collect::<Option<Vec<_>>>();
+    let Some(classes) = classes else {
+        return error_code::INVALID_CLASS;
+    };
+    match redefine::redefine_classes(ma, classes) {
+        Ok(()) => error_code::NONE,
+        Err(e) => {
+            eprintln!("Failed to redefine classes: {}", e);
+            error_code::redefine_error_code(&e)
+        }
+    }
+}
+
 fn handle_reference_type_interfaces(vm: &VirtualMachine, class_id: u32) -> Vec<u8> {
-    let ma_read = vm.method_area_read();
+    let ma_read = vm.method_area();
     let class = ma_read.get_class(&ClassId::new(NonZeroU32::new(class_id).unwrap()));
     let interface = class.get_direct_interfaces().unwrap();
     let mut buf = Vec::with_capacity(4 + interface.len() * 4); // number of interfaces + interfaces data
@@ -249,7 +297,7 @@ fn handle_reference_type_interfaces(vm: &VirtualMachine, class_id: u32) -> Vec<u
 }
 
 fn handle_class_type_superclass(vm: &VirtualMachine, class_id: u32) -> Vec<u8> {
-    let ma_read = vm.method_area_read();
+    let ma_read = vm.method_area();
     let class = ma_read.get_class(&ClassId::new(NonZeroU32::new(class_id).unwrap()));
     let super_class_id = if let Some(super_class) = class.get_super_id() {
         super_class.to_i32()
@@ -261,13 +309,13 @@ fn handle_class_type_superclass(vm: &VirtualMachine, class_id: u32) -> Vec<u8> {
 
 fn handle_vm_all_classes(vm: &VirtualMachine) -> Vec<u8> {
     let one_class_approx_size = 1 + 4 + 4 + (20 * 8) + 4; // refTypeTag + typeId + signatureLen + signature + status
-    let ma_read = vm.method_area_read();
+    let ma_read = vm.method_area();
     let classes = ma_read.classes();
     let mut classes_count: i32 = 0;
-    let mut buf = Vec::with_capacity(4 + classes.len() * one_class_approx_size); // number of classes + classes data
+    let mut buf = Vec::with_capacity(4 + classes.count() * one_class_approx_size); // number of classes + classes data
     buf.extend(&classes_count.to_be_bytes()); // placeholder for number of classes
     //TODO: I guess need to skip primitive types?
-    for (i, class) in classes.iter().enumerate() {
+    for (i, class) in classes.iter() {
         if class.is_primitive() {
             continue;
         }
@@ -357,7 +405,7 @@ fn handle_vm_capabilities_new() -> Vec<u8> {
     buf.extend(&0u8.to_be_bytes()); // canGetOwnedMonitorInfo
     buf.extend(&0u8.to_be_bytes()); // canGetCurrentContendedMonitor
     buf.extend(&0u8.to_be_bytes()); // canGetMonitorInfo
-    buf.extend(&0u8.to_be_bytes()); // canRedefineClasses
+    buf.extend(&1u8.to_be_bytes()); // canRedefineClasses
     buf.extend(&0u8.to_be_bytes()); // canAddMethod
     buf.extend(&0u8.to_be_bytes()); // canUnrestrictedlyRedefineClasses
     buf.extend(&0u8.to_be_bytes()); // canPopFrames
@@ -37,10 +37,16 @@ pub struct ClassPrepareInfo {
     pub status: ClassStatus,
 }
 
+pub struct ClassUnloadInfo {
+    pub request_id: EventRequestId,
+    pub signature: String,
+}
+
 pub enum DebugEvent {
     VMStart,
     VMDeath,
     ClassPrepare(ClassPrepareInfo),
+    ClassUnload(ClassUnloadInfo),
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, TryFromPrimitive)]
@@ -93,6 +99,7 @@ pub struct DebugState {
     pub breakpoints: DashMap<BreakpointLocation, u32>,
     pub suspend_policies: DashMap<EventRequestId, SuspendPolicy>,
     pub class_prepare_events: RwLock<ClassPatternMatcher>,
+    pub class_unload_events: RwLock<ClassPatternMatcher>,
 
     pub event_tx: UnboundedSender<DebugEvent>,
 
@@ -101,6 +108,9 @@ pub struct DebugState {
     pub connected_signal: Condvar,
 
     pub next_event_id: AtomicI32,
+
+    /// Exit code requested by `VirtualMachine.Exit`, the VM halts once the reply is sent.
+    pub exit_request: Mutex<Option<i32>>,
 }
 
 impl DebugState {
@@ -112,11 +122,13 @@ impl DebugState {
             breakpoints: DashMap::new(),
             suspend_policies: DashMap::new(),
             class_prepare_events: RwLock::new(ClassPatternMatcher::new()),
+            class_unload_events: RwLock::new(ClassPatternMatcher::new()),
             event_tx,
             connected: AtomicBool::new(false),
             connected_lock: Mutex::new(()),
             connected_signal: Condvar::new(),
             next_event_id: AtomicI32::new(1),
+            exit_request: Mutex::new(None),
         }
     }
 
@@ -167,6 +179,14 @@ impl DebugState {
         self.connected_signal.notify_all();
     }
 
+    pub fn request_exit(&self, exit_code: i32) {
+        *self.exit_request.lock().unwrap() = Some(exit_code);
+    }
+
+    pub fn take_exit_request(&self) -> Option<i32> {
+        self.exit_request.lock().unwrap().take()
+    }
+
     pub fn get_next_event_id(&self) -> EventRequestId {
         EventRequestId(self.next_event_id.fetch_add(1, Ordering::SeqCst))
     }
@@ -176,6 +196,11 @@ impl DebugState {
         matcher.matches(class_name)
     }
 
+    pub fn matches_class_unload(&self, class_name: &str) -> Option<Vec<EventRequestId>> {
+        let matcher = self.class_unload_events.read().unwrap();
+        matcher.matches(class_name)
+    }
+
     pub fn add_event_request(&self, event_request: EventRequest) {
         let event_kind = event_request.event_kind;
         let event_id = event_request.id;
@@ -193,7 +218,21 @@ impl DebugState {
                 }
             }
             EventKind::ClassUnload => {
-                // TODO: I never unload classes, so this is unimplemented for now
+                let mut matcher = self.class_unload_events.write().unwrap();
+                let mut class_matched = false;
+                for modifier in &event_request.modifiers {
+                    match modifier {
+                        EventModifier::ClassMatch { class_pattern } => {
+                            matcher.add(class_pattern.clone(), event_id);
+                            class_matched = true;
+                        }
+                        // unloading happens on no thread of the debuggee
+                        EventModifier::PlatformThreadsOnly => {}
+                    }
+                }
+                if !class_matched {
+                    matcher.add("*".to_string(), event_id);
+                }
             }
             EventKind::ThreadStart => {
                 // TODO: I have only a single thread right now, so this is unimplemented for now
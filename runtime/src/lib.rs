@@ -1,4 +1,6 @@
+use crate::class_loader::BuiltinLoaders;
 use crate::error::{JavaExceptionFromJvm, JvmError};
+use crate::heap::archive;
 use crate::heap::method_area::MethodArea;
 use crate::heap::{Heap, HeapRef};
 use crate::interpreter::Interpreter;
@@ -6,14 +8,24 @@ use crate::jdwp::agent::start_jdwp_agent;
 use crate::jdwp::{DebugEvent, DebugState};
 use crate::keys::{MethodId, MethodKey, Symbol, ThreadId};
 use crate::native::NativeRegistry;
-use crate::thread::JavaThreadState;
+use crate::rt::init::ClassInitTable;
+use crate::thread::dump::ThreadDumps;
+use crate::thread::monitor::MonitorTable;
+use crate::thread::park::ParkTable;
+use crate::thread::{JavaThreadState, ThreadRegistry, deadlock, lifecycle};
 use crate::vm::Value;
 use crate::vm::bootstrap_registry::BootstrapRegistry;
+use crate::vm::signal;
 use crate::vm::stack::FrameStack;
 use lasso::ThreadedRodeo;
-use std::path::PathBuf;
-use std::sync::{Arc, RwLock};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::mpsc::channel;
+use std::sync::{Arc, RwLock, Weak};
+use std::time::Duration;
 use tokio::sync::mpsc::unbounded_channel;
+use tracing_log::log::warn;
 
 mod class_loader;
 mod error;
@@ -22,21 +34,129 @@ mod interpreter;
 mod jdwp;
 pub mod keys;
 pub mod log_traces;
+mod module;
 mod native;
 pub mod rt;
 mod thread;
 mod vm;
 
+pub use thread::deadlock::{BlockedOn, Deadlock, DeadlockedThread};
+
 #[derive(Debug, Clone)]
 pub struct VmConfig {
     pub home: PathBuf,
     pub version: String,
-    pub main_class: String,
+    /// `None` when launched with `--module <module>` alone, the main class is then the
+    /// `ModuleMainClass` of that module.
+    pub main_class: Option<String>,
     pub class_path: Vec<String>,
+    pub module_path: Vec<String>,
+    /// Root module of the boot layer given by `--module`, `None` when the main class is in the
+    /// unnamed module.
+    pub main_module: Option<String>,
+    /// `<module>/<package>=<target>(,<target>)*` values of `--add-exports`.
+    pub add_exports: Vec<String>,
+    /// `<module>/<package>=<target>(,<target>)*` values of `--add-opens`.
+    pub add_opens: Vec<String>,
     pub initial_heap_size: usize,
     pub max_heap_size: usize,
     pub frame_stack_size: usize,
     pub jdwp_port: Option<u16>,
+    pub detect_deadlocks: bool,
+    pub verify: VerifyMode,
+    pub assertions: AssertionStatus,
+    /// Startup archive written by [`dump_archive`], the VM starts from the state it holds
+    /// instead of running the JDK initialization. The archived classes are parsed again from
+    /// the class files the archive keeps, without reading the runtime image. Ignored with a
+    /// warning when it can't be used.
+    pub archive: Option<PathBuf>,
+    /// Prints `[class,load] <class> source: <where>` for each class the VM reads for the
+    /// builtin loaders, like `-verbose:class` of hotspot.
+    pub verbose_class: bool,
+    /// File keeping the listings of the classpath directories between runs, a directory is
+    /// read again when its modification time changes.
+    pub classpath_index: Option<PathBuf>,
+}
+
+/// Which classes are type checked when they are linked, like `-Xverify` of hotspot.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VerifyMode {
+    None,
+    /// Classes defined by a class loader, the bootstrap classes are trusted.
+    #[default]
+    Remote,
+    All,
+}
+
+impl FromStr for VerifyMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::None),
+            "remote" => Ok(Self::Remote),
+            "all" => Ok(Self::All),
+            other => Err(format!(
+                "invalid verify mode '{other}', expected none, remote or all"
+            )),
+        }
+    }
+}
+
+/// Assertion switches of `-ea`/`-da`/`-esa`/`-dsa`, what `Class.desiredAssertionStatus`
+/// answers for a class whose loader set no status of its own.
+#[derive(Debug, Clone, Default)]
+pub struct AssertionStatus {
+    /// For the classes of the builtin loaders, `-ea` without a name enables them.
+    pub enabled: bool,
+    /// For the classes of the bootstrap loader, `-esa`.
+    pub system_enabled: bool,
+    /// Switches naming a class or a package, in command line order.
+    pub directives: Vec<AssertionDirective>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssertionDirective {
+    /// Binary name of a class, or of a package for `<package>...`, empty for the unnamed one.
+    pub name: String,
+    /// The switch covers the package and its subpackages.
+    pub package: bool,
+    pub enabled: bool,
+}
+
+impl AssertionStatus {
+    /// Like hotspot, the last switch naming the class wins, then the last one naming its
+    /// closest package, then the default of system or other classes.
+    pub fn enabled(&self, class_name: &str, system: bool) -> bool {
+        let last = |name: &str, package: bool| {
+            self.directives
+                .iter()
+                .rev()
+                .find(|d| d.package == package && d.name == name)
+                .map(|d| d.enabled)
+        };
+        if let Some(enabled) = last(class_name, false) {
+            return enabled;
+        }
+        // the unnamed package covers only its own classes
+        let mut package = class_name
+            .rsplit_once('.')
+            .map_or("", |(package, _)| package);
+        loop {
+            if let Some(enabled) = last(package, true) {
+                return enabled;
+            }
+            match package.rsplit_once('.') {
+                Some((parent, _)) => package = parent,
+                None => break,
+            }
+        }
+        if system {
+            self.system_enabled
+        } else {
+            self.enabled
+        }
+    }
 }
 
 //TODO: make it better
@@ -49,19 +169,37 @@ impl VmConfig {
             );
         }
     }
+
+    /// Feature release number, e.g. 25 for "25.0.1".
+    pub fn feature_version(&self) -> u32 {
+        self.version
+            .split(['.', '-', '+'])
+            .next()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0)
+    }
 }
 
 pub struct VirtualMachine {
+    this: Weak<VirtualMachine>,
     config: VmConfig,
-    method_area: RwLock<MethodArea>,
+    method_area: MethodArea,
     heap: RwLock<Heap>,
     native_registry: NativeRegistry,
     string_interner: Arc<ThreadedRodeo>,
     br: Arc<BootstrapRegistry>,
     debug_state: Arc<DebugState>,
+    threads: ThreadRegistry,
+    monitors: MonitorTable,
+    parkers: ParkTable,
+    class_init: ClassInitTable,
+    thread_dumps: ThreadDumps,
 }
 
 impl VirtualMachine {
+    /// How long [`VirtualMachine::thread_dump`] waits for a Java thread to reach a safepoint.
+    const THREAD_DUMP_TIMEOUT: Duration = Duration::from_secs(5);
+
     pub fn new(
         config: VmConfig,
         string_interner: Arc<ThreadedRodeo>,
@@ -69,30 +207,43 @@ impl VirtualMachine {
         config.validate();
         let (event_tx, event_rx) = unbounded_channel();
         let debug_state = Arc::new(DebugState::new(event_tx));
-        let (method_area, br) =
-            MethodArea::init(&config, string_interner.clone(), debug_state.clone()).map_err(
-                |e| {
-                    eprintln!("Error: Could not initialize JVM.");
-                    eprintln!("Caused by: {}", e.into_pretty_string(&string_interner));
-                },
-            )?;
+        let native_registry = NativeRegistry::new(string_interner.clone());
+        let (method_area, br) = MethodArea::init(
+            &config,
+            string_interner.clone(),
+            debug_state.clone(),
+            native_registry.substituted_methods().clone(),
+        )
+        .map_err(|e| {
+            eprintln!("Error: Could not initialize JVM.");
+            eprintln!("Caused by: {}", e.into_pretty_string(&string_interner));
+        })?;
         let heap = Self::create_heap(string_interner.clone(), &method_area).map_err(|e| {
             eprintln!("Error: Could not initialize JVM.");
             eprintln!("Caused by: {}", e.into_pretty_string(&string_interner));
         })?;
 
-        let native_registry = NativeRegistry::new(string_interner.clone());
-
-        let vm = Arc::new(Self {
+        let vm = Arc::new_cyclic(|this| Self {
+            this: this.clone(),
             config,
             native_registry,
             string_interner: string_interner.clone(),
-            method_area: RwLock::new(method_area),
+            method_area,
             heap: RwLock::new(heap),
             br,
             debug_state: debug_state.clone(),
+            threads: ThreadRegistry::default(),
+            monitors: MonitorTable::default(),
+            parkers: ParkTable::default(),
+            class_init: ClassInitTable::default(),
+            thread_dumps: ThreadDumps::default(),
         });
 
+        signal::install_vm_handlers();
+        if vm.config.detect_deadlocks {
+            deadlock::start_watchdog(Arc::downgrade(&vm));
+        }
+
         #[cfg(feature = "log-runtime-traces")]
         log_traces::debug::init(&vm);
 
@@ -108,10 +259,31 @@ impl VirtualMachine {
         // that's why I don't stop in debugger in initPhase1 etc..
         debug_state.wait_if_suspended();
 
-        let mut main_thread = vm.create_main_thread().map_err(|e| {
+        let main_thread_id = vm.threads.next_thread_id();
+        let archive = vm.config.archive.as_deref().and_then(|path| {
+            archive::load(&vm, path, main_thread_id)
+                .inspect_err(|message| {
+                    eprintln!(
+                        "Warning: Ignoring startup archive {}: {message}",
+                        path.display()
+                    )
+                })
+                .ok()
+        });
+        if let Some(archive) = archive {
+            let main_thread = archive::restore(&vm, archive, main_thread_id).map_err(|e| {
+                eprintln!("Error: Could not initialize JVM.");
+                eprintln!("Caused by: {}", e.into_pretty_string(&string_interner));
+            })?;
+            vm.threads.register(&main_thread, false);
+            return Ok((vm, main_thread));
+        }
+
+        let mut main_thread = vm.create_main_thread(main_thread_id).map_err(|e| {
             eprintln!("Error: Could not initialize JVM.");
             eprintln!("Caused by: {}", e.into_pretty_string(&string_interner));
         })?;
+        vm.threads.register(&main_thread, false);
 
         vm.initialize_main_thread(&mut main_thread).map_err(|e| {
             eprintln!("Error: Could not initialize JVM.");
@@ -133,6 +305,13 @@ impl VirtualMachine {
              */
         })?;
 
+        if let Err(e) = vm.initialize_builtin_loaders(&mut main_thread) {
+            warn!(
+                "Failed to read the builtin class loaders, classes will be defined by the bootstrap loader: {}",
+                e.into_pretty_string(&string_interner)
+            );
+        }
+
         Ok((vm, main_thread))
     }
 
@@ -162,7 +341,7 @@ impl VirtualMachine {
 
         let thread_class_id = self.br().get_java_lang_thread_id()?;
         let thread_constructor_id = self
-            .method_area_read()
+            .method_area()
             .get_instance_class(&thread_class_id)?
             .get_special_method_id(&self.br().thread_thread_group_and_name_constructor_mk)?;
         Interpreter::invoke_instance_method(
@@ -175,13 +354,14 @@ impl VirtualMachine {
                 Value::Ref(main_thread.name),
             ],
         )?;
+        lifecycle::mark_alive(self, main_thread.thread_obj, main_thread.id)?;
         Ok(())
     }
 
-    fn create_main_thread(&self) -> Result<JavaThreadState, JvmError> {
+    fn create_main_thread(&self, id: ThreadId) -> Result<JavaThreadState, JvmError> {
         let thread_class_id = self.br().get_java_lang_thread_id()?;
         let thread_instance_size = self
-            .method_area_read()
+            .method_area()
             .get_instance_class(&thread_class_id)?
             .get_instance_size()?;
         let main_thread_ref = self
@@ -191,7 +371,7 @@ impl VirtualMachine {
             .heap_write()
             .get_str_from_pool_or_new(self.br().main_sym)?;
         let thread = JavaThreadState {
-            id: ThreadId::from_index(0), // TODO: hardcoded for main thread
+            id,
             thread_obj: main_thread_ref,
             group_obj: 0,
             name: main_string_ref,
@@ -206,7 +386,7 @@ impl VirtualMachine {
     ) -> Result<HeapRef, JvmError> {
         let system_thread_group_class_id = self.br().get_java_lang_thread_group_id()?;
         let (thread_group_no_arg_constructor_id, thread_group_instance_size) = {
-            let ma = self.method_area_read();
+            let ma = self.method_area();
             let thread_group_class = ma.get_instance_class(&system_thread_group_class_id)?;
             (
                 thread_group_class.get_special_method_id(&self.br().no_arg_constructor_mk)?,
@@ -233,7 +413,7 @@ impl VirtualMachine {
     ) -> Result<HeapRef, JvmError> {
         let system_thread_group_class_id = self.br().get_java_lang_thread_group_id()?;
         let (thread_group_constructor_id, thread_group_instance_size) = {
-            let ma = self.method_area_read();
+            let ma = self.method_area();
             let thread_group_class = ma.get_instance_class(&system_thread_group_class_id)?;
             (
                 thread_group_class.get_special_method_id(
@@ -271,7 +451,7 @@ impl VirtualMachine {
         // Run initPhase1
 
         let init_phase1_method_id = self
-            .method_area_read()
+            .method_area()
             .get_instance_class(&system_class_id)?
             .get_special_method_id(&init_phase1_method_key)?;
 
@@ -296,6 +476,26 @@ impl VirtualMachine {
         Ok(())
     }
 
+    /// Reads the platform and app loaders created by `ClassLoaders.<clinit>`, which become the
+    /// defining loaders of the classes the VM finds outside the boot layer.
+    fn initialize_builtin_loaders(&self, thread: &mut JavaThreadState) -> Result<(), JvmError> {
+        let class_loaders_id = self
+            .method_area()
+            .get_class_id_or_load(self.br().jdk_internal_loader_class_loaders_sym, thread.id)?;
+        Interpreter::ensure_initialized(thread, Some(class_loaders_id), self)?;
+        let ma = self.method_area();
+        let platform = ma
+            .get_static_field_value(
+                &class_loaders_id,
+                &self.br().class_loaders_platform_loader_fk,
+            )?
+            .as_obj_ref()?;
+        let app = ma
+            .get_static_field_value(&class_loaders_id, &self.br().class_loaders_app_loader_fk)?
+            .as_obj_ref()?;
+        ma.set_builtin_loaders(BuiltinLoaders { platform, app })
+    }
+
     // TODO: refactor and improve error handling. ideally can't fail
     //TODO: exception arg should be actually JvmError, like any error
     fn map_rust_error_to_java_exception(
@@ -305,14 +505,14 @@ impl VirtualMachine {
     ) -> Result<HeapRef, JvmError> {
         let exception_ref = exception.as_reference();
         let class_id = self
-            .method_area_write()
+            .method_area()
             // TODO: fix interner usage, replace with direct symbol
             .get_class_id_or_load(
                 self.interner().get_or_intern(exception_ref.class),
                 thread.id,
             )?;
         let (method_id, instance_size) = {
-            let ma = self.method_area_read();
+            let ma = self.method_area();
             let class = ma.get_instance_class(&class_id)?;
             (
                 class.get_special_method_id(
@@ -344,7 +544,7 @@ impl VirtualMachine {
     fn unhandled_exception(&self, thread: &mut JavaThreadState, exception: JvmError) {
         if let JvmError::JavaExceptionThrown(exception_ref) = exception {
             let get_thread_group_method_id = self
-                .method_area_read()
+                .method_area()
                 .get_class(&self.br().get_java_lang_thread_id().unwrap())
                 .get_vtable_method_id(&self.br().thread_get_thread_group_mk)
                 .unwrap();
@@ -359,7 +559,7 @@ impl VirtualMachine {
             .as_obj_ref()
             .unwrap();
             let uncaught_exception_method_id = self
-                .method_area_read()
+                .method_area()
                 .get_class(&self.br().get_java_lang_thread_group_id().unwrap())
                 .get_vtable_method_id(&self.br().thread_group_uncaught_exception_mk)
                 .unwrap();
@@ -379,6 +579,105 @@ impl VirtualMachine {
         }
     }
 
+    /// Returns a `jstack`-like dump of all Java threads, or `None` if no Java thread reached
+    /// a safepoint in time (e.g. the VM is not running Java code at the moment).
+    pub fn thread_dump(&self) -> Option<String> {
+        let (tx, rx) = channel();
+        self.thread_dumps.request(false, Some(tx));
+        rx.recv_timeout(Self::THREAD_DUMP_TIMEOUT).ok()
+    }
+
+    /// Prints a thread dump as for `SIGQUIT` and waits for it, returns false if it wasn't
+    /// printed in time.
+    pub(crate) fn print_thread_dump(&self) -> bool {
+        let (tx, rx) = channel();
+        self.thread_dumps.request(true, Some(tx));
+        rx.recv_timeout(Self::THREAD_DUMP_TIMEOUT).is_ok()
+    }
+
+    /// Terminates the process immediately with the given status, without running shutdown
+    /// hooks. This is where `Runtime.halt`, `System.exit` (after hooks) and JDWP `VM.Exit` end.
+    pub fn halt(&self, status: i32) -> ! {
+        self.method_area.class_loader().save_classpath_index();
+        self.debug_state.send_event(DebugEvent::VMDeath);
+        let _ = std::io::stdout().flush();
+        let _ = std::io::stderr().flush();
+        std::process::exit(status)
+    }
+
+    /// Returns all Java-level deadlocks (cycles of threads blocked on monitors or
+    /// `AbstractOwnableSynchronizer`s owned by each other).
+    pub fn find_deadlocks(&self) -> Result<Vec<Deadlock>, JvmError> {
+        deadlock::find_deadlocks(self)
+    }
+
+    /// Called by a Java thread when the safepoint flag is raised, handles pending signals and
+    /// thread dump requests.
+    fn safepoint_poll(&self, thread: &mut JavaThreadState) -> Result<(), JvmError> {
+        let pending_signals = signal::take_pending_signals();
+        if pending_signals & (1 << libc::SIGQUIT) != 0 {
+            self.thread_dumps.request(true, None);
+        }
+        self.thread_dumps.answer(self, thread)?;
+        for sig in 1..64 {
+            if sig != libc::SIGQUIT && pending_signals & (1 << sig) != 0 {
+                self.dispatch_java_signal(thread, sig);
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs `jdk.internal.misc.Signal.dispatch` for a signal with a Java-level handler. If the
+    /// handler can't be run, the signal gets its default action instead of being lost.
+    fn dispatch_java_signal(&self, thread: &mut JavaThreadState, sig: i32) {
+        let res = (|| {
+            let signal_class_id = self
+                .method_area()
+                .get_class_id_or_load(self.br().jdk_internal_misc_signal_sym, thread.id)?;
+            let dispatch_method_id = self
+                .method_area()
+                .get_static_method_id(&signal_class_id, self.br().signal_dispatch_mk)?;
+            Interpreter::invoke_static_method(
+                thread,
+                dispatch_method_id,
+                self,
+                vec![Value::Integer(sig)],
+            )
+        })();
+        if let Err(e) = res {
+            warn!(
+                "Failed to dispatch signal {} to Java handler: {}, falling back to default action",
+                sig, e
+            );
+            signal::raise_with_default_action(sig);
+        }
+    }
+
+    /// The `DestroyJavaVM` sequence: the main thread terminates, then the VM waits for all
+    /// non-daemon threads and runs shutdown hooks via `Shutdown.shutdown()`. Daemon threads
+    /// are abandoned when the process exits.
+    fn destroy(&self, main_thread: &mut JavaThreadState) {
+        lifecycle::exit_thread(self, main_thread);
+        self.threads.wait_for_non_daemon_threads(main_thread.id);
+        let res = (|| {
+            let shutdown_class_id = self
+                .method_area()
+                .get_class_id_or_load(self.br().java_lang_shutdown_sym, main_thread.id)?;
+            let shutdown_method_id = self
+                .method_area()
+                .get_static_method_id(&shutdown_class_id, self.br().shutdown_shutdown_mk)?;
+            Interpreter::invoke_static_method(main_thread, shutdown_method_id, self, vec![])
+        })();
+        if let Err(e) = res {
+            eprintln!(
+                "Error running shutdown hooks: {}",
+                e.into_pretty_string(self.interner())
+            );
+        }
+        self.method_area.class_loader().save_classpath_index();
+        self.debug_state.send_event(DebugEvent::VMDeath);
+    }
+
     pub fn interner(&self) -> &ThreadedRodeo {
         &self.string_interner
     }
@@ -393,7 +692,7 @@ impl VirtualMachine {
     }
 
     pub fn pretty_method_not_found_message(&self, method_id: &MethodId) -> String {
-        let ma = self.method_area_read();
+        let ma = self.method_area();
         let method = ma.get_method(method_id);
         let method_desc = ma.get_method_descriptor(&method.descriptor_id());
         let class_sym = ma.get_class(&method.class_id()).get_name();
@@ -402,8 +701,37 @@ impl VirtualMachine {
         method_desc.to_java_signature(class_name, method_name)
     }
 
-    pub fn method_area_read(&self) -> std::sync::RwLockReadGuard<'_, MethodArea> {
-        self.method_area.read().unwrap()
+    pub fn method_area(&self) -> &MethodArea {
+        &self.method_area
+    }
+
+    /// The main class given on the command line, or else the `ModuleMainClass` of the
+    /// `--module` module. A main class given with `--module` has to be in that module.
+    fn main_class(&self) -> Result<String, String> {
+        let Some(module) = self.method_area.modules().main_module() else {
+            return self
+                .config
+                .main_class
+                .clone()
+                .ok_or_else(|| "no main class specified".to_string());
+        };
+        let main_class = match &self.config.main_class {
+            Some(main_class) => main_class.clone(),
+            None => module.descriptor.main_class.clone().ok_or_else(|| {
+                format!(
+                    "Module {} does not have a ModuleMainClass attribute, use -m <module>/<main-class>",
+                    module.name()
+                )
+            })?,
+        };
+        if self.method_area.modules().module_of_class(&main_class) != module.id() {
+            return Err(format!(
+                "Could not find or load main class {} in module {}",
+                main_class.replace('/', "."),
+                module.name()
+            ));
+        }
+        Ok(main_class)
     }
 
     pub fn heap_read(&self) -> std::sync::RwLockReadGuard<'_, Heap> {
@@ -413,47 +741,156 @@ impl VirtualMachine {
     pub fn heap_write(&self) -> std::sync::RwLockWriteGuard<'_, Heap> {
         self.heap.write().unwrap()
     }
-
-    pub fn method_area_write(&self) -> std::sync::RwLockWriteGuard<'_, MethodArea> {
-        self.method_area.write().unwrap()
-    }
 }
 
-pub fn start(config: VmConfig) -> Result<(), ()> {
+/// Runs the main class and returns the process exit status: 0 if `main` completed normally,
+/// 1 if the VM failed to start or `main` threw. `System.exit` terminates the process directly.
+pub fn start(config: VmConfig) -> i32 {
     let string_interner = Arc::new(ThreadedRodeo::default());
-    let (mut vm, mut main_thread) =
-        VirtualMachine::new(config, string_interner.clone()).map_err(|_| {})?;
+    let Ok((vm, mut main_thread)) = VirtualMachine::new(config, string_interner.clone()) else {
+        return 1;
+    };
 
     #[cfg(feature = "log-runtime-traces")]
     log_traces::debug::init(&vm);
 
-    let main_class_sym = vm.string_interner.get_or_intern(&vm.config.main_class);
-    let main_class_id = vm
-        .method_area_write()
+    let main_class = match vm.main_class() {
+        Ok(main_class) => main_class,
+        Err(message) => {
+            eprintln!("Error: {message}");
+            return 1;
+        }
+    };
+    let main_class_sym = vm.string_interner.get_or_intern(&main_class);
+    let main_class_id = match vm
+        .method_area()
         .get_class_id_or_load(main_class_sym, main_thread.id)
-        .map_err(|e| {
+    {
+        Ok(class_id) => class_id,
+        Err(e) => {
             eprintln!(
                 "Error: Could not find or load main class {}",
-                vm.config.main_class.replace('/', ".")
+                main_class.replace('/', ".")
             );
             eprintln!("Caused by: {}", e.into_pretty_string(&string_interner));
-        })?;
+            return 1;
+        }
+    };
     let main_method_id = vm
-        .method_area_read()
+        .method_area()
         .get_instance_class(&main_class_id)
         .unwrap()
         .get_special_method_id(&vm.br().main_mk)
-        .map_err(|_| JvmError::MainClassNotFound(vm.config.main_class.replace('/', ".")))
+        .map_err(|_| JvmError::MainClassNotFound(main_class.replace('/', ".")))
         .unwrap();
     debug_log_method!(&main_method_id, "Main method found");
 
     // TODO: it works more or less correctly, but should be improved
-    let res = Interpreter::invoke_static_method(&mut main_thread, main_method_id, &mut vm, vec![]);
-    vm.debug_state.send_event(DebugEvent::VMDeath);
-    if let Err(e) = res {
+    let res = Interpreter::invoke_static_method(&mut main_thread, main_method_id, &vm, vec![]);
+    let exit_code = if let Err(e) = res {
         vm.unhandled_exception(&mut main_thread, e);
-        Err(())
+        1
     } else {
-        Ok(())
+        0
+    };
+    vm.destroy(&mut main_thread);
+    exit_code
+}
+
+/// Boots the VM and writes its state to a startup archive at `path` instead of running a main
+/// class, see [`VmConfig::archive`]. Returns the process exit status.
+pub fn dump_archive(config: VmConfig, path: &Path) -> i32 {
+    let string_interner = Arc::new(ThreadedRodeo::default());
+    let Ok((vm, main_thread)) = VirtualMachine::new(config, string_interner) else {
+        return 1;
+    };
+    match archive::dump(&vm, &main_thread, path) {
+        Ok(()) => 0,
+        Err(message) => {
+            eprintln!(
+                "Error: Could not dump startup archive {}: {message}",
+                path.display()
+            );
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn directive(name: &str, package: bool, enabled: bool) -> AssertionDirective {
+        AssertionDirective {
+            name: name.to_string(),
+            package,
+            enabled,
+        }
+    }
+
+    #[test]
+    fn assertions_follow_the_defaults_without_directives() {
+        // given
+        let status = AssertionStatus {
+            enabled: true,
+            system_enabled: false,
+            directives: Vec::new(),
+        };
+
+        // when
+        let application = status.enabled("com.example.Main", false);
+        let system = status.enabled("java.lang.String", true);
+
+        // then
+        assert!(application);
+        assert!(!system);
+    }
+
+    #[test]
+    fn closest_package_and_last_directive_win() {
+        // given
+        let status = AssertionStatus {
+            enabled: false,
+            system_enabled: false,
+            directives: vec![
+                directive("com", true, true),
+                directive("com.example.internal", true, false),
+                directive("com.example.internal.Checked", false, false),
+                directive("com.example.internal.Checked", false, true),
+                directive("java.util", true, true),
+            ],
+        };
+
+        // when
+        let subpackage = status.enabled("com.example.Main", false);
+        let closer_package = status.enabled("com.example.internal.Other", false);
+        let class = status.enabled("com.example.internal.Checked", false);
+        let system = status.enabled("java.util.ArrayList", true);
+        let prefix_only = status.enabled("common.Main", false);
+
+        // then
+        assert!(subpackage);
+        assert!(!closer_package);
+        assert!(class);
+        assert!(system);
+        assert!(!prefix_only);
+    }
+
+    #[test]
+    fn unnamed_package_covers_only_its_own_classes() {
+        // given
+        let status = AssertionStatus {
+            enabled: false,
+            system_enabled: false,
+            directives: vec![directive("", true, true)],
+        };
+
+        // when
+        let unnamed = status.enabled("Main", false);
+        let named = status.enabled("com.example.Main", false);
+
+        // then
+        assert!(unnamed);
+        assert!(!named);
     }
 }
@@ -51,7 +51,7 @@ macro_rules! debug_log_method {
         #[cfg(feature = "log-runtime-traces")]
         {
             crate::log_traces::debug::with_vm(|vm| {
-                let ma = vm.method_area_read();
+                let ma = vm.method_area();
                 let method = ma.get_method($method_id);
                 let class_name = vm
                     .interner()
@@ -73,7 +73,7 @@ macro_rules! error_log_method {
         #[cfg(feature = "log-runtime-traces")]
         {
             crate::log_traces::debug::with_vm(|vm| {
-                let ma = vm.method_area_read();
+                let ma = vm.method_area();
                 let method = ma.get_method($method_id);
                 let class_name = vm
                     .interner()
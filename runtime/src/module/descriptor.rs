@@ -0,0 +1,297 @@
+use crate::build_exception;
+use crate::error::JvmError;
+use common::error::ClassFormatErr;
+use common::utils::manifest::Manifest;
+use jclass::ClassFile;
+use jclass::attribute::class::{
+    ClassAttr, DO_NOT_RESOLVE_BY_DEFAULT, ModuleAttribute, ModuleExports,
+};
+use jclass::constant::pool::ConstantPool;
+use std::collections::BTreeSet;
+
+pub const JAVA_BASE: &str = "java.base";
+
+#[derive(Debug, Clone)]
+pub struct Requires {
+    pub name: String,
+    pub transitive: bool,
+    /// `requires static`: needed at compile time, optional at run time.
+    pub static_phase: bool,
+}
+
+/// An `exports` or `opens` directive.
+#[derive(Debug, Clone)]
+pub struct PackageDirective {
+    /// Internal form, e.g. `java/lang`.
+    pub package: String,
+    /// Empty for an unqualified directive.
+    pub targets: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Provides {
+    /// Binary name of the service interface, e.g. `java/sql/Driver`.
+    pub service: String,
+    pub providers: Vec<String>,
+}
+
+/// https://docs.oracle.com/javase/specs/jls/se25/html/jls-7.html#jls-7.7
+///
+/// Names of classes and packages are kept in internal form, as the VM looks them up.
+#[derive(Debug, Clone)]
+pub struct ModuleDescriptor {
+    pub name: String,
+    pub version: Option<String>,
+    /// Every package is opened to every module.
+    pub open: bool,
+    /// Plain JAR on the module path: reads every module and exports and opens all its packages.
+    pub automatic: bool,
+    pub requires: Vec<Requires>,
+    pub exports: Vec<PackageDirective>,
+    pub opens: Vec<PackageDirective>,
+    pub uses: Vec<String>,
+    pub provides: Vec<Provides>,
+    pub packages: BTreeSet<String>,
+    pub main_class: Option<String>,
+    /// From the JDK-specific `ModuleResolution` attribute, incubator modules are not resolved
+    /// unless something asks for them.
+    pub do_not_resolve_by_default: bool,
+}
+
+impl ModuleDescriptor {
+    /// Reads a `module-info.class`. `packages` adds the packages found in the module content,
+    /// `module-info` only lists all of them when the optional `ModulePackages` attribute is
+    /// present.
+    pub fn read(bytes: Vec<u8>, packages: BTreeSet<String>) -> Result<Self, JvmError> {
+        let invalid = |e: ClassFormatErr| {
+            build_exception!(FindException, "Error reading module-info.class: {e:?}")
+        };
+        let cf = ClassFile::try_from(bytes).map_err(invalid)?;
+        if !cf.access_flags.is_module() {
+            return Err(build_exception!(
+                FindException,
+                "module-info.class is not a module descriptor (ACC_MODULE not set)"
+            ));
+        }
+        let Some(module) = cf.attributes.iter().find_map(|attr| match attr {
+            ClassAttr::Module(module) => Some(module),
+            _ => None,
+        }) else {
+            return Err(build_exception!(
+                FindException,
+                "module-info.class does not have a Module attribute"
+            ));
+        };
+        Self::from_class_file(&cf, module, packages).map_err(invalid)
+    }
+
+    fn from_class_file(
+        cf: &ClassFile,
+        module: &ModuleAttribute,
+        mut packages: BTreeSet<String>,
+    ) -> Result<Self, ClassFormatErr> {
+        let cp = &cf.cp;
+        let mut main_class = None;
+        let mut do_not_resolve_by_default = false;
+        for attr in &cf.attributes {
+            match attr {
+                ClassAttr::ModulePackages(indexes) => {
+                    for idx in indexes {
+                        packages.insert(cp.get_package_name(idx)?.to_string());
+                    }
+                }
+                ClassAttr::ModuleMainClass(idx) => {
+                    main_class = Some(cp.get_class_name(idx)?.to_string())
+                }
+                ClassAttr::ModuleResolution(flags) => {
+                    do_not_resolve_by_default = flags & DO_NOT_RESOLVE_BY_DEFAULT != 0
+                }
+                _ => {}
+            }
+        }
+
+        let requires = module
+            .requires
+            .iter()
+            .map(|req| {
+                Ok(Requires {
+                    name: cp.get_module_name(&req.requires_idx)?.to_string(),
+                    transitive: req.requires_flags.is_transitive(),
+                    static_phase: req.requires_flags.is_static_phase(),
+                })
+            })
+            .collect::<Result<_, ClassFormatErr>>()?;
+        let exports = Self::package_directives(cp, &module.exports)?;
+        let opens = Self::package_directives(cp, &module.opens)?;
+        let uses = module
+            .uses
+            .iter()
+            .map(|idx| cp.get_class_name(idx).map(str::to_string))
+            .collect::<Result<_, _>>()?;
+        let provides = module
+            .provides
+            .iter()
+            .map(|p| {
+                Ok(Provides {
+                    service: cp.get_class_name(&p.provides_idx)?.to_string(),
+                    providers: p
+                        .provides_with_idx
+                        .iter()
+                        .map(|idx| cp.get_class_name(idx).map(str::to_string))
+                        .collect::<Result<_, _>>()?,
+                })
+            })
+            .collect::<Result<Vec<_>, ClassFormatErr>>()?;
+
+        // packages of directives are members of the module even if no class was found there
+        packages.extend(exports.iter().map(|e| e.package.clone()));
+        packages.extend(opens.iter().map(|o| o.package.clone()));
+        for provides in &provides {
+            packages.extend(provides.providers.iter().filter_map(|p| package_of(p)));
+        }
+        if let Some(main_class) = &main_class {
+            packages.extend(package_of(main_class));
+        }
+
+        Ok(Self {
+            name: cp.get_module_name(&module.module_name_idx)?.to_string(),
+            version: match module.module_version_idx {
+                0 => None,
+                idx => Some(cp.get_utf8(&idx)?.to_string()),
+            },
+            open: module.module_flags.is_open(),
+            automatic: false,
+            requires,
+            exports,
+            opens,
+            uses,
+            provides,
+            packages,
+            main_class,
+            do_not_resolve_by_default,
+        })
+    }
+
+    fn package_directives(
+        cp: &ConstantPool,
+        entries: &[ModuleExports],
+    ) -> Result<Vec<PackageDirective>, ClassFormatErr> {
+        entries
+            .iter()
+            .map(|entry| {
+                Ok(PackageDirective {
+                    package: cp.get_package_name(&entry.package_idx)?.to_string(),
+                    targets: entry
+                        .to_idx
+                        .iter()
+                        .map(|idx| cp.get_module_name(idx).map(str::to_string))
+                        .collect::<Result<_, _>>()?,
+                })
+            })
+            .collect()
+    }
+
+    /// Descriptor of a JAR without `module-info.class` on the module path, see
+    /// `java.lang.module.ModuleFinder.of`. The name comes from the `Automatic-Module-Name`
+    /// manifest attribute or else from the file name. Providers are taken from
+    /// `META-INF/services`.
+    pub fn automatic(
+        file_name: &str,
+        manifest: Option<&Manifest>,
+        packages: BTreeSet<String>,
+        services: Vec<Provides>,
+    ) -> Result<Self, JvmError> {
+        let name = match manifest.and_then(|m| m.main_attribute("Automatic-Module-Name")) {
+            Some(name) => name.trim().to_string(),
+            None => automatic_module_name(file_name).ok_or_else(|| {
+                build_exception!(
+                    FindException,
+                    "Unable to derive module descriptor for {file_name}"
+                )
+            })?,
+        };
+        let version = file_name
+            .strip_suffix(".jar")
+            .and_then(|stem| stem.get(version_start(stem)?..))
+            .map(str::to_string);
+        let main_class = manifest
+            .and_then(|m| m.main_class())
+            .map(|c| c.trim().replace('.', "/"))
+            .filter(|c| package_of(c).is_some_and(|p| packages.contains(&p)));
+        let provides = services
+            .into_iter()
+            .filter(|s| {
+                s.providers
+                    .iter()
+                    .all(|p| package_of(p).is_some_and(|p| packages.contains(&p)))
+            })
+            .collect();
+
+        Ok(Self {
+            name,
+            version,
+            open: false,
+            automatic: true,
+            requires: vec![Requires {
+                name: JAVA_BASE.to_string(),
+                transitive: false,
+                static_phase: false,
+            }],
+            exports: Vec::new(),
+            opens: Vec::new(),
+            uses: Vec::new(),
+            provides,
+            packages,
+            main_class,
+            do_not_resolve_by_default: false,
+        })
+    }
+
+    /// Whether the module exports at least one package to everybody, the criterion for system
+    /// modules to be default roots when the main class is in the unnamed module.
+    pub fn exports_api(&self) -> bool {
+        self.automatic || self.exports.iter().any(|e| e.targets.is_empty())
+    }
+}
+
+/// Package of a binary name in internal form, `None` for the unnamed package.
+pub fn package_of(binary_name: &str) -> Option<String> {
+    binary_name
+        .rsplit_once('/')
+        .map(|(package, _)| package.to_string())
+}
+
+/// Offset of the version in a JAR file name without extension: the first `-` followed by a digit
+/// that starts a `.`-separated or trailing number, e.g. `-1.2` in `foo-bar-1.2-SNAPSHOT`.
+fn version_start(stem: &str) -> Option<usize> {
+    let bytes = stem.as_bytes();
+    (0..bytes.len().saturating_sub(1))
+        .find(|&i| {
+            bytes[i] == b'-' && bytes[i + 1].is_ascii_digit() && {
+                let rest = &bytes[i + 1..];
+                let digits = rest.iter().take_while(|b| b.is_ascii_digit()).count();
+                digits == rest.len() || rest[digits] == b'.'
+            }
+        })
+        .map(|i| i + 1)
+}
+
+/// Same derivation as `ModuleFinder.of`: drop `.jar` and the version, turn every run of
+/// non-alphanumeric characters into a single dot and trim dots at both ends.
+fn automatic_module_name(file_name: &str) -> Option<String> {
+    let stem = file_name.strip_suffix(".jar").unwrap_or(file_name);
+    let stem = match version_start(stem) {
+        Some(start) => &stem[..start - 1],
+        None => stem,
+    };
+    let mut name = String::with_capacity(stem.len());
+    for c in stem.chars() {
+        if c.is_ascii_alphanumeric() {
+            name.push(c);
+        } else if !name.is_empty() && !name.ends_with('.') {
+            name.push('.');
+        }
+    }
+    let name = name.trim_end_matches('.');
+    (!name.is_empty()).then(|| name.to_string())
+}
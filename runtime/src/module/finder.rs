@@ -0,0 +1,218 @@
+use crate::error::JvmError;
+use crate::module::descriptor::{ModuleDescriptor, Provides, package_of};
+use crate::{build_exception, debug_log};
+use common::utils::manifest::Manifest;
+use common::utils::zip::ZipArchive;
+use jimage::JImage;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use walkdir::WalkDir;
+
+const MODULE_INFO: &str = "module-info.class";
+const SERVICES_DIR: &str = "META-INF/services/";
+
+/// Where the classes of a module are read from.
+#[derive(Debug, Clone)]
+pub enum ModuleSource {
+    /// A module of the runtime image (`lib/modules`).
+    System,
+    /// A directory with `module-info.class` at its root.
+    Exploded(PathBuf),
+    /// A modular or automatic JAR.
+    Archive(Arc<ZipArchive>),
+}
+
+#[derive(Debug, Clone)]
+pub struct ModuleReference {
+    pub descriptor: ModuleDescriptor,
+    pub source: ModuleSource,
+}
+
+/// Modules of the runtime image, each described by its `/<module>/module-info.class`.
+pub fn system_modules(jimage: &JImage) -> Result<Vec<ModuleReference>, JvmError> {
+    let mut packages: HashMap<&str, BTreeSet<String>> = HashMap::new();
+    for (package, module) in jimage.packages() {
+        packages
+            .entry(module.as_str())
+            .or_default()
+            .insert(package.replace('.', "/"));
+    }
+
+    let mut modules = Vec::new();
+    let io_error = |e: std::io::Error| build_exception!(IOException, e.to_string());
+    for module in jimage.modules().map_err(io_error)? {
+        let Some(bytes) = jimage
+            .open_resource(&module, MODULE_INFO)
+            .map_err(io_error)?
+        else {
+            debug_log!("Module {module} of the runtime image has no module-info.class");
+            continue;
+        };
+        let descriptor = ModuleDescriptor::read(
+            bytes.into_owned(),
+            packages.remove(module.as_str()).unwrap_or_default(),
+        )?;
+        modules.push(ModuleReference {
+            descriptor,
+            source: ModuleSource::System,
+        });
+    }
+    Ok(modules)
+}
+
+/// Modules of `--module-path`, in path order. An entry is either a module (a JAR or a directory
+/// with `module-info.class`) or a directory of modules. A module name found again in a later
+/// entry is ignored, but two modules with the same name in one directory are an error.
+pub fn module_path(entries: &[String]) -> Result<Vec<ModuleReference>, JvmError> {
+    let mut modules: Vec<ModuleReference> = Vec::new();
+    for entry in entries {
+        let path = Path::new(entry);
+        let found = if path.is_dir() && !path.join(MODULE_INFO).is_file() {
+            scan_directory(path)?
+        } else if path.exists() {
+            vec![read_module(path)?]
+        } else {
+            debug_log!("Module path entry {entry} does not exist");
+            continue;
+        };
+        for module in found {
+            if modules
+                .iter()
+                .all(|m| m.descriptor.name != module.descriptor.name)
+            {
+                modules.push(module);
+            }
+        }
+    }
+    Ok(modules)
+}
+
+fn scan_directory(dir: &Path) -> Result<Vec<ModuleReference>, JvmError> {
+    let read_dir = std::fs::read_dir(dir)
+        .map_err(|e| build_exception!(FindException, "Error reading {}: {e}", dir.display()))?;
+    let mut children: Vec<_> = read_dir.filter_map(Result::ok).map(|e| e.path()).collect();
+    children.sort();
+
+    let mut modules: Vec<(PathBuf, ModuleReference)> = Vec::new();
+    for child in children {
+        let is_jar = child.is_file() && child.extension().is_some_and(|ext| ext == "jar");
+        let is_exploded = child.is_dir() && child.join(MODULE_INFO).is_file();
+        if !is_jar && !is_exploded {
+            continue;
+        }
+        let module = read_module(&child)?;
+        if let Some((other, _)) = modules
+            .iter()
+            .find(|(_, m)| m.descriptor.name == module.descriptor.name)
+        {
+            return Err(build_exception!(
+                FindException,
+                "Two versions of module {} found in {} ({} and {})",
+                module.descriptor.name,
+                dir.display(),
+                file_name(other),
+                file_name(&child)
+            ));
+        }
+        modules.push((child, module));
+    }
+    Ok(modules.into_iter().map(|(_, module)| module).collect())
+}
+
+fn read_module(path: &Path) -> Result<ModuleReference, JvmError> {
+    if path.is_dir() {
+        read_exploded(path)
+    } else {
+        read_jar(path)
+    }
+}
+
+fn read_exploded(root: &Path) -> Result<ModuleReference, JvmError> {
+    let mut packages = BTreeSet::new();
+    for file in WalkDir::new(root).into_iter().filter_map(Result::ok) {
+        let path = file.path();
+        if !file.file_type().is_file() || path.extension().is_none_or(|ext| ext != "class") {
+            continue;
+        }
+        if let Ok(rel) = path.strip_prefix(root) {
+            let rel: Vec<_> = rel.iter().map(|c| c.to_string_lossy()).collect();
+            packages.extend(package_of(&rel.join("/")));
+        }
+    }
+    let bytes = std::fs::read(root.join(MODULE_INFO)).map_err(|e| {
+        build_exception!(
+            FindException,
+            "Error reading module: {}: {e}",
+            root.display()
+        )
+    })?;
+    Ok(ModuleReference {
+        descriptor: ModuleDescriptor::read(bytes, packages)?,
+        source: ModuleSource::Exploded(root.to_path_buf()),
+    })
+}
+
+fn read_jar(path: &Path) -> Result<ModuleReference, JvmError> {
+    let read_error = |e: &dyn std::fmt::Display| {
+        build_exception!(
+            FindException,
+            "Error reading module: {}: {e}",
+            path.display()
+        )
+    };
+    let archive = ZipArchive::open(path).map_err(|e| read_error(&e))?;
+
+    let packages: BTreeSet<String> = archive
+        .entries()
+        .iter()
+        .filter(|e| e.name.ends_with(".class") && !e.name.starts_with("META-INF/"))
+        .filter(|e| e.name != MODULE_INFO)
+        .filter_map(|e| package_of(&e.name))
+        .collect();
+
+    let descriptor = match archive.read_by_name(MODULE_INFO) {
+        Some(bytes) => ModuleDescriptor::read(bytes.map_err(|e| read_error(&e))?, packages)?,
+        None => {
+            let manifest = Manifest::read_from(&archive).map_err(|e| read_error(&e))?;
+            let services = read_services(&archive).map_err(|e| read_error(&e))?;
+            ModuleDescriptor::automatic(&file_name(path), manifest.as_ref(), packages, services)?
+        }
+    };
+    debug_log!("Found module {} in {}", descriptor.name, path.display());
+    Ok(ModuleReference {
+        descriptor,
+        source: ModuleSource::Archive(Arc::new(archive)),
+    })
+}
+
+/// `META-INF/services/<service>` files list one provider class per line, `#` starts a comment.
+fn read_services(archive: &ZipArchive) -> Result<Vec<Provides>, common::utils::zip::ZipError> {
+    let mut services = Vec::new();
+    for entry in archive.entries() {
+        let Some(service) = entry.name.strip_prefix(SERVICES_DIR) else {
+            continue;
+        };
+        if service.is_empty() || service.contains('/') {
+            continue;
+        }
+        let content = archive.read(entry)?;
+        let providers = String::from_utf8_lossy(&content)
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or_default().trim())
+            .filter(|line| !line.is_empty())
+            .map(|line| line.replace('.', "/"))
+            .collect();
+        services.push(Provides {
+            service: service.replace('.', "/"),
+            providers,
+        });
+    }
+    Ok(services)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
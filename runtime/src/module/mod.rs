@@ -0,0 +1,444 @@
+use crate::error::JvmError;
+use crate::module::descriptor::{JAVA_BASE, ModuleDescriptor, PackageDirective};
+use crate::module::finder::{ModuleReference, ModuleSource};
+use crate::{VmConfig, build_exception, debug_log};
+use jimage::JImage;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+pub mod descriptor;
+pub mod finder;
+
+/// Index of a module of the boot layer, or the unnamed module of the application class loader.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct ModuleId(u32);
+
+impl ModuleId {
+    /// Classes from the classpath, and classes of packages that no named module contains.
+    pub const UNNAMED: ModuleId = ModuleId(u32::MAX);
+
+    pub fn is_named(&self) -> bool {
+        *self != Self::UNNAMED
+    }
+}
+
+/// Modules an `exports` or `opens` directive applies to.
+#[derive(Debug, Default)]
+struct Targets {
+    all: bool,
+    all_unnamed: bool,
+    modules: HashSet<ModuleId>,
+}
+
+impl Targets {
+    fn contains(&self, module: ModuleId) -> bool {
+        self.all || self.modules.contains(&module) || (self.all_unnamed && !module.is_named())
+    }
+}
+
+#[derive(Debug)]
+pub struct ResolvedModule {
+    id: ModuleId,
+    pub descriptor: ModuleDescriptor,
+    pub source: ModuleSource,
+    reads: HashSet<ModuleId>,
+    /// Package -> modules it is exported to.
+    exports: HashMap<String, Targets>,
+    /// Package -> modules it is opened to.
+    opens: HashMap<String, Targets>,
+}
+
+impl ResolvedModule {
+    pub fn id(&self) -> ModuleId {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.descriptor.name
+    }
+}
+
+/// The boot layer: modules resolved from the runtime image and `--module-path`, with the
+/// readability graph and the exports and opens in effect, including `--add-exports` and
+/// `--add-opens`.
+///
+/// https://docs.oracle.com/en/java/javase/25/docs/api/java.base/java/lang/module/package-summary.html
+#[derive(Debug)]
+pub struct ModuleGraph {
+    modules: Vec<ResolvedModule>,
+    by_name: HashMap<String, ModuleId>,
+    /// Package in internal form -> module that contains it.
+    package_to_module: HashMap<String, ModuleId>,
+    main_module: Option<ModuleId>,
+}
+
+impl ModuleGraph {
+    /// Resolves the boot layer like `jdk.internal.module.ModuleBootstrap`: the roots are the
+    /// `--module` module, or every system module that exports an API when the main class is on
+    /// the classpath. Their `requires` are resolved transitively and then service providers of
+    /// every `uses` are bound, until nothing is added.
+    pub fn resolve(config: &VmConfig, jimage: &JImage) -> Result<Self, JvmError> {
+        let system = finder::system_modules(jimage)?;
+        let system_count = system.len();
+        let app = finder::module_path(&config.module_path)?;
+
+        // system modules shadow module path modules with the same name
+        let mut observable: Vec<ModuleReference> = Vec::with_capacity(system.len() + app.len());
+        let mut observable_index: HashMap<String, usize> = HashMap::new();
+        for module in system.into_iter().chain(app) {
+            if !observable_index.contains_key(&module.descriptor.name) {
+                observable_index.insert(module.descriptor.name.clone(), observable.len());
+                observable.push(module);
+            } else {
+                debug_log!(
+                    "Module {} of the module path is shadowed by a system module",
+                    module.descriptor.name
+                );
+            }
+        }
+
+        let roots: Vec<usize> = match &config.main_module {
+            Some(main) => vec![
+                *observable_index
+                    .get(main)
+                    .ok_or_else(|| build_exception!(FindException, "Module {main} not found"))?,
+            ],
+            None => (0..system_count)
+                .filter(|&i| {
+                    let descriptor = &observable[i].descriptor;
+                    descriptor.exports_api() && !descriptor.do_not_resolve_by_default
+                })
+                .collect(),
+        };
+
+        let selected = Self::select(&observable, &observable_index, roots)?;
+
+        let mut graph = Self {
+            modules: Vec::with_capacity(selected.len()),
+            by_name: HashMap::with_capacity(selected.len()),
+            package_to_module: HashMap::new(),
+            main_module: None,
+        };
+        let mut observable: Vec<Option<ModuleReference>> =
+            observable.into_iter().map(Some).collect();
+        for i in selected {
+            let module = observable[i]
+                .take()
+                .expect("a module is selected only once");
+            let id = ModuleId(graph.modules.len() as u32);
+            graph.by_name.insert(module.descriptor.name.clone(), id);
+            graph.modules.push(ResolvedModule {
+                id,
+                descriptor: module.descriptor,
+                source: module.source,
+                reads: HashSet::new(),
+                exports: HashMap::new(),
+                opens: HashMap::new(),
+            });
+        }
+        graph.main_module = config
+            .main_module
+            .as_ref()
+            .and_then(|name| graph.by_name.get(name).copied());
+
+        graph.map_packages()?;
+        graph.compute_readability();
+        graph.compute_exports_and_opens();
+        for value in &config.add_exports {
+            graph.add_qualified(value, "--add-exports", false)?;
+        }
+        for value in &config.add_opens {
+            graph.add_qualified(value, "--add-opens", true)?;
+        }
+        debug_log!("Boot layer resolved with {} modules", graph.modules.len());
+        Ok(graph)
+    }
+
+    /// Indexes of the modules in the boot layer, in resolution order.
+    fn select(
+        observable: &[ModuleReference],
+        index: &HashMap<String, usize>,
+        roots: Vec<usize>,
+    ) -> Result<Vec<usize>, JvmError> {
+        let mut selected: Vec<usize> = Vec::new();
+        let mut seen: HashSet<usize> = HashSet::new();
+        let mut queue: VecDeque<usize> = VecDeque::new();
+        let mut enqueue = |i: usize, queue: &mut VecDeque<usize>, selected: &mut Vec<usize>| {
+            if seen.insert(i) {
+                selected.push(i);
+                queue.push_back(i);
+            }
+        };
+        for root in roots {
+            enqueue(root, &mut queue, &mut selected);
+        }
+
+        loop {
+            while let Some(i) = queue.pop_front() {
+                let descriptor = &observable[i].descriptor;
+                for requires in &descriptor.requires {
+                    match index.get(&requires.name) {
+                        Some(&dep) if !requires.static_phase => {
+                            enqueue(dep, &mut queue, &mut selected)
+                        }
+                        // `requires static` is only resolved if something else needs it
+                        _ if requires.static_phase => {}
+                        _ => {
+                            return Err(build_exception!(
+                                FindException,
+                                "Module {} not found, required by {}",
+                                requires.name,
+                                descriptor.name
+                            ));
+                        }
+                    }
+                }
+                // resolving one automatic module resolves all of them, they read each other
+                if descriptor.automatic {
+                    for (j, other) in observable.iter().enumerate() {
+                        if other.descriptor.automatic {
+                            enqueue(j, &mut queue, &mut selected);
+                        }
+                    }
+                }
+            }
+
+            // service binding: providers of a used service join the graph with their requires
+            let used: HashSet<&str> = selected
+                .iter()
+                .flat_map(|&i| observable[i].descriptor.uses.iter().map(String::as_str))
+                .collect();
+            for (j, module) in observable.iter().enumerate() {
+                if module
+                    .descriptor
+                    .provides
+                    .iter()
+                    .any(|p| used.contains(p.service.as_str()))
+                {
+                    enqueue(j, &mut queue, &mut selected);
+                }
+            }
+            if queue.is_empty() {
+                return Ok(selected);
+            }
+        }
+    }
+
+    fn map_packages(&mut self) -> Result<(), JvmError> {
+        for (i, module) in self.modules.iter().enumerate() {
+            for package in &module.descriptor.packages {
+                if let Some(other) = self
+                    .package_to_module
+                    .insert(package.clone(), ModuleId(i as u32))
+                {
+                    return Err(build_exception!(
+                        ResolutionException,
+                        "Package {} in both module {} and module {}",
+                        package.replace('/', "."),
+                        self.modules[other.0 as usize].name(),
+                        module.name()
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// A module reads what it requires, and, through `requires transitive`, what those modules
+    /// pass on. Automatic modules read everything, which [`ModuleGraph::can_read`] handles.
+    fn compute_readability(&mut self) {
+        let transitive: Vec<Vec<ModuleId>> = self
+            .modules
+            .iter()
+            .map(|module| {
+                module
+                    .descriptor
+                    .requires
+                    .iter()
+                    .filter(|r| r.transitive)
+                    .filter_map(|r| self.by_name.get(&r.name).copied())
+                    .collect()
+            })
+            .collect();
+        let java_base = self.by_name.get(JAVA_BASE).copied();
+
+        for module in &mut self.modules {
+            let mut reads: HashSet<ModuleId> = HashSet::new();
+            let mut pending: Vec<ModuleId> = module
+                .descriptor
+                .requires
+                .iter()
+                .filter_map(|r| self.by_name.get(&r.name).copied())
+                .chain(java_base)
+                .collect();
+            while let Some(id) = pending.pop() {
+                if reads.insert(id) {
+                    pending.extend(&transitive[id.0 as usize]);
+                }
+            }
+            module.reads = reads;
+        }
+    }
+
+    fn compute_exports_and_opens(&mut self) {
+        for i in 0..self.modules.len() {
+            let descriptor = &self.modules[i].descriptor;
+            let exports = self.targets_of(&descriptor.exports);
+            let opens = self.targets_of(&descriptor.opens);
+            let module = &mut self.modules[i];
+            module.exports = exports;
+            module.opens = opens;
+        }
+    }
+
+    /// Targets that are not in the boot layer are dropped, as by the reference implementation.
+    fn targets_of(&self, directives: &[PackageDirective]) -> HashMap<String, Targets> {
+        let mut map: HashMap<String, Targets> = HashMap::new();
+        for directive in directives {
+            let targets = map.entry(directive.package.clone()).or_default();
+            if directive.targets.is_empty() {
+                targets.all = true;
+            }
+            targets.modules.extend(
+                directive
+                    .targets
+                    .iter()
+                    .filter_map(|t| self.by_name.get(t).copied()),
+            );
+        }
+        map
+    }
+
+    /// `--add-exports` and `--add-opens` take `<module>/<package>=<target>(,<target>)*`, where a
+    /// target is a module name or `ALL-UNNAMED`. Unknown modules and packages are reported and
+    /// ignored, like the `java` launcher does.
+    fn add_qualified(&mut self, value: &str, option: &str, open: bool) -> Result<(), JvmError> {
+        let malformed = || {
+            build_exception!(
+                ResolutionException,
+                "Unable to parse {option} <module>=<value>: {value}"
+            )
+        };
+        let (source, targets) = value.split_once('=').ok_or_else(malformed)?;
+        let (module, package) = source.split_once('/').ok_or_else(malformed)?;
+        if module.is_empty() || package.is_empty() || targets.is_empty() {
+            return Err(malformed());
+        }
+
+        let Some(&module_id) = self.by_name.get(module) else {
+            eprintln!("WARNING: Unknown module: {module} specified to {option}");
+            return Ok(());
+        };
+        let package = package.replace('.', "/");
+        if self.package_to_module.get(&package) != Some(&module_id) {
+            eprintln!(
+                "WARNING: package {} not in {module}",
+                package.replace('/', ".")
+            );
+            return Ok(());
+        }
+
+        let mut resolved = Targets::default();
+        for target in targets.split(',').filter(|t| !t.is_empty()) {
+            if target == "ALL-UNNAMED" {
+                resolved.all_unnamed = true;
+            } else if let Some(&id) = self.by_name.get(target) {
+                resolved.modules.insert(id);
+            } else {
+                eprintln!("WARNING: Unknown module: {target} specified to {option}");
+            }
+        }
+
+        let module = &mut self.modules[module_id.0 as usize];
+        let map = if open {
+            &mut module.opens
+        } else {
+            &mut module.exports
+        };
+        let entry = map.entry(package).or_default();
+        entry.all_unnamed |= resolved.all_unnamed;
+        entry.modules.extend(resolved.modules);
+        Ok(())
+    }
+
+    pub fn get(&self, id: ModuleId) -> Option<&ResolvedModule> {
+        self.modules.get(id.0 as usize)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ResolvedModule> {
+        self.modules.iter()
+    }
+
+    pub fn by_name(&self, name: &str) -> Option<ModuleId> {
+        self.by_name.get(name).copied()
+    }
+
+    pub fn main_module(&self) -> Option<&ResolvedModule> {
+        self.main_module.and_then(|id| self.get(id))
+    }
+
+    /// Package in internal form, e.g. `java/lang`.
+    pub fn module_of_package(&self, package: &str) -> ModuleId {
+        self.package_to_module
+            .get(package)
+            .copied()
+            .unwrap_or(ModuleId::UNNAMED)
+    }
+
+    /// Binary name in internal form, e.g. `java/lang/String`.
+    pub fn module_of_class(&self, binary_name: &str) -> ModuleId {
+        match binary_name.rsplit_once('/') {
+            Some((package, _)) => self.module_of_package(package),
+            None => ModuleId::UNNAMED,
+        }
+    }
+
+    /// The unnamed module and automatic modules read every module, named modules never read
+    /// the unnamed module.
+    pub fn can_read(&self, from: ModuleId, to: ModuleId) -> bool {
+        if from == to {
+            return true;
+        }
+        match self.get(from) {
+            None => true,
+            Some(module) if module.descriptor.automatic => true,
+            Some(module) => module.reads.contains(&to),
+        }
+    }
+
+    /// Whether `package` of `module` is exported to `to`. The unnamed module and automatic
+    /// modules export all their packages.
+    pub fn is_exported(&self, module: ModuleId, package: &str, to: ModuleId) -> bool {
+        if module == to {
+            return true;
+        }
+        match self.get(module) {
+            None => true,
+            Some(m) if m.descriptor.automatic => true,
+            Some(m) => m.exports.get(package).is_some_and(|t| t.contains(to)),
+        }
+    }
+
+    /// Whether `package` of `module` is opened to every module, which is what makes its
+    /// resources other than classes visible through class loaders. Open modules, automatic
+    /// modules and the unnamed module open all their packages.
+    pub fn is_open_to_all(&self, module: ModuleId, package: &str) -> bool {
+        match self.get(module) {
+            None => true,
+            Some(m) if m.descriptor.automatic || m.descriptor.open => true,
+            Some(m) => m.opens.get(package).is_some_and(|t| t.all),
+        }
+    }
+
+    /// `module java.base` or `unnamed module`, as in access error messages.
+    pub fn describe(&self, id: ModuleId) -> String {
+        match self.get(id) {
+            Some(module) => format!("module {}", module.name()),
+            None => "unnamed module".to_string(),
+        }
+    }
+
+    /// `in module java.base` or `in unnamed module`, as HotSpot puts it after a class name.
+    pub fn describe_location(&self, id: ModuleId) -> String {
+        format!("in {}", self.describe(id))
+    }
+}
@@ -1,16 +1,22 @@
 mod preregistered;
+pub(crate) mod reflection;
 mod registrable;
+mod substituted;
 
 use crate::VirtualMachine;
 use crate::error::JvmError;
-use crate::keys::FullyQualifiedMethodKey;
+use crate::heap::HeapRef;
+use crate::interpreter::Interpreter;
+use crate::keys::{ClassId, FullyQualifiedMethodKey, MethodKey};
 use crate::native::preregistered::preregister_natives;
 use crate::native::registrable::add_registrable_natives;
+use crate::native::substituted::substitute_natives;
 use crate::thread::JavaThreadState;
 use crate::vm::Value;
+use common::instruction::ArrayType;
 use dashmap::DashMap;
 use lasso::ThreadedRodeo;
-use std::collections::HashMap;
+use std::collections::HashSet;
 use std::sync::Arc;
 use tracing_log::log::debug;
 
@@ -19,6 +25,8 @@ pub type NativeFn = fn(&VirtualMachine, thread: &mut JavaThreadState, &[Value])
 
 pub struct NativeRegistry {
     map: DashMap<FullyQualifiedMethodKey, NativeFn>,
+    /// Java methods whose bytecode is never run, the VM links them to a native instead.
+    substituted: HashSet<FullyQualifiedMethodKey>,
     string_interner: Arc<ThreadedRodeo>,
 }
 
@@ -27,11 +35,13 @@ impl NativeRegistry {
         debug!("Initializing NativeRegistry...");
         let mut instance = Self {
             map: DashMap::new(),
+            substituted: HashSet::new(),
             string_interner,
         };
 
         preregister_natives(&mut instance);
         add_registrable_natives(&mut instance);
+        substitute_natives(&mut instance);
 
         instance
     }
@@ -40,7 +50,75 @@ impl NativeRegistry {
         self.map.insert(key, f);
     }
 
+    /// Registers `f` for a method that isn't `native` in Java, for JDK code built on
+    /// machinery the VM doesn't have. Must happen before the declaring class is loaded.
+    fn substitute(&mut self, key: FullyQualifiedMethodKey, f: NativeFn) {
+        self.substituted.insert(key.clone());
+        self.map.insert(key, f);
+    }
+
+    pub fn substituted_methods(&self) -> &HashSet<FullyQualifiedMethodKey> {
+        &self.substituted
+    }
+
     pub fn get(&self, key: &FullyQualifiedMethodKey) -> Option<NativeFn> {
         self.map.get(key).map(|entry| *entry.value())
     }
 }
+
+fn load_and_initialize(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    class_name: &str,
+) -> Result<ClassId, JvmError> {
+    let class_id = vm
+        .method_area()
+        .get_class_id_or_load(vm.interner().get_or_intern(class_name), thread.id)?;
+    Interpreter::ensure_initialized(thread, Some(class_id), vm)?;
+    Ok(class_id)
+}
+
+/// Allocates a `class_name` and runs its constructor with descriptor `desc`.
+fn new_object(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    class_name: &str,
+    desc: &str,
+    args: Vec<Value>,
+) -> Result<HeapRef, JvmError> {
+    let class_id = load_and_initialize(vm, thread, class_name)?;
+    let (constructor_id, instance_size) = {
+        let ma = vm.method_area();
+        let class = ma.get_instance_class(&class_id)?;
+        (
+            class.get_special_method_id(&MethodKey {
+                name: vm.br().init_sym,
+                desc: vm.interner().get_or_intern(desc),
+            })?,
+            class.get_instance_size()?,
+        )
+    };
+    let instance = vm.heap_write().alloc_instance(instance_size, class_id)?;
+    let mut constructor_args = vec![Value::Ref(instance)];
+    constructor_args.extend(args);
+    Interpreter::invoke_instance_method(thread, constructor_id, vm, constructor_args)?;
+    Ok(instance)
+}
+
+/// A `byte[]` holding `bytes`.
+fn byte_array(vm: &VirtualMachine, bytes: &[u8]) -> Result<HeapRef, JvmError> {
+    let mut heap = vm.heap_write();
+    let array_ref = heap.alloc_primitive_array(
+        vm.br().get_byte_array_class_id()?,
+        ArrayType::Byte,
+        bytes.len() as i32,
+    )?;
+    for (dst, src) in heap
+        .get_byte_array_slice_mut(array_ref)?
+        .iter_mut()
+        .zip(bytes)
+    {
+        *dst = *src as i8;
+    }
+    Ok(array_ref)
+}
@@ -115,7 +115,7 @@ fn java_io_file_output_stream_write_bytes(
 
     let output_stream_class_id = vm.heap_read().get_class_id(output_stream_ref)?;
     let output_stream_fd_field_offset = vm
-        .method_area_read()
+        .method_area()
         .get_instance_class(&output_stream_class_id)?
         .get_instance_field(&vm.br().file_output_stream_fd_fk)?
         .offset;
@@ -129,7 +129,7 @@ fn java_io_file_output_stream_write_bytes(
         .as_obj_ref()?;
     let fd_class_id = vm.heap_read().get_class_id(fd_obj)?;
     let fd_fd_field_offset = vm
-        .method_area_read()
+        .method_area()
         .get_instance_class(&fd_class_id)?
         .get_instance_field(&vm.br().fd_fd_fk)?
         .offset;
@@ -250,10 +250,10 @@ fn java_io_unix_file_system_get_boolean_attributes_0(
     const BA_HIDDEN: i32 = 0x08;
 
     let file_class_id = vm
-        .method_area_write()
+        .method_area()
         .get_class_id_or_load(vm.br.java_io_file_sym, thread.id)?;
     let path_field_offset = vm
-        .method_area_read()
+        .method_area()
         .get_instance_class(&file_class_id)?
         .get_instance_field(&vm.br.file_path_fk)?
         .offset;
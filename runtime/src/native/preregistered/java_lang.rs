@@ -1,11 +1,14 @@
+use crate::heap::gc;
 use crate::keys::{ClassId, FullyQualifiedMethodKey};
 use crate::native::{NativeRegistry, NativeRet};
 use crate::thread::JavaThreadState;
 use crate::vm::Value;
+use crate::vm::signal;
 use crate::vm::stack::FrameType;
 use crate::{MethodId, VirtualMachine, throw_exception};
 use common::instruction::ArrayType;
 use common::jtype::AllocationType;
+use std::time::{Duration, Instant};
 use tracing_log::log::debug;
 
 pub(super) fn do_register_java_lang_preregistered_natives(native_registry: &mut NativeRegistry) {
@@ -75,6 +78,15 @@ pub(super) fn do_register_java_lang_preregistered_natives(native_registry: &mut
         ),
         java_lang_runtime_available_processors,
     );
+    native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "java/lang/Runtime",
+            "gc",
+            "()V",
+            &native_registry.string_interner,
+        ),
+        java_lang_runtime_gc,
+    );
     native_registry.register(
         FullyQualifiedMethodKey::new_with_str(
             "java/lang/Object",
@@ -84,6 +96,42 @@ pub(super) fn do_register_java_lang_preregistered_natives(native_registry: &mut
         ),
         java_lang_object_notify_all,
     );
+    native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "java/lang/Object",
+            "notify",
+            "()V",
+            &native_registry.string_interner,
+        ),
+        java_lang_object_notify,
+    );
+    native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "java/lang/Object",
+            "wait0",
+            "(J)V",
+            &native_registry.string_interner,
+        ),
+        java_lang_object_wait_0,
+    );
+    native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "java/lang/Shutdown",
+            "beforeHalt",
+            "()V",
+            &native_registry.string_interner,
+        ),
+        java_lang_shutdown_before_halt,
+    );
+    native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "java/lang/Shutdown",
+            "halt0",
+            "(I)V",
+            &native_registry.string_interner,
+        ),
+        java_lang_shutdown_halt_0,
+    );
     native_registry.register(
         FullyQualifiedMethodKey::new_with_str(
             "java/lang/StackTraceElement",
@@ -156,7 +204,7 @@ fn java_lang_system_arraycopy(
         throw_exception!(
             ArrayStoreException,
             "arraycopy: source type {} is not an array",
-            vm.symbol_to_pretty_string(vm.method_area_read().get_class(&src_class_id).get_name())
+            vm.symbol_to_pretty_string(vm.method_area().get_class(&src_class_id).get_name())
         )?;
     }
 
@@ -165,7 +213,7 @@ fn java_lang_system_arraycopy(
         throw_exception!(
             ArrayStoreException,
             "arraycopy: destination type {} is not an array",
-            vm.symbol_to_pretty_string(vm.method_area_read().get_class(&dest_class_id).get_name())
+            vm.symbol_to_pretty_string(vm.method_area().get_class(&dest_class_id).get_name())
         )?;
     }
 
@@ -190,18 +238,18 @@ fn java_lang_object_get_class(
         //TODO: refactor and rethink how I handle array classes and their mirrors
         //right now I put on heap for arrays the class id of the element type, but the mirror has to be of the array type
         if vm.heap_read().is_array(object_ref)? {
-            let class_name_sym = vm.method_area_read().get_class(&class_id).get_name();
+            let class_name_sym = vm.method_area().get_class(&class_id).get_name();
             let raw_name = vm.interner().resolve(&class_name_sym);
             let array_name = format!("[L{};", raw_name);
             let array_class_name_sym = vm.interner().get_or_intern(&array_name);
-            vm.method_area_write()
+            vm.method_area()
                 .load_array_class(array_class_name_sym, thread.id)?
         } else {
             class_id
         }
     };
     let res = vm
-        .method_area_write()
+        .method_area()
         .get_mirror_ref_or_create(target_class_id, &vm.heap)?;
     Ok(Some(Value::Ref(res)))
 }
@@ -237,18 +285,15 @@ fn java_lang_throwable_fill_in_stack_trace(
         .iter()
         .filter(|frame| {
             //TODO: very hacky way to skip internal frames, should be improved and very probably doesn't show real throwable constructors
-            let class_id = vm
-                .method_area_read()
-                .get_method(&frame.method_id())
-                .class_id();
-            !vm.method_area_read()
-                .instance_of(class_id, vm.br().java_lang_throwable_sym)
+            let class_id = vm.method_area().get_method(&frame.method_id()).class_id();
+            !vm.method_area()
+                .instance_of(class_id, None, vm.br().java_lang_throwable_sym)
         })
         .cloned() // TODO: very bad clone
         .collect();
     frames.reverse();
     let int_arr_class = vm
-        .method_area_write()
+        .method_area()
         .load_array_class(vm.br().int_array_desc, thread.id)?;
     let class_id_array = vm.heap_write().alloc_primitive_array(
         int_arr_class,
@@ -267,7 +312,7 @@ fn java_lang_throwable_fill_in_stack_trace(
     )?;
     for (pos, frame) in frames.iter().enumerate() {
         let class_id = vm
-            .method_area_read()
+            .method_area()
             .get_method(&frame.method_id())
             .class_id()
             .to_i32();
@@ -305,12 +350,12 @@ fn java_lang_throwable_fill_in_stack_trace(
     };
     let throwable_class_id = vm.heap_read().get_class_id(throwable_addr)?;
     let backtrace_field_offset = vm
-        .method_area_read()
+        .method_area()
         .get_instance_class(&throwable_class_id)?
         .get_instance_field(&vm.br().throwable_backtrace_fk)?
         .offset;
     let depth_field_offset = vm
-        .method_area_read()
+        .method_area()
         .get_instance_class(&throwable_class_id)?
         .get_instance_field(&vm.br().throwable_depth_fk)?
         .offset;
@@ -374,6 +419,15 @@ fn java_lang_runtime_available_processors(
     Ok(Some(Value::Integer(1)))
 }
 
+fn java_lang_runtime_gc(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    _args: &[Value],
+) -> NativeRet {
+    gc::collect(vm, thread)?;
+    Ok(None)
+}
+
 fn java_lang_stack_trace_element_init_stack_trace_elements(
     vm: &VirtualMachine,
     _thread: &mut JavaThreadState,
@@ -413,12 +467,12 @@ fn java_lang_stack_trace_element_init_stack_trace_elements(
         let cp_array = vm.heap_read().read_array_element(object, 2)?.as_obj_ref()?;
         let cp = vm.heap_read().read_array_element(cp_array, i)?.as_int()?;
         let declaring_class_object = vm
-            .method_area_write()
+            .method_area()
             .get_mirror_ref_or_create(class_id, &vm.heap)?;
-        let method_sym = vm.method_area_read().get_method(&method_id).name;
-        let class_sym = vm.method_area_read().get_class(&class_id).get_name();
+        let method_sym = vm.method_area().get_method(&method_id).name;
+        let class_sym = vm.method_area().get_class(&class_id).get_name();
         let class_source_sym = vm
-            .method_area_read()
+            .method_area()
             .get_class(&class_id)
             .get_source_file()
             .unwrap_or(vm.interner().get_or_intern("TODO: Unknown Source"));
@@ -435,7 +489,7 @@ fn java_lang_stack_trace_element_init_stack_trace_elements(
             .heap_write()
             .alloc_string_from_interned(class_source_sym)?;
         let line_nbr = vm
-            .method_area_read()
+            .method_area()
             .get_method(&method_id)
             .get_line_number_by_cp(cp)
             .unwrap_or(-1);
@@ -446,7 +500,7 @@ fn java_lang_stack_trace_element_init_stack_trace_elements(
 
         let stack_trace_class_id = vm.heap_read().get_class_id(cur_stack_trace_entry)?;
         let (a, b, c, d, e) = {
-            let ma = vm.method_area_read();
+            let ma = vm.method_area();
             let stack_trace_class = ma.get_instance_class(&stack_trace_class_id)?;
             (
                 stack_trace_class
@@ -502,14 +556,65 @@ fn java_lang_stack_trace_element_init_stack_trace_elements(
 }
 
 fn java_lang_object_notify_all(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let obj = args[0].as_obj_ref()?;
+    vm.monitors.notify(thread.id, obj, true)?;
+    Ok(None)
+}
+
+fn java_lang_object_notify(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let obj = args[0].as_obj_ref()?;
+    vm.monitors.notify(thread.id, obj, false)?;
+    Ok(None)
+}
+
+// TODO: Thread.interrupt is not supported yet, so waiting is never interrupted
+fn java_lang_object_wait_0(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let obj = args[0].as_obj_ref()?;
+    let timeout_millis = args[1].as_long()?;
+    if timeout_millis < 0 {
+        throw_exception!(IllegalArgumentException, "timeout value is negative")?
+    }
+    let deadline =
+        (timeout_millis > 0).then(|| Instant::now() + Duration::from_millis(timeout_millis as u64));
+    let thread_id = thread.id;
+    vm.monitors.wait(thread_id, obj, deadline, || {
+        if signal::poll_requested() {
+            vm.safepoint_poll(thread)?;
+        }
+        Ok(())
+    })?;
+    Ok(None)
+}
+
+fn java_lang_shutdown_before_halt(
     _vm: &VirtualMachine,
     _thread: &mut JavaThreadState,
     _args: &[Value],
 ) -> NativeRet {
-    debug!("TODO: Stub: java.lang.Object.notifyAll");
+    // hotspot only emits a JFR event here
     Ok(None)
 }
 
+fn java_lang_shutdown_halt_0(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    vm.halt(args[0].as_int()?)
+}
+
 fn java_lang_float_int_bits_to_float(
     _vm: &VirtualMachine,
     _thread: &mut JavaThreadState,
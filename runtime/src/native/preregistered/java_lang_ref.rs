@@ -27,10 +27,10 @@ fn java_lang_ref_reference_refers_to_0(
     let referent_ref = args[0].as_obj_ref()?;
     let referent_fk = vm.br.reference_referent_fk;
     let reference_class_id = vm
-        .method_area_write()
+        .method_area()
         .get_class_id_or_load(vm.br.java_lang_ref_reference_sym, thread.id)?;
     let referent_field_offset = vm
-        .method_area_read()
+        .method_area()
         .get_instance_class(&reference_class_id)?
         .get_instance_field(&referent_fk)?
         .offset;
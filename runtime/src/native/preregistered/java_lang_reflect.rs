@@ -0,0 +1,68 @@
+use crate::error::JvmError;
+use crate::keys::FullyQualifiedMethodKey;
+use crate::native::reflection::{annotation_bytes, read_named_field};
+use crate::native::{NativeRegistry, NativeRet};
+use crate::thread::JavaThreadState;
+use crate::vm::Value;
+use crate::{MethodId, VirtualMachine};
+
+pub(super) fn do_register_java_lang_reflect_preregistered_natives(
+    native_registry: &mut NativeRegistry,
+) {
+    native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "java/lang/reflect/Field",
+            "getTypeAnnotationBytes0",
+            "()[B",
+            &native_registry.string_interner,
+        ),
+        java_lang_reflect_field_get_type_annotation_bytes_0,
+    );
+    native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "java/lang/reflect/Executable",
+            "getTypeAnnotationBytes0",
+            "()[B",
+            &native_registry.string_interner,
+        ),
+        java_lang_reflect_executable_get_type_annotation_bytes_0,
+    );
+}
+
+/// The slot of a `Field` is its index in the declared fields of its class.
+fn java_lang_reflect_field_get_type_annotation_bytes_0(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let field_ref = args[0].as_obj_ref()?;
+    let mirror_ref = read_named_field(vm, field_ref, "clazz")?.as_obj_ref()?;
+    let slot = read_named_field(vm, field_ref, "slot")?.as_int()?;
+    let ma = vm.method_area();
+    let class_id = ma.get_class_id_by_mirror(&mirror_ref)?;
+    let class = ma.get_class_like(&class_id)?;
+    let field = class
+        .declared_fields()?
+        .get(slot as usize)
+        .ok_or_else(|| JvmError::Todo(format!("No declared field at slot {slot}")))?;
+    Ok(Some(annotation_bytes(
+        vm,
+        field.annotations.type_annotations.as_deref(),
+    )?))
+}
+
+/// The slot of a `Method` or `Constructor` is its method id.
+fn java_lang_reflect_executable_get_type_annotation_bytes_0(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let executable_ref = args[0].as_obj_ref()?;
+    let method_id = MethodId::from_i32(read_named_field(vm, executable_ref, "slot")?.as_int()?);
+    let ma = vm.method_area();
+    let method = ma.get_method(&method_id);
+    Ok(Some(annotation_bytes(
+        vm,
+        method.annotations().type_annotations.as_deref(),
+    )?))
+}
@@ -2,6 +2,7 @@ use crate::keys::FullyQualifiedMethodKey;
 use crate::native::{NativeRegistry, NativeRet};
 use crate::thread::JavaThreadState;
 use crate::vm::Value;
+use crate::vm::signal;
 use crate::{ThreadId, VirtualMachine};
 use tracing_log::log::debug;
 
@@ -99,7 +100,7 @@ fn jdk_internal_util_system_props_raw_platform_properties(
     // TODO: create a registry for interned common strings
     let empty_string_sym = vm.interner().get_or_intern("");
     let string_class_id = vm
-        .method_area_write()
+        .method_area()
         .get_class_id_or_load(string_class_sym, thread.id)?;
     let empty_string_stub = vm.heap_write().get_str_from_pool_or_new(empty_string_sym)?;
     let h = vm.heap_write().alloc_object_array(string_class_id, 40)?;
@@ -140,10 +141,10 @@ fn jdk_internal_util_system_props_raw_vm_properties(
     debug!("TODO: Stub: jdk.internal.util.SystemProps$Raw.vmProperties");
     let string_class_sym = vm.br().java_lang_string_sym;
     let string_class = vm
-        .method_area_write()
+        .method_area()
         .get_class_id_or_load(string_class_sym, thread.id)?;
     //TODO: same here, it needs a registry for common interned strings
-    let h = vm.heap_write().alloc_object_array(string_class, 4)?;
+    let h = vm.heap_write().alloc_object_array(string_class, 6)?;
     let java_home_key = vm
         .heap_write()
         .get_str_from_pool_or_new(vm.interner().get_or_intern("java.home"))?;
@@ -165,6 +166,20 @@ fn jdk_internal_util_system_props_raw_vm_properties(
         .write_array_element(h, 2, Value::Ref(sun_page_align_stub))?;
     vm.heap_write()
         .write_array_element(h, 3, Value::Ref(false_str))?;
+    // `Method.invoke` and `Constructor.newInstance` stay on the native accessors of
+    // `jdk.internal.reflect`, the method handle ones would spin lambda form classes for
+    // every reflected member
+    let native_accessor_only_key = vm.heap_write().get_str_from_pool_or_new(
+        vm.interner()
+            .get_or_intern("jdk.reflect.useNativeAccessorOnly"),
+    )?;
+    let true_str = vm
+        .heap_write()
+        .get_str_from_pool_or_new(vm.interner().get_or_intern("true"))?;
+    vm.heap_write()
+        .write_array_element(h, 4, Value::Ref(native_accessor_only_key))?;
+    vm.heap_write()
+        .write_array_element(h, 5, Value::Ref(true_str))?;
     Ok(Some(Value::Ref(h)))
 }
 
@@ -182,31 +197,33 @@ fn jdk_internal_misc_signal_find_signal_0(
     _thread: &mut JavaThreadState,
     args: &[Value],
 ) -> NativeRet {
-    debug!("TODO: Stub: jdk.internal.misc.Signal.findSignal0");
     let signal_name = match args[0] {
         Value::Ref(h) => vm.heap_read().get_rust_string_from_java_string(h)?,
         _ => panic!("jdk.internal.misc.Signal.findSignal0: expected signal name string"),
     };
     let signal_number = match signal_name.as_str() {
-        "HUP" => 1,
-        "INT" => 2,
-        "QUIT" => 3,
-        "ILL" => 4,
-        "ABRT" => 6,
-        "FPE" => 8,
-        "KILL" => 9,
-        "SEGV" => 11,
-        "PIPE" => 13,
-        "ALRM" => 14,
-        "TERM" => 15,
-        "USR1" => 10,
-        "USR2" => 12,
-        "CHLD" => 17,
-        "CONT" => 18,
-        "STOP" => 19,
-        "TSTP" => 20,
-        "TTIN" => 21,
-        "TTOU" => 22,
+        "HUP" => libc::SIGHUP,
+        "INT" => libc::SIGINT,
+        "QUIT" => libc::SIGQUIT,
+        "ILL" => libc::SIGILL,
+        "TRAP" => libc::SIGTRAP,
+        "ABRT" => libc::SIGABRT,
+        "BUS" => libc::SIGBUS,
+        "FPE" => libc::SIGFPE,
+        "KILL" => libc::SIGKILL,
+        "USR1" => libc::SIGUSR1,
+        "SEGV" => libc::SIGSEGV,
+        "USR2" => libc::SIGUSR2,
+        "PIPE" => libc::SIGPIPE,
+        "ALRM" => libc::SIGALRM,
+        "TERM" => libc::SIGTERM,
+        "CHLD" => libc::SIGCHLD,
+        "CONT" => libc::SIGCONT,
+        "STOP" => libc::SIGSTOP,
+        "TSTP" => libc::SIGTSTP,
+        "TTIN" => libc::SIGTTIN,
+        "TTOU" => libc::SIGTTOU,
+        "WINCH" => libc::SIGWINCH,
         _ => -1,
     };
     Ok(Some(Value::Integer(signal_number)))
@@ -215,10 +232,11 @@ fn jdk_internal_misc_signal_find_signal_0(
 fn jdk_internal_misc_signal_handle_0(
     _vm: &VirtualMachine,
     _thread: &mut JavaThreadState,
-    _args: &[Value],
+    args: &[Value],
 ) -> NativeRet {
-    debug!("TODO: Stub: jdk.internal.misc.Signal.handle0");
-    Ok(Some(Value::Long(1)))
+    let sig = args[0].as_int()?;
+    let handler = args[1].as_long()?;
+    Ok(Some(Value::Long(signal::set_java_handler(sig, handler))))
 }
 
 fn jdk_internal_misc_cds_get_cds_config_status(
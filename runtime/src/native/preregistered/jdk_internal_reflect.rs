@@ -1,9 +1,17 @@
-use crate::VirtualMachine;
-use crate::keys::{FullyQualifiedMethodKey, ThreadId};
-use crate::native::{NativeRegistry, NativeRet};
+use crate::error::JvmError;
+use crate::heap::HeapRef;
+use crate::interpreter::Interpreter;
+use crate::keys::{FullyQualifiedMethodKey, MethodKey, ThreadId};
+use crate::native::reflection::{
+    box_value, caller_class_id, read_named_field, unbox_and_widen, wrap_invocation_target,
+};
+use crate::native::{NativeFn, NativeRegistry, NativeRet};
+use crate::rt::JvmClass;
+use crate::rt::constant_pool::{RuntimeConstant, RuntimeConstantPool, RuntimeConstantType};
 use crate::thread::JavaThreadState;
 use crate::vm::Value;
-use tracing_log::log::debug;
+use crate::{MethodId, VirtualMachine, throw_exception};
+use common::jtype::{JavaType, ReturnType};
 
 pub(super) fn do_register_jdk_internal_reflect_preregistered_natives(
     native_registry: &mut NativeRegistry,
@@ -26,22 +34,108 @@ pub(super) fn do_register_jdk_internal_reflect_preregistered_natives(
         ),
         jdk_internal_reflect_reflection_get_class_access_flags,
     );
+    native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "jdk/internal/reflect/DirectMethodHandleAccessor$NativeAccessor",
+            "invoke0",
+            "(Ljava/lang/reflect/Method;Ljava/lang/Object;[Ljava/lang/Object;)Ljava/lang/Object;",
+            &native_registry.string_interner,
+        ),
+        jdk_internal_reflect_direct_method_handle_accessor_native_accessor_invoke_0,
+    );
+    native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "jdk/internal/reflect/DirectConstructorHandleAccessor$NativeAccessor",
+            "newInstance0",
+            "(Ljava/lang/reflect/Constructor;[Ljava/lang/Object;)Ljava/lang/Object;",
+            &native_registry.string_interner,
+        ),
+        jdk_internal_reflect_direct_constructor_handle_accessor_native_accessor_new_instance_0,
+    );
+    native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "jdk/internal/reflect/ConstantPool",
+            "getSize0",
+            "(Ljava/lang/Object;)I",
+            &native_registry.string_interner,
+        ),
+        jdk_internal_reflect_constant_pool_get_size_0,
+    );
+    let constant_getters: [(&str, &str, NativeFn); 8] = [
+        (
+            "getIntAt0",
+            "I",
+            jdk_internal_reflect_constant_pool_get_int_at_0,
+        ),
+        (
+            "getLongAt0",
+            "J",
+            jdk_internal_reflect_constant_pool_get_long_at_0,
+        ),
+        (
+            "getFloatAt0",
+            "F",
+            jdk_internal_reflect_constant_pool_get_float_at_0,
+        ),
+        (
+            "getDoubleAt0",
+            "D",
+            jdk_internal_reflect_constant_pool_get_double_at_0,
+        ),
+        (
+            "getUTF8At0",
+            "Ljava/lang/String;",
+            jdk_internal_reflect_constant_pool_get_utf8_at_0,
+        ),
+        (
+            "getStringAt0",
+            "Ljava/lang/String;",
+            jdk_internal_reflect_constant_pool_get_string_at_0,
+        ),
+        (
+            "getClassAt0",
+            "Ljava/lang/Class;",
+            jdk_internal_reflect_constant_pool_get_class_at_0,
+        ),
+        (
+            "getTagAt0",
+            "B",
+            jdk_internal_reflect_constant_pool_get_tag_at_0,
+        ),
+    ];
+    for (name, ret, f) in constant_getters {
+        native_registry.register(
+            FullyQualifiedMethodKey::new_with_str(
+                "jdk/internal/reflect/ConstantPool",
+                name,
+                &format!("(Ljava/lang/Object;I){ret}"),
+                &native_registry.string_interner,
+            ),
+            f,
+        );
+    }
 }
 
+/// Frame 0 is this native and frame 1 the `@CallerSensitive` method asking for its caller.
 fn jdk_internal_reflect_reflection_get_caller_class(
     vm: &VirtualMachine,
     thread: &mut JavaThreadState,
     _args: &[Value],
 ) -> NativeRet {
-    debug!("Stub: jdk/internal/reflect/Reflection.getCallerClass()");
-    // TODO: hardcoded. should use @CallerSensitive
-    let frame_minus_two = thread.stack.peek_frame_at(2)?;
-    let method_id = frame_minus_two.method_id();
-    let class_id = vm.method_area_read().get_method(&method_id).class_id();
-    let res = vm
-        .method_area_write()
-        .get_mirror_ref_or_create(class_id, &vm.heap)?;
-    Ok(Some(Value::Ref(res)))
+    let asking_method_id = thread.stack.peek_frame_at(1)?.method_id();
+    if !vm.method_area().is_caller_sensitive(&asking_method_id) {
+        throw_exception!(
+            InternalError,
+            "CallerSensitive annotation expected at frame 1"
+        )?
+    }
+    match caller_class_id(vm, thread, 2) {
+        Some(class_id) => Ok(Some(Value::Ref(
+            vm.method_area()
+                .get_mirror_ref_or_create(class_id, &vm.heap)?,
+        ))),
+        None => Ok(Some(Value::Null)),
+    }
 }
 
 fn jdk_internal_reflect_reflection_get_class_access_flags(
@@ -51,7 +145,326 @@ fn jdk_internal_reflect_reflection_get_class_access_flags(
 ) -> NativeRet {
     // TODO: implement properly (there are comments in source)
     let mirror_ref = args[0].as_obj_ref()?;
-    let class_id = vm.method_area_read().get_class_id_by_mirror(&mirror_ref)?;
-    let flags = vm.method_area_read().get_class(&class_id).get_raw_flags();
+    let class_id = vm.method_area().get_class_id_by_mirror(&mirror_ref)?;
+    let flags = vm.method_area().get_class(&class_id).get_raw_flags();
     Ok(Some(Value::Integer(flags)))
 }
+
+/// Arguments of a reflective call to `executable`, unboxed and widened to the parameter types.
+fn reflected_args(
+    vm: &VirtualMachine,
+    executable: HeapRef,
+    args: Value,
+) -> Result<Vec<Value>, JvmError> {
+    let param_types = read_named_field(vm, executable, "parameterTypes")?.as_obj_ref()?;
+    let given = match args.as_nullable_obj_ref()? {
+        Some(args) => {
+            let heap = vm.heap_read();
+            (0..heap.get_array_length(args)?)
+                .map(|i| heap.read_array_element(args, i))
+                .collect::<Result<Vec<_>, _>>()?
+        }
+        None => Vec::new(),
+    };
+    let expected = vm.heap_read().get_array_length(param_types)?;
+    if given.len() != expected as usize {
+        throw_exception!(
+            IllegalArgumentException,
+            "wrong number of arguments: {} expected: {}",
+            given.len(),
+            expected
+        )?
+    }
+    let mut converted = Vec::with_capacity(given.len());
+    for (i, arg) in given.into_iter().enumerate() {
+        let param_mirror = vm
+            .heap_read()
+            .read_array_element(param_types, i as i32)?
+            .as_obj_ref()?;
+        let ma = vm.method_area();
+        let param_class_id = ma.get_class_id_by_mirror(&param_mirror)?;
+        let arg = match ma.get_class(&param_class_id) {
+            JvmClass::Primitive(param) => unbox_and_widen(vm, arg, param.primitive_type)?,
+            _ => match arg.as_nullable_obj_ref()? {
+                Some(obj) => {
+                    let arg_class_id = vm.heap_read().get_class_id(obj)?;
+                    ma.is_assignable_from(param_class_id, arg_class_id)
+                        .then_some(arg)
+                }
+                None => Some(arg),
+            },
+        };
+        let Some(arg) = arg else {
+            throw_exception!(IllegalArgumentException, "argument type mismatch")?
+        };
+        converted.push(arg);
+    }
+    Ok(converted)
+}
+
+/// `Method.invoke` without method handles. Instance methods are selected in the class of the
+/// receiver unless they are private.
+fn jdk_internal_reflect_direct_method_handle_accessor_native_accessor_invoke_0(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let method_ref = args[0].as_obj_ref()?;
+    let method_id = MethodId::from_i32(read_named_field(vm, method_ref, "slot")?.as_int()?);
+    let (class_id, key, is_static, is_private) = {
+        let method = vm.method_area().get_method(&method_id);
+        (
+            method.class_id(),
+            MethodKey {
+                name: method.name,
+                desc: method.desc,
+            },
+            method.is_static(),
+            method.is_private(),
+        )
+    };
+    let (target_id, mut call_args) = if is_static {
+        Interpreter::ensure_initialized(thread, Some(class_id), vm)?;
+        (method_id, Vec::new())
+    } else {
+        let Some(receiver) = args[1].as_nullable_obj_ref()? else {
+            throw_exception!(NullPointerException)?
+        };
+        let receiver_class_id = vm.heap_read().get_class_id(receiver)?;
+        let ma = vm.method_area();
+        if !ma.is_assignable_from(class_id, receiver_class_id) {
+            throw_exception!(
+                IllegalArgumentException,
+                "object is not an instance of declaring class"
+            )?
+        }
+        let target_id = match ma.get_class(&receiver_class_id) {
+            _ if is_private => method_id,
+            JvmClass::Instance(receiver_class) if ma.get_class(&class_id).is_interface() => {
+                receiver_class.get_interface_method_id(&key)?
+            }
+            receiver_class => receiver_class.get_vtable_method_id(&key)?,
+        };
+        (target_id, vec![Value::Ref(receiver)])
+    };
+    call_args.extend(reflected_args(vm, method_ref, args[2])?);
+
+    let ret = if is_static {
+        Interpreter::invoke_static_method_for_value(thread, target_id, vm, call_args)
+    } else {
+        Interpreter::invoke_instance_method(thread, target_id, vm, call_args)
+    }
+    .map_err(|e| wrap_invocation_target(vm, thread, e))?;
+    let return_type = vm
+        .method_area()
+        .get_method_descriptor_by_method_id(&method_id)
+        .ret
+        .clone();
+    match (return_type, ret) {
+        (ReturnType::Type(JavaType::Primitive(ty)), Some(value)) => {
+            Ok(Some(Value::Ref(box_value(vm, thread, ty, value)?)))
+        }
+        (ReturnType::Type(_), Some(value)) => Ok(Some(value)),
+        _ => Ok(Some(Value::Null)),
+    }
+}
+
+/// `Constructor.newInstance` without method handles.
+fn jdk_internal_reflect_direct_constructor_handle_accessor_native_accessor_new_instance_0(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let constructor_ref = args[0].as_obj_ref()?;
+    let constructor_id =
+        MethodId::from_i32(read_named_field(vm, constructor_ref, "slot")?.as_int()?);
+    let class_id = vm.method_area().get_method(&constructor_id).class_id();
+    if vm
+        .method_area()
+        .get_class_like(&class_id)?
+        .flags()
+        .is_abstract()
+    {
+        throw_exception!(InstantiationException)?
+    }
+    Interpreter::ensure_initialized(thread, Some(class_id), vm)?;
+    let mut call_args = reflected_args(vm, constructor_ref, args[1])?;
+    let instance_size = vm
+        .method_area()
+        .get_instance_class(&class_id)?
+        .get_instance_size()?;
+    let instance = vm.heap_write().alloc_instance(instance_size, class_id)?;
+    call_args.insert(0, Value::Ref(instance));
+    Interpreter::invoke_instance_method(thread, constructor_id, vm, call_args)
+        .map_err(|e| wrap_invocation_target(vm, thread, e))?;
+    Ok(Some(Value::Ref(instance)))
+}
+
+/// The constant pool of the class whose mirror is the `constantPoolOop` argument.
+fn constant_pool<'a>(
+    vm: &'a VirtualMachine,
+    args: &[Value],
+) -> Result<&'a RuntimeConstantPool, JvmError> {
+    let class_id = vm
+        .method_area()
+        .get_class_id_by_mirror(&args[1].as_obj_ref()?)?;
+    vm.method_area().get_cp(&class_id)
+}
+
+/// The constant pool and the index argument, the entry at the index must be of type `expected`.
+fn constant_at<'a>(
+    vm: &'a VirtualMachine,
+    args: &[Value],
+    expected: RuntimeConstantType,
+) -> Result<(&'a RuntimeConstantPool, u16), JvmError> {
+    let cp = constant_pool(vm, args)?;
+    let idx = checked_index(cp, args)?;
+    if cp.get_constant(&idx, vm.interner())?.get_type() != expected {
+        throw_exception!(
+            IllegalArgumentException,
+            "Wrong type at constant pool index"
+        )?
+    }
+    Ok((cp, idx))
+}
+
+fn checked_index(cp: &RuntimeConstantPool, args: &[Value]) -> Result<u16, JvmError> {
+    let idx = args[2].as_int()?;
+    if idx <= 0 || idx as usize >= cp.size() {
+        throw_exception!(
+            IllegalArgumentException,
+            "Constant pool index out of bounds"
+        )?
+    }
+    Ok(idx as u16)
+}
+
+fn jdk_internal_reflect_constant_pool_get_size_0(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    Ok(Some(Value::Integer(constant_pool(vm, args)?.size() as i32)))
+}
+
+fn jdk_internal_reflect_constant_pool_get_int_at_0(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let (cp, idx) = constant_at(vm, args, RuntimeConstantType::Integer)?;
+    match cp.get_constant(&idx, vm.interner())? {
+        RuntimeConstant::Integer(v) => Ok(Some(Value::Integer(*v))),
+        _ => unreachable!(),
+    }
+}
+
+fn jdk_internal_reflect_constant_pool_get_long_at_0(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let (cp, idx) = constant_at(vm, args, RuntimeConstantType::Long)?;
+    match cp.get_constant(&idx, vm.interner())? {
+        RuntimeConstant::Long(v) => Ok(Some(Value::Long(*v))),
+        _ => unreachable!(),
+    }
+}
+
+fn jdk_internal_reflect_constant_pool_get_float_at_0(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let (cp, idx) = constant_at(vm, args, RuntimeConstantType::Float)?;
+    match cp.get_constant(&idx, vm.interner())? {
+        RuntimeConstant::Float(v) => Ok(Some(Value::Float(*v))),
+        _ => unreachable!(),
+    }
+}
+
+fn jdk_internal_reflect_constant_pool_get_double_at_0(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let (cp, idx) = constant_at(vm, args, RuntimeConstantType::Double)?;
+    match cp.get_constant(&idx, vm.interner())? {
+        RuntimeConstant::Double(v) => Ok(Some(Value::Double(*v))),
+        _ => unreachable!(),
+    }
+}
+
+fn jdk_internal_reflect_constant_pool_get_utf8_at_0(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let (cp, idx) = constant_at(vm, args, RuntimeConstantType::Utf8)?;
+    let sym = cp.get_utf8_sym(&idx, vm.interner())?;
+    Ok(Some(Value::Ref(
+        vm.heap_write().get_str_from_pool_or_new(sym)?,
+    )))
+}
+
+fn jdk_internal_reflect_constant_pool_get_string_at_0(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let (cp, idx) = constant_at(vm, args, RuntimeConstantType::String)?;
+    let sym = cp.get_string_sym(&idx, vm.interner())?;
+    Ok(Some(Value::Ref(
+        vm.heap_write().get_str_from_pool_or_new(sym)?,
+    )))
+}
+
+/// Resolves the class like the class owning the constant pool would.
+fn jdk_internal_reflect_constant_pool_get_class_at_0(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let (cp, idx) = constant_at(vm, args, RuntimeConstantType::Class)?;
+    let name = cp.get_class_sym(&idx, vm.interner())?;
+    let accessor = vm
+        .method_area()
+        .get_class_id_by_mirror(&args[1].as_obj_ref()?)?;
+    let class_id = Interpreter::resolve_class(thread, accessor, name, vm)?;
+    let mirror_ref = vm
+        .method_area()
+        .get_mirror_ref_or_create(class_id, &vm.heap)?;
+    Ok(Some(Value::Ref(mirror_ref)))
+}
+
+/// The `JVM_CONSTANT_*` tag of the entry at the index, 0 for the unusable slot after a long or
+/// a double.
+fn jdk_internal_reflect_constant_pool_get_tag_at_0(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let cp = constant_pool(vm, args)?;
+    let idx = checked_index(cp, args)?;
+    let tag = match cp.get_constant(&idx, vm.interner())?.get_type() {
+        RuntimeConstantType::Unused => 0,
+        RuntimeConstantType::Utf8 => 1,
+        RuntimeConstantType::Integer => 3,
+        RuntimeConstantType::Float => 4,
+        RuntimeConstantType::Long => 5,
+        RuntimeConstantType::Double => 6,
+        RuntimeConstantType::Class => 7,
+        RuntimeConstantType::String => 8,
+        RuntimeConstantType::Field => 9,
+        RuntimeConstantType::Method => 10,
+        RuntimeConstantType::InterfaceMethod => 11,
+        RuntimeConstantType::NameAndType
+        | RuntimeConstantType::MethodNameAndType
+        | RuntimeConstantType::FieldNameAndType => 12,
+        RuntimeConstantType::MethodHandle => 15,
+        RuntimeConstantType::MethodType => 16,
+        RuntimeConstantType::Dynamic => 17,
+        RuntimeConstantType::InvokeDynamic => 18,
+    };
+    Ok(Some(Value::Integer(tag)))
+}
@@ -0,0 +1,226 @@
+use crate::error::JvmError;
+use crate::heap::HeapRef;
+use crate::keys::ClassId;
+use crate::native::{byte_array, load_and_initialize, new_object};
+use crate::thread::JavaThreadState;
+use crate::vm::Value;
+use crate::{MethodId, VirtualMachine};
+use common::jtype::{JavaType, PrimitiveType};
+
+const BOX_CLASSES: [(PrimitiveType, &str); 8] = [
+    (PrimitiveType::Boolean, "java/lang/Boolean"),
+    (PrimitiveType::Byte, "java/lang/Byte"),
+    (PrimitiveType::Char, "java/lang/Character"),
+    (PrimitiveType::Short, "java/lang/Short"),
+    (PrimitiveType::Int, "java/lang/Integer"),
+    (PrimitiveType::Long, "java/lang/Long"),
+    (PrimitiveType::Float, "java/lang/Float"),
+    (PrimitiveType::Double, "java/lang/Double"),
+];
+
+/// Value of the instance field `name` of `obj`, looked up in the class of `obj`.
+pub(super) fn read_named_field(
+    vm: &VirtualMachine,
+    obj: HeapRef,
+    name: &str,
+) -> Result<Value, JvmError> {
+    let (offset, field_type) = {
+        let ma = vm.method_area();
+        let class_id = vm.heap_read().get_class_id(obj)?;
+        let field = *ma
+            .get_instance_class(&class_id)?
+            .get_instance_field_by_name(&vm.interner().get_or_intern(name))?;
+        (
+            field.offset,
+            ma.get_field_descriptor(&field.descriptor_id)
+                .as_allocation_type(),
+        )
+    };
+    vm.heap_read().read_field(obj, offset, field_type)
+}
+
+/// Sets the instance field `name` of `obj`, looked up in the class of `obj`.
+pub(super) fn write_named_field(
+    vm: &VirtualMachine,
+    obj: HeapRef,
+    name: &str,
+    value: Value,
+) -> Result<(), JvmError> {
+    let (offset, field_type) = {
+        let ma = vm.method_area();
+        let class_id = vm.heap_read().get_class_id(obj)?;
+        let field = *ma
+            .get_instance_class(&class_id)?
+            .get_instance_field_by_name(&vm.interner().get_or_intern(name))?;
+        (
+            field.offset,
+            ma.get_field_descriptor(&field.descriptor_id)
+                .as_allocation_type(),
+        )
+    };
+    vm.heap_write().write_field(obj, offset, value, field_type)
+}
+
+/// The `byte[]` of an annotation attribute, `null` when the class file doesn't have it.
+pub(super) fn annotation_bytes(
+    vm: &VirtualMachine,
+    bytes: Option<&[u8]>,
+) -> Result<Value, JvmError> {
+    match bytes {
+        Some(bytes) => Ok(Value::Ref(byte_array(vm, bytes)?)),
+        None => Ok(Value::Null),
+    }
+}
+
+/// Class of the first frame from `depth` down that isn't part of core reflection, like
+/// `JVM_GetCallerClass`: `Method.invoke`, the method accessors and compiled lambda forms are
+/// skipped, so a method invoked reflectively or through a method handle sees the class that
+/// called `Method.invoke` or `invokeExact`.
+pub(super) fn caller_class_id(
+    vm: &VirtualMachine,
+    thread: &JavaThreadState,
+    depth: usize,
+) -> Option<ClassId> {
+    let ma = vm.method_area();
+    thread
+        .stack
+        .frames()
+        .iter()
+        .rev()
+        .skip(depth)
+        .map(|frame| frame.method_id())
+        .find(|method_id| !is_reflection_frame(vm, method_id))
+        .map(|method_id| ma.get_method(&method_id).class_id())
+}
+
+fn is_reflection_frame(vm: &VirtualMachine, method_id: &MethodId) -> bool {
+    let ma = vm.method_area();
+    let interner = vm.interner();
+    let method = ma.get_method(method_id);
+    let class_id = method.class_id();
+    let class_name = ma.get_class(&class_id).get_name();
+    (class_name == interner.get_or_intern("java/lang/reflect/Method")
+        && method.name == interner.get_or_intern("invoke"))
+        || class_name
+            == interner
+                .get_or_intern("jdk/internal/reflect/DirectMethodHandleAccessor$NativeAccessor")
+        || ma.instance_of(
+            class_id,
+            None,
+            interner.get_or_intern("jdk/internal/reflect/MethodAccessorImpl"),
+        )
+        || ma.is_compiled_lambda_form(method_id)
+}
+
+/// A new box of `value`, like the reference implementation the caches of `valueOf` aren't used.
+pub(crate) fn box_value(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    ty: PrimitiveType,
+    value: Value,
+) -> Result<HeapRef, JvmError> {
+    let (_, class_name) = BOX_CLASSES.iter().find(|(p, _)| *p == ty).unwrap();
+    let class_id = load_and_initialize(vm, thread, class_name)?;
+    let (instance_size, offset) = {
+        let ma = vm.method_area();
+        let class = ma.get_instance_class(&class_id)?;
+        (
+            class.get_instance_size()?,
+            class
+                .get_instance_field_by_name(&vm.interner().get_or_intern("value"))?
+                .offset,
+        )
+    };
+    let mut heap = vm.heap_write();
+    let box_ref = heap.alloc_instance(instance_size, class_id)?;
+    heap.write_field(
+        box_ref,
+        offset,
+        value,
+        JavaType::Primitive(ty).as_allocation_type(),
+    )?;
+    Ok(box_ref)
+}
+
+/// Type and value of the primitive `obj` boxes, `None` if it isn't a box.
+pub(crate) fn unbox(
+    vm: &VirtualMachine,
+    obj: HeapRef,
+) -> Result<Option<(PrimitiveType, Value)>, JvmError> {
+    let class_id = vm.heap_read().get_class_id(obj)?;
+    let class_name = vm
+        .interner()
+        .resolve(&vm.method_area().get_class(&class_id).get_name());
+    let Some((ty, _)) = BOX_CLASSES.iter().find(|(_, name)| *name == class_name) else {
+        return Ok(None);
+    };
+    Ok(Some((*ty, read_named_field(vm, obj, "value")?)))
+}
+
+/// Widening primitive conversion (JLS §5.1.2) of `value` of type `from`, `None` if there is
+/// none to `to`.
+pub(super) fn widen(value: Value, from: PrimitiveType, to: PrimitiveType) -> Option<Value> {
+    use PrimitiveType::*;
+    let widens = from == to
+        || match from {
+            Byte => matches!(to, Short | Int | Long | Float | Double),
+            Short | Char => matches!(to, Int | Long | Float | Double),
+            Int => matches!(to, Long | Float | Double),
+            Long => matches!(to, Float | Double),
+            Float => to == Double,
+            Double | Boolean => false,
+        };
+    if !widens {
+        return None;
+    }
+    Some(match (value, to) {
+        (Value::Integer(i), Long) => Value::Long(i as i64),
+        (Value::Integer(i), Float) => Value::Float(i as f32),
+        (Value::Integer(i), Double) => Value::Double(i as f64),
+        (Value::Long(l), Float) => Value::Float(l as f32),
+        (Value::Long(l), Double) => Value::Double(l as f64),
+        (Value::Float(f), Double) => Value::Double(f as f64),
+        (value, _) => value,
+    })
+}
+
+/// Unboxes `value` and widens it to `to`, `None` if it is `null`, no box or doesn't widen.
+pub(super) fn unbox_and_widen(
+    vm: &VirtualMachine,
+    value: Value,
+    to: PrimitiveType,
+) -> Result<Option<Value>, JvmError> {
+    let Some(obj) = value.as_nullable_obj_ref()? else {
+        return Ok(None);
+    };
+    Ok(unbox(vm, obj)?.and_then(|(from, value)| widen(value, from, to)))
+}
+
+/// Wraps an exception thrown by a method invoked through reflection in
+/// `InvocationTargetException`.
+pub(super) fn wrap_invocation_target(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    e: JvmError,
+) -> JvmError {
+    let res = (|| {
+        let exception_ref = match e {
+            JvmError::JavaExceptionThrown(exception_ref) => exception_ref,
+            JvmError::JavaException(exception) => {
+                vm.map_rust_error_to_java_exception(thread, exception)?
+            }
+            e => return Err(e),
+        };
+        new_object(
+            vm,
+            thread,
+            "java/lang/reflect/InvocationTargetException",
+            "(Ljava/lang/Throwable;)V",
+            vec![Value::Ref(exception_ref)],
+        )
+    })();
+    match res {
+        Ok(wrapper_ref) => JvmError::JavaExceptionThrown(wrapper_ref),
+        Err(e) => e,
+    }
+}
@@ -1,12 +1,27 @@
 use crate::error::JvmError;
-use crate::keys::FullyQualifiedMethodKey;
-use crate::native::NativeRet;
+use crate::heap::HeapRef;
+use crate::interpreter::Interpreter;
+use crate::keys::{ClassId, FullyQualifiedMethodKey};
+use crate::native::reflection::annotation_bytes;
+use crate::native::{NativeRet, new_object};
+use crate::rt::RawAnnotations;
 use crate::thread::JavaThreadState;
 use crate::vm::Value;
-use crate::{ThreadId, VirtualMachine};
+use crate::{Symbol, ThreadId, VirtualMachine, throw_exception};
 use common::jtype::AllocationType;
 use tracing_log::log::debug;
 
+/// `JVM_RECOGNIZED_FIELD_MODIFIERS`, the field flags reflection shows.
+const FIELD_MODIFIERS: i32 = 0x50df;
+/// `JVM_RECOGNIZED_METHOD_MODIFIERS`
+const METHOD_MODIFIERS: i32 = 0x1dff;
+
+const FIELD_INIT_DESC: &str =
+    "(Ljava/lang/Class;Ljava/lang/String;Ljava/lang/Class;IZILjava/lang/String;[B)V";
+const METHOD_INIT_DESC: &str = "(Ljava/lang/Class;Ljava/lang/String;[Ljava/lang/Class;Ljava/lang/Class;[Ljava/lang/Class;IILjava/lang/String;[B[B[B)V";
+const CONSTRUCTOR_INIT_DESC: &str =
+    "(Ljava/lang/Class;[Ljava/lang/Class;[Ljava/lang/Class;IILjava/lang/String;[B[B)V";
+
 pub(super) fn java_lang_class_register_natives(
     vm: &VirtualMachine,
     _thread: &mut JavaThreadState,
@@ -89,6 +104,132 @@ pub(super) fn java_lang_class_register_natives(
         ),
         java_lang_class_is_assignable_from,
     );
+    vm.native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "java/lang/Class",
+            "forName0",
+            "(Ljava/lang/String;ZLjava/lang/ClassLoader;Ljava/lang/Class;)Ljava/lang/Class;",
+            &vm.string_interner,
+        ),
+        java_lang_class_for_name_0,
+    );
+    vm.native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "java/lang/Class",
+            "forName0",
+            "(Ljava/lang/String;ZLjava/lang/ClassLoader;)Ljava/lang/Class;",
+            &vm.string_interner,
+        ),
+        java_lang_class_for_name_0,
+    );
+    vm.native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "java/lang/Class",
+            "getDeclaredFields0",
+            "(Z)[Ljava/lang/reflect/Field;",
+            &vm.string_interner,
+        ),
+        java_lang_class_get_declared_fields_0,
+    );
+    vm.native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "java/lang/Class",
+            "getDeclaredMethods0",
+            "(Z)[Ljava/lang/reflect/Method;",
+            &vm.string_interner,
+        ),
+        java_lang_class_get_declared_methods_0,
+    );
+    vm.native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "java/lang/Class",
+            "getDeclaredConstructors0",
+            "(Z)[Ljava/lang/reflect/Constructor;",
+            &vm.string_interner,
+        ),
+        java_lang_class_get_declared_constructors_0,
+    );
+    vm.native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "java/lang/Class",
+            "getInterfaces0",
+            "()[Ljava/lang/Class;",
+            &vm.string_interner,
+        ),
+        java_lang_class_get_interfaces_0,
+    );
+    vm.native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "java/lang/Class",
+            "getDeclaringClass0",
+            "()Ljava/lang/Class;",
+            &vm.string_interner,
+        ),
+        java_lang_class_get_declaring_class_0,
+    );
+    vm.native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "java/lang/Class",
+            "getEnclosingMethod0",
+            "()[Ljava/lang/Object;",
+            &vm.string_interner,
+        ),
+        java_lang_class_get_enclosing_method_0,
+    );
+    vm.native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "java/lang/Class",
+            "getSimpleBinaryName0",
+            "()Ljava/lang/String;",
+            &vm.string_interner,
+        ),
+        java_lang_class_get_simple_binary_name_0,
+    );
+    vm.native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "java/lang/Class",
+            "getGenericSignature0",
+            "()Ljava/lang/String;",
+            &vm.string_interner,
+        ),
+        java_lang_class_get_generic_signature_0,
+    );
+    vm.native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "java/lang/Class",
+            "isHidden",
+            "()Z",
+            &vm.string_interner,
+        ),
+        java_lang_class_is_hidden,
+    );
+    vm.native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "java/lang/Class",
+            "getRawAnnotations",
+            "()[B",
+            &vm.string_interner,
+        ),
+        java_lang_class_get_raw_annotations,
+    );
+    vm.native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "java/lang/Class",
+            "getRawTypeAnnotations",
+            "()[B",
+            &vm.string_interner,
+        ),
+        java_lang_class_get_raw_type_annotations,
+    );
+    vm.native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "java/lang/Class",
+            "getConstantPool",
+            "()Ljdk/internal/reflect/ConstantPool;",
+            &vm.string_interner,
+        ),
+        java_lang_class_get_constant_pool,
+    );
 
     Ok(None)
 }
@@ -103,25 +244,32 @@ fn java_lang_class_is_assignable_from(
     let other_class_mirror = args[1].as_obj_ref()?;
 
     let this_class_id = vm
-        .method_area_read()
+        .method_area()
         .get_class_id_by_mirror(&this_class_mirror)?;
     let other_class_id = vm
-        .method_area_read()
+        .method_area()
         .get_class_id_by_mirror(&other_class_mirror)?;
     let is_assignable = vm
-        .method_area_read()
+        .method_area()
         .is_assignable_from(this_class_id, other_class_id);
 
     Ok(Some(Value::Integer(if is_assignable { 1 } else { 0 })))
 }
 
 fn java_lang_class_desired_assertion_status_0(
-    _vm: &VirtualMachine,
+    vm: &VirtualMachine,
     _thread: &mut JavaThreadState,
-    _args: &[Value],
+    args: &[Value],
 ) -> NativeRet {
-    debug!("TODO: Stub: java.lang.Class.desiredAssertionStatus0");
-    Ok(Some(Value::Integer(1)))
+    let ma = vm.method_area();
+    let class_id = ma.get_class_id_by_mirror(&args[0].as_obj_ref()?)?;
+    let name = vm
+        .interner()
+        .resolve(&ma.get_class(&class_id).get_name())
+        .replace('/', ".");
+    let system = ma.get_class_loader(class_id).is_none();
+    let enabled = vm.config.assertions.enabled(&name, system);
+    Ok(Some(Value::Integer(enabled as i32)))
 }
 
 fn java_lang_class_is_interface(
@@ -135,11 +283,8 @@ fn java_lang_class_is_interface(
             "java.lang.Class.isInterface: missing 0 argument".to_string(),
         ))?
         .as_obj_ref()?;
-    let target_class_id = vm.method_area_read().get_class_id_by_mirror(&mirror_ref)?;
-    let is_interface = vm
-        .method_area_read()
-        .get_class(&target_class_id)
-        .is_interface();
+    let target_class_id = vm.method_area().get_class_id_by_mirror(&mirror_ref)?;
+    let is_interface = vm.method_area().get_class(&target_class_id).is_interface();
     Ok(Some(Value::Integer(if is_interface { 1 } else { 0 })))
 }
 
@@ -154,8 +299,8 @@ fn java_lang_class_is_array(
             "java.lang.Class.isArray: missing 0 argument".to_string(),
         ))?
         .as_obj_ref()?;
-    let target_class_id = vm.method_area_read().get_class_id_by_mirror(&mirror_ref)?;
-    let is_array = vm.method_area_read().get_class(&target_class_id).is_array();
+    let target_class_id = vm.method_area().get_class_id_by_mirror(&mirror_ref)?;
+    let is_array = vm.method_area().get_class(&target_class_id).is_array();
     Ok(Some(Value::Integer(if is_array { 1 } else { 0 })))
 }
 
@@ -170,11 +315,15 @@ fn java_lang_class_get_modifiers(
             "java.lang.Class.getModifiers: missing 0 argument".to_string(),
         ))?
         .as_obj_ref()?;
-    let target_class_id = vm.method_area_read().get_class_id_by_mirror(&mirror_ref)?;
-    let modifiers = vm
-        .method_area_read()
-        .get_class(&target_class_id)
-        .get_raw_flags();
+    let target_class_id = vm.method_area().get_class_id_by_mirror(&mirror_ref)?;
+    let class = vm.method_area().get_class(&target_class_id);
+    // a nested class has the modifiers of its declaration, e.g. `static` or `private`
+    let inner_flags = class
+        .as_class_like()
+        .ok()
+        .and_then(|class| class.metadata().inner_class.as_ref())
+        .map(|inner| inner.flags as i32);
+    let modifiers = inner_flags.unwrap_or_else(|| class.get_raw_flags());
     Ok(Some(Value::Integer(modifiers)))
 }
 
@@ -193,10 +342,10 @@ fn java_lang_class_get_primitive_class(
         .heap_read()
         .get_rust_string_from_java_string(primitive_name_ref)?;
     let class_id = vm
-        .method_area_write()
+        .method_area()
         .get_class_id_or_load(vm.interner().get_or_intern(&primitive_name), thread.id)?;
     let v = vm
-        .method_area_write()
+        .method_area()
         .get_mirror_ref_or_create(class_id, &vm.heap)?;
     Ok(Some(Value::Ref(v)))
 }
@@ -214,18 +363,22 @@ fn java_lang_class_init_class_name(
         .as_obj_ref()?;
     let class_class_id = vm.br.get_java_lang_class_id()?;
     let class_name_fk = vm.br.class_name_fk;
-    let target_class_id = vm.method_area_read().get_class_id_by_mirror(&mirror_ref)?;
-    let name_sym = vm.method_area_read().get_class(&target_class_id).get_name();
+    let target_class_id = vm.method_area().get_class_id_by_mirror(&mirror_ref)?;
+    let name_sym = vm.method_area().get_class(&target_class_id).get_name();
+    // the `+0x...` suffix of a hidden class reads `/0x...`
+    let is_hidden = vm.method_area().is_hidden(&target_class_id);
     let name_ref = vm
         .heap_write()
         .alloc_string_from_interned_with_char_mapping(
             name_sym,
-            Some(&|c| {
-                if c == '/' { '.' } else { c }
+            Some(&|c| match c {
+                '/' => '.',
+                '+' if is_hidden => '/',
+                c => c,
             }),
         )?;
     let name_field_offset = {
-        let ma = vm.method_area_read();
+        let ma = vm.method_area();
         ma.get_instance_field(&class_class_id, &class_name_fk)?
             .offset
     };
@@ -249,9 +402,9 @@ fn java_lang_class_get_superclass(
             "java.lang.Class.getSuperclass: missing 0 argument".to_string(),
         ))?
         .as_obj_ref()?;
-    let target_class_id = vm.method_area_read().get_class_id_by_mirror(&mirror_ref)?;
+    let target_class_id = vm.method_area().get_class_id_by_mirror(&mirror_ref)?;
     let (super_class_id, is_interface, is_primitive) = {
-        let ma = vm.method_area_read();
+        let ma = vm.method_area();
         (
             ma.get_class(&target_class_id).get_super_id(),
             ma.get_class(&target_class_id).is_interface(),
@@ -262,10 +415,474 @@ fn java_lang_class_get_superclass(
         Ok(Some(Value::Null))
     } else if let Some(super_id) = super_class_id {
         let super_mirror_ref = vm
-            .method_area_write()
+            .method_area()
             .get_mirror_ref_or_create(super_id, &vm.heap)?;
         Ok(Some(Value::Ref(super_mirror_ref)))
     } else {
         Ok(Some(Value::Null))
     }
 }
+
+fn mirror_class_id(vm: &VirtualMachine, mirror: &Value) -> Result<ClassId, JvmError> {
+    vm.method_area()
+        .get_class_id_by_mirror(&mirror.as_obj_ref()?)
+}
+
+fn mirror(vm: &VirtualMachine, class_id: ClassId) -> Result<Value, JvmError> {
+    Ok(Value::Ref(
+        vm.method_area()
+            .get_mirror_ref_or_create(class_id, &vm.heap)?,
+    ))
+}
+
+/// The interned string of `sym`, `null` without one.
+fn interned_string(vm: &VirtualMachine, sym: Option<Symbol>) -> Result<Value, JvmError> {
+    match sym {
+        Some(sym) => Ok(Value::Ref(vm.heap_write().get_str_from_pool_or_new(sym)?)),
+        None => Ok(Value::Null),
+    }
+}
+
+fn object_array(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    array_class_name: &str,
+    elements: Vec<Value>,
+) -> Result<HeapRef, JvmError> {
+    let array_class_id = vm
+        .method_area()
+        .get_class_id_or_load(vm.interner().get_or_intern(array_class_name), thread.id)?;
+    let mut heap = vm.heap_write();
+    let array_ref = heap.alloc_object_array(array_class_id, elements.len() as i32)?;
+    for (i, element) in elements.into_iter().enumerate() {
+        heap.write_array_element(array_ref, i as i32, element)?;
+    }
+    Ok(array_ref)
+}
+
+fn class_array(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    class_ids: Vec<ClassId>,
+) -> Result<HeapRef, JvmError> {
+    let mirrors = class_ids
+        .into_iter()
+        .map(|class_id| mirror(vm, class_id))
+        .collect::<Result<_, _>>()?;
+    object_array(vm, thread, "[Ljava/lang/Class;", mirrors)
+}
+
+fn java_lang_class_for_name_0(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let Some(name_ref) = args[0].as_nullable_obj_ref()? else {
+        throw_exception!(NullPointerException)?
+    };
+    let name = vm.heap_read().get_rust_string_from_java_string(name_ref)?;
+    let initialize = args[1].as_int()? != 0;
+    let loader = args[2].as_nullable_obj_ref()?;
+    if name.is_empty() || name.contains('/') {
+        throw_exception!(ClassNotFoundException, name.clone())?
+    }
+    let name_sym = vm.interner().get_or_intern(name.replace('.', "/"));
+    let class_id = Interpreter::load_class(thread, loader, name_sym, vm)?;
+    if vm.method_area().get_class(&class_id).is_primitive() {
+        throw_exception!(ClassNotFoundException, name)?
+    }
+    if initialize {
+        Interpreter::ensure_initialized(thread, Some(class_id), vm)?;
+    }
+    Ok(Some(mirror(vm, class_id)?))
+}
+
+/// Arrays and primitive types declare no members.
+fn java_lang_class_get_declared_fields_0(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let class_id = mirror_class_id(vm, &args[0])?;
+    let public_only = args[1].as_int()? != 0;
+    let (fields, loader, is_record) = match vm.method_area().get_class(&class_id).as_class_like() {
+        Ok(class) => (
+            class.declared_fields()?.to_vec(),
+            class.loader(),
+            class.metadata().is_record,
+        ),
+        Err(_) => (Vec::new(), None, false),
+    };
+
+    let mut field_refs = Vec::with_capacity(fields.len());
+    for (slot, field) in fields.into_iter().enumerate() {
+        if public_only && !field.flags.is_public() {
+            continue;
+        }
+        let type_id = Interpreter::load_descriptor_class(
+            thread,
+            loader,
+            vm.interner().resolve(&field.key.desc),
+            vm,
+        )?;
+        // final fields of records can't be changed through reflection either
+        let trusted_final = field.flags.is_final() && (field.flags.is_static() || is_record);
+        let field_args = vec![
+            args[0],
+            interned_string(vm, Some(field.key.name))?,
+            mirror(vm, type_id)?,
+            Value::Integer(*field.flags.get_raw() as i32 & FIELD_MODIFIERS),
+            Value::Integer(trusted_final as i32),
+            Value::Integer(slot as i32),
+            interned_string(vm, field.signature)?,
+            annotation_bytes(vm, field.annotations.annotations.as_deref())?,
+        ];
+        let field_ref = new_object(
+            vm,
+            thread,
+            "java/lang/reflect/Field",
+            FIELD_INIT_DESC,
+            field_args,
+        )?;
+        field_refs.push(Value::Ref(field_ref));
+    }
+    let array_ref = object_array(vm, thread, "[Ljava/lang/reflect/Field;", field_refs)?;
+    Ok(Some(Value::Ref(array_ref)))
+}
+
+/// What `Method` and `Constructor` objects are built from.
+struct ReflectedMethod {
+    /// The current version of the method, also its slot.
+    method_id: crate::MethodId,
+    name: Symbol,
+    parameter_types: HeapRef,
+    return_type: ClassId,
+    exception_types: HeapRef,
+    modifiers: i32,
+    signature: Option<Symbol>,
+    annotations: RawAnnotations,
+}
+
+/// The methods declared by the class of `mirror` that `filter` accepts, in declaration order.
+fn reflected_methods(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    mirror: &Value,
+    public_only: bool,
+    filter: impl Fn(Symbol) -> bool,
+) -> Result<Vec<ReflectedMethod>, JvmError> {
+    let class_id = mirror_class_id(vm, mirror)?;
+    let ma = vm.method_area();
+    let (method_ids, loader) = match ma.get_class(&class_id).as_class_like() {
+        Ok(class) => (class.declared_method_ids()?.to_vec(), class.loader()),
+        Err(_) => (Vec::new(), None),
+    };
+
+    let mut methods = Vec::new();
+    for method_id in method_ids {
+        let method_id = ma.current_method_id(method_id);
+        let method = ma.get_method(&method_id);
+        if !filter(method.name) || (public_only && !method.flags().is_public()) {
+            continue;
+        }
+        let (name, desc, modifiers, annotations) = (
+            method.name,
+            method.desc,
+            *method.flags().get_raw() as i32 & METHOD_MODIFIERS,
+            method.annotations().clone(),
+        );
+        let cp = ma.get_cp_by_method_id(&method_id)?;
+        let signature = method
+            .signature_idx()
+            .map(|idx| cp.get_utf8_sym(&idx, vm.interner()))
+            .transpose()?;
+        let exception_names = method
+            .exception_idxs()
+            .iter()
+            .map(|idx| cp.get_class_sym(idx, vm.interner()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut types = Interpreter::load_method_descriptor_classes(
+            thread,
+            loader,
+            vm.interner().resolve(&desc),
+            vm,
+        )?;
+        let return_type = types.pop().ok_or_else(|| {
+            JvmError::Todo(format!(
+                "Invalid method descriptor {}",
+                vm.interner().resolve(&desc)
+            ))
+        })?;
+        let mut exception_types = Vec::with_capacity(exception_names.len());
+        for name in exception_names {
+            exception_types.push(Interpreter::load_class(thread, loader, name, vm)?);
+        }
+        methods.push(ReflectedMethod {
+            method_id,
+            name,
+            parameter_types: class_array(vm, thread, types)?,
+            return_type,
+            exception_types: class_array(vm, thread, exception_types)?,
+            modifiers,
+            signature,
+            annotations,
+        });
+    }
+    Ok(methods)
+}
+
+fn java_lang_class_get_declared_methods_0(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let public_only = args[1].as_int()? != 0;
+    let br = vm.br();
+    let methods = reflected_methods(vm, thread, &args[0], public_only, |name| {
+        name != br.init_sym && name != br.clinit_sym
+    })?;
+
+    let mut method_refs = Vec::with_capacity(methods.len());
+    for method in methods {
+        let method_args = vec![
+            args[0],
+            interned_string(vm, Some(method.name))?,
+            Value::Ref(method.parameter_types),
+            mirror(vm, method.return_type)?,
+            Value::Ref(method.exception_types),
+            Value::Integer(method.modifiers),
+            Value::Integer(method.method_id.to_i32()),
+            interned_string(vm, method.signature)?,
+            annotation_bytes(vm, method.annotations.annotations.as_deref())?,
+            annotation_bytes(vm, method.annotations.parameter_annotations.as_deref())?,
+            annotation_bytes(vm, method.annotations.annotation_default.as_deref())?,
+        ];
+        let method_ref = new_object(
+            vm,
+            thread,
+            "java/lang/reflect/Method",
+            METHOD_INIT_DESC,
+            method_args,
+        )?;
+        method_refs.push(Value::Ref(method_ref));
+    }
+    let array_ref = object_array(vm, thread, "[Ljava/lang/reflect/Method;", method_refs)?;
+    Ok(Some(Value::Ref(array_ref)))
+}
+
+fn java_lang_class_get_declared_constructors_0(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let public_only = args[1].as_int()? != 0;
+    let init_sym = vm.br().init_sym;
+    let constructors =
+        reflected_methods(vm, thread, &args[0], public_only, |name| name == init_sym)?;
+
+    let mut constructor_refs = Vec::with_capacity(constructors.len());
+    for constructor in constructors {
+        let constructor_args = vec![
+            args[0],
+            Value::Ref(constructor.parameter_types),
+            Value::Ref(constructor.exception_types),
+            Value::Integer(constructor.modifiers),
+            Value::Integer(constructor.method_id.to_i32()),
+            interned_string(vm, constructor.signature)?,
+            annotation_bytes(vm, constructor.annotations.annotations.as_deref())?,
+            annotation_bytes(vm, constructor.annotations.parameter_annotations.as_deref())?,
+        ];
+        let constructor_ref = new_object(
+            vm,
+            thread,
+            "java/lang/reflect/Constructor",
+            CONSTRUCTOR_INIT_DESC,
+            constructor_args,
+        )?;
+        constructor_refs.push(Value::Ref(constructor_ref));
+    }
+    let array_ref = object_array(
+        vm,
+        thread,
+        "[Ljava/lang/reflect/Constructor;",
+        constructor_refs,
+    )?;
+    Ok(Some(Value::Ref(array_ref)))
+}
+
+/// Arrays implement `Cloneable` and `Serializable`.
+fn java_lang_class_get_interfaces_0(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let class_id = mirror_class_id(vm, &args[0])?;
+    let ma = vm.method_area();
+    let class = ma.get_class(&class_id);
+    let interfaces = if class.is_array() {
+        let mut interfaces = Vec::with_capacity(2);
+        for name in ["java/lang/Cloneable", "java/io/Serializable"] {
+            interfaces.push(ma.get_class_id_or_load(vm.interner().get_or_intern(name), thread.id)?);
+        }
+        interfaces
+    } else if class.is_primitive() {
+        Vec::new()
+    } else {
+        class.get_direct_interfaces()?.to_vec()
+    };
+    Ok(Some(Value::Ref(class_array(vm, thread, interfaces)?)))
+}
+
+/// The class declaring this one as a member, loaded by the loader of this class.
+fn java_lang_class_get_declaring_class_0(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let class_id = mirror_class_id(vm, &args[0])?;
+    let Ok(class) = vm.method_area().get_class(&class_id).as_class_like() else {
+        return Ok(Some(Value::Null));
+    };
+    let loader = class.loader();
+    let Some(outer_class) = class
+        .metadata()
+        .inner_class
+        .as_ref()
+        .and_then(|inner| inner.outer_class)
+    else {
+        return Ok(Some(Value::Null));
+    };
+    let outer_id = Interpreter::load_class(thread, loader, outer_class, vm)?;
+    Ok(Some(mirror(vm, outer_id)?))
+}
+
+/// `[class, name, descriptor]` of the method a local or anonymous class is declared in, name
+/// and descriptor are `null` for an initializer.
+fn java_lang_class_get_enclosing_method_0(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let class_id = mirror_class_id(vm, &args[0])?;
+    let Ok(class) = vm.method_area().get_class(&class_id).as_class_like() else {
+        return Ok(Some(Value::Null));
+    };
+    let loader = class.loader();
+    let Some(enclosing) = class.metadata().enclosing_method.as_ref() else {
+        return Ok(Some(Value::Null));
+    };
+    let enclosing_id = Interpreter::load_class(thread, loader, enclosing.class, vm)?;
+    let elements = vec![
+        mirror(vm, enclosing_id)?,
+        interned_string(vm, enclosing.method.map(|key| key.name))?,
+        interned_string(vm, enclosing.method.map(|key| key.desc))?,
+    ];
+    let array_ref = object_array(vm, thread, "[Ljava/lang/Object;", elements)?;
+    Ok(Some(Value::Ref(array_ref)))
+}
+
+/// `null` for anonymous and top-level classes.
+fn java_lang_class_get_simple_binary_name_0(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let class_id = mirror_class_id(vm, &args[0])?;
+    let simple_name = vm
+        .method_area()
+        .get_class(&class_id)
+        .as_class_like()
+        .ok()
+        .and_then(|class| class.metadata().inner_class.as_ref())
+        .and_then(|inner| inner.simple_name);
+    Ok(Some(interned_string(vm, simple_name)?))
+}
+
+fn java_lang_class_get_generic_signature_0(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let class_id = mirror_class_id(vm, &args[0])?;
+    let signature = vm
+        .method_area()
+        .get_class(&class_id)
+        .as_class_like()
+        .ok()
+        .and_then(|class| class.metadata().signature);
+    Ok(Some(interned_string(vm, signature)?))
+}
+
+/// Hidden classes can't be defined.
+fn java_lang_class_is_hidden(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let class_id = mirror_class_id(vm, &args[0])?;
+    Ok(Some(Value::Integer(
+        vm.method_area().is_hidden(&class_id) as i32
+    )))
+}
+
+/// The annotations of the class, `null` for arrays and primitive types.
+fn java_lang_class_get_raw_annotations(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let class_id = mirror_class_id(vm, &args[0])?;
+    let ma = vm.method_area();
+    let bytes = ma
+        .get_class(&class_id)
+        .as_class_like()
+        .ok()
+        .and_then(|class| class.metadata().annotations.annotations.as_deref());
+    Ok(Some(annotation_bytes(vm, bytes)?))
+}
+
+fn java_lang_class_get_raw_type_annotations(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let class_id = mirror_class_id(vm, &args[0])?;
+    let ma = vm.method_area();
+    let bytes = ma
+        .get_class(&class_id)
+        .as_class_like()
+        .ok()
+        .and_then(|class| class.metadata().annotations.type_annotations.as_deref());
+    Ok(Some(annotation_bytes(vm, bytes)?))
+}
+
+/// A `ConstantPool` whose `constantPoolOop` is the mirror of the class, see
+/// `jdk_internal_reflect.rs` for its natives. Arrays and primitive types have none.
+fn java_lang_class_get_constant_pool(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let class_id = mirror_class_id(vm, &args[0])?;
+    if vm.method_area().get_class(&class_id).get_cp().is_err() {
+        return Ok(Some(Value::Null));
+    }
+    let cp_ref = new_object(
+        vm,
+        thread,
+        "jdk/internal/reflect/ConstantPool",
+        "()V",
+        vec![],
+    )?;
+    let oop_offset = {
+        let ma = vm.method_area();
+        let cp_class_id = vm.heap_read().get_class_id(cp_ref)?;
+        ma.get_instance_class(&cp_class_id)?
+            .get_instance_field_by_name(&vm.interner().get_or_intern("constantPoolOop"))?
+            .offset
+    };
+    vm.heap_write()
+        .write_field(cp_ref, oop_offset, args[0], AllocationType::Reference)?;
+    Ok(Some(Value::Ref(cp_ref)))
+}
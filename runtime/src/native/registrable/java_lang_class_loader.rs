@@ -1,22 +1,191 @@
+use crate::error::JvmError;
+use crate::heap::HeapRef;
+use crate::interpreter::Interpreter;
+use crate::keys::{ClassId, FullyQualifiedMethodKey};
 use crate::native::NativeRet;
 use crate::thread::JavaThreadState;
 use crate::vm::Value;
-use crate::{ThreadId, VirtualMachine};
+use crate::{VirtualMachine, throw_exception};
+use common::jtype::AllocationType;
+
+/// `java.lang.invoke.MethodHandles.Lookup.ClassOption`
+const HIDDEN_CLASS: i32 = 0x2;
 
 pub(super) fn java_lang_class_loader_register_natives(
     vm: &VirtualMachine,
     _thread: &mut JavaThreadState,
     _args: &[Value],
 ) -> NativeRet {
+    vm.native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "java/lang/ClassLoader",
+            "defineClass0",
+            "(Ljava/lang/ClassLoader;Ljava/lang/Class;Ljava/lang/String;[BIILjava/security/ProtectionDomain;ZILjava/lang/Object;)Ljava/lang/Class;",
+            &vm.string_interner,
+        ),
+        java_lang_class_loader_define_class_0,
+    );
+    vm.native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "java/lang/ClassLoader",
+            "defineClass1",
+            "(Ljava/lang/ClassLoader;Ljava/lang/String;[BIILjava/security/ProtectionDomain;Ljava/lang/String;)Ljava/lang/Class;",
+            &vm.string_interner,
+        ),
+        java_lang_class_loader_define_class_1,
+    );
+    vm.native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "java/lang/ClassLoader",
+            "findLoadedClass0",
+            "(Ljava/lang/String;)Ljava/lang/Class;",
+            &vm.string_interner,
+        ),
+        java_lang_class_loader_find_loaded_class_0,
+    );
+    vm.native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "java/lang/ClassLoader",
+            "findBootstrapClass",
+            "(Ljava/lang/String;)Ljava/lang/Class;",
+            &vm.string_interner,
+        ),
+        java_lang_class_loader_find_bootstrap_class,
+    );
     Ok(None)
 }
 
-/*
-fn java_lang_class_loader_(
+/// Copies `b[off..off + len]` of a `byte[]` argument of `defineClass`.
+fn class_bytes(vm: &VirtualMachine, args: &[Value], b: usize) -> Result<Vec<u8>, JvmError> {
+    let Some(array_ref) = args[b].as_nullable_obj_ref()? else {
+        throw_exception!(NullPointerException)?
+    };
+    let off = args[b + 1].as_int()?;
+    let len = args[b + 2].as_int()?;
+    let heap = vm.heap_read();
+    let bytes = heap.get_byte_array_slice(array_ref)?;
+    if off < 0 || len < 0 || off as usize + len as usize > bytes.len() {
+        throw_exception!(
+            ArrayIndexOutOfBoundsException,
+            "Array region {}..{} out of bounds for length {}",
+            off,
+            off as i64 + len as i64,
+            bytes.len()
+        )?
+    }
+    Ok(bytes[off as usize..(off + len) as usize]
+        .iter()
+        .map(|b| *b as u8)
+        .collect())
+}
+
+fn nullable_string(vm: &VirtualMachine, value: &Value) -> Result<Option<String>, JvmError> {
+    value
+        .as_nullable_obj_ref()?
+        .map(|s| vm.heap_read().get_rust_string_from_java_string(s))
+        .transpose()
+}
+
+fn mirror(vm: &VirtualMachine, class_id: Option<ClassId>) -> NativeRet {
+    match class_id {
+        Some(class_id) => Ok(Some(Value::Ref(
+            vm.method_area()
+                .get_mirror_ref_or_create(class_id, &vm.heap)?,
+        ))),
+        None => Ok(Some(Value::Null)),
+    }
+}
+
+fn java_lang_class_loader_define_class_1(
     vm: &VirtualMachine,
     thread: &mut JavaThreadState,
-    _args: &[Value],
+    args: &[Value],
+) -> NativeRet {
+    let loader = args[0].as_nullable_obj_ref()?;
+    let name = nullable_string(vm, &args[1])?;
+    let bytes = class_bytes(vm, args, 2)?;
+    let class_id = Interpreter::define_class(thread, loader, name.as_deref(), bytes, vm)?;
+    mirror(vm, Some(class_id))
+}
+
+/// Backs `Lookup.defineClass`. Hidden classes are not supported.
+fn java_lang_class_loader_define_class_0(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let loader = args[0].as_nullable_obj_ref()?;
+    let name = nullable_string(vm, &args[2])?;
+    let bytes = class_bytes(vm, args, 3)?;
+    let initialize = args[7].as_int()? != 0;
+    let flags = args[8].as_int()?;
+    let class_data = args[9].as_nullable_obj_ref()?;
+    let class_id = if flags & HIDDEN_CLASS != 0 {
+        Interpreter::define_hidden_class(thread, loader, bytes, vm)?
+    } else {
+        Interpreter::define_class(thread, loader, name.as_deref(), bytes, vm)?
+    };
+    let mirror_ref = vm
+        .method_area()
+        .get_mirror_ref_or_create(class_id, &vm.heap)?;
+    if let Some(class_data) = class_data {
+        let class_data_offset = vm
+            .method_area()
+            .get_instance_field(
+                &vm.br().get_java_lang_class_id()?,
+                &vm.br().class_class_data_fk,
+            )?
+            .offset;
+        vm.heap_write().write_field(
+            mirror_ref,
+            class_data_offset,
+            Value::Ref(class_data),
+            AllocationType::Reference,
+        )?;
+    }
+    if initialize {
+        Interpreter::ensure_initialized(thread, Some(class_id), vm)?;
+    }
+    Ok(Some(Value::Ref(mirror_ref)))
+}
+
+/// Only looks in the namespace of the loader, nothing is loaded. The builtin loaders share the
+/// namespace of the VM, each of them finds the classes it or its parents defined there.
+fn java_lang_class_loader_find_loaded_class_0(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let loader: HeapRef = args[0].as_obj_ref()?;
+    let Some(name) = nullable_string(vm, &args[1])? else {
+        return Ok(Some(Value::Null));
+    };
+    if name.contains('/') {
+        return Ok(Some(Value::Null));
+    }
+    let name_sym = vm.interner().get_or_intern(name.replace('.', "/"));
+    let ma = vm.method_area();
+    let class_id = ma
+        .find_loaded_class(Some(loader), name_sym)
+        .filter(|class_id| {
+            !ma.is_builtin_loader(Some(loader))
+                || ma.is_visible_to_builtin_loader(Some(loader), *class_id)
+        });
+    mirror(vm, class_id)
+}
+
+fn java_lang_class_loader_find_bootstrap_class(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
 ) -> NativeRet {
-    Ok(Some(Value::Ref(vm.get_thread(&thread_id).thread_obj)))
+    let Some(name) = nullable_string(vm, &args[0])? else {
+        return Ok(Some(Value::Null));
+    };
+    if name.contains('/') {
+        return Ok(Some(Value::Null));
+    }
+    let name_sym = vm.interner().get_or_intern(name.replace('.', "/"));
+    let class_id = vm.method_area().find_bootstrap_class(name_sym, thread.id)?;
+    mirror(vm, class_id)
 }
- */
@@ -0,0 +1,660 @@
+use crate::error::JvmError;
+use crate::heap::HeapRef;
+use crate::interpreter::Interpreter;
+use crate::keys::{ClassId, FieldKey, FullyQualifiedMethodKey, MethodKey};
+use crate::native::reflection::{box_value, read_named_field, write_named_field};
+use crate::native::registrable::jdk_internal_misc_unsafe::STATIC_FIELD_OFFSET_BASE;
+use crate::native::{NativeRet, load_and_initialize};
+use crate::rt::field::DeclaredField;
+use crate::thread::JavaThreadState;
+use crate::vm::Value;
+use crate::{MethodId, VirtualMachine, build_exception, throw_exception};
+use common::jtype::PrimitiveType;
+
+/// `java.lang.invoke.MethodHandleNatives.Constants`
+const IS_METHOD: i32 = 0x0001_0000;
+const IS_CONSTRUCTOR: i32 = 0x0002_0000;
+const IS_FIELD: i32 = 0x0004_0000;
+const CALLER_SENSITIVE: i32 = 0x0010_0000;
+const TRUSTED_FINAL: i32 = 0x0020_0000;
+const REFERENCE_KIND_SHIFT: i32 = 24;
+const REFERENCE_KIND_MASK: i32 = 0x0F;
+const REF_GET_FIELD: i32 = 1;
+const REF_GET_STATIC: i32 = 2;
+const REF_PUT_STATIC: i32 = 4;
+const REF_INVOKE_VIRTUAL: i32 = 5;
+const REF_INVOKE_STATIC: i32 = 6;
+const REF_INVOKE_SPECIAL: i32 = 7;
+const REF_NEW_INVOKE_SPECIAL: i32 = 8;
+const REF_INVOKE_INTERFACE: i32 = 9;
+
+/// `JVM_RECOGNIZED_METHOD_MODIFIERS` and `JVM_RECOGNIZED_FIELD_MODIFIERS`
+const METHOD_MODIFIERS: i32 = 0x1DFF;
+const FIELD_MODIFIERS: i32 = 0x50DF;
+
+pub(super) fn java_lang_invoke_method_handle_natives_register_natives(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    _args: &[Value],
+) -> NativeRet {
+    vm.native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "java/lang/invoke/MethodHandleNatives",
+            "init",
+            "(Ljava/lang/invoke/MemberName;Ljava/lang/Object;)V",
+            &vm.string_interner,
+        ),
+        java_lang_invoke_method_handle_natives_init,
+    );
+    vm.native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "java/lang/invoke/MethodHandleNatives",
+            "expand",
+            "(Ljava/lang/invoke/MemberName;)V",
+            &vm.string_interner,
+        ),
+        java_lang_invoke_method_handle_natives_expand,
+    );
+    vm.native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "java/lang/invoke/MethodHandleNatives",
+            "resolve",
+            "(Ljava/lang/invoke/MemberName;Ljava/lang/Class;IZ)Ljava/lang/invoke/MemberName;",
+            &vm.string_interner,
+        ),
+        java_lang_invoke_method_handle_natives_resolve,
+    );
+    vm.native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "java/lang/invoke/MethodHandleNatives",
+            "objectFieldOffset",
+            "(Ljava/lang/invoke/MemberName;)J",
+            &vm.string_interner,
+        ),
+        java_lang_invoke_method_handle_natives_object_field_offset,
+    );
+    vm.native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "java/lang/invoke/MethodHandleNatives",
+            "staticFieldOffset",
+            "(Ljava/lang/invoke/MemberName;)J",
+            &vm.string_interner,
+        ),
+        java_lang_invoke_method_handle_natives_static_field_offset,
+    );
+    vm.native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "java/lang/invoke/MethodHandleNatives",
+            "staticFieldBase",
+            "(Ljava/lang/invoke/MemberName;)Ljava/lang/Object;",
+            &vm.string_interner,
+        ),
+        java_lang_invoke_method_handle_natives_static_field_base,
+    );
+    vm.native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "java/lang/invoke/MethodHandleNatives",
+            "getMemberVMInfo",
+            "(Ljava/lang/invoke/MemberName;)Ljava/lang/Object;",
+            &vm.string_interner,
+        ),
+        java_lang_invoke_method_handle_natives_get_member_vm_info,
+    );
+    vm.native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "java/lang/invoke/MethodHandleNatives",
+            "setCallSiteTargetNormal",
+            "(Ljava/lang/invoke/CallSite;Ljava/lang/invoke/MethodHandle;)V",
+            &vm.string_interner,
+        ),
+        java_lang_invoke_method_handle_natives_set_call_site_target,
+    );
+    vm.native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "java/lang/invoke/MethodHandleNatives",
+            "setCallSiteTargetVolatile",
+            "(Ljava/lang/invoke/CallSite;Ljava/lang/invoke/MethodHandle;)V",
+            &vm.string_interner,
+        ),
+        java_lang_invoke_method_handle_natives_set_call_site_target,
+    );
+    vm.native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "java/lang/invoke/MethodHandleNatives",
+            "clearCallSiteContext",
+            "(Ljava/lang/invoke/MethodHandleNatives$CallSiteContext;)V",
+            &vm.string_interner,
+        ),
+        java_lang_invoke_method_handle_natives_clear_call_site_context,
+    );
+    vm.native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "java/lang/invoke/MethodHandleNatives",
+            "getNamedCon",
+            "(I[Ljava/lang/Object;)I",
+            &vm.string_interner,
+        ),
+        java_lang_invoke_method_handle_natives_get_named_con,
+    );
+
+    Ok(None)
+}
+
+fn member_name_arg(args: &[Value]) -> Result<HeapRef, JvmError> {
+    match args[0].as_nullable_obj_ref()? {
+        Some(member_name) => Ok(member_name),
+        None => throw_exception!(NullPointerException),
+    }
+}
+
+/// The class of the `clazz` field of a `MemberName` or a `Field`.
+fn clazz_class_id(vm: &VirtualMachine, obj: HeapRef) -> Result<ClassId, JvmError> {
+    let Some(mirror_ref) = read_named_field(vm, obj, "clazz")?.as_nullable_obj_ref()? else {
+        throw_exception!(IllegalArgumentException, "nothing to resolve")?
+    };
+    vm.method_area().get_class_id_by_mirror(&mirror_ref)
+}
+
+fn mirror(vm: &VirtualMachine, class_id: ClassId) -> Result<Value, JvmError> {
+    Ok(Value::Ref(
+        vm.method_area()
+            .get_mirror_ref_or_create(class_id, &vm.heap)?,
+    ))
+}
+
+fn string_value(vm: &VirtualMachine, value: Value) -> Result<Option<String>, JvmError> {
+    value
+        .as_nullable_obj_ref()?
+        .map(|string_ref| vm.heap_read().get_rust_string_from_java_string(string_ref))
+        .transpose()
+}
+
+/// The field descriptor of a class: its name for arrays, a letter for primitive types.
+fn class_descriptor(vm: &VirtualMachine, class_id: ClassId) -> String {
+    let name = vm
+        .interner()
+        .resolve(&vm.method_area().get_class(&class_id).get_name())
+        .to_string();
+    match name.as_str() {
+        "boolean" => "Z".to_string(),
+        "byte" => "B".to_string(),
+        "char" => "C".to_string(),
+        "short" => "S".to_string(),
+        "int" => "I".to_string(),
+        "long" => "J".to_string(),
+        "float" => "F".to_string(),
+        "double" => "D".to_string(),
+        "void" => "V".to_string(),
+        _ if name.starts_with('[') => name,
+        _ => format!("L{name};"),
+    }
+}
+
+/// The descriptor the `type` of a `MemberName` stands for. It is a `MethodType` or a
+/// descriptor string for methods and a `Class` or a descriptor string for fields.
+fn member_type_descriptor(vm: &VirtualMachine, type_ref: HeapRef) -> Result<String, JvmError> {
+    let class_name = {
+        let class_id = vm.heap_read().get_class_id(type_ref)?;
+        vm.method_area().get_class(&class_id).get_name()
+    };
+    if class_name == vm.br().java_lang_string_sym {
+        return vm.heap_read().get_rust_string_from_java_string(type_ref);
+    }
+    if class_name == vm.br().java_lang_class_sym {
+        let class_id = vm.method_area().get_class_id_by_mirror(&type_ref)?;
+        return Ok(class_descriptor(vm, class_id));
+    }
+    let mirror_descriptor = |value: Value| -> Result<String, JvmError> {
+        let class_id = vm
+            .method_area()
+            .get_class_id_by_mirror(&value.as_obj_ref()?)?;
+        Ok(class_descriptor(vm, class_id))
+    };
+    let ptypes = read_named_field(vm, type_ref, "ptypes")?.as_obj_ref()?;
+    let mut desc = String::from("(");
+    for i in 0..vm.heap_read().get_array_length(ptypes)? {
+        let ptype = vm.heap_read().read_array_element(ptypes, i)?;
+        desc.push_str(&mirror_descriptor(ptype)?);
+    }
+    desc.push(')');
+    desc.push_str(&mirror_descriptor(read_named_field(
+        vm, type_ref, "rtype",
+    )?)?);
+    Ok(desc)
+}
+
+/// The reference kind of a method like hotspot links it: the requested one, except that
+/// constructors, static and private methods are always invoked directly and methods of
+/// `Object` aren't interface methods.
+fn method_ref_kind(vm: &VirtualMachine, method_id: MethodId, requested: i32) -> i32 {
+    let ma = vm.method_area();
+    let method = ma.get_method(&method_id);
+    if method.name == vm.br().init_sym {
+        REF_NEW_INVOKE_SPECIAL
+    } else if method.is_static() {
+        REF_INVOKE_STATIC
+    } else if requested == REF_INVOKE_SPECIAL || method.flags().is_private() {
+        REF_INVOKE_SPECIAL
+    } else if requested == REF_INVOKE_INTERFACE && !ma.get_class(&method.class_id()).is_interface()
+    {
+        REF_INVOKE_VIRTUAL
+    } else {
+        requested
+    }
+}
+
+/// Points `member_name` at a method: its declaring class, its flags and a
+/// `ResolvedMethodName` standing for the method.
+fn set_method(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    member_name: HeapRef,
+    method_id: MethodId,
+    ref_kind: i32,
+) -> Result<(), JvmError> {
+    let (class_id, mut flags) = {
+        let ma = vm.method_area();
+        let method = ma.get_method(&method_id);
+        (
+            method.class_id(),
+            *method.flags().get_raw() as i32 & METHOD_MODIFIERS,
+        )
+    };
+    flags |= ref_kind << REFERENCE_KIND_SHIFT;
+    flags |= if ref_kind == REF_NEW_INVOKE_SPECIAL {
+        IS_CONSTRUCTOR
+    } else {
+        IS_METHOD
+    };
+    if vm.method_area().is_caller_sensitive(&method_id) {
+        flags |= CALLER_SENSITIVE;
+    }
+
+    let resolved_method_class_id =
+        load_and_initialize(vm, thread, "java/lang/invoke/ResolvedMethodName")?;
+    let instance_size = vm
+        .method_area()
+        .get_instance_class(&resolved_method_class_id)?
+        .get_instance_size()?;
+    let resolved_method_name = vm
+        .heap_write()
+        .alloc_instance(instance_size, resolved_method_class_id)?;
+    vm.method_area()
+        .set_resolved_method(resolved_method_name, method_id);
+
+    write_named_field(vm, member_name, "clazz", mirror(vm, class_id)?)?;
+    write_named_field(vm, member_name, "flags", Value::Integer(flags))?;
+    write_named_field(vm, member_name, "method", Value::Ref(resolved_method_name))
+}
+
+/// Points `member_name` at a field declared by `class_id`. Like for `Field` final fields of
+/// records, hidden classes and static final fields are trusted.
+fn set_field(
+    vm: &VirtualMachine,
+    member_name: HeapRef,
+    class_id: ClassId,
+    field: &DeclaredField,
+    ref_kind: i32,
+) -> Result<(), JvmError> {
+    let mut flags = *field.flags.get_raw() as i32 & FIELD_MODIFIERS;
+    flags |= IS_FIELD | (ref_kind << REFERENCE_KIND_SHIFT);
+    let trusted = {
+        let ma = vm.method_area();
+        field.flags.is_static()
+            || ma.is_hidden(&class_id)
+            || ma
+                .get_class_like(&class_id)
+                .is_ok_and(|class| class.metadata().is_record)
+    };
+    if field.flags.is_final() && trusted {
+        flags |= TRUSTED_FINAL;
+    }
+    write_named_field(vm, member_name, "clazz", mirror(vm, class_id)?)?;
+    write_named_field(vm, member_name, "flags", Value::Integer(flags))
+}
+
+/// The field `key` declared by `class_id`.
+fn declared_field(
+    vm: &VirtualMachine,
+    class_id: ClassId,
+    key: &FieldKey,
+) -> Result<(usize, DeclaredField), JvmError> {
+    vm.method_area()
+        .get_class_like(&class_id)?
+        .declared_fields()?
+        .iter()
+        .enumerate()
+        .find(|(_, field)| field.key == *key)
+        .map(|(slot, field)| (slot, field.clone()))
+        .ok_or(JvmError::NoSuchFieldError(
+            vm.interner().resolve(&key.name).to_string(),
+        ))
+}
+
+/// `MemberName(Method)`, `MemberName(Constructor)` and `MemberName(Field)`: points the
+/// member name at the member of a reflection object.
+fn java_lang_invoke_method_handle_natives_init(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let member_name = member_name_arg(args)?;
+    let Some(reflected) = args[1].as_nullable_obj_ref()? else {
+        throw_exception!(NullPointerException)?
+    };
+    let reflected_class_name = {
+        let class_id = vm.heap_read().get_class_id(reflected)?;
+        vm.interner()
+            .resolve(&vm.method_area().get_class(&class_id).get_name())
+            .to_string()
+    };
+    match reflected_class_name.as_str() {
+        "java/lang/reflect/Method" | "java/lang/reflect/Constructor" => {
+            let method_id = MethodId::from_i32(read_named_field(vm, reflected, "slot")?.as_int()?);
+            let declared_by_interface = {
+                let ma = vm.method_area();
+                ma.get_class(&ma.get_method(&method_id).class_id())
+                    .is_interface()
+            };
+            let requested = if declared_by_interface {
+                REF_INVOKE_INTERFACE
+            } else {
+                REF_INVOKE_VIRTUAL
+            };
+            let ref_kind = method_ref_kind(vm, method_id, requested);
+            set_method(vm, thread, member_name, method_id, ref_kind)?;
+        }
+        "java/lang/reflect/Field" => {
+            let class_id = clazz_class_id(vm, reflected)?;
+            let slot = read_named_field(vm, reflected, "slot")?.as_int()? as usize;
+            let field = vm
+                .method_area()
+                .get_class_like(&class_id)?
+                .declared_fields()?
+                .get(slot)
+                .cloned()
+                .ok_or(build_exception!(InternalError, "Field slot out of range"))?;
+            let ref_kind = if field.flags.is_static() {
+                REF_GET_STATIC
+            } else {
+                REF_GET_FIELD
+            };
+            set_field(vm, member_name, class_id, &field, ref_kind)?;
+            write_named_field(
+                vm,
+                member_name,
+                "name",
+                read_named_field(vm, reflected, "name")?,
+            )?;
+            write_named_field(
+                vm,
+                member_name,
+                "type",
+                read_named_field(vm, reflected, "type")?,
+            )?;
+        }
+        _ => throw_exception!(
+            InternalError,
+            "unrecognized member {}",
+            reflected_class_name
+        )?,
+    }
+    Ok(None)
+}
+
+/// Fills in the name and type of a member name that only knows its method.
+fn java_lang_invoke_method_handle_natives_expand(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let member_name = member_name_arg(args)?;
+    if read_named_field(vm, member_name, "method")?
+        .as_nullable_obj_ref()?
+        .is_none()
+    {
+        // fields are always created with their name and type
+        return Ok(None);
+    }
+    let method_id = Interpreter::member_name_method_id(vm, member_name)?;
+    let (name, desc) = {
+        let method = vm.method_area().get_method(&method_id);
+        (method.name, method.desc)
+    };
+    if read_named_field(vm, member_name, "name")?
+        .as_nullable_obj_ref()?
+        .is_none()
+    {
+        let name_ref = vm.heap_write().get_str_from_pool_or_new(name)?;
+        write_named_field(vm, member_name, "name", Value::Ref(name_ref))?;
+    }
+    if read_named_field(vm, member_name, "type")?
+        .as_nullable_obj_ref()?
+        .is_none()
+    {
+        let desc_ref = vm.heap_write().get_str_from_pool_or_new(desc)?;
+        write_named_field(vm, member_name, "type", Value::Ref(desc_ref))?;
+    }
+    Ok(None)
+}
+
+/// Links a member name to the method or field its class, name, type and reference kind
+/// denote. Access is checked by the caller in Java. When nothing is found it's `null` if
+/// `speculativeResolve`, a `NoSuchMethodError` or `NoSuchFieldError` otherwise.
+fn java_lang_invoke_method_handle_natives_resolve(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let member_name = member_name_arg(args)?;
+    let speculative = args[3].as_int()? != 0;
+    if read_named_field(vm, member_name, "method")?
+        .as_nullable_obj_ref()?
+        .is_some()
+    {
+        return Ok(Some(Value::Ref(member_name)));
+    }
+    let flags = read_named_field(vm, member_name, "flags")?.as_int()?;
+    let ref_kind = (flags >> REFERENCE_KIND_SHIFT) & REFERENCE_KIND_MASK;
+    let defc = clazz_class_id(vm, member_name)?;
+    let Some(name) = string_value(vm, read_named_field(vm, member_name, "name")?)? else {
+        throw_exception!(IllegalArgumentException, "nothing to resolve")?
+    };
+    let Some(type_ref) = read_named_field(vm, member_name, "type")?.as_nullable_obj_ref()? else {
+        throw_exception!(IllegalArgumentException, "nothing to resolve")?
+    };
+    let desc = member_type_descriptor(vm, type_ref)?;
+    let name_sym = vm.interner().get_or_intern(&name);
+    let desc_sym = vm.interner().get_or_intern(&desc);
+
+    if flags & (IS_METHOD | IS_CONSTRUCTOR) != 0 {
+        let key = MethodKey {
+            name: name_sym,
+            desc: desc_sym,
+        };
+        let method_id = {
+            let ma = vm.method_area();
+            if flags & IS_CONSTRUCTOR != 0 {
+                ma.get_instance_class(&defc)
+                    .ok()
+                    .and_then(|class| class.get_special_method_id(&key).ok())
+            } else {
+                ma.resolve_method(&defc, &key)
+                    .or_else(|| ma.find_signature_polymorphic_method(&defc, name_sym))
+            }
+        };
+        let Some(method_id) = method_id else {
+            if speculative {
+                return Ok(Some(Value::Null));
+            }
+            let class_name = vm.method_area().get_class(&defc).get_name();
+            throw_exception!(
+                NoSuchMethodError,
+                "{}.{}{}",
+                vm.interner().resolve(&class_name),
+                name,
+                desc
+            )?
+        };
+        let ref_kind = method_ref_kind(vm, method_id, ref_kind);
+        set_method(vm, thread, member_name, method_id, ref_kind)?;
+    } else if flags & IS_FIELD != 0 {
+        let key = FieldKey {
+            name: name_sym,
+            desc: desc_sym,
+        };
+        let is_static = ref_kind == REF_GET_STATIC || ref_kind == REF_PUT_STATIC;
+        let declaring_class_id = {
+            let ma = vm.method_area();
+            if is_static {
+                ma.resolve_static_field_actual_class_id(defc, &key).ok()
+            } else {
+                ma.get_instance_field(&defc, &key)
+                    .ok()
+                    .map(|field| field.declaring_class)
+            }
+        };
+        let Some((declaring_class_id, field)) = declaring_class_id
+            .and_then(|class_id| Some((class_id, declared_field(vm, class_id, &key).ok()?.1)))
+        else {
+            if speculative {
+                return Ok(Some(Value::Null));
+            }
+            let class_name = vm.method_area().get_class(&defc).get_name();
+            throw_exception!(
+                NoSuchFieldError,
+                "{}.{}",
+                vm.interner().resolve(&class_name),
+                name
+            )?
+        };
+        set_field(vm, member_name, declaring_class_id, &field, ref_kind)?;
+    } else {
+        throw_exception!(InternalError, "unrecognized MemberName format")?
+    }
+    Ok(Some(Value::Ref(member_name)))
+}
+
+/// The field a resolved field member name stands for, and the class declaring it.
+fn member_name_field(
+    vm: &VirtualMachine,
+    member_name: HeapRef,
+) -> Result<(ClassId, FieldKey), JvmError> {
+    let class_id = clazz_class_id(vm, member_name)?;
+    let Some(name) = string_value(vm, read_named_field(vm, member_name, "name")?)? else {
+        throw_exception!(InternalError, "MemberName is not a field")?
+    };
+    let Some(type_ref) = read_named_field(vm, member_name, "type")?.as_nullable_obj_ref()? else {
+        throw_exception!(InternalError, "MemberName is not a field")?
+    };
+    let desc = member_type_descriptor(vm, type_ref)?;
+    Ok((
+        class_id,
+        FieldKey {
+            name: vm.interner().get_or_intern(&name),
+            desc: vm.interner().get_or_intern(&desc),
+        },
+    ))
+}
+
+/// Instance field offsets are the heap offsets `Unsafe` takes.
+fn java_lang_invoke_method_handle_natives_object_field_offset(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let (class_id, key) = member_name_field(vm, member_name_arg(args)?)?;
+    let offset = vm.method_area().get_instance_field(&class_id, &key)?.offset;
+    Ok(Some(Value::Long(offset as i64)))
+}
+
+/// Static fields have no offsets, the one `Unsafe` takes is derived from the index among the
+/// declared fields, with the mirror as the base.
+fn java_lang_invoke_method_handle_natives_static_field_offset(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let (class_id, key) = member_name_field(vm, member_name_arg(args)?)?;
+    let (slot, _) = declared_field(vm, class_id, &key)?;
+    Ok(Some(Value::Long(STATIC_FIELD_OFFSET_BASE + slot as i64)))
+}
+
+fn java_lang_invoke_method_handle_natives_static_field_base(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let class_id = clazz_class_id(vm, member_name_arg(args)?)?;
+    Ok(Some(mirror(vm, class_id)?))
+}
+
+/// `{vmindex, vmtarget}`: the offset and the declaring class of a field, the slot of a method
+/// and the member name itself.
+fn java_lang_invoke_method_handle_natives_get_member_vm_info(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let member_name = member_name_arg(args)?;
+    let flags = read_named_field(vm, member_name, "flags")?.as_int()?;
+    let (vmindex, vmtarget) = if flags & IS_FIELD != 0 {
+        let (class_id, key) = member_name_field(vm, member_name)?;
+        let vmindex = if vm
+            .method_area()
+            .get_class_like(&class_id)?
+            .declared_fields()?
+            .iter()
+            .any(|field| field.key == key && field.flags.is_static())
+        {
+            STATIC_FIELD_OFFSET_BASE + declared_field(vm, class_id, &key)?.0 as i64
+        } else {
+            vm.method_area().get_instance_field(&class_id, &key)?.offset as i64
+        };
+        (vmindex, mirror(vm, class_id)?)
+    } else {
+        let method_id = Interpreter::member_name_method_id(vm, member_name)?;
+        (method_id.to_i32() as i64, Value::Ref(member_name))
+    };
+    let vmindex = box_value(vm, thread, PrimitiveType::Long, Value::Long(vmindex))?;
+    let array_class_id = vm.method_area().get_class_id_or_load(
+        vm.interner().get_or_intern("[Ljava/lang/Object;"),
+        thread.id,
+    )?;
+    let mut heap = vm.heap_write();
+    let info = heap.alloc_object_array(array_class_id, 2)?;
+    heap.write_array_element(info, 0, Value::Ref(vmindex))?;
+    heap.write_array_element(info, 1, vmtarget)?;
+    Ok(Some(Value::Ref(info)))
+}
+
+/// Nothing depends on call site targets, so there is nothing to deoptimize.
+fn java_lang_invoke_method_handle_natives_set_call_site_target(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let Some(call_site) = args[0].as_nullable_obj_ref()? else {
+        throw_exception!(NullPointerException)?
+    };
+    write_named_field(vm, call_site, "target", args[1])?;
+    Ok(None)
+}
+
+fn java_lang_invoke_method_handle_natives_clear_call_site_context(
+    _vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    _args: &[Value],
+) -> NativeRet {
+    Ok(None)
+}
+
+/// The constants `MethodHandleNatives` verifies against the VM with assertions enabled, there
+/// are none to check.
+fn java_lang_invoke_method_handle_natives_get_named_con(
+    _vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    _args: &[Value],
+) -> NativeRet {
+    Ok(Some(Value::Integer(0)))
+}
@@ -78,9 +78,9 @@ fn java_lang_system_set_out_0(
         _ => panic!("java.lang.System.setOut0: expected PrintStream object"),
     };
     let system_class_id = vm
-        .method_area_write()
+        .method_area()
         .get_class_id_or_load(vm.br().java_lang_system_sym, thread.id)?;
-    vm.method_area_read()
+    vm.method_area()
         .get_class_like(&system_class_id)?
         .set_static_field_value(&vm.br().system_out_fk, Value::Ref(val))?;
     Ok(None)
@@ -97,9 +97,9 @@ fn java_lang_system_set_err_0(
         _ => panic!("java.lang.System.setOut0: expected PrintStream object"),
     };
     let system_class_id = vm
-        .method_area_write()
+        .method_area()
         .get_class_id_or_load(vm.br().java_lang_system_sym, thread.id)?;
-    vm.method_area_read()
+    vm.method_area()
         .get_class_like(&system_class_id)?
         .set_static_field_value(&vm.br().system_err_fk, Value::Ref(val))?;
     Ok(None)
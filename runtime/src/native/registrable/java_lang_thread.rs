@@ -1,6 +1,6 @@
 use crate::keys::FullyQualifiedMethodKey;
 use crate::native::NativeRet;
-use crate::thread::JavaThreadState;
+use crate::thread::{JavaThreadState, lifecycle};
 use crate::vm::Value;
 use crate::{ThreadId, VirtualMachine};
 
@@ -18,6 +18,25 @@ pub(super) fn java_lang_thread_register_natives(
         ),
         java_lang_thread_current_thread,
     );
+    vm.native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "java/lang/Thread",
+            "start0",
+            "()V",
+            &vm.string_interner,
+        ),
+        java_lang_thread_start_0,
+    );
+    Ok(None)
+}
+
+fn java_lang_thread_start_0(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let thread_obj = args[0].as_obj_ref()?;
+    lifecycle::start_thread(vm, thread_obj)?;
     Ok(None)
 }
 
@@ -1,12 +1,18 @@
 use crate::error::JvmError;
-use crate::heap::Heap;
+use crate::heap::{Heap, HeapRef};
 use crate::interpreter::Interpreter;
-use crate::keys::FullyQualifiedMethodKey;
-use crate::native::NativeRet;
+use crate::keys::{ClassId, FieldKey, FullyQualifiedMethodKey};
+use crate::native::reflection::read_named_field;
+use crate::native::{NativeFn, NativeRet};
+use crate::rt::ClassLike;
+use crate::rt::field::DeclaredField;
 use crate::thread::JavaThreadState;
 use crate::vm::Value;
-use crate::{ThreadId, VirtualMachine};
+use crate::vm::signal;
+use crate::{ThreadId, VirtualMachine, throw_exception};
 use common::jtype::AllocationType;
+use std::sync::atomic::{Ordering, fence};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tracing_log::log::debug;
 
 pub(super) fn jdk_internal_misc_unsafe_register_natives(
@@ -46,107 +52,277 @@ pub(super) fn jdk_internal_misc_unsafe_register_natives(
     vm.native_registry.register(
         FullyQualifiedMethodKey::new_with_str(
             "jdk/internal/misc/Unsafe",
-            "fullFence",
-            "()V",
+            "ensureClassInitialized0",
+            "(Ljava/lang/Class;)V",
             &vm.string_interner,
         ),
-        jdk_internal_misc_unsafe_full_fence,
+        jdk_internal_misc_unsafe_ensure_class_initialized_0,
     );
     vm.native_registry.register(
         FullyQualifiedMethodKey::new_with_str(
             "jdk/internal/misc/Unsafe",
-            "compareAndSetInt",
-            "(Ljava/lang/Object;JII)Z",
+            "shouldBeInitialized0",
+            "(Ljava/lang/Class;)Z",
             &vm.string_interner,
         ),
-        jdk_internal_misc_unsafe_compare_and_set_int,
+        jdk_internal_misc_unsafe_should_be_initialized_0,
     );
     vm.native_registry.register(
         FullyQualifiedMethodKey::new_with_str(
             "jdk/internal/misc/Unsafe",
-            "getReferenceVolatile",
-            "(Ljava/lang/Object;J)Ljava/lang/Object;",
+            "allocateInstance",
+            "(Ljava/lang/Class;)Ljava/lang/Object;",
             &vm.string_interner,
         ),
-        jdk_internal_misc_unsafe_get_reference_volatile,
+        jdk_internal_misc_unsafe_allocate_instance,
     );
     vm.native_registry.register(
         FullyQualifiedMethodKey::new_with_str(
-            "jdk/internal/misc/Unsafe",
-            "compareAndSetReference",
-            "(Ljava/lang/Object;JLjava/lang/Object;Ljava/lang/Object;)Z",
+            "jdk/internal/loader/BootLoader",
+            "setBootLoaderUnnamedModule0",
+            "(Ljava/lang/Module;)V",
             &vm.string_interner,
         ),
-        jdk_internal_misc_unsafe_compare_and_set_reference,
+        jdk_internal_loader_boot_loader_set_boot_loader_unnamed_module_0,
     );
+
     vm.native_registry.register(
         FullyQualifiedMethodKey::new_with_str(
             "jdk/internal/misc/Unsafe",
-            "compareAndSetLong",
-            "(Ljava/lang/Object;JJJ)Z",
+            "park",
+            "(ZJ)V",
             &vm.string_interner,
         ),
-        jdk_internal_misc_unsafe_compare_and_set_long,
+        jdk_internal_misc_unsafe_park,
     );
     vm.native_registry.register(
         FullyQualifiedMethodKey::new_with_str(
             "jdk/internal/misc/Unsafe",
-            "putByte",
-            "(Ljava/lang/Object;JB)V",
+            "unpark",
+            "(Ljava/lang/Object;)V",
             &vm.string_interner,
         ),
-        jdk_internal_misc_unsafe_put_byte,
+        jdk_internal_misc_unsafe_unpark,
     );
     vm.native_registry.register(
         FullyQualifiedMethodKey::new_with_str(
             "jdk/internal/misc/Unsafe",
-            "ensureClassInitialized0",
-            "(Ljava/lang/Class;)V",
+            "objectFieldOffset0",
+            "(Ljava/lang/reflect/Field;)J",
             &vm.string_interner,
         ),
-        jdk_internal_misc_unsafe_ensure_class_initialized_0,
+        jdk_internal_misc_unsafe_object_field_offset_0,
     );
     vm.native_registry.register(
         FullyQualifiedMethodKey::new_with_str(
             "jdk/internal/misc/Unsafe",
-            "getIntVolatile",
-            "(Ljava/lang/Object;J)I",
-            &vm.string_interner,
-        ),
-        jdk_internal_misc_unsafe_get_int_volatile,
-    );
-    vm.native_registry.register(
-        FullyQualifiedMethodKey::new_with_str(
-            "jdk/internal/loader/BootLoader",
-            "setBootLoaderUnnamedModule0",
-            "(Ljava/lang/Module;)V",
+            "staticFieldOffset0",
+            "(Ljava/lang/reflect/Field;)J",
             &vm.string_interner,
         ),
-        jdk_internal_loader_boot_loader_set_boot_loader_unnamed_module_0,
+        jdk_internal_misc_unsafe_static_field_offset_0,
     );
     vm.native_registry.register(
         FullyQualifiedMethodKey::new_with_str(
             "jdk/internal/misc/Unsafe",
-            "getLong",
-            "(Ljava/lang/Object;J)J",
+            "staticFieldBase0",
+            "(Ljava/lang/reflect/Field;)Ljava/lang/Object;",
             &vm.string_interner,
         ),
-        jdk_internal_misc_unsafe_get_long,
+        jdk_internal_misc_unsafe_static_field_base_0,
     );
 
-    vm.native_registry.register(
-        FullyQualifiedMethodKey::new_with_str(
-            "jdk/internal/misc/Unsafe",
-            "getInt",
-            "(Ljava/lang/Object;J)I",
-            &vm.string_interner,
+    // The volatile accessors share the plain ones, every access goes through a heap or
+    // static field lock.
+    let accessors: [(&str, &str, NativeFn, NativeFn); 9] = [
+        (
+            "Boolean",
+            "Z",
+            jdk_internal_misc_unsafe_get_boolean,
+            jdk_internal_misc_unsafe_put_boolean,
         ),
-        jdk_internal_misc_unsafe_get_int,
-    );
+        (
+            "Byte",
+            "B",
+            jdk_internal_misc_unsafe_get_byte,
+            jdk_internal_misc_unsafe_put_byte,
+        ),
+        (
+            "Short",
+            "S",
+            jdk_internal_misc_unsafe_get_short,
+            jdk_internal_misc_unsafe_put_short,
+        ),
+        (
+            "Char",
+            "C",
+            jdk_internal_misc_unsafe_get_char,
+            jdk_internal_misc_unsafe_put_char,
+        ),
+        (
+            "Int",
+            "I",
+            jdk_internal_misc_unsafe_get_int,
+            jdk_internal_misc_unsafe_put_int,
+        ),
+        (
+            "Long",
+            "J",
+            jdk_internal_misc_unsafe_get_long,
+            jdk_internal_misc_unsafe_put_long,
+        ),
+        (
+            "Float",
+            "F",
+            jdk_internal_misc_unsafe_get_float,
+            jdk_internal_misc_unsafe_put_float,
+        ),
+        (
+            "Double",
+            "D",
+            jdk_internal_misc_unsafe_get_double,
+            jdk_internal_misc_unsafe_put_double,
+        ),
+        (
+            "Reference",
+            "Ljava/lang/Object;",
+            jdk_internal_misc_unsafe_get_reference,
+            jdk_internal_misc_unsafe_put_reference,
+        ),
+    ];
+    for (type_name, desc, get, put) in accessors {
+        for suffix in ["", "Volatile"] {
+            vm.native_registry.register(
+                FullyQualifiedMethodKey::new_with_str(
+                    "jdk/internal/misc/Unsafe",
+                    &format!("get{type_name}{suffix}"),
+                    &format!("(Ljava/lang/Object;J){desc}"),
+                    &vm.string_interner,
+                ),
+                get,
+            );
+            vm.native_registry.register(
+                FullyQualifiedMethodKey::new_with_str(
+                    "jdk/internal/misc/Unsafe",
+                    &format!("put{type_name}{suffix}"),
+                    &format!("(Ljava/lang/Object;J{desc})V"),
+                    &vm.string_interner,
+                ),
+                put,
+            );
+        }
+    }
+    let atomics: [(&str, &str, NativeFn, NativeFn); 3] = [
+        (
+            "Int",
+            "I",
+            jdk_internal_misc_unsafe_compare_and_set_int,
+            jdk_internal_misc_unsafe_compare_and_exchange_int,
+        ),
+        (
+            "Long",
+            "J",
+            jdk_internal_misc_unsafe_compare_and_set_long,
+            jdk_internal_misc_unsafe_compare_and_exchange_long,
+        ),
+        (
+            "Reference",
+            "Ljava/lang/Object;",
+            jdk_internal_misc_unsafe_compare_and_set_reference,
+            jdk_internal_misc_unsafe_compare_and_exchange_reference,
+        ),
+    ];
+    for (type_name, desc, compare_and_set, compare_and_exchange) in atomics {
+        vm.native_registry.register(
+            FullyQualifiedMethodKey::new_with_str(
+                "jdk/internal/misc/Unsafe",
+                &format!("compareAndSet{type_name}"),
+                &format!("(Ljava/lang/Object;J{desc}{desc})Z"),
+                &vm.string_interner,
+            ),
+            compare_and_set,
+        );
+        vm.native_registry.register(
+            FullyQualifiedMethodKey::new_with_str(
+                "jdk/internal/misc/Unsafe",
+                &format!("compareAndExchange{type_name}"),
+                &format!("(Ljava/lang/Object;J{desc}{desc}){desc}"),
+                &vm.string_interner,
+            ),
+            compare_and_exchange,
+        );
+    }
+    for name in ["fullFence", "loadFence", "storeFence"] {
+        vm.native_registry.register(
+            FullyQualifiedMethodKey::new_with_str(
+                "jdk/internal/misc/Unsafe",
+                name,
+                "()V",
+                &vm.string_interner,
+            ),
+            jdk_internal_misc_unsafe_fence,
+        );
+    }
 
     Ok(None)
 }
 
+fn jdk_internal_misc_unsafe_park(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let is_absolute = args[1].as_int()? != 0;
+    let time = args[2].as_long()?;
+    let deadline = match (is_absolute, time) {
+        (_, t) if t < 0 => return Ok(None),
+        (true, 0) => return Ok(None),
+        (false, 0) => None,
+        (true, millis_since_epoch) => {
+            let now_millis = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as i64;
+            Some(
+                Instant::now()
+                    + Duration::from_millis((millis_since_epoch - now_millis).max(0) as u64),
+            )
+        }
+        (false, nanos) => Some(Instant::now() + Duration::from_nanos(nanos as u64)),
+    };
+    let blocker = {
+        let thread_class_id = vm.heap_read().get_class_id(thread.thread_obj)?;
+        let offset = vm
+            .method_area()
+            .get_instance_field(&thread_class_id, &vm.br().thread_park_blocker_fk)?
+            .offset;
+        vm.heap_read()
+            .read_field(thread.thread_obj, offset, AllocationType::Reference)?
+            .as_nullable_obj_ref()?
+    };
+    let thread_id = thread.id;
+    vm.parkers.park(thread_id, blocker, deadline, || {
+        if signal::poll_requested() {
+            vm.safepoint_poll(thread)?;
+        }
+        Ok(())
+    })?;
+    Ok(None)
+}
+
+fn jdk_internal_misc_unsafe_unpark(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    if let Some(thread_obj) = args[1].as_nullable_obj_ref()?
+        && let Some(target) = vm.threads.find_by_thread_obj(thread_obj)
+    {
+        vm.parkers.unpark(target.id);
+    }
+    Ok(None)
+}
+
 fn jdk_internal_loader_boot_loader_set_boot_loader_unnamed_module_0(
     _vm: &VirtualMachine,
     _thread: &mut JavaThreadState,
@@ -165,52 +341,53 @@ fn jdk_internal_misc_unsafe_ensure_class_initialized_0(
     args: &[Value],
 ) -> NativeRet {
     let mirror_ref = args[1].as_obj_ref()?;
-    let class_id = vm.method_area_read().get_class_id_by_mirror(&mirror_ref)?;
+    let class_id = vm.method_area().get_class_id_by_mirror(&mirror_ref)?;
     Interpreter::ensure_initialized(thread, Some(class_id), vm)?;
     Ok(None)
 }
 
-fn jdk_internal_misc_unsafe_get_int_volatile(
+/// Primitive and array classes are always initialized.
+fn jdk_internal_misc_unsafe_should_be_initialized_0(
     vm: &VirtualMachine,
     _thread: &mut JavaThreadState,
     args: &[Value],
 ) -> NativeRet {
-    debug!("TODO: Stub: jdk.internal.misc.Unsafe.getIntVolatile");
-    let base = args[1].as_obj_ref()?;
-    let off = args[2].as_long()?;
-    let value = vm
-        .heap_read()
-        .read_field(base, off as usize, AllocationType::Int)?
-        .as_int()?;
-    Ok(Some(Value::Integer(value)))
-}
-
-fn jdk_internal_misc_unsafe_get_long(
-    vm: &VirtualMachine,
-    _thread: &mut JavaThreadState,
-    args: &[Value],
-) -> NativeRet {
-    let base = args[1].as_obj_ref()?;
-    let off = args[2].as_long()?;
-    let value = vm
-        .heap_read()
-        .read_field(base, off as usize, AllocationType::Long)?
-        .as_long()?;
-    Ok(Some(Value::Long(value)))
+    let mirror_ref = args[1].as_obj_ref()?;
+    let class_id = vm.method_area().get_class_id_by_mirror(&mirror_ref)?;
+    let should_be_initialized = vm
+        .method_area()
+        .get_class_like(&class_id)
+        .is_ok_and(|class| !class.is_initialized());
+    Ok(Some(Value::Integer(should_be_initialized as i32)))
 }
 
-fn jdk_internal_misc_unsafe_get_int(
+/// Allocates an instance of a class without running a constructor, the class is initialized
+/// first.
+fn jdk_internal_misc_unsafe_allocate_instance(
     vm: &VirtualMachine,
-    _thread: &mut JavaThreadState,
+    thread: &mut JavaThreadState,
     args: &[Value],
 ) -> NativeRet {
-    let base = args[1].as_obj_ref()?;
-    let off = args[2].as_long()?;
-    let value = vm
-        .heap_read()
-        .read_field(base, off as usize, AllocationType::Int)?
-        .as_int()?;
-    Ok(Some(Value::Integer(value)))
+    let Some(mirror_ref) = args[1].as_nullable_obj_ref()? else {
+        throw_exception!(NullPointerException)?
+    };
+    let class_id = vm.method_area().get_class_id_by_mirror(&mirror_ref)?;
+    let instance_size = {
+        let ma = vm.method_area();
+        let class = ma.get_class(&class_id);
+        match ma.get_instance_class(&class_id) {
+            Ok(instance_class) if !instance_class.flags().is_abstract() => {
+                instance_class.get_instance_size()?
+            }
+            _ => throw_exception!(
+                InstantiationException,
+                vm.symbol_to_pretty_string(class.get_name())
+            )?,
+        }
+    };
+    Interpreter::ensure_initialized(thread, Some(class_id), vm)?;
+    let instance_ref = vm.heap_write().alloc_instance(instance_size, class_id)?;
+    Ok(Some(Value::Ref(instance_ref)))
 }
 
 fn jdk_internal_misc_unsafe_array_base_offset_0(
@@ -222,109 +399,6 @@ fn jdk_internal_misc_unsafe_array_base_offset_0(
     Ok(Some(Value::Integer(Heap::ARRAY_ELEMENTS_OFFSET as i32)))
 }
 
-fn jdk_internal_misc_unsafe_compare_and_set_int(
-    vm: &VirtualMachine,
-    _thread: &mut JavaThreadState,
-    args: &[Value],
-) -> NativeRet {
-    let object = match args[1] {
-        Value::Ref(h) => h,
-        _ => panic!("compareAndSetInt: expected object"),
-    };
-    let offset = match args[2] {
-        Value::Long(l) if l >= 0 => l as usize,
-        _ => panic!("compareAndSetInt: expected non-negative offset"),
-    };
-    let expected = match args[3] {
-        Value::Integer(i) => i,
-        _ => panic!("compareAndSetInt: expected int expected"),
-    };
-    let new_value = match args[4] {
-        Value::Integer(i) => i,
-        _ => panic!("compareAndSetInt: expected int new_value"),
-    };
-
-    let current = vm
-        .heap_read()
-        .read_field(object, offset, AllocationType::Int)?;
-    if current == Value::Integer(expected) {
-        vm.heap_write().write_field(
-            object,
-            offset,
-            Value::Integer(new_value),
-            AllocationType::Int,
-        )?;
-        Ok(Some(Value::Integer(1)))
-    } else {
-        Ok(Some(Value::Integer(0)))
-    }
-}
-
-fn jdk_internal_misc_unsafe_compare_and_set_long(
-    vm: &VirtualMachine,
-    _thread: &mut JavaThreadState,
-    args: &[Value],
-) -> NativeRet {
-    debug!("TODO: Stub: jdk.internal.misc.Unsafe.compareAndSetLong");
-    let object = match args[1] {
-        Value::Ref(h) => h,
-        _ => panic!("jdk.internal.misc.Unsafe.compareAndSetLong: expected object"),
-    };
-    let offset = match args[2] {
-        Value::Long(l) if l >= 0 => l as usize,
-        _ => panic!("jdk.internal.misc.Unsafe.compareAndSetLong: expected non-negative offset"),
-    };
-    let expected = match args[3] {
-        Value::Long(l) => l,
-        _ => panic!("jdk.internal.misc.Unsafe.compareAndSetLong: expected long expected value"),
-    };
-    let new_value = match args[4] {
-        Value::Long(l) => l,
-        _ => panic!("jdk.internal.misc.Unsafe.compareAndSetLong: expected long new value"),
-    };
-    let object_field_value = vm
-        .heap_read()
-        .read_field(object, offset, AllocationType::Long)?;
-    if let Value::Long(current_value) = object_field_value {
-        if current_value == expected {
-            vm.heap_write().write_field(
-                object,
-                offset,
-                Value::Long(new_value),
-                AllocationType::Long,
-            )?;
-            Ok(Some(Value::Integer(1)))
-        } else {
-            Ok(Some(Value::Integer(0)))
-        }
-    } else {
-        panic!("jdk.internal.misc.Unsafe.compareAndSetLong: field at offset is not long");
-    }
-}
-
-fn jdk_internal_misc_unsafe_get_reference_volatile(
-    vm: &VirtualMachine,
-    _thread: &mut JavaThreadState,
-    args: &[Value],
-) -> NativeRet {
-    debug!("TODO: Stub: jdk.internal.misc.Unsafe.getReferenceVolatile");
-    let base = match &args[1] {
-        Value::Ref(h) => *h,
-        Value::Null => panic!("Unsafe.getReferenceVolatile base is null"),
-        _ => panic!("Unsafe.getReferenceVolatile expects an object base"),
-    };
-
-    let off = match args[2] {
-        Value::Long(x) => x,
-        _ => panic!("Unsafe.getReferenceVolatile expects a long offset"),
-    };
-    Ok(Some(vm.heap_read().read_field(
-        base,
-        off as usize,
-        AllocationType::Reference,
-    )?))
-}
-
 fn jdk_internal_misc_unsafe_object_field_offset_1(
     vm: &VirtualMachine,
     _thread: &mut JavaThreadState,
@@ -340,9 +414,9 @@ fn jdk_internal_misc_unsafe_object_field_offset_1(
         _ => panic!("jdk.internal.misc.Unsafe.objectFieldOffset: expected field name string"),
     };
     let interned_field_name = vm.interner().get_or_intern(&field_name);
-    let class_id = vm.method_area_read().get_class_id_by_mirror(class_addr)?;
+    let class_id = vm.method_area().get_class_id_by_mirror(class_addr)?;
     let offset = {
-        let ma = vm.method_area_read();
+        let ma = vm.method_area();
         ma.get_instance_class(&class_id)?
             .get_instance_field_by_name(&interned_field_name)?
             .offset
@@ -360,8 +434,8 @@ fn jdk_internal_misc_unsafe_array_index_scale_0(
         Value::Ref(h) => h,
         _ => panic!("arrayIndexScale0: expected class"),
     };
-    let class_id = vm.method_area_read().get_class_id_by_mirror(class_addr)?;
-    let class_name_sym = vm.method_area_read().get_class(&class_id).get_name();
+    let class_id = vm.method_area().get_class_id_by_mirror(class_addr)?;
+    let class_name_sym = vm.method_area().get_class(&class_id).get_name();
     let class_name = vm.interner().resolve(&class_name_sym);
 
     // Parse the class name to get element type
@@ -375,57 +449,228 @@ fn jdk_internal_misc_unsafe_array_index_scale_0(
         "[S" => 2,
         "[F" => 4,
         "[D" => 8,
-        s if s.starts_with("[L") => 8,
+        s if s.starts_with("[L") || s.starts_with("[[") => 8,
         _ => panic!("Unknown array type: {}", class_name),
     };
     Ok(Some(Value::Integer(scale)))
 }
 
-fn jdk_internal_misc_unsafe_full_fence(
-    _vm: &VirtualMachine,
+/// `Unsafe` offsets from this one up address static fields: the declared index of the field is
+/// added to it and the mirror of the declaring class is the base.
+pub(super) const STATIC_FIELD_OFFSET_BASE: i64 = 1 << 32;
+
+/// Memory an `Unsafe` access with a base object and an offset refers to.
+enum Location {
+    Heap(HeapRef, usize),
+    Static(ClassId, FieldKey),
+}
+
+impl Location {
+    fn new(vm: &VirtualMachine, base: Value, offset: Value) -> Result<Self, JvmError> {
+        let Some(base) = base.as_nullable_obj_ref()? else {
+            return Err(JvmError::Todo(
+                "Unsafe access to off-heap memory".to_string(),
+            ));
+        };
+        let offset = offset.as_long()?;
+        if offset < STATIC_FIELD_OFFSET_BASE {
+            return Ok(Location::Heap(base, offset as usize));
+        }
+        let ma = vm.method_area();
+        let class_id = ma.get_class_id_by_mirror(&base)?;
+        let key = ma
+            .get_class_like(&class_id)?
+            .declared_fields()?
+            .get((offset - STATIC_FIELD_OFFSET_BASE) as usize)
+            .map(|field| field.key)
+            .ok_or_else(|| JvmError::Todo(format!("No static field at offset {offset}")))?;
+        Ok(Location::Static(class_id, key))
+    }
+}
+
+/// Narrows an int to the sub-word type it is stored as, like a heap write does.
+fn narrow(value: Value, field_type: AllocationType) -> Result<Value, JvmError> {
+    Ok(match field_type {
+        AllocationType::Boolean => Value::Integer((value.as_int()? != 0) as i32),
+        AllocationType::Byte => Value::Integer(value.as_int()? as i8 as i32),
+        AllocationType::Short => Value::Integer(value.as_int()? as i16 as i32),
+        AllocationType::Char => Value::Integer(value.as_int()? as u16 as i32),
+        _ => value,
+    })
+}
+
+/// `getXxx(Object o, long offset)`
+fn get(vm: &VirtualMachine, args: &[Value], field_type: AllocationType) -> NativeRet {
+    let value = match Location::new(vm, args[1], args[2])? {
+        Location::Heap(base, offset) => vm.heap_read().read_field(base, offset, field_type)?,
+        Location::Static(class_id, key) => vm
+            .method_area()
+            .get_class_like(&class_id)?
+            .get_static_field_value(&key)?,
+    };
+    Ok(Some(value))
+}
+
+/// `putXxx(Object o, long offset, Xxx x)`
+fn put(vm: &VirtualMachine, args: &[Value], field_type: AllocationType) -> NativeRet {
+    match Location::new(vm, args[1], args[2])? {
+        Location::Heap(base, offset) => vm
+            .heap_write()
+            .write_field(base, offset, args[3], field_type)?,
+        Location::Static(class_id, key) => vm
+            .method_area()
+            .get_class_like(&class_id)?
+            .set_static_field_value(&key, narrow(args[3], field_type)?)?,
+    }
+    Ok(None)
+}
+
+/// `compareAndExchangeXxx(Object o, long offset, Xxx expected, Xxx x)`, the heap stays locked
+/// between the read and the write.
+pub(in crate::native) fn compare_and_exchange(
+    vm: &VirtualMachine,
+    args: &[Value],
+    field_type: AllocationType,
+) -> Result<Value, JvmError> {
+    let (expected, new_value) = (args[3], args[4]);
+    match Location::new(vm, args[1], args[2])? {
+        Location::Heap(base, offset) => {
+            let mut heap = vm.heap_write();
+            let current = heap.read_field(base, offset, field_type)?;
+            if current == expected {
+                heap.write_field(base, offset, new_value, field_type)?;
+            }
+            Ok(current)
+        }
+        Location::Static(class_id, key) => vm
+            .method_area()
+            .get_class_like(&class_id)?
+            .compare_and_exchange_static_field_value(&key, expected, new_value),
+    }
+}
+
+/// `getAndAddXxx(Object o, long offset, Xxx delta)` of the int-sized and sub-word types.
+pub(in crate::native) fn get_and_add(
+    vm: &VirtualMachine,
+    args: &[Value],
+    field_type: AllocationType,
+) -> Result<Value, JvmError> {
+    let delta = args[3].as_int()?;
+    let sum = |current: Value| {
+        narrow(
+            Value::Integer(current.as_int()?.wrapping_add(delta)),
+            field_type,
+        )
+    };
+    match Location::new(vm, args[1], args[2])? {
+        Location::Heap(base, offset) => {
+            let mut heap = vm.heap_write();
+            let current = heap.read_field(base, offset, field_type)?;
+            heap.write_field(base, offset, sum(current)?, field_type)?;
+            Ok(current)
+        }
+        Location::Static(class_id, key) => {
+            let ma = vm.method_area();
+            let class = ma.get_class_like(&class_id)?;
+            loop {
+                let current = class.get_static_field_value(&key)?;
+                let witness =
+                    class.compare_and_exchange_static_field_value(&key, current, sum(current)?)?;
+                if witness == current {
+                    return Ok(current);
+                }
+            }
+        }
+    }
+}
+
+pub(in crate::native) fn compare_and_set(
+    vm: &VirtualMachine,
+    args: &[Value],
+    field_type: AllocationType,
+) -> NativeRet {
+    let witness = compare_and_exchange(vm, args, field_type)?;
+    Ok(Some(Value::Integer((witness == args[3]) as i32)))
+}
+
+fn jdk_internal_misc_unsafe_get_boolean(
+    vm: &VirtualMachine,
     _thread: &mut JavaThreadState,
-    _args: &[Value],
+    args: &[Value],
 ) -> NativeRet {
-    debug!("TODO: Stub: jdk.internal.misc.Unsafe.fullFence");
-    Ok(None)
+    get(vm, args, AllocationType::Boolean)
 }
 
-fn jdk_internal_misc_unsafe_compare_and_set_reference(
+fn jdk_internal_misc_unsafe_get_byte(
     vm: &VirtualMachine,
     _thread: &mut JavaThreadState,
     args: &[Value],
 ) -> NativeRet {
-    let object = match args[1] {
-        Value::Ref(h) => h,
-        _ => panic!("compareAndSetReference: expected object"),
-    };
-    let offset = match args[2] {
-        Value::Long(l) if l >= 0 => l as usize,
-        _ => panic!("compareAndSetReference: expected non-negative offset"),
-    };
-    let expected = match args[3] {
-        v @ (Value::Ref(_) | Value::Null) => v,
-        _ => panic!("compareAndSetReference: expected object or null"),
-    };
-    let new_value = match args[4] {
-        Value::Ref(h) => h,
-        _ => panic!("compareAndSetReference: expected object"),
-    };
+    get(vm, args, AllocationType::Byte)
+}
 
-    let current = vm
-        .heap_read()
-        .read_field(object, offset, AllocationType::Reference)?;
-    if current == expected {
-        vm.heap_write().write_field(
-            object,
-            offset,
-            Value::Ref(new_value),
-            AllocationType::Reference,
-        )?;
-        Ok(Some(Value::Integer(1)))
-    } else {
-        Ok(Some(Value::Integer(0)))
-    }
+fn jdk_internal_misc_unsafe_get_short(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    get(vm, args, AllocationType::Short)
+}
+
+fn jdk_internal_misc_unsafe_get_char(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    get(vm, args, AllocationType::Char)
+}
+
+fn jdk_internal_misc_unsafe_get_int(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    get(vm, args, AllocationType::Int)
+}
+
+fn jdk_internal_misc_unsafe_get_long(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    get(vm, args, AllocationType::Long)
+}
+
+fn jdk_internal_misc_unsafe_get_float(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    get(vm, args, AllocationType::Float)
+}
+
+fn jdk_internal_misc_unsafe_get_double(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    get(vm, args, AllocationType::Double)
+}
+
+fn jdk_internal_misc_unsafe_get_reference(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    get(vm, args, AllocationType::Reference)
+}
+
+fn jdk_internal_misc_unsafe_put_boolean(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    put(vm, args, AllocationType::Boolean)
 }
 
 fn jdk_internal_misc_unsafe_put_byte(
@@ -433,23 +678,177 @@ fn jdk_internal_misc_unsafe_put_byte(
     _thread: &mut JavaThreadState,
     args: &[Value],
 ) -> NativeRet {
-    let object = args
-        .get(1)
-        .ok_or(JvmError::Todo("putByte: missing 1 argument".to_string()))?
-        .as_obj_ref()?;
-    let offset = match args
-        .get(2)
-        .ok_or(JvmError::Todo("putByte: missing 2 argument".to_string()))?
-    {
-        Value::Long(l) if *l >= 0 => *l as usize,
-        _ => panic!("putByte: expected non-negative offset"),
-    };
-    let value = args
-        .get(3)
-        .ok_or(JvmError::Todo("putByte: missing 3 argument".to_string()))?
-        .as_int()?;
+    put(vm, args, AllocationType::Byte)
+}
+
+fn jdk_internal_misc_unsafe_put_short(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    put(vm, args, AllocationType::Short)
+}
+
+fn jdk_internal_misc_unsafe_put_char(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    put(vm, args, AllocationType::Char)
+}
+
+fn jdk_internal_misc_unsafe_put_int(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    put(vm, args, AllocationType::Int)
+}
+
+fn jdk_internal_misc_unsafe_put_long(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    put(vm, args, AllocationType::Long)
+}
+
+fn jdk_internal_misc_unsafe_put_float(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    put(vm, args, AllocationType::Float)
+}
 
-    vm.heap_write()
-        .write_field(object, offset, Value::Integer(value), AllocationType::Byte)?;
+fn jdk_internal_misc_unsafe_put_double(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    put(vm, args, AllocationType::Double)
+}
+
+fn jdk_internal_misc_unsafe_put_reference(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    put(vm, args, AllocationType::Reference)
+}
+
+fn jdk_internal_misc_unsafe_compare_and_set_int(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    compare_and_set(vm, args, AllocationType::Int)
+}
+
+fn jdk_internal_misc_unsafe_compare_and_exchange_int(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    Ok(Some(compare_and_exchange(vm, args, AllocationType::Int)?))
+}
+
+fn jdk_internal_misc_unsafe_compare_and_set_long(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    compare_and_set(vm, args, AllocationType::Long)
+}
+
+fn jdk_internal_misc_unsafe_compare_and_exchange_long(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    Ok(Some(compare_and_exchange(vm, args, AllocationType::Long)?))
+}
+
+fn jdk_internal_misc_unsafe_compare_and_set_reference(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    compare_and_set(vm, args, AllocationType::Reference)
+}
+
+fn jdk_internal_misc_unsafe_compare_and_exchange_reference(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    Ok(Some(compare_and_exchange(
+        vm,
+        args,
+        AllocationType::Reference,
+    )?))
+}
+
+fn jdk_internal_misc_unsafe_fence(
+    _vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    _args: &[Value],
+) -> NativeRet {
+    fence(Ordering::SeqCst);
     Ok(None)
 }
+
+/// The declaring class and the declared field a `Field` object reflects.
+fn reflected_field(
+    vm: &VirtualMachine,
+    field_ref: Value,
+) -> Result<(ClassId, usize, DeclaredField), JvmError> {
+    let Some(field_ref) = field_ref.as_nullable_obj_ref()? else {
+        throw_exception!(NullPointerException)?
+    };
+    let ma = vm.method_area();
+    let class_id =
+        ma.get_class_id_by_mirror(&read_named_field(vm, field_ref, "clazz")?.as_obj_ref()?)?;
+    let slot = read_named_field(vm, field_ref, "slot")?.as_int()? as usize;
+    let field = ma
+        .get_class_like(&class_id)?
+        .declared_fields()?
+        .get(slot)
+        .cloned()
+        .ok_or_else(|| JvmError::Todo(format!("No declared field at slot {slot}")))?;
+    Ok((class_id, slot, field))
+}
+
+fn jdk_internal_misc_unsafe_object_field_offset_0(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let (class_id, _, field) = reflected_field(vm, args[1])?;
+    let offset = vm
+        .method_area()
+        .get_instance_field(&class_id, &field.key)?
+        .offset;
+    Ok(Some(Value::Long(offset as i64)))
+}
+
+fn jdk_internal_misc_unsafe_static_field_offset_0(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let (_, slot, _) = reflected_field(vm, args[1])?;
+    Ok(Some(Value::Long(STATIC_FIELD_OFFSET_BASE + slot as i64)))
+}
+
+fn jdk_internal_misc_unsafe_static_field_base_0(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let (class_id, _, _) = reflected_field(vm, args[1])?;
+    Ok(Some(Value::Ref(
+        vm.method_area()
+            .get_mirror_ref_or_create(class_id, &vm.heap)?,
+    )))
+}
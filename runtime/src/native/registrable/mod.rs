@@ -2,6 +2,7 @@ use crate::keys::FullyQualifiedMethodKey;
 use crate::native::NativeRegistry;
 use crate::native::registrable::java_lang_class::java_lang_class_register_natives;
 use crate::native::registrable::java_lang_class_loader::java_lang_class_loader_register_natives;
+use crate::native::registrable::java_lang_invoke_method_handle_natives::java_lang_invoke_method_handle_natives_register_natives;
 use crate::native::registrable::java_lang_system::java_lang_system_register_natives;
 use crate::native::registrable::java_lang_thread::java_lang_thread_register_natives;
 use crate::native::registrable::jdk_internal_misc_scoped_memory_access::jdk_internal_misc_scoped_memory_access_register_natives;
@@ -9,10 +10,11 @@ use crate::native::registrable::jdk_internal_misc_unsafe::jdk_internal_misc_unsa
 
 mod java_lang_class;
 mod java_lang_class_loader;
+mod java_lang_invoke_method_handle_natives;
 mod java_lang_system;
 mod java_lang_thread;
 mod jdk_internal_misc_scoped_memory_access;
-mod jdk_internal_misc_unsafe;
+pub(super) mod jdk_internal_misc_unsafe;
 
 pub(super) fn add_registrable_natives(native_registry: &mut NativeRegistry) {
     native_registry.register(
@@ -69,5 +71,14 @@ pub(super) fn add_registrable_natives(native_registry: &mut NativeRegistry) {
             &native_registry.string_interner,
         ),
         java_lang_class_loader_register_natives,
+    );
+    native_registry.register(
+        FullyQualifiedMethodKey::new_with_str(
+            "java/lang/invoke/MethodHandleNatives",
+            "registerNatives",
+            "()V",
+            &native_registry.string_interner,
+        ),
+        java_lang_invoke_method_handle_natives_register_natives,
     )
 }
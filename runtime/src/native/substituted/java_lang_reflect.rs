@@ -0,0 +1,548 @@
+use crate::error::JvmError;
+use crate::heap::HeapRef;
+use crate::interpreter::Interpreter;
+use crate::keys::{ClassId, FieldKey, FullyQualifiedMethodKey, MethodKey};
+use crate::native::reflection::{
+    box_value, caller_class_id, read_named_field, unbox_and_widen, widen,
+};
+use crate::native::{NativeFn, NativeRegistry, NativeRet};
+use crate::rt::JvmClass;
+use crate::thread::JavaThreadState;
+use crate::vm::Value;
+use crate::{VirtualMachine, throw_exception};
+use common::jtype::PrimitiveType;
+use jclass::flags::FieldFlags;
+
+// Since JDK 22 the field accessors of `Field` are built on method handles. The VM reads and
+// writes the field itself, after the access check `Field` would do.
+pub(super) fn do_substitute_java_lang_reflect_natives(native_registry: &mut NativeRegistry) {
+    let accessors: [(&str, &str, NativeFn); 18] = [
+        (
+            "get",
+            "(Ljava/lang/Object;)Ljava/lang/Object;",
+            java_lang_reflect_field_get,
+        ),
+        (
+            "getBoolean",
+            "(Ljava/lang/Object;)Z",
+            java_lang_reflect_field_get_boolean,
+        ),
+        (
+            "getByte",
+            "(Ljava/lang/Object;)B",
+            java_lang_reflect_field_get_byte,
+        ),
+        (
+            "getChar",
+            "(Ljava/lang/Object;)C",
+            java_lang_reflect_field_get_char,
+        ),
+        (
+            "getShort",
+            "(Ljava/lang/Object;)S",
+            java_lang_reflect_field_get_short,
+        ),
+        (
+            "getInt",
+            "(Ljava/lang/Object;)I",
+            java_lang_reflect_field_get_int,
+        ),
+        (
+            "getLong",
+            "(Ljava/lang/Object;)J",
+            java_lang_reflect_field_get_long,
+        ),
+        (
+            "getFloat",
+            "(Ljava/lang/Object;)F",
+            java_lang_reflect_field_get_float,
+        ),
+        (
+            "getDouble",
+            "(Ljava/lang/Object;)D",
+            java_lang_reflect_field_get_double,
+        ),
+        (
+            "set",
+            "(Ljava/lang/Object;Ljava/lang/Object;)V",
+            java_lang_reflect_field_set,
+        ),
+        (
+            "setBoolean",
+            "(Ljava/lang/Object;Z)V",
+            java_lang_reflect_field_set_boolean,
+        ),
+        (
+            "setByte",
+            "(Ljava/lang/Object;B)V",
+            java_lang_reflect_field_set_byte,
+        ),
+        (
+            "setChar",
+            "(Ljava/lang/Object;C)V",
+            java_lang_reflect_field_set_char,
+        ),
+        (
+            "setShort",
+            "(Ljava/lang/Object;S)V",
+            java_lang_reflect_field_set_short,
+        ),
+        (
+            "setInt",
+            "(Ljava/lang/Object;I)V",
+            java_lang_reflect_field_set_int,
+        ),
+        (
+            "setLong",
+            "(Ljava/lang/Object;J)V",
+            java_lang_reflect_field_set_long,
+        ),
+        (
+            "setFloat",
+            "(Ljava/lang/Object;F)V",
+            java_lang_reflect_field_set_float,
+        ),
+        (
+            "setDouble",
+            "(Ljava/lang/Object;D)V",
+            java_lang_reflect_field_set_double,
+        ),
+    ];
+    for (name, desc, f) in accessors {
+        native_registry.substitute(
+            FullyQualifiedMethodKey::new_with_str(
+                "java/lang/reflect/Field",
+                name,
+                desc,
+                &native_registry.string_interner,
+            ),
+            f,
+        );
+    }
+}
+
+/// The field a `Field` object reflects.
+struct ReflectedField {
+    field_ref: HeapRef,
+    class_id: ClassId,
+    key: FieldKey,
+    flags: FieldFlags,
+    /// Class of the field's type.
+    type_id: ClassId,
+    /// `setAccessible(true)` was called.
+    is_override: bool,
+}
+
+impl ReflectedField {
+    fn new(vm: &VirtualMachine, field_ref: HeapRef) -> Result<Self, JvmError> {
+        let ma = vm.method_area();
+        let class_id =
+            ma.get_class_id_by_mirror(&read_named_field(vm, field_ref, "clazz")?.as_obj_ref()?)?;
+        let slot = read_named_field(vm, field_ref, "slot")?.as_int()?;
+        let declared = ma
+            .get_class_like(&class_id)?
+            .declared_fields()?
+            .get(slot as usize)
+            .cloned()
+            .ok_or_else(|| JvmError::Todo(format!("No declared field at slot {slot}")))?;
+        let type_id =
+            ma.get_class_id_by_mirror(&read_named_field(vm, field_ref, "type")?.as_obj_ref()?)?;
+        Ok(Self {
+            field_ref,
+            class_id,
+            key: declared.key,
+            flags: declared.flags,
+            type_id,
+            is_override: read_named_field(vm, field_ref, "override")?.as_int()? != 0,
+        })
+    }
+
+    fn primitive_type(&self, vm: &VirtualMachine) -> Option<PrimitiveType> {
+        match vm.method_area().get_class(&self.type_id) {
+            JvmClass::Primitive(class) => Some(class.primitive_type),
+            _ => None,
+        }
+    }
+
+    /// `Field.checkAccess(caller, obj)` for the caller of the accessor, unless access checks
+    /// are suppressed.
+    fn check_access(
+        &self,
+        vm: &VirtualMachine,
+        thread: &mut JavaThreadState,
+        obj: Value,
+    ) -> Result<(), JvmError> {
+        if self.is_override {
+            return Ok(());
+        }
+        let caller = match caller_class_id(vm, thread, 1) {
+            Some(class_id) => Value::Ref(
+                vm.method_area()
+                    .get_mirror_ref_or_create(class_id, &vm.heap)?,
+            ),
+            None => Value::Null,
+        };
+        let field_class_id = vm.heap_read().get_class_id(self.field_ref)?;
+        let check_access_id = vm
+            .method_area()
+            .get_instance_class(&field_class_id)?
+            .get_special_method_id(&MethodKey {
+                name: vm.interner().get_or_intern("checkAccess"),
+                desc: vm
+                    .interner()
+                    .get_or_intern("(Ljava/lang/Class;Ljava/lang/Object;)V"),
+            })?;
+        Interpreter::invoke_instance_method(
+            thread,
+            check_access_id,
+            vm,
+            vec![Value::Ref(self.field_ref), caller, obj],
+        )?;
+        Ok(())
+    }
+
+    /// The object holding an instance field, `None` for a static field whose class is
+    /// initialized first.
+    fn holder(
+        &self,
+        vm: &VirtualMachine,
+        thread: &mut JavaThreadState,
+        obj: Value,
+    ) -> Result<Option<HeapRef>, JvmError> {
+        if self.flags.is_static() {
+            Interpreter::ensure_initialized(thread, Some(self.class_id), vm)?;
+            return Ok(None);
+        }
+        let Some(obj) = obj.as_nullable_obj_ref()? else {
+            throw_exception!(NullPointerException)?
+        };
+        let obj_class_id = vm.heap_read().get_class_id(obj)?;
+        if !vm
+            .method_area()
+            .is_assignable_from(self.class_id, obj_class_id)
+        {
+            throw_exception!(
+                IllegalArgumentException,
+                self.set_message(vm, &class_name(vm, obj_class_id), "")
+            )?
+        }
+        Ok(Some(obj))
+    }
+
+    fn read(&self, vm: &VirtualMachine, holder: Option<HeapRef>) -> Result<Value, JvmError> {
+        let ma = vm.method_area();
+        match holder {
+            None => ma.get_static_field_value(&self.class_id, &self.key),
+            Some(obj) => {
+                let field = ma.get_instance_field(&self.class_id, &self.key)?;
+                let field_type = ma
+                    .get_field_descriptor(&field.descriptor_id)
+                    .as_allocation_type();
+                vm.heap_read().read_field(obj, field.offset, field_type)
+            }
+        }
+    }
+
+    fn write(
+        &self,
+        vm: &VirtualMachine,
+        holder: Option<HeapRef>,
+        value: Value,
+    ) -> Result<(), JvmError> {
+        let ma = vm.method_area();
+        match holder {
+            None => ma
+                .get_class_like(&self.class_id)?
+                .set_static_field_value(&self.key, value),
+            Some(obj) => {
+                let field = ma.get_instance_field(&self.class_id, &self.key)?;
+                let field_type = ma
+                    .get_field_descriptor(&field.descriptor_id)
+                    .as_allocation_type();
+                vm.heap_write()
+                    .write_field(obj, field.offset, value, field_type)
+            }
+        }
+    }
+
+    /// Final fields can only be set after `setAccessible(true)`, and never if they are static
+    /// or in a record.
+    fn is_read_only(&self, vm: &VirtualMachine) -> Result<bool, JvmError> {
+        let trusted_final = read_named_field(vm, self.field_ref, "trustedFinal")?.as_int()? != 0;
+        Ok(self.flags.is_final() && (!self.is_override || trusted_final))
+    }
+
+    /// `X.f`, the field name qualified with its declaring class.
+    fn qualified_name(&self, vm: &VirtualMachine) -> String {
+        format!(
+            "{}.{}",
+            class_name(vm, self.class_id),
+            vm.interner().resolve(&self.key.name)
+        )
+    }
+
+    /// Message of the reference implementation for a value that can't be set, the value is
+    /// described by its type and, for a primitive, itself.
+    fn set_message(&self, vm: &VirtualMachine, value_type: &str, value: &str) -> String {
+        let mut message = "Can not set".to_string();
+        if self.flags.is_static() {
+            message.push_str(" static");
+        }
+        if self.flags.is_final() {
+            message.push_str(" final");
+        }
+        let value = match (value_type, value) {
+            ("", _) => "null value".to_string(),
+            (value_type, "") => value_type.to_string(),
+            (value_type, value) => format!("({value_type}){value}"),
+        };
+        format!(
+            "{message} {} field {} to {value}",
+            class_name(vm, self.type_id),
+            self.qualified_name(vm)
+        )
+    }
+
+    fn get_message(&self, vm: &VirtualMachine, as_type: PrimitiveType) -> String {
+        format!(
+            "Attempt to get {} field \"{}\" with illegal data type conversion to {as_type}",
+            class_name(vm, self.type_id),
+            self.qualified_name(vm)
+        )
+    }
+}
+
+fn class_name(vm: &VirtualMachine, class_id: ClassId) -> String {
+    vm.symbol_to_pretty_string(vm.method_area().get_class(&class_id).get_name())
+}
+
+/// A primitive value the way `String.valueOf` prints it.
+fn primitive_string(ty: PrimitiveType, value: Value) -> String {
+    match (ty, value) {
+        (PrimitiveType::Boolean, Value::Integer(i)) => (i != 0).to_string(),
+        (PrimitiveType::Char, Value::Integer(i)) => char::from_u32(i as u32)
+            .map(String::from)
+            .unwrap_or_default(),
+        (_, Value::Integer(i)) => i.to_string(),
+        (_, Value::Long(l)) => l.to_string(),
+        (_, Value::Float(f)) => format!("{f:?}"),
+        (_, Value::Double(d)) => format!("{d:?}"),
+        _ => String::new(),
+    }
+}
+
+/// `get` boxes a primitive field, the typed getters (`as_type`) widen it.
+fn field_get(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+    as_type: Option<PrimitiveType>,
+) -> NativeRet {
+    let field = ReflectedField::new(vm, args[0].as_obj_ref()?)?;
+    field.check_access(vm, thread, args[1])?;
+    let holder = field.holder(vm, thread, args[1])?;
+    let value = field.read(vm, holder)?;
+    match (field.primitive_type(vm), as_type) {
+        (Some(ty), None) => Ok(Some(Value::Ref(box_value(vm, thread, ty, value)?))),
+        (None, None) => Ok(Some(value)),
+        (Some(ty), Some(as_type)) if let Some(value) = widen(value, ty, as_type) => Ok(Some(value)),
+        (_, Some(as_type)) => {
+            throw_exception!(IllegalArgumentException, field.get_message(vm, as_type))
+        }
+    }
+}
+
+/// `set` unboxes a value for a primitive field, the typed setters (`value_type`) widen theirs.
+fn field_set(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+    value_type: Option<PrimitiveType>,
+) -> NativeRet {
+    let field = ReflectedField::new(vm, args[0].as_obj_ref()?)?;
+    field.check_access(vm, thread, args[1])?;
+    let holder = field.holder(vm, thread, args[1])?;
+    let value = args[2];
+    let (attempted_type, attempted_value) = match value_type {
+        Some(ty) => (ty.to_string(), primitive_string(ty, value)),
+        None => match value.as_nullable_obj_ref()? {
+            Some(obj) => (
+                class_name(vm, vm.heap_read().get_class_id(obj)?),
+                String::new(),
+            ),
+            None => (String::new(), String::new()),
+        },
+    };
+    if field.is_read_only(vm)? {
+        throw_exception!(
+            IllegalAccessException,
+            field.set_message(vm, &attempted_type, &attempted_value)
+        )?
+    }
+    let converted = match (field.primitive_type(vm), value_type) {
+        (Some(ty), None) => unbox_and_widen(vm, value, ty)?,
+        (Some(ty), Some(from)) => widen(value, from, ty),
+        (None, None) => match value.as_nullable_obj_ref()? {
+            Some(obj) => {
+                let value_class_id = vm.heap_read().get_class_id(obj)?;
+                vm.method_area()
+                    .is_assignable_from(field.type_id, value_class_id)
+                    .then_some(value)
+            }
+            None => Some(value),
+        },
+        (None, Some(_)) => None,
+    };
+    let Some(converted) = converted else {
+        throw_exception!(
+            IllegalArgumentException,
+            field.set_message(vm, &attempted_type, &attempted_value)
+        )?
+    };
+    field.write(vm, holder, converted)?;
+    Ok(None)
+}
+
+fn java_lang_reflect_field_get(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    field_get(vm, thread, args, None)
+}
+
+fn java_lang_reflect_field_get_boolean(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    field_get(vm, thread, args, Some(PrimitiveType::Boolean))
+}
+
+fn java_lang_reflect_field_get_byte(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    field_get(vm, thread, args, Some(PrimitiveType::Byte))
+}
+
+fn java_lang_reflect_field_get_char(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    field_get(vm, thread, args, Some(PrimitiveType::Char))
+}
+
+fn java_lang_reflect_field_get_short(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    field_get(vm, thread, args, Some(PrimitiveType::Short))
+}
+
+fn java_lang_reflect_field_get_int(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    field_get(vm, thread, args, Some(PrimitiveType::Int))
+}
+
+fn java_lang_reflect_field_get_long(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    field_get(vm, thread, args, Some(PrimitiveType::Long))
+}
+
+fn java_lang_reflect_field_get_float(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    field_get(vm, thread, args, Some(PrimitiveType::Float))
+}
+
+fn java_lang_reflect_field_get_double(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    field_get(vm, thread, args, Some(PrimitiveType::Double))
+}
+
+fn java_lang_reflect_field_set(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    field_set(vm, thread, args, None)
+}
+
+fn java_lang_reflect_field_set_boolean(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    field_set(vm, thread, args, Some(PrimitiveType::Boolean))
+}
+
+fn java_lang_reflect_field_set_byte(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    field_set(vm, thread, args, Some(PrimitiveType::Byte))
+}
+
+fn java_lang_reflect_field_set_char(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    field_set(vm, thread, args, Some(PrimitiveType::Char))
+}
+
+fn java_lang_reflect_field_set_short(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    field_set(vm, thread, args, Some(PrimitiveType::Short))
+}
+
+fn java_lang_reflect_field_set_int(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    field_set(vm, thread, args, Some(PrimitiveType::Int))
+}
+
+fn java_lang_reflect_field_set_long(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    field_set(vm, thread, args, Some(PrimitiveType::Long))
+}
+
+fn java_lang_reflect_field_set_float(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    field_set(vm, thread, args, Some(PrimitiveType::Float))
+}
+
+fn java_lang_reflect_field_set_double(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    field_set(vm, thread, args, Some(PrimitiveType::Double))
+}
@@ -0,0 +1,274 @@
+use crate::VirtualMachine;
+use crate::class_loader::BuiltinLoader;
+use crate::error::{JavaExceptionKind, JvmError};
+use crate::heap::HeapRef;
+use crate::keys::FullyQualifiedMethodKey;
+use crate::native::substituted::{input_stream, string_arg, url, url_enumeration};
+use crate::native::{NativeRegistry, NativeRet};
+use crate::thread::JavaThreadState;
+use crate::vm::Value;
+
+// `BuiltinClassLoader` finds resources through the module layer built by `initPhase2` and
+// `URLClassPath`, the VM answers from the boot layer it resolved and its classpath index instead.
+pub(super) fn do_substitute_jdk_internal_loader_natives(native_registry: &mut NativeRegistry) {
+    native_registry.substitute(
+        FullyQualifiedMethodKey::new_with_str(
+            "jdk/internal/loader/BootLoader",
+            "findResource",
+            "(Ljava/lang/String;)Ljava/net/URL;",
+            &native_registry.string_interner,
+        ),
+        jdk_internal_loader_boot_loader_find_resource,
+    );
+    native_registry.substitute(
+        FullyQualifiedMethodKey::new_with_str(
+            "jdk/internal/loader/BootLoader",
+            "findResources",
+            "(Ljava/lang/String;)Ljava/util/Enumeration;",
+            &native_registry.string_interner,
+        ),
+        jdk_internal_loader_boot_loader_find_resources,
+    );
+    native_registry.substitute(
+        FullyQualifiedMethodKey::new_with_str(
+            "jdk/internal/loader/BootLoader",
+            "findResource",
+            "(Ljava/lang/String;Ljava/lang/String;)Ljava/net/URL;",
+            &native_registry.string_interner,
+        ),
+        jdk_internal_loader_boot_loader_find_module_resource,
+    );
+    native_registry.substitute(
+        FullyQualifiedMethodKey::new_with_str(
+            "jdk/internal/loader/BootLoader",
+            "findResourceAsStream",
+            "(Ljava/lang/String;Ljava/lang/String;)Ljava/io/InputStream;",
+            &native_registry.string_interner,
+        ),
+        jdk_internal_loader_boot_loader_find_resource_as_stream,
+    );
+    native_registry.substitute(
+        FullyQualifiedMethodKey::new_with_str(
+            "jdk/internal/loader/BuiltinClassLoader",
+            "findResource",
+            "(Ljava/lang/String;)Ljava/net/URL;",
+            &native_registry.string_interner,
+        ),
+        jdk_internal_loader_builtin_class_loader_find_resource,
+    );
+    native_registry.substitute(
+        FullyQualifiedMethodKey::new_with_str(
+            "jdk/internal/loader/BuiltinClassLoader",
+            "findResources",
+            "(Ljava/lang/String;)Ljava/util/Enumeration;",
+            &native_registry.string_interner,
+        ),
+        jdk_internal_loader_builtin_class_loader_find_resources,
+    );
+    native_registry.substitute(
+        FullyQualifiedMethodKey::new_with_str(
+            "jdk/internal/loader/BuiltinClassLoader",
+            "findResource",
+            "(Ljava/lang/String;Ljava/lang/String;)Ljava/net/URL;",
+            &native_registry.string_interner,
+        ),
+        jdk_internal_loader_builtin_class_loader_find_module_resource,
+    );
+    native_registry.substitute(
+        FullyQualifiedMethodKey::new_with_str(
+            "jdk/internal/loader/BuiltinClassLoader",
+            "findResourceAsStream",
+            "(Ljava/lang/String;Ljava/lang/String;)Ljava/io/InputStream;",
+            &native_registry.string_interner,
+        ),
+        jdk_internal_loader_builtin_class_loader_find_resource_as_stream,
+    );
+    native_registry.substitute(
+        FullyQualifiedMethodKey::new_with_str(
+            "jdk/internal/loader/BuiltinClassLoader",
+            "loadClassOrNull",
+            "(Ljava/lang/String;Z)Ljava/lang/Class;",
+            &native_registry.string_interner,
+        ),
+        jdk_internal_loader_builtin_class_loader_load_class_or_null,
+    );
+}
+
+/// Which modules a `BuiltinClassLoader` defines. The platform loader defines none, the VM puts
+/// every module of the runtime image in the bootstrap loader.
+fn defined_by(vm: &VirtualMachine, loader: HeapRef) -> Option<BuiltinLoader> {
+    match vm.method_area().builtin_loaders() {
+        Some(builtin) if loader == builtin.platform => None,
+        Some(builtin) if loader != builtin.app => Some(BuiltinLoader::Boot),
+        _ => Some(BuiltinLoader::App),
+    }
+}
+
+/// The builtin loaders share the namespace of the VM, the VM loads the class from the runtime
+/// image or the classpath and the loader gets it if it or one of its parents defined it.
+fn jdk_internal_loader_builtin_class_loader_load_class_or_null(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let loader = args[0].as_obj_ref()?;
+    let name = string_arg(vm, &args[1])?;
+    if name.contains('/') {
+        return Ok(Some(Value::Null));
+    }
+    let name_sym = vm.interner().get_or_intern(name.replace('.', "/"));
+    let ma = vm.method_area();
+    let class_id = match ma.builtin_loaders() {
+        Some(builtin) if loader == builtin.platform || loader == builtin.app => {
+            match ma.get_class_id_or_load(name_sym, thread.id) {
+                Ok(class_id) => Some(class_id)
+                    .filter(|class_id| ma.is_visible_to_builtin_loader(Some(loader), *class_id)),
+                Err(JvmError::JavaException(e))
+                    if e.kind == JavaExceptionKind::ClassNotFoundException =>
+                {
+                    None
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        _ => ma.find_bootstrap_class(name_sym, thread.id)?,
+    };
+    match class_id {
+        Some(class_id) => Ok(Some(Value::Ref(
+            ma.get_mirror_ref_or_create(class_id, &vm.heap)?,
+        ))),
+        None => Ok(Some(Value::Null)),
+    }
+}
+
+fn find_resources(
+    vm: &VirtualMachine,
+    loader: Option<BuiltinLoader>,
+    name: &Value,
+) -> Result<Vec<String>, JvmError> {
+    let name = string_arg(vm, name)?;
+    Ok(loader
+        .map(|loader| {
+            vm.method_area()
+                .class_loader()
+                .find_resources(loader, &name)
+        })
+        .unwrap_or_default())
+}
+
+fn find_module_resource(
+    vm: &VirtualMachine,
+    loader: Option<BuiltinLoader>,
+    module_name: &Value,
+    name: &Value,
+) -> Result<Option<String>, JvmError> {
+    let (Some(loader), Some(module_name)) = (loader, module_name.as_nullable_obj_ref()?) else {
+        return Ok(None);
+    };
+    let module_name = vm
+        .heap_read()
+        .get_rust_string_from_java_string(module_name)?;
+    let name = string_arg(vm, name)?;
+    Ok(vm
+        .method_area()
+        .class_loader()
+        .find_module_resource(loader, &module_name, &name))
+}
+
+fn find_resource_as_stream(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    loader: Option<BuiltinLoader>,
+    module_name: &Value,
+    name: &Value,
+) -> NativeRet {
+    let (Some(loader), Some(module_name)) = (loader, module_name.as_nullable_obj_ref()?) else {
+        return Ok(Some(Value::Null));
+    };
+    let module_name = vm
+        .heap_read()
+        .get_rust_string_from_java_string(module_name)?;
+    let name = string_arg(vm, name)?;
+    let bytes =
+        vm.method_area()
+            .class_loader()
+            .open_module_resource(loader, &module_name, &name)?;
+    match bytes {
+        Some(bytes) => Ok(Some(input_stream(vm, thread, &bytes)?)),
+        None => Ok(Some(Value::Null)),
+    }
+}
+
+fn jdk_internal_loader_boot_loader_find_resource(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let resources = find_resources(vm, Some(BuiltinLoader::Boot), &args[0])?;
+    Ok(Some(url(vm, thread, resources.into_iter().next())?))
+}
+
+fn jdk_internal_loader_boot_loader_find_resources(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let resources = find_resources(vm, Some(BuiltinLoader::Boot), &args[0])?;
+    Ok(Some(url_enumeration(vm, thread, resources)?))
+}
+
+fn jdk_internal_loader_boot_loader_find_module_resource(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let resource = find_module_resource(vm, Some(BuiltinLoader::Boot), &args[0], &args[1])?;
+    Ok(Some(url(vm, thread, resource)?))
+}
+
+fn jdk_internal_loader_boot_loader_find_resource_as_stream(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    find_resource_as_stream(vm, thread, Some(BuiltinLoader::Boot), &args[0], &args[1])
+}
+
+fn jdk_internal_loader_builtin_class_loader_find_resource(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let loader = defined_by(vm, args[0].as_obj_ref()?);
+    let resources = find_resources(vm, loader, &args[1])?;
+    Ok(Some(url(vm, thread, resources.into_iter().next())?))
+}
+
+fn jdk_internal_loader_builtin_class_loader_find_resources(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let loader = defined_by(vm, args[0].as_obj_ref()?);
+    let resources = find_resources(vm, loader, &args[1])?;
+    Ok(Some(url_enumeration(vm, thread, resources)?))
+}
+
+fn jdk_internal_loader_builtin_class_loader_find_module_resource(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let loader = defined_by(vm, args[0].as_obj_ref()?);
+    let resource = find_module_resource(vm, loader, &args[1], &args[2])?;
+    Ok(Some(url(vm, thread, resource)?))
+}
+
+fn jdk_internal_loader_builtin_class_loader_find_resource_as_stream(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let loader = defined_by(vm, args[0].as_obj_ref()?);
+    find_resource_as_stream(vm, thread, loader, &args[1], &args[2])
+}
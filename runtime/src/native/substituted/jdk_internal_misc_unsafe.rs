@@ -0,0 +1,177 @@
+use crate::VirtualMachine;
+use crate::keys::FullyQualifiedMethodKey;
+use crate::native::registrable::jdk_internal_misc_unsafe::{
+    compare_and_exchange, compare_and_set, get_and_add,
+};
+use crate::native::{NativeFn, NativeRegistry, NativeRet};
+use crate::thread::JavaThreadState;
+use crate::vm::Value;
+use common::jtype::AllocationType;
+
+// `Unsafe` does the atomics of the sub-word types in Java, on the aligned int around the value.
+// Neither the heap layout nor the offsets of static fields have such words, so the VM updates the
+// field itself.
+pub(super) fn do_substitute_jdk_internal_misc_unsafe_natives(native_registry: &mut NativeRegistry) {
+    let atomics: [(&str, &str, NativeFn, NativeFn, Option<NativeFn>); 4] = [
+        (
+            "Byte",
+            "B",
+            jdk_internal_misc_unsafe_compare_and_exchange_byte,
+            jdk_internal_misc_unsafe_compare_and_set_byte,
+            Some(jdk_internal_misc_unsafe_get_and_add_byte),
+        ),
+        (
+            "Short",
+            "S",
+            jdk_internal_misc_unsafe_compare_and_exchange_short,
+            jdk_internal_misc_unsafe_compare_and_set_short,
+            Some(jdk_internal_misc_unsafe_get_and_add_short),
+        ),
+        (
+            "Char",
+            "C",
+            jdk_internal_misc_unsafe_compare_and_exchange_char,
+            jdk_internal_misc_unsafe_compare_and_set_char,
+            Some(jdk_internal_misc_unsafe_get_and_add_char),
+        ),
+        (
+            "Boolean",
+            "Z",
+            jdk_internal_misc_unsafe_compare_and_exchange_boolean,
+            jdk_internal_misc_unsafe_compare_and_set_boolean,
+            None,
+        ),
+    ];
+    for (type_name, desc, compare_and_exchange, compare_and_set, get_and_add) in atomics {
+        for suffix in ["", "Acquire", "Release"] {
+            native_registry.substitute(
+                FullyQualifiedMethodKey::new_with_str(
+                    "jdk/internal/misc/Unsafe",
+                    &format!("compareAndExchange{type_name}{suffix}"),
+                    &format!("(Ljava/lang/Object;J{desc}{desc}){desc}"),
+                    &native_registry.string_interner,
+                ),
+                compare_and_exchange,
+            );
+        }
+        let weak = ["", "Plain", "Acquire", "Release"]
+            .map(|suffix| format!("weakCompareAndSet{type_name}{suffix}"));
+        for name in std::iter::once(format!("compareAndSet{type_name}")).chain(weak) {
+            native_registry.substitute(
+                FullyQualifiedMethodKey::new_with_str(
+                    "jdk/internal/misc/Unsafe",
+                    &name,
+                    &format!("(Ljava/lang/Object;J{desc}{desc})Z"),
+                    &native_registry.string_interner,
+                ),
+                compare_and_set,
+            );
+        }
+        let Some(get_and_add) = get_and_add else {
+            continue;
+        };
+        for suffix in ["", "Acquire", "Release"] {
+            native_registry.substitute(
+                FullyQualifiedMethodKey::new_with_str(
+                    "jdk/internal/misc/Unsafe",
+                    &format!("getAndAdd{type_name}{suffix}"),
+                    &format!("(Ljava/lang/Object;J{desc}){desc}"),
+                    &native_registry.string_interner,
+                ),
+                get_and_add,
+            );
+        }
+    }
+}
+
+fn jdk_internal_misc_unsafe_compare_and_exchange_byte(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    Ok(Some(compare_and_exchange(vm, args, AllocationType::Byte)?))
+}
+
+fn jdk_internal_misc_unsafe_compare_and_set_byte(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    compare_and_set(vm, args, AllocationType::Byte)
+}
+
+fn jdk_internal_misc_unsafe_get_and_add_byte(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    Ok(Some(get_and_add(vm, args, AllocationType::Byte)?))
+}
+
+fn jdk_internal_misc_unsafe_compare_and_exchange_short(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    Ok(Some(compare_and_exchange(vm, args, AllocationType::Short)?))
+}
+
+fn jdk_internal_misc_unsafe_compare_and_set_short(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    compare_and_set(vm, args, AllocationType::Short)
+}
+
+fn jdk_internal_misc_unsafe_get_and_add_short(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    Ok(Some(get_and_add(vm, args, AllocationType::Short)?))
+}
+
+fn jdk_internal_misc_unsafe_compare_and_exchange_char(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    Ok(Some(compare_and_exchange(vm, args, AllocationType::Char)?))
+}
+
+fn jdk_internal_misc_unsafe_compare_and_set_char(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    compare_and_set(vm, args, AllocationType::Char)
+}
+
+fn jdk_internal_misc_unsafe_get_and_add_char(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    Ok(Some(get_and_add(vm, args, AllocationType::Char)?))
+}
+
+fn jdk_internal_misc_unsafe_compare_and_exchange_boolean(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    Ok(Some(compare_and_exchange(
+        vm,
+        args,
+        AllocationType::Boolean,
+    )?))
+}
+
+fn jdk_internal_misc_unsafe_compare_and_set_boolean(
+    vm: &VirtualMachine,
+    _thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    compare_and_set(vm, args, AllocationType::Boolean)
+}
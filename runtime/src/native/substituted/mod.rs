@@ -0,0 +1,128 @@
+use crate::error::JvmError;
+use crate::heap::HeapRef;
+use crate::interpreter::Interpreter;
+use crate::keys::MethodKey;
+use crate::native::substituted::java_lang_reflect::do_substitute_java_lang_reflect_natives;
+use crate::native::substituted::jdk_internal_loader::do_substitute_jdk_internal_loader_natives;
+use crate::native::substituted::jdk_internal_misc_unsafe::do_substitute_jdk_internal_misc_unsafe_natives;
+use crate::native::substituted::sun_net_www_protocol::do_substitute_sun_net_www_protocol_natives;
+use crate::native::{NativeRegistry, byte_array, load_and_initialize, new_object};
+use crate::thread::JavaThreadState;
+use crate::vm::Value;
+use crate::{VirtualMachine, throw_exception};
+
+mod java_lang_reflect;
+mod jdk_internal_loader;
+mod jdk_internal_misc_unsafe;
+mod sun_net_www_protocol;
+
+pub(super) fn substitute_natives(native_registry: &mut NativeRegistry) {
+    do_substitute_java_lang_reflect_natives(native_registry);
+    do_substitute_jdk_internal_loader_natives(native_registry);
+    do_substitute_jdk_internal_misc_unsafe_natives(native_registry);
+    do_substitute_sun_net_www_protocol_natives(native_registry);
+}
+
+fn string_arg(vm: &VirtualMachine, value: &Value) -> Result<String, JvmError> {
+    let Some(s) = value.as_nullable_obj_ref()? else {
+        throw_exception!(NullPointerException)?
+    };
+    vm.heap_read().get_rust_string_from_java_string(s)
+}
+
+fn invoke_static(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    class_name: &str,
+    name: &str,
+    desc: &str,
+    args: Vec<Value>,
+) -> Result<HeapRef, JvmError> {
+    let class_id = load_and_initialize(vm, thread, class_name)?;
+    let method_id = vm.method_area().get_static_method_id(
+        &class_id,
+        MethodKey {
+            name: vm.interner().get_or_intern(name),
+            desc: vm.interner().get_or_intern(desc),
+        },
+    )?;
+    Interpreter::invoke_static_method_for_value(thread, method_id, vm, args)?
+        .ok_or_else(|| JvmError::Todo(format!("{class_name}.{name} returned nothing")))?
+        .as_obj_ref()
+}
+
+/// `new URL(spec)`, or `null` without a URL.
+fn url(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    spec: Option<String>,
+) -> Result<Value, JvmError> {
+    let Some(spec) = spec else {
+        return Ok(Value::Null);
+    };
+    let spec_ref = vm.heap_write().alloc_string(&spec)?;
+    let url_ref = new_object(
+        vm,
+        thread,
+        "java/net/URL",
+        "(Ljava/lang/String;)V",
+        vec![Value::Ref(spec_ref)],
+    )?;
+    Ok(Value::Ref(url_ref))
+}
+
+/// `Collections.enumeration(Arrays.asList(urls))`
+fn url_enumeration(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    specs: Vec<String>,
+) -> Result<Value, JvmError> {
+    let mut urls = Vec::with_capacity(specs.len());
+    for spec in specs {
+        urls.push(url(vm, thread, Some(spec))?);
+    }
+    let array_class_id = vm
+        .method_area()
+        .get_class_id_or_load(vm.interner().get_or_intern("[Ljava/net/URL;"), thread.id)?;
+    let array_ref = vm
+        .heap_write()
+        .alloc_object_array(array_class_id, urls.len() as i32)?;
+    for (i, url) in urls.into_iter().enumerate() {
+        vm.heap_write()
+            .write_array_element(array_ref, i as i32, url)?;
+    }
+    let list_ref = invoke_static(
+        vm,
+        thread,
+        "java/util/Arrays",
+        "asList",
+        "([Ljava/lang/Object;)Ljava/util/List;",
+        vec![Value::Ref(array_ref)],
+    )?;
+    let enumeration_ref = invoke_static(
+        vm,
+        thread,
+        "java/util/Collections",
+        "enumeration",
+        "(Ljava/util/Collection;)Ljava/util/Enumeration;",
+        vec![Value::Ref(list_ref)],
+    )?;
+    Ok(Value::Ref(enumeration_ref))
+}
+
+/// `new ByteArrayInputStream(bytes)`
+fn input_stream(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    bytes: &[u8],
+) -> Result<Value, JvmError> {
+    let array_ref = byte_array(vm, bytes)?;
+    let stream_ref = new_object(
+        vm,
+        thread,
+        "java/io/ByteArrayInputStream",
+        "([B)V",
+        vec![Value::Ref(array_ref)],
+    )?;
+    Ok(Value::Ref(stream_ref))
+}
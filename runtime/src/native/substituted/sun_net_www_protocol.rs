@@ -0,0 +1,86 @@
+use crate::VirtualMachine;
+use crate::error::JvmError;
+use crate::heap::HeapRef;
+use crate::keys::{FieldKey, FullyQualifiedMethodKey};
+use crate::native::substituted::input_stream;
+use crate::native::{NativeRegistry, NativeRet};
+use crate::thread::JavaThreadState;
+use crate::vm::Value;
+use common::jtype::AllocationType;
+
+// The JDK reads `jrt:` URLs through `ImageReader` and `file:` and `jar:` URLs through file
+// streams and `ZipFile`, the VM reads them with its own runtime image and archive readers.
+pub(super) fn do_substitute_sun_net_www_protocol_natives(native_registry: &mut NativeRegistry) {
+    native_registry.substitute(
+        FullyQualifiedMethodKey::new_with_str(
+            "sun/net/www/protocol/jrt/JavaRuntimeURLConnection",
+            "getInputStream",
+            "()Ljava/io/InputStream;",
+            &native_registry.string_interner,
+        ),
+        sun_net_www_protocol_url_connection_get_input_stream,
+    );
+    native_registry.substitute(
+        FullyQualifiedMethodKey::new_with_str(
+            "sun/net/www/protocol/file/FileURLConnection",
+            "getInputStream",
+            "()Ljava/io/InputStream;",
+            &native_registry.string_interner,
+        ),
+        sun_net_www_protocol_url_connection_get_input_stream,
+    );
+    native_registry.substitute(
+        FullyQualifiedMethodKey::new_with_str(
+            "sun/net/www/protocol/jar/JarURLConnection",
+            "getInputStream",
+            "()Ljava/io/InputStream;",
+            &native_registry.string_interner,
+        ),
+        sun_net_www_protocol_url_connection_get_input_stream,
+    );
+}
+
+fn read_string_field(
+    vm: &VirtualMachine,
+    obj: HeapRef,
+    field_key: &FieldKey,
+) -> Result<String, JvmError> {
+    let class_id = vm.heap_read().get_class_id(obj)?;
+    let offset = vm
+        .method_area()
+        .get_instance_field(&class_id, field_key)?
+        .offset;
+    let value = vm
+        .heap_read()
+        .read_field(obj, offset, AllocationType::Reference)?;
+    match value.as_nullable_obj_ref()? {
+        Some(s) => vm.heap_read().get_rust_string_from_java_string(s),
+        None => Ok(String::new()),
+    }
+}
+
+/// The URL of a connection as `protocol:file`, which is how the VM makes them.
+fn connection_url(vm: &VirtualMachine, connection: HeapRef) -> Result<String, JvmError> {
+    let class_id = vm.heap_read().get_class_id(connection)?;
+    let offset = vm
+        .method_area()
+        .get_instance_field(&class_id, &vm.br().url_connection_url_fk)?
+        .offset;
+    let url = vm
+        .heap_read()
+        .read_field(connection, offset, AllocationType::Reference)?
+        .as_obj_ref()?;
+    let protocol = read_string_field(vm, url, &vm.br().url_protocol_fk)?;
+    let file = read_string_field(vm, url, &vm.br().url_file_fk)?;
+    Ok(format!("{protocol}:{file}"))
+}
+
+fn sun_net_www_protocol_url_connection_get_input_stream(
+    vm: &VirtualMachine,
+    thread: &mut JavaThreadState,
+    args: &[Value],
+) -> NativeRet {
+    let url = connection_url(vm, args[0].as_obj_ref()?)?;
+    let bytes = vm.method_area().class_loader().read_url(&url)?;
+    Ok(Some(input_stream(vm, thread, &bytes)?))
+}
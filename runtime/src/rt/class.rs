@@ -1,10 +1,11 @@
 use crate::error::JvmError;
+use crate::heap::HeapRef;
 use crate::heap::method_area::MethodArea;
 use crate::keys::{ClassId, FieldKey, MethodKey, ThreadId};
 use crate::rt::constant_pool::RuntimeConstantPool;
-use crate::rt::field::{InstanceField, StaticField};
+use crate::rt::field::{DeclaredField, InstanceField, StaticField};
 use crate::rt::method::Method;
-use crate::rt::{BaseClass, ClassLike, JvmClass};
+use crate::rt::{BaseClass, ClassLike, ClassMetadata, JvmClass, verifier};
 use crate::{MethodId, Symbol, build_exception, throw_exception};
 use jclass::ClassFile;
 use jclass::attribute::class::ClassAttr;
@@ -13,7 +14,7 @@ use jclass::field::FieldInfo;
 use jclass::flags::ClassFlags;
 use jclass::method::MethodInfo;
 use once_cell::sync::OnceCell;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 use std::sync::RwLock;
 
 pub struct InstanceClass {
@@ -37,13 +38,15 @@ pub struct InstanceClass {
 impl InstanceClass {
     fn load(
         super_id: Option<ClassId>,
-        method_area: &mut MethodArea,
+        method_area: &MethodArea,
         flags: ClassFlags,
         cp: RuntimeConstantPool,
         this_class: u16,
         attributes: Vec<ClassAttr>,
+        loader: Option<HeapRef>,
     ) -> Result<ClassId, JvmError> {
         let name = cp.get_class_sym(&this_class, method_area.interner())?;
+        let module = method_area.class_module(loader, name);
 
         //TODO: clean up
         let mut source_file = None;
@@ -54,8 +57,11 @@ impl InstanceClass {
             }
         }
 
+        let metadata =
+            ClassMetadata::from_attributes(name, &attributes, &cp, method_area.interner())?;
+
         let class = JvmClass::Instance(Box::new(Self {
-            base: BaseClass::new(name, flags, super_id, source_file),
+            base: BaseClass::new(name, flags, super_id, source_file, module, loader, metadata),
             cp,
             declared_method_index: OnceCell::new(),
             vtable: OnceCell::new(),
@@ -75,9 +81,10 @@ impl InstanceClass {
         methods: Vec<MethodInfo>,
         this_id: ClassId,
         super_id: Option<ClassId>,
-        method_area: &mut MethodArea,
+        method_area: &MethodArea,
     ) -> Result<(Vec<MethodId>, HashMap<MethodKey, u16>), JvmError> {
         let mut declared_index = HashMap::new();
+        let mut method_ids = Vec::with_capacity(methods.len());
         let (mut vtable, mut vtable_index) = super_id
             .map(|id| method_area.get_instance_class(&id))
             .transpose()?
@@ -101,22 +108,29 @@ impl InstanceClass {
             let descriptor_id = method_area
                 .get_or_new_method_descriptor_id(&method_key.desc)
                 .unwrap();
-            let method = Method::new(
+            let mut method = Method::new(
                 method,
                 this_id,
                 descriptor_id,
                 method_key.name,
                 method_key.desc,
             );
+            if method_area.is_substituted(this_id, &method_key) {
+                method.substitute_native();
+            }
             let is_static = method.is_static();
             let is_constructor = method_key.name == method_area.br().init_sym
                 || method_key.name == method_area.br().clinit_sym;
             let method_id = method_area.push_method(method);
+            method_ids.push(method_id);
 
             // TODO: need to think about private as well. Private methods should not be in vtable
             // but it can be called with invokevirtual from the same class...
             if !is_static && !is_constructor {
                 if let Some(pos) = vtable_index.get(&method_key) {
+                    let overridden_id = vtable[*pos as usize];
+                    method_area.check_final_override(method_id, overridden_id)?;
+                    method_area.add_override_loader_constraints(method_id, overridden_id)?;
                     vtable[*pos as usize] = method_id;
                 } else {
                     vtable_index.insert(method_key, vtable.len() as u16);
@@ -136,6 +150,7 @@ impl InstanceClass {
 
         let this = method_area.get_instance_class(&this_id)?;
         this.set_declared_methods(declared_index)?;
+        this.base.set_declared_method_ids(method_ids)?;
         Ok((vtable, vtable_index))
     }
 
@@ -143,7 +158,7 @@ impl InstanceClass {
         fields: Vec<FieldInfo>,
         this_id: ClassId,
         super_id: Option<ClassId>,
-        method_area: &mut MethodArea,
+        method_area: &MethodArea,
     ) -> Result<(), JvmError> {
         let mut instance_fields = super_id
             .map(|id| method_area.get_instance_class(&id))
@@ -170,15 +185,20 @@ impl InstanceClass {
             .transpose()?
             .unwrap_or_default();
         let mut static_fields = HashMap::new();
+        let mut declared_fields = Vec::with_capacity(fields.len());
 
         for field in fields {
-            let field_key = {
-                let cp = &method_area.get_instance_class(&this_id)?.cp;
-                FieldKey {
-                    name: cp.get_utf8_sym(&field.name_index, method_area.interner())?,
-                    desc: cp.get_utf8_sym(&field.descriptor_index, method_area.interner())?,
-                }
+            let cp = &method_area.get_instance_class(&this_id)?.cp;
+            let field_key = FieldKey {
+                name: cp.get_utf8_sym(&field.name_index, method_area.interner())?,
+                desc: cp.get_utf8_sym(&field.descriptor_index, method_area.interner())?,
             };
+            declared_fields.push(DeclaredField::new(
+                field_key,
+                &field,
+                cp,
+                method_area.interner(),
+            )?);
 
             let descriptor_id = method_area.get_or_new_field_descriptor_id(field_key.desc)?;
             let descriptor = method_area.get_field_descriptor(&descriptor_id);
@@ -216,6 +236,7 @@ impl InstanceClass {
         this.set_instance_fields_name_offset_map(instance_fields_name_offset_map)?;
         this.set_instance_size(instance_size)?;
         this.base.set_static_fields(static_fields)?;
+        this.base.set_declared_fields(declared_fields)?;
         Ok(())
     }
 
@@ -223,7 +244,8 @@ impl InstanceClass {
         interfaces: Vec<u16>,
         this_id: ClassId,
         super_id: Option<ClassId>,
-        method_area: &mut MethodArea,
+        method_area: &MethodArea,
+        loader: Option<HeapRef>,
         thread_id: ThreadId,
     ) -> Result<(), JvmError> {
         let mut interface_ids = super_id
@@ -232,14 +254,17 @@ impl InstanceClass {
             .map(|class| class.base.get_interfaces().cloned())
             .transpose()?
             .unwrap_or_default();
-        let mut direct_interfaces = HashSet::new();
+        let mut direct_interfaces = Vec::with_capacity(interfaces.len());
+        let this_name = method_area.get_class(&this_id).get_name();
 
         for interface in interfaces {
             let cp = &method_area.get_instance_class(&this_id)?.cp;
             let interface_name = cp.get_class_sym(&interface, method_area.interner())?;
-            let interface_id = method_area.get_class_id_or_load(interface_name, thread_id)?;
+            let interface_id =
+                method_area.get_class_id_or_load_in(loader, interface_name, thread_id)?;
+            method_area.check_superinterface(this_name, interface_id)?;
             interface_ids.insert(interface_id);
-            direct_interfaces.insert(interface_id);
+            direct_interfaces.push(interface_id);
 
             /* TODO: probably need to handle superinterfaces as well
                 something like:
@@ -259,7 +284,7 @@ impl InstanceClass {
     fn link_itable_and_vtable(
         this_id: ClassId,
         super_id: Option<ClassId>,
-        method_area: &mut MethodArea,
+        method_area: &MethodArea,
         mut vtable: Vec<MethodId>,
         mut vtable_index: HashMap<MethodKey, u16>,
     ) -> Result<(), JvmError> {
@@ -279,7 +304,11 @@ impl InstanceClass {
             let interface_methods = interface_class.get_methods();
             for (method_key, method_id) in interface_methods {
                 let impl_method_id = match vtable_index.get(method_key) {
-                    Some(&idx) => vtable[idx as usize],
+                    Some(&idx) => {
+                        let impl_method_id = vtable[idx as usize];
+                        method_area.add_override_loader_constraints(impl_method_id, *method_id)?;
+                        impl_method_id
+                    }
                     None => {
                         // not in vtable yet
                         if !method_area.get_method(method_id).is_abstract() {
@@ -320,7 +349,7 @@ impl InstanceClass {
         Ok(())
     }
 
-    fn prepare_cp(cp: ConstantPool, attr: &mut Vec<ClassAttr>) -> RuntimeConstantPool {
+    pub(crate) fn prepare_cp(cp: ConstantPool, attr: &mut Vec<ClassAttr>) -> RuntimeConstantPool {
         let methods = attr
             .iter()
             .position(|a| matches!(a, ClassAttr::BootstrapMethods(_)))
@@ -335,8 +364,9 @@ impl InstanceClass {
 
     pub fn load_and_link(
         mut cf: ClassFile,
-        method_area: &mut MethodArea,
+        method_area: &MethodArea,
         super_id: Option<ClassId>,
+        loader: Option<HeapRef>,
         thread_id: ThreadId,
     ) -> Result<ClassId, JvmError> {
         let runtime_cp = Self::prepare_cp(cf.cp, &mut cf.attributes);
@@ -347,16 +377,41 @@ impl InstanceClass {
             runtime_cp,
             cf.this_class,
             cf.attributes,
+            loader,
         )?;
 
+        if let Some(super_id) = super_id {
+            method_area.check_superclass(method_area.get_class(&this_id).get_name(), super_id)?;
+        }
+        let constraints = verifier::verify(
+            &cf.methods,
+            cf.major_version,
+            this_id,
+            method_area.get_cp(&this_id)?,
+            method_area,
+        )?;
+        let constraints = verifier::check_loaded_constraints(constraints, this_id, method_area)?;
+        let unchecked = !constraints.is_empty();
+        method_area
+            .get_class_like(&this_id)?
+            .set_verification_constraints(constraints)?;
         Self::link_fields(cf.fields, this_id, super_id, method_area)?;
         let (vtable, vtable_index) =
             Self::prepare_methods(cf.methods, this_id, super_id, method_area)?;
-        Self::link_interfaces(cf.interfaces, this_id, super_id, method_area, thread_id)?;
+        Self::link_interfaces(
+            cf.interfaces,
+            this_id,
+            super_id,
+            method_area,
+            loader,
+            thread_id,
+        )?;
         Self::link_itable_and_vtable(this_id, super_id, method_area, vtable, vtable_index)?;
 
-        let this = method_area.get_instance_class(&this_id)?;
-        this.set_linked();
+        // the other constraints are checked once the class is published and they can be loaded
+        if !unchecked {
+            method_area.get_instance_class(&this_id)?.set_linked();
+        }
         Ok(this_id)
     }
 
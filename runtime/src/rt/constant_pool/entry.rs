@@ -29,14 +29,6 @@ impl ClassEntry {
             name_sym: OnceCell::new(),
         }
     }
-
-    pub fn get_name_sym(&self) -> Result<Symbol, JvmError> {
-        if let Some(name_sym) = self.name_sym.get() {
-            Ok(*name_sym)
-        } else {
-            throw_exception!(InternalError, "ClassEntry name_sym not initialized")
-        }
-    }
 }
 
 pub(crate) struct StringEntry {
@@ -159,6 +151,20 @@ impl InvokeDynamicEntry {
     }
 }
 
+pub(crate) struct MethodTypeEntry {
+    pub descriptor_idx: u16,
+    pub descriptor_sym: OnceCell<Symbol>,
+}
+
+impl MethodTypeEntry {
+    pub fn new(descriptor_idx: u16) -> Self {
+        Self {
+            descriptor_idx,
+            descriptor_sym: OnceCell::new(),
+        }
+    }
+}
+
 pub(crate) struct NameAndTypeEntry {
     pub name_idx: u16,
     pub descriptor_idx: u16,
@@ -1,10 +1,14 @@
 use crate::error::JvmError;
+use crate::heap::HeapRef;
+use crate::keys::ClassId;
 use crate::rt::constant_pool::entry::{
     ClassEntry, FieldEntry, FieldEntryView, InvokeDynamicEntry, InvokeDynamicEntryView,
-    MethodEntry, MethodEntryView, MethodHandleEntryView, NameAndTypeEntry, NameAndTypeEntryView,
-    StringEntry, Utf8Entry,
+    MethodEntry, MethodEntryView, MethodHandleEntryView, MethodTypeEntry, NameAndTypeEntry,
+    NameAndTypeEntryView, StringEntry, Utf8Entry,
 };
-use crate::{Symbol, build_exception, throw_exception};
+use crate::vm::Value;
+use crate::{MethodId, Symbol, ThreadId, build_exception, throw_exception};
+use dashmap::{DashMap, DashSet};
 use jclass::attribute::class::BootstrapMethodEntry;
 use jclass::constant::ConstantInfo;
 use lasso::ThreadedRodeo;
@@ -24,6 +28,7 @@ pub enum RuntimeConstantType {
     String,
     Method,
     Field,
+    Dynamic,
     InvokeDynamic,
     InterfaceMethod,
     NameAndType,
@@ -46,6 +51,7 @@ impl Display for RuntimeConstantType {
             RuntimeConstantType::String => "String",
             RuntimeConstantType::Method => "Method",
             RuntimeConstantType::Field => "Field",
+            RuntimeConstantType::Dynamic => "Dynamic",
             RuntimeConstantType::InvokeDynamic => "InvokeDynamic",
             RuntimeConstantType::InterfaceMethod => "InterfaceMethod",
             RuntimeConstantType::NameAndType => "NameAndType",
@@ -81,10 +87,11 @@ pub enum RuntimeConstant {
     String(StringEntry),
     Method(MethodEntry),
     Field(FieldEntry),
+    Dynamic(InvokeDynamicEntry),
     InvokeDynamic(InvokeDynamicEntry),
     InterfaceMethod(MethodEntry),
     NameAndType(NameAndTypeEntry),
-    MethodType,
+    MethodType(MethodTypeEntry),
     MethodHandle(MethodHandleType), // TODO: use our own struct
 }
 
@@ -103,16 +110,40 @@ impl RuntimeConstant {
             RuntimeConstant::Field(_) => RuntimeConstantType::Field,
             RuntimeConstant::InterfaceMethod(_) => RuntimeConstantType::InterfaceMethod,
             RuntimeConstant::NameAndType(_) => RuntimeConstantType::NameAndType,
+            RuntimeConstant::Dynamic(_) => RuntimeConstantType::Dynamic,
             RuntimeConstant::InvokeDynamic(_) => RuntimeConstantType::InvokeDynamic,
-            RuntimeConstant::MethodType => RuntimeConstantType::MethodType,
+            RuntimeConstant::MethodType(_) => RuntimeConstantType::MethodType,
             RuntimeConstant::MethodHandle(_) => RuntimeConstantType::MethodHandle,
         }
     }
 }
 
+/// A signature polymorphic call site linked by `MethodHandleNatives.linkMethod`: the adapter
+/// method invoked instead, with the appendix as its trailing argument.
+#[derive(Copy, Clone)]
+pub struct LinkedCallSite {
+    pub adapter: MethodId,
+    pub appendix: Option<HeapRef>,
+}
+
 pub struct RuntimeConstantPool {
     entries: Vec<RuntimeConstant>,
     bootstrap_entries: Vec<BootstrapMethodEntry>,
+    /// Objects the method handle and method type constants resolved to, by constant index.
+    /// Like the resolved references of hotspot they are resolved once and kept alive by the
+    /// class.
+    resolved_references: DashMap<u16, HeapRef>,
+    /// Classes the class constants resolved to, access checked, by constant index.
+    resolved_classes: DashMap<u16, ClassId>,
+    /// Signature polymorphic call sites by the index of their method constant.
+    linked_call_sites: DashMap<u16, LinkedCallSite>,
+    /// Values the dynamically-computed constants resolved to, primitives unboxed.
+    resolved_dynamic_constants: DashMap<u16, Value>,
+    /// `LinkageError`s the dynamically-computed constants failed to resolve with, thrown again
+    /// by later attempts (§5.4.3).
+    dynamic_constant_errors: DashMap<u16, HeapRef>,
+    /// Dynamically-computed constants each thread is running the bootstrap method of.
+    resolving_dynamic_constants: DashSet<(ThreadId, u16)>,
 }
 
 impl RuntimeConstantPool {
@@ -142,13 +173,21 @@ impl RuntimeConstantPool {
                 ConstantInfo::InterfaceMethodRef(ref_info) => RuntimeConstant::InterfaceMethod(
                     MethodEntry::new(ref_info.class_index, ref_info.name_and_type_index),
                 ),
+                ConstantInfo::Dynamic(dynamic_info) => {
+                    RuntimeConstant::Dynamic(InvokeDynamicEntry::new(
+                        dynamic_info.bootstrap_method_attr_index,
+                        dynamic_info.name_and_type_index,
+                    ))
+                }
                 ConstantInfo::InvokeDynamic(dynamic_info) => {
                     RuntimeConstant::InvokeDynamic(InvokeDynamicEntry::new(
                         dynamic_info.bootstrap_method_attr_index,
                         dynamic_info.name_and_type_index,
                     ))
                 }
-                ConstantInfo::MethodType(_) => RuntimeConstant::MethodType,
+                ConstantInfo::MethodType(idx) => {
+                    RuntimeConstant::MethodType(MethodTypeEntry::new(idx))
+                }
                 // TODO: handle could have already mapped MethodHandleKind enum instead of u8
                 ConstantInfo::MethodHandle(handle) => {
                     let method_handle_type = match handle.reference_kind {
@@ -177,9 +216,20 @@ impl RuntimeConstantPool {
         Self {
             entries: rt_entries,
             bootstrap_entries: bootstrap_methods,
+            resolved_references: DashMap::new(),
+            resolved_classes: DashMap::new(),
+            linked_call_sites: DashMap::new(),
+            resolved_dynamic_constants: DashMap::new(),
+            dynamic_constant_errors: DashMap::new(),
+            resolving_dynamic_constants: DashSet::new(),
         }
     }
 
+    /// Number of entries, index 0 included.
+    pub fn size(&self) -> usize {
+        self.entries.len()
+    }
+
     pub fn get_constant(
         &self,
         idx: &u16,
@@ -368,12 +418,12 @@ impl RuntimeConstantPool {
                     MethodHandleType::InvokeVirtual(idx) => {
                         MethodHandleEntryView::InvokeVirtual(self.get_method_view(idx, interner)?)
                     }
-                    MethodHandleType::InvokeStatic(idx) => {
-                        MethodHandleEntryView::InvokeStatic(self.get_method_view(idx, interner)?)
-                    }
-                    MethodHandleType::InvokeSpecial(idx) => {
-                        MethodHandleEntryView::InvokeSpecial(self.get_method_view(idx, interner)?)
-                    }
+                    MethodHandleType::InvokeStatic(idx) => MethodHandleEntryView::InvokeStatic(
+                        self.get_method_or_interface_method_view(idx, interner)?,
+                    ),
+                    MethodHandleType::InvokeSpecial(idx) => MethodHandleEntryView::InvokeSpecial(
+                        self.get_method_or_interface_method_view(idx, interner)?,
+                    ),
                     MethodHandleType::NewInvokeSpecial(idx) => {
                         MethodHandleEntryView::NewInvokeSpecial(
                             self.get_method_view(idx, interner)?,
@@ -403,17 +453,7 @@ impl RuntimeConstantPool {
     ) -> Result<InvokeDynamicEntryView, JvmError> {
         match self.entry(idx)? {
             // TODO: need to review all structs, for method handle as well
-            RuntimeConstant::InvokeDynamic(entry) => {
-                let bootstrap_entry = self.bootstrap_entry(&entry.bootstrap_idx)?;
-                let method_handle_view =
-                    self.get_method_handle_view(&bootstrap_entry.bootstrap_method_idx, interner)?;
-                let nat_view = self.get_nat_view(&entry.nat_idx, interner)?;
-                Ok(InvokeDynamicEntryView::new(
-                    method_handle_view,
-                    bootstrap_entry.bootstrap_arguments.clone(),
-                    nat_view,
-                ))
-            }
+            RuntimeConstant::InvokeDynamic(entry) => self.bootstrap_specifier_view(entry, interner),
             other => throw_exception!(
                 IncompatibleClassChangeError,
                 pool_idx: *idx,
@@ -423,6 +463,144 @@ impl RuntimeConstantPool {
         }
     }
 
+    /// The bootstrap method, its static arguments and the name and field descriptor of the
+    /// dynamically-computed constant at `idx`.
+    pub fn get_dynamic_view(
+        &self,
+        idx: &u16,
+        interner: &ThreadedRodeo,
+    ) -> Result<InvokeDynamicEntryView, JvmError> {
+        match self.entry(idx)? {
+            RuntimeConstant::Dynamic(entry) => self.bootstrap_specifier_view(entry, interner),
+            other => throw_exception!(
+                IncompatibleClassChangeError,
+                pool_idx: *idx,
+                expected: RuntimeConstantType::Dynamic,
+                actual: other.get_type()
+            ),
+        }
+    }
+
+    fn bootstrap_specifier_view(
+        &self,
+        entry: &InvokeDynamicEntry,
+        interner: &ThreadedRodeo,
+    ) -> Result<InvokeDynamicEntryView, JvmError> {
+        let bootstrap_entry = self.bootstrap_entry(&entry.bootstrap_idx)?;
+        let method_handle_view =
+            self.get_method_handle_view(&bootstrap_entry.bootstrap_method_idx, interner)?;
+        let nat_view = self.get_nat_view(&entry.nat_idx, interner)?;
+        Ok(InvokeDynamicEntryView::new(
+            method_handle_view,
+            bootstrap_entry.bootstrap_arguments.clone(),
+            nat_view,
+        ))
+    }
+
+    pub fn get_method_type_sym(
+        &self,
+        idx: &u16,
+        interner: &ThreadedRodeo,
+    ) -> Result<Symbol, JvmError> {
+        match self.entry(idx)? {
+            RuntimeConstant::MethodType(entry) => entry
+                .descriptor_sym
+                .get_or_try_init(|| self.get_utf8_sym(&entry.descriptor_idx, interner))
+                .copied(),
+            other => throw_exception!(
+                IncompatibleClassChangeError,
+                pool_idx: *idx,
+                expected: RuntimeConstantType::MethodType,
+                actual: other.get_type()
+            ),
+        }
+    }
+
+    /// The object the constant at `idx` resolved to, if it was resolved.
+    pub fn get_resolved_reference(&self, idx: &u16) -> Option<HeapRef> {
+        self.resolved_references.get(idx).map(|entry| *entry)
+    }
+
+    /// Records what the constant at `idx` resolved to. When two threads resolve it at the same
+    /// time the first result wins and is returned to both.
+    pub fn set_resolved_reference(&self, idx: u16, heap_ref: HeapRef) -> HeapRef {
+        *self.resolved_references.entry(idx).or_insert(heap_ref)
+    }
+
+    /// The class the class constant at `idx` resolved to, if it was resolved.
+    pub fn get_resolved_class(&self, idx: &u16) -> Option<ClassId> {
+        self.resolved_classes.get(idx).map(|entry| *entry)
+    }
+
+    /// Records the class the class constant at `idx` resolved to, the first one wins like for
+    /// `set_resolved_reference`.
+    pub fn set_resolved_class(&self, idx: u16, class_id: ClassId) -> ClassId {
+        *self.resolved_classes.entry(idx).or_insert(class_id)
+    }
+
+    pub fn get_linked_call_site(&self, idx: &u16) -> Option<LinkedCallSite> {
+        self.linked_call_sites.get(idx).map(|entry| *entry)
+    }
+
+    pub fn set_linked_call_site(&self, idx: u16, call_site: LinkedCallSite) -> LinkedCallSite {
+        *self.linked_call_sites.entry(idx).or_insert(call_site)
+    }
+
+    /// The value the dynamically-computed constant at `idx` resolved to, if it was resolved.
+    pub fn get_resolved_dynamic_constant(&self, idx: &u16) -> Option<Value> {
+        self.resolved_dynamic_constants.get(idx).map(|entry| *entry)
+    }
+
+    /// Records the value of the dynamically-computed constant at `idx`, the first one wins like
+    /// for `set_resolved_reference`.
+    pub fn set_resolved_dynamic_constant(&self, idx: u16, value: Value) -> Value {
+        *self.resolved_dynamic_constants.entry(idx).or_insert(value)
+    }
+
+    /// The error resolving the dynamically-computed constant at `idx` failed with, if it failed.
+    pub fn get_dynamic_constant_error(&self, idx: &u16) -> Option<HeapRef> {
+        self.dynamic_constant_errors.get(idx).map(|entry| *entry)
+    }
+
+    /// Records the error resolving the dynamically-computed constant at `idx` failed with, the
+    /// first one wins like for `set_resolved_reference`.
+    pub fn set_dynamic_constant_error(&self, idx: u16, error: HeapRef) -> HeapRef {
+        *self.dynamic_constant_errors.entry(idx).or_insert(error)
+    }
+
+    /// Marks the dynamically-computed constant at `idx` as being resolved by `thread_id`,
+    /// `false` if the thread is resolving it already, when resolving it takes its own value.
+    pub fn begin_dynamic_constant_resolution(&self, idx: u16, thread_id: ThreadId) -> bool {
+        self.resolving_dynamic_constants.insert((thread_id, idx))
+    }
+
+    pub fn end_dynamic_constant_resolution(&self, idx: u16, thread_id: ThreadId) {
+        self.resolving_dynamic_constants.remove(&(thread_id, idx));
+    }
+
+    /// The resolved constants, appendices of linked call sites and resolution errors of
+    /// dynamically-computed constants, for the collector.
+    pub fn references(&self) -> Vec<HeapRef> {
+        self.resolved_references
+            .iter()
+            .map(|entry| *entry)
+            .chain(
+                self.linked_call_sites
+                    .iter()
+                    .filter_map(|entry| entry.appendix),
+            )
+            .chain(
+                self.resolved_dynamic_constants
+                    .iter()
+                    .filter_map(|entry| match *entry {
+                        Value::Ref(heap_ref) => Some(heap_ref),
+                        _ => None,
+                    }),
+            )
+            .chain(self.dynamic_constant_errors.iter().map(|entry| *entry))
+            .collect()
+    }
+
     pub fn get_string_sym(&self, idx: &u16, interner: &ThreadedRodeo) -> Result<Symbol, JvmError> {
         match self.entry(idx)? {
             RuntimeConstant::String(entry) => entry
@@ -438,6 +616,23 @@ impl RuntimeConstantPool {
         }
     }
 
+    /// Index of the class constant a class, field or method constant at `idx` refers to.
+    pub fn get_class_idx(&self, idx: &u16) -> Result<u16, JvmError> {
+        match self.entry(idx)? {
+            RuntimeConstant::Class(_) => Ok(*idx),
+            RuntimeConstant::Field(entry) => Ok(entry.class_idx),
+            RuntimeConstant::Method(entry) | RuntimeConstant::InterfaceMethod(entry) => {
+                Ok(entry.class_idx)
+            }
+            other => throw_exception!(
+                IncompatibleClassChangeError,
+                pool_idx: *idx,
+                expected: RuntimeConstantType::Class,
+                actual: other.get_type()
+            ),
+        }
+    }
+
     pub fn get_class_sym(&self, idx: &u16, interner: &ThreadedRodeo) -> Result<Symbol, JvmError> {
         match self.entry(idx)? {
             RuntimeConstant::Class(entry) => entry
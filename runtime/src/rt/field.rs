@@ -1,6 +1,14 @@
-use crate::keys::{ClassId, FieldDescriptorId};
+use crate::Symbol;
+use crate::error::JvmError;
+use crate::keys::{ClassId, FieldDescriptorId, FieldKey};
+use crate::rt::RawAnnotations;
+use crate::rt::constant_pool::RuntimeConstantPool;
 use crate::vm::Value;
+use jclass::attribute::SharedAttribute;
+use jclass::attribute::field::FieldAttribute;
+use jclass::field::FieldInfo;
 use jclass::flags::FieldFlags;
+use lasso::ThreadedRodeo;
 use std::sync::RwLock;
 
 #[derive(Debug, Copy, Clone)]
@@ -17,3 +25,44 @@ pub struct StaticField {
     pub descriptor: FieldDescriptorId,
     pub value: RwLock<Value>,
 }
+
+/// A field as declared in the class file.
+#[derive(Debug, Clone)]
+pub struct DeclaredField {
+    pub key: FieldKey,
+    pub flags: FieldFlags,
+    /// Generic signature.
+    pub signature: Option<Symbol>,
+    pub annotations: RawAnnotations,
+}
+
+impl DeclaredField {
+    pub(crate) fn new(
+        key: FieldKey,
+        field: &FieldInfo,
+        cp: &RuntimeConstantPool,
+        interner: &ThreadedRodeo,
+    ) -> Result<Self, JvmError> {
+        let signature = field
+            .attributes
+            .iter()
+            .find_map(|attr| match attr {
+                FieldAttribute::Shared(SharedAttribute::Signature(idx)) => Some(idx),
+                _ => None,
+            })
+            .map(|idx| cp.get_utf8_sym(idx, interner))
+            .transpose()?;
+        let mut annotations = RawAnnotations::default();
+        for attr in &field.attributes {
+            if let FieldAttribute::Shared(shared) = attr {
+                annotations.add(shared);
+            }
+        }
+        Ok(Self {
+            key,
+            flags: field.access_flags,
+            signature,
+            annotations,
+        })
+    }
+}
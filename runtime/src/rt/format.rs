@@ -0,0 +1,398 @@
+use crate::build_exception;
+use crate::error::JvmError;
+use common::error::ClassFormatErr;
+use jclass::ClassFile;
+use jclass::attribute::method::MethodAttribute;
+use jclass::flags::{ClassFlags, FieldFlags, MethodFlags};
+use std::collections::HashSet;
+
+const JAVA_LANG_OBJECT: &str = "java/lang/Object";
+const INIT: &str = "<init>";
+const CLINIT: &str = "<clinit>";
+
+/// First version where interface methods may be private or static.
+const JAVA_8_VERSION: u16 = 52;
+/// `ACC_STRICT` is only defined for versions 46 to 60.
+const STRICT_VERSIONS: std::ops::RangeInclusive<u16> = 46..=60;
+/// First version where a `<clinit>` that is not static is a format error.
+const JAVA_7_VERSION: u16 = 51;
+
+/// Parses a class file, a malformed one is a `ClassFormatError`.
+pub(crate) fn parse_class_file(bytes: Vec<u8>) -> Result<ClassFile, JvmError> {
+    ClassFile::try_from(bytes).map_err(|e| {
+        let message = match e {
+            ClassFormatErr::WrongMagic(magic) => {
+                format!("Incompatible magic value {magic} in class file")
+            }
+            ClassFormatErr::TrailingBytes => "Extra bytes at the end of class file".to_string(),
+            ClassFormatErr::Cursor(_) => "Truncated class file".to_string(),
+            ClassFormatErr::UnknownTag(tag) => format!("Unknown constant tag {tag} in class file"),
+            ClassFormatErr::ConstantNotFound(idx) => {
+                format!("Invalid constant pool index {idx} in class file")
+            }
+            ClassFormatErr::TypeError(idx, expected, actual) => format!(
+                "Invalid constant pool index {idx} in class file: expected {expected}, found {actual}"
+            ),
+            ClassFormatErr::UnknownStackFrameType(frame_type) => {
+                format!("Unknown frame type {frame_type} in StackMapTable")
+            }
+            other => format!("{other:?}"),
+        };
+        build_exception!(ClassFormatError, message)
+    })
+}
+
+/// https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.8
+///
+/// The static constraints of a class file that parsing alone doesn't check: names, descriptors,
+/// flag combinations, the superclass, duplicated members and `Code` attributes.
+pub(crate) fn check_format(cf: &ClassFile) -> Result<(), JvmError> {
+    let name = cf.get_class_name().map_err(|_| {
+        build_exception!(
+            ClassFormatError,
+            "Invalid this_class index {} in class file",
+            cf.this_class
+        )
+    })?;
+    if !is_class_name(name) {
+        return Err(build_exception!(
+            ClassFormatError,
+            "Illegal class name \"{name}\" in class file {name}"
+        ));
+    }
+    check_class_flags(cf.access_flags, name)?;
+    check_super_class(cf, name)?;
+    check_interfaces(cf, name)?;
+    check_fields(cf, name)?;
+    check_methods(cf, name)
+}
+
+fn check_class_flags(flags: ClassFlags, name: &str) -> Result<(), JvmError> {
+    if flags.is_module() {
+        return Err(build_exception!(
+            NoClassDefFoundError,
+            "{name} is not a class because access_flag ACC_MODULE is set"
+        ));
+    }
+    let legal = if flags.is_interface() {
+        flags.is_abstract() && !flags.is_final() && !flags.is_super() && !flags.is_enum()
+    } else {
+        !(flags.is_annotation() || flags.is_final() && flags.is_abstract())
+    };
+    if !legal {
+        return Err(build_exception!(
+            ClassFormatError,
+            "Illegal class modifiers in class {name}: 0x{:X}",
+            flags.get_raw()
+        ));
+    }
+    Ok(())
+}
+
+fn check_super_class(cf: &ClassFile, name: &str) -> Result<(), JvmError> {
+    let Some(super_name) = cf.get_super_class_name() else {
+        if name == JAVA_LANG_OBJECT {
+            return Ok(());
+        }
+        return Err(build_exception!(
+            ClassFormatError,
+            "Invalid superclass index 0 in class file {name}"
+        ));
+    };
+    let super_name = super_name.map_err(|_| {
+        build_exception!(
+            ClassFormatError,
+            "Invalid superclass index {} in class file {name}",
+            cf.super_class
+        )
+    })?;
+    if name == JAVA_LANG_OBJECT {
+        return Err(build_exception!(
+            ClassFormatError,
+            "java/lang/Object must not have a superclass in class file {name}"
+        ));
+    }
+    if super_name.starts_with('[') {
+        return Err(build_exception!(
+            ClassFormatError,
+            "Bad superclass name in class file {name}"
+        ));
+    }
+    if cf.access_flags.is_interface() && super_name != JAVA_LANG_OBJECT {
+        return Err(build_exception!(
+            ClassFormatError,
+            "Interfaces must have java.lang.Object as superclass in class file {name}"
+        ));
+    }
+    Ok(())
+}
+
+fn check_interfaces(cf: &ClassFile, name: &str) -> Result<(), JvmError> {
+    let mut seen = HashSet::with_capacity(cf.interfaces.len());
+    for interface in &cf.interfaces {
+        let interface_name = cf.cp.get_class_name(interface).map_err(|_| {
+            build_exception!(
+                ClassFormatError,
+                "Interface name has bad constant pool index {interface} in class file {name}"
+            )
+        })?;
+        if interface_name.starts_with('[') {
+            return Err(build_exception!(
+                ClassFormatError,
+                "Bad interface name \"{interface_name}\" in class file {name}"
+            ));
+        }
+        if !seen.insert(interface_name) {
+            return Err(build_exception!(
+                ClassFormatError,
+                "Duplicate interface name \"{interface_name}\" in class file {name}"
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn check_fields(cf: &ClassFile, name: &str) -> Result<(), JvmError> {
+    let is_interface = cf.access_flags.is_interface();
+    let mut seen = HashSet::with_capacity(cf.fields.len());
+    for field in &cf.fields {
+        let (field_name, desc) = member_strings(cf, field.name_index, field.descriptor_index)?;
+        if !is_unqualified_name(field_name) {
+            return Err(build_exception!(
+                ClassFormatError,
+                "Illegal field name \"{field_name}\" in class {name}"
+            ));
+        }
+        if !is_field_descriptor(desc) {
+            return Err(build_exception!(
+                ClassFormatError,
+                "Field \"{field_name}\" in class {name} has illegal signature \"{desc}\""
+            ));
+        }
+        if !legal_field_flags(field.access_flags, is_interface) {
+            return Err(build_exception!(
+                ClassFormatError,
+                "Illegal field modifiers in class {name}: 0x{:X}",
+                field.access_flags.get_raw()
+            ));
+        }
+        if !seen.insert((field_name, desc)) {
+            return Err(build_exception!(
+                ClassFormatError,
+                "Duplicate field name \"{field_name}\" with signature \"{desc}\" in class file {name}"
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn legal_field_flags(flags: FieldFlags, is_interface: bool) -> bool {
+    let access = [flags.is_public(), flags.is_private(), flags.is_protected()];
+    if access.iter().filter(|set| **set).count() > 1 {
+        return false;
+    }
+    if is_interface {
+        return flags.is_public()
+            && flags.is_static()
+            && flags.is_final()
+            && !flags.is_volatile()
+            && !flags.is_transient()
+            && !flags.is_enum();
+    }
+    !(flags.is_final() && flags.is_volatile())
+}
+
+fn check_methods(cf: &ClassFile, name: &str) -> Result<(), JvmError> {
+    let is_interface = cf.access_flags.is_interface();
+    let mut seen = HashSet::with_capacity(cf.methods.len());
+    for method in &cf.methods {
+        let (method_name, desc) = member_strings(cf, method.name_index, method.descriptor_index)?;
+        let flags = method.access_flags;
+        let is_init = method_name == INIT;
+        let is_clinit = method_name == CLINIT;
+        if !(is_init || is_clinit || is_unqualified_method_name(method_name)) {
+            return Err(build_exception!(
+                ClassFormatError,
+                "Illegal method name \"{method_name}\" in class {name}"
+            ));
+        }
+        if is_init && is_interface {
+            return Err(build_exception!(
+                ClassFormatError,
+                "Interface cannot have a method named <init>, class file {name}"
+            ));
+        }
+        let args_size = method_signature(desc)
+            .filter(|(args_size, is_void)| {
+                !(is_init || is_clinit) || *is_void && (!is_clinit || *args_size == 0)
+            })
+            .map(|(args_size, _)| args_size);
+        let Some(args_size) = args_size else {
+            return Err(build_exception!(
+                ClassFormatError,
+                "Method \"{method_name}\" in class {name} has illegal signature \"{desc}\""
+            ));
+        };
+        if args_size + usize::from(!flags.is_static()) > 255 {
+            return Err(build_exception!(
+                ClassFormatError,
+                "Too many arguments in method signature in class file {name}"
+            ));
+        }
+        if is_clinit && !flags.is_static() && cf.major_version >= JAVA_7_VERSION {
+            return Err(build_exception!(
+                ClassFormatError,
+                "Method <clinit> is not static in class file {name}"
+            ));
+        }
+        // the flags of <clinit> are ignored, other than ACC_STATIC
+        if !is_clinit && !legal_method_flags(flags, is_interface, is_init, cf.major_version) {
+            return Err(build_exception!(
+                ClassFormatError,
+                "Method {method_name} in class {name} has illegal modifiers: 0x{:X}",
+                flags.get_raw()
+            ));
+        }
+        if !seen.insert((method_name, desc)) {
+            return Err(build_exception!(
+                ClassFormatError,
+                "Duplicate method name \"{method_name}\" with signature \"{desc}\" in class file {name}"
+            ));
+        }
+        let has_code = method
+            .attributes
+            .iter()
+            .any(|attr| matches!(attr, MethodAttribute::Code(_)));
+        let needs_code = !flags.is_abstract() && !flags.is_native();
+        if has_code && !needs_code {
+            return Err(build_exception!(
+                ClassFormatError,
+                "Code attribute in native or abstract methods in class file {name}"
+            ));
+        }
+        if !has_code && needs_code {
+            return Err(build_exception!(
+                ClassFormatError,
+                "Absent Code attribute in method that is not native or abstract in class file {name}"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.6
+fn legal_method_flags(flags: MethodFlags, is_interface: bool, is_init: bool, major: u16) -> bool {
+    let access = [flags.is_public(), flags.is_private(), flags.is_protected()];
+    let access_count = access.iter().filter(|set| **set).count();
+    if access_count > 1 {
+        return false;
+    }
+    let is_strict = flags.is_strict() && STRICT_VERSIONS.contains(&major);
+    if is_interface {
+        let legal_access = if major >= JAVA_8_VERSION {
+            access_count == 1 && !flags.is_protected()
+        } else {
+            flags.is_public() && flags.is_abstract()
+        };
+        if !legal_access || flags.is_final() || flags.is_synchronized() || flags.is_native() {
+            return false;
+        }
+    }
+    if is_init
+        && (flags.is_static()
+            || flags.is_final()
+            || flags.is_synchronized()
+            || flags.is_bridge()
+            || flags.is_native()
+            || flags.is_abstract())
+    {
+        return false;
+    }
+    !(flags.is_abstract()
+        && (flags.is_private()
+            || flags.is_static()
+            || flags.is_final()
+            || flags.is_synchronized()
+            || flags.is_native()
+            || is_strict))
+}
+
+fn member_strings(
+    cf: &ClassFile,
+    name_index: u16,
+    descriptor_index: u16,
+) -> Result<(&str, &str), JvmError> {
+    let utf8 = |idx: u16| {
+        cf.cp.get_utf8(&idx).map_err(|_| {
+            build_exception!(
+                ClassFormatError,
+                "Invalid constant pool index {idx} in class file {}",
+                cf.get_class_name().unwrap_or_default()
+            )
+        })
+    };
+    Ok((utf8(name_index)?, utf8(descriptor_index)?))
+}
+
+/// https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.2.2
+fn is_unqualified_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains(['.', ';', '[', '/'])
+}
+
+fn is_unqualified_method_name(name: &str) -> bool {
+    is_unqualified_name(name) && !name.contains(['<', '>'])
+}
+
+/// https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.2.1
+///
+/// Internal name of a class or interface, or the descriptor of an array class.
+fn is_class_name(name: &str) -> bool {
+    if name.starts_with('[') {
+        return is_field_descriptor(name);
+    }
+    name.split('/').all(is_unqualified_name)
+}
+
+/// Length of the field descriptor at the start of `desc`, `None` if there is none.
+fn field_descriptor_len(desc: &str) -> Option<usize> {
+    let dims = desc.bytes().take_while(|b| *b == b'[').count();
+    if dims > 255 {
+        return None;
+    }
+    match desc.as_bytes().get(dims)? {
+        b'Z' | b'B' | b'C' | b'S' | b'I' | b'F' | b'J' | b'D' => Some(dims + 1),
+        b'L' => {
+            let end = desc[dims..].find(';')? + dims;
+            let class = &desc[dims + 1..end];
+            (!class.starts_with('[') && is_class_name(class)).then_some(end + 1)
+        }
+        _ => None,
+    }
+}
+
+/// https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.3.2
+fn is_field_descriptor(desc: &str) -> bool {
+    field_descriptor_len(desc) == Some(desc.len())
+}
+
+/// https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.3.3
+///
+/// Number of local variable slots taken by the parameters and whether the method is void,
+/// `None` for a malformed descriptor.
+fn method_signature(desc: &str) -> Option<(usize, bool)> {
+    let mut rest = desc.strip_prefix('(')?;
+    let mut size = 0;
+    loop {
+        if let Some(ret) = rest.strip_prefix(')') {
+            let is_void = ret == "V";
+            return (is_void || is_field_descriptor(ret)).then_some((size, is_void));
+        }
+        let len = field_descriptor_len(rest)?;
+        size += if matches!(&rest[..len], "J" | "D") {
+            2
+        } else {
+            1
+        };
+        rest = &rest[len..];
+    }
+}
@@ -0,0 +1,83 @@
+use crate::error::JvmError;
+use crate::keys::{ClassId, ThreadId};
+use crate::rt::{ClassLike, ClassState};
+use std::collections::HashMap;
+use std::sync::{Condvar, Mutex};
+use std::time::Duration;
+
+/// How long a thread waiting for another thread's `<clinit>` sleeps before it checks for
+/// safepoint requests.
+const INIT_WAIT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// What the caller of [`ClassInitTable::begin`] has to do with the class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InitAction {
+    /// The current thread now owns the initialization and must call [`ClassInitTable::finish`].
+    Initialize,
+    /// The class is initialized, or the current thread is already running its `<clinit>`.
+    Done,
+    /// An earlier initialization attempt failed, the caller throws `NoClassDefFoundError`.
+    Erroneous,
+}
+
+/// The initialization lock of JVMS §5.5, shared by all classes. Tracks which thread runs
+/// the `<clinit>` of each class that is being initialized.
+#[derive(Default)]
+pub struct ClassInitTable {
+    initializing: Mutex<HashMap<ClassId, ThreadId>>,
+    finished: Condvar,
+}
+
+impl ClassInitTable {
+    /// Steps 1-6 of §5.5: waits while another thread initializes the class, then either
+    /// claims the initialization for `thread_id` or reports that there is nothing to do.
+    /// `on_blocked` is invoked periodically (without the lock held) while waiting.
+    pub fn begin(
+        &self,
+        class: &dyn ClassLike,
+        class_id: ClassId,
+        thread_id: ThreadId,
+        mut on_blocked: impl FnMut() -> Result<(), JvmError>,
+    ) -> Result<InitAction, JvmError> {
+        let mut initializing = self.initializing.lock().unwrap();
+        loop {
+            match class.state() {
+                ClassState::Initialized => return Ok(InitAction::Done),
+                ClassState::Erroneous => return Ok(InitAction::Erroneous),
+                ClassState::Initializing => {
+                    if initializing.get(&class_id) == Some(&thread_id) {
+                        // recursive request, e.g. <clinit> touching its own static field
+                        return Ok(InitAction::Done);
+                    }
+                    initializing = self
+                        .finished
+                        .wait_timeout(initializing, INIT_WAIT_POLL_INTERVAL)
+                        .unwrap()
+                        .0;
+                    drop(initializing);
+                    on_blocked()?;
+                    initializing = self.initializing.lock().unwrap();
+                }
+                state @ (ClassState::Loaded | ClassState::Linked) => {
+                    if class.transition(state, ClassState::Initializing) {
+                        initializing.insert(class_id, thread_id);
+                        return Ok(InitAction::Initialize);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Steps 10-12 of §5.5: publishes the outcome and wakes up waiting threads.
+    pub fn finish(&self, class: &dyn ClassLike, class_id: ClassId, initialized: bool) {
+        let mut initializing = self.initializing.lock().unwrap();
+        let state = if initialized {
+            ClassState::Initialized
+        } else {
+            ClassState::Erroneous
+        };
+        class.transition(ClassState::Initializing, state);
+        initializing.remove(&class_id);
+        self.finished.notify_all();
+    }
+}
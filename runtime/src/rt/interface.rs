@@ -1,11 +1,12 @@
 use crate::MethodId;
 use crate::error::JvmError;
+use crate::heap::HeapRef;
 use crate::heap::method_area::MethodArea;
 use crate::keys::{ClassId, FieldKey, MethodKey, ThreadId};
 use crate::rt::constant_pool::RuntimeConstantPool;
-use crate::rt::field::StaticField;
+use crate::rt::field::{DeclaredField, StaticField};
 use crate::rt::method::Method;
-use crate::rt::{BaseClass, ClassLike, JvmClass};
+use crate::rt::{BaseClass, ClassLike, ClassMetadata, JvmClass, verifier};
 use jclass::ClassFile;
 use jclass::attribute::class::ClassAttr;
 use jclass::constant::pool::ConstantPool;
@@ -13,7 +14,7 @@ use jclass::field::FieldInfo;
 use jclass::flags::ClassFlags;
 use jclass::method::MethodInfo;
 use once_cell::sync::OnceCell;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 use std::sync::RwLock;
 
 pub struct InterfaceClass {
@@ -26,15 +27,20 @@ impl InterfaceClass {
     fn load(
         flags: ClassFlags,
         cp: RuntimeConstantPool,
-        method_area: &mut MethodArea,
+        method_area: &MethodArea,
         super_id: Option<ClassId>,
         this_class: u16,
+        attributes: &[ClassAttr],
+        loader: Option<HeapRef>,
     ) -> Result<ClassId, JvmError> {
         let name = cp.get_class_sym(&this_class, method_area.interner())?;
+        let module = method_area.class_module(loader, name);
+        let metadata =
+            ClassMetadata::from_attributes(name, attributes, &cp, method_area.interner())?;
 
         //TODO: source file name? etc
         let class = JvmClass::Interface(Box::new(Self {
-            base: BaseClass::new(name, flags, super_id, None),
+            base: BaseClass::new(name, flags, super_id, None, module, loader, metadata),
             cp,
             methods: OnceCell::new(),
         }));
@@ -45,9 +51,10 @@ impl InterfaceClass {
     fn link_methods(
         methods: Vec<MethodInfo>,
         this_id: ClassId,
-        method_area: &mut MethodArea,
+        method_area: &MethodArea,
     ) -> Result<(), JvmError> {
         let mut declared_index = HashMap::new();
+        let mut method_ids = Vec::with_capacity(methods.len());
         for method in methods {
             // TODO: can be extracted to a common function
             let method_key = {
@@ -68,6 +75,7 @@ impl InterfaceClass {
                 method_key.desc,
             );
             let method_id = method_area.push_method(method);
+            method_ids.push(method_id);
             if method_key.name == method_area.br().clinit_sym {
                 method_area
                     .get_interface_class(&this_id)?
@@ -80,6 +88,7 @@ impl InterfaceClass {
 
         let this = method_area.get_interface_class(&this_id)?;
         this.set_methods(declared_index);
+        this.base.set_declared_method_ids(method_ids)?;
 
         Ok(())
     }
@@ -87,19 +96,24 @@ impl InterfaceClass {
     fn link_fields(
         fields: Vec<FieldInfo>,
         this_id: ClassId,
-        method_area: &mut MethodArea,
+        method_area: &MethodArea,
     ) -> Result<(), JvmError> {
         let mut static_fields = HashMap::new();
+        let mut declared_fields = Vec::with_capacity(fields.len());
 
         for field in fields {
             //TODO: assert is static?
-            let field_key = {
-                let cp = &method_area.get_interface_class(&this_id)?.cp;
-                FieldKey {
-                    name: cp.get_utf8_sym(&field.name_index, method_area.interner())?,
-                    desc: cp.get_utf8_sym(&field.descriptor_index, method_area.interner())?,
-                }
+            let cp = &method_area.get_interface_class(&this_id)?.cp;
+            let field_key = FieldKey {
+                name: cp.get_utf8_sym(&field.name_index, method_area.interner())?,
+                desc: cp.get_utf8_sym(&field.descriptor_index, method_area.interner())?,
             };
+            declared_fields.push(DeclaredField::new(
+                field_key,
+                &field,
+                cp,
+                method_area.interner(),
+            )?);
 
             let descriptor_id = method_area.get_or_new_field_descriptor_id(field_key.desc)?;
             let static_field = StaticField {
@@ -112,6 +126,7 @@ impl InterfaceClass {
 
         let this = method_area.get_interface_class(&this_id)?;
         this.base.set_static_fields(static_fields)?;
+        this.base.set_declared_fields(declared_fields)?;
         Ok(())
     }
 
@@ -120,7 +135,8 @@ impl InterfaceClass {
         interfaces: Vec<u16>,
         this_id: ClassId,
         super_id: Option<ClassId>,
-        method_area: &mut MethodArea,
+        method_area: &MethodArea,
+        loader: Option<HeapRef>,
         thread_id: ThreadId,
     ) -> Result<(), JvmError> {
         let mut interface_ids = super_id
@@ -129,14 +145,17 @@ impl InterfaceClass {
             .map(|class| class.get_interfaces().cloned())
             .transpose()?
             .unwrap_or_default();
-        let mut direct_interfaces = HashSet::new();
+        let mut direct_interfaces = Vec::with_capacity(interfaces.len());
+        let this_name = method_area.get_class(&this_id).get_name();
 
         for interface in interfaces {
             let cp = &method_area.get_interface_class(&this_id)?.cp;
             let interface_name = cp.get_class_sym(&interface, method_area.interner())?;
-            let interface_id = method_area.get_class_id_or_load(interface_name, thread_id)?;
+            let interface_id =
+                method_area.get_class_id_or_load_in(loader, interface_name, thread_id)?;
+            method_area.check_superinterface(this_name, interface_id)?;
             interface_ids.insert(interface_id);
-            direct_interfaces.insert(interface_id);
+            direct_interfaces.push(interface_id);
 
             /* TODO: probably need to handle superinterfaces as well
                 something like:
@@ -168,17 +187,48 @@ impl InterfaceClass {
 
     pub fn load_and_link(
         mut cf: ClassFile,
-        method_area: &mut MethodArea,
+        method_area: &MethodArea,
         super_id: Option<ClassId>,
+        loader: Option<HeapRef>,
         thread_id: ThreadId,
     ) -> Result<ClassId, JvmError> {
         let cp = Self::prepare_cp(cf.cp, &mut cf.attributes);
-        let this_id = Self::load(cf.access_flags, cp, method_area, super_id, cf.this_class)?;
-
+        let this_id = Self::load(
+            cf.access_flags,
+            cp,
+            method_area,
+            super_id,
+            cf.this_class,
+            &cf.attributes,
+            loader,
+        )?;
+
+        let constraints = verifier::verify(
+            &cf.methods,
+            cf.major_version,
+            this_id,
+            method_area.get_cp(&this_id)?,
+            method_area,
+        )?;
+        let constraints = verifier::check_loaded_constraints(constraints, this_id, method_area)?;
+        let unchecked = !constraints.is_empty();
+        method_area
+            .get_class_like(&this_id)?
+            .set_verification_constraints(constraints)?;
         Self::link_methods(cf.methods, this_id, method_area)?;
         Self::link_fields(cf.fields, this_id, method_area)?;
-        Self::link_interfaces(cf.interfaces, this_id, super_id, method_area, thread_id)?;
-
+        Self::link_interfaces(
+            cf.interfaces,
+            this_id,
+            super_id,
+            method_area,
+            loader,
+            thread_id,
+        )?;
+
+        if !unchecked {
+            method_area.get_interface_class(&this_id)?.set_linked();
+        }
         Ok(this_id)
     }
 
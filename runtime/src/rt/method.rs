@@ -1,7 +1,10 @@
 use crate::error::JvmError;
 use crate::keys::{ClassId, MethodDescriptorId};
-use crate::{Symbol, throw_exception};
+use crate::rt::RawAnnotations;
+use crate::rt::constant_pool::RuntimeConstantPool;
+use crate::{MethodId, Symbol, throw_exception};
 use common::error::LinkageError;
+use jclass::attribute::SharedAttribute;
 use jclass::attribute::method::code::{
     CodeAttributeInfo, LineNumberEntry, LocalVariableEntry, LocalVariableTypeEntry, StackMapFrame,
 };
@@ -9,6 +12,7 @@ use jclass::attribute::method::{CodeAttribute, ExceptionTableEntry, MethodAttrib
 use jclass::flags::MethodFlags;
 use jclass::method::MethodInfo;
 use std::cell::OnceCell;
+use std::sync::Arc;
 
 pub struct CodeBody {
     pub code: Box<[u8]>,
@@ -32,6 +36,19 @@ pub struct Method {
     descriptor_id: MethodDescriptorId,
     flags: MethodFlags,
     body: MethodBody,
+    /// Utf8 constant of the generic signature.
+    signature_idx: Option<u16>,
+    /// Class constants of the `throws` clause.
+    exception_idxs: Vec<u16>,
+    /// Utf8 constants of the runtime visible annotation types.
+    annotation_type_idxs: Vec<u16>,
+    annotations: RawAnnotations,
+    /// Constant pool of the class file a redefined method comes from, `None` for the methods
+    /// of the class file the class was loaded from.
+    cp: Option<Arc<RuntimeConstantPool>>,
+    /// The method replacing this one when its class was redefined. Frames already running
+    /// this method keep its code, new invocations go to the replacement.
+    redefined_as: once_cell::sync::OnceCell<MethodId>,
 }
 
 impl Method {
@@ -58,6 +75,31 @@ impl Method {
                 .unwrap();
             MethodBody::Interpreted(CodeBody::try_from(code_attr).unwrap())
         };
+        let mut signature_idx = None;
+        let mut exception_idxs = Vec::new();
+        let mut annotation_type_idxs = Vec::new();
+        let mut annotations = RawAnnotations::default();
+        for attr in method_info.attributes {
+            match attr {
+                MethodAttribute::Shared(SharedAttribute::Signature(idx)) => {
+                    signature_idx = Some(idx)
+                }
+                MethodAttribute::Exceptions(idxs) => exception_idxs = idxs,
+                MethodAttribute::Shared(shared) => {
+                    if let SharedAttribute::RuntimeVisibleAnnotations(raw) = &shared {
+                        annotation_type_idxs = raw.value.iter().map(|a| a.type_index).collect();
+                    }
+                    annotations.add(&shared);
+                }
+                MethodAttribute::RuntimeVisibleParameterAnnotations(raw) => {
+                    annotations.parameter_annotations = Some(raw.bytes.into())
+                }
+                MethodAttribute::AnnotationsDefault(raw) => {
+                    annotations.annotation_default = Some(raw.bytes.into())
+                }
+                _ => {}
+            }
+        }
         Method {
             name,
             desc,
@@ -65,6 +107,12 @@ impl Method {
             descriptor_id,
             flags,
             body,
+            signature_idx,
+            exception_idxs,
+            annotation_type_idxs,
+            annotations,
+            cp: None,
+            redefined_as: once_cell::sync::OnceCell::new(),
         }
     }
 
@@ -80,8 +128,74 @@ impl Method {
         self.flags.is_abstract()
     }
 
+    pub fn is_final(&self) -> bool {
+        self.flags.is_final()
+    }
+
+    pub fn is_private(&self) -> bool {
+        self.flags.is_private()
+    }
+
+    /// Neither public, protected nor private.
+    pub fn is_package_private(&self) -> bool {
+        !self.flags.is_public() && !self.flags.is_protected() && !self.flags.is_private()
+    }
+
+    /// Also true for the Java methods the VM substitutes with a native implementation.
     pub fn is_native(&self) -> bool {
-        self.flags.is_native()
+        matches!(self.body, MethodBody::Native)
+    }
+
+    /// Drops the bytecode of a method registered with `NativeRegistry::substitute`.
+    pub fn substitute_native(&mut self) {
+        self.body = MethodBody::Native;
+    }
+
+    /// Makes the method resolve its symbolic references in `cp`, the constant pool of the class
+    /// file redefining its class.
+    pub(crate) fn set_constant_pool(&mut self, cp: Arc<RuntimeConstantPool>) {
+        self.cp = Some(cp);
+    }
+
+    pub fn constant_pool(&self) -> Option<&RuntimeConstantPool> {
+        self.cp.as_deref()
+    }
+
+    pub(crate) fn set_redefined_as(&self, method_id: MethodId) {
+        let _ = self.redefined_as.set(method_id);
+    }
+
+    pub fn redefined_as(&self) -> Option<MethodId> {
+        self.redefined_as.get().copied()
+    }
+
+    pub fn flags(&self) -> MethodFlags {
+        self.flags
+    }
+
+    /// Constant pool index of the generic signature, in the constant pool of the method.
+    pub fn signature_idx(&self) -> Option<u16> {
+        self.signature_idx
+    }
+
+    /// Constant pool indexes of the declared exceptions, in the constant pool of the method.
+    pub fn exception_idxs(&self) -> &[u16] {
+        &self.exception_idxs
+    }
+
+    /// Constant pool indexes of the runtime visible annotation types, in the constant pool of
+    /// the method.
+    pub fn annotation_type_idxs(&self) -> &[u16] {
+        &self.annotation_type_idxs
+    }
+
+    /// Raw bytes of the runtime visible annotation attributes, in the constant pool of the method.
+    pub fn annotations(&self) -> &RawAnnotations {
+        &self.annotations
+    }
+
+    pub fn is_synchronized(&self) -> bool {
+        self.flags.is_synchronized()
     }
 
     pub fn descriptor_id(&self) -> MethodDescriptorId {
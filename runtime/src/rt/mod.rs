@@ -1,15 +1,20 @@
 use crate::error::JvmError;
 use crate::heap::HeapRef;
 use crate::keys::{ClassId, FieldKey, MethodKey};
+use crate::module::ModuleId;
 use crate::rt::array::{ObjectArrayClass, PrimitiveArrayClass};
 use crate::rt::class::InstanceClass;
 use crate::rt::constant_pool::RuntimeConstantPool;
-use crate::rt::field::{InstanceField, StaticField};
+use crate::rt::field::{DeclaredField, InstanceField, StaticField};
 use crate::rt::interface::InterfaceClass;
+use crate::rt::verifier::VerificationConstraint;
 use crate::vm::Value;
 use crate::{MethodId, Symbol};
 use common::jtype::PrimitiveType;
+use jclass::attribute::SharedAttribute;
+use jclass::attribute::class::ClassAttr;
 use jclass::flags::ClassFlags;
+use lasso::ThreadedRodeo;
 use once_cell::sync::OnceCell;
 use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
@@ -19,8 +24,12 @@ pub mod array;
 pub mod class;
 pub mod constant_pool;
 pub mod field;
+pub(crate) mod format;
+pub mod init;
 pub mod interface;
 pub mod method;
+pub mod redefine;
+pub(crate) mod verifier;
 
 pub trait ClassLike {
     fn base(&self) -> &BaseClass;
@@ -59,6 +68,15 @@ pub trait ClassLike {
         self.base().source_file
     }
 
+    fn module(&self) -> ModuleId {
+        self.base().module
+    }
+
+    /// The defining loader, `None` for the bootstrap loader.
+    fn loader(&self) -> Option<HeapRef> {
+        self.base().loader
+    }
+
     fn has_static_field(&self, field_key: &FieldKey) -> Result<bool, JvmError> {
         self.base()
             .get_static_fields()
@@ -84,39 +102,115 @@ pub trait ClassLike {
         Ok(*static_field.value.read().unwrap())
     }
 
+    /// Sets the static field to `new_value` if it holds `expected`, returns the value it held.
+    fn compare_and_exchange_static_field_value(
+        &self,
+        field_key: &FieldKey,
+        expected: Value,
+        new_value: Value,
+    ) -> Result<Value, JvmError> {
+        let static_field = self
+            .base()
+            .get_static_fields()?
+            .get(field_key)
+            .ok_or(JvmError::Todo("No such field".to_string()))?;
+        let mut value = static_field.value.write().unwrap();
+        let current = *value;
+        if current == expected {
+            *value = new_value;
+        }
+        Ok(current)
+    }
+
+    /// Current values of the static fields, none before the class is prepared.
+    fn static_field_values(&self) -> Vec<Value> {
+        self.base()
+            .static_fields
+            .get()
+            .map(|fields| {
+                fields
+                    .values()
+                    .map(|field| *field.value.read().unwrap())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Static fields with their current values, none before the class is prepared.
+    fn static_field_entries(&self) -> Vec<(FieldKey, Value)> {
+        self.base()
+            .static_fields
+            .get()
+            .map(|fields| {
+                fields
+                    .iter()
+                    .map(|(key, field)| (*key, *field.value.read().unwrap()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
     fn get_interfaces(&self) -> Result<&HashSet<ClassId>, JvmError> {
         self.base().get_interfaces()
     }
 
-    fn get_direct_interfaces(&self) -> Result<&HashSet<ClassId>, JvmError> {
+    /// Direct superinterfaces in the order of the `interfaces` array of the class file.
+    fn get_direct_interfaces(&self) -> Result<&[ClassId], JvmError> {
         self.base().get_direct_interfaces()
     }
 
-    fn set_linked(&self) {
-        self.base()
-            .state
-            .store(ClassState::Linked as u8, Ordering::Release);
+    /// Fields declared by the class, in the order of the class file.
+    fn declared_fields(&self) -> Result<&[DeclaredField], JvmError> {
+        self.base().get_declared_fields()
     }
 
-    fn is_initializing(&self) -> bool {
-        self.base().state.load(Ordering::Acquire) == ClassState::Initializing as u8
+    /// Methods declared by the class in the order of the class file, `<init>` and `<clinit>`
+    /// included. A redefined method keeps its original id here.
+    fn declared_method_ids(&self) -> Result<&[MethodId], JvmError> {
+        self.base().get_declared_method_ids()
     }
 
-    fn set_initializing(&self) {
+    /// Assignments between classes the defining loader hadn't loaded when the class was linked,
+    /// the class stays `Loaded` until they are checked.
+    fn verification_constraints(&self) -> &[VerificationConstraint] {
         self.base()
-            .state
-            .store(ClassState::Initializing as u8, Ordering::Release);
+            .verification_constraints
+            .get()
+            .map_or(&[], Vec::as_slice)
+    }
+
+    fn set_verification_constraints(
+        &self,
+        constraints: Vec<VerificationConstraint>,
+    ) -> Result<(), JvmError> {
+        self.base()
+            .verification_constraints
+            .set(constraints)
+            .map_err(|_| JvmError::Todo("Verification constraints already set".to_string()))
     }
 
-    fn set_initialized(&self) {
+    fn metadata(&self) -> &ClassMetadata {
+        &self.base().metadata
+    }
+
+    fn state(&self) -> ClassState {
+        self.base().state.load(Ordering::Acquire).into()
+    }
+
+    /// Atomically moves the class from `from` to `to`, returns false if it was not in `from`.
+    fn transition(&self, from: ClassState, to: ClassState) -> bool {
         self.base()
             .state
-            .store(ClassState::Initialized as u8, Ordering::Release);
+            .compare_exchange(from as u8, to as u8, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+    }
+
+    fn set_linked(&self) {
+        self.transition(ClassState::Loaded, ClassState::Linked);
     }
 
-    fn is_initialized_or_initializing(&self) -> bool {
-        let state = self.base().state.load(Ordering::Acquire);
-        state == ClassState::Initialized as u8 || state == ClassState::Initializing as u8
+    fn is_initialized(&self) -> bool {
+        self.state() == ClassState::Initialized
     }
 }
 
@@ -127,10 +221,16 @@ pub struct BaseClass {
     state: AtomicU8,
     mirror_ref: OnceCell<HeapRef>,
     interfaces: OnceCell<HashSet<ClassId>>,
-    direct_interfaces: OnceCell<HashSet<ClassId>>,
+    direct_interfaces: OnceCell<Vec<ClassId>>,
     static_fields: OnceCell<HashMap<FieldKey, StaticField>>,
     clinit: OnceCell<MethodId>,
     source_file: Option<Symbol>,
+    module: ModuleId,
+    loader: Option<HeapRef>,
+    metadata: ClassMetadata,
+    declared_fields: OnceCell<Vec<DeclaredField>>,
+    declared_method_ids: OnceCell<Vec<MethodId>>,
+    verification_constraints: OnceCell<Vec<VerificationConstraint>>,
 }
 
 impl BaseClass {
@@ -139,12 +239,21 @@ impl BaseClass {
         flags: ClassFlags,
         super_id: Option<ClassId>,
         source_file: Option<Symbol>,
+        module: ModuleId,
+        loader: Option<HeapRef>,
+        metadata: ClassMetadata,
     ) -> Self {
         Self {
             name,
             flags,
             super_id,
             source_file,
+            module,
+            loader,
+            metadata,
+            declared_fields: OnceCell::new(),
+            declared_method_ids: OnceCell::new(),
+            verification_constraints: OnceCell::new(),
             state: AtomicU8::new(ClassState::Loaded as u8),
             mirror_ref: OnceCell::new(),
             interfaces: OnceCell::new(),
@@ -169,10 +278,13 @@ impl BaseClass {
             .ok_or(JvmError::Todo("BaseClass interfaces not set".to_string()))
     }
 
-    fn get_direct_interfaces(&self) -> Result<&HashSet<ClassId>, JvmError> {
-        self.direct_interfaces.get().ok_or(JvmError::Todo(
-            "BaseClass direct_interfaces not set".to_string(),
-        ))
+    fn get_direct_interfaces(&self) -> Result<&[ClassId], JvmError> {
+        self.direct_interfaces
+            .get()
+            .map(Vec::as_slice)
+            .ok_or(JvmError::Todo(
+                "BaseClass direct_interfaces not set".to_string(),
+            ))
     }
 
     fn set_interfaces(&self, interfaces: HashSet<ClassId>) -> Result<(), JvmError> {
@@ -181,7 +293,7 @@ impl BaseClass {
             .map_err(|_| JvmError::Todo("BaseClass interfaces already set".to_string()))
     }
 
-    fn set_direct_interfaces(&self, interfaces: HashSet<ClassId>) -> Result<(), JvmError> {
+    fn set_direct_interfaces(&self, interfaces: Vec<ClassId>) -> Result<(), JvmError> {
         self.direct_interfaces
             .set(interfaces)
             .map_err(|_| JvmError::Todo("BaseClass direct_interfaces already set".to_string()))
@@ -201,16 +313,161 @@ impl BaseClass {
             "BaseClass static_fields not set".to_string(),
         ))
     }
+
+    fn set_declared_fields(&self, fields: Vec<DeclaredField>) -> Result<(), JvmError> {
+        self.declared_fields
+            .set(fields)
+            .map_err(|_| JvmError::Todo("BaseClass declared_fields already set".to_string()))
+    }
+
+    fn get_declared_fields(&self) -> Result<&[DeclaredField], JvmError> {
+        self.declared_fields
+            .get()
+            .map(Vec::as_slice)
+            .ok_or(JvmError::Todo(
+                "BaseClass declared_fields not set".to_string(),
+            ))
+    }
+
+    fn set_declared_method_ids(&self, method_ids: Vec<MethodId>) -> Result<(), JvmError> {
+        self.declared_method_ids
+            .set(method_ids)
+            .map_err(|_| JvmError::Todo("BaseClass declared_method_ids already set".to_string()))
+    }
+
+    fn get_declared_method_ids(&self) -> Result<&[MethodId], JvmError> {
+        self.declared_method_ids
+            .get()
+            .map(Vec::as_slice)
+            .ok_or(JvmError::Todo(
+                "BaseClass declared_method_ids not set".to_string(),
+            ))
+    }
+}
+
+/// The runtime visible annotation attributes of a class, field or method, as core reflection
+/// parses them against the constant pool of the class.
+#[derive(Debug, Clone, Default)]
+pub struct RawAnnotations {
+    pub annotations: Option<Box<[u8]>>,
+    pub type_annotations: Option<Box<[u8]>>,
+    /// Methods only.
+    pub parameter_annotations: Option<Box<[u8]>>,
+    /// Methods of annotation interfaces only.
+    pub annotation_default: Option<Box<[u8]>>,
+}
+
+impl RawAnnotations {
+    /// Keeps the bytes of `attr` when it is a runtime visible annotations attribute.
+    pub(crate) fn add(&mut self, attr: &SharedAttribute) {
+        match attr {
+            SharedAttribute::RuntimeVisibleAnnotations(raw) => {
+                self.annotations = Some(raw.bytes.as_slice().into())
+            }
+            SharedAttribute::RuntimeVisibleTypeAnnotations(raw) => {
+                self.type_annotations = Some(raw.bytes.as_slice().into())
+            }
+            _ => {}
+        }
+    }
+}
+
+/// What the `Signature`, `Record`, `InnerClasses`, `EnclosingMethod` and annotation attributes
+/// of a class file say about the class itself.
+#[derive(Default)]
+pub struct ClassMetadata {
+    /// Generic signature.
+    pub signature: Option<Symbol>,
+    pub annotations: RawAnnotations,
+    pub is_record: bool,
+    /// Set when the class is nested in another one.
+    pub inner_class: Option<InnerClass>,
+    /// Set for local and anonymous classes.
+    pub enclosing_method: Option<EnclosingMethod>,
+}
+
+/// The `InnerClasses` entry of a nested class for itself.
+pub struct InnerClass {
+    /// The class declaring it as a member, `None` for local and anonymous classes.
+    pub outer_class: Option<Symbol>,
+    /// `None` for anonymous classes.
+    pub simple_name: Option<Symbol>,
+    /// Modifiers in the source, e.g. `static` or `private`.
+    pub flags: u16,
+}
+
+pub struct EnclosingMethod {
+    pub class: Symbol,
+    /// `None` when the class is declared in an initializer.
+    pub method: Option<MethodKey>,
+}
+
+impl ClassMetadata {
+    pub(crate) fn from_attributes(
+        name: Symbol,
+        attributes: &[ClassAttr],
+        cp: &RuntimeConstantPool,
+        interner: &ThreadedRodeo,
+    ) -> Result<Self, JvmError> {
+        let mut metadata = Self::default();
+        for attr in attributes {
+            match attr {
+                ClassAttr::Shared(SharedAttribute::Signature(idx)) => {
+                    metadata.signature = Some(cp.get_utf8_sym(idx, interner)?);
+                }
+                ClassAttr::Shared(shared) => metadata.annotations.add(shared),
+                ClassAttr::Record => metadata.is_record = true,
+                ClassAttr::InnerClasses(entries) => {
+                    for entry in entries {
+                        if cp.get_class_sym(&entry.inner_class_info_index, interner)? != name {
+                            continue;
+                        }
+                        let outer_class = match entry.outer_class_info_index {
+                            0 => None,
+                            idx => Some(cp.get_class_sym(&idx, interner)?),
+                        };
+                        let simple_name = match entry.inner_name_index {
+                            0 => None,
+                            idx => Some(cp.get_utf8_sym(&idx, interner)?),
+                        };
+                        metadata.inner_class = Some(InnerClass {
+                            outer_class,
+                            simple_name,
+                            flags: entry.inner_class_access_flags,
+                        });
+                    }
+                }
+                ClassAttr::EnclosingMethod(class_idx, method_idx) => {
+                    let method = match method_idx {
+                        0 => None,
+                        idx => {
+                            let nat = cp.get_nat_view(idx, interner)?;
+                            Some(MethodKey {
+                                name: nat.name_sym,
+                                desc: nat.descriptor_sym,
+                            })
+                        }
+                    };
+                    metadata.enclosing_method = Some(EnclosingMethod {
+                        class: cp.get_class_sym(class_idx, interner)?,
+                        method,
+                    });
+                }
+                _ => {}
+            }
+        }
+        Ok(metadata)
+    }
 }
 
-// TODO: something like that...
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ClassState {
     Loaded = 0,       // Parsed, superclass loaded
-    Linked = 1,       // Verified, prepared
+    Linked = 1,       // Verified when the verify mode covers its loader, prepared
     Initializing = 2, // <clinit> in progress
     Initialized = 3,  // <clinit> executed
+    Erroneous = 4,    // <clinit> or superclass initialization failed
 }
 
 impl From<u8> for ClassState {
@@ -220,6 +477,7 @@ impl From<u8> for ClassState {
             1 => ClassState::Linked,
             2 => ClassState::Initializing,
             3 => ClassState::Initialized,
+            4 => ClassState::Erroneous,
             _ => unreachable!(),
         }
     }
@@ -303,7 +561,7 @@ impl JvmClass {
     }
 
     // TODO: use base instead?
-    pub fn get_direct_interfaces(&self) -> Result<&HashSet<ClassId>, JvmError> {
+    pub fn get_direct_interfaces(&self) -> Result<&[ClassId], JvmError> {
         match self {
             JvmClass::Instance(inst) => inst.get_direct_interfaces(),
             JvmClass::Interface(i) => i.get_direct_interfaces(),
@@ -408,6 +666,15 @@ impl JvmClass {
         matches!(self, JvmClass::Interface(_))
     }
 
+    /// Array classes are final too.
+    pub fn is_final(&self) -> bool {
+        match self {
+            JvmClass::Instance(ic) => ic.flags().is_final(),
+            JvmClass::Interface(i) => i.flags().is_final(),
+            _ => true,
+        }
+    }
+
     pub fn get_raw_flags(&self) -> i32 {
         match self {
             JvmClass::Instance(ic) => ic.flags().get_raw_i32(),
@@ -0,0 +1,260 @@
+use crate::error::JvmError;
+use crate::heap::method_area::MethodArea;
+use crate::keys::{ClassId, MethodKey};
+use crate::rt::class::InstanceClass;
+use crate::rt::constant_pool::RuntimeConstantPool;
+use crate::rt::method::Method;
+use crate::rt::{ClassLike, JvmClass, format, verifier};
+use crate::{MethodId, Symbol, build_exception};
+use jclass::ClassFile;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::sync::Arc;
+
+/// Why a class can't be redefined, the errors of JVMTI `RedefineClasses`.
+#[derive(Debug)]
+pub enum RedefineError {
+    /// Not a class or an interface.
+    InvalidClass,
+    NamesDontMatch,
+    InvalidClassFormat(JvmError),
+    FailsVerification(JvmError),
+    /// Another superclass or other direct superinterfaces.
+    HierarchyChange,
+    ClassModifiersChange,
+    /// Fields added, removed or changed.
+    SchemaChange,
+    MethodAddition,
+    MethodDeletion,
+    MethodModifiersChange,
+    Internal(JvmError),
+}
+
+impl Display for RedefineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RedefineError::InvalidClass => write!(f, "class can't be redefined"),
+            RedefineError::NamesDontMatch => write!(f, "class name doesn't match"),
+            RedefineError::InvalidClassFormat(e) => write!(f, "invalid class file: {e}"),
+            RedefineError::FailsVerification(e) => write!(f, "verification failed: {e}"),
+            RedefineError::HierarchyChange => write!(f, "class hierarchy changed"),
+            RedefineError::ClassModifiersChange => write!(f, "class modifiers changed"),
+            RedefineError::SchemaChange => write!(f, "fields changed"),
+            RedefineError::MethodAddition => write!(f, "method added"),
+            RedefineError::MethodDeletion => write!(f, "method deleted"),
+            RedefineError::MethodModifiersChange => write!(f, "method modifiers changed"),
+            RedefineError::Internal(e) => write!(f, "internal error: {e}"),
+        }
+    }
+}
+
+/// A checked class file waiting to replace the methods of its class.
+struct Redefinition {
+    class_id: ClassId,
+    cp: Arc<RuntimeConstantPool>,
+    /// Old method and the version replacing it.
+    methods: Vec<(MethodId, Method)>,
+}
+
+/// https://docs.oracle.com/en/java/javase/25/docs/specs/jvmti.html#RedefineClasses
+///
+/// Replaces the method bodies of loaded classes with the ones of new class files. A class file
+/// must declare the same superclass, superinterfaces, fields and methods with the same
+/// modifiers as the class; its methods are verified like the ones of a loaded class. Nothing
+/// is redefined unless every class file is accepted.
+///
+/// The new methods resolve their references in the constant pool of the new class file. Frames
+/// running an old method go on with its code and constant pool, calls made afterwards run
+/// the new version. Static field values and instances are kept.
+pub fn redefine_classes(
+    method_area: &MethodArea,
+    classes: Vec<(ClassId, Vec<u8>)>,
+) -> Result<(), RedefineError> {
+    let _redefinition = method_area.lock_redefinition();
+    let redefinitions = classes
+        .into_iter()
+        .map(|(class_id, bytes)| prepare(method_area, class_id, bytes))
+        .collect::<Result<Vec<_>, _>>()?;
+    for redefinition in redefinitions {
+        method_area.replace_methods(redefinition.class_id, redefinition.cp, redefinition.methods);
+    }
+    Ok(())
+}
+
+fn prepare(
+    method_area: &MethodArea,
+    class_id: ClassId,
+    bytes: Vec<u8>,
+) -> Result<Redefinition, RedefineError> {
+    let interner = method_area.interner();
+    let class: &dyn ClassLike = match method_area.get_class(&class_id) {
+        JvmClass::Instance(class) => class.as_ref(),
+        JvmClass::Interface(interface) => interface.as_ref(),
+        _ => return Err(RedefineError::InvalidClass),
+    };
+    let cf = format::parse_class_file(bytes).map_err(RedefineError::InvalidClassFormat)?;
+    format::check_format(&cf).map_err(RedefineError::InvalidClassFormat)?;
+    if cf.get_class_name().ok() != Some(interner.resolve(&class.name())) {
+        return Err(RedefineError::NamesDontMatch);
+    }
+    check_hierarchy(method_area, class, &cf)?;
+    if cf.access_flags.get_raw() != class.flags().get_raw() {
+        return Err(RedefineError::ClassModifiersChange);
+    }
+    check_fields(method_area, class_id, &cf)?;
+
+    let ClassFile {
+        cp,
+        mut attributes,
+        methods: method_infos,
+        major_version,
+        ..
+    } = cf;
+    let cp = Arc::new(InstanceClass::prepare_cp(cp, &mut attributes));
+    let old_methods = method_area.declared_methods(class_id);
+    let mut new_keys = HashSet::new();
+    let mut methods = Vec::with_capacity(method_infos.len());
+    for method_info in &method_infos {
+        let key = MethodKey {
+            name: cp
+                .get_utf8_sym(&method_info.name_index, interner)
+                .map_err(RedefineError::InvalidClassFormat)?,
+            desc: cp
+                .get_utf8_sym(&method_info.descriptor_index, interner)
+                .map_err(RedefineError::InvalidClassFormat)?,
+        };
+        let old_id = *old_methods.get(&key).ok_or(RedefineError::MethodAddition)?;
+        let old_flags = method_area.get_method(&old_id).flags();
+        if method_info.access_flags.get_raw() != old_flags.get_raw() {
+            return Err(RedefineError::MethodModifiersChange);
+        }
+        new_keys.insert(key);
+        methods.push((key, old_id));
+    }
+    if old_methods.keys().any(|key| !new_keys.contains(key)) {
+        return Err(RedefineError::MethodDeletion);
+    }
+
+    let constraints = verifier::verify(&method_infos, major_version, class_id, &cp, method_area)
+        .map_err(RedefineError::FailsVerification)?;
+    // the class is linked already, nothing would check them later
+    if let Some(constraint) = constraints.first() {
+        return Err(RedefineError::FailsVerification(build_exception!(
+            VerifyError,
+            "{} is not loaded, cannot check that it is assignable to {}",
+            interner.resolve(&constraint.from),
+            interner.resolve(&constraint.to)
+        )));
+    }
+
+    let methods = method_infos
+        .into_iter()
+        .zip(methods)
+        .map(|(method_info, (key, old_id))| {
+            let descriptor_id = method_area
+                .get_or_new_method_descriptor_id(&key.desc)
+                .unwrap();
+            let mut method = Method::new(method_info, class_id, descriptor_id, key.name, key.desc);
+            if method_area.is_substituted(class_id, &key) {
+                method.substitute_native();
+            }
+            method.set_constant_pool(cp.clone());
+            (old_id, method)
+        })
+        .collect();
+    Ok(Redefinition {
+        class_id,
+        cp,
+        methods,
+    })
+}
+
+fn check_hierarchy(
+    method_area: &MethodArea,
+    class: &dyn ClassLike,
+    cf: &ClassFile,
+) -> Result<(), RedefineError> {
+    let interner = method_area.interner();
+    let class_name =
+        |class_id: &ClassId| interner.resolve(&method_area.get_class(class_id).get_name());
+    let super_name = class.get_super().as_ref().map(class_name);
+    if cf.get_super_class_name().map(|name| name.ok()) != super_name.map(Some) {
+        return Err(RedefineError::HierarchyChange);
+    }
+    let interfaces = class
+        .get_direct_interfaces()
+        .map_err(RedefineError::Internal)?;
+    let same_interfaces = interfaces.len() == cf.interfaces.len()
+        && interfaces.iter().zip(&cf.interfaces).all(|(old, new)| {
+            cf.cp
+                .get_class_name(new)
+                .is_ok_and(|name| name == class_name(old))
+        });
+    if !same_interfaces {
+        return Err(RedefineError::HierarchyChange);
+    }
+    Ok(())
+}
+
+/// Static fields may be declared in another order, instance fields keep their layout.
+fn check_fields(
+    method_area: &MethodArea,
+    class_id: ClassId,
+    cf: &ClassFile,
+) -> Result<(), RedefineError> {
+    let interner = method_area.interner();
+    let class = method_area
+        .get_class_like(&class_id)
+        .map_err(RedefineError::Internal)?;
+    let old_statics: HashMap<(Symbol, Symbol), u16> = class
+        .base()
+        .get_static_fields()
+        .map_err(RedefineError::Internal)?
+        .iter()
+        .map(|(key, field)| ((key.name, key.desc), *field.flags.get_raw()))
+        .collect();
+    let mut old_instance_fields = Vec::new();
+    if let JvmClass::Instance(class) = method_area.get_class(&class_id) {
+        let fields = class
+            .get_instance_fields()
+            .map_err(RedefineError::Internal)?;
+        for (key, position) in class.instance_fields_offset_map.get().into_iter().flatten() {
+            let field = &fields[*position];
+            if field.declaring_class == class_id {
+                old_instance_fields.push((
+                    field.offset,
+                    key.name,
+                    key.desc,
+                    *field.flags.get_raw(),
+                ));
+            }
+        }
+    }
+    old_instance_fields.sort_unstable_by_key(|(offset, ..)| *offset);
+
+    let mut new_statics = HashMap::new();
+    let mut new_instance_fields = Vec::new();
+    for field in &cf.fields {
+        let (Ok(name), Ok(desc)) = (
+            cf.cp.get_utf8(&field.name_index),
+            cf.cp.get_utf8(&field.descriptor_index),
+        ) else {
+            return Err(RedefineError::SchemaChange);
+        };
+        let (name, desc) = (interner.get_or_intern(name), interner.get_or_intern(desc));
+        let flags = *field.access_flags.get_raw();
+        if field.access_flags.is_static() {
+            new_statics.insert((name, desc), flags);
+        } else {
+            new_instance_fields.push((name, desc, flags));
+        }
+    }
+    let same_instance_fields = old_instance_fields
+        .into_iter()
+        .map(|(_, name, desc, flags)| (name, desc, flags))
+        .eq(new_instance_fields);
+    if old_statics != new_statics || !same_instance_fields {
+        return Err(RedefineError::SchemaChange);
+    }
+    Ok(())
+}
@@ -0,0 +1,1309 @@
+use crate::Symbol;
+use crate::rt::constant_pool::RuntimeConstant;
+use crate::rt::verifier::types::{
+    Frame, VType, from_verification_type_info, split_method_descriptor,
+};
+use crate::rt::verifier::{ClassVerifier, VerifyFailure};
+use common::error::InstructionErr;
+use common::instruction::Instruction;
+use jclass::attribute::method::code::{CodeAttributeInfo, StackMapFrame};
+use jclass::attribute::method::{CodeAttribute, ExceptionTableEntry};
+use jclass::flags::MethodFlags;
+use std::cell::Cell;
+use std::collections::{BTreeMap, HashMap};
+
+type VerifyResult<T> = Result<T, VerifyFailure>;
+
+/// Type checker of the code of one method. Instructions are checked in code order: the type
+/// state after an instruction flows into the next one, and a branch target or an exception
+/// handler must have a stack map frame the incoming state is assignable to.
+pub(super) struct MethodVerifier<'a> {
+    class: &'a ClassVerifier<'a>,
+    is_init: bool,
+    return_type: Option<VType>,
+    code: &'a [u8],
+    max_stack: usize,
+    max_locals: usize,
+    exception_table: &'a [ExceptionTableEntry],
+    stack_map_table: &'a [StackMapFrame],
+    /// Locals of the implicit first frame, as listed in a stack map frame.
+    initial_locals: Vec<VType>,
+    instructions: BTreeMap<u16, Instruction>,
+    stack_map: HashMap<u16, Frame>,
+    pc: Cell<u16>,
+}
+
+impl<'a> MethodVerifier<'a> {
+    pub fn new(
+        class: &'a ClassVerifier<'a>,
+        flags: MethodFlags,
+        name: Symbol,
+        desc: Symbol,
+        code_attr: &'a CodeAttribute,
+    ) -> VerifyResult<Self> {
+        let interner = class.interner();
+        let bad_descriptor = || VerifyFailure {
+            pc: 0,
+            reason: "Bad method descriptor".to_string(),
+            detail: Some(interner.resolve(&desc).to_string()),
+        };
+        let (params, ret) =
+            split_method_descriptor(interner.resolve(&desc)).ok_or_else(bad_descriptor)?;
+        let return_type = ret
+            .map(|ret| VType::from_descriptor(ret, interner).ok_or_else(bad_descriptor))
+            .transpose()?;
+        let is_init = interner.resolve(&name) == "<init>";
+
+        let mut initial_locals = Vec::with_capacity(params.len() + 1);
+        if !flags.is_static() {
+            if is_init && class.this_name != class.java_lang_object {
+                initial_locals.push(VType::UninitializedThis);
+            } else {
+                initial_locals.push(VType::Reference(class.this_name));
+            }
+        }
+        for param in params {
+            initial_locals
+                .push(VType::from_descriptor(param, interner).ok_or_else(bad_descriptor)?);
+        }
+
+        let stack_map_table = code_attr
+            .attributes
+            .iter()
+            .find_map(|attr| match attr {
+                CodeAttributeInfo::StackMapTable(table) => Some(table.as_slice()),
+                _ => None,
+            })
+            .unwrap_or_default();
+
+        Ok(Self {
+            class,
+            is_init,
+            return_type,
+            code: &code_attr.code,
+            max_stack: code_attr.max_stack as usize,
+            max_locals: code_attr.max_locals as usize,
+            exception_table: &code_attr.exception_table,
+            stack_map_table,
+            initial_locals,
+            instructions: BTreeMap::new(),
+            stack_map: HashMap::new(),
+            pc: Cell::new(0),
+        })
+    }
+
+    pub fn verify(&mut self) -> VerifyResult<()> {
+        let initial_frame = Frame {
+            locals: self
+                .expand_locals(&self.initial_locals)
+                .ok_or_else(|| self.fail("Arguments can't fit into locals"))?,
+            stack: Vec::new(),
+            flag_this_uninit: self.initial_locals.contains(&VType::UninitializedThis),
+        };
+        self.decode_instructions()?;
+        self.decode_stack_map()?;
+        self.check_exception_table()?;
+
+        let mut current = Some(initial_frame);
+        for (pc, instruction) in &self.instructions {
+            self.pc.set(*pc);
+            if let Some(mapped) = self.stack_map.get(pc) {
+                if let Some(frame) = &current {
+                    self.check_assignable_frame(frame, mapped, *pc)?;
+                }
+                current = Some(mapped.clone());
+            }
+            let Some(frame) = current.take() else {
+                return Err(self.fail("Expecting a stackmap frame"));
+            };
+            self.check_handlers(&frame)?;
+            let mut next = frame.clone();
+            let falls_through = self.execute(&mut next, instruction)?;
+            if next.locals != frame.locals {
+                self.check_handlers(&next)?;
+            }
+            current = falls_through.then_some(next);
+        }
+        if current.is_some() {
+            return Err(self.fail("Falling off the end of the code"));
+        }
+        Ok(())
+    }
+
+    fn fail(&self, reason: impl Into<String>) -> VerifyFailure {
+        VerifyFailure {
+            pc: self.pc.get(),
+            reason: reason.into(),
+            detail: None,
+        }
+    }
+
+    fn fail_with(&self, reason: impl Into<String>, detail: impl Into<String>) -> VerifyFailure {
+        VerifyFailure {
+            pc: self.pc.get(),
+            reason: reason.into(),
+            detail: Some(detail.into()),
+        }
+    }
+
+    fn mismatch(&self, reason: impl Into<String>, expected: &str, found: VType) -> VerifyFailure {
+        self.fail_with(
+            reason,
+            format!("expected {expected}, found {}", self.describe(found)),
+        )
+    }
+
+    fn describe(&self, t: VType) -> String {
+        t.describe(self.class.interner())
+    }
+
+    fn reference(&self, name: &str) -> VType {
+        VType::Reference(self.class.interner().get_or_intern(name))
+    }
+
+    fn expand_locals(&self, entries: &[VType]) -> Option<Vec<VType>> {
+        let mut locals = Vec::with_capacity(self.max_locals);
+        for t in entries {
+            locals.push(*t);
+            if t.is_category2() {
+                locals.push(VType::Top);
+            }
+        }
+        if locals.len() > self.max_locals {
+            return None;
+        }
+        locals.resize(self.max_locals, VType::Top);
+        Some(locals)
+    }
+
+    fn decode_instructions(&mut self) -> VerifyResult<()> {
+        if self.code.is_empty() || self.code.len() > u16::MAX as usize {
+            return Err(self.fail_with("Invalid method Code length", self.code.len().to_string()));
+        }
+        let mut pc = 0;
+        while pc < self.code.len() {
+            self.pc.set(pc as u16);
+            let instruction = Instruction::new_at(self.code, pc).map_err(|e| {
+                let detail = match e {
+                    InstructionErr::UnsupportedOpCode(opcode) => {
+                        format!("unsupported opcode 0x{opcode:02x}")
+                    }
+                    InstructionErr::UnknownArrayType(atype) => {
+                        format!("unknown array type {atype}")
+                    }
+                    InstructionErr::Cursor(_) | InstructionErr::UnexpectedEof => {
+                        "truncated instruction".to_string()
+                    }
+                };
+                self.fail_with("Bad instruction", detail)
+            })?;
+            let size = instruction.byte_size() as usize;
+            self.instructions.insert(pc as u16, instruction);
+            pc += size;
+        }
+        Ok(())
+    }
+
+    /// https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.7.4
+    fn decode_stack_map(&mut self) -> VerifyResult<()> {
+        let mut locals = self.initial_locals.clone();
+        let mut previous_pc: Option<u16> = None;
+        for stack_map_frame in self.stack_map_table {
+            let (offset_delta, stack) = match stack_map_frame {
+                StackMapFrame::Same { offset_delta }
+                | StackMapFrame::SameExtended { offset_delta } => (*offset_delta, Vec::new()),
+                StackMapFrame::SameLocals1StackItem {
+                    offset_delta,
+                    stack,
+                }
+                | StackMapFrame::SameLocals1StackItemExtended {
+                    offset_delta,
+                    stack,
+                } => (*offset_delta, vec![self.stack_map_type(stack)?]),
+                StackMapFrame::Chop { k, offset_delta } => {
+                    let k = usize::from(*k);
+                    if k > locals.len() {
+                        return Err(self.fail("StackMapTable error: bad chop frame"));
+                    }
+                    locals.truncate(locals.len() - k);
+                    (*offset_delta, Vec::new())
+                }
+                StackMapFrame::Append {
+                    offset_delta,
+                    locals: appended,
+                    ..
+                } => {
+                    for info in appended {
+                        locals.push(self.stack_map_type(info)?);
+                    }
+                    (*offset_delta, Vec::new())
+                }
+                StackMapFrame::Full {
+                    offset_delta,
+                    locals: full_locals,
+                    stack,
+                } => {
+                    locals = full_locals
+                        .iter()
+                        .map(|info| self.stack_map_type(info))
+                        .collect::<VerifyResult<_>>()?;
+                    let stack = stack
+                        .iter()
+                        .map(|info| self.stack_map_type(info))
+                        .collect::<VerifyResult<_>>()?;
+                    (*offset_delta, stack)
+                }
+            };
+            let pc = match previous_pc {
+                None => u32::from(offset_delta),
+                Some(previous_pc) => u32::from(previous_pc) + u32::from(offset_delta) + 1,
+            };
+            let pc = u16::try_from(pc)
+                .ok()
+                .filter(|pc| self.instructions.contains_key(pc))
+                .ok_or_else(|| self.fail_with("StackMapTable error: bad offset", pc.to_string()))?;
+            self.pc.set(pc);
+
+            let frame = Frame {
+                locals: self.expand_locals(&locals).ok_or_else(|| {
+                    self.fail("StackMapTable error: local variable table overflow")
+                })?,
+                flag_this_uninit: locals.contains(&VType::UninitializedThis),
+                stack,
+            };
+            if frame.stack_size() > self.max_stack {
+                return Err(self.fail("StackMapTable error: operand stack overflow"));
+            }
+            for t in frame.locals.iter().chain(&frame.stack) {
+                if let VType::Uninitialized(offset) = t
+                    && !matches!(self.instructions.get(offset), Some(Instruction::New(_)))
+                {
+                    return Err(self.fail_with(
+                        "StackMapTable error: expecting new at offset",
+                        offset.to_string(),
+                    ));
+                }
+            }
+            self.stack_map.insert(pc, frame);
+            previous_pc = Some(pc);
+        }
+        Ok(())
+    }
+
+    fn stack_map_type(
+        &self,
+        info: &jclass::attribute::method::code::VerificationTypeInfo,
+    ) -> VerifyResult<VType> {
+        from_verification_type_info(info, |idx| {
+            self.class
+                .cp
+                .get_class_sym(&idx, self.class.interner())
+                .ok()
+        })
+        .ok_or_else(|| self.fail("StackMapTable error: bad class index"))
+    }
+
+    fn check_exception_table(&self) -> VerifyResult<()> {
+        let is_boundary = |pc: u16| self.instructions.contains_key(&pc);
+        for entry in self.exception_table {
+            self.pc.set(entry.start_pc);
+            let valid_range = entry.start_pc < entry.end_pc
+                && is_boundary(entry.start_pc)
+                && (usize::from(entry.end_pc) == self.code.len() || is_boundary(entry.end_pc));
+            if !valid_range {
+                return Err(self.fail("Illegal exception table range"));
+            }
+            if !is_boundary(entry.handler_pc) {
+                return Err(self.fail("Illegal exception table handler"));
+            }
+            let catch_type = self.catch_type(entry)?;
+            if !self
+                .class
+                .is_assignable(catch_type, VType::Reference(self.class.java_lang_throwable))
+            {
+                return Err(self.fail_with(
+                    "Catch type is not a subclass of Throwable",
+                    self.describe(catch_type),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn catch_type(&self, entry: &ExceptionTableEntry) -> VerifyResult<VType> {
+        if entry.catch_type == 0 {
+            return Ok(VType::Reference(self.class.java_lang_throwable));
+        }
+        self.class_ref(entry.catch_type).map(VType::Reference)
+    }
+
+    /// The handlers of the instruction at the current pc must accept its locals with the
+    /// exception as the only operand.
+    fn check_handlers(&self, frame: &Frame) -> VerifyResult<()> {
+        let pc = self.pc.get();
+        for entry in self.exception_table {
+            if !(entry.start_pc <= pc && pc < entry.end_pc) {
+                continue;
+            }
+            let handler_frame = Frame {
+                locals: frame.locals.clone(),
+                stack: vec![self.catch_type(entry)?],
+                flag_this_uninit: frame.flag_this_uninit,
+            };
+            let target = self.stack_map.get(&entry.handler_pc).ok_or_else(|| {
+                self.fail(format!(
+                    "Expecting a stackmap frame at exception handler {}",
+                    entry.handler_pc
+                ))
+            })?;
+            self.check_assignable_frame(&handler_frame, target, entry.handler_pc)?;
+        }
+        Ok(())
+    }
+
+    /// https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.10.1.4
+    fn check_assignable_frame(&self, from: &Frame, to: &Frame, target: u16) -> VerifyResult<()> {
+        let reason = || format!("Type state does not match the stack map frame at {target}");
+        if from.stack.len() != to.stack.len() {
+            return Err(self.fail_with(
+                reason(),
+                format!(
+                    "stack height: expected {}, found {}",
+                    to.stack.len(),
+                    from.stack.len()
+                ),
+            ));
+        }
+        let slots = [
+            ("locals", &from.locals, &to.locals),
+            ("stack", &from.stack, &to.stack),
+        ];
+        for (kind, from_types, to_types) in slots {
+            for (i, (from_type, to_type)) in from_types.iter().zip(to_types.iter()).enumerate() {
+                if !self.class.is_assignable(*from_type, *to_type) {
+                    return Err(self.fail_with(
+                        reason(),
+                        format!(
+                            "{kind}[{i}]: expected {}, found {}",
+                            self.describe(*to_type),
+                            self.describe(*from_type)
+                        ),
+                    ));
+                }
+            }
+        }
+        if from.flag_this_uninit && !to.flag_this_uninit {
+            return Err(self.fail_with(reason(), "this is not initialized"));
+        }
+        Ok(())
+    }
+
+    fn branch(&self, frame: &Frame, offset: i32) -> VerifyResult<()> {
+        let target = i64::from(self.pc.get()) + i64::from(offset);
+        let target = u16::try_from(target)
+            .ok()
+            .filter(|target| self.instructions.contains_key(target))
+            .ok_or_else(|| {
+                self.fail_with("Illegal target of jump or branch", target.to_string())
+            })?;
+        let mapped = self.stack_map.get(&target).ok_or_else(|| {
+            self.fail(format!(
+                "Expecting a stackmap frame at branch target {target}"
+            ))
+        })?;
+        self.check_assignable_frame(frame, mapped, target)
+    }
+
+    fn pop_any(&self, frame: &mut Frame) -> VerifyResult<VType> {
+        frame
+            .stack
+            .pop()
+            .ok_or_else(|| self.fail("Operand stack underflow"))
+    }
+
+    fn pop(&self, frame: &mut Frame, expected: VType) -> VerifyResult<VType> {
+        let actual = self.pop_any(frame)?;
+        if !self.class.is_assignable(actual, expected) {
+            return Err(self.mismatch(
+                "Bad type on operand stack",
+                &self.describe(expected),
+                actual,
+            ));
+        }
+        Ok(actual)
+    }
+
+    /// An initialized reference, or null.
+    fn pop_object(&self, frame: &mut Frame) -> VerifyResult<VType> {
+        self.pop(frame, VType::Reference(self.class.java_lang_object))
+    }
+
+    fn pop_category1(&self, frame: &mut Frame) -> VerifyResult<VType> {
+        let actual = self.pop_any(frame)?;
+        if actual.is_category2() {
+            return Err(self.mismatch("Bad type on operand stack", "a category 1 value", actual));
+        }
+        Ok(actual)
+    }
+
+    fn push(&self, frame: &mut Frame, t: VType) -> VerifyResult<()> {
+        if frame.stack_size() + t.size() > self.max_stack {
+            return Err(self.fail("Operand stack overflow"));
+        }
+        frame.stack.push(t);
+        Ok(())
+    }
+
+    fn push_all(&self, frame: &mut Frame, types: &[VType]) -> VerifyResult<()> {
+        for t in types {
+            self.push(frame, *t)?;
+        }
+        Ok(())
+    }
+
+    fn local(&self, frame: &Frame, index: usize, size: usize) -> VerifyResult<VType> {
+        if index + size > frame.locals.len() {
+            return Err(self.fail_with("Illegal local variable number", index.to_string()));
+        }
+        Ok(frame.locals[index])
+    }
+
+    fn load(&self, frame: &mut Frame, index: usize, expected: VType) -> VerifyResult<()> {
+        let actual = self.local(frame, index, expected.size())?;
+        if !self.class.is_assignable(actual, expected) {
+            return Err(self.mismatch("Bad local variable type", &self.describe(expected), actual));
+        }
+        self.push(frame, expected)
+    }
+
+    fn store(&self, frame: &mut Frame, index: usize, t: VType) -> VerifyResult<()> {
+        self.local(frame, index, t.size())?;
+        if index > 0 && frame.locals[index - 1].is_category2() {
+            frame.locals[index - 1] = VType::Top;
+        }
+        frame.locals[index] = t;
+        if t.is_category2() {
+            frame.locals[index + 1] = VType::Top;
+        }
+        Ok(())
+    }
+
+    fn load_and_store(&self, frame: &mut Frame, index: usize, t: VType) -> VerifyResult<()> {
+        self.pop(frame, t)?;
+        self.store(frame, index, t)
+    }
+
+    fn aload(&self, frame: &mut Frame, index: usize) -> VerifyResult<()> {
+        let actual = self.local(frame, index, 1)?;
+        if !actual.is_reference() {
+            return Err(self.mismatch("Bad local variable type", "reference", actual));
+        }
+        self.push(frame, actual)
+    }
+
+    fn astore(&self, frame: &mut Frame, index: usize) -> VerifyResult<()> {
+        let actual = self.pop_any(frame)?;
+        if !actual.is_reference() {
+            return Err(self.mismatch("Bad type on operand stack", "reference", actual));
+        }
+        self.store(frame, index, actual)
+    }
+
+    fn unary(&self, frame: &mut Frame, from: VType, to: VType) -> VerifyResult<()> {
+        self.pop(frame, from)?;
+        self.push(frame, to)
+    }
+
+    fn binary(&self, frame: &mut Frame, operands: VType, result: VType) -> VerifyResult<()> {
+        self.pop(frame, operands)?;
+        self.pop(frame, operands)?;
+        self.push(frame, result)
+    }
+
+    fn shift(&self, frame: &mut Frame, t: VType) -> VerifyResult<()> {
+        self.pop(frame, VType::Integer)?;
+        self.pop(frame, t)?;
+        self.push(frame, t)
+    }
+
+    /// Pops an array of one of the `descriptors`, or null.
+    fn pop_array(&self, frame: &mut Frame, descriptors: &[&str]) -> VerifyResult<()> {
+        let actual = self.pop_any(frame)?;
+        let is_array = match actual {
+            VType::Null => true,
+            VType::Reference(name) => descriptors.contains(&self.class.interner().resolve(&name)),
+            _ => false,
+        };
+        if !is_array {
+            return Err(self.mismatch(
+                "Bad type on operand stack",
+                &format!("'{}'", descriptors[0]),
+                actual,
+            ));
+        }
+        Ok(())
+    }
+
+    fn array_load(
+        &self,
+        frame: &mut Frame,
+        descriptors: &[&str],
+        element: VType,
+    ) -> VerifyResult<()> {
+        self.pop(frame, VType::Integer)?;
+        self.pop_array(frame, descriptors)?;
+        self.push(frame, element)
+    }
+
+    fn array_store(
+        &self,
+        frame: &mut Frame,
+        descriptors: &[&str],
+        element: VType,
+    ) -> VerifyResult<()> {
+        self.pop(frame, element)?;
+        self.pop(frame, VType::Integer)?;
+        self.pop_array(frame, descriptors)
+    }
+
+    /// Pops an array of references, or null, and returns the type of its elements.
+    fn pop_reference_array(&self, frame: &mut Frame) -> VerifyResult<VType> {
+        let actual = self.pop_any(frame)?;
+        let interner = self.class.interner();
+        let element = match actual {
+            VType::Null => Some(VType::Null),
+            VType::Reference(name) => interner
+                .resolve(&name)
+                .strip_prefix('[')
+                .and_then(|component| VType::from_descriptor(component, interner))
+                .filter(|element| matches!(element, VType::Reference(_))),
+            _ => None,
+        };
+        element.ok_or_else(|| {
+            self.mismatch("Bad type on operand stack", "'[Ljava/lang/Object;'", actual)
+        })
+    }
+
+    fn class_ref(&self, idx: u16) -> VerifyResult<Symbol> {
+        self.class
+            .cp
+            .get_class_sym(&idx, self.class.interner())
+            .map_err(|_| self.fail_with("Bad class reference", idx.to_string()))
+    }
+
+    fn ldc(&self, frame: &mut Frame, idx: u16, wide: bool) -> VerifyResult<()> {
+        let constant = self
+            .class
+            .cp
+            .get_constant(&idx, self.class.interner())
+            .map_err(|_| self.fail_with("Bad constant pool index", idx.to_string()))?;
+        let t = match (constant, wide) {
+            (RuntimeConstant::Integer(_), false) => VType::Integer,
+            (RuntimeConstant::Float(_), false) => VType::Float,
+            (RuntimeConstant::String(_), false) => self.reference("java/lang/String"),
+            (RuntimeConstant::Class(_), false) => self.reference("java/lang/Class"),
+            (RuntimeConstant::MethodType(_), false) => {
+                self.reference("java/lang/invoke/MethodType")
+            }
+            (RuntimeConstant::MethodHandle(_), false) => {
+                self.reference("java/lang/invoke/MethodHandle")
+            }
+            (RuntimeConstant::Long(_), true) => VType::Long,
+            (RuntimeConstant::Double(_), true) => VType::Double,
+            (RuntimeConstant::Dynamic(_), _) => {
+                let interner = self.class.interner();
+                let view = self
+                    .class
+                    .cp
+                    .get_dynamic_view(&idx, interner)
+                    .map_err(|_| self.fail_with("Bad dynamic constant", idx.to_string()))?;
+                let desc = interner.resolve(&view.nat_view.descriptor_sym);
+                let t = VType::from_descriptor(desc, interner)
+                    .ok_or_else(|| self.fail_with("Bad field descriptor", desc))?;
+                if t.is_category2() != wide {
+                    return Err(self.fail_with("Bad constant for ldc", desc));
+                }
+                t
+            }
+            (other, _) => {
+                return Err(self.fail_with("Bad constant for ldc", other.get_type().to_string()));
+            }
+        };
+        self.push(frame, t)
+    }
+
+    /// Class, name, descriptor and type of a field reference.
+    fn field_ref(&self, idx: u16) -> VerifyResult<(Symbol, Symbol, Symbol, VType)> {
+        let interner = self.class.interner();
+        let view = self
+            .class
+            .cp
+            .get_field_view(&idx, interner)
+            .map_err(|_| self.fail_with("Bad field reference", idx.to_string()))?;
+        let desc = interner.resolve(&view.name_and_type.descriptor_sym);
+        let t = VType::from_descriptor(desc, interner)
+            .ok_or_else(|| self.fail_with("Bad field descriptor", desc))?;
+        Ok((
+            view.class_sym,
+            view.name_and_type.name_sym,
+            view.name_and_type.descriptor_sym,
+            t,
+        ))
+    }
+
+    /// Pops the object whose member of `class` is accessed, of the verified class if the member
+    /// is a protected one of a superclass in another runtime package.
+    fn pop_member_object(
+        &self,
+        frame: &mut Frame,
+        class: Symbol,
+        (name, desc, is_method): (Symbol, Symbol, bool),
+        instruction: &str,
+    ) -> VerifyResult<VType> {
+        let object = self.pop(frame, VType::Reference(class))?;
+        let this = VType::Reference(self.class.this_name);
+        if self.class.is_protected_access(class, name, desc, is_method)
+            && !self.class.is_assignable(object, this)
+        {
+            return Err(self.mismatch(
+                format!("Bad access to protected data in {instruction}"),
+                &self.describe(this),
+                object,
+            ));
+        }
+        Ok(object)
+    }
+
+    fn getfield(&self, frame: &mut Frame, idx: u16) -> VerifyResult<()> {
+        let (class, name, desc, t) = self.field_ref(idx)?;
+        self.pop_member_object(frame, class, (name, desc, false), "getfield")?;
+        self.push(frame, t)
+    }
+
+    fn putfield(&self, frame: &mut Frame, idx: u16) -> VerifyResult<()> {
+        let (class, name, desc, t) = self.field_ref(idx)?;
+        self.pop(frame, t)?;
+        // a constructor may set the fields of its class before calling super()
+        if class == self.class.this_name && frame.stack.last() == Some(&VType::UninitializedThis) {
+            self.pop_any(frame)?;
+            return Ok(());
+        }
+        self.pop_member_object(frame, class, (name, desc, false), "putfield")?;
+        Ok(())
+    }
+
+    /// Parameter types and return type of a method descriptor.
+    fn signature(&self, desc: Symbol) -> VerifyResult<(Vec<VType>, Option<VType>)> {
+        let interner = self.class.interner();
+        let desc = interner.resolve(&desc);
+        let bad_descriptor = || self.fail_with("Bad method descriptor", desc);
+        let (params, ret) = split_method_descriptor(desc).ok_or_else(bad_descriptor)?;
+        let params = params
+            .into_iter()
+            .map(|param| VType::from_descriptor(param, interner).ok_or_else(bad_descriptor))
+            .collect::<VerifyResult<_>>()?;
+        let ret = ret
+            .map(|ret| VType::from_descriptor(ret, interner).ok_or_else(bad_descriptor))
+            .transpose()?;
+        Ok((params, ret))
+    }
+
+    fn pop_args(&self, frame: &mut Frame, params: &[VType]) -> VerifyResult<()> {
+        for param in params.iter().rev() {
+            self.pop(frame, *param)?;
+        }
+        Ok(())
+    }
+
+    fn push_return(&self, frame: &mut Frame, ret: Option<VType>) -> VerifyResult<()> {
+        match ret {
+            Some(ret) => self.push(frame, ret),
+            None => Ok(()),
+        }
+    }
+
+    fn check_not_internal(&self, name: Symbol) -> VerifyResult<()> {
+        let name = self.class.interner().resolve(&name);
+        if name.starts_with('<') {
+            return Err(self.fail_with("Illegal call to internal method", name));
+        }
+        Ok(())
+    }
+
+    fn invoke(&self, frame: &mut Frame, instruction: &Instruction) -> VerifyResult<()> {
+        let interner = self.class.interner();
+        let cp = self.class.cp;
+        let bad_reference = |idx: &u16| self.fail_with("Bad method reference", idx.to_string());
+        match instruction {
+            Instruction::InvokeVirtual(idx) => {
+                let view = cp
+                    .get_method_view(idx, interner)
+                    .map_err(|_| bad_reference(idx))?;
+                self.check_not_internal(view.name_and_type.name_sym)?;
+                let (name, desc) = (
+                    view.name_and_type.name_sym,
+                    view.name_and_type.descriptor_sym,
+                );
+                let (params, ret) = self.signature(desc)?;
+                self.pop_args(frame, &params)?;
+                // arrays may call the protected Object.clone, they implement it publicly
+                let is_array_clone = interner.resolve(&name) == "clone"
+                    && view.class_sym == self.class.java_lang_object
+                    && matches!(frame.stack.last(), Some(VType::Reference(receiver))
+                        if interner.resolve(receiver).starts_with('['));
+                if is_array_clone {
+                    self.pop_object(frame)?;
+                } else {
+                    self.pop_member_object(
+                        frame,
+                        view.class_sym,
+                        (name, desc, true),
+                        "invokevirtual",
+                    )?;
+                }
+                self.push_return(frame, ret)
+            }
+            Instruction::InvokeInterface(idx, count) => {
+                let view = cp
+                    .get_interface_method_view(idx, interner)
+                    .map_err(|_| bad_reference(idx))?;
+                self.check_not_internal(view.name_and_type.name_sym)?;
+                let (params, ret) = self.signature(view.name_and_type.descriptor_sym)?;
+                let args_size: usize = params.iter().map(|t| t.size()).sum();
+                if args_size + 1 != usize::from(*count) {
+                    return Err(self.fail("Inconsistent args count operand in invokeinterface"));
+                }
+                self.pop_args(frame, &params)?;
+                self.pop_object(frame)?;
+                self.push_return(frame, ret)
+            }
+            Instruction::InvokeStatic(idx) => {
+                let view = cp
+                    .get_method_or_interface_method_view(idx, interner)
+                    .map_err(|_| bad_reference(idx))?;
+                self.check_not_internal(view.name_and_type.name_sym)?;
+                let (params, ret) = self.signature(view.name_and_type.descriptor_sym)?;
+                self.pop_args(frame, &params)?;
+                self.push_return(frame, ret)
+            }
+            Instruction::InvokeDynamic(idx) => {
+                let view = cp
+                    .get_invoke_dynamic_view(idx, interner)
+                    .map_err(|_| bad_reference(idx))?;
+                self.check_not_internal(view.nat_view.name_sym)?;
+                let (params, ret) = self.signature(view.nat_view.descriptor_sym)?;
+                self.pop_args(frame, &params)?;
+                self.push_return(frame, ret)
+            }
+            Instruction::InvokeSpecial(idx) => {
+                let view = cp
+                    .get_method_or_interface_method_view(idx, interner)
+                    .map_err(|_| bad_reference(idx))?;
+                let (params, ret) = self.signature(view.name_and_type.descriptor_sym)?;
+                if interner.resolve(&view.name_and_type.name_sym) != "<init>" {
+                    self.check_not_internal(view.name_and_type.name_sym)?;
+                    self.pop_args(frame, &params)?;
+                    self.pop(frame, VType::Reference(self.class.this_name))?;
+                    return self.push_return(frame, ret);
+                }
+                if ret.is_some() {
+                    return Err(self.fail("Bad <init> method descriptor"));
+                }
+                self.pop_args(frame, &params)?;
+                self.invoke_init(frame, view.class_sym, view.name_and_type.descriptor_sym)
+            }
+            _ => unreachable!("not an invoke instruction"),
+        }
+    }
+
+    /// `invokespecial` of a constructor initializes its receiver everywhere in the frame. A
+    /// protected constructor of a superclass in another runtime package only initializes `this`.
+    fn invoke_init(&self, frame: &mut Frame, class: Symbol, desc: Symbol) -> VerifyResult<()> {
+        let receiver = self.pop_any(frame)?;
+        match receiver {
+            VType::UninitializedThis => {
+                if class != self.class.this_name && Some(class) != self.class.this_info.super_name {
+                    return Err(self.mismatch(
+                        "Bad <init> method call",
+                        &self.describe(VType::Reference(self.class.this_name)),
+                        VType::Reference(class),
+                    ));
+                }
+                frame.initialize(receiver, VType::Reference(self.class.this_name));
+            }
+            VType::Uninitialized(offset) => {
+                let Some(Instruction::New(new_idx)) = self.instructions.get(&offset) else {
+                    return Err(self.fail_with("Expecting new at offset", offset.to_string()));
+                };
+                let new_class = self.class_ref(*new_idx)?;
+                if new_class != class {
+                    return Err(self.mismatch(
+                        "Call to wrong <init> method",
+                        &self.describe(VType::Reference(new_class)),
+                        VType::Reference(class),
+                    ));
+                }
+                let init = self.class.interner().get_or_intern("<init>");
+                if self.class.is_protected_access(class, init, desc, true) {
+                    return Err(self.fail_with(
+                        "Bad access to protected <init> method",
+                        self.describe(VType::Reference(class)),
+                    ));
+                }
+                frame.initialize(receiver, VType::Reference(new_class));
+            }
+            other => {
+                return Err(self.mismatch("Bad type on operand stack", "uninitialized", other));
+            }
+        }
+        Ok(())
+    }
+
+    fn value_return(&self, frame: &mut Frame, t: VType) -> VerifyResult<()> {
+        let Some(ret) = self.return_type else {
+            return Err(self.fail("Method does not expect a return value"));
+        };
+        let matches = match t {
+            VType::Reference(_) => matches!(ret, VType::Reference(_)),
+            _ => ret == t,
+        };
+        if !matches {
+            return Err(self.mismatch("Bad return type", &self.describe(ret), t));
+        }
+        self.pop(frame, ret)?;
+        Ok(())
+    }
+
+    fn new_object(&self, frame: &mut Frame, idx: u16) -> VerifyResult<()> {
+        let class = self.class_ref(idx)?;
+        if self.class.interner().resolve(&class).starts_with('[') {
+            return Err(self.fail("Illegal use of new on an array class"));
+        }
+        let uninit = VType::Uninitialized(self.pc.get());
+        if frame.stack.contains(&uninit) {
+            return Err(self.fail("Uninitialized object exists on backward branch"));
+        }
+        for local in frame.locals.iter_mut().filter(|local| **local == uninit) {
+            *local = VType::Top;
+        }
+        self.push(frame, uninit)
+    }
+
+    fn multianewarray(&self, frame: &mut Frame, idx: u16, dimensions: u8) -> VerifyResult<()> {
+        let class = self.class_ref(idx)?;
+        let array_dimensions = self
+            .class
+            .interner()
+            .resolve(&class)
+            .bytes()
+            .take_while(|b| *b == b'[')
+            .count();
+        if dimensions == 0 || array_dimensions < usize::from(dimensions) {
+            return Err(self.fail("Illegal dimension in multianewarray"));
+        }
+        for _ in 0..dimensions {
+            self.pop(frame, VType::Integer)?;
+        }
+        self.push(frame, VType::Reference(class))
+    }
+
+    /// https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.10.1.9
+    ///
+    /// Applies `instruction` to `frame`, returns whether execution can continue with the next
+    /// instruction.
+    fn execute(&self, frame: &mut Frame, instruction: &Instruction) -> VerifyResult<bool> {
+        use VType::{Double as D, Float as F, Integer as I, Long as L};
+        let f = frame;
+        match instruction {
+            Instruction::Nop => {}
+
+            Instruction::AconstNull => self.push(f, VType::Null)?,
+            Instruction::IconstM1
+            | Instruction::Iconst0
+            | Instruction::Iconst1
+            | Instruction::Iconst2
+            | Instruction::Iconst3
+            | Instruction::Iconst4
+            | Instruction::Iconst5
+            | Instruction::Bipush(_)
+            | Instruction::Sipush(_) => self.push(f, I)?,
+            Instruction::Lconst0 | Instruction::Lconst1 => self.push(f, L)?,
+            Instruction::Fconst0 | Instruction::Fconst1 | Instruction::Fconst2 => {
+                self.push(f, F)?
+            }
+            Instruction::Dconst0 | Instruction::Dconst1 => self.push(f, D)?,
+            Instruction::Ldc(idx) | Instruction::LdcW(idx) => self.ldc(f, *idx, false)?,
+            Instruction::Ldc2W(idx) => self.ldc(f, *idx, true)?,
+
+            Instruction::Iload(n) => self.load(f, usize::from(*n), I)?,
+            Instruction::Iload0 => self.load(f, 0, I)?,
+            Instruction::Iload1 => self.load(f, 1, I)?,
+            Instruction::Iload2 => self.load(f, 2, I)?,
+            Instruction::Iload3 => self.load(f, 3, I)?,
+            Instruction::Lload(n) => self.load(f, usize::from(*n), L)?,
+            Instruction::Lload0 => self.load(f, 0, L)?,
+            Instruction::Lload1 => self.load(f, 1, L)?,
+            Instruction::Lload2 => self.load(f, 2, L)?,
+            Instruction::Lload3 => self.load(f, 3, L)?,
+            Instruction::Fload(n) => self.load(f, usize::from(*n), F)?,
+            Instruction::Fload0 => self.load(f, 0, F)?,
+            Instruction::Fload1 => self.load(f, 1, F)?,
+            Instruction::Fload2 => self.load(f, 2, F)?,
+            Instruction::Fload3 => self.load(f, 3, F)?,
+            Instruction::Dload(n) => self.load(f, usize::from(*n), D)?,
+            Instruction::Dload0 => self.load(f, 0, D)?,
+            Instruction::Dload1 => self.load(f, 1, D)?,
+            Instruction::Dload2 => self.load(f, 2, D)?,
+            Instruction::Dload3 => self.load(f, 3, D)?,
+            Instruction::Aload(n) => self.aload(f, usize::from(*n))?,
+            Instruction::Aload0 => self.aload(f, 0)?,
+            Instruction::Aload1 => self.aload(f, 1)?,
+            Instruction::Aload2 => self.aload(f, 2)?,
+            Instruction::Aload3 => self.aload(f, 3)?,
+
+            Instruction::Istore(n) => self.load_and_store(f, usize::from(*n), I)?,
+            Instruction::Istore0 => self.load_and_store(f, 0, I)?,
+            Instruction::Istore1 => self.load_and_store(f, 1, I)?,
+            Instruction::Istore2 => self.load_and_store(f, 2, I)?,
+            Instruction::Istore3 => self.load_and_store(f, 3, I)?,
+            Instruction::Lstore(n) => self.load_and_store(f, usize::from(*n), L)?,
+            Instruction::Lstore0 => self.load_and_store(f, 0, L)?,
+            Instruction::Lstore1 => self.load_and_store(f, 1, L)?,
+            Instruction::Lstore2 => self.load_and_store(f, 2, L)?,
+            Instruction::Lstore3 => self.load_and_store(f, 3, L)?,
+            Instruction::Fstore(n) => self.load_and_store(f, usize::from(*n), F)?,
+            Instruction::Fstore0 => self.load_and_store(f, 0, F)?,
+            Instruction::Fstore1 => self.load_and_store(f, 1, F)?,
+            Instruction::Fstore2 => self.load_and_store(f, 2, F)?,
+            Instruction::Fstore3 => self.load_and_store(f, 3, F)?,
+            Instruction::Dstore(n) => self.load_and_store(f, usize::from(*n), D)?,
+            Instruction::Dstore0 => self.load_and_store(f, 0, D)?,
+            Instruction::Dstore1 => self.load_and_store(f, 1, D)?,
+            Instruction::Dstore2 => self.load_and_store(f, 2, D)?,
+            Instruction::Dstore3 => self.load_and_store(f, 3, D)?,
+            Instruction::Astore(n) => self.astore(f, usize::from(*n))?,
+            Instruction::Astore0 => self.astore(f, 0)?,
+            Instruction::Astore1 => self.astore(f, 1)?,
+            Instruction::Astore2 => self.astore(f, 2)?,
+            Instruction::Astore3 => self.astore(f, 3)?,
+            Instruction::Iinc(n, _) => {
+                let actual = self.local(f, usize::from(*n), 1)?;
+                if actual != I {
+                    return Err(self.mismatch("Bad local variable type", "integer", actual));
+                }
+            }
+
+            Instruction::Iaload => self.array_load(f, &["[I"], I)?,
+            Instruction::Laload => self.array_load(f, &["[J"], L)?,
+            Instruction::Faload => self.array_load(f, &["[F"], F)?,
+            Instruction::Daload => self.array_load(f, &["[D"], D)?,
+            Instruction::Baload => self.array_load(f, &["[B", "[Z"], I)?,
+            Instruction::Caload => self.array_load(f, &["[C"], I)?,
+            Instruction::Saload => self.array_load(f, &["[S"], I)?,
+            Instruction::Aaload => {
+                self.pop(f, I)?;
+                let element = self.pop_reference_array(f)?;
+                self.push(f, element)?;
+            }
+            Instruction::Iastore => self.array_store(f, &["[I"], I)?,
+            Instruction::Lastore => self.array_store(f, &["[J"], L)?,
+            Instruction::Fastore => self.array_store(f, &["[F"], F)?,
+            Instruction::Dastore => self.array_store(f, &["[D"], D)?,
+            Instruction::Bastore => self.array_store(f, &["[B", "[Z"], I)?,
+            Instruction::Castore => self.array_store(f, &["[C"], I)?,
+            Instruction::Sastore => self.array_store(f, &["[S"], I)?,
+            Instruction::Aastore => {
+                // the element type is checked against the runtime class of the array
+                self.pop_object(f)?;
+                self.pop(f, I)?;
+                self.pop_reference_array(f)?;
+            }
+
+            Instruction::Pop => {
+                self.pop_category1(f)?;
+            }
+            Instruction::Pop2 => {
+                if !self.pop_any(f)?.is_category2() {
+                    self.pop_category1(f)?;
+                }
+            }
+            Instruction::Dup => {
+                let v1 = self.pop_category1(f)?;
+                self.push_all(f, &[v1, v1])?;
+            }
+            Instruction::DupX1 => {
+                let v1 = self.pop_category1(f)?;
+                let v2 = self.pop_category1(f)?;
+                self.push_all(f, &[v1, v2, v1])?;
+            }
+            Instruction::DupX2 => {
+                let v1 = self.pop_category1(f)?;
+                let v2 = self.pop_any(f)?;
+                if v2.is_category2() {
+                    self.push_all(f, &[v1, v2, v1])?;
+                } else {
+                    let v3 = self.pop_category1(f)?;
+                    self.push_all(f, &[v1, v3, v2, v1])?;
+                }
+            }
+            Instruction::Dup2 => {
+                let v1 = self.pop_any(f)?;
+                if v1.is_category2() {
+                    self.push_all(f, &[v1, v1])?;
+                } else {
+                    let v2 = self.pop_category1(f)?;
+                    self.push_all(f, &[v2, v1, v2, v1])?;
+                }
+            }
+            Instruction::Dup2X1 => {
+                let v1 = self.pop_any(f)?;
+                if v1.is_category2() {
+                    let v2 = self.pop_category1(f)?;
+                    self.push_all(f, &[v1, v2, v1])?;
+                } else {
+                    let v2 = self.pop_category1(f)?;
+                    let v3 = self.pop_category1(f)?;
+                    self.push_all(f, &[v2, v1, v3, v2, v1])?;
+                }
+            }
+            Instruction::Dup2X2 => {
+                let v1 = self.pop_any(f)?;
+                if v1.is_category2() {
+                    let v2 = self.pop_any(f)?;
+                    if v2.is_category2() {
+                        self.push_all(f, &[v1, v2, v1])?;
+                    } else {
+                        let v3 = self.pop_category1(f)?;
+                        self.push_all(f, &[v1, v3, v2, v1])?;
+                    }
+                } else {
+                    let v2 = self.pop_category1(f)?;
+                    let v3 = self.pop_any(f)?;
+                    if v3.is_category2() {
+                        self.push_all(f, &[v2, v1, v3, v2, v1])?;
+                    } else {
+                        let v4 = self.pop_category1(f)?;
+                        self.push_all(f, &[v2, v1, v4, v3, v2, v1])?;
+                    }
+                }
+            }
+            Instruction::Swap => {
+                let v1 = self.pop_category1(f)?;
+                let v2 = self.pop_category1(f)?;
+                self.push_all(f, &[v1, v2])?;
+            }
+
+            Instruction::Iadd
+            | Instruction::Isub
+            | Instruction::Imul
+            | Instruction::Idiv
+            | Instruction::Irem
+            | Instruction::Iand
+            | Instruction::Ior
+            | Instruction::Ixor => self.binary(f, I, I)?,
+            Instruction::Ladd
+            | Instruction::Lsub
+            | Instruction::Lmul
+            | Instruction::Ldiv
+            | Instruction::Lrem
+            | Instruction::Land
+            | Instruction::Lor
+            | Instruction::Lxor => self.binary(f, L, L)?,
+            Instruction::Fadd
+            | Instruction::Fsub
+            | Instruction::Fmul
+            | Instruction::Fdiv
+            | Instruction::Frem => self.binary(f, F, F)?,
+            Instruction::Dadd
+            | Instruction::Dsub
+            | Instruction::Dmul
+            | Instruction::Ddiv
+            | Instruction::Drem => self.binary(f, D, D)?,
+            Instruction::Ishl | Instruction::Ishr | Instruction::Iushr => self.shift(f, I)?,
+            Instruction::Lshl | Instruction::Lshr | Instruction::Lushr => self.shift(f, L)?,
+            Instruction::Ineg => self.unary(f, I, I)?,
+            Instruction::Lneg => self.unary(f, L, L)?,
+            Instruction::Fneg => self.unary(f, F, F)?,
+            Instruction::Dneg => self.unary(f, D, D)?,
+            Instruction::Lcmp => self.binary(f, L, I)?,
+            Instruction::Fcmpl | Instruction::Fcmpg => self.binary(f, F, I)?,
+            Instruction::Dcmpl | Instruction::Dcmpg => self.binary(f, D, I)?,
+
+            Instruction::I2l => self.unary(f, I, L)?,
+            Instruction::I2f => self.unary(f, I, F)?,
+            Instruction::I2d => self.unary(f, I, D)?,
+            Instruction::L2i => self.unary(f, L, I)?,
+            Instruction::L2f => self.unary(f, L, F)?,
+            Instruction::L2d => self.unary(f, L, D)?,
+            Instruction::F2i => self.unary(f, F, I)?,
+            Instruction::F2l => self.unary(f, F, L)?,
+            Instruction::F2d => self.unary(f, F, D)?,
+            Instruction::D2i => self.unary(f, D, I)?,
+            Instruction::D2l => self.unary(f, D, L)?,
+            Instruction::D2f => self.unary(f, D, F)?,
+            Instruction::I2b | Instruction::I2c | Instruction::I2s => self.unary(f, I, I)?,
+
+            Instruction::IfEq(offset)
+            | Instruction::IfNe(offset)
+            | Instruction::IfLt(offset)
+            | Instruction::IfGe(offset)
+            | Instruction::IfGt(offset)
+            | Instruction::IfLe(offset) => {
+                self.pop(f, I)?;
+                self.branch(f, i32::from(*offset))?;
+            }
+            Instruction::IfIcmpeq(offset)
+            | Instruction::IfIcmpne(offset)
+            | Instruction::IfIcmplt(offset)
+            | Instruction::IfIcmpge(offset)
+            | Instruction::IfIcmpgt(offset)
+            | Instruction::IfIcmple(offset) => {
+                self.pop(f, I)?;
+                self.pop(f, I)?;
+                self.branch(f, i32::from(*offset))?;
+            }
+            Instruction::IfAcmpEq(offset) | Instruction::IfAcmpNe(offset) => {
+                self.pop_object(f)?;
+                self.pop_object(f)?;
+                self.branch(f, i32::from(*offset))?;
+            }
+            Instruction::Ifnull(offset) | Instruction::Ifnonnull(offset) => {
+                self.pop_object(f)?;
+                self.branch(f, i32::from(*offset))?;
+            }
+            Instruction::Goto(offset) => {
+                self.branch(f, i32::from(*offset))?;
+                return Ok(false);
+            }
+            Instruction::GotoW(offset) => {
+                self.branch(f, *offset)?;
+                return Ok(false);
+            }
+            Instruction::TableSwitch(data) => {
+                self.pop(f, I)?;
+                self.branch(f, data.default_offset)?;
+                for offset in &data.offsets {
+                    self.branch(f, *offset)?;
+                }
+                return Ok(false);
+            }
+            Instruction::Lookupswitch(data) => {
+                if data.pairs.windows(2).any(|pair| pair[0].0 >= pair[1].0) {
+                    return Err(self.fail("Bad lookupswitch instruction"));
+                }
+                self.pop(f, I)?;
+                self.branch(f, data.default_offset)?;
+                for (_, offset) in &data.pairs {
+                    self.branch(f, *offset)?;
+                }
+                return Ok(false);
+            }
+
+            Instruction::Ireturn => {
+                self.value_return(f, I)?;
+                return Ok(false);
+            }
+            Instruction::Lreturn => {
+                self.value_return(f, L)?;
+                return Ok(false);
+            }
+            Instruction::Freturn => {
+                self.value_return(f, F)?;
+                return Ok(false);
+            }
+            Instruction::Dreturn => {
+                self.value_return(f, D)?;
+                return Ok(false);
+            }
+            Instruction::Areturn => {
+                self.value_return(f, VType::Reference(self.class.java_lang_object))?;
+                return Ok(false);
+            }
+            Instruction::Return => {
+                if self.return_type.is_some() {
+                    return Err(self.fail("Method expects a return value"));
+                }
+                if self.is_init && f.flag_this_uninit {
+                    return Err(self.fail("Constructor must call super() or this() before return"));
+                }
+                return Ok(false);
+            }
+            Instruction::Athrow => {
+                self.pop(f, VType::Reference(self.class.java_lang_throwable))?;
+                return Ok(false);
+            }
+
+            Instruction::Getstatic(idx) => {
+                let (_, _, _, t) = self.field_ref(*idx)?;
+                self.push(f, t)?;
+            }
+            Instruction::Putstatic(idx) => {
+                let (_, _, _, t) = self.field_ref(*idx)?;
+                self.pop(f, t)?;
+            }
+            Instruction::Getfield(idx) => self.getfield(f, *idx)?,
+            Instruction::Putfield(idx) => self.putfield(f, *idx)?,
+            Instruction::InvokeVirtual(_)
+            | Instruction::InvokeInterface(_, _)
+            | Instruction::InvokeStatic(_)
+            | Instruction::InvokeSpecial(_)
+            | Instruction::InvokeDynamic(_) => self.invoke(f, instruction)?,
+
+            Instruction::New(idx) => self.new_object(f, *idx)?,
+            Instruction::Newarray(array_type) => {
+                self.pop(f, I)?;
+                self.push(f, self.reference(array_type.descriptor()))?;
+            }
+            Instruction::Anewarray(idx) => {
+                let class = self.class_ref(*idx)?;
+                let class = self.class.interner().resolve(&class);
+                let array = if class.starts_with('[') {
+                    format!("[{class}")
+                } else {
+                    format!("[L{class};")
+                };
+                self.pop(f, I)?;
+                self.push(f, self.reference(&array))?;
+            }
+            Instruction::Multianewarray(idx, dimensions) => {
+                self.multianewarray(f, *idx, *dimensions)?
+            }
+            Instruction::ArrayLength => {
+                let actual = self.pop_any(f)?;
+                let is_array = match actual {
+                    VType::Null => true,
+                    VType::Reference(name) => self.class.interner().resolve(&name).starts_with('['),
+                    _ => false,
+                };
+                if !is_array {
+                    return Err(self.mismatch("Bad type on operand stack", "an array", actual));
+                }
+                self.push(f, I)?;
+            }
+            Instruction::Checkcast(idx) => {
+                let class = self.class_ref(*idx)?;
+                self.pop_object(f)?;
+                self.push(f, VType::Reference(class))?;
+            }
+            Instruction::Instanceof(idx) => {
+                self.class_ref(*idx)?;
+                self.pop_object(f)?;
+                self.push(f, I)?;
+            }
+            Instruction::Monitorenter | Instruction::Monitorexit => {
+                self.pop_object(f)?;
+            }
+
+            Instruction::Jsr(_) | Instruction::JsrW(_) | Instruction::Ret(_) => {
+                return Err(self.fail_with(
+                    "Bad instruction",
+                    "jsr and ret are not allowed with a StackMapTable",
+                ));
+            }
+            Instruction::Breakpoint | Instruction::Impdep1 | Instruction::Impdep2 => {
+                return Err(self.fail_with("Bad instruction", "reserved opcode"));
+            }
+        }
+        Ok(true)
+    }
+}
@@ -0,0 +1,388 @@
+use crate::class_loader::BuiltinLoader;
+use crate::error::JvmError;
+use crate::heap::HeapRef;
+use crate::heap::method_area::MethodArea;
+use crate::keys::ClassId;
+use crate::rt::constant_pool::RuntimeConstantPool;
+use crate::rt::verifier::method::MethodVerifier;
+use crate::rt::verifier::types::VType;
+use crate::{Symbol, build_exception};
+use jclass::ClassFile;
+use jclass::attribute::method::MethodAttribute;
+use jclass::method::MethodInfo;
+use lasso::ThreadedRodeo;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+mod method;
+mod types;
+
+/// Class files older than Java 6 have no `StackMapTable`, they would need verification by
+/// type inference and are not verified.
+const FIRST_TYPE_CHECKED_VERSION: u16 = 50;
+
+/// https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.10.1
+///
+/// Type checks the code of `methods` of the class `this_id` against their stack map frames,
+/// `cp` being the constant pool of their class file. The first method that doesn't type check
+/// is reported as a `VerifyError`.
+///
+/// Assignments between classes the defining loader hasn't loaded yet can't be checked here,
+/// they are returned to be checked once the class is linked.
+pub(crate) fn verify(
+    methods: &[MethodInfo],
+    major_version: u16,
+    this_id: ClassId,
+    cp: &RuntimeConstantPool,
+    method_area: &MethodArea,
+) -> Result<Vec<VerificationConstraint>, JvmError> {
+    let class = method_area.get_class_like(&this_id)?;
+    if major_version < FIRST_TYPE_CHECKED_VERSION || !method_area.should_verify(class.loader()) {
+        return Ok(Vec::new());
+    }
+    let interner = method_area.interner();
+    let verifier = ClassVerifier {
+        method_area,
+        cp,
+        this_id,
+        loader: class.loader(),
+        this_name: class.name(),
+        this_info: ClassInfo {
+            super_name: class
+                .get_super()
+                .map(|super_id| method_area.get_class(&super_id).get_name()),
+            is_interface: class.flags().is_interface(),
+        },
+        java_lang_object: interner.get_or_intern("java/lang/Object"),
+        java_lang_throwable: interner.get_or_intern("java/lang/Throwable"),
+        class_infos: RefCell::new(HashMap::new()),
+        constraints: RefCell::new(Vec::new()),
+    };
+
+    for method in methods {
+        let Some(code) = method.attributes.iter().find_map(|attr| match attr {
+            MethodAttribute::Code(code) => Some(code),
+            _ => None,
+        }) else {
+            continue;
+        };
+        let name = verifier.cp.get_utf8_sym(&method.name_index, interner)?;
+        let desc = verifier
+            .cp
+            .get_utf8_sym(&method.descriptor_index, interner)?;
+        MethodVerifier::new(&verifier, method.access_flags, name, desc, code)
+            .and_then(|mut method_verifier| method_verifier.verify())
+            .map_err(|failure| {
+                let mut message = format!(
+                    "{} in {}.{}{} @{}",
+                    failure.reason,
+                    interner.resolve(&verifier.this_name),
+                    interner.resolve(&name),
+                    interner.resolve(&desc),
+                    failure.pc
+                );
+                if let Some(detail) = failure.detail {
+                    message.push_str(": ");
+                    message.push_str(&detail);
+                }
+                build_exception!(VerifyError, message)
+            })?;
+    }
+    Ok(verifier.constraints.into_inner())
+}
+
+/// Checks the constraints between classes the defining loader of `this_id` has loaded by now,
+/// the class being linked. Returns the others, their classes can only be loaded once the class
+/// is published.
+pub(crate) fn check_loaded_constraints(
+    constraints: Vec<VerificationConstraint>,
+    this_id: ClassId,
+    method_area: &MethodArea,
+) -> Result<Vec<VerificationConstraint>, JvmError> {
+    let class = method_area.get_class_like(&this_id)?;
+    let is_builtin = method_area.is_builtin_loader(class.loader());
+    let find_loaded = |name| {
+        method_area
+            .find_loaded_class(class.loader(), name)
+            .filter(|class_id| {
+                !is_builtin || method_area.is_visible_to_builtin_loader(class.loader(), *class_id)
+            })
+    };
+    let mut unchecked = Vec::new();
+    for constraint in constraints {
+        match (find_loaded(constraint.from), find_loaded(constraint.to)) {
+            (Some(from), Some(to)) => constraint.check(from, to, class.name(), method_area)?,
+            _ => unchecked.push(constraint),
+        }
+    }
+    Ok(unchecked)
+}
+
+/// `from` must be assignable to `to`, both named in the defining loader of the verified class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VerificationConstraint {
+    pub from: Symbol,
+    pub to: Symbol,
+}
+
+impl VerificationConstraint {
+    /// Checks the constraint of the class `class_name` with the classes its names resolved to.
+    pub fn check(
+        &self,
+        from: ClassId,
+        to: ClassId,
+        class_name: Symbol,
+        method_area: &MethodArea,
+    ) -> Result<(), JvmError> {
+        if method_area.get_class(&to).is_interface() || method_area.is_assignable_from(to, from) {
+            return Ok(());
+        }
+        let interner = method_area.interner();
+        let pretty = |name| interner.resolve(&name).replace('/', ".");
+        Err(build_exception!(
+            VerifyError,
+            "Bad type in {}: {} is not assignable to {}",
+            pretty(class_name),
+            pretty(self.from),
+            pretty(self.to)
+        ))
+    }
+}
+
+/// Why the code of a method doesn't type check.
+struct VerifyFailure {
+    pc: u16,
+    reason: String,
+    /// Usually the expected and the actual type.
+    detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ClassInfo {
+    super_name: Option<Symbol>,
+    is_interface: bool,
+}
+
+struct ClassVerifier<'a> {
+    method_area: &'a MethodArea,
+    cp: &'a RuntimeConstantPool,
+    this_id: ClassId,
+    loader: Option<HeapRef>,
+    this_name: Symbol,
+    this_info: ClassInfo,
+    java_lang_object: Symbol,
+    java_lang_throwable: Symbol,
+    class_infos: RefCell<HashMap<Symbol, Option<ClassInfo>>>,
+    constraints: RefCell<Vec<VerificationConstraint>>,
+}
+
+impl ClassVerifier<'_> {
+    fn interner(&self) -> &ThreadedRodeo {
+        self.method_area.interner()
+    }
+
+    /// https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.10.1.2
+    fn is_assignable(&self, from: VType, to: VType) -> bool {
+        match (from, to) {
+            _ if from == to => true,
+            (_, VType::Top) => true,
+            (VType::Null, VType::Reference(_)) => true,
+            (VType::Reference(from), VType::Reference(to)) => self.is_java_assignable(from, to),
+            _ => false,
+        }
+    }
+
+    /// `isJavaAssignable` of the JVMS: interfaces are treated like `Object`, any class can be
+    /// assigned to them and the interpreter checks the actual class on invocation.
+    fn is_java_assignable(&self, from: Symbol, to: Symbol) -> bool {
+        if from == to || to == self.java_lang_object {
+            return true;
+        }
+        let interner = self.interner();
+        let (from_name, to_name) = (interner.resolve(&from), interner.resolve(&to));
+        match (from_name.strip_prefix('['), to_name.strip_prefix('[')) {
+            (Some(from_component), Some(to_component)) => {
+                match (
+                    component_class(from_component),
+                    component_class(to_component),
+                ) {
+                    (Some(from_class), Some(to_class)) => self.is_java_assignable(
+                        interner.get_or_intern(from_class),
+                        interner.get_or_intern(to_class),
+                    ),
+                    _ => from_component == to_component,
+                }
+            }
+            (Some(_), None) => matches!(to_name, "java/lang/Cloneable" | "java/io/Serializable"),
+            (None, Some(_)) => false,
+            (None, None) => match self.class_info(to) {
+                Some(info) if info.is_interface => true,
+                Some(_) => self.is_subclass(from, to),
+                None => self.defer(from, to),
+            },
+        }
+    }
+
+    /// Walks the superclasses of `from`. Above a class that isn't loaded yet the walk is left to
+    /// link time.
+    fn is_subclass(&self, from: Symbol, to: Symbol) -> bool {
+        let mut current = from;
+        loop {
+            if current == to {
+                return true;
+            }
+            match self.class_info(current) {
+                None => return self.defer(current, to),
+                Some(ClassInfo {
+                    super_name: Some(super_name),
+                    ..
+                }) => current = super_name,
+                Some(ClassInfo {
+                    super_name: None, ..
+                }) => return false,
+            }
+        }
+    }
+
+    /// https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.10.1.8
+    ///
+    /// Whether the object an instruction accesses a member of `member_class` on has to be of
+    /// the verified class: the member is protected and declared by a superclass in another
+    /// runtime package. Superclasses are loaded before their subclasses are verified.
+    fn is_protected_access(
+        &self,
+        member_class: Symbol,
+        name: Symbol,
+        desc: Symbol,
+        is_method: bool,
+    ) -> bool {
+        let method_area = self.method_area;
+        let mut current = method_area
+            .get_class_like(&self.this_id)
+            .ok()
+            .and_then(|class| class.get_super());
+        while let Some(class_id) = current
+            && method_area.get_class(&class_id).get_name() != member_class
+        {
+            current = method_area.get_class(&class_id).get_super_id();
+        }
+        // resolution finds the member in the class or one of its superclasses
+        while let Some(class_id) = current {
+            let Ok(class) = method_area.get_class_like(&class_id) else {
+                return false;
+            };
+            let declared = if is_method {
+                class.declared_method_ids().ok().and_then(|method_ids| {
+                    method_ids.iter().find_map(|method_id| {
+                        let method = method_area.get_method(method_id);
+                        (method.name == name && method.desc == desc)
+                            .then(|| method.flags().is_protected())
+                    })
+                })
+            } else {
+                class.declared_fields().ok().and_then(|fields| {
+                    fields
+                        .iter()
+                        .find(|field| field.key.name == name && field.key.desc == desc)
+                        .map(|field| field.flags.is_protected())
+                })
+            };
+            if let Some(is_protected) = declared {
+                return is_protected
+                    && !method_area.is_same_runtime_package(self.this_id, class_id);
+            }
+            current = class.get_super();
+        }
+        false
+    }
+
+    /// Records that `from` must be assignable to `to`, which is assumed until it is checked.
+    fn defer(&self, from: Symbol, to: Symbol) -> bool {
+        let constraint = VerificationConstraint { from, to };
+        let mut constraints = self.constraints.borrow_mut();
+        if !constraints.contains(&constraint) {
+            constraints.push(constraint);
+        }
+        true
+    }
+
+    fn class_info(&self, name: Symbol) -> Option<ClassInfo> {
+        if name == self.this_name {
+            return Some(self.this_info);
+        }
+        if let Some(info) = self.class_infos.borrow().get(&name) {
+            return *info;
+        }
+        let info = self.lookup_class_info(name);
+        self.class_infos.borrow_mut().insert(name, info);
+        info
+    }
+
+    fn loaded_class_info(&self, class_id: ClassId) -> ClassInfo {
+        let class = self.method_area.get_class(&class_id);
+        ClassInfo {
+            super_name: class
+                .get_super_id()
+                .map(|super_id| self.method_area.get_class(&super_id).get_name()),
+            is_interface: class.is_interface(),
+        }
+    }
+
+    /// Looks the class up in the namespace of the defining loader. Classes of the builtin
+    /// loaders that are not loaded yet are read without being defined: loading them here could
+    /// reach the class being verified, which is not published before it is linked. Other
+    /// loaders only know their classes once they loaded them.
+    fn lookup_class_info(&self, name: Symbol) -> Option<ClassInfo> {
+        let method_area = self.method_area;
+        let is_builtin = method_area.is_builtin_loader(self.loader);
+        let loaded = method_area
+            .find_loaded_class(self.loader, name)
+            .filter(|class_id| {
+                !is_builtin || method_area.is_visible_to_builtin_loader(self.loader, *class_id)
+            });
+        if let Some(class_id) = loaded {
+            // the superclasses of a loaded class are known even if the loader hasn't been asked
+            // for them by name
+            let mut infos = self.class_infos.borrow_mut();
+            let mut current = method_area.get_class(&class_id).get_super_id();
+            while let Some(super_id) = current {
+                let class = method_area.get_class(&super_id);
+                infos
+                    .entry(class.get_name())
+                    .or_insert_with(|| Some(self.loaded_class_info(super_id)));
+                current = class.get_super_id();
+            }
+            return Some(self.loaded_class_info(class_id));
+        }
+        if !is_builtin {
+            return None;
+        }
+        let found = method_area
+            .class_loader()
+            .load(self.interner().resolve(&name))
+            .ok()?;
+        let is_app = method_area
+            .builtin_loaders()
+            .is_some_and(|builtin| self.loader == Some(builtin.app));
+        if found.loader == BuiltinLoader::App && !is_app {
+            return None;
+        }
+        let cf = ClassFile::try_from(found.bytes).ok()?;
+        let super_name = match cf.get_super_class_name() {
+            Some(super_name) => Some(self.interner().get_or_intern(super_name.ok()?)),
+            None => None,
+        };
+        Some(ClassInfo {
+            super_name,
+            is_interface: cf.access_flags.is_interface(),
+        })
+    }
+}
+
+/// Class name of the component type of an array of references, `None` for primitive arrays.
+fn component_class(component: &str) -> Option<&str> {
+    if component.starts_with('[') {
+        return Some(component);
+    }
+    component.strip_prefix('L')?.strip_suffix(';')
+}
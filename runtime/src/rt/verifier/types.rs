@@ -0,0 +1,140 @@
+use crate::Symbol;
+use jclass::attribute::method::code::VerificationTypeInfo;
+use lasso::ThreadedRodeo;
+
+/// https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.10.1.2
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum VType {
+    Top,
+    Integer,
+    Float,
+    Long,
+    Double,
+    Null,
+    UninitializedThis,
+    /// Object created by the `new` at this pc whose constructor hasn't run yet.
+    Uninitialized(u16),
+    /// Internal name of a class or interface, descriptor of an array class.
+    Reference(Symbol),
+}
+
+impl VType {
+    pub fn is_category2(self) -> bool {
+        matches!(self, VType::Long | VType::Double)
+    }
+
+    pub fn size(self) -> usize {
+        if self.is_category2() { 2 } else { 1 }
+    }
+
+    /// Any reference, including the ones still waiting for a constructor.
+    pub fn is_reference(self) -> bool {
+        matches!(
+            self,
+            VType::Null | VType::UninitializedThis | VType::Uninitialized(_) | VType::Reference(_)
+        )
+    }
+
+    /// Type of a value of field descriptor `desc`, `None` if it isn't one.
+    pub fn from_descriptor(desc: &str, interner: &ThreadedRodeo) -> Option<VType> {
+        match desc.as_bytes().first()? {
+            b'Z' | b'B' | b'C' | b'S' | b'I' if desc.len() == 1 => Some(VType::Integer),
+            b'F' if desc.len() == 1 => Some(VType::Float),
+            b'J' if desc.len() == 1 => Some(VType::Long),
+            b'D' if desc.len() == 1 => Some(VType::Double),
+            b'L' => {
+                let name = desc.strip_prefix('L')?.strip_suffix(';')?;
+                Some(VType::Reference(interner.get_or_intern(name)))
+            }
+            b'[' => Some(VType::Reference(interner.get_or_intern(desc))),
+            _ => None,
+        }
+    }
+
+    pub fn describe(self, interner: &ThreadedRodeo) -> String {
+        match self {
+            VType::Top => "top".to_string(),
+            VType::Integer => "integer".to_string(),
+            VType::Float => "float".to_string(),
+            VType::Long => "long".to_string(),
+            VType::Double => "double".to_string(),
+            VType::Null => "null".to_string(),
+            VType::UninitializedThis => "uninitializedThis".to_string(),
+            VType::Uninitialized(pc) => format!("uninitialized({pc})"),
+            VType::Reference(name) => format!("'{}'", interner.resolve(&name)),
+        }
+    }
+}
+
+/// Splits a method descriptor into the descriptors of its parameters and of its return type,
+/// `None` for the return type of a void method.
+pub(super) fn split_method_descriptor(desc: &str) -> Option<(Vec<&str>, Option<&str>)> {
+    let (params, ret) = desc.strip_prefix('(')?.split_once(')')?;
+    let mut param_descs = Vec::new();
+    let mut rest = params;
+    while !rest.is_empty() {
+        let len = field_descriptor_len(rest)?;
+        param_descs.push(&rest[..len]);
+        rest = &rest[len..];
+    }
+    let ret = match ret {
+        "V" => None,
+        ret if field_descriptor_len(ret) == Some(ret.len()) => Some(ret),
+        _ => return None,
+    };
+    Some((param_descs, ret))
+}
+
+fn field_descriptor_len(desc: &str) -> Option<usize> {
+    let dims = desc.bytes().take_while(|b| *b == b'[').count();
+    match desc.as_bytes().get(dims)? {
+        b'Z' | b'B' | b'C' | b'S' | b'I' | b'F' | b'J' | b'D' => Some(dims + 1),
+        b'L' => desc[dims..].find(';').map(|end| dims + end + 1),
+        _ => None,
+    }
+}
+
+/// Type state of one instruction. Longs and doubles take two local variables, the second one
+/// is `Top`, and one entry of the operand stack.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Frame {
+    pub locals: Vec<VType>,
+    pub stack: Vec<VType>,
+    pub flag_this_uninit: bool,
+}
+
+impl Frame {
+    pub fn stack_size(&self) -> usize {
+        self.stack.iter().map(|t| t.size()).sum()
+    }
+
+    /// Initializes every occurrence of `uninit` to `initialized`, after its constructor ran.
+    pub fn initialize(&mut self, uninit: VType, initialized: VType) {
+        for t in self.locals.iter_mut().chain(self.stack.iter_mut()) {
+            if *t == uninit {
+                *t = initialized;
+            }
+        }
+        if uninit == VType::UninitializedThis {
+            self.flag_this_uninit = false;
+        }
+    }
+}
+
+/// Verification types of `StackMapTable` entries, classes are only looked up on use.
+pub(super) fn from_verification_type_info(
+    info: &VerificationTypeInfo,
+    class_sym: impl Fn(u16) -> Option<Symbol>,
+) -> Option<VType> {
+    Some(match info {
+        VerificationTypeInfo::Top => VType::Top,
+        VerificationTypeInfo::Integer => VType::Integer,
+        VerificationTypeInfo::Float => VType::Float,
+        VerificationTypeInfo::Double => VType::Double,
+        VerificationTypeInfo::Long => VType::Long,
+        VerificationTypeInfo::Null => VType::Null,
+        VerificationTypeInfo::UninitializedThis => VType::UninitializedThis,
+        VerificationTypeInfo::Object(idx) => VType::Reference(class_sym(*idx)?),
+        VerificationTypeInfo::Uninitialized(offset) => VType::Uninitialized(*offset),
+    })
+}
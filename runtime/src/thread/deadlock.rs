@@ -0,0 +1,341 @@
+use crate::VirtualMachine;
+use crate::error::JvmError;
+use crate::heap::HeapRef;
+use crate::keys::ThreadId;
+use crate::thread::RegisteredThread;
+use crate::thread::dump::{ThreadStack, describe_object_class, print_dump};
+use common::jtype::AllocationType;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fmt::Write;
+use std::sync::Weak;
+use std::time::Duration;
+use tracing_log::log::warn;
+
+const WATCHDOG_INTERVAL: Duration = Duration::from_secs(1);
+
+/// What a deadlocked thread is waiting for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockedOn {
+    /// `monitorenter` or a synchronized method.
+    Monitor(HeapRef),
+    /// `LockSupport.park` with an `AbstractOwnableSynchronizer` as the park blocker.
+    OwnableSynchronizer(HeapRef),
+}
+
+#[derive(Debug, Clone)]
+pub struct DeadlockedThread {
+    pub thread_id: ThreadId,
+    pub name: String,
+    pub blocked_on: BlockedOn,
+    /// Class of the lock object as `jstack` prints it, e.g. `a java.lang.Object`.
+    pub lock_class: String,
+    pub held_by: ThreadId,
+    pub held_by_name: String,
+}
+
+/// One cycle in the waits-for graph, in the order threads wait for each other.
+#[derive(Debug, Clone)]
+pub struct Deadlock {
+    pub threads: Vec<DeadlockedThread>,
+}
+
+impl Deadlock {
+    pub fn thread_ids(&self) -> Vec<ThreadId> {
+        self.threads.iter().map(|thread| thread.thread_id).collect()
+    }
+}
+
+impl fmt::Display for Deadlock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Found one Java-level deadlock:")?;
+        writeln!(f, "=============================")?;
+        for thread in &self.threads {
+            writeln!(f, "\"{}\":", thread.name)?;
+            match thread.blocked_on {
+                BlockedOn::Monitor(obj) => writeln!(
+                    f,
+                    "  waiting to lock monitor {:#018x} (object {:#018x}, {}),",
+                    obj, obj, thread.lock_class
+                )?,
+                BlockedOn::OwnableSynchronizer(obj) => writeln!(
+                    f,
+                    "  waiting for ownable synchronizer {:#018x}, ({}),",
+                    obj, thread.lock_class
+                )?,
+            }
+            writeln!(f, "  which is held by \"{}\"", thread.held_by_name)?;
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Builds the waits-for graph from monitor owners and `AbstractOwnableSynchronizer` owners
+/// and returns every cycle in it.
+pub fn find_deadlocks(vm: &VirtualMachine) -> Result<Vec<Deadlock>, JvmError> {
+    let threads = vm.threads.all();
+    let mut waits_for = HashMap::new();
+    for thread in &threads {
+        if let Some(edge) = blocked_on(vm, thread)? {
+            waits_for.insert(thread.id, edge);
+        }
+    }
+
+    let thread_ids: Vec<_> = threads.iter().map(|thread| thread.id).collect();
+    let owners = waits_for
+        .iter()
+        .map(|(thread_id, (_, owner))| (*thread_id, *owner))
+        .collect();
+    find_cycles(&thread_ids, &owners)
+        .iter()
+        .map(|cycle| build_deadlock(vm, cycle, &waits_for))
+        .collect()
+}
+
+/// Returns the cycles of the waits-for graph given by the owner each blocked thread waits for,
+/// each starting at the first of its threads in `thread_ids`.
+fn find_cycles(
+    thread_ids: &[ThreadId],
+    owners: &HashMap<ThreadId, ThreadId>,
+) -> Vec<Vec<ThreadId>> {
+    // every thread waits for at most one other thread, so following the edges from each
+    // unvisited thread either ends at a running thread, joins an explored path or loops
+    let mut visited = HashSet::new();
+    let mut cycles = Vec::new();
+    for thread_id in thread_ids {
+        let mut path = Vec::new();
+        let mut cur = *thread_id;
+        loop {
+            if let Some(pos) = path.iter().position(|id| *id == cur) {
+                cycles.push(path.split_off(pos));
+                break;
+            }
+            if !visited.insert(cur) {
+                break;
+            }
+            path.push(cur);
+            match owners.get(&cur) {
+                Some(owner) => cur = *owner,
+                None => break,
+            }
+        }
+    }
+    cycles
+}
+
+fn blocked_on(
+    vm: &VirtualMachine,
+    thread: &RegisteredThread,
+) -> Result<Option<(BlockedOn, ThreadId)>, JvmError> {
+    if let Some(obj) = vm.monitors.contended_by(thread.id)
+        && let Some(owner) = vm.monitors.owner_of(obj)
+    {
+        return Ok(Some((BlockedOn::Monitor(obj), owner)));
+    }
+    if let Some(blocker) = vm.parkers.blocker_of(thread.id)
+        && let Some(owner) = synchronizer_owner(vm, blocker)?
+    {
+        return Ok(Some((BlockedOn::OwnableSynchronizer(blocker), owner)));
+    }
+    Ok(None)
+}
+
+/// Reads `AbstractOwnableSynchronizer.exclusiveOwnerThread` if the park blocker is one.
+fn synchronizer_owner(vm: &VirtualMachine, blocker: HeapRef) -> Result<Option<ThreadId>, JvmError> {
+    let class_id = vm.heap_read().get_class_id(blocker)?;
+    let offset = {
+        let ma = vm.method_area();
+        if !ma.instance_of(class_id, None, vm.br().abstract_ownable_synchronizer_sym) {
+            return Ok(None);
+        }
+        ma.get_instance_field(&class_id, &vm.br().aos_exclusive_owner_thread_fk)?
+            .offset
+    };
+    let owner_obj = vm
+        .heap_read()
+        .read_field(blocker, offset, AllocationType::Reference)?
+        .as_nullable_obj_ref()?;
+    Ok(owner_obj
+        .and_then(|obj| vm.threads.find_by_thread_obj(obj))
+        .map(|thread| thread.id))
+}
+
+fn thread_name(vm: &VirtualMachine, thread_id: ThreadId) -> Result<String, JvmError> {
+    match vm.threads.get(thread_id) {
+        Some(thread) => vm.heap_read().get_rust_string_from_java_string(thread.name),
+        None => Ok(format!("<unknown thread #{}>", thread_id.into_inner())),
+    }
+}
+
+fn build_deadlock(
+    vm: &VirtualMachine,
+    cycle: &[ThreadId],
+    waits_for: &HashMap<ThreadId, (BlockedOn, ThreadId)>,
+) -> Result<Deadlock, JvmError> {
+    let mut threads = Vec::with_capacity(cycle.len());
+    for thread_id in cycle {
+        let (blocked_on, held_by) = waits_for[thread_id];
+        let (BlockedOn::Monitor(obj) | BlockedOn::OwnableSynchronizer(obj)) = blocked_on;
+        threads.push(DeadlockedThread {
+            thread_id: *thread_id,
+            name: thread_name(vm, *thread_id)?,
+            blocked_on,
+            lock_class: describe_object_class(vm, obj)?,
+            held_by,
+            held_by_name: thread_name(vm, held_by)?,
+        });
+    }
+    Ok(Deadlock { threads })
+}
+
+/// Formats deadlocks like the tail of a `jstack` dump. Stacks are printed for the
+/// deadlocked threads found in `stacks`.
+pub fn format_deadlocks(
+    deadlocks: &[Deadlock],
+    stacks: &[ThreadStack],
+) -> Result<String, JvmError> {
+    let mut out = String::new();
+    for deadlock in deadlocks {
+        write!(out, "{}", deadlock).unwrap();
+        let with_stacks: Vec<_> = deadlock
+            .threads
+            .iter()
+            .filter_map(|thread| {
+                stacks
+                    .iter()
+                    .find(|state| state.id == thread.thread_id)
+                    .map(|state| (thread, state))
+            })
+            .collect();
+        if !with_stacks.is_empty() {
+            writeln!(out, "Java stack information for the threads listed above:").unwrap();
+            writeln!(out, "===================================================").unwrap();
+            for (thread, stack) in with_stacks {
+                writeln!(out, "\"{}\":", thread.name).unwrap();
+                out.push_str(&stack.frames);
+            }
+            out.push('\n');
+        }
+    }
+    writeln!(
+        out,
+        "Found {} deadlock{}.",
+        deadlocks.len(),
+        if deadlocks.len() == 1 { "" } else { "s" }
+    )
+    .unwrap();
+    Ok(out)
+}
+
+/// Periodically looks for deadlocks and prints a thread dump, as for `SIGQUIT`, when a new one
+/// appears.
+pub fn start_watchdog(vm: Weak<VirtualMachine>) {
+    let spawned = std::thread::Builder::new()
+        .name("deadlock-watchdog".to_string())
+        .spawn(move || {
+            let mut reported: Vec<HashSet<ThreadId>> = Vec::new();
+            loop {
+                std::thread::sleep(WATCHDOG_INTERVAL);
+                let Some(vm) = vm.upgrade() else {
+                    break;
+                };
+                let deadlocks = match find_deadlocks(&vm) {
+                    Ok(deadlocks) => deadlocks,
+                    Err(e) => {
+                        warn!("Deadlock detection failed: {}", e);
+                        continue;
+                    }
+                };
+                let new_deadlocks: Vec<_> = deadlocks
+                    .into_iter()
+                    .filter(|deadlock| {
+                        let ids: HashSet<_> = deadlock.thread_ids().into_iter().collect();
+                        !reported.contains(&ids)
+                    })
+                    .collect();
+                if new_deadlocks.is_empty() {
+                    continue;
+                }
+                reported.extend(
+                    new_deadlocks
+                        .iter()
+                        .map(|deadlock| deadlock.thread_ids().into_iter().collect()),
+                );
+                // blocked threads still answer safepoint requests, so the dump has their stacks
+                if !vm.print_thread_dump() {
+                    match format_deadlocks(&new_deadlocks, &[]) {
+                        Ok(report) => print_dump(&report),
+                        Err(e) => warn!("Failed to format deadlock report: {}", e),
+                    }
+                }
+            }
+        });
+    if let Err(e) = spawned {
+        warn!("Failed to start deadlock watchdog: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn owners(edges: &[(usize, usize)]) -> HashMap<ThreadId, ThreadId> {
+        edges
+            .iter()
+            .map(|(thread, owner)| (ThreadId::from_usize(*thread), ThreadId::from_usize(*owner)))
+            .collect()
+    }
+
+    fn ids(ids: &[usize]) -> Vec<ThreadId> {
+        ids.iter().map(|id| ThreadId::from_usize(*id)).collect()
+    }
+
+    #[test]
+    fn threads_locking_in_opposite_order_form_a_cycle() {
+        // given
+        let owners = owners(&[(2, 3), (3, 2)]);
+
+        // when
+        let cycles = find_cycles(&ids(&[1, 2, 3]), &owners);
+
+        // then
+        assert_eq!(cycles, vec![ids(&[2, 3])]);
+    }
+
+    #[test]
+    fn thread_waiting_on_a_cycle_is_not_part_of_it() {
+        // given
+        let owners = owners(&[(1, 2), (2, 3), (3, 4), (4, 2)]);
+
+        // when
+        let cycles = find_cycles(&ids(&[1, 2, 3, 4]), &owners);
+
+        // then
+        assert_eq!(cycles, vec![ids(&[2, 3, 4])]);
+    }
+
+    #[test]
+    fn chain_ending_at_a_running_thread_is_no_deadlock() {
+        // given
+        let owners = owners(&[(1, 2), (2, 3), (4, 3)]);
+
+        // when
+        let cycles = find_cycles(&ids(&[1, 2, 3, 4]), &owners);
+
+        // then
+        assert!(cycles.is_empty());
+    }
+
+    #[test]
+    fn separate_cycles_are_reported_once_each() {
+        // given
+        let owners = owners(&[(1, 2), (2, 1), (3, 4), (4, 5), (5, 3)]);
+
+        // when
+        let cycles = find_cycles(&ids(&[5, 4, 3, 2, 1]), &owners);
+
+        // then
+        assert_eq!(cycles, vec![ids(&[5, 3, 4]), ids(&[2, 1])]);
+    }
+}
@@ -0,0 +1,251 @@
+use crate::VirtualMachine;
+use crate::error::JvmError;
+use crate::heap::HeapRef;
+use crate::keys::ThreadId;
+use crate::thread::{JavaThreadState, RegisteredThread, deadlock};
+use crate::vm::signal;
+use crate::vm::stack::FrameType;
+use std::fmt::Write;
+use std::io::Write as _;
+use std::sync::Mutex;
+use std::sync::mpsc::Sender;
+use std::time::{Duration, Instant};
+
+/// The frames of a thread as the thread itself wrote them at a safepoint, only the owner of a
+/// stack can walk it.
+pub struct ThreadStack {
+    pub id: ThreadId,
+    /// Frames and the monitors they hold, formatted by [`write_stack`].
+    pub frames: String,
+}
+
+impl ThreadStack {
+    pub fn capture(vm: &VirtualMachine, thread: &JavaThreadState) -> Result<Self, JvmError> {
+        let mut frames = String::new();
+        write_stack(vm, thread, &mut frames)?;
+        Ok(Self {
+            id: thread.id,
+            frames,
+        })
+    }
+}
+
+struct PendingDump {
+    started: Instant,
+    /// Print to stdout, as for `SIGQUIT`.
+    print: bool,
+    replies: Vec<Sender<String>>,
+    stacks: Vec<ThreadStack>,
+}
+
+/// The thread dump being collected. Every live thread adds its stack at its next safepoint
+/// poll, the last one to do so formats the dump.
+#[derive(Default)]
+pub struct ThreadDumps {
+    pending: Mutex<Option<PendingDump>>,
+}
+
+impl ThreadDumps {
+    /// Threads that don't reach a safepoint in time are dumped without their frames.
+    const DEADLINE: Duration = Duration::from_secs(1);
+
+    /// Starts a dump, or joins the one being collected.
+    pub fn request(&self, print: bool, reply: Option<Sender<String>>) {
+        let mut pending = self.pending.lock().unwrap();
+        let dump = pending.get_or_insert_with(|| PendingDump {
+            started: Instant::now(),
+            print: false,
+            replies: Vec::new(),
+            stacks: Vec::new(),
+        });
+        dump.print |= print;
+        dump.replies.extend(reply);
+        signal::request_poll();
+    }
+
+    /// Adds the stack of `thread` to the pending dump, and finishes the dump once every live
+    /// thread did or the deadline passed.
+    pub fn answer(&self, vm: &VirtualMachine, thread: &JavaThreadState) -> Result<(), JvmError> {
+        let mut pending = self.pending.lock().unwrap();
+        let Some(dump) = pending.as_mut() else {
+            return Ok(());
+        };
+        if !dump.stacks.iter().any(|stack| stack.id == thread.id) {
+            dump.stacks.push(ThreadStack::capture(vm, thread)?);
+        }
+        let threads = vm.threads.all();
+        let answered = threads
+            .iter()
+            .all(|registered| dump.stacks.iter().any(|stack| stack.id == registered.id));
+        if !answered && dump.started.elapsed() < Self::DEADLINE {
+            // the flag was cleared by this poll, the others still have to see it
+            signal::request_poll();
+            return Ok(());
+        }
+        let Some(dump) = pending.take() else {
+            return Ok(());
+        };
+        drop(pending);
+        let text = format_thread_dump(vm, &threads, &dump.stacks)?;
+        if dump.print {
+            print_dump(&text);
+        }
+        for reply in dump.replies {
+            let _ = reply.send(text.clone());
+        }
+        Ok(())
+    }
+}
+
+/// Prints a dump or deadlock report to stdout, where `jstack`-like output of the VM goes.
+pub fn print_dump(text: &str) {
+    let mut stdout = std::io::stdout().lock();
+    let _ = writeln!(stdout, "{}", text);
+    let _ = stdout.flush();
+}
+
+/// Formats a `jstack`-like dump of the given threads, with the frames of those in `stacks`.
+pub fn format_thread_dump(
+    vm: &VirtualMachine,
+    threads: &[RegisteredThread],
+    stacks: &[ThreadStack],
+) -> Result<String, JvmError> {
+    let mut out = String::new();
+    writeln!(
+        out,
+        "Full thread dump Toy Java Virtual Machine ({} interpreted mode):",
+        vm.config.version
+    )
+    .unwrap();
+    for thread in threads {
+        out.push('\n');
+        let frames = stacks
+            .iter()
+            .find(|stack| stack.id == thread.id)
+            .map(|stack| stack.frames.as_str());
+        write_thread(vm, thread, frames, &mut out)?;
+    }
+    let deadlocks = deadlock::find_deadlocks(vm)?;
+    if !deadlocks.is_empty() {
+        out.push('\n');
+        out.push_str(&deadlock::format_deadlocks(&deadlocks, stacks)?);
+    }
+    Ok(out)
+}
+
+fn write_thread(
+    vm: &VirtualMachine,
+    thread: &RegisteredThread,
+    frames: Option<&str>,
+    out: &mut String,
+) -> Result<(), JvmError> {
+    let name = vm
+        .heap_read()
+        .get_rust_string_from_java_string(thread.name)?;
+    let (status, state) = if vm.monitors.contended_by(thread.id).is_some() {
+        ("waiting for monitor entry", "BLOCKED (on object monitor)")
+    } else if vm.monitors.waiting_on(thread.id).is_some() {
+        ("in Object.wait()", "WAITING (on object monitor)")
+    } else if vm.parkers.is_parked(thread.id) {
+        ("waiting on condition", "WAITING (parking)")
+    } else {
+        ("runnable", "RUNNABLE")
+    };
+    writeln!(
+        out,
+        "\"{}\" #{} prio=5 os_prio=0 {}",
+        name,
+        thread.id.into_inner(),
+        status
+    )
+    .unwrap();
+    writeln!(out, "   java.lang.Thread.State: {}", state).unwrap();
+    match frames {
+        Some(frames) => out.push_str(frames),
+        None => writeln!(out, "\t- frames unavailable, not at a safepoint").unwrap(),
+    }
+    out.push('\n');
+    Ok(())
+}
+
+/// Writes the frames of the thread, with the monitors each frame holds or waits for.
+pub fn write_stack(
+    vm: &VirtualMachine,
+    thread: &JavaThreadState,
+    out: &mut String,
+) -> Result<(), JvmError> {
+    let contended = vm.monitors.contended_by(thread.id);
+    let waiting_on = vm.monitors.waiting_on(thread.id);
+    let park_blocker = vm.parkers.blocker_of(thread.id);
+    let owned = vm.monitors.owned_by(thread.id);
+    let frames = thread.stack.frames();
+    for (depth, frame) in frames.iter().enumerate().rev() {
+        writeln!(out, "\tat {}", format_frame(vm, frame)).unwrap();
+        let is_top = depth + 1 == frames.len();
+        if is_top && let Some(obj) = contended {
+            writeln!(out, "\t- waiting to lock {}", describe_object(vm, obj)?).unwrap();
+        }
+        if is_top && let Some(obj) = waiting_on {
+            writeln!(out, "\t- waiting on {}", describe_object(vm, obj)?).unwrap();
+        }
+        if is_top && let Some(obj) = park_blocker {
+            writeln!(
+                out,
+                "\t- parking to wait for  {}",
+                describe_object(vm, obj)?
+            )
+            .unwrap();
+        }
+        for (obj, _) in owned.iter().filter(|(_, d)| *d == depth + 1) {
+            writeln!(out, "\t- locked {}", describe_object(vm, *obj)?).unwrap();
+        }
+    }
+    Ok(())
+}
+
+/// Formats a frame the same way `StackTraceElement.toString` does, e.g. `Foo.bar(Foo.java:10)`.
+pub fn format_frame(vm: &VirtualMachine, frame: &FrameType) -> String {
+    let ma = vm.method_area();
+    let method = ma.get_method(&frame.method_id());
+    let class = ma.get_class(&method.class_id());
+    let class_name = vm.symbol_to_pretty_string(class.get_name());
+    let method_name = vm.interner().resolve(&method.name);
+    let location = match frame {
+        FrameType::NativeFrame(_) => "Native Method".to_string(),
+        FrameType::JavaFrame(f) => {
+            let line = method.get_line_number_by_cp(f.pc() as i32);
+            match (class.get_source_file(), line) {
+                (Some(file), Some(line)) => {
+                    format!("{}:{}", vm.interner().resolve(&file), line)
+                }
+                (Some(file), None) => vm.interner().resolve(&file).to_string(),
+                (None, _) => "Unknown Source".to_string(),
+            }
+        }
+    };
+    format!("{}.{}({})", class_name, method_name, location)
+}
+
+/// Formats an object the way `jstack` prints monitors, e.g. `<0x000000000000002a> (a java.lang.Object)`.
+pub fn describe_object(vm: &VirtualMachine, obj: HeapRef) -> Result<String, JvmError> {
+    Ok(format!(
+        "<{:#018x}> ({})",
+        obj,
+        describe_object_class(vm, obj)?
+    ))
+}
+
+/// Returns `a <class>`, or `a java.lang.Class for <class>` for class mirrors.
+pub fn describe_object_class(vm: &VirtualMachine, obj: HeapRef) -> Result<String, JvmError> {
+    let class_id = vm.heap_read().get_class_id(obj)?;
+    let ma = vm.method_area();
+    let class_name = vm.symbol_to_pretty_string(ma.get_class(&class_id).get_name());
+    Ok(match ma.get_class_id_by_mirror(&obj) {
+        Ok(mirrored_id) => format!(
+            "a {} for {}",
+            class_name,
+            vm.symbol_to_pretty_string(ma.get_class(&mirrored_id).get_name())
+        ),
+        Err(_) => format!("a {}", class_name),
+    })
+}
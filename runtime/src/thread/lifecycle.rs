@@ -0,0 +1,242 @@
+use crate::error::JvmError;
+use crate::heap::HeapRef;
+use crate::interpreter::Interpreter;
+use crate::keys::{FieldKey, ThreadId};
+use crate::thread::JavaThreadState;
+use crate::vm::Value;
+use crate::vm::stack::FrameStack;
+use crate::{VirtualMachine, build_exception};
+use common::jtype::AllocationType;
+use tracing_log::log::warn;
+
+// `FieldHolder.threadStatus` values, these are JVMTI thread state bits
+const THREAD_STATUS_RUNNABLE: i32 = 0x0001 | 0x0004;
+const THREAD_STATUS_TERMINATED: i32 = 0x0002;
+
+fn field_offset(vm: &VirtualMachine, obj: HeapRef, key: &FieldKey) -> Result<usize, JvmError> {
+    let class_id = vm.heap_read().get_class_id(obj)?;
+    Ok(vm.method_area().get_instance_field(&class_id, key)?.offset)
+}
+
+fn read_field(
+    vm: &VirtualMachine,
+    obj: HeapRef,
+    key: &FieldKey,
+    field_type: AllocationType,
+) -> Result<Value, JvmError> {
+    let offset = field_offset(vm, obj, key)?;
+    vm.heap_read().read_field(obj, offset, field_type)
+}
+
+fn write_field(
+    vm: &VirtualMachine,
+    obj: HeapRef,
+    key: &FieldKey,
+    value: Value,
+    field_type: AllocationType,
+) -> Result<(), JvmError> {
+    let offset = field_offset(vm, obj, key)?;
+    vm.heap_write().write_field(obj, offset, value, field_type)
+}
+
+fn thread_holder(vm: &VirtualMachine, thread_obj: HeapRef) -> Result<HeapRef, JvmError> {
+    read_field(
+        vm,
+        thread_obj,
+        &vm.br().thread_holder_fk,
+        AllocationType::Reference,
+    )?
+    .as_obj_ref()
+}
+
+fn set_thread_status(
+    vm: &VirtualMachine,
+    thread_obj: HeapRef,
+    status: i32,
+) -> Result<(), JvmError> {
+    let holder = thread_holder(vm, thread_obj)?;
+    write_field(
+        vm,
+        holder,
+        &vm.br().thread_holder_thread_status_fk,
+        Value::Integer(status),
+        AllocationType::Int,
+    )
+}
+
+fn is_daemon(vm: &VirtualMachine, thread_obj: HeapRef) -> Result<bool, JvmError> {
+    let holder = thread_holder(vm, thread_obj)?;
+    Ok(read_field(
+        vm,
+        holder,
+        &vm.br().thread_holder_daemon_fk,
+        AllocationType::Boolean,
+    )?
+    .as_int()?
+        != 0)
+}
+
+/// Does what hotspot does when a thread gets attached: a non-zero `eetop` makes
+/// `Thread.isAlive()` return true, and the status makes `getState()` report RUNNABLE.
+pub fn mark_alive(
+    vm: &VirtualMachine,
+    thread_obj: HeapRef,
+    thread_id: ThreadId,
+) -> Result<(), JvmError> {
+    write_field(
+        vm,
+        thread_obj,
+        &vm.br().thread_eetop_fk,
+        Value::Long(thread_id.into_inner().get() as i64),
+        AllocationType::Long,
+    )?;
+    set_thread_status(vm, thread_obj, THREAD_STATUS_RUNNABLE)
+}
+
+/// Implements `Thread.start0`: runs `Thread.run()` on a new OS thread.
+pub fn start_thread(vm: &VirtualMachine, thread_obj: HeapRef) -> Result<(), JvmError> {
+    let vm_arc = vm.this.upgrade().ok_or(build_exception!(
+        InternalError,
+        "VM is shutting down, can't start a thread"
+    ))?;
+    let name = read_field(
+        vm,
+        thread_obj,
+        &vm.br().thread_name_fk,
+        AllocationType::Reference,
+    )?
+    .as_obj_ref()?;
+    let os_thread_name = vm.heap_read().get_rust_string_from_java_string(name)?;
+    let daemon = is_daemon(vm, thread_obj)?;
+    let mut thread = JavaThreadState {
+        id: vm.threads.next_thread_id(),
+        thread_obj,
+        group_obj: 0,
+        name,
+        stack: FrameStack::new(&vm.config),
+    };
+    let thread_id = thread.id;
+    mark_alive(vm, thread_obj, thread_id)?;
+    vm.threads.register(&thread, daemon);
+
+    let spawned = std::thread::Builder::new()
+        .name(os_thread_name)
+        .spawn(move || run_thread(&vm_arc, &mut thread));
+    if let Err(e) = spawned {
+        vm.threads.unregister(thread_id);
+        write_field(
+            vm,
+            thread_obj,
+            &vm.br().thread_eetop_fk,
+            Value::Long(0),
+            AllocationType::Long,
+        )?;
+        return Err(build_exception!(
+            OutOfMemoryError,
+            "unable to create native thread: {}",
+            e
+        ));
+    }
+    Ok(())
+}
+
+fn run_thread(vm: &VirtualMachine, thread: &mut JavaThreadState) {
+    let res = (|| {
+        let thread_class_id = vm.heap_read().get_class_id(thread.thread_obj)?;
+        let run_method_id = vm
+            .method_area()
+            .get_class(&thread_class_id)
+            .get_vtable_method_id(&vm.br().thread_run_mk)?;
+        Interpreter::invoke_instance_method(
+            thread,
+            run_method_id,
+            vm,
+            vec![Value::Ref(thread.thread_obj)],
+        )
+    })();
+    if let Err(e) = res {
+        dispatch_uncaught_exception(vm, thread, e);
+    }
+    exit_thread(vm, thread);
+}
+
+/// Hands an exception that escaped `run()` to `Thread.dispatchUncaughtException`, which
+/// calls the thread's uncaught exception handler.
+pub fn dispatch_uncaught_exception(vm: &VirtualMachine, thread: &mut JavaThreadState, e: JvmError) {
+    let res = (|| {
+        let exception_ref = match e {
+            JvmError::JavaExceptionThrown(exception_ref) => exception_ref,
+            JvmError::JavaException(exception) => {
+                vm.map_rust_error_to_java_exception(thread, exception)?
+            }
+            e => return Err(e),
+        };
+        let thread_class_id = vm.br().get_java_lang_thread_id()?;
+        let dispatch_method_id = vm
+            .method_area()
+            .get_instance_class(&thread_class_id)?
+            .get_special_method_id(&vm.br().thread_dispatch_uncaught_exception_mk)?;
+        Interpreter::invoke_instance_method(
+            thread,
+            dispatch_method_id,
+            vm,
+            vec![Value::Ref(thread.thread_obj), Value::Ref(exception_ref)],
+        )
+    })();
+    // like hotspot, exceptions thrown by the handler itself are ignored
+    if let Err(e) = res {
+        eprintln!(
+            "Exception in thread \"{}\": {}",
+            vm.heap_read()
+                .get_rust_string_from_java_string(thread.name)
+                .unwrap_or_default(),
+            e.into_pretty_string(vm.interner())
+        );
+    }
+}
+
+/// Runs `Thread.exit()`, then marks the thread terminated and wakes up threads in `join()`.
+pub fn exit_thread(vm: &VirtualMachine, thread: &mut JavaThreadState) {
+    let res = (|| {
+        let thread_class_id = vm.br().get_java_lang_thread_id()?;
+        let exit_method_id = vm
+            .method_area()
+            .get_instance_class(&thread_class_id)?
+            .get_special_method_id(&vm.br().thread_exit_mk)?;
+        Interpreter::invoke_instance_method(
+            thread,
+            exit_method_id,
+            vm,
+            vec![Value::Ref(thread.thread_obj)],
+        )
+    })();
+    if let Err(e) = res {
+        warn!("Thread.exit() failed: {}", e);
+    }
+    if let Err(e) = ensure_join(vm, thread) {
+        warn!(
+            "Failed to notify threads joining a terminated thread: {}",
+            e
+        );
+        vm.threads.unregister(thread.id);
+    }
+}
+
+/// `Thread.join()` waits on the thread object until `isAlive()` is false. The thread leaves the
+/// registry before the joiners can go on, so that e.g. a collection right after `join()` doesn't
+/// see it alive.
+fn ensure_join(vm: &VirtualMachine, thread: &mut JavaThreadState) -> Result<(), JvmError> {
+    let thread_obj = thread.thread_obj;
+    Interpreter::monitor_enter(thread, thread_obj, vm)?;
+    set_thread_status(vm, thread_obj, THREAD_STATUS_TERMINATED)?;
+    write_field(
+        vm,
+        thread_obj,
+        &vm.br().thread_eetop_fk,
+        Value::Long(0),
+        AllocationType::Long,
+    )?;
+    vm.monitors.notify(thread.id, thread_obj, true)?;
+    vm.threads.unregister(thread.id);
+    vm.monitors.exit(thread.id, thread_obj)
+}
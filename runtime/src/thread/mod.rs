@@ -1,6 +1,13 @@
 use crate::heap::HeapRef;
 use crate::keys::ThreadId;
 use crate::vm::stack::FrameStack;
+use std::sync::{Condvar, Mutex};
+
+pub mod deadlock;
+pub mod dump;
+pub mod lifecycle;
+pub mod monitor;
+pub mod park;
 
 pub struct JavaThreadState {
     pub id: ThreadId,
@@ -9,3 +16,89 @@ pub struct JavaThreadState {
     pub name: HeapRef,
     pub stack: FrameStack,
 }
+
+/// The part of a thread that other threads are allowed to see.
+#[derive(Debug, Clone, Copy)]
+pub struct RegisteredThread {
+    pub id: ThreadId,
+    pub thread_obj: HeapRef,
+    pub name: HeapRef,
+    pub daemon: bool,
+}
+
+#[derive(Default)]
+struct Threads {
+    next_index: usize,
+    live: Vec<RegisteredThread>,
+}
+
+/// All live Java threads, used by services that need to look at threads other than the
+/// current one (deadlock detection, `unpark`, waiting for non-daemon threads at exit).
+#[derive(Default)]
+pub struct ThreadRegistry {
+    threads: Mutex<Threads>,
+    exited: Condvar,
+}
+
+impl ThreadRegistry {
+    pub fn next_thread_id(&self) -> ThreadId {
+        let mut threads = self.threads.lock().unwrap();
+        let id = ThreadId::from_index(threads.next_index);
+        threads.next_index += 1;
+        id
+    }
+
+    pub fn register(&self, thread: &JavaThreadState, daemon: bool) {
+        self.threads.lock().unwrap().live.push(RegisteredThread {
+            id: thread.id,
+            thread_obj: thread.thread_obj,
+            name: thread.name,
+            daemon,
+        });
+    }
+
+    pub fn unregister(&self, thread_id: ThreadId) {
+        self.threads
+            .lock()
+            .unwrap()
+            .live
+            .retain(|thread| thread.id != thread_id);
+        self.exited.notify_all();
+    }
+
+    pub fn all(&self) -> Vec<RegisteredThread> {
+        self.threads.lock().unwrap().live.clone()
+    }
+
+    pub fn get(&self, thread_id: ThreadId) -> Option<RegisteredThread> {
+        self.threads
+            .lock()
+            .unwrap()
+            .live
+            .iter()
+            .find(|thread| thread.id == thread_id)
+            .copied()
+    }
+
+    pub fn find_by_thread_obj(&self, thread_obj: HeapRef) -> Option<RegisteredThread> {
+        self.threads
+            .lock()
+            .unwrap()
+            .live
+            .iter()
+            .find(|thread| thread.thread_obj == thread_obj)
+            .copied()
+    }
+
+    /// Blocks until `current` is the only non-daemon thread left, like `DestroyJavaVM` does.
+    pub fn wait_for_non_daemon_threads(&self, current: ThreadId) {
+        let mut threads = self.threads.lock().unwrap();
+        while threads
+            .live
+            .iter()
+            .any(|thread| thread.id != current && !thread.daemon)
+        {
+            threads = self.exited.wait(threads).unwrap();
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use crate::error::JvmError;
+use crate::heap::HeapRef;
+use crate::keys::ThreadId;
+use crate::throw_exception;
+use std::collections::HashMap;
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long a blocked thread sleeps before it wakes up to check for safepoint requests.
+const BLOCKED_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+struct ObjectMonitor {
+    owner: ThreadId,
+    recursions: u32,
+    /// Frame stack depth of the frame that acquired the monitor, used by thread dumps
+    /// to print `- locked <...>` under the right frame.
+    depth: usize,
+}
+
+#[derive(Default)]
+struct Monitors {
+    owned: HashMap<HeapRef, ObjectMonitor>,
+    /// Threads currently blocked in `monitorenter` and the object they are waiting for.
+    contended: HashMap<ThreadId, HeapRef>,
+    /// Threads in `Object.wait` and the object they wait on.
+    waiting: HashMap<ThreadId, Waiter>,
+}
+
+struct Waiter {
+    obj: HeapRef,
+    notified: bool,
+}
+
+/// Object monitors that are currently owned by some thread.
+///
+/// Monitors are inflated lazily on first `monitorenter` and removed as soon as the owner
+/// releases the last recursion, so the table only holds locked objects.
+#[derive(Default)]
+pub struct MonitorTable {
+    inner: Mutex<Monitors>,
+    released: Condvar,
+}
+
+impl MonitorTable {
+    /// Acquires the monitor of `obj` for `thread_id`, blocking while another thread owns it.
+    /// `on_blocked` is invoked periodically (without the table lock held) while waiting.
+    pub fn enter(
+        &self,
+        thread_id: ThreadId,
+        obj: HeapRef,
+        depth: usize,
+        mut on_blocked: impl FnMut() -> Result<(), JvmError>,
+    ) -> Result<(), JvmError> {
+        let mut monitors = self.inner.lock().unwrap();
+        loop {
+            match monitors.owned.get_mut(&obj) {
+                None => {
+                    monitors.owned.insert(
+                        obj,
+                        ObjectMonitor {
+                            owner: thread_id,
+                            recursions: 1,
+                            depth,
+                        },
+                    );
+                    monitors.contended.remove(&thread_id);
+                    return Ok(());
+                }
+                Some(monitor) if monitor.owner == thread_id => {
+                    monitor.recursions += 1;
+                    return Ok(());
+                }
+                Some(_) => {
+                    monitors.contended.insert(thread_id, obj);
+                    monitors = self
+                        .released
+                        .wait_timeout(monitors, BLOCKED_POLL_INTERVAL)
+                        .unwrap()
+                        .0;
+                    drop(monitors);
+                    let res = on_blocked();
+                    monitors = self.inner.lock().unwrap();
+                    if let Err(e) = res {
+                        monitors.contended.remove(&thread_id);
+                        return Err(e);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn exit(&self, thread_id: ThreadId, obj: HeapRef) -> Result<(), JvmError> {
+        let mut monitors = self.inner.lock().unwrap();
+        let Some(monitor) = monitors.owned.get_mut(&obj) else {
+            throw_exception!(IllegalMonitorStateException, "current thread is not owner")?
+        };
+        if monitor.owner != thread_id {
+            throw_exception!(IllegalMonitorStateException, "current thread is not owner")?
+        }
+        monitor.recursions -= 1;
+        if monitor.recursions == 0 {
+            monitors.owned.remove(&obj);
+            self.released.notify_all();
+        }
+        Ok(())
+    }
+
+    /// Implements `Object.wait`: fully releases the monitor, waits for a notification or the
+    /// deadline and then reacquires the monitor with the same recursion count.
+    pub fn wait(
+        &self,
+        thread_id: ThreadId,
+        obj: HeapRef,
+        deadline: Option<Instant>,
+        mut on_blocked: impl FnMut() -> Result<(), JvmError>,
+    ) -> Result<(), JvmError> {
+        let mut monitors = self.inner.lock().unwrap();
+        let (recursions, depth) = match monitors.owned.get(&obj) {
+            Some(monitor) if monitor.owner == thread_id => (monitor.recursions, monitor.depth),
+            _ => throw_exception!(IllegalMonitorStateException, "current thread is not owner")?,
+        };
+        monitors.owned.remove(&obj);
+        monitors.waiting.insert(
+            thread_id,
+            Waiter {
+                obj,
+                notified: false,
+            },
+        );
+        self.released.notify_all();
+
+        let mut res = Ok(());
+        loop {
+            if monitors.waiting[&thread_id].notified {
+                break;
+            }
+            let timeout = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        break;
+                    }
+                    BLOCKED_POLL_INTERVAL.min(deadline - now)
+                }
+                None => BLOCKED_POLL_INTERVAL,
+            };
+            monitors = self.released.wait_timeout(monitors, timeout).unwrap().0;
+            drop(monitors);
+            res = on_blocked();
+            monitors = self.inner.lock().unwrap();
+            if res.is_err() {
+                break;
+            }
+        }
+        monitors.waiting.remove(&thread_id);
+        drop(monitors);
+
+        // the monitor must be owned again even if waiting was interrupted by an error
+        self.enter(thread_id, obj, depth, &mut on_blocked)?;
+        let mut monitors = self.inner.lock().unwrap();
+        if let Some(monitor) = monitors.owned.get_mut(&obj) {
+            monitor.recursions = recursions;
+        }
+        res
+    }
+
+    /// Implements `Object.notify` and `Object.notifyAll`.
+    pub fn notify(&self, thread_id: ThreadId, obj: HeapRef, all: bool) -> Result<(), JvmError> {
+        let mut monitors = self.inner.lock().unwrap();
+        if monitors.owned.get(&obj).map(|monitor| monitor.owner) != Some(thread_id) {
+            throw_exception!(IllegalMonitorStateException, "current thread is not owner")?
+        }
+        for waiter in monitors
+            .waiting
+            .values_mut()
+            .filter(|waiter| waiter.obj == obj && !waiter.notified)
+        {
+            waiter.notified = true;
+            if !all {
+                break;
+            }
+        }
+        self.released.notify_all();
+        Ok(())
+    }
+
+    pub fn owner_of(&self, obj: HeapRef) -> Option<ThreadId> {
+        self.inner
+            .lock()
+            .unwrap()
+            .owned
+            .get(&obj)
+            .map(|monitor| monitor.owner)
+    }
+
+    /// Returns objects locked by the thread together with the frame depth that locked them.
+    pub fn owned_by(&self, thread_id: ThreadId) -> Vec<(HeapRef, usize)> {
+        self.inner
+            .lock()
+            .unwrap()
+            .owned
+            .iter()
+            .filter(|(_, monitor)| monitor.owner == thread_id)
+            .map(|(obj, monitor)| (*obj, monitor.depth))
+            .collect()
+    }
+
+    pub fn waiting_on(&self, thread_id: ThreadId) -> Option<HeapRef> {
+        self.inner
+            .lock()
+            .unwrap()
+            .waiting
+            .get(&thread_id)
+            .map(|waiter| waiter.obj)
+    }
+
+    pub fn contended_by(&self, thread_id: ThreadId) -> Option<HeapRef> {
+        self.inner
+            .lock()
+            .unwrap()
+            .contended
+            .get(&thread_id)
+            .copied()
+    }
+}
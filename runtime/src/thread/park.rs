@@ -0,0 +1,99 @@
+use crate::error::JvmError;
+use crate::heap::HeapRef;
+use crate::keys::ThreadId;
+use std::collections::HashMap;
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long a parked thread sleeps before it wakes up to check for safepoint requests.
+const PARKED_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Default)]
+struct ParkState {
+    permit: bool,
+    /// `Thread.parkBlocker` at the moment of parking, set only while the thread is parked.
+    blocker: Option<HeapRef>,
+    parked: bool,
+}
+
+/// Per-thread permits backing `Unsafe.park`/`Unsafe.unpark`.
+#[derive(Default)]
+pub struct ParkTable {
+    inner: Mutex<HashMap<ThreadId, ParkState>>,
+    unparked: Condvar,
+}
+
+impl ParkTable {
+    /// Blocks until the permit is available or `deadline` passes, consuming the permit.
+    /// `on_blocked` is invoked periodically (without the table lock held) while waiting.
+    pub fn park(
+        &self,
+        thread_id: ThreadId,
+        blocker: Option<HeapRef>,
+        deadline: Option<Instant>,
+        mut on_blocked: impl FnMut() -> Result<(), JvmError>,
+    ) -> Result<(), JvmError> {
+        let mut states = self.inner.lock().unwrap();
+        loop {
+            let state = states.entry(thread_id).or_default();
+            if state.permit {
+                state.permit = false;
+                state.parked = false;
+                state.blocker = None;
+                return Ok(());
+            }
+            let timeout = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        state.parked = false;
+                        state.blocker = None;
+                        return Ok(());
+                    }
+                    PARKED_POLL_INTERVAL.min(deadline - now)
+                }
+                None => PARKED_POLL_INTERVAL,
+            };
+            state.parked = true;
+            state.blocker = blocker;
+            states = self.unparked.wait_timeout(states, timeout).unwrap().0;
+            drop(states);
+            let res = on_blocked();
+            states = self.inner.lock().unwrap();
+            if let Err(e) = res {
+                let state = states.entry(thread_id).or_default();
+                state.parked = false;
+                state.blocker = None;
+                return Err(e);
+            }
+        }
+    }
+
+    pub fn unpark(&self, thread_id: ThreadId) {
+        self.inner
+            .lock()
+            .unwrap()
+            .entry(thread_id)
+            .or_default()
+            .permit = true;
+        self.unparked.notify_all();
+    }
+
+    pub fn is_parked(&self, thread_id: ThreadId) -> bool {
+        self.inner
+            .lock()
+            .unwrap()
+            .get(&thread_id)
+            .is_some_and(|state| state.parked)
+    }
+
+    /// Returns the park blocker of the thread if it is currently parked.
+    pub fn blocker_of(&self, thread_id: ThreadId) -> Option<HeapRef> {
+        self.inner
+            .lock()
+            .unwrap()
+            .get(&thread_id)
+            .filter(|state| state.parked)
+            .and_then(|state| state.blocker)
+    }
+}
@@ -17,10 +17,24 @@ pub struct BootstrapRegistry {
     pub thread_thread_group_and_name_constructor_mk: MethodKey,
     pub thread_group_uncaught_exception_mk: MethodKey,
     pub thread_get_thread_group_mk: MethodKey,
+    pub signal_dispatch_mk: MethodKey,
+    pub thread_run_mk: MethodKey,
+    pub thread_exit_mk: MethodKey,
+    pub thread_dispatch_uncaught_exception_mk: MethodKey,
+    pub shutdown_shutdown_mk: MethodKey,
+    pub throwable_cause_constructor_mk: MethodKey,
+    pub class_loader_load_class_mk: MethodKey,
 
     // Common field keys
     pub class_name_fk: FieldKey,
     pub class_primitive_fk: FieldKey,
+    pub class_class_loader_fk: FieldKey,
+    pub class_class_data_fk: FieldKey,
+    pub class_loaders_platform_loader_fk: FieldKey,
+    pub class_loaders_app_loader_fk: FieldKey,
+    pub url_connection_url_fk: FieldKey,
+    pub url_protocol_fk: FieldKey,
+    pub url_file_fk: FieldKey,
     pub system_out_fk: FieldKey,
     pub system_err_fk: FieldKey,
     pub file_output_stream_fd_fk: FieldKey,
@@ -34,6 +48,13 @@ pub struct BootstrapRegistry {
     pub stack_trace_declaring_class_name_fk: FieldKey,
     pub reference_referent_fk: FieldKey,
     pub file_path_fk: FieldKey,
+    pub thread_park_blocker_fk: FieldKey,
+    pub thread_name_fk: FieldKey,
+    pub thread_eetop_fk: FieldKey,
+    pub thread_holder_fk: FieldKey,
+    pub thread_holder_daemon_fk: FieldKey,
+    pub thread_holder_thread_status_fk: FieldKey,
+    pub aos_exclusive_owner_thread_fk: FieldKey,
 
     // Common class names (interned)
     pub java_lang_object_sym: Symbol,
@@ -44,7 +65,19 @@ pub struct BootstrapRegistry {
     pub java_lang_thread_sym: Symbol,
     pub java_lang_thread_group_sym: Symbol,
     pub java_lang_ref_reference_sym: Symbol,
+    pub java_lang_ref_soft_reference_sym: Symbol,
+    pub java_lang_ref_final_reference_sym: Symbol,
     pub java_io_file_sym: Symbol,
+    pub jdk_internal_misc_signal_sym: Symbol,
+    pub abstract_ownable_synchronizer_sym: Symbol,
+    pub java_lang_shutdown_sym: Symbol,
+    pub java_lang_error_sym: Symbol,
+    pub java_lang_linkage_error_sym: Symbol,
+    pub java_lang_exception_in_initializer_error_sym: Symbol,
+    pub jdk_internal_loader_class_loaders_sym: Symbol,
+    pub java_lang_invoke_method_handle_sym: Symbol,
+    pub java_lang_invoke_var_handle_sym: Symbol,
+    pub java_lang_invoke_method_handle_natives_sym: Symbol,
 
     // Primitive name symbols
     pub int_sym: Symbol,
@@ -65,16 +98,18 @@ pub struct BootstrapRegistry {
     pub clone_sym: Symbol,
 
     // Common descriptors (interned)
-    pub void_desc: Symbol,         // ()V
-    pub string_desc: Symbol,       // Ljava/lang/String;
-    pub object_desc: Symbol,       // Ljava/lang/Object;
-    pub class_desc: Symbol,        // Ljava/lang/Class;
-    pub string_array_desc: Symbol, // [Ljava/lang/String;
-    pub byte_array_desc: Symbol,   // [B
-    pub int_array_desc: Symbol,    // [I
-    pub int_desc: Symbol,          // I
-    pub boolean_desc: Symbol,      // Z
-    pub clone_desc: Symbol,        // ()Ljava/lang/Object;
+    pub void_desc: Symbol,                 // ()V
+    pub string_desc: Symbol,               // Ljava/lang/String;
+    pub object_desc: Symbol,               // Ljava/lang/Object;
+    pub class_desc: Symbol,                // Ljava/lang/Class;
+    pub string_array_desc: Symbol,         // [Ljava/lang/String;
+    pub byte_array_desc: Symbol,           // [B
+    pub int_array_desc: Symbol,            // [I
+    pub int_desc: Symbol,                  // I
+    pub boolean_desc: Symbol,              // Z
+    pub clone_desc: Symbol,                // ()Ljava/lang/Object;
+    pub caller_sensitive_desc: Symbol,     // Ljdk/internal/reflect/CallerSensitive;
+    pub lambda_form_compiled_desc: Symbol, // Ljava/lang/invoke/LambdaForm$Compiled;
 
     // core classes IDs
     java_lang_class_id: OnceCell<ClassId>,
@@ -165,6 +200,34 @@ impl BootstrapRegistry {
                 name: interner.get_or_intern("getThreadGroup"),
                 desc: interner.get_or_intern("()Ljava/lang/ThreadGroup;"),
             },
+            signal_dispatch_mk: MethodKey {
+                name: interner.get_or_intern("dispatch"),
+                desc: interner.get_or_intern("(I)V"),
+            },
+            thread_run_mk: MethodKey {
+                name: interner.get_or_intern("run"),
+                desc: void_desc,
+            },
+            thread_exit_mk: MethodKey {
+                name: interner.get_or_intern("exit"),
+                desc: void_desc,
+            },
+            thread_dispatch_uncaught_exception_mk: MethodKey {
+                name: interner.get_or_intern("dispatchUncaughtException"),
+                desc: interner.get_or_intern("(Ljava/lang/Throwable;)V"),
+            },
+            shutdown_shutdown_mk: MethodKey {
+                name: interner.get_or_intern("shutdown"),
+                desc: void_desc,
+            },
+            throwable_cause_constructor_mk: MethodKey {
+                name: init_sym,
+                desc: interner.get_or_intern("(Ljava/lang/Throwable;)V"),
+            },
+            class_loader_load_class_mk: MethodKey {
+                name: interner.get_or_intern("loadClass"),
+                desc: interner.get_or_intern("(Ljava/lang/String;)Ljava/lang/Class;"),
+            },
 
             // Field keys
             class_name_fk: FieldKey {
@@ -175,6 +238,35 @@ impl BootstrapRegistry {
                 name: interner.get_or_intern("primitive"),
                 desc: boolean_desc,
             },
+            class_class_loader_fk: FieldKey {
+                name: interner.get_or_intern("classLoader"),
+                desc: interner.get_or_intern("Ljava/lang/ClassLoader;"),
+            },
+            class_class_data_fk: FieldKey {
+                name: interner.get_or_intern("classData"),
+                desc: object_desc,
+            },
+            class_loaders_platform_loader_fk: FieldKey {
+                name: interner.get_or_intern("PLATFORM_LOADER"),
+                desc: interner
+                    .get_or_intern("Ljdk/internal/loader/ClassLoaders$PlatformClassLoader;"),
+            },
+            class_loaders_app_loader_fk: FieldKey {
+                name: interner.get_or_intern("APP_LOADER"),
+                desc: interner.get_or_intern("Ljdk/internal/loader/ClassLoaders$AppClassLoader;"),
+            },
+            url_connection_url_fk: FieldKey {
+                name: interner.get_or_intern("url"),
+                desc: interner.get_or_intern("Ljava/net/URL;"),
+            },
+            url_protocol_fk: FieldKey {
+                name: interner.get_or_intern("protocol"),
+                desc: string_desc,
+            },
+            url_file_fk: FieldKey {
+                name: interner.get_or_intern("file"),
+                desc: string_desc,
+            },
             throwable_backtrace_fk: FieldKey {
                 name: interner.get_or_intern("backtrace"),
                 desc: object_desc,
@@ -183,6 +275,34 @@ impl BootstrapRegistry {
                 name: interner.get_or_intern("referent"),
                 desc: object_desc,
             },
+            thread_park_blocker_fk: FieldKey {
+                name: interner.get_or_intern("parkBlocker"),
+                desc: object_desc,
+            },
+            thread_name_fk: FieldKey {
+                name: name_field,
+                desc: string_desc,
+            },
+            thread_eetop_fk: FieldKey {
+                name: interner.get_or_intern("eetop"),
+                desc: interner.get_or_intern("J"),
+            },
+            thread_holder_fk: FieldKey {
+                name: interner.get_or_intern("holder"),
+                desc: interner.get_or_intern("Ljava/lang/Thread$FieldHolder;"),
+            },
+            thread_holder_daemon_fk: FieldKey {
+                name: interner.get_or_intern("daemon"),
+                desc: interner.get_or_intern("Z"),
+            },
+            thread_holder_thread_status_fk: FieldKey {
+                name: interner.get_or_intern("threadStatus"),
+                desc: interner.get_or_intern("I"),
+            },
+            aos_exclusive_owner_thread_fk: FieldKey {
+                name: interner.get_or_intern("exclusiveOwnerThread"),
+                desc: interner.get_or_intern("Ljava/lang/Thread;"),
+            },
             throwable_depth_fk: FieldKey {
                 name: interner.get_or_intern("depth"),
                 desc: int_desc,
@@ -237,7 +357,25 @@ impl BootstrapRegistry {
             java_lang_thread_sym: interner.get_or_intern("java/lang/Thread"),
             java_lang_thread_group_sym: interner.get_or_intern("java/lang/ThreadGroup"),
             java_lang_ref_reference_sym: interner.get_or_intern("java/lang/ref/Reference"),
+            java_lang_ref_soft_reference_sym: interner.get_or_intern("java/lang/ref/SoftReference"),
+            java_lang_ref_final_reference_sym: interner
+                .get_or_intern("java/lang/ref/FinalReference"),
             java_io_file_sym: interner.get_or_intern("java/io/File"),
+            jdk_internal_misc_signal_sym: interner.get_or_intern("jdk/internal/misc/Signal"),
+            java_lang_shutdown_sym: interner.get_or_intern("java/lang/Shutdown"),
+            java_lang_error_sym: interner.get_or_intern("java/lang/Error"),
+            java_lang_linkage_error_sym: interner.get_or_intern("java/lang/LinkageError"),
+            java_lang_exception_in_initializer_error_sym: interner
+                .get_or_intern("java/lang/ExceptionInInitializerError"),
+            jdk_internal_loader_class_loaders_sym: interner
+                .get_or_intern("jdk/internal/loader/ClassLoaders"),
+            java_lang_invoke_method_handle_sym: interner
+                .get_or_intern("java/lang/invoke/MethodHandle"),
+            java_lang_invoke_var_handle_sym: interner.get_or_intern("java/lang/invoke/VarHandle"),
+            java_lang_invoke_method_handle_natives_sym: interner
+                .get_or_intern("java/lang/invoke/MethodHandleNatives"),
+            abstract_ownable_synchronizer_sym: interner
+                .get_or_intern("java/util/concurrent/locks/AbstractOwnableSynchronizer"),
 
             // Method names
             init_sym,
@@ -257,6 +395,9 @@ impl BootstrapRegistry {
             boolean_desc,
             int_array_desc: interner.get_or_intern("[I"),
             clone_desc: interner.get_or_intern("()Ljava/lang/Object;"),
+            caller_sensitive_desc: interner.get_or_intern("Ljdk/internal/reflect/CallerSensitive;"),
+            lambda_form_compiled_desc: interner
+                .get_or_intern("Ljava/lang/invoke/LambdaForm$Compiled;"),
 
             // Primitive names
             int_sym,
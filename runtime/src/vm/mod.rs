@@ -4,6 +4,7 @@ use crate::throw_exception;
 use common::jtype::{JavaType, PrimitiveType};
 
 pub mod bootstrap_registry;
+pub mod signal;
 pub mod stack;
 pub mod throw;
 
@@ -0,0 +1,145 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+/// `jdk.internal.misc.Signal` handler codes, see `Signal.handle0`.
+pub const HANDLER_DEFAULT: i64 = 0;
+pub const HANDLER_IGNORE: i64 = 1;
+pub const HANDLER_DISPATCH: i64 = 2;
+/// Returned by `handle0` when the signal is used by the VM or can't be caught at all.
+pub const HANDLER_RESERVED: i64 = -1;
+
+static PENDING_SIGNALS: AtomicU64 = AtomicU64::new(0);
+static POLL_REQUESTED: AtomicBool = AtomicBool::new(false);
+/// Signals dispatched to `jdk.internal.misc.Signal`, to install them again on a VM restored
+/// from the startup archive.
+static JAVA_HANDLED_SIGNALS: AtomicU64 = AtomicU64::new(0);
+
+/// Only records the signal and raises the safepoint flag, the actual work (thread dumps,
+/// dispatching to `jdk.internal.misc.Signal`) is done by a Java thread at the next poll.
+extern "C" fn record_signal(sig: libc::c_int) {
+    // only async-signal-safe operations are allowed here
+    PENDING_SIGNALS.fetch_or(1 << sig, Ordering::SeqCst);
+    POLL_REQUESTED.store(true, Ordering::SeqCst);
+}
+
+fn record_signal_handler() -> libc::sighandler_t {
+    record_signal as *const () as libc::sighandler_t
+}
+
+fn is_reserved(sig: i32) -> bool {
+    matches!(
+        sig,
+        libc::SIGQUIT
+            | libc::SIGKILL
+            | libc::SIGSTOP
+            | libc::SIGSEGV
+            | libc::SIGBUS
+            | libc::SIGFPE
+            | libc::SIGILL
+    )
+}
+
+fn sigaction(sig: i32, handler: libc::sighandler_t) -> Option<libc::sighandler_t> {
+    // SAFETY: sigaction structs are plain data, zeroed is a valid initial state
+    unsafe {
+        let mut new_action: libc::sigaction = std::mem::zeroed();
+        let mut old_action: libc::sigaction = std::mem::zeroed();
+        new_action.sa_sigaction = handler;
+        new_action.sa_flags = libc::SA_RESTART;
+        libc::sigemptyset(&mut new_action.sa_mask);
+        if libc::sigaction(sig, &new_action, &mut old_action) != 0 {
+            return None;
+        }
+        Some(old_action.sa_sigaction)
+    }
+}
+
+fn current_handler(sig: i32) -> Option<libc::sighandler_t> {
+    // SAFETY: passing null as new action only queries the current one
+    unsafe {
+        let mut old_action: libc::sigaction = std::mem::zeroed();
+        if libc::sigaction(sig, std::ptr::null(), &mut old_action) != 0 {
+            return None;
+        }
+        Some(old_action.sa_sigaction)
+    }
+}
+
+/// Installs handlers for signals the VM handles itself (SIGQUIT prints a thread dump).
+pub fn install_vm_handlers() {
+    sigaction(libc::SIGQUIT, record_signal_handler());
+}
+
+/// Implements `Signal.handle0`: installs the requested handler and returns the previous one
+/// in the same encoding.
+pub fn set_java_handler(sig: i32, handler: i64) -> i64 {
+    if !(1..64).contains(&sig) || is_reserved(sig) {
+        return HANDLER_RESERVED;
+    }
+    // like hotspot, don't override an inherited SIG_IGN for termination signals (e.g. nohup)
+    if handler == HANDLER_DISPATCH
+        && matches!(sig, libc::SIGHUP | libc::SIGINT | libc::SIGTERM)
+        && current_handler(sig) == Some(libc::SIG_IGN)
+    {
+        return HANDLER_IGNORE;
+    }
+    let new_handler = match handler {
+        HANDLER_DEFAULT => libc::SIG_DFL,
+        HANDLER_IGNORE => libc::SIG_IGN,
+        _ => record_signal_handler(),
+    };
+    let previous = sigaction(sig, new_handler);
+    if previous.is_some() {
+        if handler == HANDLER_DISPATCH {
+            JAVA_HANDLED_SIGNALS.fetch_or(1 << sig, Ordering::SeqCst);
+        } else {
+            JAVA_HANDLED_SIGNALS.fetch_and(!(1 << sig), Ordering::SeqCst);
+        }
+    }
+    match previous {
+        Some(libc::SIG_DFL) => HANDLER_DEFAULT,
+        Some(libc::SIG_IGN) => HANDLER_IGNORE,
+        Some(old) if old == record_signal_handler() => HANDLER_DISPATCH,
+        Some(old) => old as i64,
+        None => HANDLER_RESERVED,
+    }
+}
+
+/// Mask of the signals currently dispatched to Java handlers.
+pub fn java_handled_signals() -> u64 {
+    JAVA_HANDLED_SIGNALS.load(Ordering::SeqCst)
+}
+
+/// Dispatches the signals of `mask` to Java handlers, as `Signal.handle0` did for them.
+pub fn install_java_handlers(mask: u64) {
+    for sig in 1..64 {
+        if mask & (1 << sig) != 0 {
+            set_java_handler(sig, HANDLER_DISPATCH);
+        }
+    }
+}
+
+/// Restores the default disposition of the signal and raises it again, used when a
+/// Java-level handler couldn't be run.
+pub fn raise_with_default_action(sig: i32) {
+    sigaction(sig, libc::SIG_DFL);
+    // SAFETY: raising a signal for the current process
+    unsafe {
+        libc::raise(sig);
+    }
+}
+
+/// Asks Java threads to stop at the next safepoint poll.
+pub fn request_poll() {
+    POLL_REQUESTED.store(true, Ordering::SeqCst);
+}
+
+#[inline]
+pub fn poll_requested() -> bool {
+    POLL_REQUESTED.load(Ordering::Relaxed)
+}
+
+/// Clears the safepoint flag and returns the mask of signals received since the last call.
+pub fn take_pending_signals() -> u64 {
+    POLL_REQUESTED.store(false, Ordering::SeqCst);
+    PENDING_SIGNALS.swap(0, Ordering::SeqCst)
+}
@@ -2,6 +2,7 @@ use crate::error::JvmError;
 use crate::heap::HeapRef;
 use crate::vm::Value;
 use crate::{MethodId, VmConfig, build_exception, debug_log_method};
+use std::sync::Arc;
 
 #[derive(Clone)]
 pub enum FrameType {
@@ -20,16 +21,43 @@ impl FrameType {
     pub fn is_native(&self) -> bool {
         matches!(self, FrameType::NativeFrame(_))
     }
+
+    /// References the frame holds: locals and operands of a Java frame, arguments of a native
+    /// one.
+    pub fn references(&self) -> Vec<HeapRef> {
+        let values: Vec<&Value> = match self {
+            FrameType::JavaFrame(f) => f.locals.iter().flatten().chain(&f.operands).collect(),
+            FrameType::NativeFrame(f) => f.args.iter().collect(),
+        };
+        values
+            .into_iter()
+            .filter_map(|value| match value {
+                Value::Ref(heap_ref) => Some(*heap_ref),
+                _ => None,
+            })
+            .collect()
+    }
 }
 
 #[derive(Clone)]
 pub struct NativeFrame {
     method_id: MethodId,
+    /// Kept for the garbage collector, the native borrows them through [`NativeFrame::args`].
+    args: Arc<Vec<Value>>,
 }
 
 impl NativeFrame {
-    pub fn new(method_id: MethodId) -> Self {
-        Self { method_id }
+    pub fn new(method_id: MethodId, args: Vec<Value>) -> Self {
+        Self {
+            method_id,
+            args: Arc::new(args),
+        }
+    }
+
+    /// The arguments, shared with the frame so the native can use them while the frame is on
+    /// the stack.
+    pub fn args(&self) -> Arc<Vec<Value>> {
+        self.args.clone()
     }
 }
 
@@ -1,5 +1,8 @@
-use clap::Parser;
-use runtime::VmConfig;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser};
+use common::utils::manifest::Manifest;
+use common::utils::zip::ZipArchive;
+use runtime::{AssertionDirective, AssertionStatus, VerifyMode, VmConfig};
+use std::path::{Path, PathBuf};
 use tracing_log::log::debug;
 
 #[derive(Parser, Debug)]
@@ -11,7 +14,7 @@ pub struct Args {
         visible_alias = "cp",
         visible_alias = "class-path",
         value_delimiter = ';',
-        help = "Classpath entries (only dirs, no jars(todo)); use ';' as separator"
+        help = "Classpath entries (directories, .jar or .zip files); use ';' as separator"
     )]
     pub class_path: Vec<String>,
     #[arg(
@@ -21,13 +24,184 @@ pub struct Args {
     )]
     pub jdwp_port: Option<u16>,
     #[arg(
+        long = "detect-deadlocks",
+        help = "Periodically checks for Java-level deadlocks and prints a thread dump when one is found"
+    )]
+    pub detect_deadlocks: bool,
+    #[arg(
+        long = "jar",
+        conflicts_with = "main_class_path",
+        help = "Runs the Main-Class of the JAR's manifest; the JAR and its Class-Path replace the classpath"
+    )]
+    pub jar: Option<String>,
+    #[arg(
+        short = 'p',
+        long = "module-path",
+        value_delimiter = ';',
+        help = "Module path entries (modular or plain JARs, exploded modules or directories of modules); use ';' as separator"
+    )]
+    pub module_path: Vec<String>,
+    #[arg(
+        short = 'm',
+        long = "module",
+        conflicts_with_all = ["jar", "main_class_path"],
+        help = "Runs the main class of a module, as <module>[/<main-class>]; without a class the module's ModuleMainClass is used"
+    )]
+    pub module: Option<String>,
+    #[arg(
+        long = "add-exports",
+        help = "Exports <module>/<package> to <target-module>(,<target-module>)*, ALL-UNNAMED targets the classpath; repeatable"
+    )]
+    pub add_exports: Vec<String>,
+    #[arg(
+        long = "add-opens",
+        help = "Opens <module>/<package> to <target-module>(,<target-module>)*; only makes its resources visible to class loaders, reflection doesn't check access yet; repeatable"
+    )]
+    pub add_opens: Vec<String>,
+    #[arg(
+        long = "verify",
+        default_value = "remote",
+        help = "Classes to verify when linking: none, remote (all but the bootstrap classes) or all; -Xverify:<mode> is accepted too"
+    )]
+    pub verify: VerifyMode,
+    #[arg(
+        long = "verbose-class",
+        help = "Prints each class read for the builtin loaders and where it was read from; -verbose:class is accepted too"
+    )]
+    pub verbose_class: bool,
+    #[arg(
+        long = "enable-assertions",
+        value_name = "<package>...|<class>",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "",
+        help = "Enables assertions in the classes of the application, or only in a package and its subpackages or a class; -ea[:...] is accepted too; repeatable"
+    )]
+    pub enable_assertions: Vec<String>,
+    #[arg(
+        long = "disable-assertions",
+        value_name = "<package>...|<class>",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "",
+        help = "Disables assertions like --enable-assertions enables them; -da[:...] is accepted too; repeatable"
+    )]
+    pub disable_assertions: Vec<String>,
+    #[arg(
+        long = "enable-system-assertions",
+        help = "Enables assertions in the classes of the bootstrap loader; -esa is accepted too"
+    )]
+    pub enable_system_assertions: bool,
+    #[arg(
+        long = "disable-system-assertions",
+        help = "Disables assertions in the classes of the bootstrap loader; -dsa is accepted too"
+    )]
+    pub disable_system_assertions: bool,
+    #[arg(skip)]
+    pub assertions: AssertionStatus,
+    #[arg(
+        long = "classpath-index",
+        help = "Keeps the listings of classpath directories in this file between runs; a directory is read again when its modification time changes"
+    )]
+    pub classpath_index: Option<String>,
+    #[arg(
+        long = "archive",
+        conflicts_with = "jdwp_port",
+        help = "Starts from the state saved in a startup archive made by --dump-archive instead of initializing the JDK again"
+    )]
+    pub archive: Option<String>,
+    #[arg(
+        long = "dump-archive",
+        conflicts_with_all = ["jar", "module", "main_class_path", "archive", "jdwp_port"],
+        help = "Initializes the JDK, saves the state of the VM to a startup archive and exits"
+    )]
+    pub dump_archive: Option<String>,
+    #[arg(
+        required_unless_present_any = ["jar", "module", "dump_archive"],
         help = "Main class to run from path that matches the package structure \
         (e.g. com.example.Main or com/example/Main for com/example/Main.class)"
     )]
-    pub main_class_path: String,
+    pub main_class_path: Option<String>,
+}
+
+/// Resolves the main class and classpath of `-jar` mode from the manifest.
+fn resolve_jar(jar: &str) -> Result<(String, Vec<String>), String> {
+    let archive =
+        ZipArchive::open(jar).map_err(|e| format!("Invalid or corrupt jarfile {jar}: {e}"))?;
+    let manifest = Manifest::read_from(&archive)
+        .map_err(|e| format!("Invalid or corrupt jarfile {jar}: {e}"))?
+        .unwrap_or_default();
+
+    if let Some(agent) = manifest.main_attribute("Launcher-Agent-Class") {
+        return Err(format!(
+            "Launcher-Agent-Class {agent} in {jar} is not supported: java agents can't be loaded"
+        ));
+    }
+    let main_class = manifest
+        .main_class()
+        .ok_or_else(|| format!("no main manifest attribute, in {jar}"))?
+        .trim()
+        .to_string();
+
+    // Class-Path entries are URLs relative to the directory of the JAR
+    let base = Path::new(jar).parent().unwrap_or(Path::new(""));
+    let mut class_path = vec![jar.to_string()];
+    for entry in manifest.class_path() {
+        let entry = entry.strip_prefix("file:").unwrap_or(entry);
+        if entry.contains("://") {
+            debug!("Skipping non-file Class-Path entry {entry} of {jar}");
+            continue;
+        }
+        class_path.push(base.join(entry).to_string_lossy().into_owned());
+    }
+    Ok((main_class, class_path))
+}
+
+/// The assertion switches in command line order, a later one overrides an earlier one.
+fn assertion_status(matches: &ArgMatches) -> AssertionStatus {
+    let mut switches: Vec<(usize, bool, &str)> = Vec::new();
+    for (id, enabled) in [("enable_assertions", true), ("disable_assertions", false)] {
+        if let (Some(indices), Some(values)) =
+            (matches.indices_of(id), matches.get_many::<String>(id))
+        {
+            switches.extend(
+                indices
+                    .zip(values)
+                    .map(|(i, value)| (i, enabled, value.as_str())),
+            );
+        }
+    }
+    let mut status = AssertionStatus::default();
+    let last_system = ["enable_system_assertions", "disable_system_assertions"]
+        .into_iter()
+        .filter(|id| matches.get_flag(id))
+        .max_by_key(|id| matches.index_of(id));
+    status.system_enabled = last_system == Some("enable_system_assertions");
+    switches.sort_unstable_by_key(|(index, ..)| *index);
+    for (_, enabled, value) in switches {
+        match value {
+            "" => status.enabled = enabled,
+            name => {
+                let (name, package) = match name.strip_suffix("...") {
+                    Some(package) => (package, true),
+                    None => (name, false),
+                };
+                status.directives.push(AssertionDirective {
+                    name: name.replace('/', "."),
+                    package,
+                    enabled,
+                });
+            }
+        }
+    }
+    status
 }
 
-fn create_vm_configuration(mut args: Args, main_class: String) -> Result<VmConfig, String> {
+fn create_vm_configuration(
+    mut args: Args,
+    main_class: Option<String>,
+    main_module: Option<String>,
+) -> Result<VmConfig, String> {
     let java_home = std::env::var("JAVA_HOME").expect("JAVA_HOME not set");
     if args.class_path.is_empty() {
         let current_dir = std::env::current_dir()
@@ -47,10 +221,20 @@ fn create_vm_configuration(mut args: Args, main_class: String) -> Result<VmConfi
                 main_class,
                 version: value.trim_matches('"').to_string(),
                 class_path: args.class_path,
+                module_path: args.module_path,
+                main_module,
+                add_exports: args.add_exports,
+                add_opens: args.add_opens,
                 initial_heap_size: 0,
                 max_heap_size: 0,
                 frame_stack_size: 256,
                 jdwp_port: args.jdwp_port,
+                detect_deadlocks: args.detect_deadlocks,
+                verify: args.verify,
+                assertions: args.assertions,
+                archive: args.archive.map(PathBuf::from),
+                verbose_class: args.verbose_class,
+                classpath_index: args.classpath_index.map(PathBuf::from),
             });
         }
     }
@@ -61,19 +245,85 @@ fn create_vm_configuration(mut args: Args, main_class: String) -> Result<VmConfi
 fn main() {
     #[cfg(feature = "log-runtime-traces")]
     common::utils::telemetry::init_tracing();
-    let args = Args::parse();
+    // accept the launcher spellings `-jar`, `-Xverify:<mode>`, `-verbose:class` and of the
+    // assertion switches as well
+    let matches = Args::command().get_matches_from(std::env::args().map(|arg| {
+        let assertion_switch = |arg: &str| {
+            let (switch, name) = arg.split_once(':').unwrap_or((arg, ""));
+            let long = match switch {
+                "-ea" | "-enableassertions" => "--enable-assertions",
+                "-da" | "-disableassertions" => "--disable-assertions",
+                "-esa" | "-enablesystemassertions" => {
+                    return Some("--enable-system-assertions".into());
+                }
+                "-dsa" | "-disablesystemassertions" => {
+                    return Some("--disable-system-assertions".into());
+                }
+                _ => return None,
+            };
+            Some(match arg.contains(':') {
+                true => format!("{long}={name}"),
+                false => long.to_string(),
+            })
+        };
+        if arg == "-jar" {
+            "--jar".to_string()
+        } else if arg == "-verbose:class" {
+            "--verbose-class".to_string()
+        } else if let Some(mode) = arg.strip_prefix("-Xverify:") {
+            format!("--verify={mode}")
+        } else if let Some(long) = assertion_switch(&arg) {
+            long
+        } else {
+            arg
+        }
+    }));
+    let mut args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    args.assertions = assertion_status(&matches);
     debug!("Provided command line arguments: {:?}", args);
 
-    let main_class = args.main_class_path.replace('.', "/");
+    if let Some(path) = args.dump_archive.clone() {
+        let exit_code = match create_vm_configuration(args, None, None) {
+            Ok(config) => runtime::dump_archive(config, Path::new(&path)),
+            Err(e) => {
+                eprintln!("Error creating VM configuration: {}", e);
+                1
+            }
+        };
+        std::process::exit(exit_code);
+    }
+
+    let (main_class, main_module) = match (&args.jar, &args.module, &args.main_class_path) {
+        (Some(jar), _, _) => match resolve_jar(jar) {
+            Ok((main_class, class_path)) => {
+                args.class_path = class_path;
+                (Some(main_class), None)
+            }
+            Err(e) => {
+                eprintln!("Error: {e}");
+                std::process::exit(1);
+            }
+        },
+        (None, Some(module), _) => match module.split_once('/') {
+            Some((module, main_class)) => (Some(main_class.to_string()), Some(module.to_string())),
+            None => (None, Some(module.clone())),
+        },
+        (None, None, Some(main_class_path)) => (Some(main_class_path.clone()), None),
+        (None, None, None) => {
+            unreachable!("clap requires a main class unless -jar, -m or --dump-archive is given")
+        }
+    };
+    let main_class = main_class.map(|c| c.replace('.', "/"));
 
-    let vm_config = match create_vm_configuration(args, main_class) {
+    let vm_config = match create_vm_configuration(args, main_class, main_module) {
         Ok(config) => config,
         Err(e) => {
             eprintln!("Error creating VM configuration: {}", e);
             return;
         }
     };
-    if let Err(_) = runtime::start(vm_config) {
-        std::process::exit(1);
+    let exit_code = runtime::start(vm_config);
+    if exit_code != 0 {
+        std::process::exit(exit_code);
     }
 }
@@ -1,7 +1,12 @@
 use assert_cmd::Command;
 use insta::with_settings;
 use rstest::rstest;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
 use std::path::{Path, PathBuf};
+use std::process::{Child, Stdio};
+use std::sync::mpsc::{Receiver, channel};
+use std::time::{Duration, Instant};
 
 const DISPLAY_SNAPSHOT_PATH: &str = "../snapshots";
 
@@ -47,7 +52,11 @@ fn non_error_cases(
     let class_path = current_dir.join("tests/testdata/compiled");
     let main_class_path = transform_absolute_path_to_package(&path);
     let mut cmd = Command::cargo_bin("vm").unwrap();
-    cmd.arg("-c").arg(class_path).arg(&main_class_path);
+    // fixtures check their results with assert statements
+    cmd.arg("-ea")
+        .arg("-c")
+        .arg(class_path)
+        .arg(&main_class_path);
 
     let output = cmd.assert().success().get_output().clone();
     let stdout = String::from_utf8_lossy(&output.stdout);
@@ -83,7 +92,11 @@ fn error_cases(
     let class_path = current_dir.join("tests/testdata/compiled");
     let main_class_path = transform_absolute_path_to_package(&path);
     let mut cmd = Command::cargo_bin("vm").unwrap();
-    cmd.arg("-c").arg(class_path).arg(&main_class_path);
+    // fixtures check their results with assert statements
+    cmd.arg("-ea")
+        .arg("-c")
+        .arg(class_path)
+        .arg(&main_class_path);
 
     // when
     let output = cmd.assert().failure().get_output().clone();
@@ -107,3 +120,713 @@ fn error_cases(
         }
     );
 }
+
+#[rstest]
+#[case::uncaught_exception_in_main(
+    "shutdown.uncaught.UncaughtMain",
+    1,
+    "main throws\nhook after uncaught exception\n",
+    "Exception in thread \"main\" java.lang.IllegalStateException: main failed\n\
+     \tat shutdown.uncaught.UncaughtMain.main(UncaughtMain.java:9)\n"
+)]
+#[case::hooks_after_main_returns(
+    "shutdown.hooks.HooksMain",
+    0,
+    "main returns\nhook after main returned\n",
+    ""
+)]
+#[case::exit_from_other_thread(
+    "shutdown.thread_exit.ThreadExitMain",
+    3,
+    "worker exits\nhook after exit from worker\n",
+    ""
+)]
+#[case::halt_skips_hooks("shutdown.halt.HaltMain", 7, "main halts\n", "")]
+fn shutdown_sets_exit_status_and_runs_hooks(
+    #[case] main_class: &str,
+    #[case] exit_status: i32,
+    #[case] expected_stdout: &str,
+    #[case] expected_stderr: &str,
+) {
+    // given
+    // requires cargo build
+    let class_path = std::env::current_dir()
+        .unwrap()
+        .join("tests/testdata/compiled");
+    let mut cmd = Command::cargo_bin("vm").unwrap();
+    cmd.arg("-c").arg(class_path).arg(main_class);
+
+    // when
+    let output = cmd.assert().code(exit_status).get_output().clone();
+
+    // then
+    assert_eq!(String::from_utf8_lossy(&output.stdout), expected_stdout);
+    assert_eq!(String::from_utf8_lossy(&output.stderr), expected_stderr);
+}
+
+#[test]
+fn archived_startup_runs_like_cold_start() {
+    // given
+    // requires cargo build
+    let current_dir = std::env::current_dir().expect("Cannot get current dir");
+    let class_path = current_dir.join("tests/testdata/compiled");
+    let archive = std::env::temp_dir().join(format!("vm-startup-{}.archive", std::process::id()));
+    Command::cargo_bin("vm")
+        .unwrap()
+        .arg("--dump-archive")
+        .arg(&archive)
+        .arg("-c")
+        .arg(&class_path)
+        .assert()
+        .success();
+    let run = |archive: Option<&Path>| {
+        let mut cmd = Command::cargo_bin("vm").unwrap();
+        if let Some(archive) = archive {
+            cmd.arg("--archive").arg(archive);
+        }
+        cmd.arg("-c")
+            .arg(&class_path)
+            .arg("hello_world/HelloWorldOkMain");
+        cmd.assert().success().get_output().clone()
+    };
+
+    // when
+    let cold = run(None);
+    let archived = run(Some(&archive));
+    let _ = std::fs::remove_file(&archive);
+
+    // then
+    assert_eq!(
+        String::from_utf8_lossy(&archived.stdout),
+        String::from_utf8_lossy(&cold.stdout)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&archived.stderr),
+        String::from_utf8_lossy(&cold.stderr)
+    );
+}
+
+#[test]
+fn archived_startup_reads_archived_classes_from_the_archive() {
+    // given
+    // requires cargo build
+    let current_dir = std::env::current_dir().expect("Cannot get current dir");
+    let class_path = current_dir.join("tests/testdata/compiled");
+    let archive =
+        std::env::temp_dir().join(format!("vm-startup-verbose-{}.archive", std::process::id()));
+    let loaded_classes = |output: &[u8]| {
+        String::from_utf8_lossy(output)
+            .lines()
+            .filter_map(|line| line.strip_prefix("[class,load] "))
+            .filter_map(|line| line.split_once(" source: "))
+            .map(|(class, source)| (class.to_string(), source.to_string()))
+            .collect::<Vec<_>>()
+    };
+    let dump = Command::cargo_bin("vm")
+        .unwrap()
+        .arg("--dump-archive")
+        .arg(&archive)
+        .arg("--verbose-class")
+        .arg("-c")
+        .arg(&class_path)
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let dumped = loaded_classes(&dump.stdout);
+
+    // when
+    let run = Command::cargo_bin("vm")
+        .unwrap()
+        .arg("--archive")
+        .arg(&archive)
+        .arg("-verbose:class")
+        .arg("-c")
+        .arg(&class_path)
+        .arg("hello_world/HelloWorldOkMain")
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let _ = std::fs::remove_file(&archive);
+    let mut from_archive = loaded_classes(&run.stdout)
+        .into_iter()
+        .filter(|(_, source)| source == "startup archive")
+        .map(|(class, _)| class)
+        .collect::<Vec<_>>();
+
+    // then
+    assert!(dumped.iter().any(|(class, _)| class == "java.lang.Object"));
+    assert!(dumped.iter().all(|(_, source)| source.starts_with("jrt:/")));
+    let mut dumped = dumped
+        .into_iter()
+        .map(|(class, _)| class)
+        .collect::<Vec<_>>();
+    dumped.sort();
+    from_archive.sort();
+    assert_eq!(from_archive, dumped);
+}
+
+#[rstest]
+#[case::defaults(&[], "main: false\nchecked: false\nsystem: false\n")]
+#[case::subpackage_disabled(
+    &["-ea", "-da:assertions.switches.checked..."],
+    "main: true\nchecked: false\nsystem: false\n"
+)]
+#[case::class_and_system_enabled(
+    &["-da", "-ea:assertions.switches.checked.Checked", "-esa"],
+    "main: false\nchecked: true\nsystem: true\n"
+)]
+#[case::package_enabled(
+    &["--enable-assertions=...", "--enable-assertions=assertions..."],
+    "main: true\nchecked: true\nsystem: false\n"
+)]
+fn assertion_switches_set_desired_assertion_status(
+    #[case] switches: &[&str],
+    #[case] expected: &str,
+) {
+    // given
+    // requires cargo build
+    let current_dir = std::env::current_dir().expect("Cannot get current dir");
+    let class_path = current_dir.join("tests/testdata/compiled");
+    let mut cmd = Command::cargo_bin("vm").unwrap();
+    cmd.args(switches)
+        .arg("-c")
+        .arg(class_path)
+        .arg("assertions/switches/AssertionStatusMain");
+
+    // when
+    let output = cmd.assert().success().get_output().clone();
+
+    // then
+    assert_eq!(String::from_utf8_lossy(&output.stdout), expected);
+}
+
+/// Kills the VM if the test fails while the VM waits for it.
+struct KillOnDrop(Child);
+
+impl Drop for KillOnDrop {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+    }
+}
+
+/// Starts the VM with piped stdout and returns it with a channel of its stdout lines.
+fn spawn_vm(args: &[&str]) -> (KillOnDrop, Receiver<String>) {
+    let class_path = std::env::current_dir()
+        .unwrap()
+        .join("tests/testdata/compiled");
+    let mut child = std::process::Command::new(assert_cmd::cargo::cargo_bin("vm"))
+        .arg("-c")
+        .arg(class_path)
+        .args(args)
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Failed to start the VM");
+    let stdout = BufReader::new(child.stdout.take().unwrap());
+    let (tx, rx) = channel();
+    std::thread::spawn(move || {
+        for line in stdout.lines() {
+            if tx.send(line.unwrap()).is_err() {
+                break;
+            }
+        }
+    });
+    (KillOnDrop(child), rx)
+}
+
+/// Collects stdout lines of the VM up to and including the first one equal to `last`.
+fn read_lines_until(lines: &Receiver<String>, last: &str) -> Vec<String> {
+    let deadline = Instant::now() + Duration::from_secs(30);
+    let mut read = Vec::new();
+    loop {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        match lines.recv_timeout(timeout) {
+            Ok(line) => {
+                let done = line == last;
+                read.push(line);
+                if done {
+                    return read;
+                }
+            }
+            Err(e) => panic!("No line {last:?} ({e}), the VM printed {read:#?}"),
+        }
+    }
+}
+
+/// Replaces the hexadecimal addresses of monitors and objects, which differ between runs.
+fn mask_addresses(line: &str) -> String {
+    let mut masked = String::new();
+    let mut rest = line;
+    while let Some(pos) = rest.find("0x") {
+        masked.push_str(&rest[..pos]);
+        masked.push_str("0x...");
+        rest = rest[pos + 2..].trim_start_matches(|c: char| c.is_ascii_hexdigit());
+    }
+    masked.push_str(rest);
+    masked
+}
+
+#[test]
+fn deadlock_watchdog_reports_threads_locking_in_opposite_order() {
+    // given
+    // requires cargo build
+    let (_vm, lines) = spawn_vm(&["--detect-deadlocks", "threads.deadlock.DeadlockMain"]);
+
+    // when
+    let output = read_lines_until(&lines, "Found 1 deadlock.");
+
+    // then
+    let report_start = output
+        .iter()
+        .position(|line| line == "Found one Java-level deadlock:")
+        .expect("No deadlock report");
+    let report: Vec<_> = output[report_start..]
+        .iter()
+        .map(|line| mask_addresses(line))
+        .collect();
+    assert_eq!(
+        report,
+        [
+            "Found one Java-level deadlock:",
+            "=============================",
+            "\"left-first\":",
+            "  waiting to lock monitor 0x... (object 0x..., a threads.deadlock.DeadlockMain$Right),",
+            "  which is held by \"right-first\"",
+            "",
+            "\"right-first\":",
+            "  waiting to lock monitor 0x... (object 0x..., a threads.deadlock.DeadlockMain$Left),",
+            "  which is held by \"left-first\"",
+            "",
+            "Java stack information for the threads listed above:",
+            "===================================================",
+            "\"left-first\":",
+            "\tat threads.deadlock.DeadlockMain$LeftFirst.run(DeadlockMain.java:34)",
+            "\t- waiting to lock <0x...> (a threads.deadlock.DeadlockMain$Right)",
+            "\t- locked <0x...> (a threads.deadlock.DeadlockMain$Left)",
+            "\"right-first\":",
+            "\tat threads.deadlock.DeadlockMain$RightFirst.run(DeadlockMain.java:51)",
+            "\t- waiting to lock <0x...> (a threads.deadlock.DeadlockMain$Left)",
+            "\t- locked <0x...> (a threads.deadlock.DeadlockMain$Right)",
+            "",
+            "Found 1 deadlock.",
+        ]
+    );
+}
+
+/// Collects a thread dump the VM prints to stdout: the lines from its header until the VM
+/// stays quiet, split into one section per thread.
+fn read_thread_dump(lines: &Receiver<String>) -> Vec<Vec<String>> {
+    loop {
+        let line = lines
+            .recv_timeout(Duration::from_secs(30))
+            .expect("No thread dump");
+        if line.starts_with("Full thread dump ") {
+            break;
+        }
+    }
+    let mut sections = vec![Vec::new()];
+    while let Ok(line) = lines.recv_timeout(Duration::from_secs(2)) {
+        if line.is_empty() {
+            sections.push(Vec::new());
+        } else {
+            sections.last_mut().unwrap().push(mask_addresses(&line));
+        }
+    }
+    sections.retain(|section| !section.is_empty());
+    sections
+}
+
+/// The section of the thread named `name` with its number in the header masked.
+fn thread_section(sections: &[Vec<String>], name: &str) -> Vec<String> {
+    let header_start = format!("\"{name}\" #");
+    let mut section = sections
+        .iter()
+        .find(|section| section[0].starts_with(&header_start))
+        .unwrap_or_else(|| panic!("No thread {name} in {sections:#?}"))
+        .clone();
+    let number_end = section[0][header_start.len()..]
+        .find(' ')
+        .map(|pos| header_start.len() + pos)
+        .unwrap();
+    section[0] = format!("{header_start}..{}", &section[0][number_end..]);
+    section
+}
+
+#[test]
+fn sigquit_dumps_running_waiting_and_blocked_threads_with_stacks() {
+    // given
+    // requires cargo build
+    let (vm, lines) = spawn_vm(&["threads.dump.ThreadDumpMain"]);
+    read_lines_until(&lines, "ready");
+
+    // when
+    let status = std::process::Command::new("kill")
+        .arg("-QUIT")
+        .arg(vm.0.id().to_string())
+        .status()
+        .unwrap();
+    let sections = read_thread_dump(&lines);
+
+    // then
+    assert!(status.success());
+    let main = thread_section(&sections, "main");
+    assert_eq!(main.len(), 4, "{main:#?}");
+    assert_eq!(
+        main[..2],
+        [
+            "\"main\" #.. prio=5 os_prio=0 runnable",
+            "   java.lang.Thread.State: RUNNABLE",
+        ]
+    );
+    // the spinning loop is at its condition or its body
+    assert!(
+        [
+            "\tat threads.dump.ThreadDumpMain.main(ThreadDumpMain.java:64)",
+            "\tat threads.dump.ThreadDumpMain.main(ThreadDumpMain.java:65)",
+        ]
+        .contains(&main[2].as_str()),
+        "{main:#?}"
+    );
+    assert_eq!(
+        main[3],
+        "\t- locked <0x...> (a threads.dump.ThreadDumpMain$Monitor)"
+    );
+    let waiter = thread_section(&sections, "waiter");
+    assert_eq!(
+        waiter[..4],
+        [
+            "\"waiter\" #.. prio=5 os_prio=0 in Object.wait()",
+            "   java.lang.Thread.State: WAITING (on object monitor)",
+            "\tat java.lang.Object.wait0(Native Method)",
+            "\t- waiting on <0x...> (a threads.dump.ThreadDumpMain$WaitLock)",
+        ]
+    );
+    assert_eq!(
+        waiter.last().unwrap(),
+        "\tat threads.dump.ThreadDumpMain$Waiter.run(ThreadDumpMain.java:25)"
+    );
+    assert_eq!(
+        thread_section(&sections, "blocked"),
+        [
+            "\"blocked\" #.. prio=5 os_prio=0 waiting for monitor entry",
+            "   java.lang.Thread.State: BLOCKED (on object monitor)",
+            "\tat threads.dump.ThreadDumpMain$Blocked.run(ThreadDumpMain.java:42)",
+            "\t- waiting to lock <0x...> (a threads.dump.ThreadDumpMain$Monitor)",
+        ]
+    );
+}
+
+/// A debugger speaking just enough JDWP to redefine classes.
+struct JdwpClient {
+    stream: TcpStream,
+    next_id: u32,
+}
+
+impl JdwpClient {
+    fn connect(port: u16, vm: &mut Child) -> Self {
+        let started = Instant::now();
+        let mut stream = loop {
+            match TcpStream::connect(("127.0.0.1", port)) {
+                Ok(stream) => break stream,
+                Err(e) if started.elapsed() > Duration::from_secs(30) => {
+                    panic!("JDWP agent not listening on {port}: {e}")
+                }
+                Err(_) => {
+                    if let Some(status) = vm.try_wait().unwrap() {
+                        panic!("VM exited with {status} before the debugger connected");
+                    }
+                    std::thread::sleep(Duration::from_millis(50));
+                }
+            }
+        };
+        stream.write_all(b"JDWP-Handshake").unwrap();
+        let mut handshake = [0u8; 14];
+        stream.read_exact(&mut handshake).unwrap();
+        assert_eq!(&handshake, b"JDWP-Handshake");
+        Self { stream, next_id: 1 }
+    }
+
+    fn send(&mut self, command_set: u8, command: u8, data: &[u8]) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        let mut packet = Vec::with_capacity(11 + data.len());
+        packet.extend((11 + data.len() as u32).to_be_bytes());
+        packet.extend(id.to_be_bytes());
+        packet.extend([0, command_set, command]);
+        packet.extend(data);
+        self.stream.write_all(&packet).unwrap();
+        id
+    }
+
+    /// Sends a command and waits for its reply, skipping events. Returns the error code and
+    /// the data of the reply.
+    fn command(&mut self, command_set: u8, command: u8, data: &[u8]) -> (u16, Vec<u8>) {
+        let id = self.send(command_set, command, data);
+        loop {
+            let mut header = [0u8; 11];
+            self.stream.read_exact(&mut header).unwrap();
+            let length = u32::from_be_bytes(header[0..4].try_into().unwrap()) as usize;
+            let mut data = vec![0u8; length - 11];
+            self.stream.read_exact(&mut data).unwrap();
+            let is_reply = header[8] == 0x80;
+            if is_reply && u32::from_be_bytes(header[4..8].try_into().unwrap()) == id {
+                return (u16::from_be_bytes([header[9], header[10]]), data);
+            }
+        }
+    }
+
+    /// `VirtualMachine.AllClasses`, the reference type id of the class named `name`.
+    fn class_id(&mut self, name: &str) -> u32 {
+        let (error_code, data) = self.command(1, 3, &[]);
+        assert_eq!(error_code, 0);
+        let read_u32 = |at: usize| u32::from_be_bytes(data[at..at + 4].try_into().unwrap());
+        let mut at = 4;
+        for _ in 0..read_u32(0) {
+            let type_id = read_u32(at + 1);
+            let signature_len = read_u32(at + 5) as usize;
+            let signature = &data[at + 9..at + 9 + signature_len];
+            if signature == name.as_bytes() {
+                return type_id;
+            }
+            at += 9 + signature_len + 4;
+        }
+        panic!("{name} is not loaded");
+    }
+
+    /// `VirtualMachine.RedefineClasses` of one class, the error code of the reply.
+    fn redefine(&mut self, class_id: u32, class_file: &[u8]) -> u16 {
+        let mut data = Vec::new();
+        data.extend(1i32.to_be_bytes());
+        data.extend(class_id.to_be_bytes());
+        data.extend((class_file.len() as i32).to_be_bytes());
+        data.extend(class_file);
+        self.command(1, 18, &data).0
+    }
+}
+
+/// A tool of the JDK of `JAVA_HOME` like build.rs finds javac, the one on the path otherwise.
+fn jdk_tool(name: &str) -> std::ffi::OsString {
+    std::env::var("JAVA_HOME")
+        .map(|home| Path::new(&home).join("bin").join(name))
+        .ok()
+        .filter(|tool| tool.exists())
+        .map(|tool| tool.into_os_string())
+        .unwrap_or_else(|| name.into())
+}
+
+/// Packs `classes` of the compiled fixtures into `jar_path`, with a manifest of the given main
+/// attributes or with none.
+fn pack_jar(jar_path: &Path, manifest: Option<&str>, classes: &[&str]) {
+    let compiled = std::env::current_dir()
+        .unwrap()
+        .join("tests/testdata/compiled");
+    std::fs::create_dir_all(jar_path.parent().unwrap()).unwrap();
+    let mut cmd = std::process::Command::new(jdk_tool("jar"));
+    cmd.arg("--create").arg("--file").arg(jar_path);
+    match manifest {
+        Some(attributes) => {
+            let manifest_path = jar_path.with_extension("mf");
+            std::fs::write(&manifest_path, attributes).unwrap();
+            cmd.arg("--manifest").arg(manifest_path);
+        }
+        None => {
+            cmd.arg("--no-manifest");
+        }
+    }
+    for class in classes {
+        cmd.arg("-C").arg(&compiled).arg(format!("{class}.class"));
+    }
+    assert!(cmd.status().expect("Failed to run jar").success());
+}
+
+#[test]
+fn jar_runs_main_class_with_class_path_jars() {
+    // given
+    // requires cargo build
+    let dir = std::env::temp_dir().join(format!("{}-jar-class-path", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let app = dir.join("app.jar");
+    pack_jar(
+        &app,
+        Some("Main-Class: jar.launch.JarMain\nClass-Path: lib/greeting.jar\n"),
+        &["jar/launch/JarMain"],
+    );
+    pack_jar(
+        &dir.join("lib/greeting.jar"),
+        None,
+        &["jar/launch/lib/Greeting"],
+    );
+    let mut cmd = Command::cargo_bin("vm").unwrap();
+    cmd.arg("-jar").arg(&app);
+
+    // when
+    let output = cmd.assert().success().get_output().clone();
+    let _ = std::fs::remove_dir_all(&dir);
+
+    // then
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "JarMain from the Main-Class attribute\nGreeting from a Class-Path JAR\n"
+    );
+}
+
+#[test]
+fn jar_without_main_class_is_rejected() {
+    // given
+    // requires cargo build
+    let dir = std::env::temp_dir().join(format!("{}-jar-no-manifest", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let app = dir.join("app.jar");
+    pack_jar(&app, None, &["jar/launch/JarMain"]);
+    let mut cmd = Command::cargo_bin("vm").unwrap();
+    cmd.arg("-jar").arg(&app);
+
+    // when
+    let output = cmd.assert().code(1).get_output().clone();
+    let _ = std::fs::remove_dir_all(&dir);
+
+    // then
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        format!("Error: no main manifest attribute, in {}\n", app.display())
+    );
+}
+
+/// Compiles the modules of `tests/testdata/modules` into exploded modules under `out_dir`. The
+/// fixtures may use the internal package of `app.greeter`, the VM decides whether they can.
+fn compile_modules(out_dir: &Path) {
+    let status = std::process::Command::new(jdk_tool("javac"))
+        .arg("-g")
+        .arg("--module-source-path")
+        .arg("tests/testdata/modules")
+        .arg("--add-exports")
+        .arg("app.greeter/app.greeter.internal=app.main")
+        .arg("-d")
+        .arg(out_dir)
+        .arg("--module")
+        .arg("app.main,app.greeter")
+        .status()
+        .expect("Failed to run javac");
+    assert!(status.success());
+}
+
+#[rstest]
+#[case::package_not_exported(
+    &[],
+    "IllegalAccessError: class app.main.ModuleMain (in module app.main) cannot access class \
+     app.greeter.internal.Secret (in module app.greeter) because module app.greeter does not \
+     export app.greeter.internal to module app.main\n"
+)]
+#[case::package_added_to_exports(
+    &["--add-exports", "app.greeter/app.greeter.internal=app.main"],
+    "Secret of a package exported with --add-exports\n"
+)]
+fn module_main_class_runs_from_module_path(#[case] extra_args: &[&str], #[case] secret: &str) {
+    // given
+    // requires cargo build
+    let dir = std::env::temp_dir().join(format!(
+        "{}-modules-{}",
+        std::process::id(),
+        extra_args.len()
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    compile_modules(&dir);
+    let mut cmd = Command::cargo_bin("vm").unwrap();
+    cmd.arg("--module-path")
+        .arg(&dir)
+        .args(extra_args)
+        .arg("-m")
+        .arg("app.main/app.main.ModuleMain");
+
+    // when
+    let output = cmd.assert().success().get_output().clone();
+    let _ = std::fs::remove_dir_all(&dir);
+
+    // then
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!("Hello, app.main from module app.greeter\n{secret}")
+    );
+}
+
+/// Compiles `Greeter` of `tests/testdata/redefinitions/<version>` and reads its class file.
+fn compile_redefinition(version: &str, out_dir: &Path) -> Vec<u8> {
+    let out_dir = out_dir.join(version);
+    let status = std::process::Command::new(jdk_tool("javac"))
+        .arg("-g")
+        .arg("-d")
+        .arg(&out_dir)
+        .arg(format!(
+            "tests/testdata/redefinitions/{version}/jdwp/redefine/Greeter.java"
+        ))
+        .status()
+        .expect("Failed to run javac");
+    assert!(status.success());
+    std::fs::read(out_dir.join("jdwp/redefine/Greeter.class")).unwrap()
+}
+
+#[test]
+fn redefined_class_runs_new_bodies_while_old_frames_finish() {
+    // given
+    // requires cargo build
+    let current_dir = std::env::current_dir().expect("Cannot get current dir");
+    let class_path = current_dir.join("tests/testdata/compiled");
+    let work_dir = std::env::temp_dir().join(format!("{}-redefine", std::process::id()));
+    let _ = std::fs::remove_dir_all(&work_dir);
+    std::fs::create_dir_all(&work_dir).unwrap();
+    let body_swap = compile_redefinition("body_swap", &work_dir);
+    let schema_change = compile_redefinition("schema_change", &work_dir);
+    let port = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut vm = KillOnDrop(
+        std::process::Command::new(env!("CARGO_BIN_EXE_vm"))
+            .arg("--jdwp-port")
+            .arg(port.to_string())
+            .arg("-c")
+            .arg(&class_path)
+            .arg("jdwp/redefine/RedefineMain")
+            .current_dir(&work_dir)
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap(),
+    );
+    let mut stdout = BufReader::new(vm.0.stdout.take().unwrap()).lines();
+    let mut jdwp = JdwpClient::connect(port, &mut vm.0);
+    // VirtualMachine.Resume, the VM starts suspended
+    jdwp.send(1, 9, &[]);
+    stdout
+        .by_ref()
+        .map(Result::unwrap)
+        .find(|line| line == "greeter: run started")
+        .expect("Greeter.run didn't start");
+    let greeter = jdwp.class_id("jdwp/redefine/Greeter");
+
+    // when
+    let schema_change_error = jdwp.redefine(greeter, &schema_change);
+    let body_swap_error = jdwp.redefine(greeter, &body_swap);
+    std::fs::write(work_dir.join("redefined.flag"), b"").unwrap();
+    let output: Vec<String> = stdout
+        .map(Result::unwrap)
+        .filter(|line| line.starts_with("greeter: "))
+        .collect();
+    let status = vm.0.wait().unwrap();
+    let _ = std::fs::remove_dir_all(&work_dir);
+
+    // then
+    // SCHEMA_CHANGE_NOT_IMPLEMENTED, and the class is left as it was
+    assert_eq!(schema_change_error, 64);
+    assert_eq!(body_swap_error, 0);
+    assert!(status.success());
+    assert_eq!(
+        output,
+        [
+            "greeter: old run goes on",
+            "greeter: new greeting after 1 run",
+            "greeter: new greeting after 1 run",
+        ]
+    );
+}
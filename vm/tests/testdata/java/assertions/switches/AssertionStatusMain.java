@@ -0,0 +1,12 @@
+package assertions.switches;
+
+import assertions.switches.checked.Checked;
+
+/** Run by the assertion switches test with different -ea/-da/-esa switches. */
+public class AssertionStatusMain {
+    public static void main(String[] args) {
+        System.out.println("main: ".concat(String.valueOf(AssertionStatusMain.class.desiredAssertionStatus())));
+        System.out.println("checked: ".concat(String.valueOf(Checked.assertsRun())));
+        System.out.println("system: ".concat(String.valueOf(String.class.desiredAssertionStatus())));
+    }
+}
@@ -0,0 +1,13 @@
+package assertions.switches.checked;
+
+public class Checked {
+    /** Whether the assert statements of this class are executed. */
+    public static boolean assertsRun() {
+        try {
+            assert false;
+            return false;
+        } catch (AssertionError e) {
+            return true;
+        }
+    }
+}
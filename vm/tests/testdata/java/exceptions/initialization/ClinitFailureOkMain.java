@@ -0,0 +1,47 @@
+package exceptions.initialization.clinit_failure;
+
+public class ClinitFailureOkMain {
+    static class Faulty {
+        static int value = fail();
+
+        static int fail() {
+            throw new IllegalStateException("Exception from static initializer");
+        }
+    }
+
+    interface WithDefault {
+        Object INIT = log("WithDefault initialized");
+
+        default void method() {
+        }
+    }
+
+    interface WithoutDefault {
+        Object INIT = log("WithoutDefault initialized");
+    }
+
+    static class Impl implements WithoutDefault, WithDefault {
+        static {
+            log("Impl initialized");
+        }
+    }
+
+    static Object log(String message) {
+        System.out.println(message);
+        return message;
+    }
+
+    public static void main(String[] args) {
+        try {
+            System.out.println(Faulty.value);
+        } catch (ExceptionInInitializerError e) {
+            System.out.println(e.getCause());
+        }
+        try {
+            System.out.println(Faulty.value);
+        } catch (NoClassDefFoundError e) {
+            System.out.println(e.getMessage());
+        }
+        new Impl();
+    }
+}
@@ -0,0 +1,67 @@
+package gc.other_threads;
+
+import java.io.IOException;
+import java.io.InputStream;
+import java.lang.ref.WeakReference;
+import java.lang.reflect.Method;
+import support.DefiningLoader;
+
+/** The VM only collects while main is the only Java thread, as it can't walk other stacks. */
+public class CollectionWithOtherThreadsOkMain {
+    static final String PLUGIN = "gc.other_threads.Plugin";
+    static final Object LOCK = new Object();
+    static boolean released;
+
+    public static final class Token {
+        public final String name;
+
+        Token(String name) {
+            this.name = name;
+        }
+    }
+
+    static class Waiter extends Thread {
+        @Override
+        public void run() {
+            synchronized (LOCK) {
+                while (!released) {
+                    try {
+                        LOCK.wait();
+                    } catch (InterruptedException e) {
+                        throw new IllegalStateException(e);
+                    }
+                }
+            }
+        }
+    }
+
+    public static void main(String[] args) throws Exception {
+        byte[] bytes;
+        try (InputStream in = CollectionWithOtherThreadsOkMain.class.getResourceAsStream("Plugin.class")) {
+            bytes = in.readAllBytes();
+        }
+        Thread waiter = new Waiter();
+        waiter.start();
+
+        WeakReference<Class<?>> dropped = defineInNewLoader(bytes);
+        System.gc();
+        System.out.println("Unloaded while another thread is alive: ".concat(String.valueOf(dropped.get() == null)));
+
+        synchronized (LOCK) {
+            released = true;
+            LOCK.notifyAll();
+        }
+        waiter.join();
+        System.gc();
+        System.out.println("Unloaded once main is the only thread: ".concat(String.valueOf(dropped.get() == null)));
+
+        // the descriptor of describe was freed with the unloaded class and is parsed again
+        Class<?> defined = new DefiningLoader().define(PLUGIN, bytes);
+        Method describe = defined.getDeclaredMethod("describe", Token.class);
+        System.out.println(describe.invoke(null, new Token("a token")));
+    }
+
+    private static WeakReference<Class<?>> defineInNewLoader(byte[] bytes) {
+        return new WeakReference<>(new DefiningLoader().define(PLUGIN, bytes));
+    }
+}
@@ -0,0 +1,10 @@
+package gc.other_threads;
+
+import gc.other_threads.CollectionWithOtherThreadsOkMain.Token;
+
+public class Plugin {
+    // no class of the app loader has a method of this descriptor
+    public static String describe(Token token) {
+        return "Plugin describes ".concat(token.name);
+    }
+}
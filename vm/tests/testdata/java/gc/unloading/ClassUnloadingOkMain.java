@@ -0,0 +1,37 @@
+package gc.unloading.class_unloading;
+
+import java.io.IOException;
+import java.io.InputStream;
+import java.lang.ref.WeakReference;
+import support.DefiningLoader;
+
+public class ClassUnloadingOkMain {
+    public static void main(String[] args) throws IOException {
+        byte[] bytes;
+        try (InputStream in = ClassUnloadingOkMain.class.getResourceAsStream("Plugin.class")) {
+            bytes = in.readAllBytes();
+        }
+
+        // nothing but the weak reference refers to the class or its loader
+        WeakReference<Class<?>> dropped = defineInNewLoader(bytes);
+        System.out.println("Defined ".concat(dropped.get().getName()));
+        System.gc();
+        System.out.println("Unloaded: ".concat(String.valueOf(dropped.get() == null)));
+
+        DefiningLoader loader = new DefiningLoader();
+        Class<?> kept = loader.define(Plugin.class.getName(), bytes);
+        WeakReference<Class<?>> keptRef = new WeakReference<>(kept);
+        System.gc();
+        System.out.println("Kept: ".concat(String.valueOf(keptRef.get() == kept)));
+        System.out.println(kept.getName().concat(" defined by its loader: ").concat(String.valueOf(kept.getClassLoader() == loader)));
+        System.out.println("Same as the app class: ".concat(String.valueOf(kept == Plugin.class)));
+    }
+
+    private static WeakReference<Class<?>> defineInNewLoader(byte[] bytes) {
+        return new WeakReference<>(new DefiningLoader().define(Plugin.class.getName(), bytes));
+    }
+}
+
+class Plugin {
+    static Object state = new Object();
+}
@@ -0,0 +1,187 @@
+package invoke.dynamic_constants.bootstraps;
+
+import java.io.IOException;
+import java.lang.invoke.MethodHandles;
+import java.lang.invoke.VarHandle;
+import java.lang.reflect.InvocationTargetException;
+import java.lang.reflect.Method;
+import support.ClassAssembler;
+
+import static support.ClassAssembler.ACC_FINAL;
+import static support.ClassAssembler.ACC_PUBLIC;
+import static support.ClassAssembler.ACC_STATIC;
+import static support.ClassAssembler.ACC_SUPER;
+
+// javac doesn't emit CONSTANT_Dynamic for this code, so the class loading them is assembled here
+public class DynamicConstantsOkMain {
+    static final String LOOKUP = "Ljava/lang/invoke/MethodHandles$Lookup;";
+    static final String BOOTSTRAPS = "java/lang/invoke/ConstantBootstraps";
+
+    static int bootstrapCalls;
+
+    static Object counted(MethodHandles.Lookup lookup, String name, Class<?> type) {
+        bootstrapCalls++;
+        return "constant ".concat(name).concat(" of ").concat(lookup.lookupClass().getSimpleName());
+    }
+
+    static Object failing(MethodHandles.Lookup lookup, String name, Class<?> type) {
+        bootstrapCalls++;
+        throw new IllegalStateException(name);
+    }
+
+    static void loadingMethod(ClassAssembler assembler, String name, String descriptor,
+                              int constant) throws IOException {
+        char kind = descriptor.charAt(2);
+        boolean wide = kind == 'J' || kind == 'D';
+        int returnOpcode;
+        if (kind == 'J') {
+            returnOpcode = 0xAD;
+        } else if (kind == 'L' || kind == '[') {
+            returnOpcode = 0xB0;
+        } else {
+            returnOpcode = 0xAC;
+        }
+        assembler.method(name, descriptor, ACC_PUBLIC | ACC_STATIC, 2, 0, new byte[]{
+                (byte) (wide ? 0x14 : 0x13), (byte) (constant >> 8), (byte) constant,
+                (byte) returnOpcode});
+    }
+
+    static String bootstrapDescriptor(String extra, String result) {
+        return "(".concat(LOOKUP).concat("Ljava/lang/String;Ljava/lang/Class;").concat(extra)
+                .concat(")").concat(result);
+    }
+
+    static byte[] assemble() throws IOException {
+        String self = DynamicConstantsOkMain.class.getName().replace('.', '/');
+        ClassAssembler condy = new ClassAssembler(
+                self.substring(0, self.lastIndexOf('/') + 1).concat("Condy"), "java/lang/Object");
+        condy.flags = ACC_PUBLIC | ACC_FINAL | ACC_SUPER;
+        condy.version = 55;
+
+        int nullConstant = condy.dynamic(condy.bootstrap(condy.staticMethodHandle(BOOTSTRAPS,
+                "nullConstant", bootstrapDescriptor("", "Ljava/lang/Object;"))),
+                "_", "Ljava/lang/Object;");
+        loadingMethod(condy, "nothing", "()Ljava/lang/Object;", nullConstant);
+
+        int primitiveClass = condy.dynamic(condy.bootstrap(condy.staticMethodHandle(BOOTSTRAPS,
+                "primitiveClass", bootstrapDescriptor("", "Ljava/lang/Class;"))),
+                "J", "Ljava/lang/Class;");
+        loadingMethod(condy, "longClass", "()Ljava/lang/Class;", primitiveClass);
+
+        int enumConstant = condy.dynamic(condy.bootstrap(condy.staticMethodHandle(BOOTSTRAPS,
+                "enumConstant", bootstrapDescriptor("", "Ljava/lang/Enum;"))),
+                "SECONDS", "Ljava/util/concurrent/TimeUnit;");
+        loadingMethod(condy, "seconds", "()Ljava/util/concurrent/TimeUnit;", enumConstant);
+
+        int getStaticFinal = condy.staticMethodHandle(BOOTSTRAPS, "getStaticFinal",
+                bootstrapDescriptor("Ljava/lang/Class;", "Ljava/lang/Object;"));
+        int maxInt = condy.dynamic(condy.bootstrap(getStaticFinal,
+                condy.classRef("java/lang/Integer")), "MAX_VALUE", "I");
+        loadingMethod(condy, "maxInt", "()I", maxInt);
+        int minLong = condy.dynamic(condy.bootstrap(getStaticFinal,
+                condy.classRef("java/lang/Long")), "MIN_VALUE", "J");
+        loadingMethod(condy, "minLong", "()J", minLong);
+
+        int invoke = condy.staticMethodHandle(BOOTSTRAPS, "invoke", bootstrapDescriptor(
+                "Ljava/lang/invoke/MethodHandle;[Ljava/lang/Object;", "Ljava/lang/Object;"));
+        int valueOfInt = condy.staticMethodHandle("java/lang/String", "valueOf",
+                "(I)Ljava/lang/String;");
+        int valueOfObject = condy.staticMethodHandle("java/lang/String", "valueOf",
+                "(Ljava/lang/Object;)Ljava/lang/String;");
+        int answer = condy.dynamic(condy.bootstrap(invoke, valueOfInt, condy.integer(42)),
+                "_", "Ljava/lang/String;");
+        loadingMethod(condy, "answer", "()Ljava/lang/String;", answer);
+        int nested = condy.dynamic(condy.bootstrap(invoke, valueOfObject, maxInt),
+                "_", "Ljava/lang/String;");
+        loadingMethod(condy, "nested", "()Ljava/lang/String;", nested);
+
+        int explicitCast = condy.dynamic(condy.bootstrap(condy.staticMethodHandle(BOOTSTRAPS,
+                "explicitCast", bootstrapDescriptor("Ljava/lang/Object;", "Ljava/lang/Object;")),
+                condy.longValue(300L)), "_", "B");
+        loadingMethod(condy, "narrowed", "()B", explicitCast);
+        int flag = condy.dynamic(condy.bootstrap(condy.staticMethodHandle(BOOTSTRAPS,
+                "explicitCast", bootstrapDescriptor("Ljava/lang/Object;", "Ljava/lang/Object;")),
+                condy.integer(2)), "_", "Z");
+        loadingMethod(condy, "flag", "()Z", flag);
+
+        int arrayVarHandle = condy.dynamic(condy.bootstrap(condy.staticMethodHandle(BOOTSTRAPS,
+                "arrayVarHandle", bootstrapDescriptor("Ljava/lang/Class;",
+                        "Ljava/lang/invoke/VarHandle;")),
+                condy.classRef("[I")), "_", "Ljava/lang/invoke/VarHandle;");
+        loadingMethod(condy, "intsHandle", "()Ljava/lang/invoke/VarHandle;", arrayVarHandle);
+
+        int counted = condy.dynamic(condy.bootstrap(condy.staticMethodHandle(self, "counted",
+                bootstrapDescriptor("", "Ljava/lang/Object;"))), "first", "Ljava/lang/String;");
+        loadingMethod(condy, "counted", "()Ljava/lang/String;", counted);
+        loadingMethod(condy, "countedAgain", "()Ljava/lang/String;", counted);
+
+        int failing = condy.dynamic(condy.bootstrap(condy.staticMethodHandle(self, "failing",
+                bootstrapDescriptor("", "Ljava/lang/Object;"))), "broken", "Ljava/lang/String;");
+        loadingMethod(condy, "failing", "()Ljava/lang/String;", failing);
+        loadingMethod(condy, "failingAgain", "()Ljava/lang/String;", failing);
+
+        int cycle = condy.reserveDynamic("_", "Ljava/lang/String;");
+        condy.bootstrap(invoke, valueOfObject, cycle);
+        loadingMethod(condy, "cycle", "()Ljava/lang/String;", cycle);
+
+        return condy.toByteArray();
+    }
+
+    static Object call(Class<?> condy, String name) throws Throwable {
+        Method method = condy.getMethod(name);
+        try {
+            return method.invoke(null);
+        } catch (InvocationTargetException e) {
+            throw e.getCause();
+        }
+    }
+
+    public static void main(String[] args) throws Throwable {
+        Class<?> condy = MethodHandles.lookup().defineClass(assemble());
+        System.out.println(condy.getName());
+
+        System.out.println(String.valueOf(call(condy, "nothing")));
+        System.out.println(String.valueOf(call(condy, "longClass")));
+        System.out.println(String.valueOf(call(condy, "seconds")));
+        System.out.println(String.valueOf(call(condy, "maxInt")));
+        System.out.println(String.valueOf(call(condy, "minLong")));
+        System.out.println(String.valueOf(call(condy, "answer")));
+        System.out.println(String.valueOf(call(condy, "nested")));
+        System.out.println(String.valueOf(call(condy, "narrowed")));
+        System.out.println(String.valueOf(call(condy, "flag")));
+
+        VarHandle ints = (VarHandle) call(condy, "intsHandle");
+        int[] numbers = {1, 2, 3};
+        ints.set(numbers, 2, 30);
+        System.out.println(numbers[2]);
+        System.out.println(call(condy, "intsHandle") == ints);
+
+        System.out.println(String.valueOf(call(condy, "counted")));
+        System.out.println(String.valueOf(call(condy, "counted")));
+        System.out.println(String.valueOf(call(condy, "countedAgain")));
+        System.out.println(bootstrapCalls);
+
+        try {
+            call(condy, "failing");
+        } catch (BootstrapMethodError e) {
+            System.out.println(e.getClass().getName());
+            System.out.println(e.getCause().getClass().getName());
+            System.out.println(e.getCause().getMessage());
+        }
+        // a failed resolution is not retried, the bootstrap method isn't called again
+        for (String name : new String[]{"failing", "failingAgain"}) {
+            try {
+                call(condy, name);
+            } catch (BootstrapMethodError e) {
+                System.out.println("again ".concat(e.getClass().getName()));
+            }
+        }
+        System.out.println(bootstrapCalls);
+
+        try {
+            call(condy, "cycle");
+        } catch (StackOverflowError e) {
+            System.out.println("StackOverflowError");
+        }
+    }
+}
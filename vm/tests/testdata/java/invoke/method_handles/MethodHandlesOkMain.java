@@ -0,0 +1,107 @@
+package invoke.method_handles.basic;
+
+import java.lang.invoke.MethodHandle;
+import java.lang.invoke.MethodHandles;
+import java.lang.invoke.MethodType;
+import java.lang.invoke.WrongMethodTypeException;
+
+public class MethodHandlesOkMain {
+    interface Shape {
+        String name();
+    }
+
+    static class Square implements Shape {
+        public String name() {
+            return "square";
+        }
+    }
+
+    static class Circle implements Shape {
+        public String name() {
+            return "circle";
+        }
+    }
+
+    static class Lazy {
+        static {
+            System.out.println("Lazy initialized");
+        }
+
+        static long scale(int a, long b) {
+            return a * b;
+        }
+    }
+
+    private static String shout(String s) {
+        return s.toUpperCase();
+    }
+
+    private static void fail(String message) {
+        throw new IllegalStateException(message);
+    }
+
+    public static void main(String[] args) throws Throwable {
+        MethodHandles.Lookup lookup = MethodHandles.lookup();
+        System.out.println(lookup.lookupClass().getName());
+
+        MethodHandle concat = lookup.findVirtual(String.class, "concat",
+                MethodType.methodType(String.class, String.class));
+        System.out.println(concat.type().toString());
+        String joined = (String) concat.invokeExact("method", "handles");
+        System.out.println(joined);
+
+        MethodHandle max = lookup.findStatic(Math.class, "max",
+                MethodType.methodType(int.class, int.class, int.class));
+        int larger = (int) max.invokeExact(3, 11);
+        System.out.println(larger);
+
+        MethodHandle shout = lookup.findStatic(MethodHandlesOkMain.class, "shout",
+                MethodType.methodType(String.class, String.class));
+        System.out.println((String) shout.invokeExact("quiet"));
+
+        MethodHandle name = lookup.findVirtual(Shape.class, "name", MethodType.methodType(String.class));
+        Shape[] shapes = {new Square(), new Circle()};
+        for (Shape shape : shapes) {
+            System.out.println((String) name.invokeExact(shape));
+        }
+
+        System.out.println("before scale");
+        MethodHandle scale = lookup.findStatic(Lazy.class, "scale",
+                MethodType.methodType(long.class, int.class, long.class));
+        System.out.println("found scale");
+        long scaled = (long) scale.invokeExact(6, 7L);
+        System.out.println(scaled);
+
+        MethodHandle newBuilder = lookup.findConstructor(StringBuilder.class,
+                MethodType.methodType(void.class, String.class));
+        StringBuilder builder = (StringBuilder) newBuilder.invokeExact("built");
+        System.out.println(builder.append('!').toString());
+
+        Object boxed = max.invoke(Integer.valueOf(5), 2);
+        System.out.println(String.valueOf(boxed).concat(" ").concat(boxed.getClass().getName()));
+
+        MethodHandle greet = concat.bindTo("Hello, ");
+        System.out.println((String) greet.invokeExact("bound"));
+
+        try {
+            Object wrong = concat.invokeExact("a", "b");
+            System.out.println(wrong);
+        } catch (WrongMethodTypeException e) {
+            System.out.println("caught ".concat(e.getClass().getName()));
+        }
+
+        MethodHandle fail = lookup.findStatic(MethodHandlesOkMain.class, "fail",
+                MethodType.methodType(void.class, String.class));
+        try {
+            fail.invokeExact("boom");
+        } catch (IllegalStateException e) {
+            System.out.println("target threw ".concat(e.getMessage()));
+        }
+
+        try {
+            lookup.findVirtual(String.class, "missing", MethodType.methodType(void.class));
+        } catch (NoSuchMethodException e) {
+            System.out.println("caught ".concat(e.getClass().getName()));
+        }
+    }
+}
@@ -0,0 +1,197 @@
+package invoke.var_handles.basic;
+
+import java.lang.invoke.MethodHandles;
+import java.lang.invoke.VarHandle;
+import java.nio.ByteBuffer;
+import java.nio.ByteOrder;
+import java.util.concurrent.ConcurrentHashMap;
+import java.util.concurrent.atomic.AtomicInteger;
+import java.util.concurrent.atomic.AtomicLongArray;
+import java.util.concurrent.atomic.AtomicReference;
+
+public class VarHandlesOkMain {
+    static class Point {
+        int x;
+        volatile long stamp;
+        String label;
+        boolean visible;
+        double weight;
+    }
+
+    static class Counter {
+        static int count;
+        static String owner;
+        static short level;
+    }
+
+    static class Gauge {
+        static int total;
+        static byte step;
+        static char mark;
+        static boolean armed;
+    }
+
+    public static void main(String[] args) throws Throwable {
+        MethodHandles.Lookup lookup = MethodHandles.lookup();
+
+        VarHandle x = lookup.findVarHandle(Point.class, "x", int.class);
+        VarHandle stamp = lookup.findVarHandle(Point.class, "stamp", long.class);
+        VarHandle label = lookup.findVarHandle(Point.class, "label", String.class);
+        VarHandle visible = lookup.findVarHandle(Point.class, "visible", boolean.class);
+        VarHandle weight = lookup.findVarHandle(Point.class, "weight", double.class);
+        System.out.println(x.varType().getName());
+        System.out.println(String.valueOf(x.coordinateTypes()));
+
+        Point p = new Point();
+        x.set(p, 7);
+        System.out.println((int) x.get(p));
+        System.out.println(x.compareAndSet(p, 7, 9));
+        System.out.println(x.compareAndSet(p, 7, 11));
+        System.out.println(p.x);
+        System.out.println((int) x.getAndAdd(p, 5));
+        System.out.println((int) x.getAndSet(p, 1));
+        System.out.println((int) x.getAndBitwiseOr(p, 6));
+        System.out.println((int) x.compareAndExchange(p, 7, 3));
+        System.out.println((int) x.getVolatile(p));
+
+        stamp.setVolatile(p, 1L << 40);
+        System.out.println((long) stamp.getAndAdd(p, 1L));
+        System.out.println((long) stamp.getAcquire(p));
+        stamp.setRelease(p, -2L);
+        System.out.println(p.stamp);
+
+        System.out.println(label.compareAndSet(p, null, "origin"));
+        System.out.println((String) label.getAndSet(p, "moved"));
+        System.out.println((String) label.get(p));
+        System.out.println(label.compareAndSet(p, "origin", "lost"));
+
+        visible.set(p, true);
+        System.out.println((boolean) visible.get(p));
+        System.out.println((boolean) visible.getAndSet(p, false));
+        System.out.println(p.visible);
+
+        weight.set(p, 1.5);
+        System.out.println((double) weight.getAndAdd(p, 2.25));
+        System.out.println(weight.compareAndSet(p, 3.75, -0.5));
+        System.out.println(p.weight);
+
+        try {
+            label.set(p, Integer.valueOf(1));
+        } catch (ClassCastException e) {
+            System.out.println("ClassCastException");
+        }
+
+        VarHandle count = lookup.findStaticVarHandle(Counter.class, "count", int.class);
+        VarHandle owner = lookup.findStaticVarHandle(Counter.class, "owner", String.class);
+        VarHandle level = lookup.findStaticVarHandle(Counter.class, "level", short.class);
+        count.set(40);
+        System.out.println((int) count.getAndAdd(2));
+        System.out.println(Counter.count);
+        System.out.println(count.compareAndSet(42, 43));
+        System.out.println((int) count.getVolatile());
+        owner.setVolatile("main");
+        System.out.println((String) owner.compareAndExchange("main", "worker"));
+        System.out.println(Counter.owner);
+        level.set((short) 70000);
+        System.out.println(Counter.level);
+        System.out.println((short) level.getAndAdd((short) 1));
+        System.out.println((short) level.get());
+
+        // the sub-word statics sit in the word of total, which their updates must leave alone
+        VarHandle total = lookup.findStaticVarHandle(Gauge.class, "total", int.class);
+        VarHandle step = lookup.findStaticVarHandle(Gauge.class, "step", byte.class);
+        VarHandle mark = lookup.findStaticVarHandle(Gauge.class, "mark", char.class);
+        VarHandle armed = lookup.findStaticVarHandle(Gauge.class, "armed", boolean.class);
+        total.set(-1);
+        step.set((byte) 5);
+        System.out.println((byte) step.compareAndExchange((byte) 5, (byte) 6));
+        System.out.println(step.weakCompareAndSet((byte) 6, (byte) 7));
+        System.out.println((byte) step.getAndAdd((byte) 121));
+        System.out.println(Gauge.step);
+        mark.set('a');
+        System.out.println((char) mark.getAndAdd((char) 1));
+        System.out.println((char) mark.compareAndExchange('b', 'z'));
+        System.out.println(Gauge.mark);
+        System.out.println(armed.compareAndSet(false, true));
+        System.out.println((boolean) armed.compareAndExchange(true, false));
+        System.out.println(armed.weakCompareAndSet(true, true));
+        System.out.println(Gauge.total);
+
+        VarHandle ints = MethodHandles.arrayElementVarHandle(int[].class);
+        int[] numbers = {1, 2, 3};
+        ints.set(numbers, 1, 20);
+        System.out.println((int) ints.getAndAdd(numbers, 2, 30));
+        System.out.println(ints.compareAndSet(numbers, 0, 1, 10));
+        System.out.println(String.valueOf(numbers[0]).concat(",")
+                .concat(String.valueOf(numbers[1])).concat(",")
+                .concat(String.valueOf(numbers[2])));
+        try {
+            ints.get(numbers, 3);
+        } catch (ArrayIndexOutOfBoundsException e) {
+            System.out.println(e.getMessage());
+        }
+
+        VarHandle strings = MethodHandles.arrayElementVarHandle(String[].class);
+        String[] words = new String[2];
+        System.out.println(strings.compareAndSet(words, 1, null, "b"));
+        System.out.println((String) strings.getAndSet(words, 0, "a"));
+        System.out.println(words[0].concat(words[1]));
+
+        VarHandle chars = MethodHandles.arrayElementVarHandle(char[].class);
+        char[] letters = {'a', 'b'};
+        chars.setVolatile(letters, 0, 'z');
+        System.out.println((char) chars.get(letters, 0));
+
+        VarHandle matrix = MethodHandles.arrayElementVarHandle(int[][].class);
+        int[][] rows = new int[2][];
+        matrix.set(rows, 1, new int[] {5});
+        System.out.println(((int[]) matrix.get(rows, 1))[0]);
+
+        VarHandle littleInts = MethodHandles.byteArrayViewVarHandle(int[].class,
+                ByteOrder.LITTLE_ENDIAN);
+        VarHandle bigLongs = MethodHandles.byteArrayViewVarHandle(long[].class,
+                ByteOrder.BIG_ENDIAN);
+        byte[] bytes = new byte[16];
+        littleInts.set(bytes, 0, 0x01020304);
+        System.out.println(bytes[0]);
+        System.out.println(bytes[3]);
+        littleInts.set(bytes, 5, -1);
+        System.out.println((int) littleInts.get(bytes, 4));
+        bigLongs.set(bytes, 8, 0x1122334455667788L);
+        System.out.println(bytes[8]);
+        System.out.println(Long.toHexString((long) bigLongs.get(bytes, 8)));
+        System.out.println(littleInts.compareAndSet(bytes, 0, 0x01020304, 42));
+        System.out.println(bytes[0]);
+
+        ByteBuffer buffer = ByteBuffer.allocate(16);
+        VarHandle bufferInts = MethodHandles.byteBufferViewVarHandle(int[].class,
+                ByteOrder.BIG_ENDIAN);
+        bufferInts.set(buffer, 4, 0xCAFE);
+        System.out.println(buffer.getInt(4));
+        System.out.println((int) bufferInts.getAndAdd(buffer, 4, 1));
+        System.out.println(Integer.toHexString((int) bufferInts.get(buffer, 4)));
+        buffer.order(ByteOrder.LITTLE_ENDIAN).putShort(0, (short) 0x1234);
+        System.out.println(buffer.get(0));
+
+        AtomicInteger atomicInteger = new AtomicInteger(5);
+        System.out.println(atomicInteger.incrementAndGet());
+        System.out.println(atomicInteger.compareAndSet(6, 60));
+        System.out.println(atomicInteger.get());
+        AtomicReference<String> atomicReference = new AtomicReference<String>("first");
+        System.out.println(atomicReference.getAndSet("second"));
+        System.out.println(atomicReference.compareAndSet("second", "third"));
+        System.out.println(atomicReference.get());
+        AtomicLongArray atomicLongs = new AtomicLongArray(3);
+        atomicLongs.addAndGet(2, 9L);
+        System.out.println(atomicLongs.toString());
+
+        ConcurrentHashMap<String, Integer> map = new ConcurrentHashMap<String, Integer>();
+        map.put("one", 1);
+        map.putIfAbsent("two", 2);
+        map.putIfAbsent("one", 100);
+        map.replace("two", 2, 22);
+        System.out.println(map.get("one"));
+        System.out.println(map.get("two"));
+        System.out.println(map.size());
+    }
+}
@@ -0,0 +1,11 @@
+package jar.launch;
+
+import jar.launch.lib.Greeting;
+
+/** Packed into a JAR by the -jar tests, Greeting goes into a JAR of its Class-Path. */
+public class JarMain {
+    public static void main(String[] args) {
+        System.out.println("JarMain from the Main-Class attribute");
+        System.out.println(Greeting.greet());
+    }
+}
@@ -0,0 +1,7 @@
+package jar.launch.lib;
+
+public class Greeting {
+    public static String greet() {
+        return "Greeting from a Class-Path JAR";
+    }
+}
@@ -0,0 +1,22 @@
+package jdwp.redefine;
+
+import java.io.File;
+
+public class Greeter {
+    private static int calls;
+
+    public static String greeting() {
+        return "greeter: old greeting";
+    }
+
+    /** Waits for the debugger to create the flag file, the class is redefined meanwhile. */
+    public static void run() {
+        calls++;
+        System.out.println("greeter: run started");
+        File flag = new File("redefined.flag");
+        while (!flag.exists()) {
+        }
+        System.out.println("greeter: old run goes on");
+        System.out.println(greeting());
+    }
+}
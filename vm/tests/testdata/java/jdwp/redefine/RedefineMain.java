@@ -0,0 +1,10 @@
+package jdwp.redefine;
+
+/** Run by the RedefineClasses test while a debugger redefines {@link Greeter}. */
+public class RedefineMain {
+    public static void main(String[] args) {
+        Greeter.run();
+        System.gc();
+        System.out.println(Greeter.greeting());
+    }
+}
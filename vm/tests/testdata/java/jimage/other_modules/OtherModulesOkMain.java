@@ -0,0 +1,26 @@
+package jimage.other_modules;
+
+import com.sun.net.httpserver.Headers;
+import java.net.http.HttpClient;
+import java.sql.JDBCType;
+import java.util.logging.Level;
+
+// classes of runtime image modules other than java.base
+public class OtherModulesOkMain {
+    public static void main(String[] args) {
+        // java.logging
+        System.out.println(Level.parse("WARNING").intValue());
+        System.out.println(Level.FINE.getName());
+
+        // java.sql
+        System.out.println(JDBCType.valueOf(4).getName());
+
+        // java.net.http
+        System.out.println(HttpClient.Version.HTTP_2.name());
+
+        // jdk.httpserver
+        Headers headers = new Headers();
+        headers.add("content-type", "text/plain");
+        System.out.println(headers.getFirst("Content-Type"));
+    }
+}
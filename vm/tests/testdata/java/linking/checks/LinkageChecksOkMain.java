@@ -0,0 +1,77 @@
+package linking.checks.linkage_checks;
+
+import java.io.IOException;
+import support.ClassAssembler;
+import support.DefiningLoader;
+
+import static support.ClassAssembler.ACC_ABSTRACT;
+import static support.ClassAssembler.ACC_FINAL;
+import static support.ClassAssembler.ACC_PUBLIC;
+import static support.ClassAssembler.ACC_SUPER;
+
+public class LinkageChecksOkMain {
+    private static final String PACKAGE = "linking/checks/linkage_checks/";
+
+    public static class Base {
+        public void take(Shared shared) {
+        }
+    }
+
+    public static void main(String[] args) throws IOException {
+        ClassAssembler duplicate = assembler("DuplicateMethod", "java/lang/Object");
+        duplicate.method("m", "()V", ACC_PUBLIC, 1, 2, new byte[]{(byte) 0xb1});
+        duplicate.method("m", "()V", ACC_PUBLIC, 1, 2, new byte[]{(byte) 0xb1});
+        define(new DefiningLoader(), duplicate);
+
+        ClassAssembler finalAbstract = assembler("FinalAbstract", "java/lang/Object");
+        finalAbstract.flags = ACC_PUBLIC | ACC_SUPER | ACC_FINAL | ACC_ABSTRACT;
+        define(new DefiningLoader(), finalAbstract);
+
+        ClassAssembler badField = assembler("BadField", "java/lang/Object");
+        badField.field("f", "Q", ACC_PUBLIC);
+        define(new DefiningLoader(), badField);
+
+        ClassAssembler noCode = assembler("NoCode", "java/lang/Object");
+        noCode.method("m", "()V", ACC_PUBLIC, 0, 0, null);
+        define(new DefiningLoader(), noCode);
+
+        define(new DefiningLoader(), assembler("ExtendsString", "java/lang/String"));
+        define(new DefiningLoader(), assembler("ExtendsRunnable", "java/lang/Runnable"));
+
+        ClassAssembler implementsObject = assembler("ImplementsObject", "java/lang/Object");
+        implementsObject.implement("java/lang/Object");
+        define(new DefiningLoader(), implementsObject);
+
+        // aconst_null; areturn
+        ClassAssembler overridesFinal = assembler("OverridesFinal", "java/lang/Object");
+        overridesFinal.method("getClass", "()Ljava/lang/Class;", ACC_PUBLIC, 1, 2,
+                new byte[]{0x01, (byte) 0xb0});
+        define(new DefiningLoader(), overridesFinal);
+
+        // the loader has its own Shared, the overridden Base.take(Shared) uses the one of the app loader
+        System.out.println(Shared.class.getName());
+        DefiningLoader loader = new DefiningLoader();
+        define(loader, assembler("Shared", "java/lang/Object"));
+        ClassAssembler sub = assembler("Sub", PACKAGE.concat("LinkageChecksOkMain$Base"));
+        sub.method("take", "(L".concat(PACKAGE).concat("Shared;)V"), ACC_PUBLIC, 1, 2,
+                new byte[]{(byte) 0xb1});
+        define(loader, sub);
+    }
+
+    private static ClassAssembler assembler(String simpleName, String superName) throws IOException {
+        return new ClassAssembler(PACKAGE.concat(simpleName), superName);
+    }
+
+    private static void define(DefiningLoader loader, ClassAssembler assembler) throws IOException {
+        try {
+            Class<?> c = loader.define(assembler.binaryName(), assembler.toByteArray());
+            System.out.println("Defined ".concat(c.getName()));
+        } catch (LinkageError e) {
+            String message = e.getMessage().replaceAll("@[0-9a-f]+", "@<loader>");
+            System.out.println(e.getClass().getName().concat(": ").concat(message));
+        }
+    }
+}
+
+class Shared {
+}
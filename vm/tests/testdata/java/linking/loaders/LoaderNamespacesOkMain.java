@@ -0,0 +1,67 @@
+package linking.loaders.namespaces;
+
+import java.io.IOException;
+import java.io.InputStream;
+
+public class LoaderNamespacesOkMain {
+    private static final String GREETER = "linking.loaders.namespaces.Greeter";
+    private static final String LAZY = "linking.loaders.namespaces.Lazy";
+
+    public static void main(String[] args) throws Exception {
+        byte[] bytes;
+        try (InputStream in = LoaderNamespacesOkMain.class.getResourceAsStream("Greeter.class")) {
+            bytes = in.readAllBytes();
+        }
+
+        IsolatingLoader first = new IsolatingLoader();
+        IsolatingLoader second = new IsolatingLoader();
+        System.out.println("Loaded before definition: ".concat(String.valueOf(first.loaded(GREETER) != null)));
+
+        Class<?> firstGreeter = first.define(GREETER, bytes);
+        Class<?> secondGreeter = second.define(GREETER, bytes);
+        System.out.println("Same class in both loaders: ".concat(String.valueOf(firstGreeter == secondGreeter)));
+        System.out.println("First finds its own: ".concat(String.valueOf(first.loaded(GREETER) == firstGreeter)));
+        System.out.println("Second finds its own: ".concat(String.valueOf(second.loaded(GREETER) == secondGreeter)));
+        System.out.println("Defined by: ".concat(String.valueOf(firstGreeter.getClassLoader() == first)));
+        System.out.println("Name: ".concat(secondGreeter.getName()));
+
+        // asking a loader doesn't load a class into it, delegation doesn't make it an initiating loader
+        System.out.println("Lazy found before loading: ".concat(String.valueOf(first.loaded(LAZY) != null)));
+        Class<?> lazy = first.loadClass(LAZY);
+        System.out.println("Lazy defined by the app loader: "
+                .concat(String.valueOf(lazy.getClassLoader() == ClassLoader.getSystemClassLoader())));
+        System.out.println("Lazy found after delegation: ".concat(String.valueOf(first.loaded(LAZY) != null)));
+        System.out.println("App loader sees the custom Greeter: ".concat(String.valueOf(Greeter.class == firstGreeter)));
+
+        try {
+            first.define(GREETER, bytes);
+            System.out.println("Duplicate definition accepted");
+        } catch (LinkageError e) {
+            System.out.println("Duplicate definition: ".concat(e.getClass().getName()));
+        }
+
+        try {
+            new IsolatingLoader().define("linking.loaders.namespaces.Other", bytes);
+            System.out.println("Wrong name accepted");
+        } catch (NoClassDefFoundError e) {
+            System.out.println("Wrong name: ".concat(e.getMessage()));
+        }
+    }
+
+    private static class IsolatingLoader extends ClassLoader {
+        Class<?> define(String name, byte[] bytes) {
+            return defineClass(name, bytes, 0, bytes.length);
+        }
+
+        Class<?> loaded(String name) {
+            return findLoadedClass(name);
+        }
+    }
+}
+
+class Greeter {
+    static int greetings;
+}
+
+class Lazy {
+}
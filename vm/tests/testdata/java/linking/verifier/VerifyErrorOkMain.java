@@ -0,0 +1,141 @@
+package linking.verifier.verify_error;
+
+import java.io.IOException;
+import java.io.InputStream;
+import support.ClassAssembler;
+import support.DefiningLoader;
+
+import static support.ClassAssembler.ACC_PUBLIC;
+import static support.ClassAssembler.ACC_STATIC;
+
+public class VerifyErrorOkMain {
+    private static final String PACKAGE = "linking/verifier/verify_error/";
+    private static final String GUARDED = "linking/verifier/guarded/Guarded";
+
+    public static void main(String[] args) throws Exception {
+        // aload_0; invokespecial Object.<init>()V; return
+        ClassAssembler valid = new ClassAssembler(PACKAGE.concat("Valid"), "java/lang/Object");
+        int init = valid.methodRef("java/lang/Object", "<init>", "()V");
+        valid.method("<init>", "()V", ACC_PUBLIC, 1, 1,
+                new byte[]{0x2a, (byte) 0xb7, (byte) (init >> 8), (byte) init, (byte) 0xb1});
+        define(valid);
+        // aconst_null; ireturn
+        define("ReturnsNull", "get", "()I", ACC_PUBLIC | ACC_STATIC, 1, 0,
+                new byte[]{0x01, (byte) 0xac});
+        // iconst_0; ifeq +3; return, without a stack map frame at the branch target
+        define("MissingFrame", "run", "()V", ACC_PUBLIC | ACC_STATIC, 1, 0,
+                new byte[]{0x03, (byte) 0x99, 0, 3, (byte) 0xb1});
+        // return, without calling super()
+        define("NoSuperCall", "<init>", "()V", ACC_PUBLIC, 0, 1,
+                new byte[]{(byte) 0xb1});
+        // iconst_1; iconst_2; iadd; ireturn, one stack slot too few
+        define("StackOverflow", "sum", "()I", ACC_PUBLIC | ACC_STATIC, 1, 0,
+                new byte[]{0x04, 0x05, 0x60, (byte) 0xac});
+
+        // Holder returns a Derived as a Base before its loader has loaded either of them
+        byte[] holder;
+        try (InputStream in = VerifyErrorOkMain.class.getResourceAsStream("Holder.class")) {
+            holder = in.readAllBytes();
+        }
+        DefiningLoader loader = new DefiningLoader();
+        Class<?> c = loader.define(VerifyErrorOkMain.class.getPackageName().concat(".Holder"), holder);
+        Class.forName(c.getName(), true, loader);
+        System.out.println("Initialized ".concat(c.getName()));
+
+        // protected members of a superclass in another package only on objects of the subclass
+        ClassAssembler peekField = new ClassAssembler(PACKAGE.concat("PeekField"), GUARDED);
+        int secret = peekField.fieldRef(GUARDED, "secret", "I");
+        // aload_0; getfield Guarded.secret; ireturn
+        peekField.method("peek", "(L".concat(GUARDED).concat(";)I"), ACC_PUBLIC | ACC_STATIC, 1, 1,
+                new byte[]{0x2a, (byte) 0xb4, (byte) (secret >> 8), (byte) secret, (byte) 0xac});
+        define(peekField);
+        ClassAssembler pokeField = new ClassAssembler(PACKAGE.concat("PokeField"), GUARDED);
+        secret = pokeField.fieldRef(GUARDED, "secret", "I");
+        // aload_0; iconst_1; putfield Guarded.secret; return
+        pokeField.method("poke", "(L".concat(GUARDED).concat(";)V"), ACC_PUBLIC | ACC_STATIC, 2, 1,
+                new byte[]{0x2a, 0x04, (byte) 0xb5, (byte) (secret >> 8), (byte) secret, (byte) 0xb1});
+        define(pokeField);
+        ClassAssembler touchOther = new ClassAssembler(PACKAGE.concat("TouchOther"), GUARDED);
+        int touch = touchOther.methodRef(GUARDED, "touch", "()V");
+        // aload_0; invokevirtual Guarded.touch()V; return
+        touchOther.method("touch", "(L".concat(GUARDED).concat(";)V"), ACC_PUBLIC | ACC_STATIC, 1, 1,
+                new byte[]{0x2a, (byte) 0xb6, (byte) (touch >> 8), (byte) touch, (byte) 0xb1});
+        define(touchOther);
+        ClassAssembler newGuarded = new ClassAssembler(PACKAGE.concat("NewGuarded"), GUARDED);
+        int guardedClass = newGuarded.classRef(GUARDED);
+        int guardedInit = newGuarded.methodRef(GUARDED, "<init>", "()V");
+        // new Guarded; dup; invokespecial Guarded.<init>()V; pop; return
+        newGuarded.method("make", "()V", ACC_PUBLIC | ACC_STATIC, 2, 0,
+                new byte[]{(byte) 0xbb, (byte) (guardedClass >> 8), (byte) guardedClass, 0x59,
+                        (byte) 0xb7, (byte) (guardedInit >> 8), (byte) guardedInit, 0x57, (byte) 0xb1});
+        define(newGuarded);
+        ClassAssembler peekOwn = new ClassAssembler(PACKAGE.concat("PeekOwn"), GUARDED);
+        secret = peekOwn.fieldRef(GUARDED, "secret", "I");
+        int open = peekOwn.fieldRef(GUARDED, "open", "I");
+        // aload_0; getfield Guarded.secret; ireturn, on this
+        peekOwn.method("peek", "()I", ACC_PUBLIC, 1, 1,
+                new byte[]{0x2a, (byte) 0xb4, (byte) (secret >> 8), (byte) secret, (byte) 0xac});
+        // aload_0; getfield Guarded.open; ireturn, a public field on any Guarded
+        peekOwn.method("peekOpen", "(L".concat(GUARDED).concat(";)I"), ACC_PUBLIC | ACC_STATIC, 1, 1,
+                new byte[]{0x2a, (byte) 0xb4, (byte) (open >> 8), (byte) open, (byte) 0xac});
+        define(peekOwn);
+
+        // new Unrelated; dup; invokespecial Unrelated.<init>()V; areturn, as a Base
+        ClassAssembler badHolder = new ClassAssembler(PACKAGE.concat("BadHolder"), "java/lang/Object");
+        String unrelated = PACKAGE.concat("VerifyErrorOkMain$Unrelated");
+        int unrelatedClass = badHolder.classRef(unrelated);
+        int unrelatedInit = badHolder.methodRef(unrelated, "<init>", "()V");
+        badHolder.method("make", "()L".concat(PACKAGE).concat("VerifyErrorOkMain$Base;"),
+                ACC_PUBLIC | ACC_STATIC, 2, 0,
+                new byte[]{(byte) 0xbb, (byte) (unrelatedClass >> 8), (byte) unrelatedClass, 0x59,
+                        (byte) 0xb7, (byte) (unrelatedInit >> 8), (byte) unrelatedInit, (byte) 0xb0});
+        // the loader loads neither class before the definition, they are checked when it links
+        DefiningLoader badLoader = new DefiningLoader();
+        try {
+            badLoader.define(badHolder.binaryName(), badHolder.toByteArray());
+            System.out.println("Defined ".concat(badHolder.binaryName()));
+        } catch (VerifyError e) {
+            System.out.println("On definition: ".concat(e.getMessage()));
+        }
+        try {
+            Class.forName(badHolder.binaryName(), true, badLoader);
+            System.out.println("Initialized ".concat(badHolder.binaryName()));
+        } catch (VerifyError e) {
+            System.out.println("On initialization: ".concat(e.getMessage()));
+        }
+    }
+
+    /** A class extending Object with the one method. */
+    private static void define(String simpleName, String methodName, String descriptor, int flags,
+                               int maxStack, int maxLocals, byte[] code) throws IOException {
+        ClassAssembler assembler = new ClassAssembler(PACKAGE.concat(simpleName), "java/lang/Object");
+        assembler.method(methodName, descriptor, flags, maxStack, maxLocals, code);
+        define(assembler);
+    }
+
+    private static void define(ClassAssembler assembler) throws IOException {
+        try {
+            Class<?> c = new DefiningLoader().define(assembler.binaryName(), assembler.toByteArray());
+            System.out.println("Defined ".concat(c.getName()));
+        } catch (VerifyError e) {
+            System.out.println(e.getClass().getName().concat(": ").concat(e.getMessage()));
+        }
+    }
+
+    public static class Base {
+    }
+
+    public static class Derived extends Base {
+    }
+
+    public static class Unrelated {
+    }
+}
+
+class Holder {
+    static VerifyErrorOkMain.Base made = make();
+
+    static VerifyErrorOkMain.Base make() {
+        return new VerifyErrorOkMain.Derived();
+    }
+}
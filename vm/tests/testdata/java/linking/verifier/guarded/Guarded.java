@@ -0,0 +1,13 @@
+package linking.verifier.guarded;
+
+/** Protected members for the subclasses the verifier fixture assembles in another package. */
+public class Guarded {
+    protected int secret;
+    public int open;
+
+    protected Guarded() {
+    }
+
+    protected void touch() {
+    }
+}
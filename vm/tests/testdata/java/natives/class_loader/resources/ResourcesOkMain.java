@@ -0,0 +1,30 @@
+package natives.class_loader.resources.basic;
+
+import java.io.IOException;
+import java.io.InputStream;
+import java.net.URL;
+
+public class ResourcesOkMain {
+    public static void main(String[] args) throws IOException {
+        URL own = ResourcesOkMain.class.getResource("ResourcesOkMain.class");
+        System.out.println(own.getProtocol());
+        try (InputStream in = ResourcesOkMain.class.getResourceAsStream("ResourcesOkMain.class")) {
+            printMagic(in);
+        }
+
+        URL object = ClassLoader.getSystemResource("java/lang/Object.class");
+        System.out.println(object);
+        try (InputStream in = object.openStream()) {
+            printMagic(in);
+        }
+
+        System.out.println(ResourcesOkMain.class.getResource("Missing.txt"));
+    }
+
+    private static void printMagic(InputStream in) throws IOException {
+        byte[] magic = in.readNBytes(4);
+        int value = ((magic[0] & 0xff) << 24) | ((magic[1] & 0xff) << 16)
+                | ((magic[2] & 0xff) << 8) | (magic[3] & 0xff);
+        System.out.println(Integer.toHexString(value));
+    }
+}
@@ -0,0 +1,199 @@
+package reflection.annotations.basic;
+
+import java.lang.annotation.Annotation;
+import java.lang.annotation.ElementType;
+import java.lang.annotation.Inherited;
+import java.lang.annotation.Retention;
+import java.lang.annotation.RetentionPolicy;
+import java.lang.annotation.Target;
+import java.lang.reflect.AnnotatedType;
+import java.lang.reflect.Constructor;
+import java.lang.reflect.Field;
+import java.lang.reflect.Method;
+import java.util.ArrayList;
+import java.util.Arrays;
+import java.util.Collections;
+import java.util.List;
+
+public class AnnotationsOkMain {
+    enum Level {
+        LOW,
+        HIGH
+    }
+
+    @Retention(RetentionPolicy.RUNTIME)
+    @interface Tag {
+        String value();
+    }
+
+    @Retention(RetentionPolicy.RUNTIME)
+    @Inherited
+    @interface Info {
+        int id();
+
+        String name() default "unnamed";
+
+        Level level() default Level.LOW;
+
+        Class<?> type() default Object.class;
+
+        String[] aliases() default {};
+
+        long[] sizes() default {1L, 2L};
+
+        char letter() default 'x';
+
+        double ratio() default 0.5;
+
+        boolean enabled() default true;
+
+        Tag tag() default @Tag("default");
+    }
+
+    @Retention(RetentionPolicy.CLASS)
+    @interface Invisible {
+    }
+
+    @Retention(RetentionPolicy.RUNTIME)
+    @interface Test {
+    }
+
+    @Retention(RetentionPolicy.RUNTIME)
+    @Target({ElementType.TYPE_USE, ElementType.TYPE_PARAMETER})
+    @interface NonNull {
+    }
+
+    static class Base {
+    }
+
+    @Info(id = 7, name = "sample", level = Level.HIGH, type = String.class, aliases = {"a", "b"},
+            sizes = {3L}, letter = 'q', ratio = 1.25, enabled = false, tag = @Tag("nested"))
+    @Invisible
+    static class Annotated extends @NonNull Base {
+        @Tag("field")
+        @Invisible
+        @NonNull String name;
+
+        int plain;
+
+        @Tag("constructor")
+        Annotated(@Tag("first") int a, int b, @Tag("third") @Invisible String c) {
+        }
+
+        @Info(id = 1)
+        @NonNull String greet(@NonNull String who) {
+            @NonNull String local = who;
+            return local;
+        }
+
+        void nothing() {
+        }
+    }
+
+    static class Child extends Annotated {
+        Child() {
+            super(0, 0, null);
+        }
+    }
+
+    static class Suite {
+        @Test
+        public void second() {
+            System.out.println("running second");
+        }
+
+        public void helper() {
+            System.out.println("running helper");
+        }
+
+        @Test
+        public void first() {
+            System.out.println("running first");
+        }
+    }
+
+    private static String names(Annotation[] annotations) {
+        List<String> names = new ArrayList<>();
+        for (Annotation annotation : annotations) {
+            names.add(annotation.annotationType().getSimpleName());
+        }
+        Collections.sort(names);
+        return names.toString();
+    }
+
+    private static void printInfo(Info info) {
+        System.out.println("id: ".concat(String.valueOf(info.id())));
+        System.out.println("name: ".concat(info.name()));
+        System.out.println("level: ".concat(info.level().name()));
+        System.out.println("type: ".concat(info.type().getName()));
+        System.out.println("aliases: ".concat(Arrays.toString(info.aliases())));
+        System.out.println("sizes: ".concat(Arrays.toString(info.sizes())));
+        System.out.println("letter: ".concat(String.valueOf(info.letter())));
+        System.out.println("ratio: ".concat(String.valueOf(info.ratio())));
+        System.out.println("enabled: ".concat(String.valueOf(info.enabled())));
+        System.out.println("tag: ".concat(info.tag().value()));
+    }
+
+    public static void main(String[] args) throws Exception {
+        Class<?> annotated = Annotated.class;
+        System.out.println(names(annotated.getDeclaredAnnotations()));
+        System.out.println(annotated.isAnnotationPresent(Info.class));
+        System.out.println(annotated.isAnnotationPresent(Invisible.class));
+        System.out.println(annotated.isAnnotationPresent(Tag.class));
+        printInfo(annotated.getAnnotation(Info.class));
+
+        System.out.println("inherited: ".concat(String.valueOf(Child.class.isAnnotationPresent(Info.class))));
+        System.out.println("declared by child: ".concat(names(Child.class.getDeclaredAnnotations())));
+        System.out.println("base: ".concat(names(Base.class.getAnnotations())));
+
+        Field name = annotated.getDeclaredField("name");
+        System.out.println("field: ".concat(name.getAnnotation(Tag.class).value()));
+        System.out.println("field annotations: ".concat(names(name.getDeclaredAnnotations())));
+        System.out.println("plain field: ".concat(names(annotated.getDeclaredField("plain").getAnnotations())));
+
+        Method greet = annotated.getDeclaredMethod("greet", String.class);
+        printInfo(greet.getAnnotation(Info.class));
+        System.out.println("nothing: ".concat(names(annotated.getDeclaredMethod("nothing").getAnnotations())));
+
+        Constructor<?> constructor = annotated.getDeclaredConstructor(int.class, int.class, String.class);
+        System.out.println("constructor: ".concat(constructor.getAnnotation(Tag.class).value()));
+        Annotation[][] parameters = constructor.getParameterAnnotations();
+        System.out.println("parameters: ".concat(String.valueOf(parameters.length)));
+        for (Annotation[] parameter : parameters) {
+            if (parameter.length == 0) {
+                System.out.println("  none");
+            } else {
+                System.out.println("  ".concat(((Tag) parameter[0]).value()));
+            }
+        }
+
+        Method defaulted = Info.class.getDeclaredMethod("level");
+        System.out.println("default level: ".concat(String.valueOf(defaulted.getDefaultValue())));
+        Method tagDefault = Info.class.getDeclaredMethod("tag");
+        System.out.println("default tag: ".concat(((Tag) tagDefault.getDefaultValue()).value()));
+        System.out.println("no default: ".concat(String.valueOf(Info.class.getDeclaredMethod("id").getDefaultValue())));
+        System.out.println("retention: ".concat(Info.class.getAnnotation(Retention.class).value().name()));
+
+        AnnotatedType fieldType = name.getAnnotatedType();
+        System.out.println("field type: ".concat(names(fieldType.getAnnotations())));
+        AnnotatedType returnType = greet.getAnnotatedReturnType();
+        System.out.println("return type: ".concat(names(returnType.getAnnotations())));
+        AnnotatedType parameterType = greet.getAnnotatedParameterTypes()[0];
+        System.out.println("parameter type: ".concat(names(parameterType.getAnnotations())));
+        AnnotatedType superclass = annotated.getAnnotatedSuperclass();
+        System.out.println("superclass: ".concat(names(superclass.getAnnotations())));
+        System.out.println("greet: ".concat(greet.invoke(new Annotated(1, 2, "3"), "world").toString()));
+
+        List<String> tests = new ArrayList<>();
+        for (Method method : Suite.class.getDeclaredMethods()) {
+            if (method.isAnnotationPresent(Test.class)) {
+                tests.add(method.getName());
+            }
+        }
+        Collections.sort(tests);
+        Suite suite = new Suite();
+        for (String test : tests) {
+            Suite.class.getDeclaredMethod(test).invoke(suite);
+        }
+    }
+}
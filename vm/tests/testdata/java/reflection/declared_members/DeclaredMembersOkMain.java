@@ -0,0 +1,90 @@
+package reflection.declared_members.basic;
+
+import java.io.IOException;
+import java.lang.reflect.Constructor;
+import java.lang.reflect.Field;
+import java.lang.reflect.Method;
+import java.lang.reflect.Modifier;
+import java.util.Arrays;
+import java.util.List;
+
+public class DeclaredMembersOkMain {
+    interface Shape {
+        double area();
+    }
+
+    static class Point implements Shape, Comparable<Point> {
+        public static final int ORIGIN = 0;
+        private final int x;
+        protected long y;
+        List<String> tags;
+
+        public Point(int x, long y) {
+            this.x = x;
+            this.y = y;
+        }
+
+        private Point() throws IOException {
+            this(0, 0);
+        }
+
+        public double area() {
+            return 0;
+        }
+
+        public int compareTo(Point other) {
+            return Integer.compare(x, other.x);
+        }
+
+        static String describe(int[] values, String... names) throws IllegalStateException {
+            return "";
+        }
+    }
+
+    private static void print(Object[] members) {
+        String[] lines = new String[members.length];
+        for (int i = 0; i < members.length; i++) {
+            lines[i] = members[i].toString();
+        }
+        Arrays.sort(lines);
+        for (String line : lines) {
+            System.out.println(line);
+        }
+    }
+
+    public static void main(String[] args) throws Exception {
+        Class<?> point = Class.forName("reflection.declared_members.basic.DeclaredMembersOkMain$Point");
+        System.out.println(point == Point.class);
+        System.out.println(point.getSimpleName().concat(" in ").concat(point.getDeclaringClass().getSimpleName()));
+        System.out.println("static: ".concat(String.valueOf(Modifier.isStatic(point.getModifiers()))));
+        System.out.println(Arrays.toString(point.getInterfaces()));
+
+        print(point.getDeclaredFields());
+        print(point.getFields());
+        print(point.getDeclaredMethods());
+        print(point.getDeclaredConstructors());
+        print(point.getConstructors());
+
+        Field tags = point.getDeclaredField("tags");
+        System.out.println(tags.getGenericType());
+        Method compareTo = point.getDeclaredMethod("compareTo", Point.class);
+        System.out.println(compareTo.toGenericString());
+        Constructor<?> constructor = point.getDeclaredConstructor();
+        System.out.println(Arrays.toString(constructor.getExceptionTypes()));
+
+        Runnable local = new Runnable() {
+            public void run() {
+            }
+        };
+        System.out.println(local.getClass().getEnclosingMethod());
+        System.out.println(local.getClass().isAnonymousClass());
+        System.out.println(Shape.class.getDeclaredConstructors().length);
+        System.out.println(int[].class.getInterfaces().length);
+
+        try {
+            Class.forName("reflection.declared_members.basic.Missing");
+        } catch (ClassNotFoundException e) {
+            System.out.println("ClassNotFoundException: ".concat(e.getMessage()));
+        }
+    }
+}
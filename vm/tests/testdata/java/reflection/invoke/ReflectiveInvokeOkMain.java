@@ -0,0 +1,285 @@
+package reflection.invoke.reflective_invoke;
+
+import java.lang.reflect.Constructor;
+import java.lang.reflect.Field;
+import java.lang.reflect.InvocationTargetException;
+import java.lang.reflect.Method;
+
+class Secret {
+    private int hidden = 7;
+
+    private static String whisper() {
+        return "psst";
+    }
+}
+
+public class ReflectiveInvokeOkMain {
+    interface Greeter {
+        String greet(String name);
+
+        default String twice(String name) {
+            return greet(name).concat(" ").concat(greet(name));
+        }
+    }
+
+    static class Base implements Greeter {
+        public String greet(String name) {
+            return "Hello, ".concat(name);
+        }
+    }
+
+    static class Polite extends Base {
+        @Override
+        public String greet(String name) {
+            return "Good day, ".concat(name);
+        }
+    }
+
+    static class Lazy {
+        static int initialized = 0;
+
+        static {
+            System.out.println("Lazy initialized");
+        }
+
+        static long sum(int a, long b, double scale) {
+            return (long) ((a + b) * scale);
+        }
+    }
+
+    static class Counter {
+        static final String KIND = "counter";
+        final int limit;
+        int count;
+        long total;
+        char mark = 'c';
+        boolean enabled = true;
+        String label;
+
+        Counter(int limit) {
+            this.limit = limit;
+        }
+
+        Counter(String label) {
+            if (label == null) {
+                throw new IllegalArgumentException("no label");
+            }
+            this.limit = 0;
+            this.label = label;
+        }
+
+        void fail() {
+            throw new IllegalStateException("boom");
+        }
+
+        void nothing() {
+        }
+    }
+
+    abstract static class Shape {
+    }
+
+    /** A reflective call expected to throw. */
+    abstract static class Case {
+        /** Messages that aren't the same in every JDK release aren't printed. */
+        final boolean printMessage;
+
+        Case(boolean printMessage) {
+            this.printMessage = printMessage;
+        }
+
+        abstract void run() throws Exception;
+
+        void expectFailure() {
+            try {
+                run();
+                System.out.println("no exception");
+            } catch (Exception e) {
+                Throwable cause = e instanceof InvocationTargetException ? e.getCause() : e;
+                StringBuilder line = new StringBuilder(cause.getClass().getName());
+                if (printMessage) {
+                    line.append(": ").append(cause.getMessage());
+                }
+                System.out.println(line);
+            }
+        }
+    }
+
+    private static void print(Object... parts) {
+        StringBuilder line = new StringBuilder();
+        for (Object part : parts) {
+            if (line.length() > 0) {
+                line.append(' ');
+            }
+            line.append(part);
+        }
+        System.out.println(line);
+    }
+
+    public static void main(String[] args) throws Exception {
+        final Method sum = Lazy.class.getDeclaredMethod("sum", int.class, long.class, double.class);
+        System.out.println("before invoke");
+        Object total = sum.invoke(null, 2, 3, 1.5f);
+        print(total, total.getClass().getName());
+
+        final Method greet = Greeter.class.getMethod("greet", String.class);
+        System.out.println(greet.invoke(new Polite(), "Ann"));
+        final Method baseGreet = Base.class.getMethod("greet", String.class);
+        System.out.println(baseGreet.invoke(new Polite(), "Bob"));
+        System.out.println(Greeter.class.getMethod("twice", String.class).invoke(new Base(), "Cy"));
+
+        Method fail = Counter.class.getDeclaredMethod("fail");
+        try {
+            fail.invoke(new Counter(1));
+        } catch (InvocationTargetException e) {
+            print("target threw", e.getCause());
+        }
+        System.out.println(Counter.class.getDeclaredMethod("nothing").invoke(new Counter(1)));
+        new Case(true) {
+            void run() throws Exception {
+                sum.invoke(null, "2", 3L, 1.0);
+            }
+        }.expectFailure();
+        new Case(true) {
+            void run() throws Exception {
+                sum.invoke(null, 2L, 3L, 1.0);
+            }
+        }.expectFailure();
+        new Case(false) {
+            void run() throws Exception {
+                sum.invoke(null, null, 3L, 1.0);
+            }
+        }.expectFailure();
+        new Case(false) {
+            void run() throws Exception {
+                sum.invoke(null, 2);
+            }
+        }.expectFailure();
+        new Case(false) {
+            void run() throws Exception {
+                greet.invoke(null, "Dee");
+            }
+        }.expectFailure();
+        new Case(false) {
+            void run() throws Exception {
+                baseGreet.invoke("not a greeter", "Eve");
+            }
+        }.expectFailure();
+
+        final Method whisper = Secret.class.getDeclaredMethod("whisper");
+        new Case(true) {
+            void run() throws Exception {
+                whisper.invoke(null);
+            }
+        }.expectFailure();
+        whisper.setAccessible(true);
+        System.out.println(whisper.invoke(null));
+
+        Method forName = Class.class.getMethod("forName", String.class);
+        System.out.println(forName.invoke(null, "reflection.invoke.reflective_invoke.Secret"));
+
+        Constructor<Counter> byLimit = Counter.class.getDeclaredConstructor(int.class);
+        final Counter counter = byLimit.newInstance((short) 3);
+        print("limit", counter.limit);
+        Constructor<Counter> byLabel = Counter.class.getDeclaredConstructor(String.class);
+        print("label", byLabel.newInstance("first").label);
+        try {
+            byLabel.newInstance((Object) null);
+        } catch (InvocationTargetException e) {
+            print("constructor threw", e.getCause());
+        }
+        new Case(true) {
+            void run() throws Exception {
+                Shape.class.getDeclaredConstructor().newInstance();
+            }
+        }.expectFailure();
+
+        final Field count = Counter.class.getDeclaredField("count");
+        count.set(counter, 5);
+        count.setInt(counter, count.getInt(counter) + 1);
+        print("count", count.get(counter), count.getLong(counter), count.getDouble(counter));
+        final Field totalField = Counter.class.getDeclaredField("total");
+        totalField.set(counter, 40);
+        totalField.setShort(counter, (short) 41);
+        print("total", totalField.get(counter), counter.total);
+        Field mark = Counter.class.getDeclaredField("mark");
+        final Field enabled = Counter.class.getDeclaredField("enabled");
+        print(mark.get(counter), mark.getInt(counter), enabled.getBoolean(counter));
+        final Field label = Counter.class.getDeclaredField("label");
+        label.set(counter, "second");
+        print("label", label.get(counter));
+
+        new Case(true) {
+            void run() throws Exception {
+                totalField.getInt(counter);
+            }
+        }.expectFailure();
+        new Case(true) {
+            void run() throws Exception {
+                count.set(counter, "six");
+            }
+        }.expectFailure();
+        new Case(true) {
+            void run() throws Exception {
+                count.set(counter, null);
+            }
+        }.expectFailure();
+        new Case(true) {
+            void run() throws Exception {
+                count.setLong(counter, 6L);
+            }
+        }.expectFailure();
+        new Case(true) {
+            void run() throws Exception {
+                label.set(counter, 6);
+            }
+        }.expectFailure();
+        new Case(true) {
+            void run() throws Exception {
+                count.get("not a counter");
+            }
+        }.expectFailure();
+        new Case(false) {
+            void run() throws Exception {
+                count.get(null);
+            }
+        }.expectFailure();
+        new Case(true) {
+            void run() throws Exception {
+                enabled.getInt(counter);
+            }
+        }.expectFailure();
+
+        final Field limit = Counter.class.getDeclaredField("limit");
+        new Case(true) {
+            void run() throws Exception {
+                limit.setInt(counter, 5);
+            }
+        }.expectFailure();
+        limit.setAccessible(true);
+        limit.setInt(counter, 9);
+        print("limit", limit.get(counter));
+        final Field kind = Counter.class.getDeclaredField("KIND");
+        kind.setAccessible(true);
+        print("kind", kind.get(null));
+        new Case(true) {
+            void run() throws Exception {
+                kind.set(null, "other");
+            }
+        }.expectFailure();
+
+        Field initialized = Lazy.class.getDeclaredField("initialized");
+        initialized.set(null, 2);
+        print("initialized", Lazy.initialized);
+
+        final Field hidden = Secret.class.getDeclaredField("hidden");
+        final Secret secret = new Secret();
+        new Case(true) {
+            void run() throws Exception {
+                hidden.get(secret);
+            }
+        }.expectFailure();
+        hidden.setAccessible(true);
+        print("hidden", hidden.getInt(secret));
+    }
+}
@@ -0,0 +1,18 @@
+package shutdown;
+
+public class Hook extends Thread {
+    private final String message;
+
+    public Hook(String message) {
+        this.message = message;
+    }
+
+    public static void register(String message) {
+        Runtime.getRuntime().addShutdownHook(new Hook(message));
+    }
+
+    @Override
+    public void run() {
+        System.out.println(message);
+    }
+}
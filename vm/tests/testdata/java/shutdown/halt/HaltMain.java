@@ -0,0 +1,11 @@
+package shutdown.halt;
+
+import shutdown.Hook;
+
+public class HaltMain {
+    public static void main(String[] args) {
+        Hook.register("hook must not run on halt");
+        System.out.println("main halts");
+        Runtime.getRuntime().halt(7);
+    }
+}
@@ -0,0 +1,13 @@
+package shutdown.hooks;
+
+import shutdown.Hook;
+
+public class HooksMain {
+    public static void main(String[] args) {
+        Hook.register("hook after main returned");
+        Hook unregistered = new Hook("removed hook");
+        Runtime.getRuntime().addShutdownHook(unregistered);
+        Runtime.getRuntime().removeShutdownHook(unregistered);
+        System.out.println("main returns");
+    }
+}
@@ -0,0 +1,21 @@
+package shutdown.thread_exit;
+
+import shutdown.Hook;
+
+public class ThreadExitMain {
+    static class Exiting extends Thread {
+        @Override
+        public void run() {
+            System.out.println("worker exits");
+            System.exit(3);
+        }
+    }
+
+    public static void main(String[] args) throws InterruptedException {
+        Hook.register("hook after exit from worker");
+        new Exiting().start();
+        while (true) {
+            Thread.sleep(1000);
+        }
+    }
+}
@@ -0,0 +1,11 @@
+package shutdown.uncaught;
+
+import shutdown.Hook;
+
+public class UncaughtMain {
+    public static void main(String[] args) {
+        Hook.register("hook after uncaught exception");
+        System.out.println("main throws");
+        throw new IllegalStateException("main failed");
+    }
+}
@@ -0,0 +1,265 @@
+package support;
+
+import java.io.ByteArrayOutputStream;
+import java.io.DataOutputStream;
+import java.io.IOException;
+import java.util.ArrayList;
+import java.util.List;
+
+/**
+ * Assembles class files javac won't produce: invalid ones for the linking checks and ones
+ * loading dynamic constants. Constants are deduplicated, except dynamic ones.
+ */
+public class ClassAssembler {
+    public static final int ACC_PUBLIC = 0x0001;
+    public static final int ACC_STATIC = 0x0008;
+    public static final int ACC_FINAL = 0x0010;
+    public static final int ACC_SUPER = 0x0020;
+    public static final int ACC_ABSTRACT = 0x0400;
+
+    public final String name;
+    public int flags = ACC_PUBLIC | ACC_SUPER;
+    public int version = 52;
+
+    private final int thisClass;
+    private final int superClass;
+    private final List<Integer> interfaces = new ArrayList<Integer>();
+
+    private final List<String> keys = new ArrayList<String>();
+    private final ByteArrayOutputStream poolBytes = new ByteArrayOutputStream();
+    private final DataOutputStream pool = new DataOutputStream(poolBytes);
+    private int count = 1;
+
+    private final ByteArrayOutputStream fieldBytes = new ByteArrayOutputStream();
+    private final DataOutputStream fields = new DataOutputStream(fieldBytes);
+    private int fieldCount;
+
+    private final ByteArrayOutputStream methodBytes = new ByteArrayOutputStream();
+    private final DataOutputStream methods = new DataOutputStream(methodBytes);
+    private int methodCount;
+
+    private final ByteArrayOutputStream bootstrapBytes = new ByteArrayOutputStream();
+    private final DataOutputStream bootstraps = new DataOutputStream(bootstrapBytes);
+    private int bootstrapCount;
+
+    public ClassAssembler(String name, String superName) throws IOException {
+        this.name = name;
+        this.thisClass = classRef(name);
+        this.superClass = classRef(superName);
+    }
+
+    /** The binary name, for defining the class. */
+    public String binaryName() {
+        return name.replace('/', '.');
+    }
+
+    public void implement(String interfaceName) throws IOException {
+        interfaces.add(classRef(interfaceName));
+    }
+
+    public void field(String name, String descriptor, int flags) throws IOException {
+        fields.writeShort(flags);
+        fields.writeShort(utf8(name));
+        fields.writeShort(utf8(descriptor));
+        fields.writeShort(0);
+        fieldCount++;
+    }
+
+    /** A method with the code and no exception table, or without Code if null. */
+    public void method(String name, String descriptor, int flags, int maxStack, int maxLocals,
+                       byte[] code) throws IOException {
+        methods.writeShort(flags);
+        methods.writeShort(utf8(name));
+        methods.writeShort(utf8(descriptor));
+        if (code == null) {
+            methods.writeShort(0);
+            methodCount++;
+            return;
+        }
+        methods.writeShort(1);
+        methods.writeShort(utf8("Code"));
+        methods.writeInt(12 + code.length);
+        methods.writeShort(maxStack);
+        methods.writeShort(maxLocals);
+        methods.writeInt(code.length);
+        methods.write(code);
+        methods.writeShort(0);
+        methods.writeShort(0);
+        methodCount++;
+    }
+
+    /** The index of the constant with the key, or 0 if there's none yet. */
+    private int find(String key) {
+        return keys.indexOf(key) + 1;
+    }
+
+    private int add(String key, int slots) {
+        int index = count;
+        keys.add(key);
+        for (int i = 1; i < slots; i++) {
+            keys.add(null);
+        }
+        count += slots;
+        return index;
+    }
+
+    public int utf8(String value) throws IOException {
+        String key = "utf8 ".concat(value);
+        int index = find(key);
+        if (index == 0) {
+            index = add(key, 1);
+            pool.writeByte(1);
+            pool.writeUTF(value);
+        }
+        return index;
+    }
+
+    public int classRef(String className) throws IOException {
+        int nameIndex = utf8(className);
+        String key = "class ".concat(className);
+        int index = find(key);
+        if (index == 0) {
+            index = add(key, 1);
+            pool.writeByte(7);
+            pool.writeShort(nameIndex);
+        }
+        return index;
+    }
+
+    public int integer(int value) throws IOException {
+        String key = "int ".concat(String.valueOf(value));
+        int index = find(key);
+        if (index == 0) {
+            index = add(key, 1);
+            pool.writeByte(3);
+            pool.writeInt(value);
+        }
+        return index;
+    }
+
+    public int longValue(long value) throws IOException {
+        String key = "long ".concat(String.valueOf(value));
+        int index = find(key);
+        if (index == 0) {
+            index = add(key, 2);
+            pool.writeByte(5);
+            pool.writeLong(value);
+        }
+        return index;
+    }
+
+    public int nameAndType(String name, String descriptor) throws IOException {
+        int nameIndex = utf8(name);
+        int descriptorIndex = utf8(descriptor);
+        String key = "nat ".concat(name).concat(" ").concat(descriptor);
+        int index = find(key);
+        if (index == 0) {
+            index = add(key, 1);
+            pool.writeByte(12);
+            pool.writeShort(nameIndex);
+            pool.writeShort(descriptorIndex);
+        }
+        return index;
+    }
+
+    public int fieldRef(String owner, String name, String descriptor) throws IOException {
+        int classIndex = classRef(owner);
+        int natIndex = nameAndType(name, descriptor);
+        String key = "field ".concat(owner).concat(".").concat(name).concat(":").concat(descriptor);
+        int index = find(key);
+        if (index == 0) {
+            index = add(key, 1);
+            pool.writeByte(9);
+            pool.writeShort(classIndex);
+            pool.writeShort(natIndex);
+        }
+        return index;
+    }
+
+    public int methodRef(String owner, String name, String descriptor) throws IOException {
+        int classIndex = classRef(owner);
+        int natIndex = nameAndType(name, descriptor);
+        String key = "method ".concat(owner).concat(".").concat(name).concat(descriptor);
+        int index = find(key);
+        if (index == 0) {
+            index = add(key, 1);
+            pool.writeByte(10);
+            pool.writeShort(classIndex);
+            pool.writeShort(natIndex);
+        }
+        return index;
+    }
+
+    public int staticMethodHandle(String owner, String name, String descriptor) throws IOException {
+        int methodIndex = methodRef(owner, name, descriptor);
+        String key = "handle ".concat(String.valueOf(methodIndex));
+        int index = find(key);
+        if (index == 0) {
+            index = add(key, 1);
+            pool.writeByte(15);
+            pool.writeByte(6);
+            pool.writeShort(methodIndex);
+        }
+        return index;
+    }
+
+    public int dynamic(int bootstrapIndex, String name, String descriptor) throws IOException {
+        int natIndex = nameAndType(name, descriptor);
+        int index = add("dynamic ".concat(String.valueOf(count)), 1);
+        pool.writeByte(17);
+        pool.writeShort(bootstrapIndex);
+        pool.writeShort(natIndex);
+        return index;
+    }
+
+    /** A dynamic constant whose bootstrap method is the next one added, so it may take itself. */
+    public int reserveDynamic(String name, String descriptor) throws IOException {
+        return dynamic(bootstrapCount, name, descriptor);
+    }
+
+    public int bootstrap(int handleIndex, int... arguments) throws IOException {
+        bootstraps.writeShort(handleIndex);
+        bootstraps.writeShort(arguments.length);
+        for (int argument : arguments) {
+            bootstraps.writeShort(argument);
+        }
+        return bootstrapCount++;
+    }
+
+    public byte[] toByteArray() throws IOException {
+        int bootstrapAttribute = bootstrapCount > 0 ? utf8("BootstrapMethods") : 0;
+        ByteArrayOutputStream bytes = new ByteArrayOutputStream();
+        DataOutputStream out = new DataOutputStream(bytes);
+        out.writeInt(0xCAFEBABE);
+        out.writeShort(0);
+        out.writeShort(version);
+        out.writeShort(count);
+        pool.flush();
+        out.write(poolBytes.toByteArray());
+        out.writeShort(flags);
+        out.writeShort(thisClass);
+        out.writeShort(superClass);
+        out.writeShort(interfaces.size());
+        for (int index : interfaces) {
+            out.writeShort(index);
+        }
+        out.writeShort(fieldCount);
+        fields.flush();
+        out.write(fieldBytes.toByteArray());
+        out.writeShort(methodCount);
+        methods.flush();
+        out.write(methodBytes.toByteArray());
+        if (bootstrapCount == 0) {
+            out.writeShort(0);
+        } else {
+            out.writeShort(1);
+            out.writeShort(bootstrapAttribute);
+            bootstraps.flush();
+            out.writeInt(2 + bootstrapBytes.size());
+            out.writeShort(bootstrapCount);
+            out.write(bootstrapBytes.toByteArray());
+        }
+        out.flush();
+        return bytes.toByteArray();
+    }
+}
@@ -0,0 +1,50 @@
+package threads.concurrent_loading;
+
+import threads.concurrent_loading.loaded.Loaded;
+
+public class ConcurrentLoadingOkMain {
+    static final int THREADS = 8;
+    static final Class<?>[] LOADED = new Class<?>[THREADS];
+    static final int[] SEEN_INITIALIZATIONS = new int[THREADS];
+    static volatile boolean go;
+
+    static class Loader extends Thread {
+        private final int index;
+
+        Loader(int index) {
+            this.index = index;
+        }
+
+        @Override
+        public void run() {
+            while (!go) {
+                Thread.onSpinWait();
+            }
+            // every loader resolves the class through its own constant pool entry at once
+            LOADED[index] = Loaded.class;
+            SEEN_INITIALIZATIONS[index] = Loaded.initializations;
+        }
+    }
+
+    public static void main(String[] args) throws InterruptedException {
+        Thread[] loaders = new Thread[THREADS];
+        for (int i = 0; i < THREADS; i++) {
+            loaders[i] = new Loader(i);
+            loaders[i].start();
+        }
+        go = true;
+        for (Thread loader : loaders) {
+            loader.join();
+        }
+        boolean sameClass = true;
+        boolean initializedOnce = true;
+        for (int i = 0; i < THREADS; i++) {
+            sameClass &= LOADED[i] == LOADED[0];
+            initializedOnce &= SEEN_INITIALIZATIONS[i] == 1;
+        }
+        System.out.println("same class: ".concat(String.valueOf(sameClass)));
+        System.out.println("every loader saw it initialized once: ".concat(String.valueOf(initializedOnce)));
+        System.out.println("name: ".concat(LOADED[0].getName()));
+        System.out.println("initializations: ".concat(String.valueOf(Loaded.initializations)));
+    }
+}
@@ -0,0 +1,9 @@
+package threads.concurrent_loading.loaded;
+
+public class Loaded {
+    public static int initializations;
+
+    static {
+        initializations++;
+    }
+}
@@ -0,0 +1,66 @@
+package threads.deadlock;
+
+public class DeadlockMain {
+    static final class Left {}
+
+    static final class Right {}
+
+    static final Left LEFT = new Left();
+    static final Right RIGHT = new Right();
+    static volatile boolean leftHeld;
+    static volatile boolean rightHeld;
+
+    static void awaitOther(boolean left) {
+        try {
+            while (left ? !leftHeld : !rightHeld) {
+                Thread.sleep(10);
+            }
+        } catch (InterruptedException e) {
+            throw new IllegalStateException(e);
+        }
+    }
+
+    static class LeftFirst extends Thread {
+        LeftFirst() {
+            super("left-first");
+        }
+
+        @Override
+        public void run() {
+            synchronized (LEFT) {
+                leftHeld = true;
+                awaitOther(false);
+                synchronized (RIGHT) {
+                    System.out.println("left-first got both locks");
+                }
+            }
+        }
+    }
+
+    static class RightFirst extends Thread {
+        RightFirst() {
+            super("right-first");
+        }
+
+        @Override
+        public void run() {
+            synchronized (RIGHT) {
+                rightHeld = true;
+                awaitOther(true);
+                synchronized (LEFT) {
+                    System.out.println("right-first got both locks");
+                }
+            }
+        }
+    }
+
+    public static void main(String[] args) throws InterruptedException {
+        Thread leftFirst = new LeftFirst();
+        Thread rightFirst = new RightFirst();
+        leftFirst.start();
+        rightFirst.start();
+        System.out.println("locking in opposite order");
+        leftFirst.join();
+        rightFirst.join();
+    }
+}
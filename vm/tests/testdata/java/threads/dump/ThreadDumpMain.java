@@ -0,0 +1,69 @@
+package threads.dump;
+
+public class ThreadDumpMain {
+    static final class Monitor {}
+
+    static final class WaitLock {}
+
+    static final Monitor MONITOR = new Monitor();
+    static final WaitLock WAIT_LOCK = new WaitLock();
+    static volatile boolean waiting;
+    static volatile boolean blocking;
+    static volatile boolean spin = true;
+    static long spins;
+
+    static class Waiter extends Thread {
+        Waiter() {
+            super("waiter");
+        }
+
+        @Override
+        public void run() {
+            synchronized (WAIT_LOCK) {
+                waiting = true;
+                try {
+                    WAIT_LOCK.wait();
+                } catch (InterruptedException e) {
+                    throw new IllegalStateException(e);
+                }
+            }
+        }
+    }
+
+    static class Blocked extends Thread {
+        Blocked() {
+            super("blocked");
+        }
+
+        @Override
+        public void run() {
+            blocking = true;
+            synchronized (MONITOR) {
+                System.out.println("blocked got the monitor");
+            }
+        }
+    }
+
+    public static void main(String[] args) {
+        synchronized (MONITOR) {
+            new Waiter().start();
+            new Blocked().start();
+            while (!waiting || !blocking) {
+                spins++;
+            }
+            // the waiter holds the lock until it waits
+            synchronized (WAIT_LOCK) {
+                spins++;
+            }
+            // give the blocked thread time to reach the monitor it announced
+            long start = System.nanoTime();
+            while (System.nanoTime() - start < 500_000_000L) {
+                spins++;
+            }
+            System.out.println("ready");
+            while (spin) {
+                spins++;
+            }
+        }
+    }
+}
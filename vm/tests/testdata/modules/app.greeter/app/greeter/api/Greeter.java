@@ -0,0 +1,7 @@
+package app.greeter.api;
+
+public class Greeter {
+    public static String greet(String name) {
+        return "Hello, ".concat(name).concat(" from module app.greeter");
+    }
+}
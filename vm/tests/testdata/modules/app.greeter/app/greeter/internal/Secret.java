@@ -0,0 +1,8 @@
+package app.greeter.internal;
+
+/** Public, but in a package the module doesn't export. */
+public class Secret {
+    public static String reveal() {
+        return "Secret of a package exported with --add-exports";
+    }
+}
@@ -0,0 +1,3 @@
+module app.greeter {
+    exports app.greeter.api;
+}
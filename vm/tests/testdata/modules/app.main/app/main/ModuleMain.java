@@ -0,0 +1,15 @@
+package app.main;
+
+import app.greeter.api.Greeter;
+import app.greeter.internal.Secret;
+
+public class ModuleMain {
+    public static void main(String[] args) {
+        System.out.println(Greeter.greet("app.main"));
+        try {
+            System.out.println(Secret.reveal());
+        } catch (IllegalAccessError e) {
+            System.out.println("IllegalAccessError: ".concat(e.getMessage()));
+        }
+    }
+}
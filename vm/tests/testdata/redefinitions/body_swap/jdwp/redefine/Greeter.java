@@ -0,0 +1,14 @@
+package jdwp.redefine;
+
+public class Greeter {
+    private static int calls;
+
+    public static String greeting() {
+        return "greeter: new greeting after ".concat(String.valueOf(calls)).concat(" run");
+    }
+
+    public static void run() {
+        calls++;
+        System.out.println("greeter: new run");
+    }
+}
@@ -0,0 +1,15 @@
+package jdwp.redefine;
+
+public class Greeter {
+    private static int calls;
+    private static String last;
+
+    public static String greeting() {
+        last = "greeter: greeting with a new field";
+        return last;
+    }
+
+    public static void run() {
+        calls++;
+    }
+}